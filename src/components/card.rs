@@ -6,6 +6,8 @@
 
 use leptos::*;
 
+use crate::core::health::HealthLevel;
+
 #[slot]
 pub struct CardItem {
     children: Children,
@@ -100,12 +102,27 @@ pub fn CardSimple(children: Children) -> impl IntoView {
 pub fn CardSimpleItem(
     #[prop(into)] title: MaybeSignal<String>,
     #[prop(into)] contents: MaybeSignal<String>,
+    #[prop(optional, into)] level: MaybeSignal<HealthLevel>,
     children: Children,
 ) -> impl IntoView {
     view! {
-        <div class="flex flex-col bg-white border shadow-sm rounded-xl dark:bg-neutral-900 dark:border-neutral-800">
+        <div
+            class="flex flex-col h-full bg-white border shadow-sm rounded-xl dark:bg-neutral-900 dark:border-neutral-800"
+            class:border-yellow-400=move || level.get() == HealthLevel::Warning
+            class:border-red-500=move || level.get() == HealthLevel::Critical
+        >
             <div class="p-4 md:p-5 flex gap-x-4">
-                <div class="shrink-0 flex justify-center items-center size-[46px] bg-gray-100 rounded-lg dark:bg-neutral-800">
+                <div class=move || match level.get() {
+                    HealthLevel::Normal => {
+                        "shrink-0 flex justify-center items-center size-[46px] bg-gray-100 rounded-lg dark:bg-neutral-800"
+                    }
+                    HealthLevel::Warning => {
+                        "shrink-0 flex justify-center items-center size-[46px] bg-yellow-100 rounded-lg dark:bg-yellow-800/30"
+                    }
+                    HealthLevel::Critical => {
+                        "shrink-0 flex justify-center items-center size-[46px] bg-red-100 rounded-lg dark:bg-red-800/30"
+                    }
+                }>
                     {children()}
                 </div>
 
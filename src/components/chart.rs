@@ -0,0 +1,59 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Local};
+use leptos::*;
+use leptos_chartistry::*;
+
+use crate::components::report::ReportView;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeriesPoint {
+    pub x: DateTime<Local>,
+    pub y: Vec<f64>,
+}
+
+#[component]
+pub fn TimeSeriesChart(
+    #[prop(into)] title: String,
+    labels: &'static [&'static str],
+    #[prop(into)] data: Signal<Vec<TimeSeriesPoint>>,
+) -> impl IntoView {
+    let mut series = Series::new(|point: &TimeSeriesPoint| point.x);
+    for (num, label) in labels.iter().enumerate() {
+        series = series.line(
+            Line::new(move |point: &TimeSeriesPoint| point.y.get(num).copied().unwrap_or_default())
+                .with_name(label.to_string()),
+        );
+    }
+
+    view! {
+        <ReportView>
+
+            <div class="dash-theme gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+                <Chart
+                    aspect_ratio=AspectRatio::from_env_width(300.0)
+                    debug=false
+                    series=series
+                    data=data
+                    top=RotatedLabel::middle(title)
+                    left=TickLabels::aligned_floats()
+                    bottom=vec![TickLabels::timestamps().into_edge(), Legend::end().into_edge()]
+                    inner=[
+                        AxisMarker::left_edge().into_inner(),
+                        AxisMarker::bottom_edge().into_inner(),
+                        XGridLine::default().into_inner(),
+                        YGridLine::default().into_inner(),
+                        YGuideLine::over_mouse().into_inner(),
+                        XGuideLine::over_data().into_inner(),
+                    ]
+
+                    tooltip=Tooltip::left_cursor()
+                />
+            </div>
+        </ReportView>
+    }
+}
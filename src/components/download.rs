@@ -0,0 +1,156 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::Utc;
+use leptos::*;
+use leptos_router::use_navigate;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+
+use crate::{
+    components::{
+        icon::{IconArrowDownTray, IconXMark},
+        messages::alert::{use_alerts, Alert},
+    },
+    core::{
+        download::{request_signed_url, Export, SignedUrl},
+        http,
+        oauth::use_authorization,
+        poll::use_polling,
+    },
+    pages::format_countdown,
+};
+
+#[derive(Clone, Copy)]
+pub struct SignedDownload {
+    link: RwSignal<Option<(Export, SignedUrl)>>,
+    request: Action<Export, ()>,
+}
+
+pub fn use_signed_download() -> SignedDownload {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let link = create_rw_signal(None);
+
+    let request = create_action(move |export: &Export| {
+        let export = export.clone();
+        let auth = auth.get();
+
+        async move {
+            match request_signed_url(&auth, &export).await {
+                Ok(url) => {
+                    link.set(Some((export, url)));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    SignedDownload { link, request }
+}
+
+impl SignedDownload {
+    pub fn request(&self, export: Export) {
+        self.request.dispatch(export);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.request.pending().get()
+    }
+}
+
+#[component]
+pub fn SignedDownloadLink(download: SignedDownload) -> impl IntoView {
+    let clock = use_polling(Duration::from_secs(1));
+    let UseClipboardReturn {
+        is_supported,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+    let copy = store_value(copy);
+
+    move || {
+        download.link.get().map(|(export, signed)| {
+            let url = signed.url.clone();
+            let url_ = signed.url.clone();
+            let expires_at = signed.expires_at;
+            let remaining = move || {
+                clock.tick();
+                (expires_at - Utc::now()).num_seconds()
+            };
+
+            view! {
+                <div class="mx-4 my-4 bg-gray-50 border border-gray-200 rounded-lg p-4 dark:bg-white/10 dark:border-white/10">
+                    <div class="flex justify-between items-start gap-x-3">
+                        <div class="grow">
+                            <h3 class="text-sm font-semibold text-gray-800 dark:text-white">
+                                {export.title()}
+                            </h3>
+                            <p class="mt-1 text-sm text-gray-600 dark:text-neutral-400 tabular-nums">
+                                {move || {
+                                    let remaining = remaining();
+                                    if remaining > 0 {
+                                        format!(
+                                            "This link expires in {}.",
+                                            format_countdown(remaining as u64),
+                                        )
+                                    } else {
+                                        "This link has expired, request a new one to download the export."
+                                            .to_string()
+                                    }
+                                }}
+
+                            </p>
+                        </div>
+                        <button
+                            type="button"
+                            class="inline-flex flex-shrink-0 justify-center items-center size-5 rounded-lg text-gray-800 opacity-50 hover:opacity-100 focus:outline-none focus:opacity-100 dark:text-white"
+                            on:click=move |_| download.link.set(None)
+                        >
+                            <span class="sr-only">Close</span>
+                            <IconXMark attr:class="flex-shrink-0 size-4"/>
+                        </button>
+                    </div>
+                    <div
+                        class="mt-3 flex gap-x-2"
+                        class:hidden=move || { remaining() <= 0 }
+                    >
+                        <input
+                            type="text"
+                            readonly
+                            class="py-2 px-3 block w-full border-gray-200 rounded-lg text-sm font-mono bg-white focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                            prop:value=url.clone()
+                        />
+                        <button
+                            type="button"
+                            class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                            class:hidden=move || !is_supported.get()
+                            on:click=move |_| copy.with_value(|copy| copy(&url))
+                        >
+                            {move || if copied.get() { "Copied" } else { "Copy" }}
+                        </button>
+                        <a
+                            class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700"
+                            href=url_
+                            target="_blank"
+                            rel="noopener"
+                        >
+                            <IconArrowDownTray attr:class="flex-shrink-0 size-4"/>
+                            Download
+                        </a>
+                    </div>
+                </div>
+            }
+        })
+    }
+}
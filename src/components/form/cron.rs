@@ -0,0 +1,217 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::core::cron::{SimpleCron, WEEKDAYS};
+
+use super::FormElement;
+
+const NEXT_RUNS: usize = 3;
+
+#[component]
+pub fn SelectCron(
+    element: FormElement,
+    #[prop(optional, into)] disabled: MaybeSignal<bool>,
+) -> impl IntoView {
+    let raw_value = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .value::<String>(element.id)
+            .unwrap_or_default()
+    });
+    let parsed = create_memo(move |_| raw_value.get().parse::<SimpleCron>());
+    let value = create_memo(move |_| parsed.get().unwrap_or_default());
+    let error = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .error_string(element.id)
+            .map(|s| s.to_string())
+    });
+    let raw_mode =
+        create_rw_signal(!raw_value.get_untracked().is_empty() && parsed.get_untracked().is_err());
+    let select_class = move || {
+        if error.get().is_none() {
+            "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+        } else {
+            "py-2 px-3 pe-9 block w-full border-red-500 rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+        }
+    };
+    let set_value = move |cron: SimpleCron| {
+        element.data.update(|data| {
+            data.update(element.id, cron.to_string());
+        });
+    };
+
+    view! {
+        <div class="space-y-3">
+            <Show
+                when=move || raw_mode.get()
+                fallback=move || {
+                    view! {
+                        <div class="sm:flex rounded-lg shadow-sm">
+                            <select
+                                class=select_class
+                                on:change=move |ev| {
+                                    set_value(
+                                        value.get_untracked().with_day(event_target_value(&ev).parse().ok()),
+                                    );
+                                }
+
+                                disabled=move || disabled.get()
+                            >
+                                <option selected=move || value.get().day().is_none() value="*">
+                                    Every day
+                                </option>
+                                {WEEKDAYS
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(idx, name)| {
+                                        let day = idx as u32 + 1;
+                                        view! {
+                                            <option
+                                                selected=move || value.get().day() == Some(day)
+                                                value=day
+                                            >
+                                                {format!("On {name}")}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </select>
+                            <select
+                                class=select_class
+                                on:change=move |ev| {
+                                    set_value(
+                                        value.get_untracked().with_hour(event_target_value(&ev).parse().ok()),
+                                    );
+                                }
+
+                                disabled=move || disabled.get()
+                            >
+                                <option
+                                    selected=move || value.get().hour().is_none()
+                                    value="*"
+                                    disabled=move || value.get().day().is_some()
+                                >
+                                    Every hour
+                                </option>
+                                {(0..24)
+                                    .map(|hour| {
+                                        view! {
+                                            <option
+                                                selected=move || value.get().hour() == Some(hour)
+                                                value=hour
+                                            >
+                                                {format!("at hour {hour}")}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </select>
+                            <select
+                                class=select_class
+                                on:change=move |ev| {
+                                    if let Ok(minute) = event_target_value(&ev).parse() {
+                                        set_value(value.get_untracked().with_minute(minute));
+                                    }
+                                }
+
+                                disabled=move || disabled.get()
+                            >
+                                {(0..60)
+                                    .map(|minute| {
+                                        view! {
+                                            <option
+                                                selected=move || value.get().minute() == minute
+                                                value=minute
+                                            >
+                                                {format!("at minute {minute}")}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </select>
+                        </div>
+                    }
+                }
+            >
+
+                <input
+                    type="text"
+                    class=move || {
+                        if error.get().is_none() {
+                            "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm font-mono rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        } else {
+                            "py-2 px-3 block w-full border-red-500 shadow-sm text-sm font-mono rounded-lg focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        }
+                    }
+
+                    autocapitalize="off"
+                    placeholder="minute hour day-of-week"
+                    prop:value=move || raw_value.get()
+                    disabled=move || disabled.get()
+                    on:change=move |ev| {
+                        let new_value = event_target_value(&ev).trim().to_string();
+                        element
+                            .data
+                            .update(|data| {
+                                let result = new_value.parse::<SimpleCron>().map(|_| ());
+                                data.update(element.id, new_value);
+                                if let Err(err) = result {
+                                    data.new_error(element.id, err);
+                                }
+                            });
+                    }
+                />
+
+            </Show>
+
+            <div class="flex flex-wrap items-start justify-between gap-x-4 gap-y-1">
+                <div class="text-xs text-gray-500 dark:text-gray-400">
+                    {move || match parsed.get() {
+                        Ok(cron) => {
+                            let runs = cron
+                                .next_runs(NEXT_RUNS)
+                                .into_iter()
+                                .map(|run| run.format("%a, %d %b %Y %H:%M").to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            view! {
+                                <p class="font-medium text-gray-700 dark:text-gray-300">
+                                    {cron.describe()}
+                                </p>
+                                <p>{format!("Next runs: {runs}")}</p>
+                            }
+                                .into_view()
+                        }
+                        Err(_) => ().into_view(),
+                    }}
+
+                </div>
+                <button
+                    type="button"
+                    class="text-xs font-medium text-blue-600 hover:underline disabled:opacity-50 disabled:pointer-events-none dark:text-blue-500"
+                    disabled=move || disabled.get() || (raw_mode.get() && parsed.get().is_err())
+                    on:click=move |_| raw_mode.update(|raw_mode| *raw_mode = !*raw_mode)
+                >
+
+                    {move || if raw_mode.get() { "Use picker" } else { "Edit as text" }}
+                </button>
+            </div>
+        </div>
+
+        {move || {
+            error
+                .get()
+                .map(|error| {
+                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                })
+        }}
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        form::{FormData, FormValue},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::FormatDateTime,
+};
+
+const DRAFT_STORAGE_PREFIX: &str = "webadmin_draft";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Draft {
+    pub values: AHashMap<String, FormValue>,
+    pub saved_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy)]
+pub struct FormDraft {
+    auth: RwSignal<AccessToken>,
+    data: RwSignal<FormData>,
+    key: StoredValue<Option<String>>,
+    baseline: StoredValue<AHashMap<String, FormValue>>,
+    available: RwSignal<Option<Draft>>,
+    exclude: &'static [&'static str],
+}
+
+// Keeps the unsaved values of a form in the browser so they survive an
+// accidental navigation or an expired session. Nothing is stored until an
+// object has been loaded through `load`.
+pub fn use_form_draft(data: RwSignal<FormData>, exclude: &'static [&'static str]) -> FormDraft {
+    let draft = FormDraft {
+        auth: use_authorization(),
+        data,
+        key: store_value(None),
+        baseline: store_value(AHashMap::new()),
+        available: create_rw_signal(None),
+        exclude,
+    };
+
+    create_effect(move |_| {
+        let values = data.with(|data| data.draft_values(exclude));
+        let Some(key) = draft.key.get_value() else {
+            return;
+        };
+
+        if draft.baseline.with_value(|baseline| baseline != &values) {
+            let result = LocalStorage::set(
+                &key,
+                Draft {
+                    values,
+                    saved_at: Utc::now(),
+                },
+            );
+            if let Err(err) = result {
+                log::warn!("Failed to store form draft: {err}");
+            }
+            draft.available.set(None);
+        } else if draft
+            .available
+            .with_untracked(|available| available.is_none())
+        {
+            LocalStorage::delete(&key);
+        }
+    });
+
+    draft
+}
+
+impl FormDraft {
+    // Loads a new object into the form and starts tracking it, offering to
+    // restore any draft left behind for it.
+    pub fn load(&self, schema_id: &str, object_id: &str, f: impl FnOnce(&mut FormData)) {
+        self.key.set_value(None);
+        self.data.update(f);
+
+        let key = self.auth.with_untracked(|auth| {
+            format!(
+                "{DRAFT_STORAGE_PREFIX}_{}@{}:{schema_id}:{}",
+                auth.username,
+                auth.base_url,
+                if !object_id.is_empty() {
+                    object_id
+                } else {
+                    "_new"
+                }
+            )
+        });
+        let baseline = self
+            .data
+            .with_untracked(|data| data.draft_values(self.exclude));
+        let stored = LocalStorage::get::<Draft>(&key)
+            .ok()
+            .filter(|draft| draft.values != baseline);
+
+        self.baseline.set_value(baseline);
+        self.available.set(stored);
+        self.key.set_value(Some(key));
+    }
+
+    pub fn restore(&self) {
+        if let Some(draft) = self
+            .available
+            .try_update(|available| available.take())
+            .flatten()
+        {
+            self.data
+                .update(|data| data.restore_draft(draft.values, self.exclude));
+        }
+    }
+
+    pub fn discard(&self) {
+        self.available.set(None);
+        if let Some(key) = self.key.get_value() {
+            LocalStorage::delete(key);
+        }
+    }
+
+    // Called once the form has been saved, the stored values are no longer needed.
+    pub fn clear(&self) {
+        self.discard();
+        self.key.set_value(None);
+    }
+}
+
+#[component]
+pub fn DraftRestoreBanner(draft: FormDraft) -> impl IntoView {
+    let saved_at = move || {
+        draft
+            .available
+            .get()
+            .map(|draft| draft.saved_at.format_date_time())
+            .unwrap_or_default()
+    };
+
+    view! {
+        <Show when=move || draft.available.with(|available| available.is_some())>
+            <div
+                class="mb-5 bg-yellow-50 border border-yellow-200 text-sm text-yellow-800 rounded-lg p-4 dark:bg-yellow-800/10 dark:border-yellow-900 dark:text-yellow-500"
+                role="alert"
+            >
+                <div class="flex">
+                    <div class="grow">
+                        <h3 class="font-semibold">Unsaved draft</h3>
+                        <p class="mt-1">
+                            {move || {
+                                format!(
+                                    "Changes to this form made on {} were not saved.",
+                                    saved_at(),
+                                )
+                            }}
+
+                        </p>
+                    </div>
+                    <div class="flex-shrink-0 flex gap-x-4">
+                        <button
+                            type="button"
+                            class="font-semibold underline hover:text-yellow-900 dark:hover:text-yellow-400"
+                            on:click=move |_| draft.restore()
+                        >
+                            Restore
+                        </button>
+                        <button
+                            type="button"
+                            class="font-semibold underline hover:text-yellow-900 dark:hover:text-yellow-400"
+                            on:click=move |_| draft.discard()
+                        >
+                            Discard
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
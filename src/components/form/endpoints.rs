@@ -0,0 +1,334 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::Deserialize;
+
+use crate::{
+    components::icon::{IconPlus, IconXMark},
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+    },
+};
+
+use super::FormElement;
+
+const HEALTH_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_WEIGHT: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Store,
+    Directory,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Endpoint {
+    pub url: String,
+    pub weight: u32,
+    pub priority: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointHealth {
+    url: String,
+    status: HealthStatus,
+    #[serde(default)]
+    latency: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HealthStatus {
+    Up,
+    Down,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+// Editor for the endpoints of a replicated store or directory, showing the
+// health reported by the server for each endpoint of a saved backend.
+#[component]
+pub fn EndpointList(
+    element: FormElement,
+    backend: Backend,
+    #[prop(optional, into)] disabled: MaybeSignal<bool>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let poller = use_polling(HEALTH_INTERVAL);
+    let endpoints = create_memo(move |_| {
+        element.data.with(|data| {
+            data.array_value(element.id)
+                .filter_map(|value| value.parse::<Endpoint>().ok())
+                .collect::<Vec<_>>()
+        })
+    });
+    let error = create_memo(move |_| {
+        element
+            .data
+            .with(|data| data.error_string(element.id).map(|error| error.to_string()))
+    });
+    let backend_id = create_memo(move |_| {
+        element
+            .data
+            .with(|data| data.value_as_str("_id").unwrap_or_default().to_string())
+    });
+
+    let health = create_local_resource(
+        move || (backend_id.get(), poller.tick()),
+        move |(id, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if id.is_empty() {
+                    return vec![];
+                }
+                match HttpRequest::get((backend.health_path(), &id))
+                    .with_authorization(&auth)
+                    .send::<Vec<EndpointHealth>>()
+                    .await
+                {
+                    Ok(health) => health,
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        vec![]
+                    }
+                    // Backends that were not saved yet have no health information
+                    Err(_) => vec![],
+                }
+            }
+        },
+    );
+
+    let update = move |idx: usize, f: &dyn Fn(&mut Endpoint)| {
+        let mut endpoints = endpoints.get_untracked();
+        if let Some(endpoint) = endpoints.get_mut(idx) {
+            f(endpoint);
+        }
+        element.data.update(|data| {
+            data.array_set(element.id, endpoints.iter().map(|e| e.to_string()));
+        });
+    };
+
+    view! {
+        <div class="space-y-3">
+            <div class="grid grid-cols-12 gap-x-2 text-xs font-medium uppercase text-gray-500">
+                <span class="col-span-7">Endpoint</span>
+                <span class="col-span-2">Weight</span>
+                <span class="col-span-2">Priority</span>
+            </div>
+            <For
+                each=move || endpoints.get().into_iter().enumerate()
+                key=|(idx, endpoint)| format!("{idx}_{endpoint}")
+                children=move |(idx, endpoint)| {
+                    let url = endpoint.url.clone();
+                    let status = Signal::derive(move || {
+                        health
+                            .get()
+                            .and_then(|health| {
+                                health.into_iter().find(|health| health.url == url)
+                            })
+                    });
+                    view! {
+                        <div class="grid grid-cols-12 gap-x-2 items-center">
+                            <div class="col-span-7 flex items-center gap-x-2">
+                                <HealthIndicator status/>
+                                <input
+                                    type="text"
+                                    class="py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                    placeholder=backend.placeholder()
+                                    prop:value=endpoint.url.clone()
+                                    disabled=move || disabled.get()
+                                    on:change=move |ev| {
+                                        let url = event_target_value(&ev).trim().to_string();
+                                        update(idx, &|endpoint| endpoint.url = url.clone());
+                                    }
+                                />
+
+                            </div>
+                            <input
+                                type="number"
+                                min="1"
+                                class="col-span-2 py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                prop:value=endpoint.weight.to_string()
+                                disabled=move || disabled.get()
+                                on:change=move |ev| {
+                                    let weight = event_target_value(&ev)
+                                        .parse::<u32>()
+                                        .unwrap_or(DEFAULT_WEIGHT)
+                                        .max(1);
+                                    update(idx, &|endpoint| endpoint.weight = weight);
+                                }
+                            />
+
+                            <input
+                                type="number"
+                                min="0"
+                                class="col-span-2 py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                prop:value=endpoint.priority.to_string()
+                                disabled=move || disabled.get()
+                                on:change=move |ev| {
+                                    let priority = event_target_value(&ev)
+                                        .parse::<u32>()
+                                        .unwrap_or_default();
+                                    update(idx, &|endpoint| endpoint.priority = priority);
+                                }
+                            />
+
+                            <button
+                                type="button"
+                                class="col-span-1 p-2.5 justify-self-center rounded-md disabled:opacity-50 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                title="Remove endpoint"
+                                disabled=move || disabled.get()
+                                on:click=move |_| {
+                                    element
+                                        .data
+                                        .update(|data| {
+                                            data.array_delete(element.id, idx);
+                                        });
+                                }
+                            >
+
+                                <IconXMark/>
+                            </button>
+                        </div>
+                    }
+                }
+            />
+
+        </div>
+
+        <p class="mt-3 text-end">
+            <button
+                type="button"
+                class="py-1.5 px-2 inline-flex items-center gap-x-1 text-xs font-medium rounded-full border border-dashed border-gray-200 bg-white text-gray-800 hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300 dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                disabled=move || disabled.get()
+                on:click=move |_| {
+                    if endpoints.get().last().is_none_or(|endpoint| !endpoint.url.is_empty()) {
+                        let priority = endpoints
+                            .get()
+                            .iter()
+                            .map(|endpoint| endpoint.priority + 1)
+                            .max()
+                            .unwrap_or_default();
+                        let endpoint = Endpoint {
+                            url: String::new(),
+                            weight: DEFAULT_WEIGHT,
+                            priority,
+                        };
+                        element
+                            .data
+                            .update(|data| {
+                                data.array_push(element.id, endpoint.to_string(), false);
+                            });
+                    }
+                }
+            >
+
+                <IconPlus attr:class="flex-shrink-0 size-3.5"/>
+                Add endpoint
+            </button>
+        </p>
+
+        {move || {
+            error
+                .get()
+                .map(|error| {
+                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                })
+        }}
+    }
+}
+
+#[component]
+fn HealthIndicator(status: Signal<Option<EndpointHealth>>) -> impl IntoView {
+    let class = move || match status.get().map(|health| health.status).unwrap_or_default() {
+        HealthStatus::Up => "flex-shrink-0 size-2.5 rounded-full bg-teal-500",
+        HealthStatus::Down => "flex-shrink-0 size-2.5 rounded-full bg-red-500",
+        HealthStatus::Unknown => "flex-shrink-0 size-2.5 rounded-full bg-gray-300 dark:bg-gray-600",
+    };
+    let title = move || match status.get() {
+        Some(EndpointHealth {
+            status: HealthStatus::Up,
+            latency: Some(latency),
+            ..
+        }) => format!("Healthy, answered in {latency} ms"),
+        Some(EndpointHealth {
+            status: HealthStatus::Up,
+            ..
+        }) => "Healthy".to_string(),
+        Some(EndpointHealth {
+            status: HealthStatus::Down,
+            error,
+            ..
+        }) => format!("Unavailable: {}", error.as_deref().unwrap_or("no response")),
+        _ => "Not checked yet".to_string(),
+    };
+
+    view! { <span class=class title=title></span> }
+}
+
+impl Backend {
+    fn health_path(&self) -> &'static str {
+        match self {
+            Backend::Store => "/api/store/health",
+            Backend::Directory => "/api/directory/health",
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            Backend::Store => "postgres://replica1.example.org:5432",
+            Backend::Directory => "ldaps://ldap1.example.org:636",
+        }
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = ();
+
+    // Entries are written as "<url> <weight> <priority>", the weight and
+    // priority can be omitted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, rest) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let url = url.to_string();
+        let mut parts = rest.split_whitespace();
+        let weight = parts
+            .next()
+            .and_then(|weight| weight.parse().ok())
+            .unwrap_or(DEFAULT_WEIGHT);
+        let priority = parts
+            .next()
+            .and_then(|priority| priority.parse().ok())
+            .unwrap_or_default();
+
+        Ok(Endpoint {
+            url,
+            weight,
+            priority,
+        })
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Rows without a URL are written empty so that they are dropped on save
+        if self.url.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "{} {} {}", self.url, self.weight, self.priority)
+        }
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_use::{use_clipboard, UseClipboardReturn};
+
+use crate::{
+    components::icon::{IconDocumentText, IconXMark},
+    core::schema::Field,
+};
+
+// Extended help for a form field, shown in a side drawer next to the form
+// rather than in the tooltip which only fits a short sentence.
+// Longest help text that still reads comfortably in the tooltip
+const TOOLTIP_MAX_LEN: usize = 80;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldHelp {
+    pub title: String,
+    pub text: Option<String>,
+    pub docs: Option<&'static str>,
+    pub examples: Vec<&'static str>,
+}
+
+impl FieldHelp {
+    pub fn new(field: &Field) -> Option<Self> {
+        let text = field.help_text();
+        let is_long = text
+            .as_ref()
+            .is_some_and(|text| text.chars().count() > TOOLTIP_MAX_LEN);
+        (is_long || field.docs.is_some() || !field.examples.is_empty()).then(|| FieldHelp {
+            title: field.label_form_text(),
+            text,
+            docs: field.docs,
+            examples: field.examples.clone(),
+        })
+    }
+}
+
+#[component]
+pub fn FieldHelpButton(help: FieldHelp) -> impl IntoView {
+    let is_open = create_rw_signal(false);
+    let help = store_value(help);
+
+    view! {
+        <button
+            type="button"
+            class="ms-1 inline-flex items-center gap-x-0.5 text-xs text-blue-600 hover:underline dark:text-blue-500"
+            on:click=move |_| is_open.set(true)
+        >
+            <IconDocumentText attr:class="flex-shrink-0 size-3"/>
+            More
+        </button>
+        <Show when=move || is_open.get()>
+            <FieldHelpDrawer help=help.get_value() on_close=move |_| is_open.set(false)/>
+        </Show>
+    }
+}
+
+#[component]
+fn FieldHelpDrawer(help: FieldHelp, #[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let UseClipboardReturn {
+        is_supported,
+        text: copied_text,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+    let copy = store_value(copy);
+    let FieldHelp {
+        title,
+        text,
+        docs,
+        examples,
+    } = help;
+    let no_examples = examples.is_empty();
+
+    view! {
+        <Portal mount=document().get_element_by_id("portal_root").unwrap()>
+            <div
+                class="fixed inset-0 z-[70] bg-gray-900 bg-opacity-50 dark:bg-opacity-80"
+                on:click=move |_| on_close.call(())
+            ></div>
+            <div
+                class="fixed top-0 end-0 z-[80] h-full w-full max-w-md flex flex-col bg-white border-s border-gray-200 shadow-lg dark:bg-gray-800 dark:border-gray-700"
+                role="dialog"
+            >
+                <div class="flex justify-between items-center py-3 px-4 border-b dark:border-gray-700">
+                    <h3 class="font-bold text-gray-800 dark:text-white">{title.clone()}</h3>
+                    <button
+                        type="button"
+                        class="flex justify-center items-center size-7 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 dark:text-white dark:hover:bg-gray-700"
+                        on:click=move |_| on_close.call(())
+                    >
+                        <span class="sr-only">Close</span>
+                        <IconXMark/>
+                    </button>
+                </div>
+                <div class="p-4 space-y-6 overflow-y-auto">
+                    {text
+                        .clone()
+                        .map(|text| {
+                            view! {
+                                <p class="text-sm text-gray-800 dark:text-gray-300">{text}</p>
+                            }
+                        })}
+                    <div class="space-y-2" class:hidden=no_examples>
+                        <h4 class="text-xs font-medium uppercase text-gray-500 dark:text-gray-400">
+                            Examples
+                        </h4>
+                        {examples
+                            .clone()
+                            .into_iter()
+                            .map(|example| {
+                                let is_copied = move || {
+                                    copied.get() && copied_text.get().as_deref() == Some(example)
+                                };
+                                view! {
+                                    <div class="flex items-start gap-x-2">
+                                        <code class="grow py-2 px-3 rounded-lg bg-gray-50 border border-gray-200 text-xs font-mono break-all text-gray-800 dark:bg-white/10 dark:border-white/10 dark:text-gray-200">
+                                            {example}
+                                        </code>
+                                        <button
+                                            type="button"
+                                            class="py-2 px-3 inline-flex items-center text-xs font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                                            class:hidden=move || !is_supported.get()
+                                            on:click=move |_| copy.with_value(|copy| copy(example))
+                                        >
+                                            {move || {
+                                                if is_copied() {
+                                                    "Copied"
+                                                } else {
+                                                    "Copy"
+                                                }
+                                            }}
+                                        </button>
+                                    </div>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                    {docs
+                        .map(|url| {
+                            view! {
+                                <a
+                                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                    href=url
+                                    target="_blank"
+                                    rel="noopener"
+                                >
+                                    Read the documentation
+                                </a>
+                            }
+                        })}
+                </div>
+            </div>
+        </Portal>
+    }
+}
@@ -0,0 +1,142 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::{AHashMap, AHashSet};
+use leptos::*;
+
+use crate::core::form::{FormData, FormValue};
+
+const MAX_HISTORY: usize = 100;
+
+#[derive(Clone, PartialEq, Eq, Default)]
+struct FormState {
+    values: AHashMap<String, FormValue>,
+    secrets: AHashSet<String>,
+}
+
+#[derive(Clone, Copy)]
+pub struct FormHistory {
+    data: RwSignal<FormData>,
+    undo: RwSignal<Vec<FormState>>,
+    redo: RwSignal<Vec<FormState>>,
+    current: StoredValue<FormState>,
+}
+
+pub fn use_form_history(data: RwSignal<FormData>) -> FormHistory {
+    let history = FormHistory {
+        data,
+        undo: create_rw_signal(Vec::new()),
+        redo: create_rw_signal(Vec::new()),
+        current: store_value(data.with_untracked(|data| FormState::from(data))),
+    };
+
+    // Every change to the form values, regardless of the component that made
+    // it, becomes an undo step. Validation errors are not tracked.
+    create_effect(move |_| {
+        let state = data.with(|data| FormState::from(data));
+        let previous = history.current.get_value();
+        if previous != state {
+            history.undo.update(|undo| {
+                undo.push(previous);
+                if undo.len() > MAX_HISTORY {
+                    undo.remove(0);
+                }
+            });
+            history.redo.update(|redo| redo.clear());
+            history.current.set_value(state);
+        }
+    });
+
+    let handle = window_event_listener(ev::keydown, move |ev| {
+        if !(ev.ctrl_key() || ev.meta_key()) || is_editing_text() {
+            return;
+        }
+
+        match ev.key().as_str() {
+            "z" | "Z" if ev.shift_key() => {
+                ev.prevent_default();
+                history.redo();
+            }
+            "z" | "Z" => {
+                ev.prevent_default();
+                history.undo();
+            }
+            "y" | "Y" => {
+                ev.prevent_default();
+                history.redo();
+            }
+            _ => {}
+        }
+    });
+    on_cleanup(move || handle.remove());
+
+    history
+}
+
+impl FormHistory {
+    pub fn undo(&self) {
+        if let Some(state) = self.undo.try_update(|undo| undo.pop()).flatten() {
+            self.redo.update(|redo| redo.push(self.current.get_value()));
+            self.restore(state);
+        }
+    }
+
+    pub fn redo(&self) {
+        if let Some(state) = self.redo.try_update(|redo| redo.pop()).flatten() {
+            self.undo.update(|undo| undo.push(self.current.get_value()));
+            self.restore(state);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo.with(|undo| !undo.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo.with(|redo| !redo.is_empty())
+    }
+
+    // Forgets all changes, used when a new record is loaded into the form.
+    pub fn reset(&self) {
+        self.undo.set(Vec::new());
+        self.redo.set(Vec::new());
+        self.current
+            .set_value(self.data.with_untracked(|data| FormState::from(data)));
+    }
+
+    fn restore(&self, state: FormState) {
+        self.current.set_value(state.clone());
+        self.data.update(|data| {
+            data.values = state.values;
+            data.secrets = state.secrets;
+            data.errors.clear();
+        });
+    }
+}
+
+impl From<&FormData> for FormState {
+    fn from(data: &FormData) -> Self {
+        FormState {
+            values: data.values.clone(),
+            secrets: data.secrets.clone(),
+        }
+    }
+}
+
+// Text fields commit their value on change, so while one is focused the
+// browser's own undo applies to the text being typed.
+pub fn is_editing_text() -> bool {
+    document()
+        .active_element()
+        .is_some_and(|element| match element.tag_name().as_str() {
+            "TEXTAREA" => true,
+            "INPUT" => !matches!(
+                element.get_attribute("type").as_deref(),
+                Some("checkbox" | "radio" | "button")
+            ),
+            _ => false,
+        })
+}
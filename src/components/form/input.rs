@@ -199,11 +199,14 @@ pub fn InputPassword(
 
 const UNIT_GB: u64 = 1024 * 1024 * 1024;
 const UNIT_MB: u64 = 1024 * 1024;
+const UNIT_KB: u64 = 1024;
 
 #[component]
 pub fn InputSize(
     element: FormElement,
     #[prop(optional, into)] disabled: MaybeSignal<bool>,
+    // Shows a slider between these bounds, in bytes
+    #[prop(optional_no_strip)] range: Option<(u64, u64)>,
     #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
 ) -> impl IntoView {
     let value = create_memo(move |_| {
@@ -222,6 +225,8 @@ pub fn InputSize(
             UNIT_GB
         } else if raw_value % UNIT_MB == 0 {
             UNIT_MB
+        } else if raw_value % UNIT_KB == 0 {
+            UNIT_KB
         } else {
             1
         }
@@ -305,6 +310,9 @@ pub fn InputSize(
                     <option selected=move || multiplier.get() == 1 value="1">
                         bytes
                     </option>
+                    <option selected=move || multiplier.get() == UNIT_KB value=UNIT_KB.to_string()>
+                        KB
+                    </option>
                     <option selected=move || multiplier.get() == UNIT_MB value=UNIT_MB.to_string()>
                         MB
                     </option>
@@ -315,6 +323,35 @@ pub fn InputSize(
             </div>
         </div>
 
+        {range
+            .map(|(min, max)| {
+                // Keeps the slider on whole units so the selected unit is retained
+                let step = [UNIT_GB, UNIT_MB, UNIT_KB]
+                    .into_iter()
+                    .find(|unit| min % unit == 0 && max % unit == 0)
+                    .unwrap_or(1);
+                view! {
+                    <input
+                        type="range"
+                        class="w-full mt-3 bg-transparent cursor-pointer appearance-none disabled:opacity-50 disabled:pointer-events-none focus:outline-none [&::-webkit-slider-thumb]:w-2.5 [&::-webkit-slider-thumb]:h-2.5 [&::-webkit-slider-thumb]:-mt-0.5 [&::-webkit-slider-thumb]:appearance-none [&::-webkit-slider-thumb]:bg-white [&::-webkit-slider-thumb]:shadow-[0_0_0_4px_rgba(37,99,235,1)] [&::-webkit-slider-thumb]:rounded-full [&::-webkit-slider-runnable-track]:w-full [&::-webkit-slider-runnable-track]:h-2 [&::-webkit-slider-runnable-track]:bg-gray-100 [&::-webkit-slider-runnable-track]:rounded-full dark:[&::-webkit-slider-runnable-track]:bg-neutral-700"
+                        min=min
+                        max=max
+                        step=step
+                        prop:value=move || value.get().clamp(min, max)
+                        disabled=move || { disabled.get() || display_value.get() == 0 }
+                        on:input=move |ev| {
+                            if let Ok(new_value) = event_target_value(&ev).parse::<u64>() {
+                                element
+                                    .data
+                                    .update(|data| {
+                                        data.update(element.id, new_value.to_string());
+                                    });
+                            }
+                        }
+                    />
+                }
+            })}
+
         {move || {
             error
                 .get()
@@ -584,7 +621,7 @@ pub fn InputRate(
 pub fn InputSwitch(
     element: FormElement,
     #[prop(optional, into)] label: Option<MaybeSignal<String>>,
-    #[prop(optional)] tooltip: Option<&'static str>,
+    #[prop(optional, into)] tooltip: MaybeSignal<String>,
     #[prop(optional, into)] disabled: MaybeSignal<bool>,
     #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
 ) -> impl IntoView {
@@ -618,14 +655,15 @@ pub fn InputSwitch(
                 .map(|label| {
                     view! {
                         <label class="text-sm text-gray-500 ms-3 dark:text-gray-400">
-                            {label.get()}
+                            {move || label.get()}
                         </label>
                     }
                 })}
 
-            {tooltip
-                .filter(|s| !s.is_empty())
-                .map(|tooltip| {
+            {move || {
+                let tooltip = tooltip.get();
+                (!tooltip.is_empty())
+                    .then(|| {
                     let is_mouse_over = create_rw_signal(false);
                     view! {
                         <div class="hs-tooltip inline-block">
@@ -658,7 +696,8 @@ pub fn InputSwitch(
 
                         </div>
                     }
-                })}
+                })
+            }}
 
         </div>
     }
@@ -5,8 +5,19 @@
  */
 
 pub mod button;
+pub mod cron;
+pub mod draft;
+pub mod endpoints;
 pub mod expression;
+pub mod help;
+pub mod history;
 pub mod input;
+pub mod permissions;
+pub mod principal;
+pub mod renderer;
+pub mod rules;
+pub mod schedule;
+pub mod secret;
 pub mod select;
 pub mod stacked_badge;
 pub mod stacked_input;
@@ -15,8 +26,12 @@ pub mod tab;
 use leptos::*;
 
 use crate::{
-    components::{icon::IconInfo, messages::alert::Alerts},
-    core::form::FormData,
+    components::{
+        form::help::{FieldHelp, FieldHelpButton},
+        icon::IconInfo,
+        messages::alert::Alerts,
+    },
+    core::{form::FormData, i18n::t},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -60,16 +75,19 @@ pub fn FormButtonBar(children: Children) -> impl IntoView {
 
 #[component]
 pub fn FormSection(
-    #[prop(optional)] title: Option<String>,
+    #[prop(optional, into)] title: MaybeSignal<String>,
     #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
     #[prop(optional, into)] hide: MaybeSignal<bool>,
     #[prop(optional)] stacked: bool,
     children: Children,
 ) -> impl IntoView {
-    let title = title.filter(|s| !s.is_empty()).map(|title| {
+    let has_title = title.with_untracked(|title| !title.is_empty());
+    let title = has_title.then(|| {
         view! {
             <div class="sm:col-span-12">
-                <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">{title}</h2>
+                <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                    {move || title.get()}
+                </h2>
             </div>
         }
     });
@@ -94,17 +112,18 @@ pub fn FormSection(
 #[component]
 pub fn FormItem(
     #[prop(into)] label: MaybeSignal<String>,
-    #[prop(optional)] tooltip: Option<&'static str>,
+    #[prop(optional, into)] tooltip: MaybeSignal<String>,
     #[prop(optional, into)] hide: MaybeSignal<bool>,
     #[prop(optional, into)] is_optional: MaybeSignal<bool>,
     #[prop(optional)] stacked: bool,
+    #[prop(optional_no_strip)] help: Option<FieldHelp>,
     children: Children,
 ) -> impl IntoView {
-    let tooltip = tooltip
-    .filter(|s| !s.is_empty())
-    .map(|tooltip| {
-        let is_mouse_over = create_rw_signal(false);
-        view! {
+    let help = help.map(|help| view! { <FieldHelpButton help/> });
+    let is_mouse_over = create_rw_signal(false);
+    let tooltip = move || {
+        let tooltip = tooltip.get();
+        (!tooltip.is_empty()).then(|| view! {
             <div class="hs-tooltip inline-block">
                 <button
                     type="button"
@@ -134,13 +153,13 @@ pub fn FormItem(
                 </span>
 
             </div>
-        }
-    });
+        })
+    };
 
     let is_optional = move || {
         if is_optional.get() {
             Some(
-                view! { <span class="text-sm text-gray-400 dark:text-gray-600">{" (Optional)"}</span> },
+                view! { <span class="text-sm text-gray-400 dark:text-gray-600">{format!(" {}", t("form-optional"))}</span> },
             )
         } else {
             None
@@ -154,6 +173,7 @@ pub fn FormItem(
                     {label}
                 </label>
                 {tooltip}
+                {help}
                 {is_optional}
 
             </div>
@@ -165,7 +185,9 @@ pub fn FormItem(
     } else {
         view! {
             <div class="mb-4 sm:mb-8" class:hidden=move || hide.get()>
-                <label class="block mb-2 text-sm font-medium dark:text-white">{label}</label>
+                <label class="block mb-2 text-sm font-medium dark:text-white">
+                    {label} {help}
+                </label>
                 <div class="relative">{children()}</div>
             </div>
         }
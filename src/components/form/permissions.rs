@@ -0,0 +1,338 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashSet;
+use leptos::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconChevronDown, IconChevronRight},
+        Color,
+    },
+    core::{oauth::use_authorization, Permission},
+    pages::directory::PERMISSIONS,
+};
+
+use super::FormElement;
+
+static PERMISSION_GROUPS: &[(&str, &[&str])] = &[
+    (
+        "Directory",
+        &[
+            "individual-",
+            "group-",
+            "domain-",
+            "tenant-",
+            "mailing-list-",
+            "role-",
+            "principal-",
+            "api-key-",
+            "oauth-client-",
+            "dkim-",
+        ],
+    ),
+    (
+        "Queues and reports",
+        &["message-queue-", "outgoing-report-", "incoming-report-"],
+    ),
+    (
+        "Server administration",
+        &[
+            "settings-",
+            "webadmin-",
+            "logs-",
+            "restart",
+            "tracing-",
+            "metrics-",
+            "purge-",
+            "fts-",
+            "undelete",
+            "blob-fetch",
+            "spam-filter-",
+            "troubleshoot",
+        ],
+    ),
+    ("JMAP", &["jmap-"]),
+    ("IMAP", &["imap-"]),
+    ("POP3", &["pop3-"]),
+    ("ManageSieve", &["sieve-"]),
+];
+const OTHER_GROUP: &str = "General";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PermissionState {
+    On,
+    Off,
+    Default,
+}
+
+#[component]
+pub fn PermissionTree(
+    enabled: FormElement,
+    disabled: FormElement,
+    // Only permissions held by the signed-in administrator can be turned on
+    #[prop(optional)] restrict: bool,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let filter = create_rw_signal(String::new());
+    let expanded = create_rw_signal(AHashSet::<&'static str>::new());
+    let granted = store_value(auth.get_untracked().permissions().clone());
+
+    let enabled_set = create_memo(move |_| {
+        enabled
+            .data
+            .get()
+            .array_value(enabled.id)
+            .map(|id| id.to_string())
+            .collect::<AHashSet<_>>()
+    });
+    let disabled_set = create_memo(move |_| {
+        disabled
+            .data
+            .get()
+            .array_value(disabled.id)
+            .map(|id| id.to_string())
+            .collect::<AHashSet<_>>()
+    });
+    let state = move |id: &str| {
+        if enabled_set.with(|set| set.contains(id)) {
+            PermissionState::On
+        } else if disabled_set.with(|set| set.contains(id)) {
+            PermissionState::Off
+        } else {
+            PermissionState::Default
+        }
+    };
+    let set_state = move |ids: &[&'static str], state: PermissionState| {
+        enabled.data.update(|data| {
+            for id in ids {
+                data.array_delete_item(enabled.id, id);
+                data.array_delete_item(disabled.id, id);
+                match state {
+                    PermissionState::On => data.array_push(enabled.id, id.to_string(), true),
+                    PermissionState::Off => data.array_push(disabled.id, id.to_string(), true),
+                    PermissionState::Default => {}
+                }
+            }
+        });
+    };
+    let can_grant = move |id: &str| {
+        !restrict
+            || serde_json::from_value::<Permission>(serde_json::Value::String(id.to_string()))
+                .ok()
+                .is_some_and(|permission| {
+                    granted.with_value(|granted| granted.has_access(permission))
+                })
+    };
+
+    view! {
+        <div class="grid space-y-2">
+            <input
+                type="text"
+                class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                placeholder="Filter permissions"
+                autocapitalize="off"
+                prop:value=filter
+                on:input=move |ev| filter.set(event_target_value(&ev))
+            />
+
+            {permission_groups()
+                .into_iter()
+                .map(|(title, permissions)| {
+                    let ids = permissions.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                    let grantable = ids
+                        .iter()
+                        .copied()
+                        .filter(|id| can_grant(id))
+                        .collect::<Vec<_>>();
+                    let visible = create_memo(move |_| {
+                        let filter = filter.get().trim().to_lowercase();
+                        permissions
+                            .iter()
+                            .filter(|(id, name)| {
+                                filter.is_empty() || id.contains(&filter)
+                                    || name.to_lowercase().contains(&filter)
+                            })
+                            .copied()
+                            .collect::<Vec<_>>()
+                    });
+                    let is_expanded = create_memo(move |_| {
+                        expanded.with(|expanded| expanded.contains(title))
+                            || !filter.with(|filter| filter.trim().is_empty())
+                    });
+                    let summary = {
+                        let ids = ids.clone();
+                        create_memo(move |_| {
+                            let (mut on, mut off) = (0, 0);
+                            for id in &ids {
+                                match state(id) {
+                                    PermissionState::On => on += 1,
+                                    PermissionState::Off => off += 1,
+                                    PermissionState::Default => {}
+                                }
+                            }
+                            (on, off)
+                        })
+                    };
+                    let ids_off = ids.clone();
+                    let ids_default = ids;
+
+                    view! {
+                        <div
+                            class="border border-gray-200 rounded-lg dark:border-neutral-700"
+                            class:hidden=move || visible.with(|v| v.is_empty())
+                        >
+                            <div class="flex flex-wrap justify-between items-center gap-2 p-3">
+                                <button
+                                    type="button"
+                                    class="inline-flex items-center gap-x-2 text-sm font-medium text-gray-800 dark:text-neutral-200"
+                                    on:click=move |_| {
+                                        expanded
+                                            .update(|expanded| {
+                                                if !expanded.remove(title) {
+                                                    expanded.insert(title);
+                                                }
+                                            });
+                                    }
+                                >
+
+                                    {move || {
+                                        if is_expanded.get() {
+                                            view! { <IconChevronDown attr:class="flex-shrink-0 size-4"/> }
+                                        } else {
+                                            view! { <IconChevronRight attr:class="flex-shrink-0 size-4"/> }
+                                        }
+                                    }}
+
+                                    {title}
+                                    {move || {
+                                        let (on, off) = summary.get();
+                                        (on > 0)
+                                            .then(|| {
+                                                view! {
+                                                    <Badge color=Color::Green>{format!("{on} on")}</Badge>
+                                                }
+                                            })
+                                            .into_iter()
+                                            .chain(
+                                                (off > 0)
+                                                    .then(|| {
+                                                        view! {
+                                                            <Badge color=Color::Red>{format!("{off} off")}</Badge>
+                                                        }
+                                                    }),
+                                            )
+                                            .collect_view()
+                                    }}
+
+                                </button>
+                                <div class="flex gap-x-3 text-xs font-semibold text-blue-600 dark:text-blue-500">
+                                    <button
+                                        type="button"
+                                        class="hover:underline disabled:opacity-50 disabled:pointer-events-none"
+                                        disabled=grantable.is_empty()
+                                        on:click=move |_| set_state(&grantable, PermissionState::On)
+                                    >
+                                        All on
+                                    </button>
+                                    <button
+                                        type="button"
+                                        class="hover:underline"
+                                        on:click=move |_| set_state(&ids_off, PermissionState::Off)
+                                    >
+                                        All off
+                                    </button>
+                                    <button
+                                        type="button"
+                                        class="hover:underline"
+                                        on:click=move |_| {
+                                            set_state(&ids_default, PermissionState::Default)
+                                        }
+                                    >
+                                        Reset
+                                    </button>
+                                </div>
+                            </div>
+                            <div class="grid space-y-2 px-3 pb-3" class:hidden=move || !is_expanded.get()>
+                                <For
+                                    each=move || visible.get()
+                                    key=|(id, _)| *id
+                                    children=move |(id, name)| {
+                                        let is_grantable = can_grant(id);
+                                        view! {
+                                            <div class="flex justify-between items-center p-3 w-full bg-white border border-gray-200 rounded-lg dark:bg-neutral-900 dark:border-neutral-700">
+                                                <span class="block text-sm text-gray-600 dark:text-neutral-500">
+                                                    {name}
+                                                </span>
+
+                                                <div class="flex gap-x-6">
+                                                    {[
+                                                        (PermissionState::On, "On", "on"),
+                                                        (PermissionState::Off, "Off", "off"),
+                                                        (PermissionState::Default, "Default", "inherit"),
+                                                    ]
+                                                        .into_iter()
+                                                        .map(|(value, label, suffix)| {
+                                                            let input_id = format!("{id}-{suffix}");
+                                                            view! {
+                                                                <div class="flex">
+                                                                    <input
+                                                                        type="radio"
+                                                                        name=id
+                                                                        id=input_id.clone()
+                                                                        class="shrink-0 mt-0.5 border-gray-200 rounded-full text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-neutral-800 dark:border-neutral-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                                                        disabled=value == PermissionState::On
+                                                                            && !is_grantable
+                                                                        prop:checked=move || state(id) == value
+                                                                        on:input=move |_| set_state(&[id], value)
+                                                                    />
+
+                                                                    <label
+                                                                        for=input_id
+                                                                        class="text-sm text-gray-500 ms-2 dark:text-neutral-400"
+                                                                    >
+                                                                        {label}
+                                                                    </label>
+                                                                </div>
+                                                            }
+                                                        })
+                                                        .collect_view()}
+                                                </div>
+                                            </div>
+                                        }
+                                    }
+                                />
+
+                            </div>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+fn permission_groups() -> Vec<(&'static str, Vec<(&'static str, &'static str)>)> {
+    let mut groups = PERMISSION_GROUPS
+        .iter()
+        .map(|(title, _)| (*title, Vec::new()))
+        .chain([(OTHER_GROUP, Vec::new())])
+        .collect::<Vec<_>>();
+
+    for (id, name) in PERMISSIONS {
+        let idx = PERMISSION_GROUPS
+            .iter()
+            .position(|(_, prefixes)| prefixes.iter().any(|prefix| id.starts_with(prefix)))
+            .unwrap_or(PERMISSION_GROUPS.len());
+        groups[idx].1.push((*id, *name));
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, permissions)| !permissions.is_empty())
+        .collect()
+}
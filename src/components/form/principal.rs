@@ -0,0 +1,346 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use ahash::AHashMap;
+use leptos::{leptos_dom::helpers::TimeoutHandle, *};
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconXMark,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Source, Type},
+    },
+    pages::{
+        directory::{Principal, PrincipalType},
+        List,
+    },
+};
+
+use super::FormElement;
+
+const PAGE_SIZE: u32 = 20;
+const SEARCH_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PrincipalOption {
+    id: String,
+    label: String,
+    typ: Option<PrincipalType>,
+}
+
+#[component]
+pub fn SelectPrincipal(
+    #[prop(into)] add_button_text: String,
+    color: Color,
+    element: FormElement,
+    #[prop(into, optional)] types: Option<Signal<Vec<&'static str>>>,
+    #[prop(into, optional)] tenant: Signal<Option<String>>,
+    #[prop(into, optional)] exclude: Signal<Option<String>>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let types = types.unwrap_or_else(|| {
+        let types = element
+            .data
+            .get_untracked()
+            .schema
+            .fields
+            .get(element.id)
+            .and_then(|field| match &field.typ_ {
+                Type::Select {
+                    source: Source::Principals(types),
+                    ..
+                } => Some(types.to_vec()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Signal::derive(move || types.clone())
+    });
+
+    let show_picker = create_rw_signal(false);
+    let search = create_rw_signal(String::new());
+    let query = create_rw_signal(String::new());
+    let results = create_rw_signal(Vec::<PrincipalOption>::new());
+    let labels = create_rw_signal(AHashMap::<String, String>::new());
+    let total = create_rw_signal(0u64);
+    let page = create_rw_signal(1u32);
+    let loading = create_rw_signal(false);
+    let request_id = store_value(0u32);
+    let search_timer = store_value(None::<TimeoutHandle>);
+
+    let value = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .array_value(element.id)
+            .enumerate()
+            .map(|(idx, s)| (idx, s.to_string()))
+            .collect::<Vec<_>>()
+    });
+
+    let fetch = move |page_num: u32| {
+        let types = types.get_untracked();
+        if types.is_empty() {
+            return;
+        }
+        let auth = auth.get_untracked();
+        let filter = query.get_untracked();
+        let tenant = tenant.get_untracked();
+        let exclude = exclude.get_untracked();
+        let id = request_id.get_value().wrapping_add(1);
+        request_id.set_value(id);
+        loading.set(true);
+
+        spawn_local(async move {
+            let result = HttpRequest::get("/api/principal")
+                .with_authorization(&auth)
+                .with_parameter("types", types.join(","))
+                .with_parameter("fields", "name,description")
+                .with_parameter("page", page_num.to_string())
+                .with_parameter("limit", PAGE_SIZE.to_string())
+                .with_optional_parameter("filter", (!filter.is_empty()).then_some(filter))
+                .with_optional_parameter("tenant", tenant)
+                .send::<List<Principal>>()
+                .await;
+
+            // Discard responses to searches that have since been superseded
+            if request_id.get_value() != id {
+                return;
+            }
+            loading.set(false);
+
+            match result {
+                Ok(list) => {
+                    let items = list
+                        .items
+                        .into_iter()
+                        .filter_map(|principal| {
+                            let id = principal.name.unwrap_string();
+                            if exclude.as_deref() == Some(id.as_str()) {
+                                return None;
+                            }
+                            let label = principal
+                                .description
+                                .try_unwrap_string()
+                                .map(|d| format!("{d} ({id})"))
+                                .unwrap_or_else(|| id.clone());
+                            Some(PrincipalOption {
+                                id,
+                                label,
+                                typ: principal.typ,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
+                    labels.update(|labels| {
+                        for item in &items {
+                            labels.insert(item.id.clone(), item.label.clone());
+                        }
+                    });
+                    total.set(list.total);
+                    page.set(page_num);
+                    if page_num > 1 {
+                        results.update(|results| results.extend(items));
+                    } else {
+                        results.set(items);
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    };
+
+    create_effect(move |_| {
+        query.track();
+        types.track();
+        tenant.track();
+        if show_picker.get() {
+            fetch(1);
+        }
+    });
+
+    let available = create_memo(move |_| {
+        let data = element.data.get();
+        results.with(|results| {
+            results
+                .iter()
+                .filter(|item| !data.array_value(element.id).any(|v| v == item.id))
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+    let has_more = create_memo(move |_| (results.with(|r| r.len()) as u64) < total.get());
+    let show_types = create_memo(move |_| types.get().len() > 1);
+
+    view! {
+        <div class="relative">
+
+            <For
+                each=move || { value.get() }
+                key=move |(idx, item)| format!("{idx}_{item}")
+                children=move |(idx, item)| {
+                    let id = item.clone();
+                    view! {
+                        <div class="inline-flex flex-wrap gap-2 p-1">
+
+                            <Badge color=color large=true>
+                                {move || {
+                                    labels.with(|labels| labels.get(&id).cloned()).unwrap_or(id.clone())
+                                }}
+
+                                <button
+                                    type="button"
+                                    class="flex-shrink-0 size-4 inline-flex items-center justify-center rounded-full hover:bg-teal-200 focus:outline-none focus:bg-teal-200 focus:text-teal-500 dark:hover:bg-teal-900"
+                                    on:click=move |_| {
+                                        element
+                                            .data
+                                            .update(|data| {
+                                                data.array_delete(element.id, idx);
+                                            });
+                                    }
+                                >
+
+                                    <span class="sr-only">Remove</span>
+                                    <IconXMark attr:class="flex-shrink-0 size-3"/>
+                                </button>
+                            </Badge>
+
+                        </div>
+                    }
+                }
+            />
+
+            <div class="inline-flex flex-wrap gap-2 p-1">
+                <button
+                    type="button"
+                    class="py-1.5 px-2 inline-flex items-center gap-x-1 text-xs font-medium rounded-full border border-dashed border-gray-200 bg-white text-gray-800 hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300 dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    on:click=move |_| {
+                        search.set(String::new());
+                        query.set(String::new());
+                        show_picker.set(true);
+                    }
+                >
+
+                    "+ "
+                    {add_button_text}
+
+                </button>
+            </div>
+
+        </div>
+
+        <div
+            class="absolute z-10 w-full max-w-md mt-1 p-3 bg-white border rounded-lg shadow-md dark:bg-gray-900 dark:border-gray-700"
+            class:hidden=move || !show_picker.get()
+        >
+            <div class="flex rounded-lg shadow-sm">
+                <input
+                    type="text"
+                    class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-s-md text-sm focus:z-10 focus:border-blue-500 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700 dark:text-gray-400"
+                    placeholder="Search by name or e-mail"
+                    autocapitalize="off"
+                    prop:value=search
+                    on:input=move |ev| {
+                        search.set(event_target_value(&ev));
+                        if let Some(handle) = search_timer.get_value() {
+                            handle.clear();
+                        }
+                        search_timer
+                            .set_value(
+                                set_timeout_with_handle(
+                                        move || {
+                                            query.set(search.get_untracked().trim().to_string());
+                                        },
+                                        SEARCH_DELAY,
+                                    )
+                                    .ok(),
+                            );
+                    }
+                />
+
+                <button
+                    type="button"
+                    class="-ms-px py-2 px-3 inline-flex justify-center items-center gap-2 border font-medium bg-white text-gray-700 rounded-e-md shadow-sm align-middle hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-blue-600 transition-all text-sm dark:bg-gray-800 dark:hover:bg-slate-800 dark:border-gray-700 dark:text-gray-400 dark:hover:text-white"
+                    on:click=move |_| {
+                        show_picker.set(false);
+                    }
+                >
+
+                    Close
+                </button>
+            </div>
+
+            <ul class="mt-2 max-h-64 overflow-y-auto divide-y divide-gray-100 dark:divide-gray-800">
+                <For
+                    each=move || available.get()
+                    key=|item| item.id.clone()
+                    children=move |item| {
+                        let id = item.id.clone();
+                        let typ = item.typ.filter(|_| show_types.get());
+                        view! {
+                            <li>
+                                <button
+                                    type="button"
+                                    class="w-full flex items-center justify-between gap-x-2 py-2 px-2 text-start text-sm text-gray-800 rounded-lg hover:bg-gray-100 focus:outline-none focus:bg-gray-100 dark:text-gray-300 dark:hover:bg-gray-800 dark:focus:bg-gray-800"
+                                    on:click=move |_| {
+                                        let id = id.clone();
+                                        element
+                                            .data
+                                            .update(|data| {
+                                                data.array_push(element.id, id, false);
+                                            });
+                                    }
+                                >
+
+                                    <span class="truncate">{item.label}</span>
+                                    {typ
+                                        .map(|typ| {
+                                            view! { <Badge color=Color::Gray>{typ.name()}</Badge> }
+                                        })}
+
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+
+            </ul>
+
+            <p
+                class="mt-2 text-sm text-gray-500 dark:text-gray-400"
+                class:hidden=move || loading.get() || !available.with(|a| a.is_empty())
+            >
+                No matches found.
+            </p>
+            <p class="mt-2 text-sm text-gray-500 dark:text-gray-400" class:hidden=move || !loading.get()>
+                Searching...
+            </p>
+            <button
+                type="button"
+                class="mt-2 text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                class:hidden=move || loading.get() || !has_more.get()
+                on:click=move |_| fetch(page.get_untracked() + 1)
+            >
+
+                Load more
+            </button>
+        </div>
+    }
+}
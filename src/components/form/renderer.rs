@@ -0,0 +1,88 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{rc::Rc, sync::Arc};
+
+use ahash::AHashMap;
+use leptos::*;
+
+use crate::core::schema::{Field, Schema, Type};
+
+use super::FormElement;
+
+pub type FieldRenderer = Rc<dyn Fn(FieldContext) -> View>;
+
+#[derive(Clone)]
+pub struct FieldContext {
+    pub element: FormElement,
+    pub field: Arc<Field>,
+    pub disabled: bool,
+}
+
+// Renderers registered for a specific schema field take precedence over
+// renderers registered for a field type.
+#[derive(Clone, Default)]
+pub struct FieldRenderers {
+    types: AHashMap<&'static str, FieldRenderer>,
+    fields: AHashMap<(&'static str, &'static str), FieldRenderer>,
+}
+
+pub fn use_field_renderers() -> FieldRenderers {
+    use_context::<FieldRenderers>().unwrap_or_default()
+}
+
+impl FieldRenderers {
+    pub fn get(&self, schema: &Schema, field: &Field) -> Option<FieldRenderer> {
+        self.fields
+            .get(&(schema.id, field.id))
+            .or_else(|| self.types.get(field.typ_.name()))
+            .cloned()
+    }
+}
+
+// Called from `build_field_renderers`, `with_type` is only used by
+// customized builds.
+#[allow(dead_code)]
+impl FieldRenderers {
+    pub fn with_type(
+        mut self,
+        typ: &'static str,
+        renderer: impl Fn(FieldContext) -> View + 'static,
+    ) -> Self {
+        self.types.insert(typ, Rc::new(renderer));
+        self
+    }
+
+    pub fn with_field(
+        mut self,
+        schema_id: &'static str,
+        field_id: &'static str,
+        renderer: impl Fn(FieldContext) -> View + 'static,
+    ) -> Self {
+        self.fields.insert((schema_id, field_id), Rc::new(renderer));
+        self
+    }
+}
+
+impl<S, F> Type<S, F> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Type::Input => "input",
+            Type::Array => "array",
+            Type::Secret => "secret",
+            Type::Text => "text",
+            Type::Expression => "expression",
+            Type::Rules => "rules",
+            Type::Select { .. } => "select",
+            Type::Boolean => "boolean",
+            Type::Duration => "duration",
+            Type::Rate => "rate",
+            Type::Size => "size",
+            Type::Cron => "cron",
+            Type::Custom(name) => name,
+        }
+    }
+}
@@ -0,0 +1,585 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::icon::{IconArrowRightCircle, IconPlus, IconXMark},
+    core::form::{ExpressionError, ExpressionIfThen, FormError, FormErrorType},
+};
+
+use super::FormElement;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variable {
+    RemoteIp,
+    Listener,
+    RemoteHost,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    InNetwork,
+    Is,
+    IsNot,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Condition {
+    variable: Variable,
+    operator: Operator,
+    value: String,
+}
+
+const VARIABLES: &[Variable] = &[Variable::RemoteIp, Variable::Listener, Variable::RemoteHost];
+const OPERATORS: &[Operator] = &[
+    Operator::InNetwork,
+    Operator::Is,
+    Operator::IsNot,
+    Operator::StartsWith,
+    Operator::EndsWith,
+];
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const INPUT_ERR_CLASS: &str = "py-2 px-3 block w-full border-red-500 shadow-sm rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+// Edits an expression as a list of "condition -> value" rules. Conditions are
+// compiled to expressions, conditions that were written by hand and cannot be
+// represented by the builder are shown as raw expressions.
+#[component]
+pub fn InputRules(
+    element: FormElement,
+    #[prop(optional, into)] placeholder: MaybeSignal<String>,
+) -> impl IntoView {
+    let num_rules = create_memo(move |_| element.data.get().expr_if_thens(element.id).count());
+    let else_value = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .expr_else(element.id)
+            .unwrap_or_default()
+            .to_string()
+    });
+    let else_err = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .error(element.id)
+            .and_then(|e| match e.id {
+                FormErrorType::Expression(ExpressionError::Else) => Some(e.error.clone()),
+                _ => None,
+            })
+    });
+    let placeholder_ = placeholder.clone();
+
+    view! {
+        <div class="space-y-3">
+            <For
+                each=move || 0..num_rules.get()
+                key=|idx| *idx
+                children=move |idx| {
+                    view! { <RuleItem element idx placeholder=placeholder_.clone()/> }
+                }
+            />
+
+            <div>
+                <div class="flex items-center gap-x-2">
+                    <span class="text-sm text-gray-500 min-w-fit dark:text-gray-400">
+                        Otherwise
+                    </span>
+                    <input
+                        type="text"
+                        class=move || {
+                            if else_err.get().is_none() { INPUT_CLASS } else { INPUT_ERR_CLASS }
+                        }
+
+                        placeholder=move || placeholder.get()
+                        prop:value=else_value
+                        on:change=move |ev| {
+                            element
+                                .data
+                                .update(|data| {
+                                    data.expr_update_else(element.id, event_target_value(&ev));
+                                });
+                        }
+                    />
+
+                </div>
+                {move || {
+                    else_err
+                        .get()
+                        .map(|error| {
+                            view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                        })
+                }}
+
+            </div>
+        </div>
+
+        <p class="mt-3 text-end">
+            <button
+                type="button"
+                class="py-1.5 px-2 inline-flex items-center gap-x-1 text-xs font-medium rounded-full border border-dashed border-gray-200 bg-white text-gray-800 hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300 dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                on:click=move |_| {
+                    element
+                        .data
+                        .update(|data| {
+                            data.expr_push_if_then(element.id, "", "");
+                        });
+                }
+            >
+
+                <IconPlus attr:class="flex-shrink-0 size-3.5"/>
+                Add Rule
+            </button>
+        </p>
+    }
+}
+
+#[component]
+fn RuleItem(element: FormElement, idx: usize, placeholder: MaybeSignal<String>) -> impl IntoView {
+    let if_then = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .expr_if_thens(element.id)
+            .nth(idx)
+            .cloned()
+            .unwrap_or_else(|| ExpressionIfThen {
+                if_: String::new(),
+                then_: String::new(),
+            })
+    });
+    // Holds the condition being edited until it can be compiled, so that
+    // changing the variable or operator of an incomplete rule is not lost.
+    let draft = create_rw_signal(None::<(String, Condition)>);
+    let condition = create_memo(move |_| {
+        let if_ = if_then.get().if_;
+        if let Some((_, condition)) = draft.get().filter(|(source, _)| *source == if_) {
+            Some(condition)
+        } else if if_.is_empty() {
+            Some(Condition::default())
+        } else {
+            Condition::parse(&if_)
+        }
+    });
+    let error = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .error(element.id)
+            .and_then(|e| match e.id {
+                FormErrorType::Expression(ExpressionError::If(pos)) if pos == idx => {
+                    Some((true, e.error.clone()))
+                }
+                FormErrorType::Expression(ExpressionError::Then(pos)) if pos == idx => {
+                    Some((false, e.error.clone()))
+                }
+                _ => None,
+            })
+    });
+
+    let update_condition = move |f: &dyn Fn(&mut Condition)| {
+        let mut condition = condition.get().unwrap_or_default();
+        f(&mut condition);
+        element.data.update(|data| match condition.compile() {
+            Ok(if_) => {
+                draft.set(Some((if_.clone(), condition.clone())));
+                data.expr_update_if(element.id, idx, if_);
+            }
+            Err(error) => {
+                draft.set(Some((if_then.get_untracked().if_, condition.clone())));
+                data.errors.insert(
+                    element.id.to_string(),
+                    FormError {
+                        id: FormErrorType::Expression(ExpressionError::If(idx)),
+                        error: error.to_string(),
+                    },
+                );
+            }
+        });
+    };
+
+    let condition_view = move || match condition.get() {
+        Some(current) => {
+            let operators = OPERATORS
+                .iter()
+                .copied()
+                .filter(|operator| {
+                    *operator != Operator::InNetwork || current.variable == Variable::RemoteIp
+                })
+                .collect::<Vec<_>>();
+
+            view! {
+                <select
+                    class=SELECT_CLASS
+                    on:change=move |ev| {
+                        let variable = Variable::parse_id(&event_target_value(&ev));
+                        update_condition(
+                            &|condition| {
+                                condition.variable = variable;
+                                if variable != Variable::RemoteIp
+                                    && condition.operator == Operator::InNetwork
+                                {
+                                    condition.operator = Operator::Is;
+                                }
+                            },
+                        );
+                    }
+                >
+
+                    {VARIABLES
+                        .iter()
+                        .map(|variable| {
+                            view! {
+                                <option
+                                    value=variable.id()
+                                    selected=*variable == current.variable
+                                >
+                                    {variable.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <select
+                    class=SELECT_CLASS
+                    on:change=move |ev| {
+                        let operator = Operator::parse_id(&event_target_value(&ev));
+                        update_condition(&|condition| condition.operator = operator);
+                    }
+                >
+
+                    {operators
+                        .into_iter()
+                        .map(|operator| {
+                            view! {
+                                <option
+                                    value=operator.id()
+                                    selected=operator == current.operator
+                                >
+                                    {operator.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <input
+                    type="text"
+                    class=move || {
+                        if error.get().is_some_and(|(is_if, _)| is_if) {
+                            INPUT_ERR_CLASS
+                        } else {
+                            INPUT_CLASS
+                        }
+                    }
+
+                    placeholder=current.placeholder()
+                    prop:value=current.value.clone()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev).trim().to_string();
+                        update_condition(&|condition| condition.value = value.clone());
+                    }
+                />
+            }
+            .into_view()
+        }
+        None => view! {
+            <input
+                type="text"
+                placeholder="if"
+                class=move || {
+                    if error.get().is_some_and(|(is_if, _)| is_if) {
+                        INPUT_ERR_CLASS
+                    } else {
+                        INPUT_CLASS
+                    }
+                }
+
+                prop:value=move || if_then.get().if_
+                on:change=move |ev| {
+                    element
+                        .data
+                        .update(|data| {
+                            data.expr_update_if(element.id, idx, event_target_value(&ev));
+                        });
+                }
+            />
+        }
+        .into_view(),
+    };
+
+    view! {
+        <div>
+            <div class="grid gap-2 sm:flex sm:items-center">
+                {condition_view}
+                <IconArrowRightCircle attr:class="hidden sm:block flex-shrink-0 size-4 text-gray-400"/>
+                <input
+                    type="text"
+                    placeholder=move || placeholder.get()
+                    class=move || {
+                        if error.get().is_some_and(|(is_if, _)| !is_if) {
+                            INPUT_ERR_CLASS
+                        } else {
+                            INPUT_CLASS
+                        }
+                    }
+
+                    prop:value=move || if_then.get().then_
+                    on:change=move |ev| {
+                        element
+                            .data
+                            .update(|data| {
+                                data.expr_update_then(element.id, idx, event_target_value(&ev));
+                            });
+                    }
+                />
+
+                <button
+                    type="button"
+                    class="p-2.5 rounded-md dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    on:click=move |_| {
+                        element
+                            .data
+                            .update(|data| {
+                                data.expr_delete_if_then(element.id, idx);
+                            });
+                    }
+                >
+
+                    <IconXMark/>
+                </button>
+            </div>
+            {move || {
+                error
+                    .get()
+                    .map(|(_, error)| {
+                        view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                    })
+            }}
+
+        </div>
+    }
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Condition {
+            variable: Variable::RemoteIp,
+            operator: Operator::InNetwork,
+            value: String::new(),
+        }
+    }
+}
+
+impl Condition {
+    fn compile(&self) -> Result<String, &'static str> {
+        if self.value.is_empty() {
+            return Err("A value is required");
+        } else if self.value.contains('\'') {
+            return Err("Values cannot contain quotes");
+        }
+
+        let variable = self.variable.expr();
+        let value = &self.value;
+        Ok(match self.operator {
+            Operator::InNetwork => match network_prefix(value)? {
+                NetworkPrefix::Address(addr) => format!("{variable} == '{addr}'"),
+                NetworkPrefix::Prefix(prefix) => format!("starts_with({variable}, '{prefix}')"),
+            },
+            Operator::Is => format!("{variable} == '{value}'"),
+            Operator::IsNot => format!("{variable} != '{value}'"),
+            Operator::StartsWith => format!("starts_with({variable}, '{value}')"),
+            Operator::EndsWith => format!("ends_with({variable}, '{value}')"),
+        })
+    }
+
+    fn parse(expr: &str) -> Option<Self> {
+        let expr = expr.trim();
+        for (function, operator) in [
+            ("starts_with(", Operator::StartsWith),
+            ("ends_with(", Operator::EndsWith),
+        ] {
+            if let Some((variable, value)) = expr
+                .strip_prefix(function)
+                .and_then(|args| args.strip_suffix("')"))
+                .and_then(|args| args.split_once(", '"))
+            {
+                let variable = Variable::parse_expr(variable)?;
+                let value = value.to_string();
+                if value.contains('\'') {
+                    return None;
+                }
+                return Some(match (operator, variable, prefix_network(&value)) {
+                    (Operator::StartsWith, Variable::RemoteIp, Some(network)) => Condition {
+                        variable,
+                        operator: Operator::InNetwork,
+                        value: network,
+                    },
+                    _ => Condition {
+                        variable,
+                        operator,
+                        value,
+                    },
+                });
+            }
+        }
+        for (separator, operator) in [(" == '", Operator::Is), (" != '", Operator::IsNot)] {
+            if let Some((variable, value)) = expr
+                .split_once(separator)
+                .and_then(|(variable, value)| Some((variable, value.strip_suffix('\'')?)))
+            {
+                let variable = Variable::parse_expr(variable)?;
+                if value.contains('\'') {
+                    return None;
+                }
+                return Some(Condition {
+                    variable,
+                    operator,
+                    value: value.to_string(),
+                });
+            }
+        }
+        None
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match (self.variable, self.operator) {
+            (Variable::RemoteIp, Operator::InNetwork) => "192.168.0.0/16",
+            (Variable::RemoteIp, _) => "192.168.1.1",
+            (Variable::Listener, _) => "smtp",
+            (Variable::RemoteHost, _) => "mail.example.org",
+        }
+    }
+}
+
+enum NetworkPrefix {
+    Address(String),
+    Prefix(String),
+}
+
+// Expressions have no network matching function, IPv4 masks on octet
+// boundaries are translated to a prefix match on the address.
+fn network_prefix(value: &str) -> Result<NetworkPrefix, &'static str> {
+    let (addr, mask) = value.split_once('/').unwrap_or((value, "32"));
+    let octets = addr
+        .split('.')
+        .map(|octet| octet.parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|octets| octets.len() == 4)
+        .ok_or("Invalid IPv4 network")?;
+    match mask.parse::<u8>() {
+        Ok(32) => Ok(NetworkPrefix::Address(addr.to_string())),
+        Ok(mask @ (8 | 16 | 24)) => {
+            let num_octets = (mask / 8) as usize;
+            if octets[num_octets..].iter().any(|octet| *octet != 0) {
+                return Err("The host part of the network must be zero");
+            }
+            let mut prefix = octets[..num_octets]
+                .iter()
+                .map(|octet| octet.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+            prefix.push('.');
+            Ok(NetworkPrefix::Prefix(prefix))
+        }
+        _ => Err("Only /8, /16, /24 and /32 masks are supported"),
+    }
+}
+
+fn prefix_network(prefix: &str) -> Option<String> {
+    let octets = prefix
+        .strip_suffix('.')?
+        .split('.')
+        .map(|octet| octet.parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if (1..=3).contains(&octets.len()) {
+        let mask = octets.len() * 8;
+        let mut octets = octets
+            .into_iter()
+            .map(|octet| octet.to_string())
+            .collect::<Vec<_>>();
+        octets.resize(4, "0".to_string());
+        Some(format!("{}/{mask}", octets.join(".")))
+    } else {
+        None
+    }
+}
+
+impl Variable {
+    fn id(&self) -> &'static str {
+        match self {
+            Variable::RemoteIp => "remote-ip",
+            Variable::Listener => "listener",
+            Variable::RemoteHost => "remote-host",
+        }
+    }
+
+    fn parse_id(id: &str) -> Self {
+        match id {
+            "listener" => Variable::Listener,
+            "remote-host" => Variable::RemoteHost,
+            _ => Variable::RemoteIp,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Variable::RemoteIp => "Remote IP",
+            Variable::Listener => "Listener",
+            Variable::RemoteHost => "Remote host",
+        }
+    }
+
+    fn expr(&self) -> &'static str {
+        match self {
+            Variable::RemoteIp => "remote_ip",
+            Variable::Listener => "listener",
+            Variable::RemoteHost => "ip_reverse_name(remote_ip)",
+        }
+    }
+
+    fn parse_expr(expr: &str) -> Option<Self> {
+        VARIABLES
+            .iter()
+            .copied()
+            .find(|variable| variable.expr() == expr.trim())
+    }
+}
+
+impl Operator {
+    fn id(&self) -> &'static str {
+        match self {
+            Operator::InNetwork => "in-network",
+            Operator::Is => "is",
+            Operator::IsNot => "is-not",
+            Operator::StartsWith => "starts-with",
+            Operator::EndsWith => "ends-with",
+        }
+    }
+
+    fn parse_id(id: &str) -> Self {
+        OPERATORS
+            .iter()
+            .copied()
+            .find(|operator| operator.id() == id)
+            .unwrap_or(Operator::Is)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Operator::InNetwork => "is in network",
+            Operator::Is => "is",
+            Operator::IsNot => "is not",
+            Operator::StartsWith => "starts with",
+            Operator::EndsWith => "ends with",
+        }
+    }
+}
@@ -0,0 +1,237 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+
+use crate::{
+    components::{
+        icon::{IconPlus, IconXMark},
+        timeline::{Timeline, TimelineItem},
+        Color,
+    },
+    core::{
+        expr::ParseValue,
+        schedule::{format_duration, format_schedule, split_schedule, ScheduleKind},
+    },
+};
+
+use super::{expression::InputExpression, FormElement};
+
+#[component]
+pub fn InputSchedule(
+    element: FormElement,
+    kind: ScheduleKind,
+    expire: &'static str,
+    #[prop(optional, into)] disabled: MaybeSignal<bool>,
+) -> impl IntoView {
+    let items = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .expr_else(element.id)
+            .map(split_schedule)
+            .unwrap_or_default()
+    });
+    let expire = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .expr_else(expire)
+            .and_then(Duration::parse_value)
+    });
+    let schedule = create_memo(move |_| {
+        items
+            .get()
+            .iter()
+            .map(|item| Duration::parse_value(item))
+            .collect::<Option<Vec<_>>>()
+    });
+    let error = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .error_string(element.id)
+            .map(|s| s.to_string())
+            .or_else(|| {
+                schedule
+                    .get()
+                    .and_then(|schedule| kind.check(&schedule, expire.get()).err())
+            })
+    });
+
+    // Conditional schedules and lists using functions or variables can
+    // only be edited as expressions.
+    let raw_mode = {
+        let data = element.data.get_untracked();
+        data.expr_if_thens(element.id).next().is_some()
+            || data.expr_else(element.id).is_some_and(|value| {
+                split_schedule(value)
+                    .iter()
+                    .any(|item| Duration::parse_value(item).is_none())
+            })
+    };
+    let set_items = move |items: Vec<String>| {
+        element.data.update(|data| {
+            data.expr_update_else(element.id, format_schedule(&items));
+        });
+    };
+    let (item_label, add_label) = match kind {
+        ScheduleKind::Intervals => ("Retry after", "Add retry"),
+        ScheduleKind::Offsets => ("Notify after", "Add notification"),
+    };
+
+    if raw_mode {
+        return view! {
+            <InputExpression element/>
+            <p class="mt-2 text-xs text-gray-500 dark:text-gray-400">
+                This schedule uses conditions or expressions and can only be edited as an expression.
+            </p>
+        }
+        .into_view();
+    }
+
+    view! {
+        <div class="space-y-2">
+            <For
+                each=move || items.get().into_iter().enumerate()
+                key=|(idx, item)| format!("{idx}_{item}")
+                children=move |(idx, item)| {
+                    let is_valid = Duration::parse_value(&item).is_some();
+                    view! {
+                        <div class="flex items-center gap-x-2">
+                            <span class="w-24 flex-shrink-0 text-xs text-gray-500 dark:text-gray-400">
+                                {item_label}
+                            </span>
+                            <input
+                                type="text"
+                                class=if is_valid {
+                                    "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm font-mono rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                } else {
+                                    "py-2 px-3 block w-full border-red-500 shadow-sm text-sm font-mono rounded-lg focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                }
+
+                                autocapitalize="off"
+                                placeholder="5m"
+                                prop:value=item
+                                disabled=move || disabled.get()
+                                on:change=move |ev| {
+                                    let mut items = items.get_untracked();
+                                    if let Some(item) = items.get_mut(idx) {
+                                        *item = event_target_value(&ev).trim().to_string();
+                                    }
+                                    set_items(items);
+                                }
+                            />
+
+                            <button
+                                type="button"
+                                class="p-2 inline-flex items-center text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                                disabled=move || {
+                                    disabled.get()
+                                        || (kind == ScheduleKind::Intervals && items.get().len() == 1)
+                                }
+                                on:click=move |_| {
+                                    let mut items = items.get_untracked();
+                                    if idx < items.len() {
+                                        items.remove(idx);
+                                    }
+                                    set_items(items);
+                                }
+                            >
+
+                                <IconXMark/>
+                            </button>
+                        </div>
+                    }
+                }
+            />
+
+        </div>
+
+        <p class="mt-3 text-end">
+            <button
+                type="button"
+                class="py-1.5 px-2 inline-flex items-center gap-x-1 text-xs font-medium rounded-full border border-dashed border-gray-200 bg-white text-gray-800 hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300 dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                disabled=move || disabled.get()
+                on:click=move |_| {
+                    let mut items = items.get_untracked();
+                    let next = items.last().cloned().unwrap_or_else(|| "5m".to_string());
+                    items.push(next);
+                    set_items(items);
+                }
+            >
+
+                <IconPlus attr:class="flex-shrink-0 size-3.5"/>
+                {add_label}
+            </button>
+        </p>
+
+        {move || {
+            error
+                .get()
+                .map(|error| {
+                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                })
+        }}
+
+        <div class="mt-4 p-4 rounded-lg border border-gray-200 dark:border-gray-700">
+            {move || {
+                let Some(schedule) = schedule.get() else {
+                    return view! {
+                        <p class="text-xs text-gray-500 dark:text-gray-400">
+                            Fix the invalid durations to preview the schedule.
+                        </p>
+                    }
+                        .into_view();
+                };
+                let expire = expire.get();
+                let (events, remaining) = kind.events(&schedule, expire);
+                let mut items = events
+                    .into_iter()
+                    .map(|event| {
+                        let time = if event.at.is_zero() {
+                            "Immediately".to_string()
+                        } else {
+                            format!("After {}", format_duration(event.at))
+                        };
+                        view! { <TimelineItem color=Color::Blue title=event.title time/> }
+                    })
+                    .collect::<Vec<_>>();
+                if remaining > 0 {
+                    items
+                        .push(
+                            view! {
+                                <TimelineItem
+                                    color=Color::Gray
+                                    title=format!("{remaining} more attempts")
+                                    time="Until expiry"
+                                />
+                            },
+                        );
+                }
+                if let Some(expire) = expire {
+                    items
+                        .push(
+                            view! {
+                                <TimelineItem
+                                    color=Color::Red
+                                    title="Message expires"
+                                    time=format!("After {}", format_duration(expire))
+                                    subtitle="Undelivered messages are returned to the sender"
+                                        .to_string()
+                                />
+                            },
+                        );
+                }
+                view! { <Timeline>{items}</Timeline> }.into_view()
+            }}
+
+        </div>
+    }
+    .into_view()
+}
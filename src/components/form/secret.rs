@@ -0,0 +1,475 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        icon::{IconCheckCircle, IconExclamationCircle, IconEye, IconLockClosed},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::is_secret_reference,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        Permission,
+    },
+};
+
+use super::{button::Button, input::InputPassword, FormElement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SecretMode {
+    Value,
+    Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReferenceKind {
+    Env,
+    File,
+    Vault,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RevealResponse {
+    value: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ResolveResponse {
+    resolved: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[component]
+pub fn InputSecret(
+    element: FormElement,
+    #[prop(optional, into)] disabled: MaybeSignal<bool>,
+) -> impl IntoView {
+    let is_retained = create_memo(move |_| element.data.get().secret_is_retained(element.id));
+    let is_set = create_memo(move |_| element.data.get().secret_is_set(element.id));
+    let mode = create_rw_signal(
+        if element
+            .data
+            .get_untracked()
+            .value_as_str(element.id)
+            .is_some_and(is_secret_reference)
+        {
+            SecretMode::Reference
+        } else {
+            SecretMode::Value
+        },
+    );
+    let replacing = create_rw_signal(false);
+    let revealed = create_rw_signal(None::<String>);
+    let set_mode = move |new_mode: SecretMode| {
+        if mode.get() != new_mode {
+            mode.set(new_mode);
+            element.data.update(|data| data.remove(element.id));
+        }
+    };
+
+    // Stored secrets can only be read back by the server for tokens with the
+    // "settings-secrets-read" permission, every reveal is audited server-side.
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let setting_key = create_memo(move |_| {
+        element.data.with(|data| {
+            data.schema
+                .setting_key(data.value_as_str("_id").unwrap_or_default(), element.id)
+        })
+    });
+    let can_reveal = create_memo(move |_| {
+        setting_key.get().is_some()
+            && auth.with(|auth| {
+                auth.permissions()
+                    .has_access(Permission::SettingsSecretsRead)
+            })
+    });
+    let reveal_action = create_action(move |key: &String| {
+        let key = key.clone();
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::get("/api/settings/secret")
+                .with_authorization(&auth)
+                .with_parameter("key", key)
+                .send::<RevealResponse>()
+                .await
+            {
+                Ok(response) => {
+                    revealed.set(Some(response.value));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::Forbidden) => {
+                    alert.set(Alert::error(
+                        "You do not have permission to reveal stored secrets.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        {move || {
+            if is_retained.get() && !replacing.get() {
+                view! {
+                    <div class="flex items-center gap-x-3">
+                        <span class="inline-flex items-center gap-x-1.5 py-1.5 px-3 rounded-lg text-sm font-medium bg-gray-100 text-gray-800 dark:bg-white/10 dark:text-white">
+                            <IconLockClosed size=16 attr:class="flex-shrink-0 size-4"/>
+                            Secret is set
+                        </span>
+                        <Show when=move || can_reveal.get() && revealed.with(|value| value.is_none())>
+                            <button
+                                type="button"
+                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                title="Revealing a secret is recorded in the audit log"
+                                disabled=move || reveal_action.pending().get()
+                                on:click=move |_| {
+                                    if let Some(key) = setting_key.get_untracked() {
+                                        reveal_action.dispatch(key);
+                                    }
+                                }
+                            >
+
+                                <IconEye size=14 attr:class="flex-shrink-0 size-3.5"/>
+                                Reveal
+                            </button>
+                        </Show>
+                        <button
+                            type="button"
+                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                            disabled=move || disabled.get()
+                            on:click=move |_| {
+                                replacing.set(true);
+                                revealed.set(None);
+                            }
+                        >
+
+                            Replace
+                        </button>
+                        <button
+                            type="button"
+                            class="text-sm text-red-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                            disabled=move || disabled.get()
+                            on:click=move |_| {
+                                element.data.update(|data| data.secret_clear(element.id));
+                            }
+                        >
+
+                            Clear
+                        </button>
+                    </div>
+                    {move || {
+                        revealed
+                            .get()
+                            .map(|value| {
+                                view! {
+                                    <div class="mt-2 flex items-start gap-x-3">
+                                        <code class="py-1.5 px-3 block w-full rounded-lg text-sm font-mono break-all bg-gray-100 text-gray-800 dark:bg-white/10 dark:text-gray-200">
+                                            {value}
+                                        </code>
+                                        <button
+                                            type="button"
+                                            class="py-1.5 text-sm text-blue-600 decoration-2 hover:underline font-medium"
+                                            on:click=move |_| revealed.set(None)
+                                        >
+
+                                            Hide
+                                        </button>
+                                    </div>
+                                }
+                            })
+                    }}
+                }
+                    .into_view()
+            } else {
+                view! {
+                    <div class="space-y-2">
+                        {move || match mode.get() {
+                            SecretMode::Value => {
+                                view! { <InputPassword element=element disabled=disabled/> }
+                            }
+                            SecretMode::Reference => {
+                                view! { <InputSecretReference element=element disabled=disabled/> }
+                            }
+                        }}
+                        <div class="flex gap-x-3 text-xs">
+                            <button
+                                type="button"
+                                class=move || {
+                                    if mode.get() == SecretMode::Value {
+                                        "font-semibold text-gray-800 dark:text-gray-200"
+                                    } else {
+                                        "text-blue-600 decoration-2 hover:underline"
+                                    }
+                                }
+
+                                on:click=move |_| set_mode(SecretMode::Value)
+                            >
+                                Value
+                            </button>
+                            <button
+                                type="button"
+                                class=move || {
+                                    if mode.get() == SecretMode::Reference {
+                                        "font-semibold text-gray-800 dark:text-gray-200"
+                                    } else {
+                                        "text-blue-600 decoration-2 hover:underline"
+                                    }
+                                }
+
+                                on:click=move |_| set_mode(SecretMode::Reference)
+                            >
+                                Server-side reference
+                            </button>
+                            <button
+                                type="button"
+                                class="text-blue-600 decoration-2 hover:underline"
+                                class:hidden=move || !is_set.get()
+                                on:click=move |_| {
+                                    replacing.set(false);
+                                    element.data.update(|data| data.remove(element.id));
+                                }
+                            >
+
+                                Keep current secret
+                            </button>
+                        </div>
+                    </div>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn InputSecretReference(element: FormElement, disabled: MaybeSignal<bool>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let reference = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .value_as_str(element.id)
+            .and_then(ReferenceKind::parse)
+    });
+    let kind = create_rw_signal(
+        reference
+            .get_untracked()
+            .map_or(ReferenceKind::Env, |(kind, _)| kind),
+    );
+    let target = create_memo(move |_| {
+        reference
+            .get()
+            .map(|(_, target)| target)
+            .unwrap_or_default()
+    });
+    let error = create_memo(move |_| {
+        element
+            .data
+            .get()
+            .error_string(element.id)
+            .map(|s| s.to_string())
+    });
+    let status = create_rw_signal(None::<Result<(), String>>);
+
+    let update_reference = move |kind: ReferenceKind, target: String| {
+        let target = target.trim().to_string();
+        status.set(None);
+        element.data.update(|data| {
+            if !target.is_empty() {
+                data.update(element.id, kind.build(&target));
+            } else {
+                data.remove(element.id);
+            }
+        });
+    };
+
+    let verify_action = create_action(move |reference: &String| {
+        let reference = reference.clone();
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::get("/api/settings/resolve")
+                .with_authorization(&auth)
+                .with_parameter("reference", reference)
+                .send::<ResolveResponse>()
+                .await
+            {
+                Ok(response) if response.resolved => {
+                    status.set(Some(Ok(())));
+                }
+                Ok(response) => {
+                    status.set(Some(Err(response.reason.unwrap_or_else(|| {
+                        "The server could not resolve the reference".to_string()
+                    }))));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    status.set(Some(Err(
+                        "This server does not support resolving references".to_string(),
+                    )));
+                }
+                Err(err) => {
+                    status.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <div class="flex gap-x-2">
+            <select
+                class="py-2 px-3 pe-9 block w-48 border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                disabled=move || disabled.get()
+                on:change=move |ev| {
+                    let new_kind = ReferenceKind::from_id(&event_target_value(&ev));
+                    kind.set(new_kind);
+                    update_reference(new_kind, target.get_untracked());
+                }
+            >
+
+                {[ReferenceKind::Env, ReferenceKind::File, ReferenceKind::Vault]
+                    .into_iter()
+                    .map(|item| {
+                        view! {
+                            <option value=item.id() selected=move || kind.get() == item>
+                                {item.label()}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+
+            </select>
+            <input
+                type="text"
+                class=move || {
+                    if error.get().is_none() {
+                        "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    } else {
+                        "py-2 px-3 block w-full border-red-500 shadow-sm text-sm rounded-lg focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    }
+                }
+
+                autocapitalize="off"
+                placeholder=move || kind.get().placeholder()
+                prop:value=move || target.get()
+                disabled=move || disabled.get()
+                on:change=move |ev| {
+                    update_reference(kind.get_untracked(), event_target_value(&ev));
+                }
+            />
+
+            <Button
+                text="Verify"
+                color=Color::Gray
+                disabled=Signal::derive(move || {
+                    disabled.get() || target.get().is_empty() || verify_action.pending().get()
+                })
+
+                on_click=move |_| {
+                    verify_action.dispatch(kind.get_untracked().build(&target.get_untracked()));
+                }
+            />
+
+        </div>
+        {move || {
+            error
+                .get()
+                .map(|error| {
+                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                })
+        }}
+
+        {move || match status.get() {
+            Some(Ok(())) => {
+                Some(
+                    view! {
+                        <p class="inline-flex items-center gap-x-1 text-xs text-teal-600 mt-2">
+                            <IconCheckCircle size=14 attr:class="flex-shrink-0 size-3.5"/>
+                            The server resolved this reference
+                        </p>
+                    }
+                        .into_view(),
+                )
+            }
+            Some(Err(reason)) => {
+                Some(
+                    view! {
+                        <p class="inline-flex items-center gap-x-1 text-xs text-red-600 mt-2">
+                            <IconExclamationCircle attr:class="flex-shrink-0 size-3.5"/>
+                            {reason}
+                        </p>
+                    }
+                        .into_view(),
+                )
+            }
+            None => None,
+        }}
+    }
+}
+
+impl ReferenceKind {
+    fn parse(value: &str) -> Option<(Self, String)> {
+        let (kind, target) = value
+            .strip_prefix("%{")
+            .and_then(|v| v.strip_suffix("}%"))
+            .and_then(|v| v.split_once(':'))?;
+
+        Some((ReferenceKind::from_id(kind), target.to_string()))
+    }
+
+    fn build(&self, target: &str) -> String {
+        format!("%{{{}:{}}}%", self.id(), target)
+    }
+
+    fn from_id(id: &str) -> Self {
+        match id {
+            "file" => ReferenceKind::File,
+            "vault" => ReferenceKind::Vault,
+            _ => ReferenceKind::Env,
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        match self {
+            ReferenceKind::Env => "env",
+            ReferenceKind::File => "file",
+            ReferenceKind::Vault => "vault",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ReferenceKind::Env => "Environment variable",
+            ReferenceKind::File => "File",
+            ReferenceKind::Vault => "Vault",
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            ReferenceKind::Env => "SECRET_NAME",
+            ReferenceKind::File => "/run/secrets/secret-name",
+            ReferenceKind::Vault => "secret/data/stalwart#password",
+        }
+    }
+}
@@ -4,11 +4,6 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{
-    fmt::{Display, Formatter},
-    str::FromStr,
-};
-
 use ahash::AHashSet;
 use leptos::*;
 
@@ -183,16 +178,19 @@ pub fn CheckboxGroup(
 }
 
 #[component]
-pub fn SelectCron(
+pub fn GroupedCheckboxGroup(
     element: FormElement,
+    groups: &'static [(&'static str, &'static [&'static str])],
     #[prop(optional, into)] disabled: MaybeSignal<bool>,
 ) -> impl IntoView {
-    let value = create_memo(move |_| {
+    let options = create_memo(move |_| element.data.get().select_sources(element.id));
+    let values = create_memo(move |_| {
         element
             .data
             .get()
-            .value::<SimpleCron>(element.id)
-            .unwrap_or_default()
+            .array_value(element.id)
+            .map(|s| s.to_string())
+            .collect::<AHashSet<_>>()
     });
     let error = create_memo(move |_| {
         element
@@ -201,161 +199,159 @@ pub fn SelectCron(
             .error_string(element.id)
             .map(|s| s.to_string())
     });
+    let filter = create_rw_signal(String::new());
+
+    // Options that do not match any of the group prefixes are listed last.
+    let grouped_options = create_memo(move |_| {
+        let mut grouped = groups
+            .iter()
+            .map(|(label, _)| (*label, Vec::new()))
+            .chain([("Other", Vec::new())])
+            .collect::<Vec<_>>();
+        for (id, label) in options.get() {
+            let idx = groups
+                .iter()
+                .position(|(_, prefixes)| prefixes.iter().any(|prefix| id.starts_with(prefix)))
+                .unwrap_or(groups.len());
+            grouped[idx].1.push((id, label));
+        }
+        grouped.retain(|(_, options)| !options.is_empty());
+        grouped
+    });
 
-    view! {
-        <div class="space-y-3">
-            <div class="relative">
-                <div class="sm:flex rounded-lg shadow-sm">
-                    <select
-                        class=move || {
-                            if error.get().is_none() {
-                                "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            } else {
-                                "py-2 px-3 pe-9 block w-full border-red-500 rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            }
-                        }
-
-                        on:change=move |ev| {
-                            element
-                                .data
-                                .update(|data| {
-                                    data.update(
-                                        element.id,
-                                        data
-                                            .value::<SimpleCron>(element.id)
-                                            .unwrap_or_default()
-                                            .day(event_target_value(&ev))
-                                            .to_string(),
-                                    );
-                                });
-                        }
-
-                        disabled=move || disabled.get()
-                    >
-
-                        <For
-                            each=move || 0..=7
-                            key=move |num| *num
-                            children=move |num| {
-                                let id = if num == 0 { "*".to_string() } else { num.to_string() };
-                                let id_ = id.clone();
-                                view! {
-                                    <option selected=move || value.get().day == id value=id_>
-                                        {match num {
-                                            0 => "Every day",
-                                            1 => "On Monday",
-                                            2 => "On Tuesday",
-                                            3 => "On Wednesday",
-                                            4 => "On Thursday",
-                                            5 => "On Friday",
-                                            6 => "On Saturday",
-                                            7 => "On Sunday",
-                                            _ => unreachable!(),
-                                        }}
-
-                                    </option>
-                                }
-                            }
-                        />
-
-                    </select>
-                    <select
-                        class=move || {
-                            if error.get().is_none() {
-                                "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            } else {
-                                "py-2 px-3 pe-9 block w-full border-red-500 rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            }
-                        }
-
-                        on:change=move |ev| {
-                            element
-                                .data
-                                .update(|data| {
-                                    data.update(
-                                        element.id,
-                                        data
-                                            .value::<SimpleCron>(element.id)
-                                            .unwrap_or_default()
-                                            .hour(event_target_value(&ev))
-                                            .to_string(),
-                                    );
-                                });
-                        }
-
-                        disabled=move || disabled.get()
-                    >
+    let set_values = move |f: &dyn Fn(&mut AHashSet<String>)| {
+        let mut values = values.get();
+        f(&mut values);
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        values.sort();
+        element.data.update(|data| {
+            data.update(element.id, values);
+        });
+    };
 
-                        <For
-                            each=move || 0..=24
-                            key=move |num| *num
-                            children=move |num| {
-                                let id = if num == 0 {
-                                    "*".to_string()
-                                } else {
-                                    (num - 1).to_string()
-                                };
-                                let id_ = id.clone();
-                                view! {
-                                    <option selected=move || value.get().hour == id value=id_>
-                                        {if num == 0 {
-                                            "Every hour".to_string()
-                                        } else {
-                                            format!("at hour {}", num - 1)
-                                        }}
-
-                                    </option>
-                                }
-                            }
-                        />
-
-                    </select>
-                    <select
-                        class=move || {
-                            if error.get().is_none() {
-                                "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            } else {
-                                "py-2 px-3 pe-9 block w-full border-red-500 rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                            }
-                        }
+    view! {
+        <input
+            type="text"
+            class="py-2 px-3 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+            placeholder="Filter"
+            prop:value=move || filter.get()
+            on:input=move |ev| filter.set(event_target_value(&ev))
+        />
+        <div class="mt-2 space-y-2">
+            <For
+                each=move || grouped_options.get()
+                key=|(label, options)| format!("{label}-{}", options.len())
+                children=move |(label, options)| {
+                    let group_ids = options.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+                    let group_ids_ = group_ids.clone();
+                    let total = group_ids.len();
+                    let num_selected = create_memo(move |_| {
+                        let values = values.get();
+                        group_ids_.iter().filter(|id| values.contains(*id)).count()
+                    });
+                    let is_open = create_rw_signal(false);
+                    let options = store_value(options);
+                    let visible_options = create_memo(move |_| {
+                        let filter = filter.get().to_lowercase();
+                        options
+                            .get_value()
+                            .into_iter()
+                            .filter(|(id, label)| {
+                                filter.is_empty()
+                                    || id.contains(&filter)
+                                    || label.to_lowercase().contains(&filter)
+                            })
+                            .collect::<Vec<_>>()
+                    });
 
-                        on:change=move |ev| {
-                            element
-                                .data
-                                .update(|data| {
-                                    data.update(
-                                        element.id,
-                                        data
-                                            .value::<SimpleCron>(element.id)
-                                            .unwrap_or_default()
-                                            .minute(event_target_value(&ev))
-                                            .to_string(),
-                                    );
-                                });
-                        }
+                    view! {
+                        <div
+                            class="border border-gray-200 rounded-lg dark:border-gray-700"
+                            class:hidden=move || visible_options.get().is_empty()
+                        >
+                            <div class="flex items-center gap-x-3 p-3">
+                                <input
+                                    type="checkbox"
+                                    class="shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                    prop:checked=move || num_selected.get() == total
+                                    prop:indeterminate=move || {
+                                        let num_selected = num_selected.get();
+                                        num_selected > 0 && num_selected < total
+                                    }
+
+                                    disabled=move || disabled.get()
+                                    on:input=move |_| {
+                                        let select_all = num_selected.get() < total;
+                                        set_values(
+                                            &|values| {
+                                                for id in &group_ids {
+                                                    if select_all {
+                                                        values.insert(id.clone());
+                                                    } else {
+                                                        values.remove(id);
+                                                    }
+                                                }
+                                            },
+                                        );
+                                    }
+                                />
+
+                                <button
+                                    type="button"
+                                    class="flex-1 text-start text-sm font-medium text-gray-800 dark:text-gray-200"
+                                    aria-expanded=move || {
+                                        (is_open.get() || !filter.get().is_empty()).to_string()
+                                    }
+
+                                    on:click=move |_| is_open.update(|open| *open = !*open)
+                                >
+                                    {label}
+                                </button>
+                                <span class="text-xs text-gray-500 dark:text-gray-400">
+                                    {move || format!("{} of {total}", num_selected.get())}
+                                </span>
+                            </div>
+                            <div
+                                class="grid sm:grid-cols-2 gap-2 px-3 pb-3"
+                                class:hidden=move || !is_open.get() && filter.get().is_empty()
+                            >
+                                <For
+                                    each=move || visible_options.get()
+                                    key=|(id, _)| id.clone()
+                                    children=move |(id, label)| {
+                                        let id_ = id.clone();
+                                        view! {
+                                            <label class="flex text-sm">
+                                                <input
+                                                    type="checkbox"
+                                                    class="shrink-0 mt-0.5 border-gray-200 rounded text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                                    prop:checked=move || values.get().contains(&id_)
+                                                    disabled=move || disabled.get()
+                                                    on:input=move |_| {
+                                                        set_values(
+                                                            &|values| {
+                                                                if !values.remove(&id) {
+                                                                    values.insert(id.clone());
+                                                                }
+                                                            },
+                                                        );
+                                                    }
+                                                />
+
+                                                <span class="text-sm text-gray-500 ms-3 dark:text-gray-400">
+                                                    {label}
+                                                </span>
+                                            </label>
+                                        }
+                                    }
+                                />
 
-                        disabled=move || disabled.get()
-                    >
-
-                        <For
-                            each=move || 0..=59
-                            key=move |num| *num
-                            children=move |num| {
-                                view! {
-                                    <option
-                                        selected=move || value.get().minute == num.to_string()
-                                        value=num
-                                    >
-                                        {format!("at minute {num}")}
-
-                                    </option>
-                                }
-                            }
-                        />
+                            </div>
+                        </div>
+                    }
+                }
+            />
 
-                    </select>
-                </div>
-            </div>
         </div>
 
         {move || {
@@ -401,6 +397,10 @@ impl FormData {
                     source: Source::DynamicSelf { field, filter },
                     ..
                 } => self.build_sources(&self.schema, field, filter),
+                Type::Select {
+                    source: Source::Principals(_),
+                    ..
+                } => Vec::new(),
                 _ => {
                     log::warn!("Invalid schema type for select");
                     Vec::new()
@@ -442,56 +442,3 @@ impl FormData {
             .unwrap_or_default()
     }
 }
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct SimpleCron {
-    hour: String,
-    minute: String,
-    day: String,
-}
-
-impl FromStr for SimpleCron {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split_whitespace();
-        Ok(SimpleCron {
-            minute: parts.next().unwrap_or("0").to_string(),
-            hour: parts.next().unwrap_or("3").to_string(),
-            day: parts.next().unwrap_or("*").to_string(),
-        })
-    }
-}
-
-impl SimpleCron {
-    fn hour(mut self, hour: String) -> Self {
-        self.hour = hour;
-        self
-    }
-
-    fn minute(mut self, minute: String) -> Self {
-        self.minute = minute;
-        self
-    }
-
-    fn day(mut self, day: String) -> Self {
-        self.day = day;
-        self
-    }
-}
-
-impl Default for SimpleCron {
-    fn default() -> Self {
-        SimpleCron {
-            hour: "3".to_string(),
-            minute: "0".to_string(),
-            day: "*".to_string(),
-        }
-    }
-}
-
-impl Display for SimpleCron {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {} {}", self.minute, self.hour, self.day)
-    }
-}
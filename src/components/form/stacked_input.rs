@@ -42,7 +42,7 @@ pub fn StackedInput(
     });
     let error = create_memo(move |_| {
         element.data.get().error(element.id).and_then(|e| {
-            if e.id == FormErrorType::None {
+            if matches!(e.id, FormErrorType::None | FormErrorType::Section) {
                 Some(e.error.clone())
             } else {
                 None
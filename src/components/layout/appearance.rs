@@ -0,0 +1,327 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::{html::Div, *};
+use leptos_meta::Html;
+use leptos_use::on_click_outside;
+use serde::{Deserialize, Serialize};
+
+use crate::{components::icon::IconEye, core::oauth::use_authorization};
+
+const APPEARANCE_STORAGE_KEY: &str = "webadmin_appearance";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Appearance {
+    pub theme: Theme,
+    pub font_size: FontSize,
+    #[serde(default)]
+    pub density: Density,
+    #[serde(default)]
+    pub size_units: SizeUnits,
+    #[serde(default)]
+    pub time_zone: TimeZone,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FontSize {
+    #[default]
+    Normal,
+    Large,
+    ExtraLarge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Density {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SizeUnits {
+    #[default]
+    Decimal,
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeZone {
+    #[default]
+    Local,
+    Utc,
+}
+
+pub fn init_appearance() {
+    let auth = use_authorization();
+    let appearance = create_rw_signal(Appearance::default());
+
+    // Preferences are stored per user, reload them whenever a different user logs in.
+    create_effect(move |_| {
+        let username = auth.with(|auth| auth.username.to_string());
+        appearance.set(Appearance::load(&username));
+    });
+
+    provide_context(appearance);
+}
+
+pub fn use_appearance() -> RwSignal<Appearance> {
+    expect_context::<RwSignal<Appearance>>()
+}
+
+#[component]
+pub fn ApplyAppearance() -> impl IntoView {
+    let appearance = use_appearance();
+
+    view! {
+        <Html
+            class=move || {
+                let appearance = appearance.get();
+                format!("{} {}", appearance.theme.class(), appearance.density.class())
+                    .trim()
+                    .to_string()
+            }
+            attr:style=move || format!("font-size: {}", appearance.get().font_size.size())
+        />
+    }
+}
+
+#[component]
+pub fn AppearanceMenu() -> impl IntoView {
+    let appearance = use_appearance();
+    let auth = use_authorization();
+    let is_open = create_rw_signal(false);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    let update = move |f: &dyn Fn(&mut Appearance)| {
+        appearance.update(|appearance| {
+            f(appearance);
+            appearance.save(&auth.get_untracked().username);
+        });
+    };
+
+    view! {
+        <div class="relative inline-flex" node_ref=target>
+            <button
+                type="button"
+                class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                title="Appearance"
+                aria-haspopup="true"
+                aria-expanded=move || is_open.get().to_string()
+                on:click=move |_| is_open.update(|open| *open = !*open)
+            >
+
+                <IconEye/>
+            </button>
+            <div
+                class="absolute end-0 top-full mt-2 z-10 min-w-48 bg-white shadow-md rounded-lg p-2 dark:bg-gray-800 dark:border dark:border-gray-700"
+                class:hidden=move || !is_open.get()
+            >
+
+                <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    Theme
+                </span>
+                {[Theme::Light, Theme::Dark, Theme::HighContrast]
+                    .into_iter()
+                    .map(|theme| {
+                        view! {
+                            <AppearanceOption
+                                label=theme.label()
+                                is_selected=Signal::derive(move || appearance.get().theme == theme)
+                                on_select=move || update(&|appearance| appearance.theme = theme)
+                            />
+                        }
+                    })
+                    .collect_view()}
+                <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    Text size
+                </span>
+                {[FontSize::Normal, FontSize::Large, FontSize::ExtraLarge]
+                    .into_iter()
+                    .map(|font_size| {
+                        view! {
+                            <AppearanceOption
+                                label=font_size.label()
+                                is_selected=Signal::derive(move || {
+                                    appearance.get().font_size == font_size
+                                })
+
+                                on_select=move || update(&|appearance| appearance.font_size = font_size)
+                            />
+                        }
+                    })
+                    .collect_view()}
+                <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    Density
+                </span>
+                {[Density::Comfortable, Density::Compact]
+                    .into_iter()
+                    .map(|density| {
+                        view! {
+                            <AppearanceOption
+                                label=density.label()
+                                is_selected=Signal::derive(move || appearance.get().density == density)
+                                on_select=move || update(&|appearance| appearance.density = density)
+                            />
+                        }
+                    })
+                    .collect_view()}
+                <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    Size units
+                </span>
+                {[SizeUnits::Decimal, SizeUnits::Binary]
+                    .into_iter()
+                    .map(|size_units| {
+                        view! {
+                            <AppearanceOption
+                                label=size_units.label()
+                                is_selected=Signal::derive(move || {
+                                    appearance.get().size_units == size_units
+                                })
+
+                                on_select=move || {
+                                    update(&|appearance| appearance.size_units = size_units)
+                                }
+                            />
+                        }
+                    })
+                    .collect_view()}
+                <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    Time zone
+                </span>
+                {[TimeZone::Local, TimeZone::Utc]
+                    .into_iter()
+                    .map(|time_zone| {
+                        view! {
+                            <AppearanceOption
+                                label=time_zone.label()
+                                is_selected=Signal::derive(move || {
+                                    appearance.get().time_zone == time_zone
+                                })
+
+                                on_select=move || update(&|appearance| appearance.time_zone = time_zone)
+                            />
+                        }
+                    })
+                    .collect_view()}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn AppearanceOption(
+    label: &'static str,
+    is_selected: Signal<bool>,
+    on_select: impl Fn() + 'static,
+) -> impl IntoView {
+    view! {
+        <button
+            type="button"
+            class="w-full flex items-center gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-gray-400 dark:hover:bg-gray-700 dark:hover:text-gray-300"
+            class:font-semibold=move || is_selected.get()
+            aria-pressed=move || is_selected.get().to_string()
+            on:click=move |_| on_select()
+        >
+            {label}
+        </button>
+    }
+}
+
+impl Appearance {
+    fn load(username: &str) -> Self {
+        LocalStorage::get::<Appearance>(storage_key(username)).unwrap_or_default()
+    }
+
+    fn save(&self, username: &str) {
+        if let Err(err) = LocalStorage::set(storage_key(username), self) {
+            log::warn!("Failed to store appearance preferences: {err}");
+        }
+    }
+}
+
+impl Theme {
+    fn class(&self) -> &'static str {
+        match self {
+            Theme::Light => "",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "dark high-contrast",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+        }
+    }
+}
+
+impl FontSize {
+    fn size(&self) -> &'static str {
+        match self {
+            FontSize::Normal => "100%",
+            FontSize::Large => "112.5%",
+            FontSize::ExtraLarge => "125%",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            FontSize::Normal => "Normal",
+            FontSize::Large => "Large",
+            FontSize::ExtraLarge => "Extra large",
+        }
+    }
+}
+
+impl Density {
+    fn class(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "",
+            Density::Compact => "compact",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Density::Comfortable => "Comfortable",
+            Density::Compact => "Compact",
+        }
+    }
+}
+
+impl SizeUnits {
+    fn label(&self) -> &'static str {
+        match self {
+            SizeUnits::Decimal => "Decimal (kB, MB)",
+            SizeUnits::Binary => "Binary (KiB, MiB)",
+        }
+    }
+}
+
+impl TimeZone {
+    fn label(&self) -> &'static str {
+        match self {
+            TimeZone::Local => "Browser time zone",
+            TimeZone::Utc => "UTC",
+        }
+    }
+}
+
+fn storage_key(username: &str) -> String {
+    format!("{APPEARANCE_STORAGE_KEY}_{username}")
+}
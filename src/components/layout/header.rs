@@ -9,19 +9,32 @@ use leptos::*;
 use leptos_router::use_navigate;
 
 use crate::{
-    components::icon::{
-        IconAdjustmentsHorizontal, IconHeart, IconPower, IconServer, IconUserCircle,
+    components::{
+        icon::{
+            IconAdjustmentsHorizontal, IconHeart, IconPower, IconServer, IconSparkles,
+            IconUserCircle,
+        },
+        layout::{
+            appearance::AppearanceMenu, language::LanguageMenu, notifications::NotificationBell,
+            presence::Presence, server::ServerMenu, whats_new::use_whats_new,
+        },
     },
-    core::{oauth::use_authorization, url::UrlBuilder, AccessToken, Permission, Permissions},
-    pages::config::edit::DEFAULT_SETTINGS_URL,
+    core::{
+        i18n::t, impersonate::IMPERSONATION_STORAGE_KEY, oauth::use_authorization, url::UrlBuilder,
+        AccessToken, Permission, Permissions,
+    },
+    pages::{config::edit::DEFAULT_SETTINGS_URL, enterprise::health::HealthIndicator},
     STATE_STORAGE_KEY, VERSION_NAME,
 };
 use web_sys::wasm_bindgen::JsCast;
 
 #[component]
 pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
+    let whats_new = use_whats_new();
+    let auth = use_authorization();
+
     view! {
-        <header class="sticky top-0 inset-x-0 flex flex-wrap sm:justify-start sm:flex-nowrap z-[48] w-full bg-white border-b text-sm py-2.5 sm:py-4 lg:ps-64 dark:bg-gray-800 dark:border-gray-700">
+        <header class="print:hidden sticky top-0 inset-x-0 flex flex-wrap sm:justify-start sm:flex-nowrap z-[48] w-full bg-white border-b text-sm py-2.5 sm:py-4 lg:ps-64 dark:bg-gray-800 dark:border-gray-700">
             <nav class="flex basis-full items-center w-full mx-auto px-4 sm:px-6 md:px-8">
 
                 <div class="me-5 lg:me-0 lg:hidden">
@@ -78,7 +91,7 @@ pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
                                 <input
                                     type="text"
                                     class="py-2 px-4 ps-11 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                                    placeholder="Search settings"
+                                    placeholder=move || t("header-search-settings")
                                     on:keyup=move |ev| {
                                         let key_code = ev
                                             .unchecked_ref::<web_sys::KeyboardEvent>()
@@ -104,11 +117,28 @@ pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
                     </Show>
 
                     <div class="flex flex-row items-center justify-end gap-2">
+                        <Show when=move || {
+                            auth.get().is_enterprise()
+                                && permissions
+                                    .get()
+                                    .is_some_and(|p| p.has_access(Permission::MetricsList))
+                        }>
+                            <HealthIndicator/>
+                        </Show>
+                        <Show when=move || {
+                            permissions.get().is_some_and(|p| p.has_admin_access())
+                        }>
+                            <Presence/>
+                            <NotificationBell/>
+                        </Show>
+                        <ServerMenu permissions/>
+                        <AppearanceMenu/>
+                        <LanguageMenu/>
                         <a
                             href=move || { permissions.get().map(|p| { p.default_url(false) }) }
 
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                            title="Management"
+                            title=move || t("header-management")
                             class:hidden=move || {
                                 permissions.get().map_or(true, |p| { !p.has_admin_access() })
                             }
@@ -120,7 +150,7 @@ pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
                         <a
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
                             href=DEFAULT_SETTINGS_URL
-                            title="Settings"
+                            title=move || t("header-settings")
                             class:hidden=move || {
                                 permissions
                                     .get()
@@ -145,7 +175,7 @@ pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
                                     })
                             }
 
-                            title="Account"
+                            title=move || t("header-account")
                             class:hidden=move || {
                                 permissions
                                     .get()
@@ -164,20 +194,31 @@ pub fn Header(permissions: Memo<Option<Permissions>>) -> impl IntoView {
                             <IconUserCircle/>
 
                         </a>
+                        <button
+                            type="button"
+                            class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                            title=move || t("header-whats-new")
+                            on:click=move |_| whats_new.show()
+                        >
+
+                            <IconSparkles/>
+
+                        </button>
                         <a
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
                             href="https://github.com/sponsors/stalwartlabs"
                             target="_blank"
-                            title="Sponsor Stalwart open source"
+                            title=move || t("header-sponsor")
                         >
                             <IconHeart/>
 
                         </a>
                         <a
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                            title="Logout"
+                            title=move || t("header-logout")
                             on:click=move |_| {
                                 SessionStorage::delete(STATE_STORAGE_KEY);
+                                SessionStorage::delete(IMPERSONATION_STORAGE_KEY);
                                 use_authorization().set(AccessToken::default());
                                 use_navigate()("/login", Default::default());
                             }
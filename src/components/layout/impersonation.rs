@@ -0,0 +1,100 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::Utc;
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::icon::IconEye,
+    core::{
+        impersonate::{current_impersonation, end_impersonation},
+        oauth::use_authorization,
+        poll::use_polling,
+        url::UrlBuilder,
+    },
+    pages::format_countdown,
+};
+
+#[component]
+pub fn ImpersonationBanner() -> impl IntoView {
+    let auth = use_authorization();
+    let clock = use_polling(Duration::from_secs(1));
+    let impersonation = create_memo(move |_| {
+        auth.track();
+        current_impersonation()
+    });
+
+    let end_session = create_action(move |_: &()| async move {
+        if let Some(impersonation) = end_impersonation(auth).await {
+            use_navigate()(
+                &UrlBuilder::new("/manage/impersonations")
+                    .with_parameter("account", impersonation.account)
+                    .finish(),
+                Default::default(),
+            );
+        }
+    });
+
+    let remaining = move || {
+        clock.tick();
+        impersonation
+            .get()
+            .map(|impersonation| (impersonation.expires_at - Utc::now()).num_seconds())
+            .unwrap_or_default()
+    };
+
+    // Sessions end on their own once the limited token expires
+    create_effect(move |_| {
+        if impersonation.with(|i| i.is_some()) && remaining() <= 0 && !end_session.pending().get() {
+            end_session.dispatch(());
+        }
+    });
+
+    move || {
+        impersonation.get().map(|impersonation| {
+            view! {
+                <div
+                    class="mb-4 bg-red-50 border-s-4 border-red-500 p-4 dark:bg-red-800/30"
+                    role="alert"
+                >
+                    <div class="flex items-center gap-x-3">
+                        <IconEye attr:class="flex-shrink-0 size-5 text-red-800 dark:text-red-400"/>
+                        <div class="grow">
+                            <h3 class="text-sm font-semibold text-red-800 dark:text-white">
+                                {format!("Impersonating {}", impersonation.account)}
+                            </h3>
+                            <p class="text-sm text-red-700 dark:text-neutral-400 tabular-nums">
+                                {format!(
+                                    "Signed in on behalf of {} by {}. All actions are recorded in the audit log. ",
+                                    impersonation.account,
+                                    impersonation.impersonator.username,
+                                )}
+                                {move || {
+                                    format!(
+                                        "Session ends in {}.",
+                                        format_countdown(remaining().max(0) as u64),
+                                    )
+                                }}
+
+                            </p>
+                        </div>
+                        <button
+                            type="button"
+                            class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-red-600 text-white hover:bg-red-700 disabled:opacity-50 disabled:pointer-events-none"
+                            disabled=move || end_session.pending().get()
+                            on:click=move |_| end_session.dispatch(())
+                        >
+                            End session
+                        </button>
+                    </div>
+                </div>
+            }
+        })
+    }
+}
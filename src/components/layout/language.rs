@@ -0,0 +1,65 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::{html::Div, *};
+use leptos_use::on_click_outside;
+
+use crate::{
+    components::icon::IconLanguage,
+    core::i18n::{t, use_i18n, Locale},
+};
+
+#[component]
+pub fn LanguageMenu() -> impl IntoView {
+    let i18n = use_i18n();
+    let is_open = create_rw_signal(false);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    view! {
+        <div class="relative inline-flex" node_ref=target>
+            <button
+                type="button"
+                class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                title=move || t("header-language")
+                aria-haspopup="true"
+                aria-expanded=move || is_open.get().to_string()
+                on:click=move |_| is_open.update(|open| *open = !*open)
+            >
+
+                <IconLanguage/>
+            </button>
+            <div
+                class="absolute end-0 top-full mt-2 z-10 min-w-40 bg-white shadow-md rounded-lg p-2 dark:bg-gray-800 dark:border dark:border-gray-700"
+                class:hidden=move || !is_open.get()
+            >
+
+                {Locale::ALL
+                    .into_iter()
+                    .map(|locale| {
+                        let is_selected = move || i18n.locale.get() == locale;
+                        view! {
+                            <button
+                                type="button"
+                                class="w-full flex items-center gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-gray-400 dark:hover:bg-gray-700 dark:hover:text-gray-300"
+                                class:font-semibold=is_selected
+                                aria-pressed=move || is_selected().to_string()
+                                lang=locale.code()
+                                on:click=move |_| {
+                                    i18n.set_locale(locale);
+                                    is_open.set(false);
+                                }
+                            >
+
+                                {locale.name()}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+        </div>
+    }
+}
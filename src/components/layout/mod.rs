@@ -4,9 +4,19 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod appearance;
 pub mod header;
+pub mod impersonation;
+pub mod language;
+pub mod notifications;
+pub mod palette;
+pub mod presence;
+pub mod recording;
+pub mod server;
+pub mod session;
 pub mod sidebar;
 pub mod toggle;
+pub mod whats_new;
 
 use std::hash::{DefaultHasher, Hash, Hasher};
 
@@ -16,7 +26,17 @@ use leptos_router::Outlet;
 
 use crate::{
     components::{
-        layout::{header::Header, sidebar::SideBar, toggle::ToggleNavigation},
+        layout::{
+            header::Header,
+            impersonation::ImpersonationBanner,
+            notifications::init_notifications,
+            palette::Palette,
+            recording::RecordingBanner,
+            session::SessionExpiredModal,
+            sidebar::SideBar,
+            toggle::ToggleNavigation,
+            whats_new::{init_whats_new, WhatsNewPanel},
+        },
         messages::modal::Modal,
     },
     core::{
@@ -52,14 +72,21 @@ pub fn Layout(
 ) -> impl IntoView {
     let menu_items_toggle = menu_items.clone();
     let show_sidebar = create_rw_signal(false);
+    init_whats_new();
+    init_notifications();
 
     view! {
         <Body class="bg-gray-50 dark:bg-slate-900"/>
         <Modal/>
+        <SessionExpiredModal/>
+        <Palette/>
         <Header permissions/>
         <ToggleNavigation menu_items show_sidebar/>
         <SideBar menu_items=menu_items_toggle show_sidebar/>
-        <div class="w-full pt-10 px-4 sm:px-6 md:px-8 lg:ps-72">
+        <div class="w-full pt-10 px-4 sm:px-6 md:px-8 lg:ps-72 print:p-0">
+            <ImpersonationBanner/>
+            <RecordingBanner/>
+            <WhatsNewPanel/>
             <Outlet/>
         </div>
     }
@@ -0,0 +1,240 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use ahash::AHashMap;
+use leptos::{html::Div, *};
+use leptos_use::on_click_outside;
+
+use crate::{
+    components::icon::{IconBell, IconXMark},
+    core::{events::use_change_events, http::HttpRequest, oauth::use_authorization},
+    pages::account::notifications::{
+        NotificationCategory, NotificationChannel, NotificationPreferences,
+    },
+};
+
+// Events are only notified when pushed by the server, the polling fallback
+// is kept slow as it does not produce notifications.
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(300);
+const TOAST_TIMEOUT: Duration = Duration::from_secs(6);
+
+#[derive(Clone, Copy)]
+pub struct Notifications {
+    preferences: RwSignal<NotificationPreferences>,
+    unseen: RwSignal<AHashMap<NotificationCategory, u32>>,
+    toasts: RwSignal<Vec<(u64, NotificationCategory)>>,
+    next_id: StoredValue<u64>,
+}
+
+pub fn init_notifications() {
+    provide_context(Notifications {
+        preferences: create_rw_signal(NotificationPreferences::default()),
+        unseen: create_rw_signal(AHashMap::new()),
+        toasts: create_rw_signal(vec![]),
+        next_id: store_value(0),
+    });
+}
+
+pub fn use_notifications() -> Notifications {
+    expect_context::<Notifications>()
+}
+
+impl Notifications {
+    pub fn set_preferences(&self, preferences: NotificationPreferences) {
+        self.preferences.set(preferences);
+    }
+
+    fn notify(&self, category: NotificationCategory) {
+        let (show_badge, show_toast) = self.preferences.with_untracked(|preferences| {
+            (
+                preferences.allows(category, NotificationChannel::Badge),
+                preferences.allows(category, NotificationChannel::Toast),
+            )
+        });
+
+        if show_badge {
+            self.unseen
+                .update(|unseen| *unseen.entry(category).or_default() += 1);
+        }
+        if show_toast {
+            let id = self.next_id.get_value();
+            let toasts = self.toasts;
+            self.next_id.set_value(id + 1);
+            toasts.update(|toasts| toasts.push((id, category)));
+            set_timeout(
+                move || toasts.update(|toasts| toasts.retain(|(toast_id, _)| *toast_id != id)),
+                TOAST_TIMEOUT,
+            );
+        }
+    }
+
+    fn total_unseen(&self) -> u32 {
+        self.unseen.with(|unseen| unseen.values().sum())
+    }
+}
+
+#[component]
+pub fn NotificationBell() -> impl IntoView {
+    let auth = use_authorization();
+    let notifications = use_notifications();
+    let is_open = create_rw_signal(false);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    // Servers without stored preferences answer with an error, in which case
+    // the default channels are used.
+    let preferences = create_local_resource(
+        move || auth.get().access_token.clone(),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/account/notifications")
+                    .with_authorization(&auth)
+                    .send::<NotificationPreferences>()
+                    .await
+                    .ok()
+            }
+        },
+    );
+    create_effect(move |_| {
+        if let Some(preferences) = preferences.get().flatten() {
+            notifications.set_preferences(preferences);
+        }
+    });
+
+    for category in NotificationCategory::ALL.iter().copied() {
+        let events = use_change_events(category.topic(), FALLBACK_INTERVAL);
+        create_effect(move |prev: Option<u64>| {
+            let received = events.received();
+            if prev.is_some_and(|prev| prev != received) {
+                notifications.notify(category);
+            }
+            received
+        });
+    }
+
+    view! {
+        <div class="relative inline-flex" node_ref=target>
+            <button
+                type="button"
+                class="relative w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                title="Notifications"
+                aria-haspopup="true"
+                aria-expanded=move || is_open.get().to_string()
+                on:click=move |_| is_open.update(|open| *open = !*open)
+            >
+
+                <IconBell/>
+                <span
+                    class="absolute top-0 end-0 inline-flex items-center py-0.5 px-1.5 rounded-full text-xs font-medium bg-red-500 text-white"
+                    class:hidden=move || notifications.total_unseen() == 0
+                >
+                    {move || notifications.total_unseen()}
+                </span>
+            </button>
+            <div
+                class="absolute end-0 top-full mt-2 z-10 min-w-60 bg-white shadow-md rounded-lg p-2 dark:bg-gray-800 dark:border dark:border-gray-700"
+                class:hidden=move || !is_open.get()
+            >
+
+                {NotificationCategory::ALL
+                    .iter()
+                    .copied()
+                    .map(|category| {
+                        let count = move || {
+                            notifications
+                                .unseen
+                                .with(|unseen| unseen.get(&category).copied().unwrap_or_default())
+                        };
+                        view! {
+                            <a
+                                class="flex items-center justify-between gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 dark:text-gray-400 dark:hover:bg-gray-700 dark:hover:text-gray-300"
+                                href=category.route()
+                                on:click=move |_| {
+                                    is_open.set(false);
+                                    notifications
+                                        .unseen
+                                        .update(|unseen| {
+                                            unseen.remove(&category);
+                                        });
+                                }
+                            >
+
+                                {category.name()}
+                                <span
+                                    class="inline-flex items-center py-0.5 px-1.5 rounded-full text-xs font-medium bg-gray-100 text-gray-800 dark:bg-white/10 dark:text-white"
+                                    class:hidden=move || count() == 0
+                                >
+                                    {count}
+                                </span>
+                            </a>
+                        }
+                    })
+                    .collect_view()}
+                <div class="border-t my-2 dark:border-gray-700"></div>
+                <a
+                    class="block py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 dark:text-gray-400 dark:hover:bg-gray-700 dark:hover:text-gray-300"
+                    href="/account/notifications"
+                    on:click=move |_| is_open.set(false)
+                >
+                    Preferences
+                </a>
+            </div>
+        </div>
+        <NotificationToasts/>
+    }
+}
+
+#[component]
+fn NotificationToasts() -> impl IntoView {
+    let notifications = use_notifications();
+
+    view! {
+        <Portal mount=document().get_element_by_id("portal_root").unwrap()>
+            <div class="fixed bottom-4 end-4 z-[90] flex flex-col gap-y-2">
+                <For
+                    each=move || notifications.toasts.get()
+                    key=|(id, _)| *id
+                    children=move |(id, category)| {
+                        view! {
+                            <div
+                                class="flex items-start gap-x-3 max-w-xs bg-white border border-gray-200 rounded-xl shadow-lg p-4 dark:bg-gray-800 dark:border-gray-700"
+                                role="alert"
+                            >
+                                <IconBell attr:class="flex-shrink-0 size-4 mt-0.5 text-blue-600 dark:text-blue-500"/>
+                                <a class="grow" href=category.route()>
+                                    <p class="text-sm font-semibold text-gray-800 dark:text-white">
+                                        {category.name()}
+                                    </p>
+                                    <p class="text-sm text-gray-700 dark:text-gray-400">
+                                        {category.description()}
+                                    </p>
+                                </a>
+                                <button
+                                    type="button"
+                                    class="flex-shrink-0 inline-flex justify-center items-center size-5 rounded-lg text-gray-800 opacity-50 hover:opacity-100 dark:text-white"
+                                    on:click=move |_| {
+                                        notifications
+                                            .toasts
+                                            .update(|toasts| toasts.retain(|(toast_id, _)| *toast_id != id))
+                                    }
+                                >
+
+                                    <span class="sr-only">Close</span>
+                                    <IconXMark attr:class="flex-shrink-0 size-4"/>
+                                </button>
+                            </div>
+                        }
+                    }
+                />
+
+            </div>
+        </Portal>
+    }
+}
@@ -0,0 +1,337 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::{html::Input, *};
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{form::history::is_editing_text, layout::LayoutBuilder},
+    core::{
+        command::{use_commands, Command, CommandAction, CommandGroup},
+        oauth::use_authorization,
+        Permission,
+    },
+    pages::config::search::SettingsIndex,
+};
+
+const MAX_RESULTS: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteMode {
+    // Settings index, opened with the `/` key
+    Settings,
+    // Pages, recently opened objects and actions, opened with Ctrl+K
+    // (Cmd+K on macOS)
+    Commands,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PaletteResult {
+    path: Option<String>,
+    title: String,
+    key: Option<String>,
+    subtitle: String,
+    group: Option<&'static str>,
+    target: PaletteTarget,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PaletteTarget {
+    Url(String),
+    // Position in the commands collected when the palette opened
+    Command(usize),
+}
+
+// Overlay shared by the settings search and the command palette, opening
+// one of them closes the other.
+#[component]
+pub fn Palette() -> impl IntoView {
+    let auth = use_authorization();
+    let registry = use_commands();
+    let index = expect_context::<Arc<SettingsIndex>>();
+    let mode = create_rw_signal(None::<PaletteMode>);
+    let query = create_rw_signal(String::new());
+    let selected = create_rw_signal(0usize);
+    let commands = store_value(Vec::<Command>::new());
+    let input_ref = create_node_ref::<Input>();
+
+    let results = create_memo(move |_| match mode.get() {
+        Some(PaletteMode::Settings) => query.with(|query| {
+            index
+                .search(query, MAX_RESULTS)
+                .into_iter()
+                .map(|entry| PaletteResult {
+                    path: Some(entry.path.clone()),
+                    title: entry.label.to_string(),
+                    key: Some(entry.key.clone()),
+                    subtitle: entry.help.unwrap_or_default().to_string(),
+                    group: None,
+                    target: PaletteTarget::Url(entry.url.clone()),
+                })
+                .collect::<Vec<_>>()
+        }),
+        Some(PaletteMode::Commands) => {
+            let words = query.with(|query| {
+                query
+                    .split_whitespace()
+                    .map(|word| word.to_lowercase())
+                    .collect::<Vec<_>>()
+            });
+            commands.with_value(|commands| command_results(commands, &words))
+        }
+        None => vec![],
+    });
+
+    let open = move |new_mode: PaletteMode| {
+        if new_mode == PaletteMode::Commands {
+            let auth = auth.get_untracked();
+            let permissions = auth.permissions();
+            let mut pages = LayoutBuilder::manage(permissions);
+            if permissions.has_access(Permission::SettingsList) {
+                pages.extend(LayoutBuilder::settings(""));
+            }
+            pages.extend(LayoutBuilder::account(permissions));
+            commands.set_value(registry.commands(&auth, &pages));
+        }
+        query.set(String::new());
+        selected.set(0);
+        mode.set(Some(new_mode));
+    };
+
+    let open_result = move |idx: usize| {
+        let Some(result) = results.with_untracked(|results| results.get(idx).cloned()) else {
+            return;
+        };
+        mode.set(None);
+        match result.target {
+            PaletteTarget::Url(url) => use_navigate()(&url, Default::default()),
+            PaletteTarget::Command(idx) => {
+                if let Some(command) = commands.with_value(|commands| commands.get(idx).cloned()) {
+                    match command.action {
+                        CommandAction::Navigate(url) => use_navigate()(&url, Default::default()),
+                        CommandAction::Run(callback) => callback.call(()),
+                    }
+                }
+            }
+        }
+    };
+
+    let handle = window_event_listener(ev::keydown, move |ev| {
+        if (ev.ctrl_key() || ev.meta_key()) && ev.key().eq_ignore_ascii_case("k") {
+            ev.prevent_default();
+            if mode.get_untracked() == Some(PaletteMode::Commands) {
+                mode.set(None);
+            } else {
+                open(PaletteMode::Commands);
+            }
+        } else if ev.key() == "/"
+            && mode.get_untracked().is_none()
+            && !is_editing_text()
+            && auth
+                .get_untracked()
+                .permissions()
+                .has_access(Permission::SettingsList)
+        {
+            ev.prevent_default();
+            open(PaletteMode::Settings);
+        }
+    });
+    on_cleanup(move || handle.remove());
+
+    create_effect(move |_| {
+        if mode.get().is_some() {
+            request_animation_frame(move || {
+                if let Some(input) = input_ref.get_untracked() {
+                    let _ = input.focus();
+                }
+            });
+        }
+    });
+
+    view! {
+        <Show when=move || mode.get().is_some()>
+            <Portal mount=document().get_element_by_id("portal_root").unwrap()>
+                <div
+                    class="fixed inset-0 z-[80] bg-gray-900 bg-opacity-50 dark:bg-opacity-80 overflow-y-auto"
+                    on:click=move |_| mode.set(None)
+                >
+                    <div
+                        class="sm:max-w-2xl sm:w-full m-3 mt-16 sm:mx-auto bg-white border shadow-sm rounded-xl dark:bg-gray-800 dark:border-gray-700"
+                        on:click=|ev| ev.stop_propagation()
+                    >
+                        <div class="border-b dark:border-gray-700">
+                            <input
+                                type="text"
+                                class="py-3 px-4 block w-full border-transparent rounded-t-xl text-sm focus:border-transparent focus:ring-0 dark:bg-gray-800 dark:text-gray-300"
+                                placeholder=move || match mode.get() {
+                                    Some(PaletteMode::Settings) => {
+                                        "Search settings by name, key or description"
+                                    }
+                                    _ => "Go to a page or run an action",
+                                }
+
+                                node_ref=input_ref
+                                prop:value=move || query.get()
+                                on:input=move |ev| {
+                                    query.set(event_target_value(&ev));
+                                    selected.set(0);
+                                }
+
+                                on:keydown=move |ev| {
+                                    let total = results.with_untracked(|results| results.len());
+                                    match ev.key().as_str() {
+                                        "Escape" => {
+                                            ev.prevent_default();
+                                            mode.set(None);
+                                        }
+                                        "ArrowDown" if total > 0 => {
+                                            ev.prevent_default();
+                                            selected.update(|idx| *idx = (*idx + 1) % total);
+                                        }
+                                        "ArrowUp" if total > 0 => {
+                                            ev.prevent_default();
+                                            selected.update(|idx| *idx = (*idx + total - 1) % total);
+                                        }
+                                        "Enter" => {
+                                            ev.prevent_default();
+                                            open_result(selected.get_untracked());
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            />
+
+                        </div>
+                        <ul class="max-h-96 overflow-y-auto p-2">
+                            {move || {
+                                results
+                                    .get()
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(idx, result)| {
+                                        view! {
+                                            <li
+                                                class=move || {
+                                                    if selected.get() == idx {
+                                                        "flex items-center justify-between gap-x-3 cursor-pointer rounded-lg py-2 px-3 bg-gray-100 dark:bg-gray-700"
+                                                    } else {
+                                                        "flex items-center justify-between gap-x-3 cursor-pointer rounded-lg py-2 px-3 hover:bg-gray-100 dark:hover:bg-gray-700"
+                                                    }
+                                                }
+
+                                                on:mouseenter=move |_| selected.set(idx)
+                                                on:click=move |_| open_result(idx)
+                                            >
+                                                <div class="min-w-0">
+                                                    {result
+                                                        .path
+                                                        .map(|path| {
+                                                            view! {
+                                                                <p class="text-xs text-gray-500 dark:text-gray-400">
+                                                                    {path}
+                                                                </p>
+                                                            }
+                                                        })}
+                                                    <p class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                                                        {result.title}
+                                                        {result
+                                                            .key
+                                                            .map(|key| {
+                                                                view! {
+                                                                    <code class="ms-2 font-mono text-xs text-gray-500">
+                                                                        {key}
+                                                                    </code>
+                                                                }
+                                                            })}
+                                                    </p>
+                                                    <p class="text-xs text-gray-500 truncate">
+                                                        {result.subtitle}
+                                                    </p>
+                                                </div>
+                                                {result
+                                                    .group
+                                                    .map(|group| {
+                                                        view! {
+                                                            <span class="flex-shrink-0 text-xs text-gray-500 dark:text-gray-400">
+                                                                {group}
+                                                            </span>
+                                                        }
+                                                    })}
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </ul>
+                        <p
+                            class="py-2 px-4 text-sm text-gray-500"
+                            class:hidden=move || {
+                                results.with(|results| !results.is_empty())
+                                    || (mode.get() == Some(PaletteMode::Settings)
+                                        && query.with(|query| query.trim().is_empty()))
+                            }
+                        >
+
+                            {move || match mode.get() {
+                                Some(PaletteMode::Settings) => "No settings match your search.",
+                                _ if query.with(|query| query.trim().is_empty()) => {
+                                    "Type to search pages and actions."
+                                }
+                                _ => "No pages or actions match your search.",
+                            }}
+
+                        </p>
+                        <div class="flex justify-end gap-x-4 py-2 px-4 border-t text-xs text-gray-500 dark:border-gray-700">
+                            <span>"↑↓ to navigate"</span>
+                            <span>"↵ to open"</span>
+                            <span>"esc to close"</span>
+                        </div>
+                    </div>
+                </div>
+            </Portal>
+        </Show>
+    }
+}
+
+// Matching commands, best match first. Pages are only listed once the
+// admin starts typing.
+fn command_results(commands: &[Command], words: &[String]) -> Vec<PaletteResult> {
+    let mut results = commands
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, command)| {
+            if words.is_empty() {
+                (command.group != CommandGroup::Page).then_some((0, command.group, idx))
+            } else {
+                command
+                    .score(words)
+                    .map(|score| (score, command.group, idx))
+            }
+        })
+        .collect::<Vec<_>>();
+    results.sort_by(|(l_score, l_group, _), (r_score, r_group, _)| {
+        r_score.cmp(l_score).then_with(|| l_group.cmp(r_group))
+    });
+    results
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, group, idx)| PaletteResult {
+            path: None,
+            title: commands[idx].title.clone(),
+            key: None,
+            subtitle: commands[idx].subtitle.clone(),
+            group: Some(match group {
+                CommandGroup::Action => "Action",
+                CommandGroup::Recent => "Recent",
+                CommandGroup::Page => "Page",
+            }),
+            target: PaletteTarget::Command(idx),
+        })
+        .collect()
+}
@@ -0,0 +1,150 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use codee::string::JsonSerdeCodec;
+use leptos::*;
+use leptos_router::use_location;
+use leptos_use::{
+    core::ConnectionReadyState, use_websocket_with_options, ReconnectLimit, UseWebSocketOptions,
+    UseWebSocketReturn,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    events::websocket_url, http::HttpRequest, oauth::use_authorization, url::UrlBuilder,
+};
+
+const MAX_AVATARS: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+enum PresenceRequest {
+    View { path: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct PresenceUpdate {
+    pub path: String,
+    #[serde(default)]
+    pub viewers: Vec<Viewer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Viewer {
+    pub id: String,
+    pub name: String,
+}
+
+#[component]
+pub fn Presence() -> impl IntoView {
+    let auth = use_authorization();
+
+    // Servers without a presence channel answer with an error, in which case
+    // nothing is displayed.
+    let token = create_local_resource(
+        move || auth.get().access_token.clone(),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/presence/token")
+                    .with_authorization(&auth)
+                    .send::<String>()
+                    .await
+                    .ok()
+            }
+        },
+    );
+
+    move || {
+        token.get().flatten().map(|token| {
+            let UseWebSocketReturn {
+                ready_state,
+                message,
+                send,
+                ..
+            } = use_websocket_with_options::<PresenceRequest, PresenceUpdate, JsonSerdeCodec>(
+                &UrlBuilder::new(websocket_url(
+                    &auth.get_untracked().base_url,
+                    "/api/presence/ws",
+                ))
+                .with_parameter("token", token)
+                .finish(),
+                UseWebSocketOptions::default()
+                    .reconnect_limit(ReconnectLimit::Limited(5))
+                    .reconnect_interval(5000),
+            );
+            let location = use_location();
+            let path = location.pathname;
+
+            create_effect(move |_| {
+                if ready_state.get() == ConnectionReadyState::Open {
+                    send(&PresenceRequest::View { path: path.get() });
+                }
+            });
+
+            let viewers = create_memo(move |_| {
+                message
+                    .get()
+                    .filter(|update| update.path == path.get())
+                    .map(|update| update.viewers)
+                    .unwrap_or_default()
+            });
+
+            view! {
+                <div
+                    class="flex -space-x-2"
+                    class:hidden=move || viewers.get().is_empty()
+                    title=move || {
+                        let names = viewers
+                            .get()
+                            .into_iter()
+                            .map(|viewer| viewer.name)
+                            .collect::<Vec<_>>();
+                        format!("Also viewing this page: {}", names.join(", "))
+                    }
+                >
+
+                    {move || {
+                        viewers
+                            .get()
+                            .into_iter()
+                            .take(MAX_AVATARS)
+                            .map(|viewer| {
+                                view! {
+                                    <span class="inline-flex items-center justify-center size-[2.375rem] rounded-full bg-blue-100 ring-2 ring-white text-sm font-semibold text-blue-800 dark:bg-blue-500/10 dark:ring-gray-800 dark:text-blue-500">
+                                        {initials(&viewer.name)}
+                                    </span>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                    {move || {
+                        let num_hidden = viewers.get().len().saturating_sub(MAX_AVATARS);
+                        (num_hidden > 0)
+                            .then(|| {
+                                view! {
+                                    <span class="inline-flex items-center justify-center size-[2.375rem] rounded-full bg-gray-100 ring-2 ring-white text-sm font-medium text-gray-800 dark:bg-gray-700 dark:ring-gray-800 dark:text-white">
+                                        {format!("+{num_hidden}")}
+                                    </span>
+                                }
+                            })
+                    }}
+
+                </div>
+            }
+        })
+    }
+}
+
+fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
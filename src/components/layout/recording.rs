@@ -0,0 +1,79 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+
+use crate::{
+    components::icon::IconPlayCircle,
+    core::{
+        poll::use_polling,
+        recording::{current_recording, download_recording, stop_recording},
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+#[component]
+pub fn RecordingBanner() -> impl IntoView {
+    let poller = use_polling(Duration::from_secs(2));
+    let recording = create_memo(move |_| {
+        poller.tick();
+        current_recording()
+    });
+
+    move || {
+        recording.get().map(|recording| {
+            view! {
+                <div
+                    class="mb-4 bg-yellow-50 border-s-4 border-yellow-500 p-4 dark:bg-yellow-800/30"
+                    role="alert"
+                >
+                    <div class="flex items-center gap-x-3">
+                        <IconPlayCircle attr:class="flex-shrink-0 size-5 text-yellow-800 dark:text-yellow-400"/>
+                        <div class="grow">
+                            <h3 class="text-sm font-semibold text-yellow-800 dark:text-white">
+                                Recording changes
+                            </h3>
+                            <p class="text-sm text-yellow-700 dark:text-neutral-400">
+                                {format!(
+                                    "{} captured since {}.",
+                                    maybe_plural(recording.calls.len(), "API call", "API calls"),
+                                    recording.started_at.format_date_time(),
+                                )}
+
+                            </p>
+                        </div>
+                        <button
+                            type="button"
+                            class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-yellow-200 bg-white text-yellow-800 hover:bg-yellow-100 dark:bg-transparent dark:border-yellow-700 dark:text-yellow-400"
+                            on:click=move |_| {
+                                stop_recording();
+                                poller.refresh();
+                            }
+                        >
+
+                            Discard
+                        </button>
+                        <button
+                            type="button"
+                            class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-yellow-600 text-white hover:bg-yellow-700"
+                            on:click=move |_| {
+                                if let Some(recording) = stop_recording() {
+                                    download_recording(&recording);
+                                }
+                                poller.refresh();
+                            }
+                        >
+
+                            Stop and download
+                        </button>
+                    </div>
+                </div>
+            }
+        })
+    }
+}
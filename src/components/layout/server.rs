@@ -0,0 +1,332 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::{html::Div, *};
+use leptos_router::use_navigate;
+use leptos_use::on_click_outside;
+
+use crate::{
+    components::{
+        icon::{IconCheckCircle, IconPower, IconRefresh, IconServerStack},
+        messages::{
+            alert::{Alert, ReloadMessageItem},
+            modal::{use_modals, Modal},
+        },
+    },
+    core::{
+        http::{self, HttpRequest},
+        i18n::t,
+        oauth::use_authorization,
+        schema::Schemas,
+        Permission, Permissions,
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, NodeApplyResult, NodeApplyStatus, ReloadOutcome},
+            ReloadMessage, ReloadSettings,
+        },
+        maybe_plural,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ServerAction {
+    Validate,
+    Reload,
+    Restart,
+}
+
+#[derive(Clone)]
+enum ActionResult {
+    Success(&'static str),
+    Messages {
+        summary: &'static str,
+        messages: Vec<ReloadMessage>,
+    },
+    Cluster(Vec<NodeApplyResult>),
+    Failed(Box<Alert>),
+}
+
+#[component]
+pub fn ServerMenu(permissions: Memo<Option<Permissions>>) -> impl IntoView {
+    let auth = use_authorization();
+    let modal = use_modals();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let is_open = create_rw_signal(false);
+    let result = create_rw_signal(None::<ActionResult>);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    let can_reload = move || {
+        permissions
+            .get()
+            .is_some_and(|p| p.has_access(Permission::SettingsReload))
+    };
+    let can_restart = move || {
+        permissions
+            .get()
+            .is_some_and(|p| p.has_access(Permission::Restart))
+    };
+
+    let execute = create_action(move |action: &ServerAction| {
+        let action = *action;
+        let auth = auth.get();
+        let schemas = schemas.get_value();
+
+        async move {
+            result.set(None);
+            let response = match action {
+                ServerAction::Validate => HttpRequest::get("/api/reload/")
+                    .with_authorization(&auth)
+                    .with_parameter("dry-run", "true")
+                    .send::<ReloadSettings>()
+                    .await
+                    .map(|reload| action.reload_result(reload, &schemas)),
+                // In a cluster every node is reloaded
+                ServerAction::Reload => {
+                    reload_settings(&auth, "")
+                        .await
+                        .map(|outcome| match outcome {
+                            ReloadOutcome::Local(reload) => action.reload_result(reload, &schemas),
+                            ReloadOutcome::Cluster(results) => ActionResult::Cluster(results),
+                        })
+                }
+                ServerAction::Restart => HttpRequest::get("/api/restart")
+                    .with_authorization(&auth)
+                    .send::<Option<String>>()
+                    .await
+                    .map(|_| ActionResult::Success(action.success_message())),
+            };
+
+            match response {
+                Ok(response) => result.set(Some(response)),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => result.set(Some(ActionResult::Failed(Box::new(Alert::from(err))))),
+            }
+        }
+    });
+    let pending = execute.pending();
+
+    let item_class = "w-full flex items-center gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:text-gray-400 dark:hover:bg-gray-700 dark:hover:text-gray-300";
+
+    view! {
+        <Show when=move || can_reload() || can_restart()>
+            <div class="relative inline-flex" node_ref=target>
+                <button
+                    type="button"
+                    class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    title=move || t("header-server")
+                    aria-haspopup="true"
+                    aria-expanded=move || is_open.get().to_string()
+                    on:click=move |_| is_open.update(|open| *open = !*open)
+                >
+
+                    <IconServerStack/>
+                </button>
+                <div
+                    class="absolute end-0 top-full mt-2 z-10 w-80 bg-white shadow-md rounded-lg p-2 dark:bg-gray-800 dark:border dark:border-gray-700"
+                    class:hidden=move || !is_open.get()
+                >
+
+                    <Show when=can_reload>
+                        <button
+                            type="button"
+                            class=item_class
+                            disabled=move || pending.get()
+                            on:click=move |_| execute.dispatch(ServerAction::Validate)
+                        >
+                            <IconCheckCircle attr:class="flex-shrink-0 size-4"/>
+                            {move || t("server-validate")}
+                        </button>
+                        <button
+                            type="button"
+                            class=item_class
+                            disabled=move || pending.get()
+                            on:click=move |_| execute.dispatch(ServerAction::Reload)
+                        >
+                            <IconRefresh attr:class="flex-shrink-0 size-4"/>
+                            {move || t("server-reload")}
+                        </button>
+                    </Show>
+                    <Show when=can_restart>
+                        <button
+                            type="button"
+                            class=item_class
+                            disabled=move || pending.get()
+                            on:click=move |_| {
+                                is_open.set(false);
+                                modal
+                                    .set(
+                                        Modal::with_title("Restart server")
+                                            .with_message(
+                                                concat!(
+                                                    "Are you sure you want to restart the server? ",
+                                                    "Active connections will be interrupted and the ",
+                                                    "server will be unavailable for a few seconds.",
+                                                ),
+                                            )
+                                            .with_button("Restart")
+                                            .with_dangerous_callback(move || {
+                                                is_open.set(true);
+                                                execute.dispatch(ServerAction::Restart);
+                                            }),
+                                    );
+                            }
+                        >
+
+                            <IconPower attr:class="flex-shrink-0 size-4"/>
+                            {move || t("server-restart")}
+                        </button>
+                    </Show>
+
+                    <Show when=move || pending.get() || result.with(|result| result.is_some())>
+                        <div class="mt-2 pt-2 px-3 pb-1 border-t border-gray-200 text-sm dark:border-gray-700">
+                            {move || match result.get() {
+                                None => {
+                                    view! {
+                                        <p class="text-gray-500 dark:text-gray-400">
+                                            {t("server-working")}
+                                        </p>
+                                    }
+                                        .into_view()
+                                }
+                                Some(ActionResult::Success(message)) => {
+                                    view! { <p class="text-teal-600 dark:text-teal-500">{message}</p> }
+                                        .into_view()
+                                }
+                                Some(ActionResult::Cluster(results)) => {
+                                    let failed = results
+                                        .iter()
+                                        .filter(|result| {
+                                            matches!(result.status, NodeApplyStatus::Error(_))
+                                        })
+                                        .count();
+                                    let has_failed = failed > 0;
+                                    view! {
+                                        <p
+                                            class="font-medium"
+                                            class:text-teal-600=!has_failed
+                                            class:text-red-600=has_failed
+                                        >
+                                            {if !has_failed {
+                                                format!(
+                                                    "Configuration reloaded on {}.",
+                                                    maybe_plural(results.len(), "node", "nodes"),
+                                                )
+                                            } else {
+                                                format!(
+                                                    "The configuration was not reloaded on {}",
+                                                    maybe_plural(failed, "node", "nodes"),
+                                                )
+                                            }}
+
+                                        </p>
+                                        <ul class="mt-2 max-h-64 overflow-y-auto space-y-1 text-xs text-gray-700 dark:text-gray-300">
+                                            {results
+                                                .into_iter()
+                                                .map(|result| {
+                                                    let status = match result.status {
+                                                        NodeApplyStatus::Success => "Applied".to_string(),
+                                                        NodeApplyStatus::Warnings(warnings) => {
+                                                            format!(
+                                                                "Applied with {}",
+                                                                maybe_plural(warnings, "warning", "warnings"),
+                                                            )
+                                                        }
+                                                        NodeApplyStatus::Error(error) => error,
+                                                    };
+                                                    view! {
+                                                        <li>
+                                                            <span class="font-medium">{result.node.id}</span>
+                                                            {format!(": {status}")}
+                                                        </li>
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </ul>
+                                    }
+                                        .into_view()
+                                }
+                                Some(ActionResult::Failed(alert)) => {
+                                    view! {
+                                        <p class="font-medium text-red-600 dark:text-red-500">
+                                            {alert.message}
+                                        </p>
+                                        <div class="mt-1 text-xs text-gray-700 dark:text-gray-300">
+                                            {alert.details}
+                                        </div>
+                                    }
+                                        .into_view()
+                                }
+                                Some(ActionResult::Messages { summary, messages }) => {
+                                    let has_errors = messages.iter().any(|message| message.is_error);
+                                    view! {
+                                        <p
+                                            class="font-medium"
+                                            class:text-red-600=has_errors
+                                            class:text-yellow-600=!has_errors
+                                        >
+                                            {summary}
+                                        </p>
+                                        <ul
+                                            class="mt-2 max-h-64 overflow-y-auto list-disc space-y-1 ps-5 text-xs text-gray-700 dark:text-gray-300"
+                                            on:click=move |_| is_open.set(false)
+                                        >
+                                            {messages
+                                                .into_iter()
+                                                .map(|message| view! { <ReloadMessageItem message/> })
+                                                .collect_view()}
+                                        </ul>
+                                    }
+                                        .into_view()
+                                }
+                            }}
+
+                        </div>
+                    </Show>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+impl ServerAction {
+    fn reload_result(&self, reload: ReloadSettings, schemas: &Schemas) -> ActionResult {
+        if reload.errors.is_empty() && reload.warnings.is_empty() {
+            ActionResult::Success(self.success_message())
+        } else {
+            ActionResult::Messages {
+                summary: if !reload.errors.is_empty() {
+                    self.error_message()
+                } else {
+                    "The configuration has warnings"
+                },
+                messages: reload.messages(schemas),
+            }
+        }
+    }
+
+    fn success_message(&self) -> &'static str {
+        match self {
+            ServerAction::Validate => "The configuration is valid.",
+            ServerAction::Reload => "Configuration reloaded.",
+            ServerAction::Restart => "Restarting server, try reloading this page in a few seconds.",
+        }
+    }
+
+    fn error_message(&self) -> &'static str {
+        match self {
+            ServerAction::Validate => "The configuration has errors",
+            ServerAction::Reload | ServerAction::Restart => {
+                "The configuration was not reloaded due to errors"
+            }
+        }
+    }
+}
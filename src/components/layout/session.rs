@@ -0,0 +1,162 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::form::{
+        input::{InputPassword, InputText},
+        FormElement,
+    },
+    core::{
+        oauth::{oauth_authenticate, AuthenticationResult},
+        schema::Schemas,
+        session::{store_grant, use_session},
+        Permissions,
+    },
+};
+
+// Asks for the password when the session could not be renewed in the
+// background. Requests waiting on the session are resumed after signing in,
+// so the page and any unsaved changes are kept.
+#[component]
+pub fn SessionExpiredModal() -> impl IntoView {
+    let session = use_session();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let show_totp = create_rw_signal(false);
+    let error = create_rw_signal(None::<String>);
+    let data = create_rw_signal(schemas.build_form("login"));
+
+    // Start with a blank form every time the session expires
+    create_effect(move |_| {
+        if session.expired.get() {
+            let username = session.auth.get_untracked().username.to_string();
+            data.set(schemas.build_form("login").with_value("login", username));
+            show_totp.set(false);
+            error.set(None);
+        }
+    });
+
+    let sign_in = create_action(move |password: &String| {
+        let password = password.clone();
+        let auth = session.auth.get_untracked();
+
+        async move {
+            match oauth_authenticate(&auth.base_url, &auth.username, &password).await {
+                AuthenticationResult::Success(response) => {
+                    store_grant(session.auth, response.grant);
+                    session.auth.update(|auth_token| {
+                        auth_token.permissions = Permissions::new(response.permissions);
+                        auth_token.is_enterprise = response.is_enterprise;
+                    });
+                    session.finish(true);
+                }
+                AuthenticationResult::TotpRequired => {
+                    show_totp.set(true);
+                }
+                AuthenticationResult::Error(err) => {
+                    error.set(Some(err.message));
+                }
+            }
+        }
+    });
+
+    let sign_out = move |_| {
+        session.finish(false);
+        use_navigate()("/login", Default::default());
+    };
+
+    view! {
+        <Show when=move || session.expired.get()>
+            <Portal mount=document().get_element_by_id("portal_root").unwrap()>
+                <div class="transition duration fixed inset-0 z-50 bg-gray-900 bg-opacity-50 dark:bg-opacity-80 hs-overlay-backdrop">
+                    <div class="open hs-overlay size-full fixed top-0 start-0 z-[80] overflow-x-hidden overflow-y-auto">
+                        <div class="hs-overlay-open:mt-7 hs-overlay-open:opacity-100 hs-overlay-open:duration-500 mt-0 opacity-100 ease-out transition-all sm:max-w-lg sm:w-full m-3 sm:mx-auto min-h-[calc(100%-3.5rem)] flex items-center">
+                            <form
+                                class="w-full flex flex-col bg-white border shadow-sm rounded-xl dark:bg-gray-800 dark:border-gray-700 dark:shadow-slate-700/[.7]"
+                                on:submit=move |ev| {
+                                    ev.prevent_default();
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            let password = match (
+                                                data.value::<String>("password").unwrap_or_default(),
+                                                data.value::<String>("totp-code"),
+                                            ) {
+                                                (password, Some(totp)) => format!("{}${}", password, totp),
+                                                (password, None) => password,
+                                            };
+                                            error.set(None);
+                                            sign_in.dispatch(password);
+                                        }
+                                    });
+                                }
+                            >
+
+                                <div class="py-3 px-4 border-b dark:border-gray-700">
+                                    <h3 class="font-bold text-gray-800 dark:text-white">
+                                        Session expired
+                                    </h3>
+                                </div>
+                                <div class="p-4 grid gap-y-4">
+                                    <p class="text-sm text-gray-800 dark:text-gray-400">
+                                        {move || {
+                                            format!(
+                                                "Sign in again as {} to continue where you left off.",
+                                                session.auth.get().username,
+                                            )
+                                        }}
+
+                                    </p>
+                                    <Show when=move || !show_totp.get()>
+                                        <div>
+                                            <label class="block text-sm mb-2 dark:text-white">
+                                                Password
+                                            </label>
+                                            <InputPassword element=FormElement::new("password", data)/>
+                                        </div>
+                                    </Show>
+                                    <Show when=move || show_totp.get()>
+                                        <div>
+                                            <label class="block text-sm mb-2 dark:text-white">
+                                                TOTP Token
+                                            </label>
+                                            <InputText element=FormElement::new("totp-code", data)/>
+                                        </div>
+                                    </Show>
+                                    <p
+                                        class="text-sm text-red-600"
+                                        class:hidden=move || error.with(|error| error.is_none())
+                                    >
+                                        {move || error.get().unwrap_or_default()}
+                                    </p>
+                                </div>
+                                <div class="flex justify-end items-center gap-x-2 py-3 px-4 border-t dark:border-gray-700">
+                                    <button
+                                        type="button"
+                                        class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                        on:click=sign_out
+                                    >
+                                        Sign out
+                                    </button>
+                                    <button
+                                        type="submit"
+                                        class="py-3 px-4 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                        disabled=move || sign_in.pending().get()
+                                    >
+                                        Sign in
+                                    </button>
+                                </div>
+                            </form>
+                        </div>
+                    </div>
+                </div>
+            </Portal>
+        </Show>
+    }
+}
@@ -61,7 +61,7 @@ pub fn ToggleNavigation(menu_items: Vec<MenuItem>, show_sidebar: RwSignal<bool>)
     };
 
     view! {
-        <div class="sticky top-0 inset-x-0 z-20 bg-white border-y px-4 sm:px-6 md:px-8 lg:hidden dark:bg-gray-800 dark:border-gray-700">
+        <div class="print:hidden sticky top-0 inset-x-0 z-20 bg-white border-y px-4 sm:px-6 md:px-8 lg:hidden dark:bg-gray-800 dark:border-gray-700">
             <div class="flex items-center py-4">
                 <button
                     type="button"
@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::icon::{IconSparkles, IconXMark},
+    core::changelog::{mark_releases_seen, unseen_releases, Release, RELEASES},
+};
+
+#[derive(Clone, Copy)]
+pub struct WhatsNew(RwSignal<Vec<&'static Release>>);
+
+pub fn init_whats_new() {
+    provide_context(WhatsNew(create_rw_signal(unseen_releases())));
+}
+
+pub fn use_whats_new() -> WhatsNew {
+    expect_context::<WhatsNew>()
+}
+
+impl WhatsNew {
+    // Reopens the panel with the highlights of the running version.
+    pub fn show(&self) {
+        self.0.set(RELEASES.iter().take(1).collect());
+    }
+
+    pub fn dismiss(&self) {
+        mark_releases_seen();
+        self.0.set(vec![]);
+    }
+}
+
+#[component]
+pub fn WhatsNewPanel() -> impl IntoView {
+    let whats_new = use_whats_new();
+
+    move || {
+        let releases = whats_new.0.get();
+        (!releases.is_empty()).then(|| {
+            view! {
+                <div
+                    class="mb-4 bg-blue-50 border-s-4 border-blue-500 p-4 dark:bg-blue-800/30"
+                    role="region"
+                    aria-label="What's new"
+                >
+                    <div class="flex gap-x-3">
+                        <IconSparkles attr:class="flex-shrink-0 size-5 text-blue-800 dark:text-blue-400"/>
+                        <div class="grow">
+                            <h3 class="text-sm font-semibold text-blue-800 dark:text-white">
+                                {format!("What's new in version {}", releases[0].version)}
+                            </h3>
+                            {releases
+                                .into_iter()
+                                .map(|release| {
+                                    view! {
+                                        <ul class="mt-2 space-y-1 text-sm text-blue-700 dark:text-neutral-400">
+                                            {release
+                                                .highlights
+                                                .iter()
+                                                .map(|highlight| {
+                                                    view! {
+                                                        <li>
+                                                            {match highlight.link {
+                                                                Some(link) => {
+                                                                    view! {
+                                                                        <a
+                                                                            class="font-semibold underline hover:text-blue-900 dark:hover:text-blue-300"
+                                                                            href=link
+                                                                            on:click=move |_| whats_new.dismiss()
+                                                                        >
+                                                                            {highlight.title}
+                                                                        </a>
+                                                                    }
+                                                                        .into_view()
+                                                                }
+                                                                None => {
+                                                                    view! {
+                                                                        <span class="font-semibold">
+                                                                            {highlight.title}
+                                                                        </span>
+                                                                    }
+                                                                        .into_view()
+                                                                }
+                                                            }}
+                                                            {format!(": {}", highlight.description)}
+                                                        </li>
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </ul>
+                                    }
+                                })
+                                .collect_view()}
+                        </div>
+                        <button
+                            type="button"
+                            class="flex-shrink-0 inline-flex justify-center items-center size-5 rounded-lg text-blue-800 hover:opacity-75 dark:text-blue-400"
+                            title="Dismiss"
+                            on:click=move |_| whats_new.dismiss()
+                        >
+                            <IconXMark attr:class="flex-shrink-0 size-4"/>
+                        </button>
+                    </div>
+                </div>
+            }
+        })
+    }
+}
@@ -8,14 +8,31 @@ use leptos::*;
 
 use crate::components::list::ItemSelection;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+// Headers with a sort key can be clicked to sort the list by that column,
+// the sorting itself is done by the caller (usually server-side).
+#[derive(Clone)]
+pub struct ColumnSort {
+    pub keys: Vec<Option<&'static str>>,
+    pub current: Signal<(String, SortOrder)>,
+    pub on_sort: Callback<(&'static str, SortOrder)>,
+}
+
 #[component]
 pub fn ColumnList(
     #[prop(into)] headers: MaybeSignal<Vec<String>>,
     #[prop(into, optional)] has_select_all: bool,
+    #[prop(optional)] sort: Option<ColumnSort>,
     children: Children,
 ) -> impl IntoView {
     let headers_ = headers.clone();
     let total_columns = create_memo(move |_| headers_.get().len());
+    let sort = store_value(sort);
 
     view! {
         <thead class="bg-gray-50 dark:bg-slate-800">
@@ -55,14 +72,56 @@ pub fn ColumnList(
                         } else {
                             "px-6 py-3 text-start"
                         };
-                        view! {
-                            <th scope="col" class=class>
-                                <div class="flex items-center gap-x-2">
-                                    <span class="text-xs font-semibold uppercase tracking-wide text-gray-800 dark:text-gray-200">
-                                        {header}
-                                    </span>
-                                </div>
-                            </th>
+                        let sort_key = sort
+                            .with_value(|sort| {
+                                sort.as_ref().and_then(|sort| sort.keys.get(idx).copied().flatten())
+                            });
+                        match sort_key.zip(sort.get_value()) {
+                            Some((key, sort)) => {
+                                let current = sort.current;
+                                let indicator = move || {
+                                    let (current_key, order) = current.get();
+                                    match order {
+                                        _ if current_key != key => "",
+                                        SortOrder::Ascending => "▲",
+                                        SortOrder::Descending => "▼",
+                                    }
+                                };
+                                view! {
+                                    <th scope="col" class=class>
+                                        <button
+                                            type="button"
+                                            class="flex items-center gap-x-2 text-xs font-semibold uppercase tracking-wide text-gray-800 hover:text-blue-600 dark:text-gray-200 dark:hover:text-blue-500"
+                                            on:click=move |_| {
+                                                let (current_key, order) = current.get_untracked();
+                                                let order = if current_key == key
+                                                    && order == SortOrder::Descending
+                                                {
+                                                    SortOrder::Ascending
+                                                } else {
+                                                    SortOrder::Descending
+                                                };
+                                                sort.on_sort.call((key, order));
+                                            }
+                                        >
+
+                                            {header}
+                                            <span class="text-gray-500">{indicator}</span>
+                                        </button>
+                                    </th>
+                                }
+                            }
+                            None => {
+                                view! {
+                                    <th scope="col" class=class>
+                                        <div class="flex items-center gap-x-2">
+                                            <span class="text-xs font-semibold uppercase tracking-wide text-gray-800 dark:text-gray-200">
+                                                {header}
+                                            </span>
+                                        </div>
+                                    </th>
+                                }
+                            }
                         }
                     }
                 />
@@ -6,7 +6,10 @@
 
 use leptos::*;
 
-use crate::components::icon::{IconArrowLeft, IconArrowRight};
+use crate::{
+    components::icon::{IconArrowLeft, IconArrowRight},
+    core::format::format_count,
+};
 
 #[component]
 pub fn Pagination(
@@ -26,7 +29,7 @@ pub fn Pagination(
 
                     <p class="text-sm text-gray-600 dark:text-gray-400">
                         <span class="font-semibold text-gray-800 dark:text-gray-200">
-                            {move || { total_results.get().map_or(0, |r| r) }}
+                            {move || { format_count(total_results.get().map_or(0, |r| r) as u64) }}
                         </span>
                         " results. Page"
 
@@ -12,8 +12,12 @@ use crate::{
     components::icon::{
         IconCheckCircle, IconExclamationCircle, IconExclamationTriangle, IconXMark,
     },
-    core::http::{self, ManagementApiError},
-    pages::config::{ConfigError, ConfigWarning, ReloadSettings},
+    core::{
+        http::{self, ManagementApiError},
+        i18n::{t, tr},
+        schema::Schemas,
+    },
+    pages::config::{ReloadMessage, ReloadSettings},
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -324,54 +328,15 @@ impl From<http::Error> for Alert {
     }
 }
 
-impl From<ReloadSettings> for Alert {
-    fn from(value: ReloadSettings) -> Self {
+impl Alert {
+    pub fn from_reload(value: ReloadSettings, schemas: &Schemas) -> Self {
         if value.errors.is_empty() && value.warnings.is_empty() {
-            Alert::success("Settings successfully reloaded")
+            Alert::success(t("reload-success"))
         } else {
             let messages = value
-                .errors
-                .iter()
-                .map(|(key, error)| {
-                    view! {
-                        <li>
-                            {match error {
-                                ConfigError::Parse { error } => {
-                                    format!("Failed to parse {key:?}: {error}")
-                                }
-                                ConfigError::Build { error } => {
-                                    format!("Build error for {key:?}: {error}")
-                                }
-                                ConfigError::Macro { error } => {
-                                    format!("Macro error on {key:?}: {error}")
-                                }
-                            }}
-
-                        </li>
-                    }
-                })
-                .chain(value.warnings.iter().map(|(key, warning)| {
-                    view! {
-                        <li>
-                            {match warning {
-                                ConfigWarning::Missing => format!("Waring: Missing setting {key:?}"),
-                                ConfigWarning::AppliedDefault { default } => {
-                                    format!("Warning: Applied default value {default:?} to {key:?}")
-                                }
-                                ConfigWarning::Unread { value } => {
-                                    format!("Warning: Unread value {value:?} for {key:?}")
-                                }
-                                ConfigWarning::Build { error } => {
-                                    format!("Error for {key:?}: {error}")
-                                }
-                                ConfigWarning::Parse { error } => {
-                                    format!("Warning: Parse error for {key:?}: {error}")
-                                }
-                            }}
-
-                        </li>
-                    }
-                }))
+                .messages(schemas)
+                .into_iter()
+                .map(|message| view! { <ReloadMessageItem message/> })
                 .collect_view();
 
             Alert {
@@ -380,7 +345,7 @@ impl From<ReloadSettings> for Alert {
                 } else {
                     AlertType::Error
                 },
-                message: "Failed to reload settings".to_string(),
+                message: t("reload-failed"),
                 details: Some(
                     view! { <ul class="list-disc space-y-1 ps-5">{messages}</ul> }.into_view(),
                 ),
@@ -389,3 +354,23 @@ impl From<ReloadSettings> for Alert {
         }
     }
 }
+
+#[component]
+pub fn ReloadMessageItem(message: ReloadMessage) -> impl IntoView {
+    view! {
+        <li>
+            {message.text}
+            {message
+                .location
+                .map(|location| {
+                    view! {
+                        " "
+                        <a class="font-medium underline hover:opacity-80" href=location.url>
+                            {tr("reload-edit", &[("setting", location.title)])}
+                        </a>
+                    }
+                })}
+
+        </li>
+    }
+}
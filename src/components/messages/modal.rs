@@ -17,6 +17,7 @@ pub struct Modal {
     pub is_open: bool,
     pub title: String,
     pub message: String,
+    pub details: Vec<(String, String)>,
     pub button_text: String,
     pub danger: bool,
     pub on_confirm: Arc<dyn Fn()>,
@@ -94,6 +95,29 @@ pub fn Modal() -> impl IntoView {
                                     <p class="text-gray-800 dark:text-gray-400">
                                         {move || { modal.get().message }}
                                     </p>
+                                    <dl
+                                        class="mt-3 grid grid-cols-3 gap-x-4 gap-y-2 text-sm"
+                                        class:hidden=move || modal.get().details.is_empty()
+                                    >
+                                        {move || {
+                                            modal
+                                                .get()
+                                                .details
+                                                .into_iter()
+                                                .map(|(name, value)| {
+                                                    view! {
+                                                        <dt class="font-medium text-gray-800 dark:text-gray-200">
+                                                            {name}
+                                                        </dt>
+                                                        <dd class="col-span-2 text-gray-600 break-words dark:text-gray-400">
+                                                            {value}
+                                                        </dd>
+                                                    }
+                                                })
+                                                .collect_view()
+                                        }}
+
+                                    </dl>
                                 </div>
                                 <div class="flex justify-end items-center gap-x-2 py-3 px-4 border-t dark:border-gray-700">
                                     <button
@@ -150,6 +174,7 @@ impl Modal {
             is_open: true,
             title: title.into(),
             message: String::new(),
+            details: Vec::new(),
             button_text: "Confirm".to_string(),
             danger: false,
             on_confirm: Arc::new(|| {}),
@@ -160,6 +185,17 @@ impl Modal {
         self
     }
 
+    pub fn with_details(
+        mut self,
+        details: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.details = details
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+        self
+    }
+
     pub fn with_button(mut self, button_text: impl Into<String>) -> Self {
         self.button_text = button_text.into();
         self
@@ -184,6 +220,7 @@ impl Default for Modal {
             is_open: false,
             title: String::new(),
             message: String::new(),
+            details: Vec::new(),
             button_text: "Confirm".to_string(),
             danger: false,
             on_confirm: Arc::new(|| {}),
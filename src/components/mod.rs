@@ -6,13 +6,19 @@
 
 pub mod badge;
 pub mod card;
+pub mod chart;
+pub mod download;
 pub mod form;
 pub mod icon;
 pub mod layout;
 pub mod list;
 pub mod messages;
+pub mod progress;
 pub mod report;
 pub mod skeleton;
+pub mod time;
+pub mod timeline;
+pub mod upload;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Color {
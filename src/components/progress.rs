@@ -0,0 +1,49 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::core::task::{TaskProgress, TaskStatus};
+
+#[component]
+pub fn TaskProgressBar(status: TaskStatus, progress: Option<TaskProgress>) -> impl IntoView {
+    let percent = match (status, progress) {
+        (TaskStatus::Completed, _) => 100,
+        (_, Some(progress)) => progress.percent(),
+        _ => 0,
+    };
+    let details = progress
+        .map(|progress| format!("{} / {}", progress.done, progress.total))
+        .unwrap_or_else(|| format!("{percent}%"));
+    let bar_class = match status {
+        TaskStatus::Failed | TaskStatus::Cancelled => "bg-red-600",
+        TaskStatus::Completed => "bg-teal-500",
+        TaskStatus::Queued | TaskStatus::Running => "bg-blue-600",
+    };
+
+    view! {
+        <div class="flex items-center gap-x-3 whitespace-nowrap">
+            <div
+                class="flex w-full h-2 bg-gray-200 rounded-full overflow-hidden dark:bg-neutral-700"
+                role="progressbar"
+                aria-valuenow=percent
+                aria-valuemin="0"
+                aria-valuemax="100"
+            >
+                <div
+                    class=format!(
+                        "flex flex-col justify-center rounded-full overflow-hidden {bar_class} transition duration-500",
+                    )
+
+                    style=format!("width: {percent}%")
+                ></div>
+            </div>
+            <div class="w-20 text-end">
+                <span class="text-sm text-gray-800 dark:text-white tabular-nums">{details}</span>
+            </div>
+        </div>
+    }
+}
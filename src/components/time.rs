@@ -0,0 +1,32 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use leptos::*;
+
+use crate::pages::FormatDateTime;
+
+// Timestamp rendered in the preferred language and time zone, hovering shows
+// the exact time in ISO 8601.
+#[component]
+pub fn Timestamp(
+    date: DateTime<Utc>,
+    #[prop(optional)] relative: bool,
+    #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
+) -> impl IntoView {
+    view! {
+        <time
+            {..attrs}
+            datetime=date.to_rfc3339_opts(SecondsFormat::Secs, true)
+            title=move || date.format_iso()
+        >
+            {move || {
+                if relative { date.format_relative() } else { date.format_date_time() }
+            }}
+
+        </time>
+    }
+}
@@ -0,0 +1,81 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::A;
+
+use super::Color;
+
+#[component]
+pub fn Timeline(children: Children) -> impl IntoView {
+    view! { <div>{children()}</div> }
+}
+
+#[component]
+pub fn TimelineItem(
+    color: Color,
+    #[prop(into)] title: String,
+    #[prop(into)] time: String,
+    #[prop(into, default = None)] subtitle: Option<String>,
+    #[prop(into, default = None)] href: Option<String>,
+    #[prop(optional)] items: Vec<String>,
+) -> impl IntoView {
+    let dot = match color {
+        Color::Blue => "bg-blue-600 dark:bg-blue-500",
+        Color::Red => "bg-red-600 dark:bg-red-500",
+        Color::Yellow => "bg-yellow-500 dark:bg-yellow-400",
+        Color::Green => "bg-teal-600 dark:bg-teal-500",
+        Color::Gray => "bg-gray-400 dark:bg-gray-600",
+    };
+    let title = match href {
+        Some(href) => view! {
+            <A href=href class="hover:underline">
+                {title}
+            </A>
+        }
+        .into_view(),
+        None => title.into_view(),
+    };
+
+    view! {
+        <div class="group relative flex gap-x-5">
+
+            <div class="relative group-last:after:hidden after:absolute after:top-8 after:bottom-2 after:start-3 after:w-px after:-translate-x-[0.5px] after:bg-gray-200 dark:after:bg-neutral-700">
+                <div class="relative z-10 size-6 flex justify-center items-center">
+                    <div class=format!("size-2.5 rounded-full {dot}")></div>
+                </div>
+            </div>
+
+            <div class="grow pb-8 group-last:pb-0">
+                <h3 class="mb-1 text-xs text-gray-600 dark:text-neutral-400">{time}</h3>
+                <p class="font-semibold text-sm text-gray-800 dark:text-neutral-200">{title}</p>
+                {subtitle
+                    .map(|subtitle| {
+                        view! {
+                            <p class="mt-1 text-sm text-gray-600 dark:text-neutral-400">
+                                {subtitle}
+                            </p>
+                        }
+                    })}
+                <ul class="list-disc ms-6 mt-3 space-y-1.5">
+
+                    {items
+                        .into_iter()
+                        .map(|item| {
+                            view! {
+                                <li class="ps-1 text-sm text-gray-600 dark:text-neutral-400">
+                                    {item}
+                                </li>
+                            }
+                        })
+                        .collect_view()}
+
+                </ul>
+            </div>
+
+        </div>
+    }
+}
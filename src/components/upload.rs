@@ -0,0 +1,345 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use web_sys::{File, HtmlInputElement};
+use web_time::Instant;
+
+use crate::{
+    components::{
+        form::button::Button,
+        icon::IconArrowUpTray,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        format::{format_bytes, format_rate},
+        http,
+        oauth::use_authorization,
+        poll::use_polling,
+        upload::{ChunkedUpload, UploadKind},
+    },
+};
+
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UploadState {
+    Starting,
+    Uploading,
+    Paused,
+    Interrupted,
+    Verifying,
+    Completed,
+    Failed,
+}
+
+#[component]
+pub fn FileUpload(
+    #[prop(into)] kind: Signal<UploadKind>,
+    #[prop(optional, into)] account: Signal<Option<String>>,
+    #[prop(optional, into)] on_validate: Option<Callback<(), bool>>,
+    #[prop(optional, into)] on_complete: Option<Callback<String>>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let file = create_rw_signal(None::<File>);
+    let upload = store_value(None::<ChunkedUpload>);
+    let state = create_rw_signal(None::<UploadState>);
+    let progress = create_rw_signal((0u64, 0u64));
+    let rate = create_rw_signal(None::<f64>);
+    let pause_requested = create_rw_signal(false);
+    let cancel_requested = create_rw_signal(false);
+
+    let run = move |resync: bool| {
+        let Some(mut current) = upload.try_update_value(|upload| upload.take()).flatten() else {
+            return;
+        };
+        let auth = auth.get_untracked();
+        pause_requested.set(false);
+        state.set(Some(UploadState::Uploading));
+        rate.set(None);
+
+        spawn_local(async move {
+            let mut result = if resync {
+                current.resync(&auth).await
+            } else {
+                Ok(())
+            };
+
+            let started = (Instant::now(), current.offset());
+            while result.is_ok() && !current.is_sent() && !cancel_requested.get_untracked() {
+                progress.set((current.offset(), current.size()));
+                let elapsed = started.0.elapsed().as_secs_f64();
+                if elapsed > 0.0 && current.offset() > started.1 {
+                    rate.set(Some((current.offset() - started.1) as f64 / elapsed));
+                }
+                if pause_requested.get_untracked() {
+                    state.set(Some(UploadState::Paused));
+                    upload.try_set_value(Some(current));
+                    return;
+                }
+                result = current.send_next_chunk(&auth).await;
+            }
+            progress.set((current.offset(), current.size()));
+
+            // Cancelled while a chunk was being sent
+            if cancel_requested.get_untracked() {
+                let _ = current.cancel(&auth).await;
+                return;
+            }
+
+            if result.is_ok() {
+                state.set(Some(UploadState::Verifying));
+                result = current.complete(&auth).await;
+            }
+
+            match result {
+                Ok(_) => {
+                    state.set(Some(UploadState::Completed));
+                    alert.set(Alert::success(format!(
+                        "{} uploaded and verified successfully.",
+                        current.name()
+                    )));
+                    if let Some(on_complete) = on_complete {
+                        on_complete.call(current.name());
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    upload.try_set_value(Some(current));
+                    state.set(Some(UploadState::Paused));
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::Network(_)) => {
+                    // Retried by the poller below once the server is reachable
+                    upload.try_set_value(Some(current));
+                    state.set(Some(UploadState::Interrupted));
+                }
+                Err(err) => {
+                    state.set(Some(UploadState::Failed));
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    };
+
+    let retry = use_polling(RETRY_INTERVAL);
+    create_effect(move |_| {
+        retry.tick();
+        if state.get_untracked() == Some(UploadState::Interrupted) {
+            run(true);
+        }
+    });
+
+    let start = move || {
+        let Some(selected) = file.get_untracked() else {
+            alert.set(Alert::warning("Select a file to upload first."));
+            return;
+        };
+        if on_validate.is_some_and(|on_validate| !on_validate.call(())) {
+            return;
+        }
+        let auth = auth.get_untracked();
+        let kind = kind.get_untracked();
+        let account = account.get_untracked();
+        progress.set((0, selected.size() as u64));
+        cancel_requested.set(false);
+        state.set(Some(UploadState::Starting));
+
+        spawn_local(async move {
+            match ChunkedUpload::start(&auth, selected, kind, account.as_deref()).await {
+                Ok(started) if cancel_requested.get_untracked() => {
+                    let _ = started.cancel(&auth).await;
+                }
+                Ok(started) => {
+                    upload.set_value(Some(started));
+                    run(false);
+                }
+                Err(http::Error::Unauthorized) => {
+                    state.set(None);
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    state.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    };
+
+    // Uploads that are being sent are deleted by `run` once the current
+    // chunk completes, paused or interrupted ones are deleted here.
+    let cancel = move || {
+        cancel_requested.set(true);
+        if let Some(current) = upload.try_update_value(|upload| upload.take()).flatten() {
+            let auth = auth.get_untracked();
+            spawn_local(async move {
+                let _ = current.cancel(&auth).await;
+            });
+        }
+        state.set(None);
+        progress.set((0, 0));
+    };
+
+    let is_idle = Signal::derive(move || {
+        matches!(
+            state.get(),
+            None | Some(UploadState::Completed | UploadState::Failed)
+        )
+    });
+    let percent = move || {
+        let (done, total) = progress.get();
+        (done.min(total) * 100)
+            .checked_div(total)
+            .unwrap_or_default()
+    };
+
+    view! {
+        <div class="space-y-3">
+            <label class="block">
+                <span class="sr-only">Choose file</span>
+                <input
+                    type="file"
+                    class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 file:disabled:opacity-50 file:disabled:pointer-events-none dark:text-neutral-500"
+                    accept=move || kind.get().accept()
+                    disabled=move || !is_idle.get()
+                    on:change=move |ev| {
+                        let selected = event_target::<HtmlInputElement>(&ev)
+                            .files()
+                            .and_then(|files| files.get(0));
+                        state.set(None);
+                        progress.set((0, selected.as_ref().map_or(0, |f| f.size() as u64)));
+                        file.set(selected);
+                    }
+                />
+
+            </label>
+
+            <Show when=move || state.get().is_some()>
+                <div class="flex items-center gap-x-3 whitespace-nowrap">
+                    <div
+                        class="flex w-full h-2 bg-gray-200 rounded-full overflow-hidden dark:bg-neutral-700"
+                        role="progressbar"
+                        aria-valuenow=percent
+                        aria-valuemin="0"
+                        aria-valuemax="100"
+                    >
+                        <div
+                            class=move || {
+                                format!(
+                                    "flex flex-col justify-center rounded-full overflow-hidden {} transition duration-500",
+                                    state.get().map_or("bg-blue-600", UploadState::bar_class),
+                                )
+                            }
+
+                            style=move || format!("width: {}%", percent())
+                        ></div>
+                    </div>
+                    <div class="w-12 text-end">
+                        <span class="text-sm text-gray-800 dark:text-white tabular-nums">
+                            {move || format!("{}%", percent())}
+                        </span>
+                    </div>
+                </div>
+                <p class="text-sm text-gray-500 dark:text-neutral-500 tabular-nums">
+                    {move || {
+                        let (done, total) = progress.get();
+                        let state = state.get();
+                        let rate = rate
+                            .get()
+                            .filter(|_| state == Some(UploadState::Uploading))
+                            .map(|rate| format!(" ({})", format_rate(rate)))
+                            .unwrap_or_default();
+                        format!(
+                            "{} - {} of {}{rate}",
+                            state.map_or("", UploadState::label),
+                            format_bytes(done),
+                            format_bytes(total),
+                        )
+                    }}
+
+                </p>
+            </Show>
+
+            <div class="flex justify-end gap-x-2">
+                <Show when=move || !is_idle.get()>
+                    <Button
+                        text="Cancel"
+                        color=Color::Red
+                        on_click=move |_| {
+                            cancel();
+                        }
+                    />
+
+                </Show>
+                <Show when=move || {
+                    matches!(state.get(), Some(UploadState::Uploading))
+                }>
+                    <Button
+                        text="Pause"
+                        color=Color::Gray
+                        disabled=pause_requested
+                        on_click=move |_| {
+                            pause_requested.set(true);
+                        }
+                    />
+
+                </Show>
+                <Show when=move || {
+                    matches!(state.get(), Some(UploadState::Paused | UploadState::Interrupted))
+                }>
+                    <Button
+                        text="Resume"
+                        color=Color::Blue
+                        on_click=move |_| {
+                            run(true);
+                        }
+                    />
+
+                </Show>
+                <Show when=move || is_idle.get()>
+                    <Button
+                        text="Upload"
+                        color=Color::Blue
+                        disabled=Signal::derive(move || file.with(|file| file.is_none()))
+                        on_click=move |_| {
+                            start();
+                        }
+                    >
+
+                        <IconArrowUpTray attr:class="flex-shrink-0 size-4"/>
+                    </Button>
+                </Show>
+            </div>
+        </div>
+    }
+}
+
+impl UploadState {
+    fn label(self) -> &'static str {
+        match self {
+            UploadState::Starting => "Preparing upload",
+            UploadState::Uploading => "Uploading",
+            UploadState::Paused => "Paused",
+            UploadState::Interrupted => "Connection lost, retrying",
+            UploadState::Verifying => "Verifying integrity",
+            UploadState::Completed => "Completed",
+            UploadState::Failed => "Failed",
+        }
+    }
+
+    fn bar_class(self) -> &'static str {
+        match self {
+            UploadState::Failed => "bg-red-600",
+            UploadState::Completed => "bg-teal-500",
+            UploadState::Paused | UploadState::Interrupted => "bg-yellow-500",
+            _ => "bg-blue-600",
+        }
+    }
+}
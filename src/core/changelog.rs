@@ -0,0 +1,301 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{LocalStorage, Storage};
+
+const SEEN_VERSION_STORAGE_KEY: &str = "webadmin_seen_version";
+
+pub struct Release {
+    pub version: &'static str,
+    pub highlights: &'static [Highlight],
+}
+
+pub struct Highlight {
+    pub title: &'static str,
+    pub description: &'static str,
+    pub link: Option<&'static str>,
+}
+
+// Newest release first. Only user-visible additions belong here, fixes are
+// listed in CHANGELOG.md.
+pub static RELEASES: &[Release] = &[
+    Release {
+        version: "0.1.22",
+        highlights: &[
+            Highlight {
+                title: "Quick settings",
+                description: "Pin individual fields from any settings form to a personal page.",
+                link: Some("/settings/quick"),
+            },
+            Highlight {
+                title: "Migration assistant",
+                description: "Translate Postfix and Dovecot configuration snippets into Stalwart settings.",
+                link: Some("/settings/migrate"),
+            },
+            Highlight {
+                title: "Command palette",
+                description: "Press Ctrl+K to jump to any page, reopen recent objects or run common actions.",
+                link: None,
+            },
+            Highlight {
+                title: "Languages",
+                description: "The interface can be switched to German, Spanish or French from the header.",
+                link: None,
+            },
+            Highlight {
+                title: "Metrics retention",
+                description: "Keep metrics history at several resolutions and view up to a year on the dashboard.",
+                link: Some("/settings/telemetry-history/edit"),
+            },
+            Highlight {
+                title: "Localized dates",
+                description: "Dates follow the selected language and time zone, with relative times in queue, report and log tables.",
+                link: None,
+            },
+            Highlight {
+                title: "Load balancing",
+                description: "PostgreSQL, MySQL and LDAP backends can spread queries across weighted endpoints, with live health for each one.",
+                link: None,
+            },
+            Highlight {
+                title: "Certificate deployment",
+                description: "Renewed ACME certificates can be pushed to webhooks, S3 buckets and SFTP servers, with a history of each push.",
+                link: Some("/settings/acme-deploy"),
+            },
+            Highlight {
+                title: "Server controls",
+                description: "Validate, reload or restart from the header, with configuration errors linking to the form of each setting.",
+                link: None,
+            },
+            Highlight {
+                title: "Live queue and report lists",
+                description: "The queue and report lists update as soon as messages or reports change on the server, and refresh periodically when live updates are not available.",
+                link: Some("/manage/queue/messages"),
+            },
+            Highlight {
+                title: "Size and rate fields",
+                description: "Size limits such as JMAP request and upload sizes are entered with a KB, MB or GB unit selector, socket buffers with a slider, and sizes and rates are validated before saving.",
+                link: None,
+            },
+            Highlight {
+                title: "IP reputation check",
+                description: "Check the outbound addresses against public DNS blocklists with links to request removal, and verify that their reverse DNS matches the EHLO hostname.",
+                link: Some("/manage/troubleshoot/reputation"),
+            },
+            Highlight {
+                title: "Queue schedule editor",
+                description: "Retry and delay notification schedules are edited as lists of durations, with a timeline of delivery attempts up to message expiry and checks for schedules that exceed it.",
+                link: Some("/settings/smtp-out-queue/edit"),
+            },
+            Highlight {
+                title: "TLS reporting overview",
+                description: "Received SMTP TLS reports are now aggregated per policy domain over a selectable time range, with successful and failed sessions charted per day and a breakdown of failure types that links to the individual reports.",
+                link: Some("/manage/reports/tls/overview"),
+            },
+            Highlight {
+                title: "Hostname DNS check",
+                description: "Saving the server hostname now checks that it resolves and that the reverse DNS of its addresses points back to it, a common cause of rejected outbound mail.",
+                link: Some("/settings/network/edit"),
+            },
+            Highlight {
+                title: "Field help drawer",
+                description: "Settings with extended documentation now have a More link next to their label that opens a side panel with the full help text, a link to the documentation and example values you can copy.",
+                link: None,
+            },
+            Highlight {
+                title: "Active sessions",
+                description: "Watch the IMAP, SMTP and HTTP connections open on the server, with their remote address, account, duration and traffic, and terminate the ones that misbehave.",
+                link: Some("/manage/sessions"),
+            },
+            Highlight {
+                title: "JMAP session inspector",
+                description: "Fetch the JMAP session of any account and browse its capabilities, account ids and endpoints to debug client interoperability.",
+                link: Some("/manage/troubleshoot/jmap"),
+            },
+            Highlight {
+                title: "Client autoconfiguration preview",
+                description: "See exactly what Thunderbird autoconfig and Outlook autodiscover return for an address, along with the DNS records clients need to find them.",
+                link: Some("/manage/troubleshoot/autoconfig"),
+            },
+            Highlight {
+                title: "Sieve script viewer",
+                description: "Review the active and stored Sieve scripts of an account and deactivate a script that is losing mail, with the action and its reason recorded in the audit log.",
+                link: Some("/manage/sieve"),
+            },
+            Highlight {
+                title: "Mailbox migration",
+                description: "Migrate accounts from another IMAP server or a Maildir tree with a guided wizard: map accounts by hand or from a CSV file, review a dry run and follow each account as it is copied.",
+                link: Some("/manage/migration"),
+            },
+            Highlight {
+                title: "Value provenance",
+                description: "Inspect any settings field to see its raw stored keys, who changed it last and whether it came from a default, an import or a manual edit.",
+                link: None,
+            },
+            Highlight {
+                title: "Storage health",
+                description: "See the connection state and recent operation latency of every data, blob, full-text and lookup store in the performance dashboard.",
+                link: Some("/manage/dashboard/performance"),
+            },
+            Highlight {
+                title: "Metric thresholds",
+                description: "Set warning and critical thresholds on dashboard widgets to color them, and follow the overall server health from the indicator in the header.",
+                link: Some("/manage/dashboard/overview"),
+            },
+            Highlight {
+                title: "SQL query console",
+                description: "Run the lookup queries of an SQL store with a sample account name or address and see the rows returned, to debug directory queries.",
+                link: Some("/manage/store/query"),
+            },
+            Highlight {
+                title: "Troubleshooting runbooks",
+                description: "Guided checks for deferred mail, failed logins and certificates that do not renew, with a report that can be copied for support.",
+                link: Some("/manage/troubleshoot/runbooks"),
+            },
+            Highlight {
+                title: "Bind address checks",
+                description: "Listener forms warn when a bind address and port are already used by another listener, with a link to it.",
+                link: Some("/settings/listener"),
+            },
+            Highlight {
+                title: "Deliverability probe",
+                description: "Send a message to a seed mailbox at Gmail, Outlook or another provider and see whether it arrived, in which folder and with which authentication results.",
+                link: Some("/manage/troubleshoot/probe"),
+            },
+            Highlight {
+                title: "Listener wizard",
+                description: "Create SMTP, submission, IMAP and HTTPS listeners from presets, with port conflicts checked before saving.",
+                link: Some("/settings/listener-wizard"),
+            },
+            Highlight {
+                title: "Cluster reload",
+                description: "Reloading from forms, lists, quick settings or the server menu in a cluster reloads every node and shows the result and version of each one, failed nodes can be retried individually.",
+                link: None,
+            },
+            Highlight {
+                title: "Component restarts",
+                description: "Restart SMTP listeners, the FTS indexer or the ACME manager on their own, with the uptime and last error of each component.",
+                link: Some("/manage/maintenance"),
+            },
+            Highlight {
+                title: "Bulk edit",
+                description: "Settings lists can apply one field value to all selected entries in a single update, skipping entries where the field does not apply.",
+                link: None,
+            },
+            Highlight {
+                title: "Frequently adjusted settings",
+                description: "The dashboard lists the settings changed most often and flags those changed several times a week as candidates for automation.",
+                link: Some("/manage/dashboard/overview"),
+            },
+            Highlight {
+                title: "Duplicate objects",
+                description: "Settings lists have a Duplicate action that opens a new form pre-filled with the values of an existing object.",
+                link: None,
+            },
+            Highlight {
+                title: "AUTH mechanisms",
+                description: "Choose the SASL mechanisms offered on each SMTP listener, with warnings for plaintext mechanisms offered without TLS.",
+                link: Some("/settings/smtp-auth-mechanisms"),
+            },
+            Highlight {
+                title: "Secret reveal",
+                description: "Administrators with the settings-secrets-read permission can reveal stored secrets such as TSIG keys, every reveal is audited.",
+                link: None,
+            },
+            Highlight {
+                title: "DKIM verifier",
+                description: "Paste a signed message to check its DKIM signatures, the header fields they cover and why verification failed.",
+                link: Some("/manage/troubleshoot/dkim"),
+            },
+            Highlight {
+                title: "Virtual queues",
+                description: "Size, throughput and pause controls for each named delivery queue.",
+                link: Some("/manage/queue/virtual"),
+            },
+            Highlight {
+                title: "Audit log",
+                description: "Filter administrative actions by actor, object and date, and export them.",
+                link: Some("/manage/audit"),
+            },
+            Highlight {
+                title: "Summary digest",
+                description: "Periodic e-mail with queue statistics, errors, certificates and storage growth.",
+                link: Some("/settings/metrics-digest/edit"),
+            },
+            Highlight {
+                title: "Directory export",
+                description: "Download accounts, groups, lists and domains as CSV or JSON.",
+                link: Some("/manage/directory/accounts"),
+            },
+            Highlight {
+                title: "Form drafts",
+                description: "Unsaved changes to settings and directory forms can be restored after leaving the page.",
+                link: None,
+            },
+        ],
+    },
+    Release {
+        version: "0.1.21",
+        highlights: &[
+            Highlight {
+                title: "Spam filter settings",
+                description: "New configuration sections and options for the spam filter.",
+                link: Some("/settings/spam-settings/edit"),
+            },
+        ],
+    },
+    Release {
+        version: "0.1.20",
+        highlights: &[
+            Highlight {
+                title: "Delivery troubleshooting",
+                description: "Diagnose delivery and DMARC problems for a given address.",
+                link: Some("/manage/troubleshoot/delivery"),
+            },
+            Highlight {
+                title: "Azure blob storage",
+                description: "Store blobs in Azure containers.",
+                link: Some("/settings/store"),
+            },
+            Highlight {
+                title: "External list members",
+                description: "Mailing lists can include addresses outside this server.",
+                link: Some("/manage/directory/lists"),
+            },
+        ],
+    },
+];
+
+// Releases newer than the one the admin last acknowledged. Browsers that never
+// stored a version are assumed to have seen the previous release, so the
+// upgrade that introduced the panel still lists its additions.
+pub fn unseen_releases() -> Vec<&'static Release> {
+    let seen = LocalStorage::get::<String>(SEEN_VERSION_STORAGE_KEY).unwrap_or_else(|_| {
+        let previous = RELEASES.get(1).map_or("", |release| release.version);
+        if let Err(err) = LocalStorage::set(SEEN_VERSION_STORAGE_KEY, previous) {
+            log::warn!("Failed to store seen version: {err}");
+        }
+        previous.to_string()
+    });
+
+    RELEASES
+        .iter()
+        .filter(|release| parse_version(release.version) > parse_version(&seen))
+        .collect()
+}
+
+pub fn mark_releases_seen() {
+    if let Err(err) = LocalStorage::set(SEEN_VERSION_STORAGE_KEY, env!("CARGO_PKG_VERSION")) {
+        log::warn!("Failed to store seen version: {err}");
+    }
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or_default())
+        .collect()
+}
@@ -0,0 +1,219 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::cell::Cell;
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::layout::MenuItem;
+
+use super::{AccessToken, Permissions};
+
+const RECENT_OBJECTS_STORAGE_KEY: &str = "webadmin_recent_objects";
+const MAX_RECENT_OBJECTS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommandGroup {
+    Recent,
+    Action,
+    Page,
+}
+
+#[derive(Clone)]
+pub enum CommandAction {
+    Navigate(String),
+    Run(Callback<()>),
+}
+
+#[derive(Clone)]
+pub struct Command {
+    pub group: CommandGroup,
+    pub title: String,
+    pub subtitle: String,
+    pub action: CommandAction,
+}
+
+// Page modules contribute their global actions through a provider, which is
+// called each time the palette opens with the permissions of the admin.
+pub type CommandProvider = fn(&Permissions) -> Vec<Command>;
+
+#[derive(Clone, Copy)]
+pub struct CommandRegistry {
+    providers: StoredValue<Vec<CommandProvider>>,
+    // Actions registered by the pages currently mounted
+    mounted: RwSignal<Vec<(usize, Command)>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentObject {
+    pub title: String,
+    pub url: String,
+}
+
+thread_local! {
+    static NEXT_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+pub fn init_commands(providers: Vec<CommandProvider>) {
+    provide_context(CommandRegistry {
+        providers: store_value(providers),
+        mounted: create_rw_signal(vec![]),
+    });
+}
+
+pub fn use_commands() -> CommandRegistry {
+    expect_context::<CommandRegistry>()
+}
+
+// Makes `commands` available in the palette while the calling component is
+// mounted.
+pub fn register_commands(commands: Vec<Command>) {
+    let registry = use_commands();
+    let id = NEXT_ID.get();
+    NEXT_ID.set(id + 1);
+
+    registry
+        .mounted
+        .update(|mounted| mounted.extend(commands.into_iter().map(|command| (id, command))));
+    on_cleanup(move || {
+        registry
+            .mounted
+            .update(|mounted| mounted.retain(|(owner, _)| *owner != id));
+    });
+}
+
+impl CommandRegistry {
+    pub fn commands(&self, auth: &AccessToken, pages: &[MenuItem]) -> Vec<Command> {
+        let permissions = auth.permissions();
+        let mut commands = self
+            .mounted
+            .get_untracked()
+            .into_iter()
+            .map(|(_, command)| command)
+            .collect::<Vec<_>>();
+        self.providers.with_value(|providers| {
+            for provider in providers {
+                commands.extend(provider(permissions));
+            }
+        });
+        commands.extend(recent_objects(auth).into_iter().map(|recent| Command {
+            group: CommandGroup::Recent,
+            title: recent.title,
+            subtitle: recent.url.clone(),
+            action: CommandAction::Navigate(recent.url),
+        }));
+        add_pages(&mut commands, pages, &[]);
+        commands
+    }
+}
+
+fn add_pages(commands: &mut Vec<Command>, menu: &[MenuItem], parents: &[&str]) {
+    for item in menu {
+        if let Some(route) = item.route.as_ref().filter(|route| !route.is_empty()) {
+            commands.push(Command {
+                group: CommandGroup::Page,
+                title: item.name.clone(),
+                subtitle: parents.join(" › "),
+                action: CommandAction::Navigate(route.clone()),
+            });
+        }
+        if !item.children.is_empty() {
+            let mut parents = parents.to_vec();
+            parents.push(&item.name);
+            add_pages(commands, &item.children, &parents);
+        }
+    }
+}
+
+impl Command {
+    pub fn navigate(
+        title: impl Into<String>,
+        subtitle: impl Into<String>,
+        url: impl Into<String>,
+    ) -> Self {
+        Command {
+            group: CommandGroup::Action,
+            title: title.into(),
+            subtitle: subtitle.into(),
+            action: CommandAction::Navigate(url.into()),
+        }
+    }
+
+    pub fn run(
+        title: impl Into<String>,
+        subtitle: impl Into<String>,
+        callback: impl Fn(()) + 'static,
+    ) -> Self {
+        Command {
+            group: CommandGroup::Action,
+            title: title.into(),
+            subtitle: subtitle.into(),
+            action: CommandAction::Run(Callback::new(callback)),
+        }
+    }
+
+    // Every word has to match the title or subtitle, either as a substring
+    // or as an abbreviation of the title.
+    pub fn score(&self, words: &[String]) -> Option<u32> {
+        let title = self.title.to_lowercase();
+        let subtitle = self.subtitle.to_lowercase();
+        words
+            .iter()
+            .map(|word| {
+                if let Some(pos) = title.find(word.as_str()) {
+                    let is_word_start = title[..pos]
+                        .chars()
+                        .next_back()
+                        .is_none_or(|ch| !ch.is_alphanumeric());
+                    Some(if is_word_start { 6 } else { 4 })
+                } else if subtitle.contains(word.as_str()) {
+                    Some(2)
+                } else if is_subsequence(&title, word) {
+                    Some(1)
+                } else {
+                    None
+                }
+            })
+            .sum()
+    }
+}
+
+pub fn recent_objects(auth: &AccessToken) -> Vec<RecentObject> {
+    LocalStorage::get(recent_objects_key(auth)).unwrap_or_default()
+}
+
+// Records an object opened for editing, most recent first.
+pub fn remember_recent_object(
+    auth: &AccessToken,
+    title: impl Into<String>,
+    url: impl Into<String>,
+) {
+    let recent = RecentObject {
+        title: title.into(),
+        url: url.into(),
+    };
+    let mut objects = recent_objects(auth);
+    objects.retain(|object| object.url != recent.url);
+    objects.insert(0, recent);
+    objects.truncate(MAX_RECENT_OBJECTS);
+
+    if let Err(err) = LocalStorage::set(recent_objects_key(auth), objects) {
+        log::warn!("Failed to store recent objects: {err}");
+    }
+}
+
+fn recent_objects_key(auth: &AccessToken) -> String {
+    format!("{RECENT_OBJECTS_STORAGE_KEY}:{}", auth.username)
+}
+
+pub fn is_subsequence(haystack: &str, needle: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle
+        .chars()
+        .all(|ch| haystack.by_ref().any(|other| other == ch))
+}
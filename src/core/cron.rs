@@ -0,0 +1,185 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Timelike};
+
+use super::i18n::t;
+
+pub static WEEKDAYS: &[&str] = &[
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+// Schedules accepted by the server, written as "<minute> <hour> <day of week>"
+// where the hour and day of week can be "*" and days are numbered from 1
+// (Monday) to 7 (Sunday).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimpleCron {
+    Hour { minute: u32 },
+    Day { hour: u32, minute: u32 },
+    Week { day: u32, hour: u32, minute: u32 },
+}
+
+impl FromStr for SimpleCron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        let [minute, hour, day] = parts[..] else {
+            return Err(t("validation-cron-format"));
+        };
+        let minute = parse_part(minute, 0, 59, "validation-cron-minute")?
+            .ok_or_else(|| t("validation-cron-minute"))?;
+        let hour = parse_part(hour, 0, 23, "validation-cron-hour")?;
+        let day = parse_part(day, 1, 7, "validation-cron-day")?;
+
+        match (hour, day) {
+            (None, None) => Ok(SimpleCron::Hour { minute }),
+            (Some(hour), None) => Ok(SimpleCron::Day { hour, minute }),
+            (Some(hour), Some(day)) => Ok(SimpleCron::Week { day, hour, minute }),
+            (None, Some(_)) => Err(t("validation-cron-weekly")),
+        }
+    }
+}
+
+fn parse_part(value: &str, min: u32, max: u32, error: &str) -> Result<Option<u32>, String> {
+    if value == "*" {
+        Ok(None)
+    } else {
+        value
+            .parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .map(Some)
+            .ok_or_else(|| t(error))
+    }
+}
+
+impl SimpleCron {
+    pub fn minute(&self) -> u32 {
+        match self {
+            SimpleCron::Hour { minute }
+            | SimpleCron::Day { minute, .. }
+            | SimpleCron::Week { minute, .. } => *minute,
+        }
+    }
+
+    pub fn hour(&self) -> Option<u32> {
+        match self {
+            SimpleCron::Hour { .. } => None,
+            SimpleCron::Day { hour, .. } | SimpleCron::Week { hour, .. } => Some(*hour),
+        }
+    }
+
+    pub fn day(&self) -> Option<u32> {
+        match self {
+            SimpleCron::Week { day, .. } => Some(*day),
+            _ => None,
+        }
+    }
+
+    pub fn with_minute(self, minute: u32) -> Self {
+        Self::from_parts(minute, self.hour(), self.day())
+    }
+
+    pub fn with_hour(self, hour: Option<u32>) -> Self {
+        Self::from_parts(self.minute(), hour, self.day())
+    }
+
+    pub fn with_day(self, day: Option<u32>) -> Self {
+        Self::from_parts(self.minute(), self.hour(), day)
+    }
+
+    fn from_parts(minute: u32, hour: Option<u32>, day: Option<u32>) -> Self {
+        match (hour, day) {
+            (Some(hour), Some(day)) => SimpleCron::Week { day, hour, minute },
+            (Some(hour), None) => SimpleCron::Day { hour, minute },
+            // Weekly schedules need an hour, so picking a day on an hourly
+            // schedule falls back to midnight.
+            (None, Some(day)) => SimpleCron::Week {
+                day,
+                hour: 0,
+                minute,
+            },
+            (None, None) => SimpleCron::Hour { minute },
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            SimpleCron::Hour { minute } => format!("Every hour at minute {minute}"),
+            SimpleCron::Day { hour, minute } => format!("Every day at {hour:02}:{minute:02}"),
+            SimpleCron::Week { day, hour, minute } => format!(
+                "Every {} at {hour:02}:{minute:02}",
+                WEEKDAYS[(*day - 1) as usize]
+            ),
+        }
+    }
+
+    pub fn next_run<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> DateTime<Tz> {
+        let next = after.clone() + Duration::minutes(1);
+        let mut next = next
+            .with_second(0)
+            .and_then(|next| next.with_nanosecond(0))
+            .unwrap_or(next);
+
+        // Align to the scheduled minute and then step one hour at a time,
+        // any schedule matches within a week.
+        for _ in 0..=(2 * 7 * 24) {
+            if next.minute() != self.minute() {
+                let minutes = (60 + self.minute() as i64 - next.minute() as i64) % 60;
+                next += Duration::minutes(minutes);
+                continue;
+            }
+            let matches_hour = self.hour().is_none_or(|hour| next.hour() == hour);
+            let matches_day = self
+                .day()
+                .is_none_or(|day| next.weekday().number_from_monday() == day);
+            if matches_hour && matches_day {
+                return next;
+            }
+            next += Duration::hours(1);
+        }
+
+        next
+    }
+
+    pub fn next_runs(&self, count: usize) -> Vec<DateTime<Local>> {
+        let mut runs = Vec::with_capacity(count);
+        let mut after = Local::now();
+        for _ in 0..count {
+            after = self.next_run(&after);
+            runs.push(after);
+        }
+        runs
+    }
+}
+
+impl Default for SimpleCron {
+    fn default() -> Self {
+        SimpleCron::Day { hour: 3, minute: 0 }
+    }
+}
+
+impl Display for SimpleCron {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimpleCron::Hour { minute } => write!(f, "{minute} * *"),
+            SimpleCron::Day { hour, minute } => write!(f, "{minute} {hour} *"),
+            SimpleCron::Week { day, hour, minute } => write!(f, "{minute} {hour} {day}"),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{Array, ArrayBuffer, Object, Reflect, Uint8Array},
+    wasm_bindgen::{JsCast, JsValue},
+    AesDerivedKeyParams, AesGcmParams, CryptoKey, Pbkdf2Params, SubtleCrypto,
+};
+
+const CIPHER: &str = "AES-256-GCM";
+const KDF: &str = "PBKDF2-SHA256";
+const KDF_ITERATIONS: u32 = 600_000;
+// Bounds for the iterations read from an encrypted file
+const KDF_MIN_ITERATIONS: u32 = 100_000;
+const KDF_MAX_ITERATIONS: u32 = 10_000_000;
+
+// Passphrase protected payload, encrypted in the browser with the WebCrypto API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedData {
+    pub cipher: String,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub iv: String,
+    pub data: String,
+}
+
+pub async fn encrypt_with_passphrase(
+    passphrase: &str,
+    plaintext: &[u8],
+) -> Result<EncryptedData, String> {
+    let mut salt = [0u8; 16];
+    let mut iv = [0u8; 12];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut iv);
+
+    let key = derive_key(passphrase, &salt, KDF_ITERATIONS, "encrypt").await?;
+    let params = AesGcmParams::new("AES-GCM", &Uint8Array::from(&iv[..]));
+    let ciphertext = resolve(
+        subtle()?
+            .encrypt_with_object_and_u8_array(&params, &key, plaintext)
+            .map_err(js_error)?,
+    )
+    .await?;
+
+    Ok(EncryptedData {
+        cipher: CIPHER.to_string(),
+        kdf: KDF.to_string(),
+        iterations: KDF_ITERATIONS,
+        salt: STANDARD.encode(salt),
+        iv: STANDARD.encode(iv),
+        data: STANDARD.encode(bytes(ciphertext)),
+    })
+}
+
+pub async fn decrypt_with_passphrase(
+    passphrase: &str,
+    encrypted: &EncryptedData,
+) -> Result<Vec<u8>, String> {
+    if encrypted.cipher != CIPHER || encrypted.kdf != KDF {
+        return Err(format!(
+            "Unsupported encryption {} with {}",
+            encrypted.cipher, encrypted.kdf
+        ));
+    } else if !(KDF_MIN_ITERATIONS..=KDF_MAX_ITERATIONS).contains(&encrypted.iterations) {
+        return Err(format!(
+            "Unsupported number of key derivation iterations {}",
+            encrypted.iterations
+        ));
+    }
+    let decode = |value: &str| {
+        STANDARD
+            .decode(value)
+            .map_err(|_| "The encrypted file is corrupted".to_string())
+    };
+    let salt = decode(&encrypted.salt)?;
+    let iv = decode(&encrypted.iv)?;
+    let data = decode(&encrypted.data)?;
+
+    let key = derive_key(passphrase, &salt, encrypted.iterations, "decrypt").await?;
+    let params = AesGcmParams::new("AES-GCM", &Uint8Array::from(iv.as_slice()));
+    let plaintext = resolve(
+        subtle()?
+            .decrypt_with_object_and_u8_array(&params, &key, &data)
+            .map_err(js_error)?,
+    )
+    .await
+    // AES-GCM does not tell a wrong key apart from tampered data
+    .map_err(|_| "Wrong passphrase or corrupted file".to_string())?;
+
+    Ok(bytes(plaintext))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    RsaSha256,
+    RsaSha1,
+    Ed25519,
+}
+
+pub async fn digest(algorithm: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    resolve(
+        subtle()?
+            .digest_with_str_and_u8_array(algorithm, data)
+            .map_err(js_error)?,
+    )
+    .await
+    .map(bytes)
+}
+
+// RSA keys are expected in SubjectPublicKeyInfo form and Ed25519 keys as the
+// raw 32 byte public key.
+pub async fn verify_signature(
+    algorithm: SignatureAlgorithm,
+    public_key: &[u8],
+    signature: &[u8],
+    data: &[u8],
+) -> Result<bool, String> {
+    let subtle = subtle()?;
+    let usages = Array::of1(&JsValue::from_str("verify"));
+    let key_data = Uint8Array::from(public_key);
+    let key = match algorithm {
+        SignatureAlgorithm::RsaSha256 | SignatureAlgorithm::RsaSha1 => {
+            let params = Object::new();
+            Reflect::set(&params, &"name".into(), &"RSASSA-PKCS1-v1_5".into()).map_err(js_error)?;
+            Reflect::set(
+                &params,
+                &"hash".into(),
+                &if algorithm == SignatureAlgorithm::RsaSha256 {
+                    "SHA-256"
+                } else {
+                    "SHA-1"
+                }
+                .into(),
+            )
+            .map_err(js_error)?;
+            subtle.import_key_with_object("spki", &key_data, &params, false, &usages)
+        }
+        SignatureAlgorithm::Ed25519 => {
+            subtle.import_key_with_str("raw", &key_data, "Ed25519", false, &usages)
+        }
+    }
+    .map_err(js_error)?;
+    let key = resolve(key)
+        .await
+        .map_err(|err| format!("Invalid public key: {err}"))?
+        .unchecked_into::<CryptoKey>();
+
+    let name = match algorithm {
+        SignatureAlgorithm::RsaSha256 | SignatureAlgorithm::RsaSha1 => "RSASSA-PKCS1-v1_5",
+        SignatureAlgorithm::Ed25519 => "Ed25519",
+    };
+    resolve(
+        subtle
+            .verify_with_str_and_u8_array_and_u8_array(name, &key, signature, data)
+            .map_err(js_error)?,
+    )
+    .await
+    .map(|valid| valid.as_bool().unwrap_or_default())
+}
+
+async fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    iterations: u32,
+    usage: &str,
+) -> Result<CryptoKey, String> {
+    let subtle = subtle()?;
+    let base_key = resolve(
+        subtle
+            .import_key_with_str(
+                "raw",
+                &Uint8Array::from(passphrase.as_bytes()),
+                "PBKDF2",
+                false,
+                &Array::of1(&JsValue::from_str("deriveKey")),
+            )
+            .map_err(js_error)?,
+    )
+    .await?;
+
+    resolve(
+        subtle
+            .derive_key_with_object_and_object(
+                &Pbkdf2Params::new(
+                    "PBKDF2",
+                    &JsValue::from_str("SHA-256"),
+                    iterations,
+                    &Uint8Array::from(salt),
+                ),
+                &base_key.unchecked_into::<CryptoKey>(),
+                &AesDerivedKeyParams::new("AES-GCM", 256),
+                false,
+                &Array::of1(&JsValue::from_str(usage)),
+            )
+            .map_err(js_error)?,
+    )
+    .await
+    .map(|key| key.unchecked_into::<CryptoKey>())
+}
+
+fn subtle() -> Result<SubtleCrypto, String> {
+    web_sys::window()
+        .and_then(|window| window.crypto().ok())
+        .map(|crypto| crypto.subtle())
+        .ok_or_else(|| "Encryption is not supported by this browser".to_string())
+}
+
+async fn resolve(promise: web_sys::js_sys::Promise) -> Result<JsValue, String> {
+    JsFuture::from(promise).await.map_err(js_error)
+}
+
+fn bytes(buffer: JsValue) -> Vec<u8> {
+    Uint8Array::new(&buffer.unchecked_into::<ArrayBuffer>()).to_vec()
+}
+
+fn js_error(err: JsValue) -> String {
+    err.dyn_ref::<web_sys::js_sys::Error>()
+        .map(|err| String::from(err.message()))
+        .unwrap_or_else(|| format!("{err:?}"))
+}
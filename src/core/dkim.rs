@@ -0,0 +1,660 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+
+use super::crypto::{digest, verify_signature, SignatureAlgorithm};
+
+// DER encoded AlgorithmIdentifier for rsaEncryption, used to wrap PKCS#1 keys
+static RSA_ALGORITHM_ID: &[u8] = &[
+    0x30, 0x0d, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub headers: Vec<Header>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub name: String,
+    // Header as it appears in the message, including folding and the final CRLF
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DkimSignature {
+    pub domain: String,
+    pub selector: String,
+    pub algorithm: SignatureAlgorithm,
+    pub header_canon: Canonicalization,
+    pub body_canon: Canonicalization,
+    pub signed_headers: Vec<String>,
+    pub identity: Option<String>,
+    pub body_length: Option<usize>,
+    pub timestamp: Option<i64>,
+    pub expiration: Option<i64>,
+    body_hash: Vec<u8>,
+    signature: Vec<u8>,
+    header_idx: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Canonicalization {
+    Simple,
+    Relaxed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedHeader {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DkimResult {
+    Pass,
+    Fail(String),
+    PermError(String),
+    TempError(String),
+}
+
+impl Message {
+    // Pasted messages usually have bare LF line endings, which are restored to
+    // CRLF as they were when the message was signed.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim_start().replace("\r\n", "\n").replace('\n', "\r\n");
+        let (header, body) = match raw.find("\r\n\r\n") {
+            Some(pos) => (&raw[..pos + 2], &raw[pos + 4..]),
+            None => (raw.as_str(), ""),
+        };
+
+        let mut headers: Vec<Header> = Vec::new();
+        for line in header.split_inclusive("\r\n") {
+            if line.starts_with([' ', '\t']) {
+                match headers.last_mut() {
+                    Some(header) => header.raw.push_str(line),
+                    None => return Err("The message starts with a folded line".to_string()),
+                }
+            } else if headers.is_empty() && line.starts_with("From ") {
+                // Envelope line of messages copied from an mbox file
+                continue;
+            } else if let Some((name, _)) = line.split_once(':') {
+                headers.push(Header {
+                    name: name.trim().to_string(),
+                    raw: line.to_string(),
+                });
+            } else if !line.trim().is_empty() {
+                return Err(format!("Invalid header line {:?}", line.trim_end()));
+            }
+        }
+        if headers.is_empty() {
+            return Err("No headers were found in the message".to_string());
+        }
+
+        Ok(Message {
+            headers,
+            body: body.as_bytes().to_vec(),
+        })
+    }
+
+    pub fn signatures(&self) -> Vec<Result<DkimSignature, String>> {
+        self.headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| header.name.eq_ignore_ascii_case("DKIM-Signature"))
+            .map(|(idx, header)| DkimSignature::parse(idx, header.value()))
+            .collect()
+    }
+
+    // Header instances are consumed from the bottom of the message, as
+    // described in RFC 6376, section 5.4.2.
+    fn select_headers(&self, names: &[String]) -> Vec<(String, Option<&Header>)> {
+        let mut used = vec![false; self.headers.len()];
+        names
+            .iter()
+            .map(|name| {
+                let header = self
+                    .headers
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find(|(idx, header)| !used[*idx] && header.name.eq_ignore_ascii_case(name))
+                    .map(|(idx, header)| {
+                        used[idx] = true;
+                        header
+                    });
+                (name.clone(), header)
+            })
+            .collect()
+    }
+}
+
+impl Header {
+    pub fn value(&self) -> &str {
+        self.raw
+            .split_once(':')
+            .map(|(_, value)| value)
+            .unwrap_or_default()
+    }
+
+    fn canonicalize(&self, canon: Canonicalization) -> String {
+        match canon {
+            Canonicalization::Simple => self.raw.clone(),
+            Canonicalization::Relaxed => {
+                format!(
+                    "{}:{}\r\n",
+                    self.name.to_lowercase(),
+                    compress_whitespace(&self.value().replace("\r\n", "")).trim()
+                )
+            }
+        }
+    }
+}
+
+impl DkimSignature {
+    fn parse(header_idx: usize, value: &str) -> Result<Self, String> {
+        let tags = parse_tags(value);
+        let tag = |name: &str| {
+            tags.iter()
+                .find_map(|(tag, value)| (*tag == name).then_some(value.as_str()))
+        };
+        let required = |name: &str| {
+            tag(name)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("Missing required tag {name}="))
+        };
+
+        if required("v")? != "1" {
+            return Err(format!("Unsupported version v={}", required("v")?));
+        }
+        let algorithm = match required("a")?.to_lowercase().as_str() {
+            "rsa-sha256" => SignatureAlgorithm::RsaSha256,
+            "rsa-sha1" => SignatureAlgorithm::RsaSha1,
+            "ed25519-sha256" => SignatureAlgorithm::Ed25519,
+            other => return Err(format!("Unsupported algorithm a={other}")),
+        };
+        let (header_canon, body_canon) = match tag("c").unwrap_or("simple/simple").split_once('/') {
+            Some((header, body)) => (
+                Canonicalization::parse(header)?,
+                Canonicalization::parse(body)?,
+            ),
+            None => (
+                Canonicalization::parse(tag("c").unwrap_or("simple"))?,
+                Canonicalization::Simple,
+            ),
+        };
+        let signed_headers = required("h")?
+            .split(':')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        if !signed_headers
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case("From"))
+        {
+            return Err("The From header field is not signed".to_string());
+        }
+        let domain = required("d")?.to_lowercase();
+        let identity = tag("i").map(|identity| identity.to_string());
+        if let Some(identity) = &identity {
+            let identity_domain = identity
+                .rsplit_once('@')
+                .map(|(_, domain)| domain)
+                .unwrap_or(identity)
+                .to_lowercase();
+            if identity_domain != domain && !identity_domain.ends_with(&format!(".{domain}")) {
+                return Err(format!(
+                    "The identity i={identity} is not within the signing domain d={domain}"
+                ));
+            }
+        }
+        let number = |name: &str| {
+            tag(name)
+                .map(|value| {
+                    value
+                        .parse::<i64>()
+                        .map_err(|_| format!("Invalid number in {name}={value}"))
+                })
+                .transpose()
+        };
+
+        Ok(DkimSignature {
+            domain,
+            selector: required("s")?.to_string(),
+            algorithm,
+            header_canon,
+            body_canon,
+            signed_headers,
+            identity,
+            body_length: number("l")?.map(|length| length.max(0) as usize),
+            timestamp: number("t")?,
+            expiration: number("x")?,
+            body_hash: decode_base64(required("bh")?)
+                .map_err(|_| "The body hash bh= is not valid base64".to_string())?,
+            signature: decode_base64(required("b")?)
+                .map_err(|_| "The signature b= is not valid base64".to_string())?,
+            header_idx,
+        })
+    }
+
+    pub fn key_name(&self) -> String {
+        format!("{}._domainkey.{}", self.selector, self.domain)
+    }
+
+    pub fn algorithm_name(&self) -> &'static str {
+        match self.algorithm {
+            SignatureAlgorithm::RsaSha256 => "rsa-sha256",
+            SignatureAlgorithm::RsaSha1 => "rsa-sha1",
+            SignatureAlgorithm::Ed25519 => "ed25519-sha256",
+        }
+    }
+
+    pub fn canonicalization_name(&self) -> String {
+        format!(
+            "{}/{}",
+            self.header_canon.as_str(),
+            self.body_canon.as_str()
+        )
+    }
+
+    pub fn signed_header_values(&self, message: &Message) -> Vec<SignedHeader> {
+        message
+            .select_headers(&self.signed_headers)
+            .into_iter()
+            .map(|(name, header)| SignedHeader {
+                name,
+                value: header.map(|header| {
+                    compress_whitespace(&header.value().replace("\r\n", ""))
+                        .trim()
+                        .to_string()
+                }),
+            })
+            .collect()
+    }
+
+    // `records` are the TXT records published at `key_name`
+    pub async fn verify(&self, message: &Message, records: &[String]) -> DkimResult {
+        if let Some(expiration) = self.expiration {
+            if expiration < Utc::now().timestamp() {
+                return DkimResult::Fail("The signature has expired".to_string());
+            }
+        }
+
+        let key = match DomainKey::from_records(records) {
+            Ok(key) => key,
+            Err(err) => return DkimResult::PermError(err),
+        };
+        if key.public_key.is_empty() {
+            return DkimResult::PermError("The key has been revoked (empty p= tag)".to_string());
+        }
+        let expected_type = match self.algorithm {
+            SignatureAlgorithm::RsaSha256 | SignatureAlgorithm::RsaSha1 => "rsa",
+            SignatureAlgorithm::Ed25519 => "ed25519",
+        };
+        if key.key_type != expected_type {
+            return DkimResult::PermError(format!(
+                "The key type k={} does not match the signature algorithm {}",
+                key.key_type,
+                self.algorithm_name()
+            ));
+        }
+
+        let hash = self.hash_name();
+        let mut body = self.body_canon.canonicalize_body(&message.body);
+        if let Some(length) = self.body_length {
+            if length > body.len() {
+                return DkimResult::Fail(format!(
+                    "The body is shorter than the signed length l={length}"
+                ));
+            }
+            body.truncate(length);
+        }
+        match digest(hash, &body).await {
+            Ok(body_hash) if body_hash != self.body_hash => {
+                return DkimResult::Fail(
+                    "The body hash does not match, the message body was modified after signing"
+                        .to_string(),
+                );
+            }
+            Ok(_) => (),
+            Err(err) => return DkimResult::PermError(err),
+        }
+
+        let mut data = String::new();
+        for (_, header) in message.select_headers(&self.signed_headers) {
+            if let Some(header) = header {
+                data.push_str(&header.canonicalize(self.header_canon));
+            }
+        }
+        let signature_header = &message.headers[self.header_idx];
+        let signature_header = Header {
+            name: signature_header.name.clone(),
+            raw: remove_signature(&signature_header.raw),
+        }
+        .canonicalize(self.header_canon);
+        data.push_str(signature_header.trim_end_matches("\r\n"));
+
+        let result = match self.algorithm {
+            SignatureAlgorithm::Ed25519 => match digest(hash, data.as_bytes()).await {
+                Ok(hash) => {
+                    verify_signature(self.algorithm, &key.public_key, &self.signature, &hash).await
+                }
+                Err(err) => Err(err),
+            },
+            _ => {
+                verify_signature(
+                    self.algorithm,
+                    &rsa_spki(key.public_key),
+                    &self.signature,
+                    data.as_bytes(),
+                )
+                .await
+            }
+        };
+
+        match result {
+            Ok(true) => DkimResult::Pass,
+            Ok(false) => DkimResult::Fail(
+                "The signature does not match, signed header fields were modified or the key does not belong to the signer"
+                    .to_string(),
+            ),
+            Err(err) => DkimResult::PermError(err),
+        }
+    }
+
+    fn hash_name(&self) -> &'static str {
+        match self.algorithm {
+            SignatureAlgorithm::RsaSha1 => "SHA-1",
+            SignatureAlgorithm::RsaSha256 | SignatureAlgorithm::Ed25519 => "SHA-256",
+        }
+    }
+}
+
+struct DomainKey {
+    key_type: String,
+    public_key: Vec<u8>,
+}
+
+impl DomainKey {
+    fn from_records(records: &[String]) -> Result<Self, String> {
+        // Long keys are published as several strings within the same record
+        let records = records
+            .iter()
+            .map(|record| record.trim().trim_matches('"').replace("\" \"", ""))
+            .filter(|record| record.contains("p="))
+            .collect::<Vec<_>>();
+        let record = match records.as_slice() {
+            [record] => record,
+            [] => return Err("No DKIM key record was found".to_string()),
+            _ => return Err("More than one DKIM key record was found".to_string()),
+        };
+
+        let tags = parse_tags(record);
+        let tag = |name: &str| {
+            tags.iter()
+                .find_map(|(tag, value)| (*tag == name).then_some(value.as_str()))
+        };
+        if tag("v").is_some_and(|version| version != "DKIM1") {
+            return Err(format!("Unsupported key version v={}", tag("v").unwrap()));
+        }
+
+        Ok(DomainKey {
+            key_type: tag("k").unwrap_or("rsa").to_lowercase(),
+            public_key: decode_base64(tag("p").unwrap_or_default())
+                .map_err(|_| "The public key p= is not valid base64".to_string())?,
+        })
+    }
+}
+
+impl Canonicalization {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "simple" => Ok(Canonicalization::Simple),
+            "relaxed" => Ok(Canonicalization::Relaxed),
+            other => Err(format!("Unsupported canonicalization {other:?}")),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Canonicalization::Simple => "simple",
+            Canonicalization::Relaxed => "relaxed",
+        }
+    }
+
+    fn canonicalize_body(&self, body: &[u8]) -> Vec<u8> {
+        let body = String::from_utf8_lossy(body);
+        let mut lines = body
+            .split("\r\n")
+            .map(|line| match self {
+                Canonicalization::Simple => line.to_string(),
+                Canonicalization::Relaxed => compress_whitespace(line).trim_end().to_string(),
+            })
+            .collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        if lines.is_empty() {
+            match self {
+                Canonicalization::Simple => b"\r\n".to_vec(),
+                Canonicalization::Relaxed => vec![],
+            }
+        } else {
+            let mut body = lines.join("\r\n");
+            body.push_str("\r\n");
+            body.into_bytes()
+        }
+    }
+}
+
+fn parse_tags(value: &str) -> Vec<(&str, String)> {
+    value
+        .split(';')
+        .filter_map(|tag| {
+            let (name, value) = tag.split_once('=')?;
+            Some((
+                name.trim(),
+                value.split_whitespace().collect::<Vec<_>>().join(""),
+            ))
+        })
+        .collect()
+}
+
+// Empties the value of the b= tag, keeping the rest of the header untouched
+fn remove_signature(raw: &str) -> String {
+    let Some((name, value)) = raw.split_once(':') else {
+        return raw.to_string();
+    };
+    let value = value
+        .split(';')
+        .map(|tag| match tag.split_once('=') {
+            Some((tag_name, tag_value)) if tag_name.trim() == "b" => {
+                // Whitespace around the value, including the final CRLF when
+                // b= is the last tag, is not part of it
+                let leading = &tag_value[..tag_value.len() - tag_value.trim_start().len()];
+                let trailing = &tag_value[tag_value.trim_end().len()..];
+                if tag_value.trim().is_empty() {
+                    format!("{tag_name}={tag_value}")
+                } else {
+                    format!("{tag_name}={leading}{trailing}")
+                }
+            }
+            _ => tag.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+    format!("{name}:{value}")
+}
+
+fn compress_whitespace(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut last_space = false;
+    for ch in value.chars() {
+        if ch == ' ' || ch == '\t' {
+            if !last_space {
+                result.push(' ');
+            }
+            last_space = true;
+        } else {
+            result.push(ch);
+            last_space = false;
+        }
+    }
+    result
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(
+        value
+            .chars()
+            .filter(|ch| !ch.is_whitespace())
+            .collect::<String>(),
+    )
+}
+
+// Keys are usually published as SubjectPublicKeyInfo, but bare PKCS#1
+// RSAPublicKey structures are also found in the wild.
+fn rsa_spki(key: Vec<u8>) -> Vec<u8> {
+    if key
+        .windows(RSA_ALGORITHM_ID.len())
+        .take(8)
+        .any(|window| window == RSA_ALGORITHM_ID)
+    {
+        return key;
+    }
+
+    let mut bit_string = Vec::with_capacity(key.len() + 1);
+    bit_string.push(0);
+    bit_string.extend_from_slice(&key);
+
+    let mut content = RSA_ALGORITHM_ID.to_vec();
+    content.extend(der_element(0x03, &bit_string));
+    der_element(0x30, &content)
+}
+
+fn der_element(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        element.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let bytes = &bytes[bytes.iter().position(|byte| *byte != 0).unwrap_or(0)..];
+        element.push(0x80 | bytes.len() as u8);
+        element.extend_from_slice(bytes);
+    }
+    element.extend_from_slice(content);
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &str, raw: &str) -> Header {
+        Header {
+            name: name.to_string(),
+            raw: raw.to_string(),
+        }
+    }
+
+    fn records(records: &[&str]) -> Vec<String> {
+        records.iter().map(|record| record.to_string()).collect()
+    }
+
+    fn domain_key(values: &[&str]) -> Result<(String, Vec<u8>), String> {
+        DomainKey::from_records(&records(values)).map(|key| (key.key_type, key.public_key))
+    }
+
+    #[test]
+    fn header_canonicalization() {
+        let subject = header("SUBJECT", "SUBJECT : Hello \r\n\t World  \r\n");
+        assert_eq!(
+            subject.canonicalize(Canonicalization::Simple),
+            "SUBJECT : Hello \r\n\t World  \r\n"
+        );
+        assert_eq!(
+            subject.canonicalize(Canonicalization::Relaxed),
+            "subject:Hello World\r\n"
+        );
+    }
+
+    #[test]
+    fn body_canonicalization() {
+        let body = b"Hi  there \r\n\tagain\r\n\r\n\r\n";
+        assert_eq!(
+            Canonicalization::Simple.canonicalize_body(body),
+            b"Hi  there \r\n\tagain\r\n"
+        );
+        assert_eq!(
+            Canonicalization::Relaxed.canonicalize_body(body),
+            b"Hi there\r\n again\r\n"
+        );
+
+        // An empty body is a single CRLF in simple and nothing in relaxed
+        assert_eq!(Canonicalization::Simple.canonicalize_body(b""), b"\r\n");
+        assert_eq!(
+            Canonicalization::Relaxed.canonicalize_body(b"\r\n\r\n"),
+            b""
+        );
+    }
+
+    #[test]
+    fn parse_domain_key() {
+        assert_eq!(
+            domain_key(&["v=DKIM1; k=rsa; p=AQID"]),
+            Ok(("rsa".to_string(), vec![1, 2, 3]))
+        );
+        // Long keys are split into several strings
+        assert_eq!(
+            domain_key(&["\"v=DKIM1; k=Ed25519; p=AQ\" \"ID\""]),
+            Ok(("ed25519".to_string(), vec![1, 2, 3]))
+        );
+        // Records without a key, such as SPF, are ignored
+        assert_eq!(
+            domain_key(&["v=spf1 -all", "p=AQID"]),
+            Ok(("rsa".to_string(), vec![1, 2, 3]))
+        );
+
+        assert_eq!(
+            domain_key(&["v=spf1 -all"]),
+            Err("No DKIM key record was found".to_string())
+        );
+        assert_eq!(
+            domain_key(&["p=AQID", "p=BAUG"]),
+            Err("More than one DKIM key record was found".to_string())
+        );
+        assert_eq!(
+            domain_key(&["v=DKIM2; p=AQID"]),
+            Err("Unsupported key version v=DKIM2".to_string())
+        );
+        assert_eq!(
+            domain_key(&["v=DKIM1; p=not base64!"]),
+            Err("The public key p= is not valid base64".to_string())
+        );
+    }
+
+    #[test]
+    fn signature_removal() {
+        assert_eq!(
+            remove_signature("DKIM-Signature: v=1; b=abc\r\n def; bh=xyz\r\n"),
+            "DKIM-Signature: v=1; b=; bh=xyz\r\n"
+        );
+        // Whitespace after the last tag is kept
+        assert_eq!(
+            remove_signature("DKIM-Signature: v=1; bh=xyz;\r\n b = abc\r\n def\r\n"),
+            "DKIM-Signature: v=1; bh=xyz;\r\n b = \r\n"
+        );
+        assert_eq!(
+            remove_signature("DKIM-Signature: v=1; bh=xyz; b=\r\n"),
+            "DKIM-Signature: v=1; bh=xyz; b=\r\n"
+        );
+    }
+}
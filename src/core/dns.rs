@@ -0,0 +1,68 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::net::IpAddr;
+
+use serde::Deserialize;
+
+use super::{
+    http::{self, HttpRequest},
+    AccessToken,
+};
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct DnsLookup {
+    #[serde(default)]
+    records: Vec<String>,
+}
+
+// Browsers cannot send DNS queries, lookups go through the server
+pub async fn dns_lookup(auth: &AccessToken, name: &str, typ: &str) -> http::Result<Vec<String>> {
+    dns_lookup_with_resolver(auth, name, typ, None).await
+}
+
+// Queries a specific public resolver instead of the one used by the server
+pub async fn dns_lookup_with_resolver(
+    auth: &AccessToken,
+    name: &str,
+    typ: &str,
+    resolver: Option<&str>,
+) -> http::Result<Vec<String>> {
+    HttpRequest::get("/api/dns/lookup")
+        .with_authorization(auth)
+        .with_parameter("name", name)
+        .with_parameter("type", typ)
+        .with_optional_parameter("resolver", resolver)
+        .send::<DnsLookup>()
+        .await
+        .map(|lookup| lookup.records)
+}
+
+// Name queried for the PTR record of an address
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(_) => reverse_ip(ip, "in-addr.arpa"),
+        IpAddr::V6(_) => reverse_ip(ip, "ip6.arpa"),
+    }
+}
+
+// Reversed octets for IPv4 and nibbles for IPv6, as used by PTR and DNSBL queries
+pub fn reverse_ip(ip: IpAddr, zone: &str) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, d] = ip.octets();
+            format!("{d}.{c}.{b}.{a}.{zone}")
+        }
+        IpAddr::V6(ip) => {
+            let mut name = String::with_capacity(64 + zone.len());
+            for byte in ip.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", byte & 0x0f, byte >> 4));
+            }
+            name.push_str(zone);
+            name
+        }
+    }
+}
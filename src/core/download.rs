@@ -0,0 +1,98 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use leptos::document;
+use serde::{Deserialize, Serialize};
+use web_sys::{wasm_bindgen::JsCast, HtmlElement};
+
+use super::{
+    http::{self, HttpRequest},
+    AccessToken,
+};
+
+// Large exports are served by the server through a time-limited signed URL,
+// the browser downloads them directly instead of through the WASM app.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+pub enum Export {
+    Mailbox {
+        account: String,
+    },
+    Logs {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filter: Option<String>,
+    },
+    AuditLog {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        actor: Option<String>,
+        #[serde(rename = "objectType", skip_serializing_if = "Option::is_none")]
+        object_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        since: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        until: Option<String>,
+    },
+    Backup,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedUrl {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn request_signed_url(auth: &AccessToken, export: &Export) -> http::Result<SignedUrl> {
+    HttpRequest::post("/api/download/sign")
+        .with_authorization(auth)
+        .with_body(export)?
+        .send::<SignedUrl>()
+        .await
+        .map(|mut signed| {
+            if signed.url.starts_with('/') {
+                signed.url = format!("{}{}", auth.base_url, signed.url);
+            }
+            signed
+        })
+}
+
+impl Export {
+    pub fn title(&self) -> String {
+        match self {
+            Export::Mailbox { account } => format!("Mailbox export for {account}"),
+            Export::Logs { .. } => "Log export".to_string(),
+            Export::AuditLog { .. } => "Audit log export".to_string(),
+            Export::Backup => "Server backup".to_string(),
+        }
+    }
+}
+
+// Small documents generated in the browser are downloaded from a data URL
+pub fn download_file(name: &str, mime_type: &str, contents: &str) {
+    let document = document();
+    if let Ok(link) = document.create_element("a") {
+        let _ = link.set_attribute(
+            "href",
+            &format!(
+                "data:{mime_type};base64,{}",
+                STANDARD.encode(contents.as_bytes())
+            ),
+        );
+        let _ = link.set_attribute("download", name);
+        link.unchecked_into::<HtmlElement>().click();
+    }
+}
+
+pub fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,189 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use codee::string::JsonSerdeCodec;
+use leptos::*;
+use leptos_use::{
+    core::ConnectionReadyState, use_websocket_with_options, ReconnectLimit, UseWebSocketOptions,
+    UseWebSocketReturn,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{http::HttpRequest, oauth::use_authorization, poll::use_polling, url::UrlBuilder};
+
+// Bursts of changes, such as a queue run, are coalesced into a single refresh
+const THROTTLE: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeTopic {
+    Queue,
+    QueueReports,
+    Reports,
+    Certificates,
+    Security,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ChangeEvent {
+    pub topic: ChangeTopic,
+}
+
+// Change notifications pushed by the server for a topic. When the event
+// stream is not available the tick is driven by polling instead.
+#[derive(Clone, Copy)]
+pub struct ChangeEvents {
+    tick: RwSignal<u64>,
+    received: RwSignal<u64>,
+    is_live: RwSignal<bool>,
+}
+
+pub fn use_change_events(topic: ChangeTopic, fallback: Duration) -> ChangeEvents {
+    let auth = use_authorization();
+    let poller = use_polling(fallback);
+    let events = ChangeEvents {
+        tick: create_rw_signal(0),
+        received: create_rw_signal(0),
+        is_live: create_rw_signal(false),
+    };
+    let pending = store_value(false);
+
+    // Servers without an event stream answer with an error, in which case
+    // the list keeps polling.
+    let token = create_local_resource(
+        move || auth.get().access_token.clone(),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/events/token")
+                    .with_authorization(&auth)
+                    .send::<String>()
+                    .await
+                    .ok()
+            }
+        },
+    );
+
+    create_effect(move |_| {
+        let Some(token) = token.get().flatten() else {
+            events.is_live.set(false);
+            return;
+        };
+        let UseWebSocketReturn {
+            ready_state,
+            message,
+            ..
+        } = use_websocket_with_options::<ChangeEvent, ChangeEvent, JsonSerdeCodec>(
+            &UrlBuilder::new(websocket_url(
+                &auth.get_untracked().base_url,
+                "/api/events/ws",
+            ))
+            .with_parameter("token", token)
+            .with_parameter("topic", topic.as_str())
+            .finish(),
+            UseWebSocketOptions::default()
+                .reconnect_limit(ReconnectLimit::Limited(5))
+                .reconnect_interval(5000),
+        );
+
+        create_effect(move |was_live: Option<bool>| {
+            let is_live = ready_state.get() == ConnectionReadyState::Open;
+            events.is_live.set(is_live);
+
+            // Changes missed while disconnected are picked up on reconnect
+            if is_live && was_live == Some(false) {
+                events.refresh();
+            }
+            is_live
+        });
+
+        create_effect(move |_| {
+            if message.get().is_some_and(|event| event.topic == topic) && !pending.get_value() {
+                pending.set_value(true);
+                set_timeout(
+                    move || {
+                        pending.set_value(false);
+                        events.received.update(|received| *received += 1);
+                        events.refresh();
+                    },
+                    THROTTLE,
+                );
+            }
+        });
+    });
+
+    create_effect(move |_| {
+        if events.is_live.get() {
+            poller.pause();
+        } else {
+            poller.resume();
+        }
+    });
+
+    create_effect(move |prev: Option<u64>| {
+        let tick = poller.tick();
+        if prev.is_some_and(|prev| prev != tick) {
+            events.refresh();
+        }
+        tick
+    });
+
+    events
+}
+
+impl ChangeTopic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeTopic::Queue => "queue",
+            ChangeTopic::QueueReports => "queue-reports",
+            ChangeTopic::Reports => "reports",
+            ChangeTopic::Certificates => "certificates",
+            ChangeTopic::Security => "security",
+            ChangeTopic::Other => "other",
+        }
+    }
+}
+
+impl ChangeEvents {
+    pub fn tick(&self) -> u64 {
+        self.tick.get()
+    }
+
+    pub fn refresh(&self) {
+        self.tick.update(|tick| *tick += 1);
+    }
+
+    // Changes pushed by the server, unlike the tick this ignores polling
+    // and reconnects
+    pub fn received(&self) -> u64 {
+        self.received.get()
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.is_live.get()
+    }
+}
+
+pub fn websocket_url(base_url: &str, path: &str) -> String {
+    let base_url = if base_url.is_empty() {
+        window().location().origin().unwrap_or_default()
+    } else {
+        base_url.to_string()
+    };
+
+    if let Some(url) = base_url.strip_prefix("https://") {
+        format!("wss://{url}{path}")
+    } else if let Some(url) = base_url.strip_prefix("http://") {
+        format!("ws://{url}{path}")
+    } else {
+        format!("{base_url}{path}")
+    }
+}
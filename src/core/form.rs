@@ -11,17 +11,23 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use leptos::RwSignal;
+use serde::{Deserialize, Serialize};
 
 use crate::pages::config::{Settings, SettingsValues};
 
+use super::cron::SimpleCron;
 use super::expr::parser::ExpressionParser;
 use super::expr::tokenizer::Tokenizer;
 use super::expr::{Constant, ParseValue, Token};
+use super::i18n::{t, tr};
+use super::schedule::parse_schedule;
 use super::schema::{NumberType, SchemaType, SelectType, Type};
 
-use super::schema::{InputCheck, Schema, Transformer, Validator};
+use super::schema::{
+    AsyncValidator, Constraint, Field, InputCheck, Schema, Transformer, Validator,
+};
 
 pub type ExternalSources = AHashMap<String, Vec<(String, String)>>;
 
@@ -29,25 +35,28 @@ pub type ExternalSources = AHashMap<String, Vec<(String, String)>>;
 pub struct FormData {
     pub values: AHashMap<String, FormValue>,
     pub errors: AHashMap<String, FormError>,
+    pub section_errors: AHashMap<usize, Vec<String>>,
     pub external_sources: Arc<ExternalSources>,
+    // Secrets stored on the server, their values are never loaded
+    pub secrets: AHashSet<String>,
     pub schema: Arc<Schema>,
     pub is_update: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FormValue {
     Value(String),
     Array(Vec<String>),
     Expression(Expression),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Expression {
     pub if_thens: Vec<ExpressionIfThen>,
     pub else_: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExpressionIfThen {
     pub if_: String,
     pub then_: String,
@@ -63,6 +72,8 @@ pub struct FormError {
 pub enum FormErrorType {
     Expression(ExpressionError<usize>),
     Array(usize),
+    // Attached by a section constraint, evaluated again on every validation
+    Section,
     None,
 }
 
@@ -147,9 +158,23 @@ impl FormData {
         self.errors.remove(id);
     }
 
+    pub fn secret_is_set(&self, id: &str) -> bool {
+        self.secrets.contains(id)
+    }
+
+    pub fn secret_is_retained(&self, id: &str) -> bool {
+        self.secrets.contains(id) && !self.values.contains_key(id)
+    }
+
+    pub fn secret_clear(&mut self, id: &str) {
+        self.secrets.remove(id);
+        self.remove(id);
+    }
+
     pub fn reset(&mut self) {
         self.values.clear();
         self.errors.clear();
+        self.section_errors.clear();
         self.apply_defaults(false);
     }
 
@@ -383,7 +408,7 @@ impl FormData {
                     if let Some(default) = field.default.eval(self) {
                         //let c = log::debug!("adding default {:?} = {default:?}", field.id);
                         let value = match (&field.typ_, default) {
-                            (Type::Expression, FormValue::Value(default)) => {
+                            (Type::Expression | Type::Rules, FormValue::Value(default)) => {
                                 FormValue::Expression(Expression {
                                     else_: default.to_string(),
                                     ..Default::default()
@@ -412,7 +437,7 @@ impl FormData {
                 && !self.values.contains_key(field.id)
                 && field.checks.if_thens.is_empty()
                 && (!only_required
-                    || (matches!(field.typ_, Type::Boolean | Type::Expression)
+                    || (matches!(field.typ_, Type::Boolean | Type::Expression | Type::Rules)
                         || field
                             .checks
                             .default
@@ -421,7 +446,7 @@ impl FormData {
             {
                 if let Some(default) = field.default.default.as_ref() {
                     let value = match (&field.typ_, default) {
-                        (Type::Expression, FormValue::Value(default)) => {
+                        (Type::Expression | Type::Rules, FormValue::Value(default)) => {
                             FormValue::Expression(Expression {
                                 else_: default.to_string(),
                                 ..Default::default()
@@ -459,6 +484,9 @@ impl FormData {
     }
 
     pub fn validate_form(&mut self) -> bool {
+        self.section_errors.clear();
+        self.errors
+            .retain(|_, error| error.id != FormErrorType::Section);
         if !self.errors.is_empty() {
             log::debug!("Skipping validation, form has errors: {:#?}", self.errors);
             return false;
@@ -470,6 +498,10 @@ impl FormData {
                 continue;
             }
 
+            if matches!(field.typ_, Type::Secret) && self.secret_is_retained(field.id) {
+                continue;
+            }
+
             if let Some(check) = field.input_check(self) {
                 match field.typ_ {
                     Type::Input
@@ -480,12 +512,20 @@ impl FormData {
                     | Type::Duration
                     | Type::Rate
                     | Type::Cron
+                    | Type::Custom(_)
                     | Type::Select {
                         typ: SelectType::Single,
                         ..
                     } => {
                         match check.check_value(self.value::<String>(field.id).unwrap_or_default())
                         {
+                            Ok(value)
+                                if matches!(field.typ_, Type::Secret)
+                                    && is_secret_reference(&value)
+                                    && !is_valid_secret_reference(&value) =>
+                            {
+                                self.new_error(field.id, t("validation-secret-reference"));
+                            }
                             Ok(value) => {
                                 if !value.is_empty() {
                                     self.values.insert(field.id.into(), value.into());
@@ -500,7 +540,10 @@ impl FormData {
                     }
                     Type::Array
                     | Type::Select {
-                        typ: SelectType::Many | SelectType::ManyWithSearch,
+                        typ:
+                            SelectType::Many
+                            | SelectType::ManyWithSearch
+                            | SelectType::ManyWithGroups(_),
                         ..
                     } => {
                         let mut total_values = 0;
@@ -539,14 +582,17 @@ impl FormData {
                                 match validator {
                                     Validator::Required => {
                                         if total_values == 0 {
-                                            self.new_error(field.id, "This field is required");
+                                            self.new_error(field.id, t("validation-required"));
                                         }
                                     }
                                     Validator::MinItems(min) => {
                                         if total_values < *min {
                                             self.new_error(
                                                 field.id,
-                                                format!("At least {min} items are required"),
+                                                tr(
+                                                    "validation-min-items",
+                                                    &[("count", min.to_string())],
+                                                ),
                                             );
                                         }
                                     }
@@ -554,7 +600,10 @@ impl FormData {
                                         if total_values > *max {
                                             self.new_error(
                                                 field.id,
-                                                format!("At most {max} items are allowed"),
+                                                tr(
+                                                    "validation-max-items",
+                                                    &[("count", max.to_string())],
+                                                ),
                                             );
                                         }
                                     }
@@ -563,7 +612,7 @@ impl FormData {
                             }
                         }
                     }
-                    Type::Expression => {
+                    Type::Expression | Type::Rules => {
                         let mut has_expression = false;
                         let validator = *check
                             .validators
@@ -602,9 +651,9 @@ impl FormData {
                                                 ))
                                             })
                                             .ok_or_else(|| {
-                                                format!(
-                                                    "Invalid variable or function name {:?}",
-                                                    token
+                                                tr(
+                                                    "validation-expression-token",
+                                                    &[("token", token.to_string())],
                                                 )
                                             })
                                     }
@@ -619,8 +668,7 @@ impl FormData {
                                                 field.id.to_string(),
                                                 FormError {
                                                     id: FormErrorType::Expression(expr_item),
-                                                    error: "This expression cannot be empty"
-                                                        .to_string(),
+                                                    error: t("validation-expression-empty"),
                                                 },
                                             );
                                             has_expression = true;
@@ -647,7 +695,7 @@ impl FormData {
                                 field.id.to_string(),
                                 FormError {
                                     id: FormErrorType::Expression(ExpressionError::Else),
-                                    error: "This field is required".to_string(),
+                                    error: t("validation-required"),
                                 },
                             );
                         }
@@ -656,7 +704,189 @@ impl FormData {
             }
         }
 
-        self.errors.is_empty()
+        if self.errors.is_empty() {
+            self.validate_sections();
+        }
+
+        self.errors.is_empty() && self.section_errors.is_empty()
+    }
+
+    fn validate_sections(&mut self) {
+        let schema = self.schema.clone();
+        for (idx, section) in schema.form.sections.iter().enumerate() {
+            if !section.display(self) {
+                continue;
+            }
+
+            let mut errors = Vec::new();
+            for constraint in &section.constraints {
+                match constraint {
+                    Constraint::RequiredIf { field, eval } => {
+                        if field.display(self) && eval.eval(self) && !self.is_set(field.id) {
+                            errors.push((
+                                Some(field.id),
+                                tr(
+                                    "validation-required-if",
+                                    &[
+                                        ("field", field.label_text()),
+                                        ("other", eval.field.label_text()),
+                                        ("values", eval.values.join(", ")),
+                                    ],
+                                ),
+                            ));
+                        }
+                    }
+                    Constraint::RequiredWith { field, other } => {
+                        if field.display(self)
+                            && other.display(self)
+                            && self.is_set(other.id)
+                            && !self.is_set(field.id)
+                        {
+                            errors.push((
+                                Some(field.id),
+                                tr(
+                                    "validation-required-with",
+                                    &[("field", field.label_text()), ("other", other.label_text())],
+                                ),
+                            ));
+                        }
+                    }
+                    Constraint::GreaterThan { field, other } => {
+                        if !field.display(self) || !other.display(self) {
+                            continue;
+                        }
+                        if let (Some(value), Some(other_value)) =
+                            (self.number_value(field), self.number_value(other))
+                        {
+                            if value <= other_value {
+                                errors.push((
+                                    Some(field.id),
+                                    tr(
+                                        "validation-greater-than",
+                                        &[
+                                            ("field", field.label_text()),
+                                            ("other", other.label_text()),
+                                        ],
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                    Constraint::Schedule {
+                        field,
+                        kind,
+                        expire,
+                    } => {
+                        if !field.display(self) {
+                            continue;
+                        }
+                        if let Some(schedule) = self.schedule_value(field.id) {
+                            let expire = self
+                                .schedule_value(expire.id)
+                                .and_then(|expire| expire.first().copied());
+                            if let Err(error) = kind.check(&schedule, expire) {
+                                errors.push((Some(field.id), error));
+                            }
+                        }
+                    }
+                    Constraint::AtLeastOne { fields } => {
+                        let fields = fields
+                            .iter()
+                            .filter(|field| field.display(self))
+                            .collect::<Vec<_>>();
+                        if !fields.is_empty() && !fields.iter().any(|field| self.is_set(field.id)) {
+                            errors.push((
+                                None,
+                                tr(
+                                    "validation-at-least-one",
+                                    &[(
+                                        "fields",
+                                        fields
+                                            .iter()
+                                            .map(|field| field.label_text())
+                                            .collect::<Vec<_>>()
+                                            .join(", "),
+                                    )],
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if !errors.is_empty() {
+                let mut messages = Vec::with_capacity(errors.len());
+                for (id, error) in errors {
+                    if let Some(id) = id {
+                        if !self.errors.contains_key(id) {
+                            self.errors.insert(
+                                id.to_string(),
+                                FormError {
+                                    id: FormErrorType::Section,
+                                    error: error.clone(),
+                                },
+                            );
+                        }
+                    }
+                    messages.push(error);
+                }
+                self.section_errors.insert(idx, messages);
+            }
+        }
+    }
+
+    // Checks that need the server, run once the form passes validation. Values
+    // that were not changed since the form was loaded are not checked again.
+    pub fn async_checks(
+        &self,
+        loaded: &AHashMap<String, FormValue>,
+    ) -> Vec<(Arc<Field>, AsyncValidator, String)> {
+        let mut checks = Vec::new();
+        for field in self.schema.fields.values() {
+            if field.async_validators.is_empty()
+                || !field.display(self)
+                || loaded.get(field.id) == self.values.get(field.id)
+            {
+                continue;
+            }
+            // Each branch of an expression is checked on its own
+            let values = match self.values.get(field.id) {
+                Some(FormValue::Value(value)) => vec![value.as_str()],
+                Some(FormValue::Expression(expr)) => expr
+                    .if_thens
+                    .iter()
+                    .map(|if_then| if_then.then_.as_str())
+                    .chain([expr.else_.as_str()])
+                    .collect(),
+                _ => vec![],
+            };
+            for value in values.into_iter().filter(|value| !value.is_empty()) {
+                for validator in &field.async_validators {
+                    checks.push((field.clone(), *validator, value.to_string()));
+                }
+            }
+        }
+        checks
+    }
+
+    fn is_set(&self, id: &str) -> bool {
+        !self.value_is_empty(id) || self.secret_is_retained(id)
+    }
+
+    // Only unconditional schedules can be checked
+    fn schedule_value(&self, id: &str) -> Option<Vec<Duration>> {
+        if self.expr_if_thens(id).next().is_some() {
+            return None;
+        }
+        parse_schedule(self.expr_else(id)?)
+    }
+
+    fn number_value(&self, field: &Field) -> Option<f64> {
+        let value = self.get(field.id)?;
+        match field.typ_ {
+            Type::Duration => Duration::parse_value(value).map(|d| d.as_secs_f64()),
+            _ => value.parse::<f64>().ok(),
+        }
     }
 
     pub fn from_settings(schema: Arc<Schema>, settings: Option<Settings>) -> Self {
@@ -666,8 +896,16 @@ impl FormData {
         if let Some(mut settings) = settings {
             for field in schema.fields.values() {
                 match &field.typ_ {
+                    Type::Secret => {
+                        if let Some(value) = settings.remove(field.id) {
+                            if is_secret_reference(&value) {
+                                data.set(field.id, value);
+                            } else {
+                                data.secrets.insert(field.id.to_string());
+                            }
+                        }
+                    }
                     Type::Input
-                    | Type::Secret
                     | Type::Text
                     | Type::Select {
                         typ: SelectType::Single,
@@ -677,14 +915,18 @@ impl FormData {
                     | Type::Duration
                     | Type::Rate
                     | Type::Cron
-                    | Type::Size => {
+                    | Type::Size
+                    | Type::Custom(_) => {
                         if let Some(value) = settings.remove(field.id) {
                             data.set(field.id, value);
                         }
                     }
                     Type::Array
                     | Type::Select {
-                        typ: SelectType::Many | SelectType::ManyWithSearch,
+                        typ:
+                            SelectType::Many
+                            | SelectType::ManyWithSearch
+                            | SelectType::ManyWithGroups(_),
                         ..
                     } => {
                         let values = settings.array_values(field.id);
@@ -692,7 +934,7 @@ impl FormData {
                             data.array_set(field.id, values.into_iter().map(|(_, value)| value));
                         }
                     }
-                    Type::Expression => {
+                    Type::Expression | Type::Rules => {
                         let mut expr = Expression::default();
                         if let Some(else_) = settings.remove(field.id) {
                             expr.else_ = else_;
@@ -788,15 +1030,79 @@ impl FormData {
         data
     }
 
+    // Turns a loaded object into a new one with the same values. Secrets are
+    // write-only and have to be entered again.
+    pub fn into_duplicate(mut self) -> Self {
+        self.is_update = false;
+        self.secrets.clear();
+        self.remove("_id");
+        self
+    }
+
     pub fn is_required(&self, id: &str) -> bool {
         self.schema.fields.get(id).unwrap().is_required(self)
     }
 
+    // Values that can be kept in a draft: schema fields only, never secrets
+    // or the excluded keys.
+    pub fn draft_values(&self, exclude: &[&str]) -> AHashMap<String, FormValue> {
+        self.values
+            .iter()
+            .filter(|(key, _)| {
+                !exclude.contains(&key.as_str())
+                    && self
+                        .schema
+                        .fields
+                        .get(key.as_str())
+                        .is_some_and(|field| !matches!(field.typ_, Type::Secret))
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    pub fn restore_draft(&mut self, values: AHashMap<String, FormValue>, exclude: &[&str]) {
+        let current = self.draft_values(exclude);
+        for key in current.keys() {
+            self.values.remove(key);
+        }
+        self.values.extend(values);
+        self.errors.clear();
+        self.section_errors.clear();
+    }
+
     pub fn into_signal(self) -> RwSignal<Self> {
         RwSignal::new(self)
     }
 }
 
+pub fn is_secret_reference(value: &str) -> bool {
+    value.starts_with("%{") && value.ends_with("}%")
+}
+
+// References have the form %{env:NAME}%, %{file:/path}% or %{vault:path#key}%
+pub fn is_valid_secret_reference(value: &str) -> bool {
+    let Some((kind, target)) = value
+        .strip_prefix("%{")
+        .and_then(|v| v.strip_suffix("}%"))
+        .and_then(|v| v.split_once(':'))
+    else {
+        return false;
+    };
+
+    !target.is_empty()
+        && !target.contains("}%")
+        && match kind {
+            "env" => target
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_'),
+            "file" => target.starts_with('/'),
+            "vault" => target
+                .split_once('#')
+                .is_some_and(|(path, key)| !path.is_empty() && !key.is_empty()),
+            _ => false,
+        }
+}
+
 impl InputCheck {
     pub fn check_value(&self, mut value: String) -> Result<String, Cow<'static, str>> {
         for transformer in &self.transformers {
@@ -813,7 +1119,7 @@ impl InputCheck {
                 match validator {
                     Validator::IsEmail => {
                         if !value.contains('@') {
-                            return Err("This field must be a valid email address".into());
+                            return Err(t("validation-email").into());
                         }
                     }
                     Validator::IsId => {
@@ -821,53 +1127,102 @@ impl InputCheck {
                             .chars()
                             .find(|c| !c.is_alphanumeric() && !['_', '-', '.'].contains(c))
                         {
-                            return Err(format!("Invalid character '{ch}' in this field").into());
+                            return Err(tr(
+                                "validation-invalid-character",
+                                &[("character", ch.to_string())],
+                            )
+                            .into());
                         }
                     }
                     Validator::IsHost => {
                         if value.contains('/') || value.contains(':') {
-                            return Err("This field must be a valid hostname".into());
+                            return Err(t("validation-host").into());
                         }
                     }
                     Validator::IsPort => {
                         if value.parse::<u16>().is_err() {
-                            return Err("This field must be a valid port number".into());
+                            return Err(t("validation-port").into());
                         }
                     }
                     Validator::IsSocketAddr => {
                         if value.parse::<SocketAddr>().is_err() {
-                            return Err("This field must be a valid socket address".into());
+                            return Err(t("validation-socket-address").into());
                         }
                     }
                     Validator::IsUrl => {
                         if !value.contains("://") {
-                            return Err("This field must be a valid URL".into());
+                            return Err(t("validation-url").into());
                         }
                     }
                     Validator::IsDomain => {
                         if !value.contains('.') || value.starts_with('.') || value.ends_with('.') {
-                            return Err("This field must be a valid domain name".into());
+                            return Err(t("validation-domain").into());
+                        }
+                    }
+                    Validator::IsCron => {
+                        if let Err(err) = value.parse::<SimpleCron>() {
+                            return Err(err.into());
+                        }
+                    }
+                    Validator::IsSize => {
+                        if value.parse::<u64>().is_err() {
+                            return Err(t("validation-size").into());
+                        }
+                    }
+                    Validator::IsRate => {
+                        // Rates are written as "amount/period", "false" disables them
+                        let is_valid = value == "false"
+                            || value.split_once('/').is_some_and(|(amount, period)| {
+                                amount.trim().parse::<u64>().is_ok_and(|amount| amount > 0)
+                                    && Duration::parse_value(period.trim())
+                                        .is_some_and(|period| !period.is_zero())
+                            });
+                        if !is_valid {
+                            return Err(t("validation-rate").into());
+                        }
+                    }
+                    Validator::IsHostKeyFingerprint => {
+                        // OpenSSH format, a base64 encoded SHA-256 digest without padding
+                        let is_valid = value.strip_prefix("SHA256:").is_some_and(|digest| {
+                            digest.len() == 43
+                                && digest.bytes().all(|ch| {
+                                    ch.is_ascii_alphanumeric() || ch == b'+' || ch == b'/'
+                                })
+                        });
+                        if !is_valid {
+                            return Err(t("validation-host-key").into());
+                        }
+                    }
+                    Validator::IsEndpoint => {
+                        // Written as "<url> <weight> <priority>", weights start at 1
+                        let mut parts = value.split_whitespace().skip(1);
+                        let weight = parts.next().map(|weight| weight.parse::<u32>());
+                        let priority = parts.next().map(|priority| priority.parse::<u32>());
+                        if !matches!(weight, None | Some(Ok(1..))) {
+                            return Err(t("validation-endpoint-weight").into());
+                        } else if matches!(priority, Some(Err(_))) || parts.next().is_some() {
+                            return Err(t("validation-endpoint").into());
                         }
                     }
                     Validator::IsRegex => {
                         if regex::Regex::new(&value).is_err() {
-                            return Err("This field must be a valid regular expression".into());
+                            return Err(t("validation-regex").into());
                         }
                     }
                     Validator::MinLength(length) => {
                         if value.len() < *length {
-                            return Err(format!(
-                                "This field must be at least {} characters",
-                                length
+                            return Err(tr(
+                                "validation-min-length",
+                                &[("length", length.to_string())],
                             )
                             .into());
                         }
                     }
                     Validator::MaxLength(length) => {
                         if value.len() > *length {
-                            return Err(format!(
-                                "This field must be at most {} characters",
-                                length
+                            return Err(tr(
+                                "validation-max-length",
+                                &[("length", length.to_string())],
                             )
                             .into());
                         }
@@ -875,31 +1230,52 @@ impl InputCheck {
                     Validator::MinValue(val) => match val {
                         NumberType::Integer(val) => {
                             if value.parse::<i64>().ok().filter(|v| v >= val).is_none() {
-                                return Err(format!("This field must be at least {val}").into());
+                                return Err(tr(
+                                    "validation-min-value",
+                                    &[("value", val.to_string())],
+                                )
+                                .into());
                             }
                         }
                         NumberType::Float(val) => {
                             if value.parse::<f64>().ok().filter(|v| v >= val).is_none() {
-                                return Err(format!("This field must be at least {val}").into());
+                                return Err(tr(
+                                    "validation-min-value",
+                                    &[("value", val.to_string())],
+                                )
+                                .into());
                             }
                         }
                     },
                     Validator::MaxValue(val) => match val {
                         NumberType::Integer(val) => {
                             if value.parse::<i64>().ok().filter(|v| v <= val).is_none() {
-                                return Err(format!("This field must be at most {}", val).into());
+                                return Err(tr(
+                                    "validation-max-value",
+                                    &[("value", val.to_string())],
+                                )
+                                .into());
                             }
                         }
                         NumberType::Float(val) => {
                             if value.parse::<f64>().ok().filter(|v| v <= val).is_none() {
-                                return Err(format!("This field must be at most {}", val).into());
+                                return Err(tr(
+                                    "validation-max-value",
+                                    &[("value", val.to_string())],
+                                )
+                                .into());
                             }
                         }
                     },
+                    Validator::IsIp => {
+                        if value.parse::<std::net::IpAddr>().is_err() {
+                            return Err(t("validation-ip").into());
+                        }
+                    }
                     Validator::IsIpOrMask => {
                         let value = if let Some((ip, mask)) = value.rsplit_once('/') {
                             if mask.parse::<u8>().is_err() {
-                                return Err("Invalid IP address mask".into());
+                                return Err(t("validation-ip-mask").into());
                             }
                             ip
                         } else {
@@ -907,7 +1283,7 @@ impl InputCheck {
                         };
 
                         if value.parse::<std::net::IpAddr>().is_err() {
-                            return Err("This field must be a valid IP address or network".into());
+                            return Err(t("validation-ip-network").into());
                         }
                     }
                     Validator::IsValidExpression { .. }
@@ -917,7 +1293,7 @@ impl InputCheck {
                 }
             }
         } else if self.validators.contains(&Validator::Required) {
-            return Err("This field is required".into());
+            return Err(t("validation-required").into());
         }
 
         Ok(value)
@@ -0,0 +1,254 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::cell::RefCell;
+
+use ahash::AHashMap;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use leptos::{use_context, RwSignal, SignalWith};
+use web_sys::{
+    js_sys::{Array, Date, Intl, Object, Reflect},
+    wasm_bindgen::JsValue,
+};
+
+use crate::components::layout::appearance::{Appearance, SizeUnits, TimeZone};
+
+use super::i18n::{current_locale, Locale};
+
+const DECIMAL_UNITS: &[&str] = &["B", "kB", "MB", "GB", "TB", "PB"];
+const BINARY_UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+struct Separators {
+    group: String,
+    decimal: String,
+}
+
+thread_local! {
+    static SEPARATORS: RefCell<AHashMap<Locale, Separators>> = RefCell::new(AHashMap::new());
+}
+
+// Sizes follow the unit preference from the appearance menu, reading it
+// inside a view closure keeps the output in sync when it changes.
+pub fn format_bytes(bytes: u64) -> String {
+    let (base, units) = match size_units() {
+        SizeUnits::Decimal => (1000.0, DECIMAL_UNITS),
+        SizeUnits::Binary => (1024.0, BINARY_UNITS),
+    };
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", format_count(bytes), units[0])
+    } else {
+        format!("{} {}", format_decimal(value, 2), units[unit])
+    }
+}
+
+pub fn format_rate(bytes_per_second: f64) -> String {
+    format!(
+        "{}/s",
+        format_bytes(bytes_per_second.max(0.0).round() as u64)
+    )
+}
+
+pub fn format_count(value: u64) -> String {
+    with_separators(|separators| group_digits(&value.to_string(), &separators.group))
+}
+
+// Trailing zeros are dropped, 1.50 is displayed as 1.5
+pub fn format_decimal(value: f64, max_decimals: usize) -> String {
+    let formatted = format!("{value:.max_decimals$}");
+    let (integer, fraction) = formatted
+        .split_once('.')
+        .unwrap_or((formatted.as_str(), ""));
+    let fraction = fraction.trim_end_matches('0');
+
+    with_separators(|separators| {
+        let mut result = group_digits(integer, &separators.group);
+        if !fraction.is_empty() {
+            result.push_str(&separators.decimal);
+            result.push_str(fraction);
+        }
+        result
+    })
+}
+
+// Dates are rendered by the browser in the language selected in the header and
+// the time zone chosen in the appearance menu.
+pub fn format_date_time(date: &DateTime<Utc>) -> String {
+    format_with_intl(
+        date,
+        &[
+            ("weekday", "short"),
+            ("year", "numeric"),
+            ("month", "short"),
+            ("day", "numeric"),
+            ("hour", "2-digit"),
+            ("minute", "2-digit"),
+            ("second", "2-digit"),
+        ],
+    )
+    .unwrap_or_else(|| with_time_zone(date, "%a, %d %b %Y %H:%M:%S"))
+}
+
+pub fn format_date(date: &DateTime<Utc>) -> String {
+    format_with_intl(
+        date,
+        &[
+            ("weekday", "short"),
+            ("year", "numeric"),
+            ("month", "short"),
+            ("day", "numeric"),
+        ],
+    )
+    .unwrap_or_else(|| with_time_zone(date, "%a, %d %b %Y"))
+}
+
+pub fn format_time(date: &DateTime<Utc>) -> String {
+    format_with_intl(
+        date,
+        &[
+            ("hour", "2-digit"),
+            ("minute", "2-digit"),
+            ("second", "2-digit"),
+        ],
+    )
+    .unwrap_or_else(|| with_time_zone(date, "%H:%M:%S"))
+}
+
+pub fn format_iso(date: &DateTime<Utc>) -> String {
+    match time_zone() {
+        TimeZone::Local => date
+            .with_timezone(&Local)
+            .to_rfc3339_opts(SecondsFormat::Secs, false),
+        TimeZone::Utc => date.to_rfc3339_opts(SecondsFormat::Secs, true),
+    }
+}
+
+// Phrases such as "3 min ago" or "in 2 hours", using the largest unit that
+// fits the distance from now.
+pub fn format_relative(date: &DateTime<Utc>) -> String {
+    let seconds = (date.timestamp_millis() - Utc::now().timestamp_millis()) as f64 / 1000.0;
+    let (value, unit) = match seconds.abs() {
+        secs if secs < 60.0 => (seconds, "second"),
+        secs if secs < 3600.0 => (seconds / 60.0, "minute"),
+        secs if secs < 86400.0 => (seconds / 3600.0, "hour"),
+        secs if secs < 86400.0 * 30.0 => (seconds / 86400.0, "day"),
+        secs if secs < 86400.0 * 365.0 => (seconds / (86400.0 * 30.0), "month"),
+        _ => (seconds / (86400.0 * 365.0), "year"),
+    };
+
+    let options = intl_options(&[("numeric", "auto"), ("style", "short")]);
+    Intl::RelativeTimeFormat::new(&intl_locales(), &options)
+        .format(value.trunc(), unit)
+        .into()
+}
+
+fn format_with_intl(date: &DateTime<Utc>, fields: &[(&str, &str)]) -> Option<String> {
+    let options = intl_options(fields);
+    if time_zone() == TimeZone::Utc {
+        Reflect::set(&options, &"timeZone".into(), &"UTC".into()).ok()?;
+    }
+    let date = Date::new(&JsValue::from_f64(date.timestamp_millis() as f64));
+
+    Intl::DateTimeFormat::new(&intl_locales(), &options)
+        .format()
+        .call1(&JsValue::NULL, &date)
+        .ok()
+        .and_then(|formatted| formatted.as_string())
+}
+
+fn with_time_zone(date: &DateTime<Utc>, format: &str) -> String {
+    match time_zone() {
+        TimeZone::Local => date.with_timezone(&Local).format(format).to_string(),
+        TimeZone::Utc => date.format(format).to_string(),
+    }
+}
+
+fn intl_locales() -> Array {
+    Array::of1(&current_locale().code().into())
+}
+
+fn intl_options(fields: &[(&str, &str)]) -> Object {
+    let options = Object::new();
+    for (key, value) in fields {
+        let _ = Reflect::set(&options, &(*key).into(), &(*value).into());
+    }
+    options
+}
+
+fn time_zone() -> TimeZone {
+    use_context::<RwSignal<Appearance>>()
+        .map(|appearance| appearance.with(|appearance| appearance.time_zone))
+        .unwrap_or_default()
+}
+
+fn size_units() -> SizeUnits {
+    use_context::<RwSignal<Appearance>>()
+        .map(|appearance| appearance.with(|appearance| appearance.size_units))
+        .unwrap_or_default()
+}
+
+fn group_digits(integer: &str, group: &str) -> String {
+    let (sign, digits) = match integer.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", integer),
+    };
+    let mut result = String::with_capacity(integer.len() + (digits.len() / 3) * group.len());
+    result.push_str(sign);
+    for (pos, digit) in digits.chars().enumerate() {
+        if pos > 0 && (digits.len() - pos) % 3 == 0 {
+            result.push_str(group);
+        }
+        result.push(digit);
+    }
+    result
+}
+
+fn with_separators<T>(f: impl FnOnce(&Separators) -> T) -> T {
+    let locale = current_locale();
+    SEPARATORS.with(|separators| {
+        f(separators
+            .borrow_mut()
+            .entry(locale)
+            .or_insert_with(|| locale_separators(locale)))
+    })
+}
+
+// Each language is probed once by formatting a sample number, numbering
+// systems without ASCII digits fall back to the English separators.
+fn locale_separators(locale: Locale) -> Separators {
+    let sample = Intl::NumberFormat::new(&Array::of1(&locale.code().into()), &Object::new())
+        .format()
+        .call1(&JsValue::NULL, &JsValue::from_f64(1234567.5))
+        .ok()
+        .and_then(|formatted| formatted.as_string())
+        .unwrap_or_default();
+    let digits = sample.chars().filter(|ch| ch.is_ascii_digit()).count();
+    let separators = sample
+        .chars()
+        .filter(|ch| !ch.is_ascii_digit())
+        .collect::<Vec<_>>();
+
+    match separators.as_slice() {
+        [group, .., decimal] if digits == 8 => Separators {
+            group: group.to_string(),
+            decimal: decimal.to_string(),
+        },
+        [decimal] if digits == 8 => Separators {
+            group: String::new(),
+            decimal: decimal.to_string(),
+        },
+        _ => Separators {
+            group: ",".to_string(),
+            decimal: ".".to_string(),
+        },
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Color,
+    pages::config::{Settings, UpdateSettings},
+};
+
+use super::{
+    http::{self, HttpRequest},
+    AccessToken,
+};
+
+const THRESHOLDS_PREFIX: &str = "webadmin.thresholds";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum HealthLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Threshold {
+    #[serde(default)]
+    pub warning: Option<f64>,
+    #[serde(default)]
+    pub critical: Option<f64>,
+}
+
+// Thresholds keyed by widget id, stored in the server settings so that every
+// administrator follows the same health view
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Thresholds(AHashMap<String, Threshold>);
+
+#[derive(Clone, Copy)]
+pub struct HealthState {
+    pub thresholds: RwSignal<Thresholds>,
+    // Last evaluated level of each widget with a threshold
+    pub levels: RwSignal<AHashMap<String, HealthLevel>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FetchSettings {
+    items: Settings,
+}
+
+pub fn init_health() {
+    provide_context(HealthState {
+        thresholds: create_rw_signal(Thresholds::default()),
+        levels: create_rw_signal(AHashMap::new()),
+    });
+}
+
+pub fn use_health() -> HealthState {
+    expect_context::<HealthState>()
+}
+
+impl HealthState {
+    pub fn level(&self, id: &str, value: f64) -> HealthLevel {
+        self.thresholds
+            .with(|thresholds| thresholds.get(id).map(|threshold| threshold.level(value)))
+            .unwrap_or_default()
+    }
+
+    pub fn overall(&self) -> HealthLevel {
+        self.levels
+            .with(|levels| levels.values().max().copied())
+            .unwrap_or_default()
+    }
+
+    pub fn set_thresholds(&self, thresholds: Thresholds) {
+        self.thresholds.set(thresholds);
+        self.levels.set(AHashMap::new());
+    }
+
+    pub fn set_threshold(&self, id: &str, threshold: Threshold) {
+        self.thresholds.update(|thresholds| {
+            if threshold.is_empty() {
+                thresholds.0.remove(id);
+            } else {
+                thresholds.0.insert(id.to_string(), threshold);
+            }
+        });
+        if threshold.is_empty() {
+            self.levels.update(|levels| {
+                levels.remove(id);
+            });
+        }
+    }
+}
+
+impl Threshold {
+    pub fn level(&self, value: f64) -> HealthLevel {
+        if self.critical.is_some_and(|critical| value >= critical) {
+            HealthLevel::Critical
+        } else if self.warning.is_some_and(|warning| value >= warning) {
+            HealthLevel::Warning
+        } else {
+            HealthLevel::Normal
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warning.is_none() && self.critical.is_none()
+    }
+}
+
+impl Thresholds {
+    pub fn get(&self, id: &str) -> Option<&Threshold> {
+        self.0.get(id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Stored as "<widget>.warning" and "<widget>.critical" under the thresholds prefix
+pub async fn fetch_thresholds(auth: &AccessToken) -> http::Result<Thresholds> {
+    let settings = HttpRequest::get("/api/settings/list")
+        .with_authorization(auth)
+        .with_parameter("prefix", THRESHOLDS_PREFIX)
+        .send::<FetchSettings>()
+        .await?
+        .items;
+    let mut thresholds = Thresholds::default();
+    for (key, value) in settings {
+        let Some((id, level)) = key.rsplit_once('.') else {
+            continue;
+        };
+        let Ok(value) = value.parse::<f64>() else {
+            continue;
+        };
+        let threshold = thresholds.0.entry(id.to_string()).or_default();
+        match level {
+            "warning" => threshold.warning = Some(value),
+            "critical" => threshold.critical = Some(value),
+            _ => {}
+        }
+    }
+    thresholds.0.retain(|_, threshold| !threshold.is_empty());
+
+    Ok(thresholds)
+}
+
+pub async fn save_threshold(
+    auth: &AccessToken,
+    id: &str,
+    threshold: Threshold,
+) -> http::Result<()> {
+    let prefix = format!("{THRESHOLDS_PREFIX}.{id}");
+    let mut updates = vec![UpdateSettings::Clear {
+        prefix: format!("{prefix}."),
+        filter: None,
+    }];
+    let values = [
+        ("warning", threshold.warning),
+        ("critical", threshold.critical),
+    ]
+    .into_iter()
+    .filter_map(|(level, value)| Some((level.to_string(), value?.to_string())))
+    .collect::<Vec<_>>();
+    if !values.is_empty() {
+        updates.push(UpdateSettings::Insert {
+            prefix: Some(prefix),
+            values,
+            assert_empty: false,
+        });
+    }
+
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(updates)?
+        .send::<serde_json::Value>()
+        .await
+        .map(|_| ())
+}
+
+impl HealthLevel {
+    pub fn color(&self) -> Color {
+        match self {
+            HealthLevel::Normal => Color::Green,
+            HealthLevel::Warning => Color::Yellow,
+            HealthLevel::Critical => Color::Red,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HealthLevel::Normal => "Healthy",
+            HealthLevel::Warning => "Warning",
+            HealthLevel::Critical => "Critical",
+        }
+    }
+}
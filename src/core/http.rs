@@ -4,18 +4,33 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use std::sync::Arc;
+
 use ahash::AHashMap;
 use base64::{engine::general_purpose::STANDARD, Engine};
-use gloo_net::http::{Headers, Method, RequestBuilder};
+use gloo_net::http::{Headers, Method, Request, RequestBuilder};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_sys::js_sys::Uint8Array;
 
-use super::{url::UrlBuilder, AccessToken};
+use super::{
+    recording::{is_recording, record_call},
+    session::renew_session,
+    url::UrlBuilder,
+    AccessToken,
+};
 
 pub struct HttpRequest {
     method: Method,
     url: UrlBuilder,
     headers: Headers,
-    body: Option<String>,
+    body: Option<Body>,
+    access_token: Option<Arc<String>>,
+    record: bool,
+}
+
+enum Body {
+    Text(String),
+    Binary(Vec<u8>),
 }
 
 #[derive(Deserialize)]
@@ -73,6 +88,8 @@ impl<'x> HttpRequest {
             url: url.into_url_builder(),
             headers: Headers::new(),
             body: None,
+            access_token: None,
+            record: true,
         }
     }
 
@@ -124,6 +141,7 @@ impl<'x> HttpRequest {
         if !auth_token.base_url.is_empty() {
             result.url.prepend_path(auth_token.base_url.as_str());
         }
+        result.access_token = Some(auth_token.access_token.clone());
         result
     }
 
@@ -167,7 +185,17 @@ impl<'x> HttpRequest {
     }
 
     pub fn with_raw_body(mut self, body: String) -> Self {
-        self.body = Some(body);
+        self.body = Some(Body::Text(body));
+        self
+    }
+
+    pub fn with_binary_body(mut self, body: Vec<u8>) -> Self {
+        self.body = Some(Body::Binary(body));
+        self
+    }
+
+    pub fn without_recording(mut self) -> Self {
+        self.record = false;
         self
     }
 
@@ -197,7 +225,25 @@ impl<'x> HttpRequest {
         }
     }
 
-    pub async fn send_raw(self) -> Result<Vec<u8>> {
+    // Sends the requests one after the other so that the outcome of each item
+    // can be reported. An expired session stops the batch.
+    pub async fn send_batch<T>(
+        requests: impl IntoIterator<Item = (String, HttpRequest)>,
+    ) -> Result<Vec<(String, Result<T>)>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut results = Vec::new();
+        for (item, request) in requests {
+            match request.send::<T>().await {
+                Err(Error::Unauthorized) => return Err(Error::Unauthorized),
+                result => results.push((item, result)),
+            }
+        }
+        Ok(results)
+    }
+
+    pub async fn send_raw(mut self) -> Result<Vec<u8>> {
         let abort_controller = web_sys::AbortController::new().ok();
         let abort_signal = abort_controller.as_ref().map(|a| a.signal());
 
@@ -208,20 +254,42 @@ impl<'x> HttpRequest {
             }
         });
 
-        let builder = RequestBuilder::new(&self.url.finish())
-            .method(self.method)
-            .headers(self.headers)
-            .abort_signal(abort_signal.as_ref());
-        let req = if let Some(body) = self.body {
-            builder.body(body)
+        let url = std::mem::take(&mut self.url).finish();
+        // Binary bodies are file uploads, which cannot be replayed
+        let recorded = if self.record && is_recording() {
+            match &self.body {
+                Some(Body::Text(text)) => Some(Some(text.clone())),
+                Some(Body::Binary(_)) => None,
+                None => Some(None),
+            }
+            .map(|body| (self.method.clone(), self.headers.get("Content-Type"), body))
         } else {
-            builder.build()
-        }?;
-
-        let response = req.send().await?;
+            None
+        };
+
+        let mut response = self.build(&url, abort_signal.as_ref())?.send().await?;
+
+        // Expired sessions are renewed and the call is sent again with the new token
+        if response.status() == 401 {
+            if let Some(access_token) = &self.access_token {
+                // Boxed, renewing the session sends requests of its own
+                if let Some(auth_token) = Box::pin(renew_session(access_token)).await {
+                    self.headers.set(
+                        "Authorization",
+                        &format!("Bearer {}", auth_token.access_token),
+                    );
+                    response = self.build(&url, abort_signal.as_ref())?.send().await?;
+                }
+            }
+        }
 
         match response.status() {
-            200..=299 => response.binary().await.map_err(Into::into),
+            200..=299 => {
+                if let Some((method, content_type, body)) = recorded {
+                    record_call(&method, &url, content_type, body);
+                }
+                response.binary().await.map_err(Into::into)
+            }
             401 => Err(Error::Unauthorized),
             402 => Err(Error::TotpRequired),
             403 => Err(Error::Forbidden),
@@ -232,6 +300,23 @@ impl<'x> HttpRequest {
             })),
         }
     }
+
+    fn build(&self, url: &str, abort_signal: Option<&web_sys::AbortSignal>) -> Result<Request> {
+        let headers = Headers::new();
+        for (name, value) in self.headers.entries() {
+            headers.set(&name, &value);
+        }
+        let builder = RequestBuilder::new(url)
+            .method(self.method.clone())
+            .headers(headers)
+            .abort_signal(abort_signal);
+        match &self.body {
+            Some(Body::Text(body)) => builder.body(body.as_str()),
+            Some(Body::Binary(body)) => builder.body(Uint8Array::from(body.as_slice())),
+            None => builder.build(),
+        }
+        .map_err(Into::into)
+    }
 }
 
 impl IntoUrlBuilder for String {
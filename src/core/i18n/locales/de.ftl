@@ -0,0 +1,100 @@
+## Header
+
+header-language = Sprache
+header-management = Verwaltung
+header-settings = Einstellungen
+header-account = Konto
+header-whats-new = Neuigkeiten
+header-sponsor = Stalwart Open Source unterstützen
+header-logout = Abmelden
+header-search-settings = Einstellungen durchsuchen
+header-server = Server
+
+## Server
+
+server-validate = Konfiguration prüfen
+server-reload = Konfiguration neu laden
+server-restart = Server neu starten
+server-working = Wird ausgeführt…
+
+## Forms
+
+form-optional = (Optional)
+button-cancel = Abbrechen
+button-undo = Rückgängig
+button-redo = Wiederholen
+button-save = Änderungen speichern
+button-save-reload = Speichern & neu laden
+
+## Validation
+
+validation-required = Dieses Feld ist erforderlich
+validation-email = Dieses Feld muss eine gültige E-Mail-Adresse sein
+validation-invalid-character = Ungültiges Zeichen '{ $character }' in diesem Feld
+validation-host = Dieses Feld muss ein gültiger Hostname sein
+validation-port = Dieses Feld muss eine gültige Portnummer sein
+validation-socket-address = Dieses Feld muss eine gültige Socket-Adresse sein
+validation-url = Dieses Feld muss eine gültige URL sein
+validation-domain = Dieses Feld muss ein gültiger Domainname sein
+validation-regex = Dieses Feld muss ein gültiger regulärer Ausdruck sein
+validation-min-length = Dieses Feld muss mindestens { $length } Zeichen lang sein
+validation-max-length = Dieses Feld darf höchstens { $length } Zeichen lang sein
+validation-min-value = Dieses Feld muss mindestens { $value } sein
+validation-max-value = Dieses Feld darf höchstens { $value } sein
+validation-ip = Dieses Feld muss eine gültige IP-Adresse sein
+validation-ip-mask = Ungültige IP-Adressmaske
+validation-ip-network = Dieses Feld muss eine gültige IP-Adresse oder ein gültiges Netzwerk sein
+validation-size = Dieses Feld muss eine Größe in Bytes sein
+validation-rate = Dieses Feld muss eine Rate wie 100/1m sein
+validation-host-key = Dieses Feld muss ein SHA256-Host-Key-Fingerabdruck wie SHA256:jK3t… sein
+validation-endpoint-weight = Die Gewichtung des Endpunkts muss mindestens 1 betragen
+validation-endpoint = Endpunkte müssen als URL mit optionaler Gewichtung und Priorität angegeben werden
+validation-min-items = Mindestens { $count } Einträge sind erforderlich
+validation-max-items = Höchstens { $count } Einträge sind erlaubt
+validation-secret-reference = Dieses Feld muss eine gültige env-, file- oder vault-Referenz sein
+validation-cron-format = Erwartet werden drei durch Leerzeichen getrennte Werte: Minute, Stunde und Wochentag
+validation-cron-minute = Die Minute muss eine Zahl zwischen 0 und 59 sein
+validation-cron-hour = Die Stunde muss "*" oder eine Zahl zwischen 0 und 23 sein
+validation-cron-day = Der Wochentag muss "*" oder eine Zahl zwischen 1 und 7 sein
+validation-cron-weekly = Ein wöchentlicher Zeitplan erfordert eine Stunde
+validation-expression-token = Ungültiger Variablen- oder Funktionsname "{ $token }"
+validation-expression-empty = Dieser Ausdruck darf nicht leer sein
+validation-required-if = { $field } ist erforderlich, wenn { $other } den Wert { $values } hat
+validation-required-with = { $field } ist erforderlich, wenn { $other } gesetzt ist
+validation-greater-than = { $field } muss größer als { $other } sein
+validation-at-least-one = Mindestens eines der Felder { $fields } muss gesetzt sein
+
+## Stores
+
+section-store-configuration = Konfiguration
+section-store-authentication = Authentifizierung
+field-store-_id = Speicher-ID
+    .help = Eindeutige Kennung des Speichers
+field-store-type = Typ
+    .help = Art des Speicher-Backends
+field-store-host = Hostname
+    .help = Hostname des Datenbankservers
+field-store-port = Port
+    .help = Port des Datenbankservers
+field-store-database = Datenbank
+    .help = Name der Datenbank
+field-store-user = Benutzername
+    .help = Benutzername für die Verbindung zur Datenbank
+field-store-password = Passwort
+    .help = Passwort für die Verbindung zur Datenbank
+field-store-timeout = Zeitüberschreitung
+    .help = Zeitüberschreitung der Verbindung zur Datenbank
+
+## Reload
+
+reload-success = Einstellungen erfolgreich neu geladen
+reload-failed = Einstellungen konnten nicht neu geladen werden
+reload-edit = { $setting } bearbeiten
+reload-error-parse = { $key } konnte nicht gelesen werden: { $error }
+reload-error-build = Fehler beim Erstellen von { $key }: { $error }
+reload-error-macro = Makrofehler in { $key }: { $error }
+reload-warning-missing = Warnung: Fehlende Einstellung { $key }
+reload-warning-default = Warnung: Standardwert { $value } für { $key } verwendet
+reload-warning-unread = Warnung: Ungelesener Wert { $value } für { $key }
+reload-warning-build = Warnung: Fehler beim Erstellen von { $key }: { $error }
+reload-warning-parse = Warnung: Lesefehler für { $key }: { $error }
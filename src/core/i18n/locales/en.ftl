@@ -0,0 +1,91 @@
+# Messages of the interface, schema labels and help texts are written in
+# English in the schema builder and only need entries in other languages:
+#
+#   form-<schema> = Form title
+#       .subtitle = Form subtitle
+#   section-<schema>-<section title> = Section title
+#   field-<schema>-<field> = Field label
+#       .help = Field help text
+#
+# Dots in field ids are replaced by dashes, for example
+# field-store-pool-max-connections.
+
+## Header
+
+header-language = Language
+header-management = Management
+header-settings = Settings
+header-account = Account
+header-whats-new = What's new
+header-sponsor = Sponsor Stalwart open source
+header-logout = Logout
+header-search-settings = Search settings
+header-server = Server
+
+## Server
+
+server-validate = Validate configuration
+server-reload = Reload configuration
+server-restart = Restart server
+server-working = Working…
+
+## Forms
+
+form-optional = (Optional)
+button-cancel = Cancel
+button-undo = Undo
+button-redo = Redo
+button-save = Save changes
+button-save-reload = Save & Reload
+
+## Validation
+
+validation-required = This field is required
+validation-email = This field must be a valid email address
+validation-invalid-character = Invalid character '{ $character }' in this field
+validation-host = This field must be a valid hostname
+validation-port = This field must be a valid port number
+validation-socket-address = This field must be a valid socket address
+validation-url = This field must be a valid URL
+validation-domain = This field must be a valid domain name
+validation-regex = This field must be a valid regular expression
+validation-min-length = This field must be at least { $length } characters
+validation-max-length = This field must be at most { $length } characters
+validation-min-value = This field must be at least { $value }
+validation-max-value = This field must be at most { $value }
+validation-ip = This field must be a valid IP address
+validation-ip-mask = Invalid IP address mask
+validation-ip-network = This field must be a valid IP address or network
+validation-size = This field must be a size in bytes
+validation-rate = This field must be a rate such as 100/1m
+validation-host-key = This field must be a SHA256 host key fingerprint such as SHA256:jK3t…
+validation-endpoint-weight = The endpoint weight must be at least 1
+validation-endpoint = Endpoints must be written as a URL followed by an optional weight and priority
+validation-min-items = At least { $count } items are required
+validation-max-items = At most { $count } items are allowed
+validation-secret-reference = This field must be a valid env, file or vault reference
+validation-cron-format = Expected three values separated by spaces: minute, hour and day of week
+validation-cron-minute = The minute must be a number between 0 and 59
+validation-cron-hour = The hour must be "*" or a number between 0 and 23
+validation-cron-day = The day of week must be "*" or a number between 1 and 7
+validation-cron-weekly = A weekly schedule requires an hour
+validation-expression-token = Invalid variable or function name "{ $token }"
+validation-expression-empty = This expression cannot be empty
+validation-required-if = { $field } is required when { $other } is { $values }
+validation-required-with = { $field } is required when { $other } is set
+validation-greater-than = { $field } must be greater than { $other }
+validation-at-least-one = At least one of { $fields } must be set
+
+## Reload
+
+reload-success = Settings successfully reloaded
+reload-failed = Failed to reload settings
+reload-edit = Edit { $setting }
+reload-error-parse = Failed to parse { $key }: { $error }
+reload-error-build = Build error for { $key }: { $error }
+reload-error-macro = Macro error on { $key }: { $error }
+reload-warning-missing = Warning: Missing setting { $key }
+reload-warning-default = Warning: Applied default value { $value } to { $key }
+reload-warning-unread = Warning: Unread value { $value } for { $key }
+reload-warning-build = Warning: Build error for { $key }: { $error }
+reload-warning-parse = Warning: Parse error for { $key }: { $error }
@@ -0,0 +1,100 @@
+## Header
+
+header-language = Idioma
+header-management = Administración
+header-settings = Configuración
+header-account = Cuenta
+header-whats-new = Novedades
+header-sponsor = Patrocinar el código abierto de Stalwart
+header-logout = Cerrar sesión
+header-search-settings = Buscar en la configuración
+header-server = Servidor
+
+## Server
+
+server-validate = Validar configuración
+server-reload = Recargar configuración
+server-restart = Reiniciar servidor
+server-working = Procesando…
+
+## Forms
+
+form-optional = (Opcional)
+button-cancel = Cancelar
+button-undo = Deshacer
+button-redo = Rehacer
+button-save = Guardar cambios
+button-save-reload = Guardar y recargar
+
+## Validation
+
+validation-required = Este campo es obligatorio
+validation-email = Este campo debe ser una dirección de correo válida
+validation-invalid-character = Carácter '{ $character }' no válido en este campo
+validation-host = Este campo debe ser un nombre de host válido
+validation-port = Este campo debe ser un número de puerto válido
+validation-socket-address = Este campo debe ser una dirección de socket válida
+validation-url = Este campo debe ser una URL válida
+validation-domain = Este campo debe ser un nombre de dominio válido
+validation-regex = Este campo debe ser una expresión regular válida
+validation-min-length = Este campo debe tener al menos { $length } caracteres
+validation-max-length = Este campo debe tener como máximo { $length } caracteres
+validation-min-value = Este campo debe ser como mínimo { $value }
+validation-max-value = Este campo debe ser como máximo { $value }
+validation-ip = Este campo debe ser una dirección IP válida
+validation-ip-mask = Máscara de dirección IP no válida
+validation-ip-network = Este campo debe ser una dirección IP o red válida
+validation-size = Este campo debe ser un tamaño en bytes
+validation-rate = Este campo debe ser una tasa como 100/1m
+validation-host-key = Este campo debe ser una huella de clave de host SHA256 como SHA256:jK3t…
+validation-endpoint-weight = El peso del endpoint debe ser al menos 1
+validation-endpoint = Los endpoints deben escribirse como una URL seguida de un peso y una prioridad opcionales
+validation-min-items = Se requieren al menos { $count } elementos
+validation-max-items = Se permiten como máximo { $count } elementos
+validation-secret-reference = Este campo debe ser una referencia env, file o vault válida
+validation-cron-format = Se esperan tres valores separados por espacios: minuto, hora y día de la semana
+validation-cron-minute = El minuto debe ser un número entre 0 y 59
+validation-cron-hour = La hora debe ser "*" o un número entre 0 y 23
+validation-cron-day = El día de la semana debe ser "*" o un número entre 1 y 7
+validation-cron-weekly = Una programación semanal requiere una hora
+validation-expression-token = Nombre de variable o función no válido "{ $token }"
+validation-expression-empty = Esta expresión no puede estar vacía
+validation-required-if = { $field } es obligatorio cuando { $other } es { $values }
+validation-required-with = { $field } es obligatorio cuando { $other } está definido
+validation-greater-than = { $field } debe ser mayor que { $other }
+validation-at-least-one = Al menos uno de { $fields } debe estar definido
+
+## Stores
+
+section-store-configuration = Configuración
+section-store-authentication = Autenticación
+field-store-_id = Identificador del almacén
+    .help = Identificador único del almacén
+field-store-type = Tipo
+    .help = Tipo de almacenamiento
+field-store-host = Nombre de host
+    .help = Nombre de host del servidor de base de datos
+field-store-port = Puerto
+    .help = Puerto del servidor de base de datos
+field-store-database = Base de datos
+    .help = Nombre de la base de datos
+field-store-user = Usuario
+    .help = Usuario para conectarse a la base de datos
+field-store-password = Contraseña
+    .help = Contraseña para conectarse a la base de datos
+field-store-timeout = Tiempo de espera
+    .help = Tiempo de espera de la conexión a la base de datos
+
+## Reload
+
+reload-success = Configuración recargada correctamente
+reload-failed = No se pudo recargar la configuración
+reload-edit = Editar { $setting }
+reload-error-parse = No se pudo analizar { $key }: { $error }
+reload-error-build = Error de construcción en { $key }: { $error }
+reload-error-macro = Error de macro en { $key }: { $error }
+reload-warning-missing = Aviso: falta el ajuste { $key }
+reload-warning-default = Aviso: se aplicó el valor predeterminado { $value } a { $key }
+reload-warning-unread = Aviso: valor no leído { $value } en { $key }
+reload-warning-build = Aviso: error de construcción en { $key }: { $error }
+reload-warning-parse = Aviso: error de análisis en { $key }: { $error }
@@ -0,0 +1,100 @@
+## Header
+
+header-language = Langue
+header-management = Administration
+header-settings = Paramètres
+header-account = Compte
+header-whats-new = Nouveautés
+header-sponsor = Soutenir l'open source Stalwart
+header-logout = Se déconnecter
+header-search-settings = Rechercher dans les paramètres
+header-server = Serveur
+
+## Server
+
+server-validate = Valider la configuration
+server-reload = Recharger la configuration
+server-restart = Redémarrer le serveur
+server-working = En cours…
+
+## Forms
+
+form-optional = (Facultatif)
+button-cancel = Annuler
+button-undo = Annuler la modification
+button-redo = Rétablir
+button-save = Enregistrer
+button-save-reload = Enregistrer et recharger
+
+## Validation
+
+validation-required = Ce champ est obligatoire
+validation-email = Ce champ doit être une adresse e-mail valide
+validation-invalid-character = Caractère '{ $character }' non valide dans ce champ
+validation-host = Ce champ doit être un nom d'hôte valide
+validation-port = Ce champ doit être un numéro de port valide
+validation-socket-address = Ce champ doit être une adresse de socket valide
+validation-url = Ce champ doit être une URL valide
+validation-domain = Ce champ doit être un nom de domaine valide
+validation-regex = Ce champ doit être une expression régulière valide
+validation-min-length = Ce champ doit contenir au moins { $length } caractères
+validation-max-length = Ce champ doit contenir au plus { $length } caractères
+validation-min-value = Ce champ doit être au moins égal à { $value }
+validation-max-value = Ce champ doit être au plus égal à { $value }
+validation-ip = Ce champ doit être une adresse IP valide
+validation-ip-mask = Masque d'adresse IP non valide
+validation-ip-network = Ce champ doit être une adresse IP ou un réseau valide
+validation-size = Ce champ doit être une taille en octets
+validation-rate = Ce champ doit être un débit tel que 100/1m
+validation-host-key = Ce champ doit être une empreinte de clé d'hôte SHA256 telle que SHA256:jK3t…
+validation-endpoint-weight = Le poids du point de terminaison doit être d'au moins 1
+validation-endpoint = Les points de terminaison doivent être une URL suivie d'un poids et d'une priorité facultatifs
+validation-min-items = Au moins { $count } éléments sont requis
+validation-max-items = Au plus { $count } éléments sont autorisés
+validation-secret-reference = Ce champ doit être une référence env, file ou vault valide
+validation-cron-format = Trois valeurs séparées par des espaces sont attendues : minute, heure et jour de la semaine
+validation-cron-minute = La minute doit être un nombre entre 0 et 59
+validation-cron-hour = L'heure doit être "*" ou un nombre entre 0 et 23
+validation-cron-day = Le jour de la semaine doit être "*" ou un nombre entre 1 et 7
+validation-cron-weekly = Une planification hebdomadaire nécessite une heure
+validation-expression-token = Nom de variable ou de fonction non valide "{ $token }"
+validation-expression-empty = Cette expression ne peut pas être vide
+validation-required-if = { $field } est requis lorsque { $other } vaut { $values }
+validation-required-with = { $field } est requis lorsque { $other } est défini
+validation-greater-than = { $field } doit être supérieur à { $other }
+validation-at-least-one = Au moins un des champs { $fields } doit être défini
+
+## Stores
+
+section-store-configuration = Configuration
+section-store-authentication = Authentification
+field-store-_id = Identifiant du stockage
+    .help = Identifiant unique du stockage
+field-store-type = Type
+    .help = Type de stockage
+field-store-host = Nom d'hôte
+    .help = Nom d'hôte du serveur de base de données
+field-store-port = Port
+    .help = Port du serveur de base de données
+field-store-database = Base de données
+    .help = Nom de la base de données
+field-store-user = Utilisateur
+    .help = Utilisateur pour se connecter à la base de données
+field-store-password = Mot de passe
+    .help = Mot de passe pour se connecter à la base de données
+field-store-timeout = Délai d'attente
+    .help = Délai d'attente de la connexion à la base de données
+
+## Reload
+
+reload-success = Paramètres rechargés avec succès
+reload-failed = Échec du rechargement des paramètres
+reload-edit = Modifier { $setting }
+reload-error-parse = Impossible d'analyser { $key } : { $error }
+reload-error-build = Erreur de construction pour { $key } : { $error }
+reload-error-macro = Erreur de macro sur { $key } : { $error }
+reload-warning-missing = Avertissement : paramètre manquant { $key }
+reload-warning-default = Avertissement : valeur par défaut { $value } appliquée à { $key }
+reload-warning-unread = Avertissement : valeur non lue { $value } pour { $key }
+reload-warning-build = Avertissement : erreur de construction pour { $key } : { $error }
+reload-warning-parse = Avertissement : erreur d'analyse pour { $key } : { $error }
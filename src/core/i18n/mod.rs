@@ -0,0 +1,222 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{cell::Cell, sync::OnceLock};
+
+use ahash::AHashMap;
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+const LOCALE_STORAGE_KEY: &str = "webadmin_locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+}
+
+#[derive(Clone, Copy)]
+pub struct I18n {
+    pub locale: RwSignal<Locale>,
+}
+
+// Messages are kept in Fluent files, of which a subset is supported: simple
+// messages with attributes, multiline values and `{ $variable }` placeables.
+type Catalog = AHashMap<String, String>;
+
+thread_local! {
+    // Also read outside of a reactive owner, for example by form validation
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::En) };
+}
+
+pub fn init_i18n() {
+    let locale = LocalStorage::get::<Locale>(LOCALE_STORAGE_KEY)
+        .ok()
+        .or_else(|| {
+            window()
+                .navigator()
+                .languages()
+                .iter()
+                .filter_map(|language| language.as_string())
+                .find_map(|language| Locale::from_code(&language))
+        })
+        .unwrap_or_default();
+    LOCALE.set(locale);
+    set_document_lang(locale);
+    provide_context(I18n {
+        locale: create_rw_signal(locale),
+    });
+}
+
+pub fn use_i18n() -> I18n {
+    expect_context::<I18n>()
+}
+
+impl I18n {
+    pub fn set_locale(&self, locale: Locale) {
+        LOCALE.set(locale);
+        set_document_lang(locale);
+        if let Err(err) = LocalStorage::set(LOCALE_STORAGE_KEY, locale) {
+            log::warn!("Failed to store language preference: {err}");
+        }
+        self.locale.set(locale);
+    }
+}
+
+// Returns the message `key` in the current language, falling back to English
+// and then to the key itself.
+pub fn t(key: &str) -> String {
+    tr(key, &[])
+}
+
+pub fn tr(key: &str, args: &[(&str, String)]) -> String {
+    lookup(key)
+        .map(|message| format_message(message, args))
+        .unwrap_or_else(|| key.to_string())
+}
+
+// Translates text defined in code, such as schema labels, which is used as is
+// when the current language has no message for `key`.
+pub fn translate(key: &str, text: &str) -> String {
+    let locale = current_locale();
+    if locale != Locale::En {
+        if let Some(message) = locale.catalog().get(key) {
+            return message.clone();
+        }
+    }
+    text.to_string()
+}
+
+fn lookup(key: &str) -> Option<&'static str> {
+    current_locale()
+        .catalog()
+        .get(key)
+        .or_else(|| Locale::En.catalog().get(key))
+        .map(|message| message.as_str())
+}
+
+pub fn current_locale() -> Locale {
+    // Track the locale so views are updated when the language changes
+    if let Some(i18n) = use_context::<I18n>() {
+        i18n.locale.track();
+    }
+    LOCALE.get()
+}
+
+fn format_message(message: &str, args: &[(&str, String)]) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let placeable = rest[start + 1..start + end].trim();
+        match placeable
+            .strip_prefix('$')
+            .and_then(|name| args.iter().find(|(arg, _)| *arg == name))
+        {
+            Some((_, value)) => result.push_str(value),
+            None => result.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn parse_catalog(source: &str) -> Catalog {
+    let mut catalog = Catalog::new();
+    let mut message: Option<String> = None;
+    let mut attribute: Option<String> = None;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || line.starts_with('#') {
+            continue;
+        } else if line.starts_with(char::is_whitespace) {
+            if let Some(key) = &message {
+                if let Some((name, value)) = trimmed
+                    .strip_prefix('.')
+                    .and_then(|attr| attr.split_once('='))
+                {
+                    let key = format!("{key}.{}", name.trim());
+                    catalog.insert(key.clone(), value.trim().to_string());
+                    attribute = Some(key);
+                } else if let Some(value) =
+                    catalog.get_mut(attribute.as_deref().unwrap_or(key.as_str()))
+                {
+                    // Continuation of a multiline value
+                    if !value.is_empty() {
+                        value.push('\n');
+                    }
+                    value.push_str(trimmed);
+                }
+            }
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_string();
+            catalog.insert(key.clone(), value.trim().to_string());
+            message = Some(key);
+            attribute = None;
+        }
+    }
+
+    catalog
+}
+
+fn set_document_lang(locale: Locale) {
+    if let Some(root) = document().document_element() {
+        let _ = root.set_attribute("lang", locale.code());
+    }
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [Locale::En, Locale::De, Locale::Es, Locale::Fr];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Es => "es",
+            Locale::Fr => "fr",
+        }
+    }
+
+    // Native name, as shown in the language switcher
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::De => "Deutsch",
+            Locale::Es => "Español",
+            Locale::Fr => "Français",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let language = code.split(['-', '_']).next().unwrap_or_default();
+        Locale::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        static EN: OnceLock<Catalog> = OnceLock::new();
+        static DE: OnceLock<Catalog> = OnceLock::new();
+        static ES: OnceLock<Catalog> = OnceLock::new();
+        static FR: OnceLock<Catalog> = OnceLock::new();
+
+        match self {
+            Locale::En => EN.get_or_init(|| parse_catalog(include_str!("locales/en.ftl"))),
+            Locale::De => DE.get_or_init(|| parse_catalog(include_str!("locales/de.ftl"))),
+            Locale::Es => ES.get_or_init(|| parse_catalog(include_str!("locales/es.ftl"))),
+            Locale::Fr => FR.get_or_init(|| parse_catalog(include_str!("locales/fr.ftl"))),
+        }
+    }
+}
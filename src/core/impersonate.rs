@@ -0,0 +1,164 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashSet;
+use chrono::{DateTime, Utc};
+use gloo_storage::{SessionStorage, Storage};
+use leptos::{RwSignal, SignalUpdate};
+use serde::{Deserialize, Serialize};
+
+use crate::{pages::List, STATE_STORAGE_KEY};
+
+use super::{
+    http::{self, HttpRequest},
+    url::UrlBuilder,
+    AccessToken, Permission, Permissions,
+};
+
+pub const IMPERSONATION_STORAGE_KEY: &str = "webadmin_impersonation";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Impersonation {
+    pub session_id: String,
+    pub account: String,
+    pub expires_at: DateTime<Utc>,
+    pub impersonator: AccessToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpersonationSession {
+    pub id: String,
+    pub impersonator: String,
+    pub account: String,
+    #[serde(default)]
+    pub reason: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImpersonationGrant {
+    session_id: String,
+    access_token: String,
+    expires_at: DateTime<Utc>,
+    #[serde(default)]
+    permissions: AHashSet<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImpersonationRequest<'x> {
+    reason: &'x str,
+    duration: u64,
+}
+
+// The server issues a short-lived token without a refresh token and records
+// the session in its audit log. The administrator's own token is parked in
+// session storage until the session is ended.
+pub async fn start_impersonation(
+    auth: RwSignal<AccessToken>,
+    impersonator: &AccessToken,
+    account: &str,
+    reason: &str,
+    duration: u64,
+) -> http::Result<()> {
+    let grant = HttpRequest::post(
+        UrlBuilder::new("/api/principal")
+            .with_subpath(account)
+            .with_subpath("impersonate")
+            .finish(),
+    )
+    .with_authorization(impersonator)
+    .with_body(ImpersonationRequest { reason, duration })?
+    .send::<ImpersonationGrant>()
+    .await?;
+
+    let impersonation = Impersonation {
+        session_id: grant.session_id,
+        account: account.to_string(),
+        expires_at: grant.expires_at,
+        impersonator: impersonator.clone(),
+    };
+    if let Err(err) = SessionStorage::set(IMPERSONATION_STORAGE_KEY, &impersonation) {
+        log::error!("Failed to save impersonation to session storage: {}", err);
+    }
+
+    let mut permissions = grant.permissions;
+    permissions.retain(|permission| !permission.is_admin_permission());
+    auth.update(|auth| {
+        *auth = AccessToken {
+            base_url: impersonator.base_url.clone(),
+            access_token: grant.access_token.into(),
+            refresh_token: Default::default(),
+            username: account.to_string().into(),
+            is_valid: true,
+            is_enterprise: impersonator.is_enterprise,
+            permissions: Permissions::new(permissions),
+        };
+        save_token(auth);
+    });
+
+    Ok(())
+}
+
+pub async fn end_impersonation(auth: RwSignal<AccessToken>) -> Option<Impersonation> {
+    let impersonation = current_impersonation()?;
+    SessionStorage::delete(IMPERSONATION_STORAGE_KEY);
+
+    if let Err(err) = HttpRequest::post(
+        UrlBuilder::new("/api/impersonation")
+            .with_subpath(&impersonation.session_id)
+            .with_subpath("end")
+            .finish(),
+    )
+    .with_authorization(&impersonation.impersonator)
+    .send::<serde_json::Value>()
+    .await
+    {
+        log::warn!("Failed to close impersonation session: {:?}", err);
+    }
+
+    // The parked token may have expired meanwhile, force a refresh
+    auth.update(|auth| {
+        *auth = AccessToken {
+            is_valid: false,
+            ..impersonation.impersonator.clone()
+        };
+        save_token(auth);
+    });
+
+    Some(impersonation)
+}
+
+pub fn current_impersonation() -> Option<Impersonation> {
+    SessionStorage::get::<Impersonation>(IMPERSONATION_STORAGE_KEY).ok()
+}
+
+pub async fn fetch_impersonations(
+    auth: &AccessToken,
+    page: u32,
+    limit: u32,
+    account: Option<String>,
+) -> http::Result<List<ImpersonationSession>> {
+    HttpRequest::get("/api/impersonation")
+        .with_authorization(auth)
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .with_optional_parameter("account", account)
+        .send::<List<ImpersonationSession>>()
+        .await
+}
+
+fn save_token(auth: &AccessToken) {
+    if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth.clone()) {
+        log::error!(
+            "Failed to save authorization token to session storage: {}",
+            err
+        );
+    }
+}
@@ -0,0 +1,319 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{
+    http::{self, HttpRequest, ManagementApiError},
+    url::UrlBuilder,
+    AccessToken,
+};
+
+const CAPABILITIES: &[&str] = &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"];
+
+// Opening a browsing session records an audit log entry on the server and
+// returns the JMAP account id used for the read-only calls below.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseSession {
+    pub account_id: String,
+}
+
+// Session object (RFC 8620, section 2) as the server returns it to the account
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JmapSession {
+    #[serde(default)]
+    pub capabilities: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, JmapAccount>,
+    #[serde(default)]
+    pub primary_accounts: BTreeMap<String, String>,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub api_url: String,
+    #[serde(default)]
+    pub download_url: String,
+    #[serde(default)]
+    pub upload_url: String,
+    #[serde(default)]
+    pub event_source_url: String,
+    #[serde(default)]
+    pub state: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JmapAccount {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub is_personal: bool,
+    #[serde(default)]
+    pub is_read_only: bool,
+    #[serde(default)]
+    pub account_capabilities: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mailbox {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub sort_order: u32,
+    #[serde(default)]
+    pub total_emails: u64,
+    #[serde(default)]
+    pub unread_emails: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailSummary {
+    pub id: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub from: Option<Vec<EmailAddress>>,
+    pub received_at: DateTime<Utc>,
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailHeaders {
+    pub id: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub received_at: DateTime<Utc>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub headers: Vec<EmailHeader>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EmailAddress {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EmailHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JmapRequest {
+    using: &'static [&'static str],
+    method_calls: Vec<(&'static str, Value, &'static str)>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JmapResponse {
+    method_responses: Vec<(String, Value, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetResponse<T> {
+    list: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    total: u64,
+}
+
+pub async fn open_browse_session(auth: &AccessToken, account: &str) -> http::Result<BrowseSession> {
+    HttpRequest::post(
+        UrlBuilder::new("/api/principal")
+            .with_subpath(account)
+            .with_subpath("browse")
+            .finish(),
+    )
+    .with_authorization(auth)
+    .send::<BrowseSession>()
+    .await
+}
+
+// Returns the parsed session together with the raw object, which may carry
+// properties the parsed form does not know about.
+pub async fn fetch_session(
+    auth: &AccessToken,
+    account: &str,
+) -> http::Result<(JmapSession, Value)> {
+    let session = HttpRequest::get(
+        UrlBuilder::new("/api/principal")
+            .with_subpath(account)
+            .with_subpath("jmap-session")
+            .finish(),
+    )
+    .with_authorization(auth)
+    .send::<Value>()
+    .await?;
+
+    serde_json::from_value(session.clone())
+        .map(|parsed| (parsed, session))
+        .map_err(Into::into)
+}
+
+pub async fn fetch_mailboxes(auth: &AccessToken, account_id: &str) -> http::Result<Vec<Mailbox>> {
+    let mut responses = send(
+        auth,
+        vec![(
+            "Mailbox/get",
+            json!({
+                "accountId": account_id,
+                "properties": [
+                    "id", "name", "parentId", "role", "sortOrder", "totalEmails", "unreadEmails"
+                ],
+            }),
+            "m",
+        )],
+    )
+    .await?;
+
+    response::<GetResponse<Mailbox>>(&mut responses, "m").map(|response| response.list)
+}
+
+pub async fn fetch_emails(
+    auth: &AccessToken,
+    account_id: &str,
+    mailbox_id: &str,
+    page: u32,
+    limit: u32,
+) -> http::Result<(Vec<EmailSummary>, u64)> {
+    let mut responses = send(
+        auth,
+        vec![
+            (
+                "Email/query",
+                json!({
+                    "accountId": account_id,
+                    "filter": { "inMailbox": mailbox_id },
+                    "sort": [{ "property": "receivedAt", "isAscending": false }],
+                    "position": page.saturating_sub(1) * limit,
+                    "limit": limit,
+                    "calculateTotal": true,
+                }),
+                "q",
+            ),
+            (
+                "Email/get",
+                json!({
+                    "accountId": account_id,
+                    "#ids": { "resultOf": "q", "name": "Email/query", "path": "/ids" },
+                    "properties": ["id", "subject", "from", "receivedAt", "size"],
+                }),
+                "g",
+            ),
+        ],
+    )
+    .await?;
+
+    let total = response::<QueryResponse>(&mut responses, "q")?.total;
+    response::<GetResponse<EmailSummary>>(&mut responses, "g")
+        .map(|response| (response.list, total))
+}
+
+pub async fn fetch_email_headers(
+    auth: &AccessToken,
+    account_id: &str,
+    email_id: &str,
+) -> http::Result<Option<EmailHeaders>> {
+    let mut responses = send(
+        auth,
+        vec![(
+            "Email/get",
+            json!({
+                "accountId": account_id,
+                "ids": [email_id],
+                "properties": ["id", "subject", "receivedAt", "size", "headers"],
+            }),
+            "h",
+        )],
+    )
+    .await?;
+
+    response::<GetResponse<EmailHeaders>>(&mut responses, "h")
+        .map(|response| response.list.into_iter().next())
+}
+
+async fn send(
+    auth: &AccessToken,
+    method_calls: Vec<(&'static str, Value, &'static str)>,
+) -> http::Result<Vec<(String, Value, String)>> {
+    let response = HttpRequest::post("/jmap/")
+        .with_authorization(auth)
+        .with_body(JmapRequest {
+            using: CAPABILITIES,
+            method_calls,
+        })?
+        .send_raw()
+        .await?;
+
+    serde_json::from_slice::<JmapResponse>(&response)
+        .map(|response| response.method_responses)
+        .map_err(|err| http::Error::Serializer {
+            error: err.to_string(),
+            response: String::from_utf8_lossy(&response).to_string(),
+        })
+}
+
+fn response<T: DeserializeOwned>(
+    responses: &mut Vec<(String, Value, String)>,
+    call_id: &str,
+) -> http::Result<T> {
+    let pos = responses
+        .iter()
+        .position(|(_, _, id)| id == call_id)
+        .ok_or_else(|| {
+            http::Error::Server(ManagementApiError::Other {
+                details: "Missing JMAP response".to_string(),
+                reason: Some(call_id.to_string()),
+            })
+        })?;
+    let (name, value, _) = responses.swap_remove(pos);
+
+    if name == "error" {
+        Err(http::Error::Server(ManagementApiError::Other {
+            details: "JMAP request failed".to_string(),
+            reason: value
+                .get("description")
+                .or_else(|| value.get("type"))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string()),
+        }))
+    } else {
+        serde_json::from_value(value).map_err(Into::into)
+    }
+}
+
+impl EmailAddress {
+    pub fn display(&self) -> String {
+        match &self.name {
+            Some(name) if !name.is_empty() => format!("{name} <{}>", self.email),
+            _ => self.email.clone(),
+        }
+    }
+}
@@ -9,11 +9,34 @@ use std::{fmt::Display, sync::Arc};
 use ahash::AHashSet;
 use serde::{Deserialize, Serialize};
 
+pub mod changelog;
+pub mod command;
+pub mod connection;
+pub mod cron;
+pub mod crypto;
+pub mod dkim;
+pub mod dns;
+pub mod download;
+pub mod events;
 pub mod expr;
 pub mod form;
+pub mod format;
+pub mod health;
 pub mod http;
+pub mod i18n;
+pub mod impersonate;
+pub mod jmap;
+pub mod monitor;
 pub mod oauth;
+pub mod poll;
+pub mod recording;
+pub mod saml;
+pub mod schedule;
 pub mod schema;
+pub mod session;
+pub mod sieve;
+pub mod task;
+pub mod upload;
 pub mod url;
 
 pub const MINIMUM_API_VERSION: Semver = Semver::new(0, 11, 0);
@@ -54,6 +77,7 @@ pub enum Permission {
     SettingsUpdate,
     SettingsDelete,
     SettingsReload,
+    SettingsSecretsRead,
     IndividualList,
     IndividualGet,
     IndividualUpdate,
@@ -109,6 +133,12 @@ pub enum Permission {
     MetricsList,
     MetricsLive,
     Troubleshoot,
+    MailboxBrowse,
+    StoreQuery,
+    MailboxMigrate,
+    SieveInspect,
+    SessionList,
+    SessionTerminate,
 
     // Account Management
     ManageEncryption,
@@ -252,6 +282,7 @@ impl Permission {
                 | Permission::SettingsUpdate
                 | Permission::SettingsDelete
                 | Permission::SettingsReload
+                | Permission::SettingsSecretsRead
                 | Permission::IndividualList
                 | Permission::IndividualGet
                 | Permission::IndividualUpdate
@@ -305,6 +336,12 @@ impl Permission {
                 | Permission::MetricsList
                 | Permission::MetricsLive
                 | Permission::Troubleshoot
+                | Permission::MailboxBrowse
+                | Permission::StoreQuery
+                | Permission::MailboxMigrate
+                | Permission::SieveInspect
+                | Permission::SessionList
+                | Permission::SessionTerminate
         )
     }
 }
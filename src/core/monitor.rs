@@ -0,0 +1,126 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{components::Color, pages::List};
+
+use super::{
+    http::{self, HttpRequest},
+    AccessToken,
+};
+
+pub static SESSION_PROTOCOLS: &[SessionProtocol] = &[
+    SessionProtocol::Smtp,
+    SessionProtocol::Lmtp,
+    SessionProtocol::Imap,
+    SessionProtocol::Pop3,
+    SessionProtocol::ManageSieve,
+    SessionProtocol::Http,
+];
+
+// A connection currently open on one of the server listeners
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSession {
+    pub id: String,
+    pub protocol: SessionProtocol,
+    pub remote_ip: String,
+    #[serde(default)]
+    pub remote_port: u16,
+    #[serde(default)]
+    pub listener: Option<String>,
+    #[serde(default)]
+    pub account: Option<String>,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub bytes_received: u64,
+    #[serde(default)]
+    pub bytes_sent: u64,
+    #[serde(default)]
+    pub is_tls: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionProtocol {
+    Smtp,
+    Lmtp,
+    Imap,
+    Pop3,
+    ManageSieve,
+    Http,
+    #[serde(other)]
+    Other,
+}
+
+pub async fn fetch_client_sessions(
+    auth: &AccessToken,
+    filter: Option<String>,
+    protocol: Option<SessionProtocol>,
+    page: u32,
+    limit: u32,
+) -> http::Result<List<ClientSession>> {
+    HttpRequest::get("/api/sessions")
+        .with_authorization(auth)
+        .with_optional_parameter("filter", filter)
+        .with_optional_parameter("protocol", protocol.map(|protocol| protocol.id()))
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .send::<List<ClientSession>>()
+        .await
+}
+
+// The server closes the connection and records the termination in the audit log
+pub async fn terminate_client_session(auth: &AccessToken, id: &str) -> http::Result<()> {
+    HttpRequest::delete(("/api/sessions", id))
+        .with_authorization(auth)
+        .send::<()>()
+        .await
+}
+
+impl SessionProtocol {
+    pub fn id(&self) -> &'static str {
+        match self {
+            SessionProtocol::Smtp => "smtp",
+            SessionProtocol::Lmtp => "lmtp",
+            SessionProtocol::Imap => "imap",
+            SessionProtocol::Pop3 => "pop3",
+            SessionProtocol::ManageSieve => "managesieve",
+            SessionProtocol::Http => "http",
+            SessionProtocol::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SessionProtocol::Smtp => "SMTP",
+            SessionProtocol::Lmtp => "LMTP",
+            SessionProtocol::Imap => "IMAP",
+            SessionProtocol::Pop3 => "POP3",
+            SessionProtocol::ManageSieve => "ManageSieve",
+            SessionProtocol::Http => "HTTP",
+            SessionProtocol::Other => "Other",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            SessionProtocol::Smtp | SessionProtocol::Lmtp => Color::Blue,
+            SessionProtocol::Imap | SessionProtocol::Pop3 => Color::Green,
+            SessionProtocol::ManageSieve | SessionProtocol::Http => Color::Yellow,
+            SessionProtocol::Other => Color::Gray,
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        SESSION_PROTOCOLS
+            .iter()
+            .find(|protocol| protocol.id() == id)
+            .copied()
+    }
+}
@@ -309,6 +309,7 @@ impl OAuthCodeResponse {
                 Permission::SettingsUpdate,
                 Permission::SettingsDelete,
                 Permission::SettingsReload,
+                Permission::SettingsSecretsRead,
                 Permission::IndividualList,
                 Permission::IndividualGet,
                 Permission::IndividualUpdate,
@@ -366,6 +367,12 @@ impl OAuthCodeResponse {
                 Permission::ManageEncryption,
                 Permission::ManagePasswords,
                 Permission::Troubleshoot,
+                Permission::MailboxBrowse,
+                Permission::StoreQuery,
+                Permission::MailboxMigrate,
+                Permission::SieveInspect,
+                Permission::SessionList,
+                Permission::SessionTerminate,
             ] {
                 self.permissions.insert(permission);
             }
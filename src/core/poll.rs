@@ -0,0 +1,76 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::{leptos_dom::helpers::IntervalHandle, *};
+
+#[derive(Clone, Copy)]
+pub struct Poller {
+    tick: RwSignal<u64>,
+    paused: RwSignal<bool>,
+}
+
+pub fn use_polling(interval: impl Into<MaybeSignal<Duration>>) -> Poller {
+    let interval = interval.into();
+    let poller = Poller {
+        tick: create_rw_signal(0),
+        paused: create_rw_signal(false),
+    };
+    let handle: StoredValue<Option<IntervalHandle>> = store_value(None);
+
+    create_effect(move |_| {
+        let interval = interval.get();
+        let paused = poller.paused.get();
+
+        handle.update_value(|handle| {
+            if let Some(handle) = handle.take() {
+                handle.clear();
+            }
+            if !paused {
+                *handle = set_interval_with_handle(
+                    move || {
+                        if !document().hidden() {
+                            poller.tick.update(|tick| *tick += 1);
+                        }
+                    },
+                    interval,
+                )
+                .ok();
+            }
+        });
+    });
+
+    on_cleanup(move || {
+        if let Some(handle) = handle.try_update_value(|handle| handle.take()).flatten() {
+            handle.clear();
+        }
+    });
+
+    poller
+}
+
+impl Poller {
+    pub fn tick(&self) -> u64 {
+        self.tick.get()
+    }
+
+    pub fn refresh(&self) {
+        self.tick.update(|tick| *tick += 1);
+    }
+
+    pub fn pause(&self) {
+        self.paused.set(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.set(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+}
@@ -0,0 +1,219 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use gloo_net::http::Method;
+use gloo_storage::{SessionStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    download::download_file,
+    http::{self, HttpRequest},
+    AccessToken,
+};
+
+pub const RECORDING_STORAGE_KEY: &str = "webadmin_recording";
+
+// Calls that read data, authenticate or only make sense on the server
+// they were issued to are left out of recordings.
+static IGNORED_PATHS: &[&str] = &[
+    "/api/oauth",
+    "/api/account/",
+    "/api/download/sign",
+    "/api/impersonation",
+    "/api/troubleshoot",
+    "/api/spam-filter/classify",
+    "/api/webhook/test",
+    "/api/directory/test",
+    "/api/scim/token",
+    "/api/upload",
+    "/api/migration/verify",
+    "/api/migration/dry-run",
+    "/api/telemetry/digest/test",
+];
+
+// Same as above for calls on a single object, such as
+// `/api/principal/{id}/impersonate` which opens a session on the server or
+// `/api/store/{id}/query` which only reads data.
+static IGNORED_SUFFIXES: &[&str] = &["/impersonate", "/browse", "/query"];
+
+// Credentials in request bodies, such as the master password of a migration
+// source, are replaced before the call is stored in the session.
+static REDACTED_FIELDS: &[&str] = &["secret", "password"];
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recording {
+    pub server: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub calls: Vec<RecordedCall>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedCall {
+    pub method: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+pub fn start_recording(auth: &AccessToken) {
+    let recording = Recording {
+        server: server_name(auth),
+        started_at: Utc::now(),
+        finished_at: None,
+        calls: vec![],
+    };
+    if let Err(err) = SessionStorage::set(RECORDING_STORAGE_KEY, &recording) {
+        log::error!("Failed to save recording to session storage: {}", err);
+    }
+}
+
+pub fn stop_recording() -> Option<Recording> {
+    let mut recording = current_recording()?;
+    SessionStorage::delete(RECORDING_STORAGE_KEY);
+    recording.finished_at = Some(Utc::now());
+    Some(recording)
+}
+
+pub fn current_recording() -> Option<Recording> {
+    SessionStorage::get::<Recording>(RECORDING_STORAGE_KEY).ok()
+}
+
+pub fn is_recording() -> bool {
+    SessionStorage::raw()
+        .get_item(RECORDING_STORAGE_KEY)
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+// Invoked by HttpRequest after a request succeeded, the server's base URL is
+// stripped from `url` so the script can be replayed against any server.
+pub fn record_call(method: &Method, url: &str, content_type: Option<String>, body: Option<String>) {
+    if *method == Method::GET || *method == Method::HEAD || *method == Method::OPTIONS {
+        return;
+    }
+    let Some(mut recording) = current_recording() else {
+        return;
+    };
+    let path = url.find("/api/").map(|pos| &url[pos..]).unwrap_or_default();
+    if path.is_empty() || is_ignored(path) {
+        return;
+    }
+
+    let is_json = content_type
+        .as_deref()
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let body = body.map(|body| {
+        is_json
+            .then(|| serde_json::from_str(&body).ok())
+            .flatten()
+            .map(|mut value| {
+                redact_credentials(&mut value);
+                value
+            })
+            .unwrap_or(serde_json::Value::String(body))
+    });
+    recording.calls.push(RecordedCall {
+        method: method.to_string(),
+        path: path.to_string(),
+        content_type,
+        body,
+        recorded_at: Utc::now(),
+    });
+
+    if let Err(err) = SessionStorage::set(RECORDING_STORAGE_KEY, &recording) {
+        log::error!("Failed to save recording to session storage: {}", err);
+    }
+}
+
+fn is_ignored(path: &str) -> bool {
+    let path_only = path.split_once('?').map_or(path, |(path, _)| path);
+    IGNORED_PATHS
+        .iter()
+        .any(|ignored| path_only.starts_with(ignored))
+        || IGNORED_SUFFIXES
+            .iter()
+            .any(|ignored| path_only.trim_end_matches('/').ends_with(ignored))
+}
+
+fn redact_credentials(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_FIELDS.contains(&key.as_str()) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_credentials(value);
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_credentials),
+        _ => {}
+    }
+}
+
+pub fn download_recording(recording: &Recording) {
+    match serde_json::to_string_pretty(recording) {
+        Ok(contents) => download_file(
+            &format!(
+                "changes-{}.json",
+                recording.started_at.format("%Y%m%d-%H%M%S")
+            ),
+            "application/json",
+            &contents,
+        ),
+        Err(err) => {
+            log::error!("Failed to serialize recording: {}", err);
+        }
+    }
+}
+
+pub async fn replay_call(auth: &AccessToken, call: &RecordedCall) -> http::Result<()> {
+    let method = Method::from_bytes(call.method.as_bytes())
+        .map_err(|_| http::Error::Network(format!("Unsupported HTTP method {}", call.method)))?;
+    let mut request = HttpRequest::new(method, call.path.as_str())
+        .with_authorization(auth)
+        .without_recording();
+    if let Some(body) = &call.body {
+        let body = match body {
+            serde_json::Value::String(body)
+                if !call
+                    .content_type
+                    .as_deref()
+                    .is_some_and(|ct| ct.starts_with("application/json")) =>
+            {
+                body.clone()
+            }
+            body => serde_json::to_string(body)?,
+        };
+        request = request.with_raw_body(body);
+    }
+    if let Some(content_type) = &call.content_type {
+        request = request.with_header("Content-Type", content_type);
+    }
+
+    request.send::<serde_json::Value>().await.map(|_| ())
+}
+
+fn server_name(auth: &AccessToken) -> String {
+    if !auth.base_url.is_empty() {
+        auth.base_url.to_string()
+    } else {
+        web_sys::window()
+            .and_then(|window| window.location().origin().ok())
+            .unwrap_or_default()
+    }
+}
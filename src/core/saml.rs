@@ -0,0 +1,360 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sha2::{Digest, Sha256};
+use web_sys::{DomParser, Element, SupportedType};
+
+const BINDING_REDIRECT: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Redirect";
+const BINDING_POST: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdpMetadata {
+    pub entity_id: String,
+    pub sso_url: Option<String>,
+    pub slo_url: Option<String>,
+    pub certificate: Option<String>,
+    pub name_id_formats: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SamlAssertion {
+    pub issuer: Option<String>,
+    pub status: Option<String>,
+    pub destination: Option<String>,
+    pub name_id: Option<String>,
+    pub name_id_format: Option<String>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_on_or_after: Option<DateTime<Utc>>,
+    pub audiences: Vec<String>,
+    pub attributes: Vec<SamlAttribute>,
+    pub is_signed: bool,
+    pub is_encrypted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SamlAttribute {
+    pub name: String,
+    pub friendly_name: Option<String>,
+    pub values: Vec<String>,
+}
+
+impl IdpMetadata {
+    pub fn parse(xml: &str) -> Result<Self, String> {
+        let root = parse_xml(xml)?;
+        let entity = if root.local_name() == "EntityDescriptor" {
+            Some(root)
+        } else {
+            elements(&root, "EntityDescriptor")
+                .into_iter()
+                .find(|entity| !elements(entity, "IDPSSODescriptor").is_empty())
+        }
+        .ok_or("No entity descriptor was found in the metadata")?;
+        let idp = first(&entity, "IDPSSODescriptor")
+            .ok_or("The metadata does not describe an identity provider")?;
+
+        let services = elements(&idp, "SingleSignOnService");
+        let sso_url = [BINDING_REDIRECT, BINDING_POST]
+            .iter()
+            .find_map(|binding| {
+                services
+                    .iter()
+                    .find(|service| service.get_attribute("Binding").as_deref() == Some(binding))
+            })
+            .or(services.first())
+            .and_then(|service| service.get_attribute("Location"));
+        let slo_url = first(&idp, "SingleLogoutService")
+            .and_then(|service| service.get_attribute("Location"));
+        let certificate = elements(&idp, "KeyDescriptor")
+            .into_iter()
+            .filter(|key| key.get_attribute("use").as_deref() != Some("encryption"))
+            .find_map(|key| text(&key, "X509Certificate"))
+            .map(|cert| to_pem(&cert));
+
+        Ok(IdpMetadata {
+            entity_id: entity
+                .get_attribute("entityID")
+                .ok_or("The entity descriptor has no entity ID")?,
+            sso_url,
+            slo_url,
+            certificate,
+            name_id_formats: elements(&idp, "NameIDFormat")
+                .iter()
+                .filter_map(|format| format.text_content())
+                .map(|format| format.trim().to_string())
+                .collect(),
+        })
+    }
+}
+
+impl SamlAssertion {
+    // Accepts either the raw XML or the base64 encoded SAMLResponse posted
+    // by the identity provider.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let xml = if input.starts_with('<') {
+            input.to_string()
+        } else {
+            let bytes = STANDARD
+                .decode(input.split_whitespace().collect::<String>())
+                .map_err(|_| "The response is neither XML nor valid base64")?;
+            String::from_utf8(bytes).map_err(|_| {
+                concat!(
+                    "The decoded response is not XML. Compressed HTTP-Redirect messages ",
+                    "are not supported, paste the SAMLResponse of an HTTP-POST instead."
+                )
+            })?
+        };
+        let root = parse_xml(&xml)?;
+        let is_encrypted = !elements(&root, "EncryptedAssertion").is_empty();
+        let assertion = if root.local_name() == "Assertion" {
+            Some(root.clone())
+        } else {
+            first(&root, "Assertion")
+        };
+        let conditions = assertion
+            .as_ref()
+            .and_then(|assertion| first(assertion, "Conditions"));
+        let name_id = assertion
+            .as_ref()
+            .and_then(|assertion| first(assertion, "NameID"));
+
+        Ok(SamlAssertion {
+            issuer: text(&root, "Issuer"),
+            status: first(&root, "StatusCode")
+                .and_then(|status| status.get_attribute("Value"))
+                .map(|status| status.rsplit(':').next().unwrap_or_default().to_string()),
+            destination: root.get_attribute("Destination"),
+            name_id: name_id
+                .as_ref()
+                .and_then(|name_id| name_id.text_content())
+                .map(|name_id| name_id.trim().to_string()),
+            name_id_format: name_id.and_then(|name_id| name_id.get_attribute("Format")),
+            not_before: conditions
+                .as_ref()
+                .and_then(|conditions| parse_time(conditions.get_attribute("NotBefore"))),
+            not_on_or_after: conditions
+                .as_ref()
+                .and_then(|conditions| parse_time(conditions.get_attribute("NotOnOrAfter"))),
+            audiences: conditions
+                .map(|conditions| {
+                    elements(&conditions, "Audience")
+                        .iter()
+                        .filter_map(|audience| audience.text_content())
+                        .map(|audience| audience.trim().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            attributes: assertion
+                .as_ref()
+                .map(|assertion| {
+                    elements(assertion, "Attribute")
+                        .iter()
+                        .map(|attribute| SamlAttribute {
+                            name: attribute.get_attribute("Name").unwrap_or_default(),
+                            friendly_name: attribute.get_attribute("FriendlyName"),
+                            values: elements(attribute, "AttributeValue")
+                                .iter()
+                                .filter_map(|value| value.text_content())
+                                .map(|value| value.trim().to_string())
+                                .collect(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            is_signed: !elements(&root, "Signature").is_empty(),
+            is_encrypted,
+        })
+    }
+
+    // Attributes can be mapped by name or friendly name, "NameID" maps the
+    // subject of the assertion.
+    pub fn attribute(&self, name: &str) -> Option<Vec<String>> {
+        if name.eq_ignore_ascii_case("NameID") {
+            self.name_id.clone().map(|name_id| vec![name_id])
+        } else {
+            self.attributes
+                .iter()
+                .find(|attribute| {
+                    attribute.name == name || attribute.friendly_name.as_deref() == Some(name)
+                })
+                .map(|attribute| attribute.values.clone())
+        }
+    }
+}
+
+impl CertificateInfo {
+    pub fn parse(pem: &str) -> Result<Self, String> {
+        let der = STANDARD
+            .decode(
+                pem.lines()
+                    .filter(|line| !line.starts_with("-----"))
+                    .flat_map(|line| line.split_whitespace())
+                    .collect::<String>(),
+            )
+            .map_err(|_| "The certificate is not valid PEM or base64")?;
+        let invalid = || "The certificate could not be decoded".to_string();
+
+        // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signature }
+        let (_, cert, _) = read_tlv(&der).ok_or_else(invalid)?;
+        let (_, tbs, _) = read_tlv(cert).ok_or_else(invalid)?;
+        let mut fields = Vec::new();
+        let mut rest = tbs;
+        while let Some((tag, value, next)) = read_tlv(rest) {
+            // Skip the optional explicit version
+            if !(fields.is_empty() && tag == 0xa0) {
+                fields.push(value);
+            }
+            rest = next;
+        }
+        let [_, _, issuer, validity, subject, ..] = fields[..] else {
+            return Err(invalid());
+        };
+        let (not_before, not_after) = match read_tlv(validity) {
+            Some((tag, value, rest)) => (
+                parse_asn1_time(tag, value),
+                read_tlv(rest).and_then(|(tag, value, _)| parse_asn1_time(tag, value)),
+            ),
+            None => (None, None),
+        };
+
+        Ok(CertificateInfo {
+            subject: parse_name(subject),
+            issuer: parse_name(issuer),
+            not_before,
+            not_after,
+            fingerprint: Sha256::digest(&der)
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<_>>()
+                .join(":"),
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.not_after
+            .is_some_and(|not_after| not_after < Utc::now())
+    }
+}
+
+fn parse_xml(xml: &str) -> Result<Element, String> {
+    let document = DomParser::new()
+        .and_then(|parser| parser.parse_from_string(xml, SupportedType::ApplicationXml))
+        .map_err(|_| "Failed to parse XML")?;
+    if document.get_elements_by_tag_name("parsererror").length() > 0 {
+        return Err("The document is not well-formed XML".to_string());
+    }
+    document
+        .document_element()
+        .ok_or_else(|| "The document is empty".to_string())
+}
+
+fn elements(parent: &Element, name: &str) -> Vec<Element> {
+    match parent.get_elements_by_tag_name_ns(Some("*"), name) {
+        Ok(collection) => (0..collection.length())
+            .filter_map(|idx| collection.item(idx))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn first(parent: &Element, name: &str) -> Option<Element> {
+    parent
+        .get_elements_by_tag_name_ns(Some("*"), name)
+        .ok()?
+        .item(0)
+}
+
+fn text(parent: &Element, name: &str) -> Option<String> {
+    first(parent, name)
+        .and_then(|element| element.text_content())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_time(value: Option<String>) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&value?)
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn to_pem(cert: &str) -> String {
+    let cert = cert.split_whitespace().collect::<String>();
+    let mut pem = String::from("-----BEGIN CERTIFICATE-----\n");
+    for line in cert.as_bytes().chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str("-----END CERTIFICATE-----");
+    pem
+}
+
+fn read_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, data) = data.split_first()?;
+    let (&len, mut data) = data.split_first()?;
+    let len = if len & 0x80 == 0 {
+        len as usize
+    } else {
+        let num_bytes = (len & 0x7f) as usize;
+        if num_bytes == 0 || num_bytes > 4 || data.len() < num_bytes {
+            return None;
+        }
+        let (bytes, rest) = data.split_at(num_bytes);
+        data = rest;
+        bytes
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize)
+    };
+    (data.len() >= len).then(|| (tag, &data[..len], &data[len..]))
+}
+
+fn parse_asn1_time(tag: u8, value: &[u8]) -> Option<DateTime<Utc>> {
+    let value = std::str::from_utf8(value).ok()?;
+    let format = match tag {
+        0x17 => "%y%m%d%H%M%SZ",
+        0x18 => "%Y%m%d%H%M%SZ",
+        _ => return None,
+    };
+    NaiveDateTime::parse_from_str(value, format)
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+// Name ::= SEQUENCE OF SET OF SEQUENCE { type OID, value ANY }
+fn parse_name(name: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut sets = name;
+    while let Some((_, set, next)) = read_tlv(sets) {
+        let mut items = set;
+        while let Some((_, item, next)) = read_tlv(items) {
+            if let Some((0x06, oid, rest)) = read_tlv(item) {
+                let label = match oid {
+                    [0x55, 0x04, 0x03] => "CN",
+                    [0x55, 0x04, 0x0a] => "O",
+                    [0x55, 0x04, 0x0b] => "OU",
+                    [0x55, 0x04, 0x06] => "C",
+                    _ => "",
+                };
+                if let (false, Some((_, value, _))) = (label.is_empty(), read_tlv(rest)) {
+                    parts.push(format!("{label}={}", String::from_utf8_lossy(value)));
+                }
+            }
+            items = next;
+        }
+        sets = next;
+    }
+    parts.join(", ")
+}
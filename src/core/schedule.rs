@@ -0,0 +1,308 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use super::expr::ParseValue;
+
+// Retry attempts shown before the remaining ones are summarized
+pub const MAX_ATTEMPTS: usize = 12;
+
+// Queue schedules are written as a list of durations such as "[2m, 5m, 10m]",
+// a single duration is a list with one item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleKind {
+    // Time between consecutive delivery attempts, the last one is repeated
+    // until the message expires
+    Intervals,
+    // Time since the message was queued, used for delay notifications
+    Offsets,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleEvent {
+    pub at: Duration,
+    pub title: String,
+}
+
+// Returns None when the value is not a plain list of durations, for example
+// when it calls functions or uses variables.
+pub fn schedule_items(value: &str) -> Option<Vec<String>> {
+    let items = split_schedule(value);
+    items
+        .iter()
+        .all(|item| Duration::parse_value(item).is_some())
+        .then_some(items)
+}
+
+// Splits a list without validating its items
+pub fn split_schedule(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .unwrap_or(value);
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+pub fn parse_schedule(value: &str) -> Option<Vec<Duration>> {
+    schedule_items(value)?
+        .iter()
+        .map(|item| Duration::parse_value(item))
+        .collect()
+}
+
+pub fn format_schedule(items: &[String]) -> String {
+    format!("[{}]", items.join(", "))
+}
+
+// Formats a duration with its two most significant units, such as "1d 4h"
+pub fn format_duration(duration: Duration) -> String {
+    let mut remaining = duration.as_secs();
+    let mut parts = Vec::with_capacity(2);
+    for (size, unit) in [(86400, "d"), (3600, "h"), (60, "m"), (1, "s")] {
+        if remaining >= size {
+            parts.push(format!("{}{unit}", remaining / size));
+            remaining %= size;
+        } else if !parts.is_empty() {
+            break;
+        }
+        if parts.len() == 2 {
+            break;
+        }
+    }
+
+    if !parts.is_empty() {
+        parts.join(" ")
+    } else {
+        "0s".to_string()
+    }
+}
+
+impl ScheduleKind {
+    // Checks that the schedule only moves forward and fits within the expiry
+    pub fn check(&self, schedule: &[Duration], expire: Option<Duration>) -> Result<(), String> {
+        if schedule.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            ScheduleKind::Intervals => {
+                if let Some(pos) = schedule.windows(2).position(|pair| pair[1] < pair[0]) {
+                    return Err(format!(
+                        "Retry intervals must not decrease, retry {} waits {} after waiting {}",
+                        pos + 2,
+                        format_duration(schedule[pos + 1]),
+                        format_duration(schedule[pos])
+                    ));
+                }
+                let total = schedule.iter().sum::<Duration>();
+                if let Some(expire) = expire.filter(|expire| total > *expire) {
+                    return Err(format!(
+                        "Messages expire after {}, before the last scheduled retry at {}",
+                        format_duration(expire),
+                        format_duration(total)
+                    ));
+                }
+            }
+            ScheduleKind::Offsets => {
+                if let Some(pos) = schedule.windows(2).position(|pair| pair[1] <= pair[0]) {
+                    return Err(format!(
+                        "Notifications must be in increasing order, {} is not after {}",
+                        format_duration(schedule[pos + 1]),
+                        format_duration(schedule[pos])
+                    ));
+                }
+                if let Some(expire) = expire {
+                    if let Some(offset) = schedule.iter().find(|offset| **offset >= expire) {
+                        return Err(format!(
+                            "Messages expire after {}, before the notification at {}",
+                            format_duration(expire),
+                            format_duration(*offset)
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Events are listed in order up to the expiry, repeated retries beyond
+    // MAX_ATTEMPTS are returned as a count.
+    pub fn events(
+        &self,
+        schedule: &[Duration],
+        expire: Option<Duration>,
+    ) -> (Vec<ScheduleEvent>, usize) {
+        let mut events = Vec::new();
+        let before_expiry = |at: Duration| expire.is_none_or(|expire| at < expire);
+
+        match self {
+            ScheduleKind::Intervals => {
+                let mut at = Duration::ZERO;
+                let mut remaining = 0;
+                events.push(ScheduleEvent {
+                    at,
+                    title: "Attempt 1".to_string(),
+                });
+                for interval in schedule.iter().copied() {
+                    at += interval;
+                    if !before_expiry(at) {
+                        return (events, remaining);
+                    } else if events.len() < MAX_ATTEMPTS {
+                        events.push(ScheduleEvent {
+                            at,
+                            title: format!("Attempt {}", events.len() + 1),
+                        });
+                    } else {
+                        remaining += 1;
+                    }
+                }
+
+                // The last interval is repeated until the message expires
+                if let (Some(expire), Some(last)) = (expire, schedule.last().copied()) {
+                    while events.len() < MAX_ATTEMPTS {
+                        at += last;
+                        if at >= expire {
+                            return (events, remaining);
+                        }
+                        events.push(ScheduleEvent {
+                            at,
+                            title: format!("Attempt {}", events.len() + 1),
+                        });
+                    }
+                    remaining +=
+                        ((expire - at).as_millis().saturating_sub(1) / last.as_millis()) as usize;
+                }
+                (events, remaining)
+            }
+            ScheduleKind::Offsets => {
+                for (num, at) in schedule.iter().copied().enumerate() {
+                    if before_expiry(at) {
+                        events.push(ScheduleEvent {
+                            at,
+                            title: format!("Delay notification {}", num + 1),
+                        });
+                    }
+                }
+                (events, 0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn parse_schedule_list() {
+        assert_eq!(split_schedule(" [2m, 5m ,, 10m] "), ["2m", "5m", "10m"]);
+        assert_eq!(split_schedule("30m"), ["30m"]);
+        assert_eq!(
+            parse_schedule("[2m, 5m, 1h]"),
+            Some(vec![minutes(2), minutes(5), minutes(60)])
+        );
+        assert_eq!(parse_schedule("[2m, retry_time]"), None);
+        assert_eq!(format_schedule(&split_schedule("[2m,5m]")), "[2m, 5m]");
+    }
+
+    #[test]
+    fn format_duration_units() {
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+        assert_eq!(format_duration(minutes(90)), "1h 30m");
+        assert_eq!(format_duration(minutes(28 * 60 + 5)), "1d 4h");
+        assert_eq!(format_duration(minutes(24 * 60 + 5)), "1d");
+    }
+
+    #[test]
+    fn check_decreasing_intervals() {
+        let kind = ScheduleKind::Intervals;
+        assert!(kind
+            .check(&[minutes(2), minutes(5), minutes(5)], None)
+            .is_ok());
+        assert_eq!(
+            kind.check(&[minutes(2), minutes(10), minutes(5)], None),
+            Err(
+                "Retry intervals must not decrease, retry 3 waits 5m after waiting 10m".to_string()
+            )
+        );
+        assert_eq!(
+            ScheduleKind::Offsets.check(&[minutes(10), minutes(10)], None),
+            Err("Notifications must be in increasing order, 10m is not after 10m".to_string())
+        );
+    }
+
+    #[test]
+    fn check_expiry_before_last_retry() {
+        let kind = ScheduleKind::Intervals;
+        assert!(kind
+            .check(&[minutes(30), minutes(30)], Some(minutes(60)))
+            .is_ok());
+        assert_eq!(
+            kind.check(&[minutes(30), minutes(60)], Some(minutes(60))),
+            Err("Messages expire after 1h, before the last scheduled retry at 1h 30m".to_string())
+        );
+        assert_eq!(
+            ScheduleKind::Offsets.check(&[minutes(30), minutes(60)], Some(minutes(60))),
+            Err("Messages expire after 1h, before the notification at 1h".to_string())
+        );
+    }
+
+    #[test]
+    fn events_remaining_attempts() {
+        let kind = ScheduleKind::Intervals;
+
+        // Without an expiry only the listed intervals are attempted
+        let (events, remaining) = kind.events(&[minutes(2), minutes(5)], None);
+        assert_eq!(
+            events.iter().map(|event| event.at).collect::<Vec<_>>(),
+            [Duration::ZERO, minutes(2), minutes(7)]
+        );
+        assert_eq!(events[2].title, "Attempt 3");
+        assert_eq!(remaining, 0);
+
+        // Retries every minute for an hour, attempts at 12m to 59m are summarized
+        let (events, remaining) = kind.events(&[minutes(1)], Some(minutes(60)));
+        assert_eq!(events.len(), MAX_ATTEMPTS);
+        assert_eq!(events.last().unwrap().at, minutes(11));
+        assert_eq!(remaining, 48);
+
+        // No attempt is made at the expiry itself
+        let (events, remaining) = kind.events(&[minutes(20)], Some(minutes(60)));
+        assert_eq!(
+            events.iter().map(|event| event.at).collect::<Vec<_>>(),
+            [Duration::ZERO, minutes(20), minutes(40)]
+        );
+        assert_eq!(remaining, 0);
+
+        // Listed intervals beyond the expiry are dropped
+        let (events, remaining) =
+            kind.events(&[minutes(30), minutes(30), minutes(30)], Some(minutes(60)));
+        assert_eq!(events.len(), 2);
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn events_offsets() {
+        let (events, remaining) =
+            ScheduleKind::Offsets.events(&[minutes(30), minutes(90)], Some(minutes(60)));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].title, "Delay notification 1");
+        assert_eq!(remaining, 0);
+    }
+}
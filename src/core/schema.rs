@@ -4,11 +4,19 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{hash::Hasher, sync::Arc};
+use std::{hash::Hasher, net::IpAddr, sync::Arc};
 
 use ahash::AHashMap;
+use futures::future::{try_join, try_join_all};
 
-use super::form::{FormData, FormValue};
+use super::{
+    dns::{dns_lookup, reverse_name},
+    form::{FormData, FormValue},
+    http::{self, HttpRequest},
+    i18n::translate,
+    schedule::ScheduleKind,
+    AccessToken,
+};
 
 #[derive(Default)]
 pub struct Schemas {
@@ -28,6 +36,7 @@ pub enum Type<S, F> {
     Text,
     #[default]
     Expression,
+    Rules,
     Select {
         typ: SelectType,
         source: Source<S, F>,
@@ -37,6 +46,8 @@ pub enum Type<S, F> {
     Rate,
     Size,
     Cron,
+    // Rendered by a component registered in `FieldRenderers`, stored as a single value.
+    Custom(&'static str),
 }
 
 #[derive(Clone, Copy, Default, Debug)]
@@ -45,6 +56,7 @@ pub enum SelectType {
     Single,
     Many,
     ManyWithSearch,
+    ManyWithGroups(&'static [(&'static str, &'static [&'static str])]),
 }
 
 #[derive(Clone, Default, Debug)]
@@ -53,13 +65,19 @@ pub struct Field {
     pub label_form: &'static str,
     pub label_column: &'static str,
     pub help: Option<&'static str>,
+    pub docs: Option<&'static str>,
+    pub examples: Vec<&'static str>,
     pub checks: Value<InputCheck>,
+    pub async_validators: Vec<AsyncValidator>,
     pub typ_: Type<Arc<Schema>, Arc<Field>>,
     pub default: Value<FormValue>,
     pub placeholder: Value<&'static str>,
     pub display: Vec<Eval>,
     pub readonly: bool,
     pub enterprise: bool,
+    // Fluent message holding the translated label, with the help text and
+    // column label as its `.help` and `.column` attributes
+    pub message_id: String,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -132,8 +150,34 @@ pub enum Action {
 #[derive(Clone, Default, Debug)]
 pub struct Section {
     pub title: Option<&'static str>,
+    pub message_id: String,
     pub display: Vec<Eval>,
     pub fields: Vec<Arc<Field>>,
+    pub constraints: Vec<Constraint>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    RequiredIf {
+        field: Arc<Field>,
+        eval: Eval,
+    },
+    RequiredWith {
+        field: Arc<Field>,
+        other: Arc<Field>,
+    },
+    GreaterThan {
+        field: Arc<Field>,
+        other: Arc<Field>,
+    },
+    AtLeastOne {
+        fields: Vec<Arc<Field>>,
+    },
+    Schedule {
+        field: Arc<Field>,
+        kind: ScheduleKind,
+        expire: Arc<Field>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -149,6 +193,8 @@ pub enum Source<S, F> {
         field: F,
         filter: Value<&'static [&'static str]>,
     },
+    // Principals of the given types, searched through the management API.
+    Principals(&'static [&'static str]),
 }
 
 #[derive(Clone, Default, Debug)]
@@ -198,10 +244,16 @@ pub enum Validator {
     IsHost,
     IsDomain,
     IsPort,
+    IsIp,
     IsIpOrMask,
     IsUrl,
     IsRegex,
     IsSocketAddr,
+    IsCron,
+    IsSize,
+    IsRate,
+    IsHostKeyFingerprint,
+    IsEndpoint,
     MinLength(usize),
     MaxLength(usize),
     MinValue(NumberType),
@@ -211,6 +263,19 @@ pub enum Validator {
     IsValidExpression(ExpressionValidator),
 }
 
+// Validators that query the server and are therefore only run when the form
+// is saved. A failure is reported as a warning the user can override, since
+// DNS changes are often made after the configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsyncValidator {
+    // Forward-confirmed reverse DNS: the hostname resolves and every address
+    // it resolves to has a PTR record pointing back at it
+    HasReverseDns,
+    // The DKIM signature named by a literal value is defined, names built by
+    // an expression are only known to the server
+    IsSignature,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct ExpressionValidator {
     pub variables: &'static [&'static str],
@@ -242,7 +307,9 @@ impl From<Arc<Schema>> for FormData {
         FormData {
             values: Default::default(),
             errors: Default::default(),
+            section_errors: Default::default(),
             external_sources: Default::default(),
+            secrets: Default::default(),
             schema,
             is_update: false,
         }
@@ -313,6 +380,37 @@ impl Field {
         self.display.is_empty() || self.display.iter().any(|eval| eval.eval(settings))
     }
 
+    pub fn label(&self) -> &'static str {
+        [self.label_form, self.label_column]
+            .into_iter()
+            .find(|label| !label.is_empty())
+            .unwrap_or(self.id)
+    }
+
+    // Translated counterpart of `label`, used in validation messages
+    pub fn label_text(&self) -> String {
+        if !self.label_form.is_empty() {
+            self.label_form_text()
+        } else if !self.label_column.is_empty() {
+            self.label_column_text()
+        } else {
+            self.id.to_string()
+        }
+    }
+
+    pub fn label_form_text(&self) -> String {
+        translate(&self.message_id, self.label_form)
+    }
+
+    pub fn label_column_text(&self) -> String {
+        translate(&format!("{}.column", self.message_id), self.label_column)
+    }
+
+    pub fn help_text(&self) -> Option<String> {
+        self.help
+            .map(|help| translate(&format!("{}.help", self.message_id), help))
+    }
+
     pub fn placeholder(&self, settings: &FormData) -> Option<&str> {
         self.placeholder.eval(settings).copied()
     }
@@ -325,6 +423,42 @@ impl Field {
         self.checks.eval(settings)
     }
 
+    // Bounds declared with `MinValue` and `MaxValue`, used by range sliders
+    pub fn integer_range(&self, settings: &FormData) -> Option<(i64, i64)> {
+        let validators = &self.input_check(settings)?.validators;
+        let min = validators.iter().find_map(|validator| match validator {
+            Validator::MinValue(NumberType::Integer(value)) => Some(*value),
+            _ => None,
+        })?;
+        let max = validators.iter().find_map(|validator| match validator {
+            Validator::MaxValue(NumberType::Integer(value)) => Some(*value),
+            _ => None,
+        })?;
+        (min < max).then_some((min, max))
+    }
+
+    // Size and rate values declaring input checks are also checked for
+    // their format, fields without checks are left as they are.
+    fn with_type_validators(mut self) -> Self {
+        let validator = match self.typ_ {
+            Type::Size => Validator::IsSize,
+            Type::Rate => Validator::IsRate,
+            _ => return self,
+        };
+        for check in self
+            .checks
+            .if_thens
+            .iter_mut()
+            .map(|if_then| &mut if_then.value)
+            .chain(self.checks.default.as_mut())
+        {
+            if !check.validators.contains(&validator) {
+                check.validators.push(validator);
+            }
+        }
+        self
+    }
+
     pub fn is_required(&self, settings: &FormData) -> bool {
         matches!(self.typ_, Type::Boolean | Type::Select { .. })
             || self
@@ -338,8 +472,11 @@ impl Field {
             self.typ_,
             Type::Array
                 | Type::Expression
+                | Type::Rules
                 | Type::Select {
-                    typ: SelectType::Many | SelectType::ManyWithSearch,
+                    typ: SelectType::Many
+                        | SelectType::ManyWithSearch
+                        | SelectType::ManyWithGroups(_),
                     ..
                 }
         )
@@ -359,8 +496,14 @@ impl<T> Value<T> {
 }
 
 impl Eval {
+    // Hidden fields have no value, so conditions on them also depend on
+    // whatever hides them
     pub fn eval(&self, settings: &FormData) -> bool {
-        let value = settings.get(self.field.id);
+        let value = if self.field.display(settings) {
+            settings.get(self.field.id)
+        } else {
+            None
+        };
         match self.condition {
             Condition::MatchAny => self.values.iter().any(|v| value == Some(v)),
             Condition::MatchNone => self.values.iter().all(|v| value != Some(v)),
@@ -372,6 +515,20 @@ impl Section {
     pub fn display(&self, settings: &FormData) -> bool {
         self.display.is_empty() || self.display.iter().any(|eval| eval.eval(settings))
     }
+
+    pub fn title_text(&self) -> Option<String> {
+        self.title.map(|title| translate(&self.message_id, title))
+    }
+}
+
+impl Schema {
+    pub fn form_title_text(&self) -> String {
+        translate(&format!("form-{}", self.id), self.form.title)
+    }
+
+    pub fn form_subtitle_text(&self) -> String {
+        translate(&format!("form-{}.subtitle", self.id), self.form.subtitle)
+    }
 }
 
 impl Schemas {
@@ -417,22 +574,24 @@ impl Builder<Schemas, ()> {
 impl Builder<Schemas, Schema> {
     pub fn new_field(self, id: &'static str) -> Builder<(Schemas, Schema), Field> {
         Builder {
-            parent: (self.parent, self.item),
             item: Field {
                 id,
+                message_id: field_message_id(self.item.id, id),
                 ..Default::default()
             },
+            parent: (self.parent, self.item),
         }
         .typ(Type::Input)
     }
 
     pub fn new_id_field(self) -> Builder<(Schemas, Schema), Field> {
         Builder {
-            parent: (self.parent, self.item),
             item: Field {
                 id: "_id",
+                message_id: field_message_id(self.item.id, "_id"),
                 ..Default::default()
             },
+            parent: (self.parent, self.item),
         }
         .label("Id")
         .typ(Type::Input)
@@ -445,11 +604,12 @@ impl Builder<Schemas, Schema> {
 
     pub fn new_value_field(self) -> Builder<(Schemas, Schema), Field> {
         Builder {
-            parent: (self.parent, self.item),
             item: Field {
                 id: "_value",
+                message_id: field_message_id(self.item.id, "_value"),
                 ..Default::default()
             },
+            parent: (self.parent, self.item),
         }
         .label("Value")
         .typ(Type::Input)
@@ -615,6 +775,30 @@ impl Builder<(Schemas, Schema), Field> {
         self
     }
 
+    // Documentation link and example values are shown in the field's help
+    // drawer, together with the full help text.
+    pub fn docs(mut self, url: &'static str) -> Self {
+        self.item.docs = Some(url);
+        self
+    }
+
+    pub fn example(mut self, example: &'static str) -> Self {
+        self.item.examples.push(example);
+        self
+    }
+
+    pub fn async_validator(mut self, validator: AsyncValidator) -> Self {
+        self.item.async_validators.push(validator);
+        self
+    }
+
+    // Uses a shared message for the label and help text instead of the one
+    // derived from the schema and field ids.
+    pub fn message_id(mut self, message_id: &'static str) -> Self {
+        self.item.message_id = message_id.to_string();
+        self
+    }
+
     pub fn readonly(mut self) -> Self {
         self.item.readonly = true;
         self
@@ -797,7 +981,7 @@ impl Builder<(Schemas, Schema), Field> {
         self.parent
             .1
             .fields
-            .insert(self.item.id, Arc::new(self.item));
+            .insert(self.item.id, Arc::new(self.item.with_type_validators()));
         Builder {
             parent: self.parent.0,
             item: self.parent.1,
@@ -807,6 +991,7 @@ impl Builder<(Schemas, Schema), Field> {
     pub fn new_field(mut self, id: &'static str) -> Self {
         let cloned_field = Field {
             id,
+            message_id: field_message_id(self.parent.1.id, id),
             typ_: self.item.typ_.clone(),
             display: self.item.display.clone(),
             checks: self.item.checks.clone(),
@@ -815,7 +1000,7 @@ impl Builder<(Schemas, Schema), Field> {
         self.parent
             .1
             .fields
-            .insert(self.item.id, Arc::new(self.item));
+            .insert(self.item.id, Arc::new(self.item.with_type_validators()));
         Builder {
             parent: self.parent,
             item: cloned_field,
@@ -826,6 +1011,16 @@ impl Builder<(Schemas, Schema), Field> {
 impl Builder<(Schemas, Schema), Section> {
     pub fn title(mut self, title: &'static str) -> Self {
         self.item.title = Some(title);
+        self.item.message_id = format!(
+            "section-{}-{}",
+            self.parent.1.id,
+            title
+                .to_lowercase()
+                .split(|ch: char| !ch.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+        );
         self
     }
 
@@ -895,6 +1090,84 @@ impl Builder<(Schemas, Schema), Section> {
         self.display_if(field, values, Condition::MatchNone)
     }
 
+    pub fn required_if_eq(
+        mut self,
+        field: &'static str,
+        other: &'static str,
+        values: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        let constraint = Constraint::RequiredIf {
+            field: self.schema_field(field),
+            eval: Eval {
+                field: self.schema_field(other),
+                values: values.into_iter().collect(),
+                condition: Condition::MatchAny,
+            },
+        };
+        self.item.constraints.push(constraint);
+        self
+    }
+
+    pub fn required_with(mut self, field: &'static str, other: &'static str) -> Self {
+        let constraint = Constraint::RequiredWith {
+            field: self.schema_field(field),
+            other: self.schema_field(other),
+        };
+        self.item.constraints.push(constraint);
+        self
+    }
+
+    pub fn greater_than(mut self, field: &'static str, other: &'static str) -> Self {
+        let constraint = Constraint::GreaterThan {
+            field: self.schema_field(field),
+            other: self.schema_field(other),
+        };
+        self.item.constraints.push(constraint);
+        self
+    }
+
+    pub fn at_least_one(mut self, fields: impl IntoIterator<Item = &'static str>) -> Self {
+        let constraint = Constraint::AtLeastOne {
+            fields: fields
+                .into_iter()
+                .map(|field| self.schema_field(field))
+                .collect(),
+        };
+        self.item.constraints.push(constraint);
+        self
+    }
+
+    // Checks the ordering of a queue schedule and that it fits within the
+    // expiry, skipped when either value is a conditional expression
+    pub fn schedule(
+        mut self,
+        field: &'static str,
+        kind: ScheduleKind,
+        expire: &'static str,
+    ) -> Self {
+        let constraint = Constraint::Schedule {
+            field: self.schema_field(field),
+            kind,
+            expire: self.schema_field(expire),
+        };
+        self.item.constraints.push(constraint);
+        self
+    }
+
+    fn schema_field(&self, field: &'static str) -> Arc<Field> {
+        self.parent
+            .1
+            .fields
+            .get(field)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Field {field:?} not found in schema {:?}.",
+                    self.parent.1.id
+                )
+            })
+            .clone()
+    }
+
     pub fn build(mut self) -> Builder<Schemas, Schema> {
         self.parent.1.form.sections.push(self.item);
         Builder {
@@ -954,6 +1227,73 @@ impl InputCheck {
     }
 }
 
+impl AsyncValidator {
+    // Returns a description of the problem when the value does not pass
+    pub async fn validate(&self, auth: &AccessToken, value: &str) -> http::Result<Option<String>> {
+        match self {
+            AsyncValidator::HasReverseDns => {
+                let hostname = value.trim_end_matches('.');
+                let (mut addresses, addresses_v6) = try_join(
+                    dns_lookup(auth, hostname, "A"),
+                    dns_lookup(auth, hostname, "AAAA"),
+                )
+                .await?;
+                addresses.extend(addresses_v6);
+                if addresses.is_empty() {
+                    return Ok(Some(format!("{hostname} does not resolve to any address.")));
+                }
+
+                let ips = addresses
+                    .iter()
+                    .filter_map(|address| address.parse::<IpAddr>().ok())
+                    .collect::<Vec<_>>();
+                let lookups = ips
+                    .iter()
+                    .map(|ip| async move { dns_lookup(auth, &reverse_name(*ip), "PTR").await });
+                let ptrs = try_join_all(lookups).await?;
+
+                let mut problems = Vec::new();
+                for (ip, ptr) in ips.into_iter().zip(ptrs) {
+                    if ptr.is_empty() {
+                        problems.push(format!("{ip} has no PTR record"));
+                    } else if !ptr
+                        .iter()
+                        .any(|name| name.trim_end_matches('.').eq_ignore_ascii_case(hostname))
+                    {
+                        problems.push(format!("{ip} points back to {}", ptr.join(", ")));
+                    }
+                }
+
+                Ok((!problems.is_empty()).then(|| {
+                    format!(
+                        "The reverse DNS does not match {hostname}: {}. Receiving servers may reject outbound messages.",
+                        problems.join("; ")
+                    )
+                }))
+            }
+            AsyncValidator::IsSignature => {
+                let Some(id) = value
+                    .trim()
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''))
+                    .filter(|id| !id.is_empty() && !id.contains(['\'', '+', '(']))
+                else {
+                    return Ok(None);
+                };
+                let key = format!("signature.{id}.domain");
+                let settings = HttpRequest::get("/api/settings/keys")
+                    .with_authorization(auth)
+                    .with_parameter("keys", &key)
+                    .send::<AHashMap<String, String>>()
+                    .await?;
+
+                Ok((!settings.contains_key(&key))
+                    .then(|| format!("There is no DKIM signature named {id}.")))
+            }
+        }
+    }
+}
+
 impl ExpressionValidator {
     pub fn new(variables: &'static [&'static str], constants: &'static [&'static str]) -> Self {
         ExpressionValidator {
@@ -979,6 +1319,7 @@ impl From<Type<&'static str, &'static str>> for Type<Arc<Schema>, Arc<Field>> {
             Type::Boolean => Type::Boolean,
             Type::Duration => Type::Duration,
             Type::Expression => Type::Expression,
+            Type::Rules => Type::Rules,
             Type::Input => Type::Input,
             Type::Array => Type::Array,
             Type::Secret => Type::Secret,
@@ -986,6 +1327,7 @@ impl From<Type<&'static str, &'static str>> for Type<Arc<Schema>, Arc<Field>> {
             Type::Size => Type::Size,
             Type::Cron => Type::Cron,
             Type::Rate => Type::Rate,
+            Type::Custom(name) => Type::Custom(name),
             Type::Select {
                 source: Source::Static(items),
                 typ,
@@ -1000,6 +1342,13 @@ impl From<Type<&'static str, &'static str>> for Type<Arc<Schema>, Arc<Field>> {
                 source: Source::StaticId(items),
                 typ,
             },
+            Type::Select {
+                source: Source::Principals(types),
+                typ,
+            } => Type::Select {
+                source: Source::Principals(types),
+                typ,
+            },
             Type::Select { .. } => unreachable!(),
         }
     }
@@ -1016,3 +1365,7 @@ impl From<f64> for NumberType {
         NumberType::Float(value)
     }
 }
+
+fn field_message_id(schema_id: &str, field_id: &str) -> String {
+    format!("field-{schema_id}-{}", field_id.replace('.', "-"))
+}
@@ -0,0 +1,163 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    cell::{Cell, RefCell},
+    time::Duration,
+};
+
+use gloo_storage::{SessionStorage, Storage};
+use leptos::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{Function, Promise},
+    wasm_bindgen::JsValue,
+};
+
+use crate::STATE_STORAGE_KEY;
+
+use super::{
+    impersonate::current_impersonation,
+    oauth::{oauth_refresh_token, OAuthGrant},
+    AccessToken,
+};
+
+#[derive(Clone, Copy)]
+pub struct Session {
+    pub auth: RwSignal<AccessToken>,
+    pub expired: RwSignal<bool>,
+}
+
+thread_local! {
+    static SESSION: Cell<Option<Session>> = const { Cell::new(None) };
+    // Calls waiting for the session to be renewed, `None` when no renewal
+    // is in progress.
+    static WAITERS: RefCell<Option<Vec<Function>>> = const { RefCell::new(None) };
+}
+
+pub fn init_session(auth: RwSignal<AccessToken>) {
+    let session = Session {
+        auth,
+        expired: create_rw_signal(false),
+    };
+    SESSION.set(Some(session));
+    provide_context(session);
+}
+
+pub fn use_session() -> Session {
+    expect_context::<Session>()
+}
+
+// Obtains a new access token after the server rejected `rejected_token`. The
+// refresh token is tried first, then the admin is asked to sign in again.
+// Concurrent callers share the same renewal and all resume once it completes.
+pub async fn renew_session(rejected_token: &str) -> Option<AccessToken> {
+    let session = SESSION.get()?;
+    let current = session.auth.get_untracked();
+    if !current.is_logged_in() {
+        return None;
+    } else if current.access_token.as_str() != rejected_token {
+        // Another call already renewed the session
+        return Some(current);
+    }
+
+    let is_renewing = WAITERS.with_borrow_mut(|waiters| {
+        if waiters.is_none() {
+            *waiters = Some(vec![]);
+            false
+        } else {
+            true
+        }
+    });
+    if !is_renewing {
+        if !current.refresh_token.is_empty() {
+            if let Some(grant) =
+                oauth_refresh_token(&current.base_url, &current.refresh_token).await
+            {
+                store_grant(session.auth, grant);
+                session.finish(true);
+                return Some(session.auth.get_untracked());
+            }
+        }
+
+        // Impersonation tokens cannot be renewed by the impersonated account
+        if current_impersonation().is_some() {
+            session.finish(false);
+            return None;
+        }
+
+        session.expired.set(true);
+    }
+
+    if wait_for_renewal().await {
+        Some(session.auth.get_untracked())
+    } else {
+        None
+    }
+}
+
+// Stores a newly issued grant and schedules its refresh before it expires.
+pub fn store_grant(auth: RwSignal<AccessToken>, grant: OAuthGrant) {
+    let refresh_token = grant.refresh_token.unwrap_or_default();
+    auth.update(|auth_token| {
+        auth_token.access_token = grant.access_token.into();
+        auth_token.refresh_token = refresh_token.clone().into();
+        auth_token.is_valid = true;
+
+        if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth_token.clone()) {
+            log::error!(
+                "Failed to save authorization token to session storage: {}",
+                err
+            );
+        }
+    });
+
+    // Set timer to refresh token
+    if grant.expires_in > 0 && !refresh_token.is_empty() {
+        log::debug!("Next OAuth token refresh in {} seconds.", grant.expires_in);
+        set_timeout(
+            move || {
+                auth.update(|auth_token| {
+                    auth_token.is_valid = false;
+                });
+            },
+            Duration::from_secs(grant.expires_in),
+        );
+    }
+}
+
+impl Session {
+    // Resumes the calls waiting for the session, which fail with
+    // `Unauthorized` when it could not be renewed.
+    pub fn finish(&self, renewed: bool) {
+        self.expired.set(false);
+        let waiters = WAITERS.with_borrow_mut(|waiters| waiters.take());
+        for waiter in waiters.into_iter().flatten() {
+            if let Err(err) = waiter.call1(&JsValue::NULL, &JsValue::from_bool(renewed)) {
+                log::warn!("Failed to resume pending request: {err:?}");
+            }
+        }
+    }
+}
+
+async fn wait_for_renewal() -> bool {
+    let promise = Promise::new(&mut |resolve, _| {
+        WAITERS.with_borrow_mut(|waiters| {
+            if let Some(waiters) = waiters {
+                waiters.push(resolve);
+            } else {
+                // The renewal finished in the meantime
+                let _ = resolve.call1(&JsValue::NULL, &JsValue::FALSE);
+            }
+        });
+    });
+
+    JsFuture::from(promise)
+        .await
+        .ok()
+        .and_then(|renewed| renewed.as_bool())
+        .unwrap_or_default()
+}
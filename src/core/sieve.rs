@@ -0,0 +1,89 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    http::{self, HttpRequest},
+    url::UrlBuilder,
+    AccessToken,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SieveScript {
+    pub name: String,
+    #[serde(default)]
+    pub is_active: bool,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SieveScriptContent {
+    pub name: String,
+    #[serde(default)]
+    pub is_active: bool,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DeactivateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+// Reading and deactivating the scripts of another account is recorded in the
+// audit log by the server, together with the reason given by the admin.
+pub async fn fetch_sieve_scripts(
+    auth: &AccessToken,
+    account: &str,
+) -> http::Result<Vec<SieveScript>> {
+    HttpRequest::get(sieve_url(account).finish())
+        .with_authorization(auth)
+        .send::<Vec<SieveScript>>()
+        .await
+}
+
+pub async fn fetch_sieve_script(
+    auth: &AccessToken,
+    account: &str,
+    name: &str,
+) -> http::Result<SieveScriptContent> {
+    HttpRequest::get(sieve_url(account).with_subpath(name).finish())
+        .with_authorization(auth)
+        .send::<SieveScriptContent>()
+        .await
+}
+
+pub async fn deactivate_sieve_script(
+    auth: &AccessToken,
+    account: &str,
+    name: &str,
+    reason: Option<String>,
+) -> http::Result<()> {
+    HttpRequest::post(
+        sieve_url(account)
+            .with_subpath(name)
+            .with_subpath("deactivate")
+            .finish(),
+    )
+    .with_authorization(auth)
+    .with_body(DeactivateRequest { reason })?
+    .send::<()>()
+    .await
+}
+
+fn sieve_url(account: &str) -> UrlBuilder {
+    UrlBuilder::new("/api/principal")
+        .with_subpath(account)
+        .with_subpath("sieve")
+}
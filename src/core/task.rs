@@ -0,0 +1,278 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    pages::List,
+};
+
+use super::{
+    http::{self, HttpRequest},
+    oauth::use_authorization,
+    poll::{use_polling, Poller},
+    AccessToken, Permission,
+};
+
+const TRACK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskKind {
+    FtsReindex,
+    PurgeBlobs,
+    CompactStore,
+    PurgeMetrics,
+    MailboxMigration,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: TaskKind,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub progress: Option<TaskProgress>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub error: Option<String>,
+    // Tasks working on several objects, such as migrations, report each one
+    // as a step that can be retried on its own.
+    #[serde(default)]
+    pub steps: Vec<TaskStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStep {
+    pub id: String,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub progress: Option<TaskProgress>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub done: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct StartTask<T: Serialize> {
+    #[serde(rename = "type")]
+    kind: TaskKind,
+    #[serde(flatten)]
+    params: T,
+}
+
+#[derive(Debug, Serialize)]
+struct RetrySteps {
+    steps: Vec<String>,
+}
+
+pub async fn start_task(auth: &AccessToken, kind: TaskKind) -> http::Result<Task> {
+    start_task_with(auth, kind, ()).await
+}
+
+pub async fn start_task_with(
+    auth: &AccessToken,
+    kind: TaskKind,
+    params: impl Serialize,
+) -> http::Result<Task> {
+    HttpRequest::post("/api/tasks")
+        .with_authorization(auth)
+        .with_body(StartTask { kind, params })?
+        .send::<Task>()
+        .await
+}
+
+pub async fn retry_task_steps(
+    auth: &AccessToken,
+    id: &str,
+    steps: Vec<String>,
+) -> http::Result<Task> {
+    HttpRequest::post(format!("/api/tasks/{id}/retry"))
+        .with_authorization(auth)
+        .with_body(RetrySteps { steps })?
+        .send::<Task>()
+        .await
+}
+
+pub async fn fetch_task(auth: &AccessToken, id: &str) -> http::Result<Task> {
+    HttpRequest::get(("/api/tasks", id))
+        .with_authorization(auth)
+        .send::<Task>()
+        .await
+}
+
+pub async fn fetch_tasks(auth: &AccessToken, page: u32, limit: u32) -> http::Result<List<Task>> {
+    HttpRequest::get("/api/tasks")
+        .with_authorization(auth)
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .send::<List<Task>>()
+        .await
+}
+
+// Keeps a task up to date until it finishes, polling is resumed whenever an
+// unfinished task is set again, for example after retrying some of its steps.
+pub fn use_task_tracker(task: RwSignal<Option<Task>>) -> Poller {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let poller = use_polling(TRACK_INTERVAL);
+
+    create_effect(move |_| {
+        if task.with(|task| task.as_ref().is_some_and(|task| !task.status.is_finished())) {
+            poller.resume();
+        } else {
+            poller.pause();
+        }
+    });
+
+    create_effect(move |_| {
+        poller.tick();
+        let Some(id) = task.with_untracked(|task| {
+            task.as_ref()
+                .filter(|task| !task.status.is_finished())
+                .map(|task| task.id.clone())
+        }) else {
+            return;
+        };
+        let auth = auth.get_untracked();
+
+        spawn_local(async move {
+            match fetch_task(&auth, &id).await {
+                Ok(updated) => {
+                    task.set(Some(updated));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    poller.pause();
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    });
+
+    poller
+}
+
+impl TaskKind {
+    // FTS reindexing is requested through its own maintenance action
+    pub const LAUNCHABLE: &'static [TaskKind] = &[
+        TaskKind::PurgeBlobs,
+        TaskKind::CompactStore,
+        TaskKind::PurgeMetrics,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskKind::FtsReindex => "Reindex FTS",
+            TaskKind::PurgeBlobs => "Purge deleted blobs",
+            TaskKind::CompactStore => "Compact store",
+            TaskKind::PurgeMetrics => "Purge old metrics",
+            TaskKind::MailboxMigration => "Mailbox migration",
+            TaskKind::Other => "Other",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            TaskKind::FtsReindex => {
+                "Rebuilds the full-text search index for all accounts. This may take some time."
+            }
+            TaskKind::PurgeBlobs => {
+                "Removes blobs that are no longer referenced by any message or account."
+            }
+            TaskKind::CompactStore => {
+                "Reclaims space in the data store by compacting deleted and expired entries."
+            }
+            TaskKind::PurgeMetrics => {
+                "Deletes metrics history older than the retention configured for each resolution."
+            }
+            TaskKind::MailboxMigration => {
+                "Copies the mailboxes of accounts on another mail server into this one."
+            }
+            TaskKind::Other => "",
+        }
+    }
+
+    pub fn permission(&self) -> Permission {
+        match self {
+            TaskKind::FtsReindex => Permission::FtsReindex,
+            TaskKind::PurgeBlobs => Permission::PurgeBlobStore,
+            TaskKind::MailboxMigration => Permission::MailboxMigrate,
+            TaskKind::CompactStore | TaskKind::PurgeMetrics | TaskKind::Other => {
+                Permission::PurgeDataStore
+            }
+        }
+    }
+}
+
+impl TaskStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskStatus::Queued => "Queued",
+            TaskStatus::Running => "Running",
+            TaskStatus::Completed => "Completed",
+            TaskStatus::Failed => "Failed",
+            TaskStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            TaskStatus::Queued => Color::Gray,
+            TaskStatus::Running => Color::Blue,
+            TaskStatus::Completed => Color::Green,
+            TaskStatus::Failed => Color::Red,
+            TaskStatus::Cancelled => Color::Yellow,
+        }
+    }
+}
+
+impl TaskProgress {
+    pub fn percent(&self) -> u64 {
+        (self.done.min(self.total) * 100)
+            .checked_div(self.total)
+            .unwrap_or_default()
+    }
+}
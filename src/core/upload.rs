@@ -0,0 +1,219 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{js_sys::Uint8Array, File};
+
+use super::{
+    http::{self, HttpRequest, ManagementApiError},
+    url::UrlBuilder,
+    AccessToken,
+};
+
+pub const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UploadKind {
+    Mbox,
+    MigrationBundle,
+    License,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UploadSession {
+    pub id: String,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct NewUpload<'x> {
+    name: &'x str,
+    size: u64,
+    #[serde(rename = "type")]
+    kind: UploadKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<&'x str>,
+}
+
+#[derive(Debug, Serialize)]
+struct CompleteUpload {
+    sha256: String,
+}
+
+// Large files are sent in fixed size chunks to an upload session, so a
+// dropped connection only costs the chunk in flight. The SHA-256 digest is
+// computed as chunks are acknowledged and verified by the server at the end.
+#[derive(Clone)]
+pub struct ChunkedUpload {
+    file: File,
+    session: UploadSession,
+    hasher: Sha256,
+    hashed: u64,
+}
+
+impl ChunkedUpload {
+    pub async fn start(
+        auth: &AccessToken,
+        file: File,
+        kind: UploadKind,
+        account: Option<&str>,
+    ) -> http::Result<Self> {
+        let name = file.name();
+        let session = HttpRequest::post("/api/upload")
+            .with_authorization(auth)
+            .with_body(NewUpload {
+                name: &name,
+                size: file.size() as u64,
+                kind,
+                account,
+            })?
+            .send::<UploadSession>()
+            .await?;
+        let mut upload = ChunkedUpload {
+            file,
+            session,
+            hasher: Sha256::new(),
+            hashed: 0,
+        };
+        upload.hash_until(upload.session.offset).await?;
+
+        Ok(upload)
+    }
+
+    pub fn name(&self) -> String {
+        self.file.name()
+    }
+
+    pub fn size(&self) -> u64 {
+        self.file.size() as u64
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.session.offset
+    }
+
+    pub fn is_sent(&self) -> bool {
+        self.session.offset >= self.size()
+    }
+
+    pub async fn send_next_chunk(&mut self, auth: &AccessToken) -> http::Result<()> {
+        let start = self.session.offset;
+        let end = (start + CHUNK_SIZE).min(self.size());
+        let chunk = self.read(start, end).await?;
+
+        let session = HttpRequest::put(
+            UrlBuilder::new("/api/upload")
+                .with_subpath(&self.session.id)
+                .finish(),
+        )
+        .with_authorization(auth)
+        .with_header("Content-Type", "application/octet-stream")
+        .with_header(
+            "Content-Range",
+            format!("bytes {}-{}/{}", start, end - 1, self.size()),
+        )
+        .with_binary_body(chunk.clone())
+        .send::<UploadSession>()
+        .await?;
+
+        if self.hashed == start {
+            self.hasher.update(&chunk);
+            self.hashed = end;
+        }
+        self.session.offset = session.offset;
+        self.hash_until(session.offset).await
+    }
+
+    // After an interrupted transfer the server is the source of truth for
+    // how much of the file it has stored.
+    pub async fn resync(&mut self, auth: &AccessToken) -> http::Result<()> {
+        let session = HttpRequest::get(("/api/upload", &self.session.id))
+            .with_authorization(auth)
+            .send::<UploadSession>()
+            .await?;
+        self.session.offset = session.offset;
+        self.hash_until(session.offset).await
+    }
+
+    pub async fn complete(&self, auth: &AccessToken) -> http::Result<()> {
+        let digest = self.hasher.clone().finalize();
+        HttpRequest::post(
+            UrlBuilder::new("/api/upload")
+                .with_subpath(&self.session.id)
+                .with_subpath("complete")
+                .finish(),
+        )
+        .with_authorization(auth)
+        .with_body(CompleteUpload {
+            sha256: digest.iter().map(|b| format!("{b:02x}")).collect(),
+        })?
+        .send::<serde_json::Value>()
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn cancel(&self, auth: &AccessToken) -> http::Result<()> {
+        HttpRequest::delete(("/api/upload", &self.session.id))
+            .with_authorization(auth)
+            .send::<serde_json::Value>()
+            .await
+            .map(|_| ())
+    }
+
+    async fn hash_until(&mut self, offset: u64) -> http::Result<()> {
+        if offset < self.hashed {
+            self.hasher = Sha256::new();
+            self.hashed = 0;
+        }
+        while self.hashed < offset {
+            let end = (self.hashed + CHUNK_SIZE).min(offset);
+            let chunk = self.read(self.hashed, end).await?;
+            self.hasher.update(&chunk);
+            self.hashed = end;
+        }
+        Ok(())
+    }
+
+    async fn read(&self, start: u64, end: u64) -> http::Result<Vec<u8>> {
+        let blob = self
+            .file
+            .slice_with_f64_and_f64(start as f64, end as f64)
+            .map_err(read_error)?;
+        JsFuture::from(blob.array_buffer())
+            .await
+            .map(|buf| Uint8Array::new(&buf).to_vec())
+            .map_err(read_error)
+    }
+}
+
+fn read_error(err: web_sys::wasm_bindgen::JsValue) -> http::Error {
+    http::Error::Server(ManagementApiError::Other {
+        details: "Failed to read file".to_string(),
+        reason: err.as_string(),
+    })
+}
+
+impl UploadKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UploadKind::Mbox => "Mailbox archive",
+            UploadKind::MigrationBundle => "Migration bundle",
+            UploadKind::License => "License file",
+        }
+    }
+
+    pub fn accept(&self) -> &'static str {
+        match self {
+            UploadKind::Mbox => ".mbox,.mbx,.gz,.zip",
+            UploadKind::MigrationBundle => ".tar,.gz,.zip",
+            UploadKind::License => ".key,.txt,.lic",
+        }
+    }
+}
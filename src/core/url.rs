@@ -8,6 +8,7 @@ use std::borrow::Cow;
 
 use ahash::AHashMap;
 
+#[derive(Default)]
 pub struct UrlBuilder {
     pub path: String,
     pub params: AHashMap<Cow<'static, str>, String>,
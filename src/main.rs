@@ -6,13 +6,14 @@
 
 #![allow(unstable_name_collisions)]
 use core::{schema::Schemas, AccessToken, Permission, Permissions};
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
 use components::{
     icon::{
-        IconAdjustmentsHorizontal, IconBeaker, IconChartBarSquare, IconClock, IconDocumentChartBar,
-        IconKey, IconLockClosed, IconQueueList, IconShieldCheck, IconSignal, IconSquare2x2,
-        IconUserGroup, IconWrench,
+        IconAdjustmentsHorizontal, IconArrowUpTray, IconBeaker, IconBell, IconChartBarSquare,
+        IconCircleStack, IconClock, IconCodeBracket, IconDocumentChartBar, IconDocumentText,
+        IconInbox, IconInboxArrowDown, IconKey, IconLockClosed, IconNoSymbol, IconQueueList,
+        IconShieldCheck, IconSignal, IconSquare2x2, IconUserGroup, IconWrench,
     },
     layout::MenuItem,
 };
@@ -25,16 +26,53 @@ use pages::{
     account::{
         app_password::{AppPasswordCreate, AppPasswords},
         mfa::ManageMfa,
+        notifications::ManageNotifications,
     },
     config::edit::DEFAULT_SETTINGS_URL,
-    directory::{dns::DnsDisplay, edit::PrincipalEdit, list::PrincipalList},
+    directory::{
+        addresses::DomainAddressRules,
+        dkim::DkimKeys,
+        dns::DnsDisplay,
+        edit::PrincipalEdit,
+        impersonate::{ImpersonateAccount, ImpersonationLog},
+        import::AccountImport,
+        list::PrincipalList,
+        mta_sts::MtaStsPolicy,
+        quota::{AccountUsage, StorageQuotas},
+        sandbox::SandboxDomains,
+    },
     enterprise::{
         dashboard::Dashboard,
+        monitor::LiveMonitor,
         tracing::{display::SpanDisplay, list::SpanList, live::LiveTracing},
         undelete::UndeleteList,
     },
     manage::{
+        access_list::AccessList,
+        api_explorer::ApiExplorer,
+        audit::AuditLog,
+        autoconfig::TroubleshootAutoconfig,
+        dkim::TroubleshootDkim,
+        import::ImportUpload,
+        jmap::TroubleshootJmap,
+        mailbox::{
+            browse::{MailboxBrowseOpen, MailboxBrowser},
+            headers::MessageHeaders,
+        },
+        metering::UsageMetering,
+        migration::MailboxMigration,
+        probe::TroubleshootProbe,
+        recording::ChangeRecording,
+        reputation::TroubleshootReputation,
+        rule_hits::RuleHitList,
+        runbook::TroubleshootRunbooks,
+        scim_log::ScimActivityList,
+        security::BannedAddresses,
+        sessions::ActiveSessions,
+        sieve::{SieveScripts, SieveScriptsOpen},
         spam::{SpamTest, SpamTrain},
+        store_query::StoreQueryConsole,
+        trace::search::MessageTraceSearch,
         troubleshoot::{TroubleshootDelivery, TroubleshootDmarc},
     },
 };
@@ -43,22 +81,49 @@ pub static VERSION_NAME: &str = concat!("Stalwart Management UI v", env!("CARGO_
 
 use crate::{
     components::{
-        layout::{Layout, LayoutBuilder},
+        form::{
+            endpoints::{Backend, EndpointList},
+            renderer::FieldRenderers,
+            schedule::InputSchedule,
+        },
+        layout::{
+            appearance::{init_appearance, ApplyAppearance},
+            Layout, LayoutBuilder,
+        },
         messages::{alert::init_alerts, modal::init_modals},
     },
-    core::oauth::oauth_refresh_token,
+    core::{
+        command::init_commands,
+        health::init_health,
+        i18n::init_i18n,
+        oauth::oauth_refresh_token,
+        schedule::ScheduleKind,
+        session::{init_session, store_grant},
+    },
     pages::{
         account::{crypto::ManageCrypto, password::ChangePassword},
         authorize::Authorize,
-        config::{edit::SettingsEdit, list::SettingsList, search::SettingsSearch},
+        config::{
+            auth_mechanisms::SmtpAuthMechanisms,
+            edit::SettingsEdit,
+            list::SettingsList,
+            listener::ListenerWizard,
+            migrate::LegacyImport,
+            pending::PendingChangesList,
+            quick::QuickSettings,
+            search::{SettingsIndex, SettingsSearch},
+            snapshot::SnapshotList,
+        },
         login::Login,
         manage::{logs::Logs, maintenance::Maintenance},
         notfound::NotFound,
         queue::{
-            messages::{list::QueueList, manage::QueueManage},
+            messages::{list::QueueList, manage::QueueManage, queues::VirtualQueueList},
             reports::{display::ReportDisplay, list::ReportList},
         },
-        reports::{display::IncomingReportDisplay, list::IncomingReportList},
+        reports::{
+            display::IncomingReportDisplay, list::IncomingReportList, tls::TlsReportOverview,
+        },
     },
 };
 
@@ -88,9 +153,24 @@ pub fn App() -> impl IntoView {
     );
     provide_meta_context();
     provide_context(auth_token);
-    provide_context(build_schemas());
+    init_i18n();
+    let schemas = build_schemas();
+    provide_context(Arc::new(SettingsIndex::build(
+        &schemas,
+        &LayoutBuilder::settings(""),
+    )));
+    provide_context(schemas);
+    provide_context(build_field_renderers());
     init_alerts();
     init_modals();
+    init_appearance();
+    init_health();
+    init_session(auth_token);
+    init_commands(vec![
+        pages::config::commands,
+        pages::directory::commands,
+        pages::queue::messages::commands,
+    ]);
 
     // Create a resource to refresh the OAuth token
     let _refresh_token_resource = create_resource(
@@ -106,36 +186,7 @@ pub fn App() -> impl IntoView {
                     )
                     .await
                     {
-                        let refresh_token = grant.refresh_token.unwrap_or_default();
-                        auth_token.update(|auth_token| {
-                            auth_token.access_token = grant.access_token.into();
-                            auth_token.refresh_token = refresh_token.clone().into();
-                            auth_token.is_valid = true;
-
-                            if let Err(err) =
-                                SessionStorage::set(STATE_STORAGE_KEY, auth_token.clone())
-                            {
-                                log::error!(
-                                    "Failed to save authorization token to session storage: {}",
-                                    err
-                                );
-                            }
-                        });
-                        // Set timer to refresh token
-                        if grant.expires_in > 0 && !refresh_token.is_empty() {
-                            log::debug!(
-                                "Next OAuth token refresh in {} seconds.",
-                                grant.expires_in
-                            );
-                            set_timeout(
-                                move || {
-                                    auth_token.update(|auth_token| {
-                                        auth_token.is_valid = false;
-                                    });
-                                },
-                                Duration::from_secs(grant.expires_in),
-                            );
-                        }
+                        store_grant(auth_token, grant);
                     }
                 }
             }
@@ -152,6 +203,7 @@ pub fn App() -> impl IntoView {
     });
 
     view! {
+        <ApplyAppearance/>
         <Router>
             <Routes>
                 <ProtectedRoute
@@ -166,6 +218,16 @@ pub fn App() -> impl IntoView {
                     redirect_path="/login"
                     condition=move || permissions.get().is_some()
                 >
+                    <ProtectedRoute
+                        path="/dashboard/live"
+                        view=LiveMonitor
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::MetricsList))
+                        }
+                    />
                     <ProtectedRoute
                         path="/dashboard/:object?"
                         view=Dashboard
@@ -256,6 +318,132 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/quotas"
+                        view=StorageQuotas
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::IndividualList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/quotas/:id"
+                        view=AccountUsage
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::IndividualGet))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/impersonate/:id"
+                        view=ImpersonateAccount
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Impersonate))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/impersonations"
+                        view=ImpersonationLog
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Impersonate))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/accounts/import"
+                        view=AccountImport
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::IndividualCreate))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/sandbox"
+                        view=SandboxDomains
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[
+                                            Permission::DomainCreate,
+                                            Permission::DomainDelete,
+                                            Permission::IndividualCreate,
+                                            Permission::IndividualDelete,
+                                        ],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/dkim"
+                        view=DkimKeys
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::DkimSignatureGet, Permission::SettingsList],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/dns/:id/mta-sts"
+                        view=MtaStsPolicy
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[
+                                            Permission::DomainGet,
+                                            Permission::SettingsList,
+                                            Permission::SettingsUpdate,
+                                        ],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/dns/:id/addresses"
+                        view=DomainAddressRules
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[
+                                            Permission::DomainGet,
+                                            Permission::IndividualList,
+                                            Permission::IndividualUpdate,
+                                        ],
+                                    )
+                                })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/queue/messages"
                         view=QueueList
@@ -267,6 +455,17 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/queue/virtual"
+                        view=VirtualQueueList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| { p.has_access(Permission::MessageQueueList) })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/queue/message/:id"
                         view=QueueManage
@@ -311,6 +510,17 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/reports/tls/overview"
+                        view=TlsReportOverview
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .map_or(false, |p| { p.has_access(Permission::IncomingReportGet) })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/reports/:object/:id"
                         view=IncomingReportDisplay
@@ -333,6 +543,15 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/audit"
+                        view=AuditLog
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::LogsView))
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/spam/train"
                         view=SpamTrain
@@ -371,6 +590,9 @@ pub fn App() -> impl IntoView {
                                                 Permission::Restart,
                                                 Permission::SpamFilterUpdate,
                                                 Permission::WebadminUpdate,
+                                                Permission::FtsReindex,
+                                                Permission::PurgeBlobStore,
+                                                Permission::PurgeDataStore,
                                             ],
                                         )
                                     },
@@ -378,6 +600,75 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/mailbox"
+                        view=MailboxBrowseOpen
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::MailboxBrowse))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/mailbox/:id"
+                        view=MailboxBrowser
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::MailboxBrowse))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/mailbox/:id/:message"
+                        view=MessageHeaders
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::MailboxBrowse))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/sieve"
+                        view=SieveScriptsOpen
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::SieveInspect))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/sieve/:id"
+                        view=SieveScripts
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::SieveInspect))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/import"
+                        view=ImportUpload
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_admin_access())
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/migration"
+                        view=MailboxMigration
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::MailboxMigrate))
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/undelete/:id"
                         view=UndeleteList
@@ -411,6 +702,21 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/trace"
+                        view=MessageTraceSearch
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::TracingList, Permission::TracingGet],
+                                    )
+                                })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/tracing/:object"
                         view=SpanList
@@ -422,6 +728,103 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/access-list/:object"
+                        view=AccessList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/api-explorer"
+                        view=ApiExplorer
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::SettingsList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/store/query"
+                        view=StoreQueryConsole
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(&[Permission::StoreQuery, Permission::SettingsList])
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/recording"
+                        view=ChangeRecording
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_admin_access())
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/metering"
+                        view=UsageMetering
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::DomainList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/sessions"
+                        view=ActiveSessions
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::SessionList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/rule-hits"
+                        view=RuleHitList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::MetricsList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/scim-activity"
+                        view=ScimActivityList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_access(Permission::SettingsList))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/security/bans"
+                        view=BannedAddresses
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/troubleshoot/delivery"
                         view=TroubleshootDelivery
@@ -444,6 +847,72 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/troubleshoot/dkim"
+                        view=TroubleshootDkim
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/troubleshoot/autoconfig"
+                        view=TroubleshootAutoconfig
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/troubleshoot/jmap"
+                        view=TroubleshootJmap
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/troubleshoot/probe"
+                        view=TroubleshootProbe
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/troubleshoot/reputation"
+                        view=TroubleshootReputation
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/troubleshoot/runbooks"
+                        view=TroubleshootRunbooks
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::Troubleshoot))
+                        }
+                    />
+
                 </ProtectedRoute>
                 <ProtectedRoute
                     path="/settings"
@@ -477,6 +946,92 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/snapshots"
+                        view=SnapshotList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/pending-changes"
+                        view=PendingChangesList
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| p.has_access(Permission::SettingsUpdate))
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/migrate"
+                        view=LegacyImport
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/quick"
+                        view=QuickSettings
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/listener-wizard"
+                        view=ListenerWizard
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
+                    <ProtectedRoute
+                        path="/smtp-auth-mechanisms"
+                        view=SmtpAuthMechanisms
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions
+                                .get()
+                                .is_some_and(|p| {
+                                    p.has_access_all(
+                                        &[Permission::SettingsList, Permission::SettingsUpdate],
+                                    )
+                                })
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/search"
                         view=SettingsSearch
@@ -534,6 +1089,15 @@ pub fn App() -> impl IntoView {
                         }
                     />
 
+                    <ProtectedRoute
+                        path="/notifications"
+                        view=ManageNotifications
+                        redirect_path="/login"
+                        condition=move || {
+                            permissions.get().is_some_and(|p| p.has_admin_access())
+                        }
+                    />
+
                     <ProtectedRoute
                         path="/app-passwords"
                         view=AppPasswords
@@ -588,6 +1152,9 @@ impl LayoutBuilder {
             .create("Performance")
             .route("/dashboard/performance")
             .insert(true)
+            .create("Live monitor")
+            .route("/dashboard/live")
+            .insert(true)
             .insert(permissions.has_access_all(&[Permission::MetricsList, Permission::MetricsLive]))
             .create("Directory")
             .icon(view! { <IconUserGroup/> })
@@ -609,12 +1176,41 @@ impl LayoutBuilder {
             .create("Tenants")
             .route("/directory/tenants")
             .insert(permissions.has_access(Permission::TenantList))
+            .create("DKIM Keys")
+            .route("/dkim")
+            .insert(
+                permissions
+                    .has_access_all(&[Permission::DkimSignatureGet, Permission::SettingsList]),
+            )
+            .create("Import Accounts")
+            .route("/accounts/import")
+            .insert(permissions.has_access(Permission::IndividualCreate))
+            .create("Sandbox")
+            .route("/sandbox")
+            .insert(permissions.has_access_all(&[
+                Permission::DomainCreate,
+                Permission::DomainDelete,
+                Permission::IndividualCreate,
+                Permission::IndividualDelete,
+            ]))
+            .create("Quotas")
+            .route("/quotas")
+            .insert(permissions.has_access(Permission::IndividualList))
+            .create("Impersonations")
+            .route("/impersonations")
+            .insert(permissions.has_access(Permission::Impersonate))
+            .create("Usage")
+            .route("/metering")
+            .insert(permissions.has_access(Permission::DomainList))
             .create("API Keys")
             .route("/directory/api-keys")
             .insert(permissions.has_access(Permission::ApiKeyList))
             .create("OAuth Clients")
             .route("/directory/oauth-clients")
             .insert(permissions.has_access(Permission::OauthClientList))
+            .create("SCIM Activity")
+            .route("/scim-activity")
+            .insert(permissions.has_access(Permission::SettingsList))
             .insert(permissions.has_access_any(&[
                 Permission::IndividualList,
                 Permission::GroupList,
@@ -630,6 +1226,9 @@ impl LayoutBuilder {
             .create("Messages")
             .route("/queue/messages")
             .insert(permissions.has_access(Permission::MessageQueueList))
+            .create("Virtual Queues")
+            .route("/queue/virtual")
+            .insert(permissions.has_access(Permission::MessageQueueList))
             .create("Reports")
             .route("/queue/reports")
             .insert(permissions.has_access(Permission::OutgoingReportList))
@@ -647,6 +1246,9 @@ impl LayoutBuilder {
             .create("TLS Aggregate")
             .route("/reports/tls")
             .insert(true)
+            .create("TLS Overview")
+            .route("/reports/tls/overview")
+            .insert(permissions.has_access(Permission::IncomingReportGet))
             .create("Failures")
             .route("/reports/arf")
             .insert(true)
@@ -659,16 +1261,33 @@ impl LayoutBuilder {
             .create("Delivery Attempts")
             .route("/tracing/delivery")
             .insert(true)
+            .create("Message Trace")
+            .route("/trace")
+            .insert(permissions.has_access(Permission::TracingGet))
             .insert(permissions.has_access(Permission::TracingList))
             .create("Telemetry")
             .icon(view! { <IconSignal/> })
             .create("Logs")
             .route("/logs")
             .insert(permissions.has_access(Permission::LogsView))
+            .create("Audit log")
+            .route("/audit")
+            .insert(permissions.has_access(Permission::LogsView))
             .create("Live tracing")
             .route("/tracing/live")
             .insert(permissions.has_access(Permission::TracingLive))
-            .insert(permissions.has_access_any(&[Permission::LogsView, Permission::TracingLive]))
+            .create("Rule hits")
+            .route("/rule-hits")
+            .insert(permissions.has_access(Permission::MetricsList))
+            .create("Active sessions")
+            .route("/sessions")
+            .insert(permissions.has_access(Permission::SessionList))
+            .insert(permissions.has_access_any(&[
+                Permission::LogsView,
+                Permission::TracingLive,
+                Permission::MetricsList,
+                Permission::SessionList,
+            ]))
             .create("Spam filter")
             .icon(view! { <IconShieldCheck/> })
             .create("Train")
@@ -678,6 +1297,26 @@ impl LayoutBuilder {
             .route("/spam/test")
             .insert(true)
             .insert(permissions.has_access(Permission::SpamFilterTrain))
+            .create("Access control")
+            .icon(view! { <IconNoSymbol/> })
+            .create("Banned IPs")
+            .route("/security/bans")
+            .insert(true)
+            .create("Blocked IPs")
+            .route("/access-list/blocked-ip")
+            .insert(true)
+            .create("Allowed IPs")
+            .route("/access-list/allowed-ip")
+            .insert(true)
+            .create("Blocked domains")
+            .route("/access-list/blocked-domains")
+            .insert(true)
+            .create("Trusted domains")
+            .route("/access-list/trusted-domains")
+            .insert(true)
+            .insert(
+                permissions.has_access_all(&[Permission::SettingsList, Permission::SettingsUpdate]),
+            )
             .create("Troubleshoot")
             .icon(view! { <IconBeaker/> })
             .create("E-mail Delivery")
@@ -686,7 +1325,37 @@ impl LayoutBuilder {
             .create("DMARC")
             .route("/troubleshoot/dmarc")
             .insert(true)
+            .create("DKIM")
+            .route("/troubleshoot/dkim")
+            .insert(true)
+            .create("Client Autoconfig")
+            .route("/troubleshoot/autoconfig")
+            .insert(true)
+            .create("JMAP Session")
+            .route("/troubleshoot/jmap")
+            .insert(true)
+            .create("Deliverability Probe")
+            .route("/troubleshoot/probe")
+            .insert(true)
+            .create("IP Reputation")
+            .route("/troubleshoot/reputation")
+            .insert(true)
+            .create("Guided Troubleshooting")
+            .route("/troubleshoot/runbooks")
+            .insert(true)
             .insert(permissions.has_access(Permission::Troubleshoot))
+            .create("API Explorer")
+            .icon(view! { <IconCodeBracket/> })
+            .route("/api-explorer")
+            .insert(permissions.has_access(Permission::SettingsList))
+            .create("Query Console")
+            .icon(view! { <IconCircleStack/> })
+            .route("/store/query")
+            .insert(permissions.has_access_all(&[Permission::StoreQuery, Permission::SettingsList]))
+            .create("Change Recording")
+            .icon(view! { <IconDocumentText/> })
+            .route("/recording")
+            .insert(permissions.has_admin_access())
             .create("Settings")
             .icon(view! { <IconAdjustmentsHorizontal/> })
             .raw_route(DEFAULT_SETTINGS_URL)
@@ -699,7 +1368,26 @@ impl LayoutBuilder {
                 Permission::Restart,
                 Permission::SpamFilterUpdate,
                 Permission::WebadminUpdate,
+                Permission::FtsReindex,
+                Permission::PurgeBlobStore,
+                Permission::PurgeDataStore,
             ]))
+            .create("Mailbox Browser")
+            .icon(view! { <IconInbox/> })
+            .route("/mailbox")
+            .insert(permissions.has_access(Permission::MailboxBrowse))
+            .create("Sieve Scripts")
+            .icon(view! { <IconCodeBracket/> })
+            .route("/sieve")
+            .insert(permissions.has_access(Permission::SieveInspect))
+            .create("Import")
+            .icon(view! { <IconArrowUpTray/> })
+            .route("/import")
+            .insert(permissions.has_admin_access())
+            .create("Migration")
+            .icon(view! { <IconInboxArrowDown/> })
+            .route("/migration")
+            .insert(permissions.has_access(Permission::MailboxMigrate))
             .menu_items
     }
 
@@ -721,10 +1409,58 @@ impl LayoutBuilder {
             .icon(view! { <IconSquare2x2/> })
             .route("/app-passwords")
             .insert(permissions.has_access(Permission::ManagePasswords))
+            .create("Notifications")
+            .icon(view! { <IconBell/> })
+            .route("/notifications")
+            .insert(permissions.has_admin_access())
             .menu_items
     }
 }
 
+// Custom field renderers, such as widgets for `Type::Custom` fields, are
+// registered here with `FieldRenderers::with_type` or `with_field`.
+pub fn build_field_renderers() -> FieldRenderers {
+    FieldRenderers::default()
+        .with_field("store", "balance.endpoints", |ctx| {
+            view! {
+                <EndpointList element=ctx.element backend=Backend::Store disabled=ctx.disabled/>
+            }
+            .into_view()
+        })
+        .with_field("directory", "balance.endpoints", |ctx| {
+            view! {
+                <EndpointList
+                    element=ctx.element
+                    backend=Backend::Directory
+                    disabled=ctx.disabled
+                />
+            }
+            .into_view()
+        })
+        .with_field("smtp-out-queue", "queue.schedule.retry", |ctx| {
+            view! {
+                <InputSchedule
+                    element=ctx.element
+                    kind=ScheduleKind::Intervals
+                    expire="queue.schedule.expire"
+                    disabled=ctx.disabled
+                />
+            }
+            .into_view()
+        })
+        .with_field("smtp-out-queue", "queue.schedule.notify", |ctx| {
+            view! {
+                <InputSchedule
+                    element=ctx.element
+                    kind=ScheduleKind::Offsets
+                    expire="queue.schedule.expire"
+                    disabled=ctx.disabled
+                />
+            }
+            .into_view()
+        })
+}
+
 pub fn build_schemas() -> Arc<Schemas> {
     Schemas::builder()
         .build_login()
@@ -737,6 +1473,7 @@ pub fn build_schemas() -> Arc<Schemas> {
         .build_server()
         .build_listener()
         .build_telemetry()
+        .build_metrics()
         .build_smtp_inbound()
         .build_smtp_outbound()
         .build_mail_auth()
@@ -745,13 +1482,31 @@ pub fn build_schemas() -> Arc<Schemas> {
         .build_sieve()
         .build_spam_lists()
         .build_spam_manage()
+        .build_access_lists()
         .build_password_change()
         .build_crypto()
+        .build_notifications()
         .build_authorize()
         .build_mfa()
         .build_app_passwords()
         .build_live_tracing()
         .build_troubleshoot()
+        .build_runbooks()
+        .build_reputation()
+        .build_autoconfig()
+        .build_jmap_session()
+        .build_store_query()
+        .build_migration()
+        .build_mta_sts()
+        .build_dkim()
+        .build_bulk_quota()
+        .build_import()
+        .build_message_trace()
+        .build_impersonate()
+        .build_mailbox_browse()
+        .build_sieve_inspect()
+        .build_sandbox()
+        .build_api_explorer()
         .build()
         .into()
 }
@@ -7,7 +7,6 @@
 use std::{sync::Arc, vec};
 
 use chrono::{DateTime, Utc};
-use chrono_humanize::HumanTime;
 use leptos::*;
 use leptos_router::{use_navigate, use_query_map};
 use pwhash::sha512_crypt;
@@ -33,6 +32,7 @@ use crate::{
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
@@ -358,8 +358,7 @@ fn PasswordItem(password: AppPassword) -> impl IntoView {
             <ListItem subclass="px-6 py-1.5">
                 {password
                     .created
-                    .map(|created| HumanTime::from(created).to_string())
-                    .unwrap_or_default()}
+                    .map(|date| view! { <Timestamp date relative=true/> })}
             </ListItem>
 
         </tr>
@@ -0,0 +1,607 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use web_sys::{
+    js_sys::{Array, Date, Function, Intl::DateTimeFormat, Object, Reflect},
+    wasm_bindgen::{JsCast, JsValue},
+};
+
+use crate::{
+    components::{
+        form::{
+            button::Button,
+            input::{InputSwitch, InputText},
+            select::Select,
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        layout::notifications::use_notifications,
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        events::ChangeTopic,
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationCategory {
+    Certificates,
+    Queue,
+    Security,
+    Reports,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationChannel {
+    Toast,
+    Badge,
+    Email,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationPreferences {
+    #[serde(default)]
+    pub categories: Vec<CategoryPreferences>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryPreferences {
+    pub category: NotificationCategory,
+    #[serde(default)]
+    pub channels: Vec<NotificationChannel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub start: u32,
+    pub end: u32,
+    pub timezone: String,
+    #[serde(default)]
+    pub hold_email: bool,
+}
+
+static HOURS: &[(&str, &str)] = &[
+    ("0", "00:00"),
+    ("1", "01:00"),
+    ("2", "02:00"),
+    ("3", "03:00"),
+    ("4", "04:00"),
+    ("5", "05:00"),
+    ("6", "06:00"),
+    ("7", "07:00"),
+    ("8", "08:00"),
+    ("9", "09:00"),
+    ("10", "10:00"),
+    ("11", "11:00"),
+    ("12", "12:00"),
+    ("13", "13:00"),
+    ("14", "14:00"),
+    ("15", "15:00"),
+    ("16", "16:00"),
+    ("17", "17:00"),
+    ("18", "18:00"),
+    ("19", "19:00"),
+    ("20", "20:00"),
+    ("21", "21:00"),
+    ("22", "22:00"),
+    ("23", "23:00"),
+];
+
+#[component]
+pub fn ManageNotifications() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let notifications = use_notifications();
+    let fetch_preferences = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/account/notifications")
+                    .with_authorization(&auth)
+                    .send::<NotificationPreferences>()
+                    .await
+            }
+        },
+    );
+
+    let (pending, set_pending) = create_signal(false);
+
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("notifications")
+        .into_signal();
+
+    let save_changes = create_action(move |changes: &NotificationPreferences| {
+        let changes = changes.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::put("/api/account/notifications")
+                .with_authorization(&auth)
+                .with_body(changes.clone())
+                .unwrap()
+                .send::<serde_json::Value>()
+                .await;
+            set_pending.set(false);
+
+            if result.is_ok() {
+                notifications.set_preferences(changes);
+            }
+            alert.set(match result {
+                Ok(_) => Alert::success("Preferences saved")
+                    .with_details("Your notification preferences have been updated"),
+                Err(err) => Alert::from(err),
+            });
+        }
+    });
+
+    view! {
+        <Form
+            title="Notifications"
+            subtitle="Choose which events notify you and how. These preferences apply on every device you sign in from."
+        >
+
+            <Transition fallback=Skeleton set_pending>
+
+                {move || match fetch_preferences.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok(preferences)) => {
+                        data.update(|data| {
+                            data.from_notification_preferences(&preferences);
+                        });
+                        let no_quiet_hours = create_memo(move |_| {
+                            !data.get().value::<bool>("quiet-hours.enable").unwrap_or_default()
+                        });
+                        Some(
+                            view! {
+                                <FormSection title="Events".to_string()>
+                                    <NotificationMatrix data=data/>
+                                </FormSection>
+
+                                <FormSection title="Quiet Hours".to_string()>
+                                    <FormItem label="Enable">
+                                        <InputSwitch
+                                            label="Suppress toasts and badges during a daily time window"
+                                            element=FormElement::new("quiet-hours.enable", data)
+                                        />
+                                    </FormItem>
+                                    <FormItem label="From" hide=no_quiet_hours>
+                                        <Select element=FormElement::new("quiet-hours.start", data)/>
+                                    </FormItem>
+                                    <FormItem label="Until" hide=no_quiet_hours>
+                                        <Select element=FormElement::new("quiet-hours.end", data)/>
+                                    </FormItem>
+                                    <FormItem
+                                        label="Time zone"
+                                        tooltip="IANA time zone name, such as Europe/Madrid"
+                                        hide=no_quiet_hours
+                                    >
+                                        <InputText element=FormElement::new("quiet-hours.timezone", data)/>
+                                    </FormItem>
+                                    <FormItem label="E-mail" hide=no_quiet_hours>
+                                        <InputSwitch
+                                            label="Hold e-mail notifications until quiet hours end"
+                                            element=FormElement::new("quiet-hours.hold-email", data)
+                                        />
+                                    </FormItem>
+                                </FormSection>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <FormButtonBar>
+
+                <Button
+                    text="Save changes"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        data.update(|data| {
+                            if let Some(changes) = data.to_notification_preferences() {
+                                save_changes.dispatch(changes);
+                            }
+                        });
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+
+        </Form>
+    }
+}
+
+#[component]
+fn NotificationMatrix(data: RwSignal<FormData>) -> impl IntoView {
+    view! {
+        <div class="overflow-x-auto">
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700">
+                <thead>
+                    <tr>
+                        <th class="py-2 pe-6 text-start text-xs font-semibold uppercase text-gray-800 dark:text-gray-200">
+                            Category
+                        </th>
+                        {NotificationChannel::ALL
+                            .iter()
+                            .map(|channel| {
+                                view! {
+                                    <th class="py-2 px-6 text-center text-xs font-semibold uppercase text-gray-800 dark:text-gray-200">
+                                        {channel.name()}
+                                    </th>
+                                }
+                            })
+                            .collect_view()}
+                    </tr>
+                </thead>
+                <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                    {NotificationCategory::ALL
+                        .iter()
+                        .map(|category| {
+                            view! {
+                                <tr>
+                                    <td class="py-3 pe-6">
+                                        <p class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                                            {category.name()}
+                                        </p>
+                                        <p class="text-xs text-gray-500 dark:text-gray-400">
+                                            {category.description()}
+                                        </p>
+                                    </td>
+                                    {NotificationChannel::ALL
+                                        .iter()
+                                        .map(|channel| {
+                                            let id = category.field_id(*channel);
+                                            view! {
+                                                <td class="py-3 px-6 text-center">
+                                                    <input
+                                                        type="checkbox"
+                                                        class="shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                                        prop:checked=move || {
+                                                            data.get().value::<bool>(id).unwrap_or_default()
+                                                        }
+
+                                                        on:change=move |ev| {
+                                                            let checked = event_target_checked(&ev);
+                                                            data.update(|data| {
+                                                                data.update(id, if checked { "true" } else { "false" });
+                                                            });
+                                                        }
+                                                    />
+
+                                                </td>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+#[allow(clippy::wrong_self_convention)]
+impl FormData {
+    fn from_notification_preferences(&mut self, preferences: &NotificationPreferences) {
+        if !preferences.categories.is_empty() {
+            for category in NotificationCategory::ALL {
+                let channels = preferences
+                    .categories
+                    .iter()
+                    .find(|c| c.category == *category)
+                    .map(|c| c.channels.as_slice())
+                    .unwrap_or_default();
+                for channel in NotificationChannel::ALL {
+                    self.set(
+                        category.field_id(*channel),
+                        channels.contains(channel).to_string(),
+                    );
+                }
+            }
+        }
+
+        if let Some(quiet_hours) = &preferences.quiet_hours {
+            self.set("quiet-hours.enable", "true");
+            self.set("quiet-hours.start", quiet_hours.start.to_string());
+            self.set("quiet-hours.end", quiet_hours.end.to_string());
+            self.set("quiet-hours.timezone", &quiet_hours.timezone);
+            self.set("quiet-hours.hold-email", quiet_hours.hold_email.to_string());
+        } else {
+            self.set("quiet-hours.enable", "false");
+            if let Some(timezone) = browser_timezone() {
+                self.set("quiet-hours.timezone", timezone);
+            }
+        }
+    }
+
+    fn to_notification_preferences(&mut self) -> Option<NotificationPreferences> {
+        if !self.validate_form() {
+            return None;
+        }
+
+        let categories = NotificationCategory::ALL
+            .iter()
+            .map(|category| CategoryPreferences {
+                category: *category,
+                channels: NotificationChannel::ALL
+                    .iter()
+                    .filter(|channel| {
+                        self.value::<bool>(category.field_id(**channel))
+                            .unwrap_or_default()
+                    })
+                    .copied()
+                    .collect(),
+            })
+            .collect();
+        let quiet_hours = if self.value::<bool>("quiet-hours.enable").unwrap_or_default() {
+            let start = self.value::<u32>("quiet-hours.start").unwrap_or_default();
+            let end = self.value::<u32>("quiet-hours.end").unwrap_or_default();
+            if start == end {
+                self.new_error("quiet-hours.end", "End time must differ from start time");
+                return None;
+            }
+            Some(QuietHours {
+                start,
+                end,
+                timezone: self.value("quiet-hours.timezone").unwrap_or_default(),
+                hold_email: self
+                    .value::<bool>("quiet-hours.hold-email")
+                    .unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
+        Some(NotificationPreferences {
+            categories,
+            quiet_hours,
+        })
+    }
+}
+
+impl NotificationPreferences {
+    // Whether an event of this category is delivered on a channel right now.
+    // Quiet hours only hold back toasts and badges, e-mail is sent by the server.
+    pub fn allows(&self, category: NotificationCategory, channel: NotificationChannel) -> bool {
+        let is_enabled = if self.categories.is_empty() {
+            category.is_default(channel)
+        } else {
+            self.categories
+                .iter()
+                .find(|c| c.category == category)
+                .is_some_and(|c| c.channels.contains(&channel))
+        };
+
+        is_enabled
+            && (channel == NotificationChannel::Email
+                || !self.quiet_hours.as_ref().is_some_and(QuietHours::is_active))
+    }
+}
+
+impl QuietHours {
+    pub fn is_active(&self) -> bool {
+        let Some(hour) = current_hour(&self.timezone) else {
+            return false;
+        };
+        if self.start < self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+// Hour of the day in a time zone, `None` if the browser does not know the zone
+fn current_hour(timezone: &str) -> Option<u32> {
+    let options = Object::new();
+    Reflect::set(&options, &"timeZone".into(), &timezone.into()).ok()?;
+    Reflect::set(&options, &"hour".into(), &"numeric".into()).ok()?;
+    Reflect::set(&options, &"hourCycle".into(), &"h23".into()).ok()?;
+
+    // Constructed through Reflect as unknown time zones throw a RangeError
+    let constructor = Reflect::get(
+        &DateTimeFormat::new(&Array::new(), &Object::new()),
+        &"constructor".into(),
+    )
+    .ok()?
+    .dyn_into::<Function>()
+    .ok()?;
+    let args = Array::of2(&"en-US".into(), &options);
+    Reflect::construct(&constructor, &args)
+        .ok()?
+        .unchecked_into::<DateTimeFormat>()
+        .format()
+        .call1(&JsValue::UNDEFINED, &Date::new_0())
+        .ok()?
+        .as_string()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn browser_timezone() -> Option<String> {
+    let options = DateTimeFormat::new(&Array::new(), &Object::new()).resolved_options();
+    Reflect::get(&options, &"timeZone".into()).ok()?.as_string()
+}
+
+impl NotificationCategory {
+    pub const ALL: &'static [NotificationCategory] = &[
+        NotificationCategory::Certificates,
+        NotificationCategory::Queue,
+        NotificationCategory::Security,
+        NotificationCategory::Reports,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationCategory::Certificates => "Certificates",
+            NotificationCategory::Queue => "Queue",
+            NotificationCategory::Security => "Security",
+            NotificationCategory::Reports => "Reports",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            NotificationCategory::Certificates => "Certificate renewals, failures and expirations",
+            NotificationCategory::Queue => "Delivery failures and queue backlogs",
+            NotificationCategory::Security => {
+                "Blocked addresses, failed logins and fail2ban events"
+            }
+            NotificationCategory::Reports => "Incoming DMARC, TLS and ARF reports",
+        }
+    }
+
+    pub fn topic(&self) -> ChangeTopic {
+        match self {
+            NotificationCategory::Certificates => ChangeTopic::Certificates,
+            NotificationCategory::Queue => ChangeTopic::Queue,
+            NotificationCategory::Security => ChangeTopic::Security,
+            NotificationCategory::Reports => ChangeTopic::Reports,
+        }
+    }
+
+    pub fn route(&self) -> &'static str {
+        match self {
+            NotificationCategory::Certificates => "/settings/certificate",
+            NotificationCategory::Queue => "/manage/queue/messages",
+            NotificationCategory::Security => "/manage/security/bans",
+            NotificationCategory::Reports => "/manage/reports/dmarc",
+        }
+    }
+
+    // Channels used until the admin saves preferences
+    fn is_default(&self, channel: NotificationChannel) -> bool {
+        !matches!(
+            (self, channel),
+            (NotificationCategory::Reports, _) | (_, NotificationChannel::Email)
+        )
+    }
+
+    fn field_id(&self, channel: NotificationChannel) -> &'static str {
+        match (self, channel) {
+            (NotificationCategory::Certificates, NotificationChannel::Toast) => {
+                "certificates.toast"
+            }
+            (NotificationCategory::Certificates, NotificationChannel::Badge) => {
+                "certificates.badge"
+            }
+            (NotificationCategory::Certificates, NotificationChannel::Email) => {
+                "certificates.email"
+            }
+            (NotificationCategory::Queue, NotificationChannel::Toast) => "queue.toast",
+            (NotificationCategory::Queue, NotificationChannel::Badge) => "queue.badge",
+            (NotificationCategory::Queue, NotificationChannel::Email) => "queue.email",
+            (NotificationCategory::Security, NotificationChannel::Toast) => "security.toast",
+            (NotificationCategory::Security, NotificationChannel::Badge) => "security.badge",
+            (NotificationCategory::Security, NotificationChannel::Email) => "security.email",
+            (NotificationCategory::Reports, NotificationChannel::Toast) => "reports.toast",
+            (NotificationCategory::Reports, NotificationChannel::Badge) => "reports.badge",
+            (NotificationCategory::Reports, NotificationChannel::Email) => "reports.email",
+        }
+    }
+}
+
+impl NotificationChannel {
+    pub const ALL: &'static [NotificationChannel] = &[
+        NotificationChannel::Toast,
+        NotificationChannel::Badge,
+        NotificationChannel::Email,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationChannel::Toast => "Toast",
+            NotificationChannel::Badge => "Badge",
+            NotificationChannel::Email => "E-mail",
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_notifications(self) -> Self {
+        let mut builder = self.new_schema("notifications");
+        for category in NotificationCategory::ALL {
+            for channel in NotificationChannel::ALL {
+                builder = builder
+                    .new_field(category.field_id(*channel))
+                    .typ(Type::Boolean)
+                    .default(if category.is_default(*channel) {
+                        "true"
+                    } else {
+                        "false"
+                    })
+                    .build();
+            }
+        }
+
+        builder
+            .new_field("quiet-hours.enable")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("quiet-hours.start")
+            .typ(Type::Select {
+                source: Source::Static(HOURS),
+                typ: SelectType::Single,
+            })
+            .default("22")
+            .display_if_eq("quiet-hours.enable", ["true"])
+            .build()
+            .new_field("quiet-hours.end")
+            .typ(Type::Select {
+                source: Source::Static(HOURS),
+                typ: SelectType::Single,
+            })
+            .default("7")
+            .display_if_eq("quiet-hours.enable", ["true"])
+            .build()
+            .new_field("quiet-hours.timezone")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .default("UTC")
+            .display_if_eq("quiet-hours.enable", ["true"])
+            .build()
+            .new_field("quiet-hours.hold-email")
+            .typ(Type::Boolean)
+            .default("false")
+            .display_if_eq("quiet-hours.enable", ["true"])
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,354 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use leptos::{leptos_dom::helpers::IntervalHandle, *};
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        Color,
+    },
+    core::{
+        dns::dns_lookup_with_resolver, expr::ParseValue, form::FormData, http,
+        oauth::use_authorization,
+    },
+};
+
+// Public resolvers queried through the server, browsers cannot send DNS queries directly.
+static RESOLVERS: &[(&str, &str)] = &[
+    ("Cloudflare", "1.1.1.1"),
+    ("Google", "8.8.8.8"),
+    ("Quad9", "9.9.9.9"),
+    ("OpenDNS", "208.67.222.222"),
+];
+
+const MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PropagationStatus {
+    Idle,
+    Checking,
+    Found,
+    Mismatch,
+    Missing,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ResolverState {
+    status: PropagationStatus,
+    records: Vec<String>,
+    checked_at: Option<DateTime<Local>>,
+}
+
+#[component]
+pub fn AcmePropagationPanel(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let is_dns = create_memo(move |_| data.get().value_as_str("challenge") == Some("dns-01"));
+    let domains = create_memo(move |_| {
+        data.get()
+            .array_value("domains")
+            .map(|domain| domain.trim_start_matches("*.").to_string())
+            .filter(|domain| !domain.is_empty())
+            .collect::<Vec<_>>()
+    });
+    let interval = create_memo(move |_| {
+        data.get()
+            .value_as_str("polling-interval")
+            .and_then(Duration::parse_value)
+            .unwrap_or_default()
+            .max(MIN_INTERVAL)
+    });
+
+    let selected = create_rw_signal(String::new());
+    let expected = create_rw_signal(String::new());
+    let states = create_rw_signal(
+        RESOLVERS
+            .iter()
+            .map(|_| ResolverState::default())
+            .collect::<Vec<_>>(),
+    );
+    let unsupported = create_rw_signal(false);
+    let timer = store_value(None::<IntervalHandle>);
+    let is_running = create_rw_signal(false);
+
+    let record_name = create_memo(move |_| {
+        let domain = selected.get();
+        if domain.is_empty() {
+            domains
+                .get()
+                .first()
+                .map(|domain| format!("_acme-challenge.{domain}"))
+                .unwrap_or_default()
+        } else {
+            format!("_acme-challenge.{domain}")
+        }
+    });
+
+    let stop = move || {
+        if let Some(handle) = timer.get_value() {
+            handle.clear();
+        }
+        timer.set_value(None);
+        is_running.set(false);
+    };
+
+    let check = move || {
+        let name = record_name.get_untracked();
+        if name.is_empty() {
+            return;
+        }
+        let auth = auth.get_untracked();
+        let expected = expected
+            .get_untracked()
+            .trim()
+            .trim_matches('"')
+            .to_string();
+
+        for (idx, (_, resolver)) in RESOLVERS.iter().enumerate() {
+            let name = name.clone();
+            let auth = auth.clone();
+            let expected = expected.clone();
+            states.update(|states| states[idx].status = PropagationStatus::Checking);
+
+            spawn_local(async move {
+                let result = dns_lookup_with_resolver(&auth, &name, "TXT", Some(resolver)).await;
+                let (status, records) = match result {
+                    Ok(records) => {
+                        let records = records
+                            .into_iter()
+                            .map(|record| record.trim_matches('"').to_string())
+                            .collect::<Vec<_>>();
+                        let status = if records.is_empty() {
+                            PropagationStatus::Missing
+                        } else if expected.is_empty() || records.contains(&expected) {
+                            PropagationStatus::Found
+                        } else {
+                            PropagationStatus::Mismatch
+                        };
+                        (status, records)
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        stop();
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(http::Error::NotFound) => {
+                        stop();
+                        unsupported.set(true);
+                        return;
+                    }
+                    Err(http::Error::Network(details)) => {
+                        (PropagationStatus::Failed(details), vec![])
+                    }
+                    Err(_) => (
+                        PropagationStatus::Failed("Lookup failed".to_string()),
+                        vec![],
+                    ),
+                };
+
+                states.update(|states| {
+                    states[idx] = ResolverState {
+                        status,
+                        records,
+                        checked_at: Some(Local::now()),
+                    };
+                });
+
+                // Polling ends once every resolver returns the expected record
+                if is_running.get_untracked()
+                    && states.with_untracked(|states| {
+                        states.iter().all(|s| s.status == PropagationStatus::Found)
+                    })
+                {
+                    stop();
+                }
+            });
+        }
+    };
+
+    let start = move || {
+        stop();
+        unsupported.set(false);
+        check();
+        timer.set_value(set_interval_with_handle(check, interval.get_untracked()).ok());
+        is_running.set(true);
+    };
+
+    on_cleanup(stop);
+
+    view! {
+        <Show when=move || is_dns.get()>
+            <FormSection title="DNS Propagation".to_string()>
+                <FormItem
+                    label="Record"
+                    tooltip="The TXT record published for the DNS-01 challenge"
+                >
+                    <div class="flex flex-col gap-y-2">
+                        <select
+                            class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            on:change=move |ev| {
+                                stop();
+                                selected.set(event_target_value(&ev));
+                            }
+                        >
+
+                            <For
+                                each=move || domains.get()
+                                key=|domain| domain.clone()
+                                children=move |domain| {
+                                    let domain_ = domain.clone();
+                                    view! {
+                                        <option
+                                            value=domain.clone()
+                                            selected=move || selected.get() == domain_
+                                        >
+                                            {format!("_acme-challenge.{domain}")}
+                                        </option>
+                                    }
+                                }
+                            />
+
+                        </select>
+                        <input
+                            type="text"
+                            class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            placeholder="Expected TXT value (optional)"
+                            prop:value=expected
+                            on:change=move |ev| expected.set(event_target_value(&ev))
+                        />
+                    </div>
+                </FormItem>
+                <FormItem label="Resolvers">
+                    <div class="flex flex-col gap-y-3">
+                        <Show
+                            when=move || !unsupported.get()
+                            fallback=|| {
+                                view! {
+                                    <p class="text-sm text-gray-500 dark:text-gray-400">
+                                        This server does not support DNS lookups.
+                                    </p>
+                                }
+                            }
+                        >
+
+                            <ul class="divide-y divide-gray-200 dark:divide-gray-700">
+                                {RESOLVERS
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(idx, (name, address))| {
+                                        let state = create_memo(move |_| {
+                                            states.with(|states| states[idx].clone())
+                                        });
+                                        view! {
+                                            <li class="py-2 flex flex-wrap items-center justify-between gap-2">
+                                                <div>
+                                                    <p class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                                                        {*name}
+                                                        <span class="ms-1 text-xs font-normal text-gray-500">
+                                                            {*address}
+                                                        </span>
+                                                    </p>
+                                                    <p class="text-xs font-mono text-gray-500 dark:text-gray-400 break-all">
+                                                        {move || state.get().records.join(", ")}
+                                                    </p>
+                                                </div>
+                                                <div class="flex items-center gap-x-2">
+                                                    <span class="text-xs text-gray-500 dark:text-gray-400">
+                                                        {move || {
+                                                            state
+                                                                .get()
+                                                                .checked_at
+                                                                .map(|time| time.format("%H:%M:%S").to_string())
+                                                                .unwrap_or_default()
+                                                        }}
+
+                                                    </span>
+                                                    {move || {
+                                                        let (color, label) = state.get().status.badge();
+                                                        view! { <Badge color=color>{label}</Badge> }
+                                                    }}
+
+                                                </div>
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </ul>
+                        </Show>
+                        <div class="flex items-center gap-x-3">
+                            <Button
+                                text=Signal::derive(move || {
+                                    if is_running.get() { "Stop" } else { "Check propagation" }
+                                        .to_string()
+                                })
+
+                                color=Color::Gray
+                                on_click=Callback::new(move |_| {
+                                    if is_running.get_untracked() {
+                                        stop();
+                                    } else {
+                                        start();
+                                    }
+                                })
+
+                                disabled=Signal::derive(move || record_name.get().is_empty())
+                            />
+                            <p class="text-xs text-gray-500 dark:text-gray-400">
+                                {move || {
+                                    if is_running.get() {
+                                        format!(
+                                            "Checking every {} seconds until all resolvers agree.",
+                                            interval.get().as_secs(),
+                                        )
+                                    } else if states
+                                        .with(|states| {
+                                            states.iter().all(|s| s.status == PropagationStatus::Found)
+                                        })
+                                    {
+                                        "The record is visible on all resolvers, it is safe to proceed."
+                                            .to_string()
+                                    } else {
+                                        String::new()
+                                    }
+                                }}
+
+                            </p>
+                        </div>
+                    </div>
+                </FormItem>
+            </FormSection>
+        </Show>
+    }
+}
+
+impl PropagationStatus {
+    fn badge(&self) -> (Color, String) {
+        match self {
+            PropagationStatus::Idle => (Color::Gray, "Not checked".to_string()),
+            PropagationStatus::Checking => (Color::Blue, "Checking".to_string()),
+            PropagationStatus::Found => (Color::Green, "Propagated".to_string()),
+            PropagationStatus::Mismatch => (Color::Yellow, "Different value".to_string()),
+            PropagationStatus::Missing => (Color::Yellow, "Not found".to_string()),
+            PropagationStatus::Failed(err) => (Color::Red, err.clone()),
+        }
+    }
+}
+
+impl Default for ResolverState {
+    fn default() -> Self {
+        ResolverState {
+            status: PropagationStatus::Idle,
+            records: vec![],
+            checked_at: None,
+        }
+    }
+}
@@ -0,0 +1,726 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{net::SocketAddr, sync::Arc};
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, renderer::use_field_renderers, Form, FormButtonBar, FormItem,
+            FormSection,
+        },
+        icon::{IconExclamationTriangle, IconRefresh},
+        messages::alert::{use_alerts, Alert, Alerts},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        form::{Expression, ExpressionIfThen, FormData, FormValue},
+        format::format_count,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::Schemas,
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            edit::{fetch_schema_settings, render_field, FetchResult},
+            Settings, SettingsValues,
+        },
+        List,
+    },
+};
+
+const SCHEMA_ID: &str = "smtp-in-auth";
+const FIELD_ID: &str = "session.auth.mechanisms";
+
+// Constant, label and whether credentials are sent in clear text
+static MECHANISMS: &[(&str, &str, bool)] = &[
+    ("plain", "PLAIN", true),
+    ("login", "LOGIN", true),
+    ("oauthbearer", "OAUTHBEARER", false),
+    ("xoauth2", "XOAUTH2", false),
+    ("scram_sha_1", "SCRAM-SHA-1", false),
+    ("scram_sha_256", "SCRAM-SHA-256", false),
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct ListenerPolicy {
+    id: String,
+    implicit_tls: bool,
+    ports: Vec<u16>,
+    mechanisms: Vec<&'static str>,
+    // Offer plaintext mechanisms only once the session is encrypted
+    require_tls: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MechanismUsage {
+    listener: String,
+    mechanism: String,
+    count: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AuthPolicy {
+    data: FormData,
+    listeners: Vec<ListenerPolicy>,
+    usage: Vec<MechanismUsage>,
+    // Expressions that cannot be represented per listener
+    is_custom: bool,
+}
+
+#[component]
+pub fn SmtpAuthMechanisms() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let schema = store_value(schemas.with_value(|schemas| schemas.get(SCHEMA_ID)));
+    let renderers = store_value(use_field_renderers());
+    let (pending, set_pending) = create_signal(false);
+    let cluster_results = create_rw_signal(None);
+    let data = create_rw_signal(FormData::default());
+    let listeners = create_rw_signal(Vec::<ListenerPolicy>::new());
+    let is_custom = create_rw_signal(false);
+
+    let fetch_policy = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let schema = schema.get_value();
+
+            async move {
+                let data = match fetch_schema_settings(&auth, schema.clone(), String::new()).await?
+                {
+                    FetchResult::Update {
+                        settings,
+                        external_sources,
+                    } => FormData::from_settings(schema, Some(settings))
+                        .with_external_sources(external_sources),
+                    FetchResult::Create { external_sources } => {
+                        FormData::from_settings(schema, None)
+                            .with_external_sources(external_sources)
+                    }
+                    FetchResult::NotFound => FormData::from_settings(schema, None),
+                };
+                let items = HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "server.listener")
+                    .with_parameter("suffix", "protocol")
+                    .send::<List<Settings>>()
+                    .await?
+                    .items;
+                // Usage statistics are optional, older servers do not collect them
+                let usage = match HttpRequest::get("/api/smtp/auth/usage")
+                    .with_authorization(&auth)
+                    .send::<Vec<MechanismUsage>>()
+                    .await
+                {
+                    Ok(usage) => usage,
+                    Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+                    Err(_) => vec![],
+                };
+
+                let mut listeners = items
+                    .into_iter()
+                    .filter(|item| item.get("protocol").map(|p| p.as_str()) == Some("smtp"))
+                    .filter_map(|mut item| {
+                        let ports = item
+                            .array_values("bind")
+                            .into_iter()
+                            .filter_map(|(_, bind)| bind.trim().parse::<SocketAddr>().ok())
+                            .map(|bind| bind.port())
+                            .collect();
+                        Some(ListenerPolicy {
+                            id: item.remove("_id")?,
+                            implicit_tls: item.get("tls.implicit").map(|v| v.as_str())
+                                == Some("true"),
+                            ports,
+                            mechanisms: vec![],
+                            require_tls: false,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                listeners.sort_by(|a, b| a.id.cmp(&b.id));
+                let is_custom = match data.values.get(FIELD_ID) {
+                    Some(FormValue::Expression(expr)) => !apply_expression(expr, &mut listeners),
+                    Some(FormValue::Value(value)) => !apply_expression(
+                        &Expression {
+                            if_thens: vec![],
+                            else_: value.clone(),
+                        },
+                        &mut listeners,
+                    ),
+                    _ => false,
+                };
+
+                Ok(AuthPolicy {
+                    data,
+                    listeners,
+                    usage,
+                    is_custom,
+                })
+            }
+        },
+    );
+
+    let save_changes = create_action(move |reload: &bool| {
+        let reload = *reload;
+        let auth = auth.get();
+        let schemas = schemas.get_value();
+        // Expressions that cannot be shown per listener are saved as edited
+        let changes = data
+            .try_update(|data| {
+                if is_custom.get_untracked() {
+                    data.validate_form()
+                        .then(|| data.build_fields_update(&[FIELD_ID]))
+                } else {
+                    let mut data = data.clone();
+                    data.values.insert(
+                        FIELD_ID.to_string(),
+                        FormValue::Expression(build_expression(&listeners.get_untracked())),
+                    );
+                    Some(data.build_fields_update(&[FIELD_ID]))
+                }
+            })
+            .flatten();
+
+        async move {
+            let Some(changes) = changes else {
+                return;
+            };
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/settings")
+                .with_authorization(&auth)
+                .with_body(changes)
+                .unwrap()
+                .send::<Option<String>>()
+                .await
+                .map(|_| ());
+            let result = match result {
+                Ok(_) if reload => reload_settings(&auth, "").await.map(Some),
+                Ok(_) => Ok(None),
+                Err(err) => Err(err),
+            };
+            set_pending.set(false);
+
+            match result {
+                Ok(Some(ReloadOutcome::Local(result))) if !result.errors.is_empty() => {
+                    alert.set(Alert::from_reload(result, &schemas));
+                }
+                Ok(Some(outcome)) => {
+                    if let ReloadOutcome::Cluster(results) = outcome {
+                        cluster_results.set(Some(results));
+                    }
+                    alert.set(Alert::success(
+                        "AUTH mechanisms saved and configuration reloaded.",
+                    ));
+                    fetch_policy.refetch();
+                }
+                Ok(None) => {
+                    alert.set(Alert::success(
+                        "AUTH mechanisms saved, reload the configuration to apply them.",
+                    ));
+                    fetch_policy.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title="AUTH Mechanisms"
+            subtitle="SASL mechanisms offered to clients on each SMTP listener"
+        >
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            <Alerts/>
+            <Transition fallback=Skeleton set_pending>
+
+                {move || match fetch_policy.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok(policy)) if policy.listeners.is_empty() => {
+                        Some(
+                            view! {
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    There are no SMTP listeners configured.
+                                </p>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(policy)) if policy.is_custom => {
+                        data.set(policy.data);
+                        is_custom.set(true);
+                        let schema = schema.get_value();
+                        let field = schema.fields.get(FIELD_ID).cloned().unwrap();
+                        let label = field.label_form_text();
+                        let help = field.help_text().unwrap_or_default();
+                        let component = renderers
+                            .with_value(|renderers| {
+                                render_field(&schema, field, data, false, renderers)
+                            });
+                        Some(
+                            view! {
+                                <div class="p-4 text-sm text-yellow-800 rounded-lg bg-yellow-50 dark:bg-gray-800 dark:text-yellow-300">
+                                    The current setting uses an expression that cannot be edited per listener, it can be changed below instead.
+                                </div>
+                                <FormSection title="Expression">
+                                    <FormItem label tooltip=help>
+                                        {component}
+                                    </FormItem>
+                                </FormSection>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(policy)) => {
+                        data.set(policy.data);
+                        is_custom.set(false);
+                        listeners.set(policy.listeners.clone());
+                        let usage = store_value(policy.usage);
+                        let sections = policy
+                            .listeners
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, listener)| {
+                                view! {
+                                    <ListenerMechanisms
+                                        idx
+                                        listener
+                                        listeners
+                                        usage=usage.get_value()
+                                    />
+                                }
+                            })
+                            .collect_view();
+                        Some(sections.into_view())
+                    }
+                }}
+
+            </Transition>
+
+            <FormButtonBar>
+                <Button
+                    text="Save & Reload"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        save_changes.dispatch(true);
+                    })
+
+                    disabled=pending
+                >
+
+                    <IconRefresh/>
+                </Button>
+
+                <Button
+                    text="Save changes"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        save_changes.dispatch(false);
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+fn ListenerMechanisms(
+    idx: usize,
+    listener: ListenerPolicy,
+    listeners: RwSignal<Vec<ListenerPolicy>>,
+    usage: Vec<MechanismUsage>,
+) -> impl IntoView {
+    let policy = create_memo(move |_| {
+        listeners.with(|listeners| listeners.get(idx).cloned().unwrap_or_default())
+    });
+    let update = move |f: &dyn Fn(&mut ListenerPolicy)| {
+        listeners.update(|listeners| {
+            if let Some(listener) = listeners.get_mut(idx) {
+                f(listener);
+            }
+        });
+    };
+    let has_plaintext = create_memo(move |_| {
+        policy.with(|policy| {
+            policy
+                .mechanisms
+                .iter()
+                .any(|mechanism| is_plaintext(mechanism))
+        })
+    });
+    let downgrade_warning = move || {
+        policy.with(|policy| {
+            (has_plaintext.get() && !policy.implicit_tls && !policy.require_tls).then_some(concat!(
+                "PLAIN and LOGIN send credentials in clear text. This listener does not use ",
+                "implicit TLS, offer them only after STARTTLS to prevent downgrade attacks.",
+            ))
+        })
+    };
+    let implicit_tls = listener.implicit_tls;
+    let mut used = usage
+        .into_iter()
+        .filter(|usage| usage.listener == listener.id)
+        .collect::<Vec<_>>();
+    used.sort_by_key(|usage| std::cmp::Reverse(usage.count));
+
+    view! {
+        <FormSection title=format!("Listener {}", listener.id)>
+            <FormItem label="Encryption">
+                {if implicit_tls {
+                    view! { <Badge color=Color::Green>Implicit TLS</Badge> }
+                } else {
+                    view! { <Badge color=Color::Gray>STARTTLS</Badge> }
+                }}
+
+            </FormItem>
+            <FormItem
+                label="Mechanisms"
+                tooltip="Mechanisms advertised in the EHLO response, leave empty to disable authentication on this listener"
+            >
+                <div class="grid sm:grid-cols-3 gap-2">
+                    {MECHANISMS
+                        .iter()
+                        .map(|(id, label, _)| {
+                            let id = *id;
+                            view! {
+                                <label class="flex p-3 w-full bg-white border border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400">
+                                    <input
+                                        type="checkbox"
+                                        class="shrink-0 mt-0.5 border-gray-200 rounded text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                        prop:checked=move || {
+                                            policy.with(|policy| policy.mechanisms.contains(&id))
+                                        }
+
+                                        on:input=move |_| {
+                                            update(
+                                                &|policy| {
+                                                    if policy.mechanisms.contains(&id) {
+                                                        policy.mechanisms.retain(|m| *m != id);
+                                                    } else {
+                                                        policy.mechanisms.push(id);
+                                                    }
+                                                },
+                                            );
+                                        }
+                                    />
+
+                                    <span class="text-sm text-gray-500 ms-3 dark:text-gray-400">
+                                        {*label}
+                                    </span>
+                                </label>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+            </FormItem>
+            <Show when=move || has_plaintext.get() && !implicit_tls>
+                <FormItem label="Plaintext mechanisms">
+                    <label class="flex items-center text-sm text-gray-600 dark:text-gray-400">
+                        <input
+                            type="checkbox"
+                            class="shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                            prop:checked=move || policy.with(|policy| policy.require_tls)
+                            on:input=move |_| {
+                                update(&|policy| policy.require_tls = !policy.require_tls);
+                            }
+                        />
+
+                        <span class="ms-3">Only offer PLAIN and LOGIN after STARTTLS</span>
+                    </label>
+                </FormItem>
+            </Show>
+            {move || {
+                downgrade_warning()
+                    .map(|warning| {
+                        view! {
+                            <p class="sm:col-span-12 inline-flex items-start gap-x-2 text-sm text-yellow-600 dark:text-yellow-500">
+                                <IconExclamationTriangle attr:class="flex-shrink-0 size-4 mt-0.5"/>
+                                {warning}
+                            </p>
+                        }
+                    })
+            }}
+
+            <FormItem
+                label="Used by clients"
+                tooltip="Successful authentications per mechanism as reported by the server"
+            >
+                {if used.is_empty() {
+                    view! {
+                        <span class="text-sm text-gray-500 dark:text-gray-400">
+                            No authentications recorded on this listener.
+                        </span>
+                    }
+                        .into_view()
+                } else {
+                    view! {
+                        <div class="flex flex-wrap gap-2">
+                            {used
+                                .into_iter()
+                                .map(|usage| {
+                                    let mechanism = usage.mechanism.to_ascii_lowercase();
+                                    let label = MECHANISMS
+                                        .iter()
+                                        .find(|(id, _, _)| id.replace('_', "-") == mechanism)
+                                        .map_or(usage.mechanism.to_ascii_uppercase(), |(_, label, _)| {
+                                            label.to_string()
+                                        });
+                                    let color = if is_plaintext(&mechanism) {
+                                        Color::Yellow
+                                    } else {
+                                        Color::Gray
+                                    };
+                                    view! {
+                                        <Badge color>
+                                            {format!("{label}: {}", format_count(usage.count))}
+                                        </Badge>
+                                    }
+                                })
+                                .collect_view()}
+                        </div>
+                    }
+                        .into_view()
+                }}
+
+            </FormItem>
+        </FormSection>
+    }
+}
+
+fn is_plaintext(mechanism: &str) -> bool {
+    MECHANISMS
+        .iter()
+        .any(|(id, _, plaintext)| *plaintext && *id == mechanism)
+}
+
+fn is_disabled(value: &str) -> bool {
+    matches!(value.trim(), "false" | "[]" | "")
+}
+
+// Expressions are evaluated for every port of a listener, with and without
+// TLS. They can be edited here when the result is a single set of mechanisms
+// per listener, optionally offering PLAIN and LOGIN only once the session is
+// encrypted. Conditions may combine `listener`, `local_port` and `is_tls`
+// comparisons with `&&`, anything else is reported as a custom expression.
+fn apply_expression(expr: &Expression, listeners: &mut [ListenerPolicy]) -> bool {
+    let mut rules = Vec::with_capacity(expr.if_thens.len());
+    for if_then in &expr.if_thens {
+        let (Some(condition), Some(mechanisms)) = (
+            parse_condition(&if_then.if_),
+            parse_mechanisms(&if_then.then_),
+        ) else {
+            return false;
+        };
+        rules.push((condition, mechanisms));
+    }
+    let Some(default) = parse_mechanisms(&expr.else_) else {
+        return false;
+    };
+
+    for listener in listeners {
+        let evaluate = |is_tls: bool| {
+            let mut result: Option<Vec<&'static str>> = None;
+            let ports = if !listener.ports.is_empty() {
+                listener.ports.iter().copied().map(Some).collect::<Vec<_>>()
+            } else {
+                vec![None]
+            };
+            for port in ports {
+                let mut mechanisms = default.clone();
+                for (condition, then) in &rules {
+                    if condition
+                        .iter()
+                        .map(|term| term.matches(listener, port, is_tls))
+                        .collect::<Option<Vec<_>>>()?
+                        .into_iter()
+                        .all(|matches| matches)
+                    {
+                        mechanisms = then.clone();
+                        break;
+                    }
+                }
+                mechanisms.sort_unstable();
+                mechanisms.dedup();
+                if result.as_ref().is_some_and(|result| *result != mechanisms) {
+                    return None;
+                }
+                result = Some(mechanisms);
+            }
+            result
+        };
+
+        let Some(encrypted) = evaluate(true) else {
+            return false;
+        };
+        if !listener.implicit_tls {
+            let Some(unencrypted) = evaluate(false) else {
+                return false;
+            };
+            if unencrypted != encrypted {
+                let without_plaintext = encrypted
+                    .iter()
+                    .copied()
+                    .filter(|id| !is_plaintext(id))
+                    .collect::<Vec<_>>();
+                if unencrypted != without_plaintext {
+                    return false;
+                }
+                listener.require_tls = true;
+            }
+        }
+        listener.mechanisms = encrypted;
+    }
+
+    true
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConditionTerm {
+    Listener { id: String, is_eq: bool },
+    LocalPort { port: u16, is_eq: bool },
+    IsTls { is_tls: bool },
+}
+
+impl ConditionTerm {
+    // Returns None when the term cannot be evaluated for the listener
+    fn matches(&self, listener: &ListenerPolicy, port: Option<u16>, is_tls: bool) -> Option<bool> {
+        match self {
+            ConditionTerm::Listener { id, is_eq } => Some((listener.id == *id) == *is_eq),
+            ConditionTerm::LocalPort { port: value, is_eq } => {
+                port.map(|port| (port == *value) == *is_eq)
+            }
+            ConditionTerm::IsTls { is_tls: value } => Some(is_tls == *value),
+        }
+    }
+}
+
+fn parse_condition(condition: &str) -> Option<Vec<ConditionTerm>> {
+    condition
+        .split("&&")
+        .map(|term| {
+            let term = term.trim();
+            match term {
+                "is_tls" => return Some(ConditionTerm::IsTls { is_tls: true }),
+                "!is_tls" => return Some(ConditionTerm::IsTls { is_tls: false }),
+                _ => {}
+            }
+            let (variable, value, is_eq) = if let Some((variable, value)) = term.split_once("==") {
+                (variable.trim(), value.trim(), true)
+            } else {
+                let (variable, value) = term.split_once("!=")?;
+                (variable.trim(), value.trim(), false)
+            };
+            match variable {
+                "listener" => value
+                    .strip_prefix('\'')
+                    .and_then(|id| id.strip_suffix('\''))
+                    .map(|id| ConditionTerm::Listener {
+                        id: id.to_string(),
+                        is_eq,
+                    }),
+                "local_port" => value
+                    .parse()
+                    .ok()
+                    .map(|port| ConditionTerm::LocalPort { port, is_eq }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn parse_mechanisms(value: &str) -> Option<Vec<&'static str>> {
+    let value = value.trim();
+    if is_disabled(value) {
+        return Some(vec![]);
+    }
+    value
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(',')
+        .map(|item| {
+            let item = item.trim();
+            MECHANISMS
+                .iter()
+                .find(|(id, _, _)| *id == item)
+                .map(|(id, _, _)| *id)
+        })
+        .collect()
+}
+
+fn build_expression(listeners: &[ListenerPolicy]) -> Expression {
+    let format_list = |mechanisms: &[&str]| format!("[{}]", mechanisms.join(", "));
+    let mut if_thens = Vec::new();
+
+    for listener in listeners {
+        // Keep the order in which mechanisms are listed on the page
+        let mechanisms = MECHANISMS
+            .iter()
+            .filter(|(id, _, _)| listener.mechanisms.contains(id))
+            .map(|(id, _, _)| *id)
+            .collect::<Vec<_>>();
+        if mechanisms.is_empty() {
+            continue;
+        }
+
+        let condition = format!("listener == '{}'", listener.id);
+        let encrypted_only = listener.require_tls && !listener.implicit_tls;
+        if encrypted_only && mechanisms.iter().any(|id| is_plaintext(id)) {
+            if_thens.push(ExpressionIfThen {
+                if_: format!("{condition} && is_tls"),
+                then_: format_list(&mechanisms),
+            });
+            let unencrypted = mechanisms
+                .iter()
+                .copied()
+                .filter(|id| !is_plaintext(id))
+                .collect::<Vec<_>>();
+            if !unencrypted.is_empty() {
+                if_thens.push(ExpressionIfThen {
+                    if_: condition,
+                    then_: format_list(&unencrypted),
+                });
+            }
+        } else {
+            if_thens.push(ExpressionIfThen {
+                if_: condition,
+                then_: format_list(&mechanisms),
+            });
+        }
+    }
+
+    Expression {
+        if_thens,
+        else_: "false".to_string(),
+    }
+}
@@ -0,0 +1,329 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        form::{button::Button, renderer::use_field_renderers},
+        list::ItemSelection,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::SelectType,
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, NodeApplyResult, ReloadOutcome},
+            edit::render_field,
+            snapshot::create_snapshot,
+            Field, Schema, SchemaType, Schemas, Settings, Type, UpdateSettings,
+        },
+        maybe_plural, List,
+    },
+};
+
+// Applies the value of a single field to every selected entry of a list of
+// records in a single update and reloads the configuration. Entries where the
+// field is not displayed, for example because it belongs to another type, are
+// left unchanged.
+#[component]
+pub fn BulkEditPanel(
+    schema: Arc<Schema>,
+    selection: ItemSelection,
+    #[prop(into)] filter: Option<String>,
+    total: u32,
+    cluster_results: RwSignal<Option<Vec<NodeApplyResult>>>,
+    #[prop(into)] on_close: Callback<bool>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let renderers = store_value(use_field_renderers());
+    let fields = schema
+        .form
+        .sections
+        .iter()
+        .flat_map(|section| section.fields.iter())
+        .filter(|field| is_bulk_editable(field))
+        .fold(Vec::<Arc<Field>>::new(), |mut fields, field| {
+            if !fields.iter().any(|f| f.id == field.id) {
+                fields.push(field.clone());
+            }
+            fields
+        });
+    let field = create_rw_signal(fields.first().cloned());
+    let data = FormData::from_settings(schema.clone(), None).into_signal();
+    let selection = store_value(selection);
+    let schema = store_value(schema);
+    let fields = store_value(fields);
+    let total_selected = selection.with_value(|selection| selection.total_selected(Some(total)));
+
+    let apply_action = create_action(move |(field_id, value): &(&'static str, String)| {
+        let field_id = *field_id;
+        let value = value.clone();
+        let auth = auth.get();
+        let schema = schema.get_value();
+        let schemas = schemas.get_value();
+        let selection = selection.get_value();
+        let filter = filter.clone();
+
+        async move {
+            let SchemaType::Record { prefix, .. } = schema.typ else {
+                return;
+            };
+            let entries = match HttpRequest::get("/api/settings/group")
+                .with_authorization(&auth)
+                .with_parameter("page", "1")
+                .with_parameter("limit", total.to_string())
+                .with_parameter("prefix", schema.unwrap_prefix())
+                .with_parameter("suffix", schema.try_unwrap_suffix().unwrap_or_default())
+                .with_optional_parameter("filter", filter)
+                .send::<List<Settings>>()
+                .await
+            {
+                Ok(list) => list.items,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+
+            let mut updates = Vec::new();
+            let mut skipped = Vec::new();
+            for mut entry in entries {
+                let Some(id) = entry.remove("_id") else {
+                    continue;
+                };
+                if !selection.is_selected(&id) {
+                    continue;
+                }
+                let entry = FormData::from_settings(schema.clone(), Some(entry));
+                if !schema.form.sections.iter().any(|section| {
+                    section.display(&entry)
+                        && section
+                            .fields
+                            .iter()
+                            .any(|field| field.id == field_id && field.display(&entry))
+                }) {
+                    skipped.push(id);
+                } else if !value.is_empty() {
+                    updates.push(UpdateSettings::Insert {
+                        prefix: Some(format!("{prefix}.{id}")),
+                        values: vec![(field_id.to_string(), value.clone())],
+                        assert_empty: false,
+                    });
+                } else {
+                    updates.push(UpdateSettings::Delete {
+                        keys: vec![format!("{prefix}.{id}.{field_id}")],
+                    });
+                }
+            }
+
+            if updates.is_empty() {
+                alert.set(
+                    Alert::warning("The field does not apply to any of the selected entries.")
+                        .with_details_list(skipped),
+                );
+                return;
+            }
+
+            if let Err(err) = create_snapshot(
+                &auth,
+                &schemas,
+                format!(
+                    "Before bulk editing {}",
+                    maybe_plural(updates.len(), schema.name_singular, schema.name_plural)
+                ),
+            )
+            .await
+            {
+                alert.set(Alert::from(err));
+                return;
+            }
+
+            let num_updated = updates.len();
+            match HttpRequest::post("/api/settings")
+                .with_authorization(&auth)
+                .with_body(updates)
+                .unwrap()
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(_) => (),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            }
+
+            // The entries are saved at this point, a failed reload is reported on its own
+            match reload_settings(&auth, schema.reload_prefix.unwrap_or_default()).await {
+                Ok(ReloadOutcome::Local(result)) if !result.errors.is_empty() => {
+                    alert.set(Alert::from_reload(result, &schemas));
+                }
+                Ok(outcome) => {
+                    if let ReloadOutcome::Cluster(results) = outcome {
+                        cluster_results.set(Some(results));
+                    }
+                    let message = format!(
+                        "Updated {} and reloaded the configuration.",
+                        maybe_plural(num_updated, schema.name_singular, schema.name_plural)
+                    );
+                    alert.set(if skipped.is_empty() {
+                        Alert::success(message)
+                    } else {
+                        Alert::success(format!(
+                            "{message} The field does not apply to {}:",
+                            maybe_plural(skipped.len(), schema.name_singular, schema.name_plural)
+                        ))
+                        .with_details_list(skipped)
+                    });
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+            on_close.call(true);
+        }
+    });
+
+    let apply = move |_| {
+        let Some(field) = field.get_untracked() else {
+            return;
+        };
+        let value = data.try_update(|data| {
+            let value = data.value::<String>(field.id).unwrap_or_default();
+            match field
+                .input_check(data)
+                .map(|check| check.check_value(value.clone()))
+            {
+                Some(Ok(value)) => Some(value),
+                Some(Err(err)) => {
+                    data.new_error(field.id, err);
+                    None
+                }
+                None => Some(value),
+            }
+        });
+        if let Some(Some(value)) = value {
+            apply_action.dispatch((field.id, value));
+        }
+    };
+
+    view! {
+        <div class="mb-4 p-4 sm:p-6 bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-slate-900 dark:border-gray-700">
+            <h3 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                {format!(
+                    "Bulk edit {}",
+                    schema
+                        .with_value(|schema| {
+                            maybe_plural(total_selected, schema.name_singular, schema.name_plural)
+                        }),
+                )}
+
+            </h3>
+            <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                The value is written to every selected entry, leave it empty to remove the setting.
+            </p>
+            <div class="mt-4 grid sm:grid-cols-12 gap-4">
+                <div class="sm:col-span-4">
+                    <select
+                        class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            let id = event_target_value(&ev);
+                            field
+                                .set(
+                                    fields
+                                        .with_value(|fields| {
+                                            fields.iter().find(|f| f.id == id).cloned()
+                                        }),
+                                );
+                        }
+                    >
+
+                        {fields
+                            .get_value()
+                            .into_iter()
+                            .map(|f| {
+                                let label = f.label_form_text();
+                                view! { <option value=f.id>{label}</option> }
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+                <div class="sm:col-span-8">
+                    {move || {
+                        field
+                            .get()
+                            .map(|field| {
+                                schema
+                                    .with_value(|schema| {
+                                        renderers
+                                            .with_value(|renderers| {
+                                                render_field(schema, field, data, false, renderers)
+                                            })
+                                    })
+                            })
+                    }}
+
+                </div>
+            </div>
+            <div class="mt-4 flex justify-end gap-x-2">
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| on_close.call(false)
+                />
+                <Button
+                    text="Apply"
+                    color=Color::Blue
+                    on_click=apply
+                    disabled=Signal::derive(move || {
+                        apply_action.pending().get() || field.with(|field| field.is_none())
+                    })
+                />
+            </div>
+        </div>
+    }
+}
+
+fn is_bulk_editable(field: &Field) -> bool {
+    field.id != "_id"
+        && matches!(
+            field.typ_,
+            Type::Input
+                | Type::Text
+                | Type::Boolean
+                | Type::Duration
+                | Type::Rate
+                | Type::Size
+                | Type::Cron
+                | Type::Select {
+                    typ: SelectType::Single,
+                    ..
+                }
+        )
+}
@@ -0,0 +1,349 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::{IconRefresh, IconTrash},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::FormatDateTime,
+};
+
+// Revocation reasons accepted by ACME servers (RFC 5280, section 5.3.1)
+static REVOCATION_REASONS: &[(u8, &str)] = &[
+    (0, "Unspecified"),
+    (1, "Key compromise"),
+    (3, "Affiliation changed"),
+    (4, "Superseded"),
+    (5, "Cessation of operation"),
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateStatus {
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub not_after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub ocsp: Option<OcspStatus>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OcspStatus {
+    pub status: OcspCertStatus,
+    #[serde(default)]
+    pub responder: Option<String>,
+    #[serde(default)]
+    pub produced_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub next_update: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub reason: Option<u8>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OcspCertStatus {
+    Good,
+    Revoked,
+    #[default]
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RevokeRequest {
+    reason: u8,
+}
+
+#[component]
+pub fn CertificateStatusPanel(
+    #[prop(into)] source: MaybeSignal<&'static str>,
+    #[prop(into)] id: Signal<String>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let reason = create_rw_signal(0u8);
+
+    let status = create_resource(
+        move || (source.get(), id.get()),
+        move |(source, id)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if id.is_empty() {
+                    return Ok(None);
+                }
+                HttpRequest::get("/api/certificate/status")
+                    .with_authorization(&auth)
+                    .with_parameter("source", source)
+                    .with_parameter("id", id)
+                    .try_send::<CertificateStatus>()
+                    .await
+            }
+        },
+    );
+
+    let revoke_action = create_action(move |request: &(String, RevokeRequest)| {
+        let (id, request) = request.clone();
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::post(("/api/acme/revoke", id.as_str()))
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<()>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "The certificate issued for {id} has been revoked, it will be replaced on the next renewal."
+                    )));
+                    status.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Show when=move || !id.get().is_empty()>
+            <FormSection title="Certificate Status".to_string()>
+                {move || match status.get() {
+                    None => {
+                        view! {
+                            <FormItem label="Status">
+                                <span class="text-sm text-gray-500 dark:text-gray-400">
+                                    Loading...
+                                </span>
+                            </FormItem>
+                        }
+                            .into_view()
+                    }
+                    Some(Ok(None)) => {
+                        view! {
+                            <FormItem label="Status">
+                                <span class="text-sm text-gray-500 dark:text-gray-400">
+                                    No certificate has been issued yet.
+                                </span>
+                            </FormItem>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        ().into_view()
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        ().into_view()
+                    }
+                    Some(Ok(Some(cert))) => {
+                        let is_revoked = cert
+                            .ocsp
+                            .as_ref()
+                            .is_some_and(|ocsp| ocsp.status == OcspCertStatus::Revoked);
+                        let can_revoke = source.get() == "acme" && !is_revoked;
+                        let (color, label) = cert.badge();
+                        let ocsp = cert.ocsp.clone().unwrap_or_default();
+                        view! {
+                            <FormItem label="Subjects">
+                                <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                                    <span class="text-sm text-gray-800 dark:text-gray-200">
+                                        {cert.subjects.join(", ")}
+                                    </span>
+                                    <Badge color=color>{label}</Badge>
+                                </div>
+                            </FormItem>
+                            <FormItem label="Issuer">
+                                <span class="text-sm text-gray-600 dark:text-gray-400">
+                                    {cert.issuer.clone().unwrap_or_default()}
+                                </span>
+                            </FormItem>
+                            <FormItem label="Serial number">
+                                <span class="text-xs font-mono break-all text-gray-600 dark:text-gray-400">
+                                    {cert.serial_number.clone().unwrap_or_default()}
+                                </span>
+                            </FormItem>
+                            <FormItem label="Expires">
+                                <span class="text-sm text-gray-600 dark:text-gray-400">
+                                    {cert
+                                        .not_after
+                                        .map(|time| time.format_date_time())
+                                        .unwrap_or_default()}
+                                </span>
+                            </FormItem>
+                            <FormItem
+                                label="OCSP"
+                                tooltip="Revocation status reported by the issuer's OCSP responder"
+                            >
+                                <div class="flex flex-col gap-y-1 text-sm text-gray-600 dark:text-gray-400">
+                                    {ocsp.describe().into_iter().map(|line| view! { <p>{line}</p> }).collect_view()}
+                                </div>
+                            </FormItem>
+                            <FormItem label="">
+                                <div class="flex flex-wrap items-center gap-x-3 gap-y-2">
+                                    <Button
+                                        text="Check again"
+                                        color=Color::Gray
+                                        on_click=move |_| {
+                                            status.refetch();
+                                        }
+                                    >
+
+                                        <IconRefresh/>
+                                    </Button>
+                                    <Show when=move || can_revoke>
+                                        <select
+                                            class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                            on:change=move |ev| {
+                                                reason.set(event_target_value(&ev).parse().unwrap_or_default());
+                                            }
+                                        >
+
+                                            {REVOCATION_REASONS
+                                                .iter()
+                                                .map(|(code, name)| {
+                                                    view! {
+                                                        <option
+                                                            value=code.to_string()
+                                                            selected=move || reason.get() == *code
+                                                        >
+                                                            {*name}
+                                                        </option>
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </select>
+                                        <Button
+                                            text="Revoke"
+                                            color=Color::Red
+                                            on_click=move |_| {
+                                                let id = id.get_untracked();
+                                                let reason = reason.get_untracked();
+                                                modal
+                                                    .set(
+                                                        Modal::with_title("Confirm revocation")
+                                                            .with_message(
+                                                                format!(
+                                                                    "Are you sure you want to revoke the certificate issued for {id}? The CA will be notified and clients will stop trusting it. This action cannot be undone.",
+                                                                ),
+                                                            )
+                                                            .with_button("Revoke")
+                                                            .with_dangerous_callback(move || {
+                                                                revoke_action
+                                                                    .dispatch((id.clone(), RevokeRequest { reason }));
+                                                            }),
+                                                    );
+                                            }
+
+                                            disabled=Signal::derive(move || revoke_action.pending().get())
+                                        >
+
+                                            <IconTrash/>
+                                        </Button>
+                                    </Show>
+                                </div>
+                            </FormItem>
+                        }
+                            .into_view()
+                    }
+                }}
+
+            </FormSection>
+        </Show>
+    }
+}
+
+impl CertificateStatus {
+    fn badge(&self) -> (Color, String) {
+        match &self.ocsp {
+            Some(ocsp) if ocsp.status == OcspCertStatus::Revoked => {
+                (Color::Red, "Revoked".to_string())
+            }
+            _ if self
+                .not_after
+                .is_some_and(|not_after| not_after < Utc::now()) =>
+            {
+                (Color::Red, "Expired".to_string())
+            }
+            Some(ocsp) if ocsp.status == OcspCertStatus::Good => (Color::Green, "Good".to_string()),
+            _ => (Color::Gray, "Unknown".to_string()),
+        }
+    }
+}
+
+impl OcspStatus {
+    fn describe(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(error) = &self.error {
+            lines.push(format!("Status unavailable: {error}"));
+            return lines;
+        }
+        lines.push(match self.status {
+            OcspCertStatus::Good => "The responder reports this certificate as valid.".to_string(),
+            OcspCertStatus::Revoked => {
+                let reason = self
+                    .reason
+                    .and_then(|reason| {
+                        REVOCATION_REASONS
+                            .iter()
+                            .find_map(|(code, name)| (*code == reason).then_some(*name))
+                    })
+                    .unwrap_or("unspecified reason");
+                match self.revoked_at {
+                    Some(revoked_at) => {
+                        format!("Revoked on {} ({reason}).", revoked_at.format_date_time())
+                    }
+                    None => format!("Revoked ({reason})."),
+                }
+            }
+            OcspCertStatus::Unknown => "The responder does not know this certificate.".to_string(),
+        });
+        if let Some(produced_at) = self.produced_at {
+            lines.push(format!("Checked {}.", HumanTime::from(produced_at)));
+        }
+        if let Some(next_update) = self.next_update {
+            lines.push(format!("Next update {}.", HumanTime::from(next_update)));
+        }
+        if let Some(responder) = &self.responder {
+            lines.push(format!("Responder: {responder}"));
+        }
+        lines
+    }
+}
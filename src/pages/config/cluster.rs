@@ -0,0 +1,302 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::button::Button,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::maybe_plural,
+};
+
+use super::ReloadSettings;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ClusterNode {
+    pub id: String,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeApplyStatus {
+    Success,
+    Warnings(usize),
+    Error(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeApplyResult {
+    pub node: ClusterNode,
+    pub status: NodeApplyStatus,
+}
+
+// Standalone servers do not expose the node list, an empty list is returned
+// so that callers fall back to a local reload.
+pub async fn fetch_cluster_nodes(auth: &AccessToken) -> http::Result<Vec<ClusterNode>> {
+    match HttpRequest::get("/api/cluster/nodes")
+        .with_authorization(auth)
+        .send::<Vec<ClusterNode>>()
+        .await
+    {
+        Ok(nodes) => Ok(nodes),
+        Err(http::Error::NotFound) => Ok(vec![]),
+        Err(err) => Err(err),
+    }
+}
+
+// Reloads the settings on each node in turn, a node that fails does not
+// prevent the remaining ones from being reloaded.
+pub async fn reload_cluster(
+    auth: &AccessToken,
+    reload_prefix: &str,
+    nodes: Vec<ClusterNode>,
+) -> http::Result<Vec<NodeApplyResult>> {
+    let requests = nodes.iter().map(|node| {
+        (
+            node.id.clone(),
+            HttpRequest::get(format!("/api/reload/{reload_prefix}"))
+                .with_authorization(auth)
+                .with_parameter("node", node.id.clone()),
+        )
+    });
+    let results = HttpRequest::send_batch::<ReloadSettings>(requests).await?;
+
+    Ok(nodes
+        .into_iter()
+        .zip(results)
+        .map(|(node, (_, result))| NodeApplyResult {
+            node,
+            status: NodeApplyStatus::from(result),
+        })
+        .collect())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReloadOutcome {
+    Local(ReloadSettings),
+    Cluster(Vec<NodeApplyResult>),
+}
+
+// Reloads every node when the server is part of a cluster, otherwise only
+// the local server. Callers show cluster results with ClusterApplyResults.
+pub async fn reload_settings(
+    auth: &AccessToken,
+    reload_prefix: &str,
+) -> http::Result<ReloadOutcome> {
+    match fetch_cluster_nodes(auth).await {
+        Ok(nodes) if nodes.len() > 1 => {
+            return reload_cluster(auth, reload_prefix, nodes)
+                .await
+                .map(ReloadOutcome::Cluster);
+        }
+        Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+        _ => (),
+    }
+
+    HttpRequest::get(format!("/api/reload/{reload_prefix}"))
+        .with_authorization(auth)
+        .send::<ReloadSettings>()
+        .await
+        .map(ReloadOutcome::Local)
+}
+
+impl From<http::Result<ReloadSettings>> for NodeApplyStatus {
+    fn from(result: http::Result<ReloadSettings>) -> Self {
+        match result {
+            Ok(result) if !result.errors.is_empty() => NodeApplyStatus::Error(format!(
+                "{} in the configuration",
+                maybe_plural(result.errors.len(), "error", "errors")
+            )),
+            Ok(result) if !result.warnings.is_empty() => {
+                NodeApplyStatus::Warnings(result.warnings.len())
+            }
+            Ok(_) => NodeApplyStatus::Success,
+            Err(err) => NodeApplyStatus::Error(Alert::from(err).message),
+        }
+    }
+}
+
+#[component]
+pub fn ClusterApplyResults(
+    results: RwSignal<Option<Vec<NodeApplyResult>>>,
+    #[prop(into)] reload_prefix: MaybeSignal<String>,
+    #[prop(into)] on_close: Callback<()>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let retry = create_action(move |node: &ClusterNode| {
+        let node = node.clone();
+        let auth = auth.get();
+        let reload_prefix = reload_prefix.get();
+
+        async move {
+            let status = match HttpRequest::get(format!("/api/reload/{reload_prefix}"))
+                .with_authorization(&auth)
+                .with_parameter("node", node.id.clone())
+                .send::<ReloadSettings>()
+                .await
+            {
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                result => NodeApplyStatus::from(result),
+            };
+            if let NodeApplyStatus::Error(err) = &status {
+                alert.set(
+                    Alert::error(format!("Failed to reload node {}", node.id)).with_details(err),
+                );
+            }
+            results.update(|results| {
+                if let Some(result) = results
+                    .iter_mut()
+                    .flatten()
+                    .find(|result| result.node.id == node.id)
+                {
+                    result.status = status;
+                }
+            });
+        }
+    });
+    let pending = retry.pending();
+
+    view! {
+        {move || {
+            results
+                .get()
+                .map(|results| {
+                    let failed = results
+                        .iter()
+                        .filter(|result| matches!(result.status, NodeApplyStatus::Error(_)))
+                        .count();
+                    view! {
+                        <div class="mb-4 p-4 sm:p-6 bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-slate-900 dark:border-gray-700">
+                            <h3 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                                Cluster reload
+                            </h3>
+                            <p class="mt-1 text-sm text-gray-600 dark:text-gray-400">
+                                {if failed == 0 {
+                                    format!(
+                                        "The settings were applied on {}.",
+                                        maybe_plural(results.len(), "node", "nodes"),
+                                    )
+                                } else {
+                                    format!(
+                                        "The settings were saved but could not be applied on {}, they will run with the previous settings until reloaded.",
+                                        maybe_plural(failed, "node", "nodes"),
+                                    )
+                                }}
+
+                            </p>
+                            <table class="mt-4 min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                                <thead>
+                                    <tr class="text-xs font-medium uppercase text-gray-500">
+                                        <th class="py-2 pe-4 text-start">Node</th>
+                                        <th class="py-2 pe-4 text-start">Version</th>
+                                        <th class="py-2 pe-4 text-start">Result</th>
+                                        <th class="py-2"></th>
+                                    </tr>
+                                </thead>
+                                <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                    {results
+                                        .into_iter()
+                                        .map(|result| {
+                                            let node = result.node;
+                                            let (color, status, error) = match result.status {
+                                                NodeApplyStatus::Success => {
+                                                    (Color::Green, "Applied".to_string(), None)
+                                                }
+                                                NodeApplyStatus::Warnings(warnings) => {
+                                                    (
+                                                        Color::Yellow,
+                                                        format!(
+                                                            "Applied with {}",
+                                                            maybe_plural(warnings, "warning", "warnings"),
+                                                        ),
+                                                        None,
+                                                    )
+                                                }
+                                                NodeApplyStatus::Error(error) => {
+                                                    (Color::Red, "Failed".to_string(), Some(error))
+                                                }
+                                            };
+                                            let can_retry = error.is_some();
+                                            view! {
+                                                <tr class="align-top text-gray-800 dark:text-gray-200">
+                                                    <td class="py-2 pe-4">
+                                                        <span class="block font-medium">
+                                                            {node.id.clone()}
+                                                        </span>
+                                                        <span class="block text-xs text-gray-500">
+                                                            {node.address.clone().unwrap_or_default()}
+                                                        </span>
+                                                    </td>
+                                                    <td class="py-2 pe-4 font-mono">
+                                                        {node.version.clone().unwrap_or_default()}
+                                                    </td>
+                                                    <td class="py-2 pe-4">
+                                                        <Badge color>{status}</Badge>
+                                                        {error
+                                                            .map(|error| {
+                                                                view! {
+                                                                    <span class="block mt-1 text-xs text-red-600 dark:text-red-500">
+                                                                        {error}
+                                                                    </span>
+                                                                }
+                                                            })}
+
+                                                    </td>
+                                                    <td class="py-2 text-end">
+                                                        <Show when=move || can_retry>
+                                                            <Button
+                                                                text="Retry"
+                                                                color=Color::Gray
+                                                                disabled=pending
+                                                                on_click={
+                                                                    let node = node.clone();
+                                                                    move |_| {
+                                                                        retry.dispatch(node.clone());
+                                                                    }
+                                                                }
+                                                            />
+                                                        </Show>
+                                                    </td>
+                                                </tr>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                            <div class="mt-4 flex justify-end">
+                                <Button
+                                    text="Close"
+                                    color=Color::Blue
+                                    on_click=move |_| {
+                                        on_close.call(());
+                                    }
+                                />
+                            </div>
+                        </div>
+                    }
+                })
+        }}
+    }
+}
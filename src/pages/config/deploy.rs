@@ -0,0 +1,208 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::{IconArrowUpTray, IconRefresh},
+        messages::alert::{use_alerts, Alert},
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::List,
+};
+
+const HISTORY_SIZE: u32 = 20;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeployEvent {
+    pub pushed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub status: DeployStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DeployStatus {
+    Success,
+    Failed,
+}
+
+#[component]
+pub fn CertificateDeployPanel(#[prop(into)] id: Signal<String>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let history = create_resource(
+        move || id.get(),
+        move |id| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if id.is_empty() {
+                    return Ok(List {
+                        items: vec![],
+                        total: 0,
+                    });
+                }
+                HttpRequest::get(("/api/acme/deploy/history", &id))
+                    .with_authorization(&auth)
+                    .with_parameter("limit", HISTORY_SIZE.to_string())
+                    .send::<List<DeployEvent>>()
+                    .await
+            }
+        },
+    );
+
+    let push_action = create_action(move |id: &String| {
+        let id = id.clone();
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::post(("/api/acme/deploy", &id))
+                .with_authorization(&auth)
+                .send::<()>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "The current certificates have been queued for deployment to {id}."
+                    )));
+                    history.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Show when=move || !id.get().is_empty()>
+            <FormSection title="Push History".to_string()>
+                <FormItem label="Recent pushes">
+                    {move || match history.get() {
+                        None => {
+                            view! {
+                                <span class="text-sm text-gray-500 dark:text-gray-400">
+                                    Loading...
+                                </span>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            ().into_view()
+                        }
+                        Some(Ok(history)) if history.items.is_empty() => {
+                            view! {
+                                <span class="text-sm text-gray-500 dark:text-gray-400">
+                                    No certificates have been pushed to this target yet.
+                                </span>
+                            }
+                                .into_view()
+                        }
+                        Some(Ok(history)) => {
+                            view! {
+                                <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                                    <thead>
+                                        <tr class="text-xs font-medium uppercase text-gray-500">
+                                            <th class="py-2 pe-4 text-start">Pushed</th>
+                                            <th class="py-2 pe-4 text-start">Certificate</th>
+                                            <th class="py-2 text-start">Result</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                        {history
+                                            .items
+                                            .into_iter()
+                                            .map(|event| {
+                                                let (color, label) = match event.status {
+                                                    DeployStatus::Success => (Color::Green, "Deployed"),
+                                                    DeployStatus::Failed => (Color::Red, "Failed"),
+                                                };
+                                                view! {
+                                                    <tr class="text-gray-800 dark:text-gray-200 align-top">
+                                                        <td class="py-2 pe-4 whitespace-nowrap">
+                                                            <Timestamp date=event.pushed_at relative=true/>
+                                                        </td>
+                                                        <td class="py-2 pe-4">
+                                                            <span class="block">{event.subjects.join(", ")}</span>
+                                                            <span class="block text-xs text-gray-500">
+                                                                {event.provider.unwrap_or_default()}
+                                                            </span>
+                                                        </td>
+                                                        <td class="py-2">
+                                                            <Badge color=color>{label}</Badge>
+                                                            <span class="block mt-1 text-xs text-red-600">
+                                                                {event.error.unwrap_or_default()}
+                                                            </span>
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </tbody>
+                                </table>
+                            }
+                                .into_view()
+                        }
+                    }}
+
+                </FormItem>
+                <FormItem label="">
+                    <div class="flex flex-wrap items-center gap-x-3 gap-y-2">
+                        <Button
+                            text="Push now"
+                            color=Color::Gray
+                            on_click=move |_| {
+                                push_action.dispatch(id.get_untracked());
+                            }
+
+                            disabled=Signal::derive(move || push_action.pending().get())
+                        >
+
+                            <IconArrowUpTray/>
+                        </Button>
+                        <Button
+                            text="Refresh"
+                            color=Color::Gray
+                            on_click=move |_| {
+                                history.refetch();
+                            }
+                        >
+
+                            <IconRefresh/>
+                        </Button>
+                    </div>
+                </FormItem>
+            </FormSection>
+        </Show>
+    }
+}
@@ -8,24 +8,29 @@ use std::sync::Arc;
 
 use ahash::AHashMap;
 use leptos::*;
-use leptos_router::{use_navigate, use_params_map};
+use leptos_router::{use_location, use_navigate, use_params_map, use_query_map};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
         form::{
             button::Button,
+            cron::SelectCron,
+            draft::{use_form_draft, DraftRestoreBanner},
             expression::InputExpression,
-            input::{
-                InputDuration, InputPassword, InputRate, InputSize, InputSwitch, InputText,
-                TextArea,
-            },
-            select::{CheckboxGroup, Select, SelectCron},
+            help::FieldHelp,
+            history::use_form_history,
+            input::{InputDuration, InputRate, InputSize, InputSwitch, InputText, TextArea},
+            principal::SelectPrincipal,
+            renderer::{use_field_renderers, FieldContext, FieldRenderers},
+            rules::InputRules,
+            secret::InputSecret,
+            select::{CheckboxGroup, GroupedCheckboxGroup, Select},
             stacked_badge::StackedBadge,
             stacked_input::StackedInput,
             Form, FormButtonBar, FormElement, FormItem, FormSection,
         },
-        icon::IconRefresh,
+        icon::{IconArrowUTurnLeft, IconBeaker, IconPaperAirplane, IconRefresh, IconVariable},
         messages::{
             alert::{use_alerts, Alert},
             modal::{use_modals, Modal},
@@ -34,13 +39,32 @@ use crate::{
         Color,
     },
     core::{
+        command::remember_recent_object,
         form::{ExternalSources, FormData},
         http::{self, HttpRequest},
+        i18n::t,
         oauth::use_authorization,
-        schema::SelectType,
+        schema::{SelectType, Source},
+        AccessToken,
     },
     pages::{
-        config::{ReloadSettings, Schema, SchemaType, Schemas, Settings, Type, UpdateSettings},
+        config::{
+            acme::AcmePropagationPanel,
+            certificate::CertificateStatusPanel,
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            connection::StoreConnectionPanel,
+            deploy::CertificateDeployPanel,
+            git::GitSyncPanel,
+            listener::{self, LISTENER_SCHEMA_ID},
+            pending::{queue_pending_change, settings_version, PendingChangesNotice},
+            provenance::FieldProvenance,
+            quick::{is_pinnable, PinFieldButton},
+            retention::MetricsRetentionPanel,
+            saml::SamlPanel,
+            scim::ScimEndpoint,
+            usage::FrequentSettingsPanel,
+            Field, Schema, SchemaType, Schemas, Settings, Type, UpdateSettings,
+        },
         List,
     },
 };
@@ -51,8 +75,61 @@ struct FetchSettings {
     pub total: u64,
 }
 
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct EnvOverride {
+    pub source: String,
+    pub value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SettingsTestRequest {
+    id: String,
+    settings: Vec<(String, String)>,
+    // Stored secrets are not loaded into the form, the server reads them
+    // from its settings instead
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    retained_secrets: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct DirectoryTestResult {
+    pub bind: DirectoryTestStep,
+    pub lookup: DirectoryTestStep,
+    #[serde(rename = "sampleUser")]
+    #[serde(default)]
+    pub sample_user: Option<DirectoryTestUser>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct DirectoryTestStep {
+    pub success: bool,
+    #[serde(rename = "elapsedMs")]
+    #[serde(default)]
+    pub elapsed_ms: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct DirectoryTestUser {
+    pub name: String,
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct WebhookTestResult {
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(rename = "elapsedMs")]
+    #[serde(default)]
+    pub elapsed_ms: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
-enum FetchResult {
+pub enum FetchResult {
     Update {
         settings: Settings,
         external_sources: ExternalSources,
@@ -70,153 +147,96 @@ pub fn SettingsEdit() -> impl IntoView {
     let auth = use_authorization();
     let alert = use_alerts();
     let params = use_params_map();
+    let query = use_query_map();
     let modal = use_modals();
 
-    let schemas = expect_context::<Arc<Schemas>>();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
     let current_schema = create_memo(move |_| {
-        if let Some(schema) = params
+        let schema = params
             .get()
             .get("object")
-            .and_then(|id| schemas.schemas.get(id.as_str()))
-        {
-            schema.clone()
+            .and_then(|id| schemas.with_value(|schemas| schemas.schemas.get(id.as_str()).cloned()));
+        if let Some(schema) = schema {
+            schema
         } else {
             use_navigate()("/404", Default::default());
             Arc::new(Schema::default())
         }
     });
 
+    // New objects can be pre-filled from an existing one with ?from=<id>
+    let duplicate_from = create_memo(move |_| {
+        let is_create = params.with(|p| p.get("id").is_none_or(|id| id.is_empty()));
+        query
+            .with(|q| q.get("from").cloned())
+            .filter(|from| is_create && !from.is_empty())
+    });
     let fetch_settings = create_resource(
-        move || params.get().get("id").cloned().unwrap_or_default(),
+        move || {
+            duplicate_from
+                .get()
+                .or_else(|| params.get().get("id").cloned())
+                .unwrap_or_default()
+        },
         move |name| {
             let auth = auth.get_untracked();
             let current_schema = current_schema.get();
-            let is_create = name.is_empty();
 
-            async move {
-                // Fetch external sources
-                let mut external_sources = ExternalSources::new();
-                for (schema, field) in current_schema.external_sources() {
-                    let schema = schema.unwrap_or_else(|| current_schema.clone());
-                    let source_key = format!("{}_{}", schema.id, field.id);
-                    if !external_sources.contains_key(&source_key) {
-                        let items = HttpRequest::get("/api/settings/group")
-                            .with_authorization(&auth)
-                            .with_parameter("prefix", schema.unwrap_prefix())
-                            .with_parameter(
-                                "suffix",
-                                schema.try_unwrap_suffix().unwrap_or_default(),
-                            )
-                            .with_parameter("field", field.id)
-                            .send::<List<Settings>>()
-                            .await?
-                            .items;
-
-                        external_sources.insert(
-                            source_key,
-                            items
-                                .into_iter()
-                                .filter_map(|mut item| {
-                                    (
-                                        item.remove("_id")?,
-                                        item.remove(field.id).unwrap_or_default(),
-                                    )
-                                        .into()
-                                })
-                                .collect::<Vec<_>>(),
-                        );
-                    }
-                }
-
-                // Fetch settings
-                match current_schema.typ {
-                    SchemaType::Record { prefix, .. } => {
-                        if !is_create {
-                            HttpRequest::get("/api/settings/list")
-                                .with_authorization(&auth)
-                                .with_parameter("prefix", format!("{prefix}.{name}"))
-                                .send::<FetchSettings>()
-                                .await
-                                .map(|mut list| {
-                                    if !list.items.is_empty() {
-                                        list.items.insert("_id".to_string(), name.to_string());
-                                        FetchResult::Update {
-                                            settings: list.items,
-                                            external_sources,
-                                        }
-                                    } else {
-                                        FetchResult::NotFound
-                                    }
-                                })
-                        } else {
-                            Ok(FetchResult::Create { external_sources })
-                        }
-                    }
-                    SchemaType::Entry { prefix } => {
-                        if !is_create {
-                            HttpRequest::get("/api/settings/keys")
-                                .with_authorization(&auth)
-                                .with_parameter("keys", format!("{prefix}.{name}"))
-                                .send::<AHashMap<String, Option<String>>>()
-                                .await
-                                .map(|list| {
-                                    if let Some(value) = list.into_values().next().flatten() {
-                                        let mut settings = Settings::new();
-                                        settings.insert("_id".to_string(), name.to_string());
-                                        settings.insert("_value".to_string(), value);
-                                        FetchResult::Update {
-                                            settings,
-                                            external_sources,
-                                        }
-                                    } else {
-                                        FetchResult::NotFound
-                                    }
-                                })
-                        } else {
-                            Ok(FetchResult::Create { external_sources })
-                        }
-                    }
-                    SchemaType::List => {
-                        let mut keys = Vec::new();
-                        let mut prefixes = Vec::new();
-
-                        for field in current_schema.fields.values() {
-                            if field.is_multivalue() {
-                                prefixes.push(field.id);
-                                keys.push(field.id);
-                            } else {
-                                keys.push(field.id);
-                            }
-                        }
+            async move { fetch_schema_settings(&auth, current_schema, name).await }
+        },
+    );
+    let fetch_overrides = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
 
-                        HttpRequest::get("/api/settings/keys")
-                            .with_authorization(&auth)
-                            .with_parameter("keys", keys.join(","))
-                            .with_parameter("prefixes", prefixes.join(","))
-                            .send::<Settings>()
-                            .await
-                            .map(|mut list| {
-                                let mut settings = Settings::new();
-                                for (name, value) in list.drain() {
-                                    settings.insert(name, value);
-                                }
+            async move {
+                // Older servers do not report environment overrides
+                HttpRequest::get("/api/settings/overrides")
+                    .with_authorization(&auth)
+                    .send::<AHashMap<String, EnvOverride>>()
+                    .await
+                    .unwrap_or_default()
+            }
+        },
+    );
+    // Other listeners, loaded to warn about addresses that are already bound
+    let bind_siblings = create_local_resource(
+        move || current_schema.get(),
+        move |schema| {
+            let auth = auth.get_untracked();
 
-                                if !settings.is_empty() {
-                                    FetchResult::Update {
-                                        settings,
-                                        external_sources,
-                                    }
-                                } else {
-                                    FetchResult::Create { external_sources }
-                                }
-                            })
-                    }
+            async move {
+                if schema.id != LISTENER_SCHEMA_ID {
+                    return vec![];
                 }
+                HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", schema.unwrap_prefix())
+                    .with_parameter("suffix", schema.try_unwrap_suffix().unwrap_or_default())
+                    .send::<List<Settings>>()
+                    .await
+                    .map(|list| list.items)
+                    .unwrap_or_default()
             }
         },
     );
     let (pending, set_pending) = create_signal(false);
     let data = FormData::default().into_signal();
+    let bind_conflicts = create_memo(move |_| {
+        bind_siblings.with(|siblings| {
+            siblings
+                .as_ref()
+                .map(|siblings| data.with(|data| listener::bind_conflicts(data, siblings)))
+                .unwrap_or_default()
+        })
+    });
+    let history = use_form_history(data);
+    let draft = use_form_draft(data, &[]);
+    let renderers = store_value(use_field_renderers());
+    let cluster_results = create_rw_signal(None);
+    let loaded_version = store_value((String::new(), 0u64));
+    let loaded_values = store_value(AHashMap::new());
 
     let save_changes = create_action(
         move |(changes, reload): &(Arc<Vec<UpdateSettings>>, bool)| {
@@ -224,28 +244,32 @@ pub fn SettingsEdit() -> impl IntoView {
             let reload = *reload;
             let auth = auth.get();
             let schema = current_schema.get();
+            let schemas = schemas.get_value();
+            let (object_id, version) = loaded_version.get_value();
 
             async move {
                 set_pending.set(true);
                 match HttpRequest::post("/api/settings")
                     .with_authorization(&auth)
-                    .with_body(changes)
+                    .with_body(changes.clone())
                     .unwrap()
                     .send::<Option<String>>()
                     .await
                     .map(|_| ())
                 {
                     Ok(_) => {
+                        draft.clear();
                         if reload {
-                            match HttpRequest::get(format!(
-                                "/api/reload/{}",
-                                schema.reload_prefix.unwrap_or_default()
-                            ))
-                            .with_authorization(&auth)
-                            .send::<ReloadSettings>()
-                            .await
+                            // In a cluster every node has to reload, the result of each
+                            // one is shown so that failed nodes can be retried
+                            match reload_settings(&auth, schema.reload_prefix.unwrap_or_default())
+                                .await
                             {
-                                Ok(result) => {
+                                Ok(ReloadOutcome::Cluster(results)) => {
+                                    set_pending.set(false);
+                                    cluster_results.set(Some(results));
+                                }
+                                Ok(ReloadOutcome::Local(result)) => {
                                     set_pending.set(false);
                                     if result.errors.is_empty() {
                                         match schema.list_path() {
@@ -264,7 +288,7 @@ pub fn SettingsEdit() -> impl IntoView {
                                             }
                                         }
                                     } else {
-                                        alert.set(Alert::from(result));
+                                        alert.set(Alert::from_reload(result, &schemas));
                                     }
                                 }
                                 Err(http::Error::Unauthorized) => {
@@ -299,6 +323,37 @@ pub fn SettingsEdit() -> impl IntoView {
                             http::Error::Unauthorized => {
                                 use_navigate()("/login", Default::default());
                             }
+                            http::Error::Network(_) => {
+                                let message = if queue_pending_change(
+                                    &auth,
+                                    &schema,
+                                    &object_id,
+                                    version,
+                                    changes.as_ref().clone(),
+                                    reload,
+                                )
+                                .is_some()
+                                {
+                                    concat!(
+                                        "Your changes could not be saved because the ",
+                                        "server could not be reached. They have been kept ",
+                                        "in this browser and can be retried from the ",
+                                        "Pending changes page once the connection is restored."
+                                    )
+                                } else {
+                                    concat!(
+                                        "Your changes could not be saved because the ",
+                                        "server could not be reached. They include secret ",
+                                        "values, which are never stored in this browser, so ",
+                                        "please save them again once the connection is restored."
+                                    )
+                                };
+                                modal.set(
+                                    Modal::with_title("Server unreachable")
+                                        .with_message(message)
+                                        .with_button("OK"),
+                                );
+                            }
                             err => {
                                 alert.set(Alert::from(err));
                             }
@@ -309,11 +364,258 @@ pub fn SettingsEdit() -> impl IntoView {
         },
     );
 
+    let test_connection = create_action(move |request: &Arc<SettingsTestRequest>| {
+        let request = request.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/directory/test")
+                .with_authorization(&auth)
+                .with_body(request.as_ref())
+                .unwrap()
+                .send::<DirectoryTestResult>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(result) => {
+                    let sample_user = result
+                        .sample_user
+                        .map(|user| {
+                            if let Some(email) = user.emails.first() {
+                                format!("{} <{}>", user.name, email)
+                            } else {
+                                user.name
+                            }
+                        })
+                        .unwrap_or_else(|| "No users found".to_string());
+                    modal.set(
+                        Modal::with_title("Connection test")
+                            .with_message(if result.bind.success && result.lookup.success {
+                                "The directory server accepted the entered settings."
+                            } else {
+                                "The directory server rejected the entered settings."
+                            })
+                            .with_details([
+                                ("Bind", result.bind.format()),
+                                ("Lookup", result.lookup.format()),
+                                ("Sample user", sample_user),
+                            ])
+                            .with_button("OK"),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let send_test_event = create_action(move |request: &Arc<SettingsTestRequest>| {
+        let request = request.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/webhook/test")
+                .with_authorization(&auth)
+                .with_body(request.as_ref())
+                .unwrap()
+                .send::<WebhookTestResult>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(result) => {
+                    let is_success = result
+                        .status
+                        .is_some_and(|status| (200..300).contains(&status));
+                    let mut details = vec![(
+                        "HTTP status",
+                        result
+                            .status
+                            .map(|status| status.to_string())
+                            .unwrap_or_else(|| "No response".to_string()),
+                    )];
+                    if let Some(elapsed_ms) = result.elapsed_ms {
+                        details.push(("Response time", format!("{elapsed_ms} ms")));
+                    }
+                    if let Some(error) = result.error {
+                        details.push(("Error", error));
+                    }
+                    modal.set(
+                        Modal::with_title("Test event")
+                            .with_message(if is_success {
+                                "The webhook endpoint accepted the test event."
+                            } else {
+                                "The webhook endpoint did not accept the test event."
+                            })
+                            .with_details(details)
+                            .with_button("OK"),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let send_test_digest = create_action(move |request: &Arc<SettingsTestRequest>| {
+        let request = request.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/telemetry/digest/test")
+                .with_authorization(&auth)
+                .with_body(request.as_ref())
+                .unwrap()
+                .send::<serde_json::Value>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(_) => {
+                    alert.set(Alert::success(
+                        "A digest covering the selected period has been sent to the recipients.",
+                    ));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    // Scroll to the section linked from the settings search
+    let location = use_location();
+    create_effect(move |_| {
+        let hash = location.hash.get();
+        if fetch_settings.with(|result| result.is_some()) && hash.len() > 1 {
+            request_animation_frame(move || {
+                if let Some(section) = document().get_element_by_id(&hash[1..]) {
+                    section.scroll_into_view();
+                }
+            });
+        }
+    });
+    let can_test_connection = create_memo(move |_| {
+        current_schema.get().id == "directory"
+            && matches!(
+                data.get().value_as_str("type"),
+                Some("ldap" | "sql" | "oidc")
+            )
+    });
+
+    let confirm_save = move |changes: Arc<Vec<UpdateSettings>>, reload: bool| {
+        if !bind_conflicts.with_untracked(|conflicts| conflicts.is_empty()) {
+            modal.set(
+                Modal::with_title("Bind address in use")
+                    .with_message(concat!(
+                        "This listener uses addresses that are already bound by ",
+                        "other listeners, the server will not be able to listen on ",
+                        "them. Save anyway?"
+                    ))
+                    .with_button("Save anyway")
+                    .with_dangerous_callback(move || {
+                        save_changes.dispatch((changes.clone(), reload));
+                    }),
+            );
+        } else {
+            save_changes.dispatch((changes, reload));
+        }
+    };
+    let run_async_checks = create_action(
+        move |(changes, reload): &(Arc<Vec<UpdateSettings>>, bool)| {
+            let changes = changes.clone();
+            let reload = *reload;
+            let auth = auth.get();
+            let checks = loaded_values
+                .with_value(|loaded| data.with_untracked(|data| data.async_checks(loaded)));
+
+            async move {
+                set_pending.set(true);
+                let mut problems = Vec::new();
+                for (field, validator, value) in checks {
+                    match validator.validate(&auth, &value).await {
+                        Ok(Some(problem)) => problems.push((field.label_form_text(), problem)),
+                        Ok(None) => {}
+                        Err(http::Error::Unauthorized) => {
+                            set_pending.set(false);
+                            use_navigate()("/login", Default::default());
+                            return;
+                        }
+                        Err(err) => problems.push((
+                            field.label_form_text(),
+                            format!("Could not be verified: {}", Alert::from(err).message),
+                        )),
+                    }
+                }
+                set_pending.set(false);
+
+                if problems.is_empty() {
+                    confirm_save(changes, reload);
+                } else {
+                    modal.set(
+                        Modal::with_title("DNS misconfiguration")
+                            .with_message(concat!(
+                                "Some values failed checks that were run against the ",
+                                "server DNS. Save anyway?"
+                            ))
+                            .with_details(problems)
+                            .with_button("Save anyway")
+                            .with_dangerous_callback(move || {
+                                confirm_save(changes.clone(), reload);
+                            }),
+                    );
+                }
+            }
+        },
+    );
+
+    let save = move |reload: bool| {
+        let Some(changes) = data
+            .try_update(|data| data.validate_form().then(|| Arc::new(data.build_update())))
+            .flatten()
+        else {
+            return;
+        };
+        if loaded_values
+            .with_value(|loaded| data.with_untracked(|data| data.async_checks(loaded).is_empty()))
+        {
+            confirm_save(changes, reload);
+        } else {
+            run_async_checks.dispatch((changes, reload));
+        }
+    };
+
     view! {
         <Form
-            title=Signal::derive(move || current_schema.get().form.title.to_string())
-            subtitle=Signal::derive(move || current_schema.get().form.subtitle.to_string())
+            title=Signal::derive(move || current_schema.get().form_title_text())
+            subtitle=Signal::derive(move || current_schema.get().form_subtitle_text())
         >
+            <PendingChangesNotice/>
+            <DraftRestoreBanner draft/>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=Signal::derive(move || {
+                    current_schema.get().reload_prefix.unwrap_or_default().to_string()
+                })
+                on_close=move |_| {
+                    cluster_results.set(None);
+                    if let Some(url) = current_schema.get().list_path() {
+                        use_navigate()(&url, Default::default());
+                    }
+                }
+            />
 
             <Transition fallback=Skeleton set_pending>
 
@@ -333,9 +635,10 @@ pub fn SettingsEdit() -> impl IntoView {
                         Some(view! { <div></div> }.into_view())
                     }
                     Some(Ok(result)) => {
+                        let is_duplicate = duplicate_from.get_untracked().is_some();
                         let (is_create, settings, external_sources) = match result {
                             FetchResult::Update { settings, external_sources } => {
-                                (false, Some(settings), external_sources)
+                                (is_duplicate, Some(settings), external_sources)
                             }
                             FetchResult::Create { external_sources } => {
                                 (true, None, external_sources)
@@ -344,28 +647,79 @@ pub fn SettingsEdit() -> impl IntoView {
                         };
                         let schema = current_schema.get();
                         let sections = schema.form.sections.iter().cloned();
+                        let overrides = fetch_overrides.get().unwrap_or_default();
+                        let setting_id = params.get_untracked().get("id").cloned().unwrap_or_default();
                         let is_enterprise = auth.get().is_enterprise();
-                        data.set(
-                            FormData::from_settings(schema.clone(), settings)
-                                .with_external_sources(external_sources),
-                        );
+                        loaded_version.set_value((
+                            setting_id.clone(),
+                            settings_version(settings.as_ref().filter(|_| !is_create)),
+                        ));
+                        if !is_create && !setting_id.is_empty() {
+                            remember_recent_object(
+                                &auth.get_untracked(),
+                                format!("{setting_id} ({})", schema.name_singular),
+                                format!("/settings/{}/{setting_id}/edit", schema.id),
+                            );
+                        }
+                        draft
+                            .load(
+                                schema.id,
+                                &setting_id,
+                                |data| {
+                                    *data = FormData::from_settings(schema.clone(), settings)
+                                        .with_external_sources(external_sources);
+                                    loaded_values.set_value(if is_create {
+                                        AHashMap::new()
+                                    } else {
+                                        data.values.clone()
+                                    });
+                                    if is_duplicate {
+                                        if !data.secrets.is_empty() {
+                                            alert
+                                                .set(
+                                                    Alert::warning(
+                                                        "Secrets are not copied, enter them again before saving.",
+                                                    ),
+                                                );
+                                        }
+                                        *data = std::mem::take(data).into_duplicate();
+                                    }
+                                },
+                            );
+                        history.reset();
                         Some(
                             sections
-                                .map(|section| {
-                                    let title = section.title.map(|s| s.to_string());
+                                .enumerate()
+                                .map(|(section_idx, section)| {
+                                    let section_ = section.clone();
+                                    let title = Signal::derive(move || {
+                                        section_.title_text().unwrap_or_default()
+                                    });
                                     let section_ = section.clone();
                                     let hide_section = create_memo(move |_| {
                                         !section_.display(&data.get())
                                     });
+                                    let section_errors = create_memo(move |_| {
+                                        data.get()
+                                            .section_errors
+                                            .get(&section_idx)
+                                            .cloned()
+                                            .unwrap_or_default()
+                                    });
                                     let components = section
                                         .fields
                                         .iter()
-                                        .cloned()
                                         .map(|field| {
                                             let is_disabled = (field.readonly && !is_create)
                                                 || (!is_enterprise && field.enterprise);
-                                            let field_label = field.label_form;
-                                            let help = field.help;
+                                            let field_ = field.clone();
+                                            let field_label = Signal::derive(move || {
+                                                field_.label_form_text()
+                                            });
+                                            let field_ = field.clone();
+                                            let help = Signal::derive(move || {
+                                                field_.help_text().unwrap_or_default()
+                                            });
                                             let field_ = field.clone();
                                             let hide_label = create_memo(move |_| {
                                                 !field_.display(&data.get())
@@ -374,158 +728,69 @@ pub fn SettingsEdit() -> impl IntoView {
                                             let is_optional = create_memo(move |_| {
                                                 !field_.is_required(&data.get())
                                             });
+                                            let field_help = FieldHelp::new(field);
                                             let is_switch = matches!(field.typ_, Type::Boolean);
-                                            let component = match field.typ_ {
-                                                Type::Input => {
+                                            let env_override = schema
+                                                .setting_key(&setting_id, field.id)
+                                                .and_then(|key| overrides.get(&key).cloned())
+                                                .map(|env| {
+                                                    let is_secret = matches!(field.typ_, Type::Secret);
+                                                    view! { <EnvOverrideNotice env is_secret/> }
+                                                });
+                                            let pin = is_pinnable(&schema, &setting_id, field)
+                                                .then(|| {
                                                     view! {
-                                                        <InputText
-                                                            element=FormElement::new(field.id, data)
-                                                            placeholder=create_memo(move |_| {
-                                                                field
-                                                                    .placeholder(&data.get())
-                                                                    .unwrap_or_default()
-                                                                    .to_string()
-                                                            })
-
-                                                            disabled=is_disabled
+                                                        <PinFieldButton
+                                                            schema=schema.id
+                                                            id=setting_id.clone()
+                                                            field=field.id
                                                         />
                                                     }
-                                                        .into_view()
-                                                }
-                                                Type::Array => {
-                                                    view! {
-                                                        <StackedInput
-                                                            add_button_text="Add".to_string()
-                                                            element=FormElement::new(field.id, data)
-                                                            placeholder=create_memo(move |_| {
-                                                                field
-                                                                    .placeholder(&data.get())
-                                                                    .unwrap_or_default()
-                                                                    .to_string()
-                                                            })
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Secret => {
+                                                });
+                                            // Duplicates and new records have nothing stored yet
+                                            let provenance = schema
+                                                .setting_key(&setting_id, field.id)
+                                                .filter(|_| {
+                                                    !is_create || matches!(schema.typ, SchemaType::List)
+                                                })
+                                                .map(|key| {
                                                     view! {
-                                                        <InputPassword
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
+                                                        <FieldProvenance
+                                                            key
+                                                            is_secret=matches!(field.typ_, Type::Secret)
+                                                            is_multivalue=field.is_multivalue()
                                                         />
                                                     }
-                                                        .into_view()
-                                                }
-                                                Type::Select { typ: SelectType::Single, .. } => {
-                                                    view! {
-                                                        <Select
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Select { typ: SelectType::Many, .. } => {
-                                                    view! {
-                                                        <CheckboxGroup
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Select { typ: SelectType::ManyWithSearch, .. } => {
-                                                    view! {
-                                                        <StackedBadge
-                                                            element=FormElement::new(field.id, data)
-                                                            add_button_text="Add Item"
-                                                            color=Color::Green
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Size => {
-                                                    view! {
-                                                        <InputSize
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Boolean => {
-                                                    view! {
-                                                        <InputSwitch
-                                                            label=field_label
-                                                            tooltip=help.unwrap_or_default()
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Duration => {
-                                                    view! {
-                                                        <InputDuration
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Rate => {
-                                                    view! {
-                                                        <InputRate
-                                                            element=FormElement::new(field.id, data)
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Expression => {
-                                                    view! {
-                                                        <InputExpression element=FormElement::new(field.id, data)/>
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Cron => {
-                                                    view! {
-                                                        <SelectCron element=FormElement::new(field.id, data)/>
-                                                    }
-                                                        .into_view()
-                                                }
-                                                Type::Text => {
-                                                    view! {
-                                                        <TextArea
-                                                            element=FormElement::new(field.id, data)
-                                                            placeholder=create_memo(move |_| {
-                                                                field
-                                                                    .placeholder(&data.get())
-                                                                    .unwrap_or_default()
-                                                                    .to_string()
-                                                            })
-
-                                                            disabled=is_disabled
-                                                        />
-                                                    }
-                                                        .into_view()
-                                                }
-                                            };
+                                                });
+                                            let component = renderers
+                                                .with_value(|r| render_field(&schema, field.clone(), data, is_disabled, r));
                                             if !is_switch {
                                                 view! {
                                                     <FormItem
                                                         label=field_label
                                                         hide=hide_label
                                                         is_optional=is_optional
-                                                        tooltip=help.unwrap_or_default()
+                                                        tooltip=help
+                                                        help=field_help
                                                     >
                                                         {component}
+                                                        {env_override}
+                                                        {pin}
+                                                        {provenance}
                                                     </FormItem>
                                                 }
                                             } else {
                                                 view! {
-                                                    <FormItem label="" hide=hide_label is_optional=is_optional>
+                                                    <FormItem
+                                                        label=""
+                                                        hide=hide_label
+                                                        is_optional=is_optional
+                                                        help=field_help
+                                                    >
                                                         {component}
+                                                        {env_override}
+                                                        {pin}
+                                                        {provenance}
                                                     </FormItem>
                                                 }
                                             }
@@ -533,10 +798,28 @@ pub fn SettingsEdit() -> impl IntoView {
                                         .collect_view();
                                     view! {
                                         <FormSection
-                                            title=title.unwrap_or_default()
+                                            title=title
                                             hide=hide_section
+                                            attr:id=format!("section-{section_idx}")
                                         >
                                             {components}
+                                            {move || {
+                                                let errors = section_errors.get();
+                                                (!errors.is_empty())
+                                                    .then(|| {
+                                                        view! {
+                                                            <div class="sm:col-span-12">
+                                                                <ul class="list-disc space-y-1 ps-5 text-sm text-red-600">
+                                                                    {errors
+                                                                        .into_iter()
+                                                                        .map(|error| view! { <li>{error}</li> })
+                                                                        .collect_view()}
+                                                                </ul>
+                                                            </div>
+                                                        }
+                                                    })
+                                            }}
+
                                         </FormSection>
                                     }
                                         .into_view()
@@ -548,9 +831,85 @@ pub fn SettingsEdit() -> impl IntoView {
 
             </Transition>
 
+            <Show when=move || current_schema.get().id == "acme">
+                <AcmePropagationPanel data=data/>
+            </Show>
+
+            <Show when=move || matches!(current_schema.get().id, "acme" | "certificate")>
+                <CertificateStatusPanel
+                    source=Signal::derive(move || current_schema.get().id)
+                    id=Signal::derive(move || params.get().get("id").cloned().unwrap_or_default())
+                />
+            </Show>
+
+            <Show when=move || current_schema.get().id == "acme-deploy">
+                <CertificateDeployPanel id=Signal::derive(move || {
+                    params.get().get("id").cloned().unwrap_or_default()
+                })/>
+            </Show>
+
+            <Show when=move || current_schema.get().id == "scim">
+                <ScimEndpoint/>
+            </Show>
+
+            <Show when=move || current_schema.get().id == "git-sync">
+                <GitSyncPanel/>
+            </Show>
+
+            <Show when=move || current_schema.get().id == "store">
+                <StoreConnectionPanel
+                    data=data
+                    is_create=Signal::derive(move || {
+                        params.with(|params| params.get("id").is_none())
+                    })
+                />
+            </Show>
+
+            <Show when=move || current_schema.get().id == "saml">
+                <SamlPanel data=data/>
+            </Show>
+
+            <Show when=move || current_schema.get().id == "telemetry-history">
+                <MetricsRetentionPanel data=data/>
+            </Show>
+
+            <Show when=move || !bind_conflicts.with(|conflicts| conflicts.is_empty())>
+                <div class="p-4 mb-4 text-sm text-yellow-800 rounded-lg bg-yellow-50 dark:bg-gray-800 dark:text-yellow-300">
+                    <p class="font-medium">
+                        Some bind addresses are already used by other listeners:
+                    </p>
+                    <ul class="mt-1.5 list-disc list-inside">
+                        {move || {
+                            bind_conflicts
+                                .get()
+                                .into_iter()
+                                .map(|conflict| {
+                                    view! {
+                                        <li>
+                                            {format!("{} overlaps with {} on listener ", conflict.bind, conflict.existing)}
+                                            <a
+                                                class="font-medium underline"
+                                                href=format!(
+                                                    "/settings/{LISTENER_SCHEMA_ID}/{}/edit",
+                                                    conflict.listener_id,
+                                                )
+                                            >
+
+                                                {conflict.listener_id.clone()}
+                                            </a>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+
+                    </ul>
+                </div>
+            </Show>
+
             <FormButtonBar>
                 <Button
-                    text="Cancel"
+                    text=Signal::derive(|| t("button-cancel"))
                     color=Color::Gray
                     on_click=move |_| {
                         use_navigate()(
@@ -561,15 +920,132 @@ pub fn SettingsEdit() -> impl IntoView {
                 />
 
                 <Button
-                    text="Save & Reload"
+                    text=Signal::derive(|| t("button-undo"))
                     color=Color::Gray
-                    on_click=Callback::new(move |_| {
-                        data.update(|data| {
-                            if data.validate_form() {
-                                save_changes.dispatch((Arc::new(data.build_update()), true));
-                            }
-                        });
-                    })
+                    on_click=move |_| history.undo()
+                    disabled=Signal::derive(move || !history.can_undo())
+                    attr:title="Undo (Ctrl+Z)"
+                >
+
+                    <IconArrowUTurnLeft/>
+                </Button>
+
+                <Button
+                    text=Signal::derive(|| t("button-redo"))
+                    color=Color::Gray
+                    on_click=move |_| history.redo()
+                    disabled=Signal::derive(move || !history.can_redo())
+                    attr:title="Redo (Ctrl+Y)"
+                />
+
+                <Show when=move || can_test_connection.get()>
+                    <Button
+                        text="Test connection"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            data.update(|data| {
+                                if data.validate_form() {
+                                    let id = data.value_as_str("_id").unwrap_or_default().to_string();
+                                    let settings = data
+                                        .build_key_values()
+                                        .into_iter()
+                                        .map(|(key, value)| (format!("directory.{id}.{key}"), value))
+                                        .collect();
+                                    let retained_secrets = data
+                                        .retained_secrets()
+                                        .into_iter()
+                                        .map(|key| format!("directory.{id}.{key}"))
+                                        .collect();
+                                    test_connection
+                                        .dispatch(
+                                            Arc::new(SettingsTestRequest {
+                                                id,
+                                                settings,
+                                                retained_secrets,
+                                            }),
+                                        );
+                                }
+                            });
+                        })
+
+                        disabled=pending
+                    >
+
+                        <IconBeaker/>
+                    </Button>
+                </Show>
+
+                <Show when=move || current_schema.get().id == "web-hooks">
+                    <Button
+                        text="Send test event"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            data.update(|data| {
+                                if data.validate_form() {
+                                    let id = data.value_as_str("_id").unwrap_or_default().to_string();
+                                    let settings = data
+                                        .build_key_values()
+                                        .into_iter()
+                                        .map(|(key, value)| (format!("webhook.{id}.{key}"), value))
+                                        .collect();
+                                    let retained_secrets = data
+                                        .retained_secrets()
+                                        .into_iter()
+                                        .map(|key| format!("webhook.{id}.{key}"))
+                                        .collect();
+                                    send_test_event
+                                        .dispatch(
+                                            Arc::new(SettingsTestRequest {
+                                                id,
+                                                settings,
+                                                retained_secrets,
+                                            }),
+                                        );
+                                }
+                            });
+                        })
+
+                        disabled=pending
+                    >
+
+                        <IconPaperAirplane/>
+                    </Button>
+                </Show>
+
+                <Show when=move || {
+                    current_schema.get().id == "metrics-digest"
+                        && data
+                            .with(|data| data.value_as_str("metrics.digest.enable") == Some("true"))
+                }>
+                    <Button
+                        text="Send now"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            data.update(|data| {
+                                if data.validate_form() {
+                                    send_test_digest
+                                        .dispatch(
+                                            Arc::new(SettingsTestRequest {
+                                                id: "digest".to_string(),
+                                                settings: data.build_key_values(),
+                                                retained_secrets: data.retained_secrets(),
+                                            }),
+                                        );
+                                }
+                            });
+                        })
+
+                        disabled=pending
+                    >
+
+                        <IconPaperAirplane/>
+                    </Button>
+                </Show>
+
+                <Button
+                    text=Signal::derive(|| t("button-save-reload"))
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| save(true))
 
                     disabled=pending
                 >
@@ -578,25 +1054,380 @@ pub fn SettingsEdit() -> impl IntoView {
                 </Button>
 
                 <Button
-                    text="Save changes"
+                    text=Signal::derive(|| t("button-save"))
                     color=Color::Blue
-                    on_click=Callback::new(move |_| {
-                        data.update(|data| {
-                            if data.validate_form() {
-                                save_changes.dispatch((Arc::new(data.build_update()), false));
-                            }
-                        });
-                    })
+                    on_click=Callback::new(move |_| save(false))
 
                     disabled=pending
                 />
             </FormButtonBar>
 
+            <Show when=move || current_schema.get().id == "network">
+                <div class="mt-6">
+                    <FrequentSettingsPanel/>
+                </div>
+            </Show>
+
         </Form>
     }
 }
 
+pub async fn fetch_schema_settings(
+    auth: &AccessToken,
+    current_schema: Arc<Schema>,
+    name: String,
+) -> http::Result<FetchResult> {
+    let is_create = name.is_empty();
+
+    // Fetch external sources
+    let mut external_sources = ExternalSources::new();
+    for (schema, field) in current_schema.external_sources() {
+        let schema = schema.unwrap_or_else(|| current_schema.clone());
+        let source_key = format!("{}_{}", schema.id, field.id);
+        if !external_sources.contains_key(&source_key) {
+            let items = HttpRequest::get("/api/settings/group")
+                .with_authorization(auth)
+                .with_parameter("prefix", schema.unwrap_prefix())
+                .with_parameter("suffix", schema.try_unwrap_suffix().unwrap_or_default())
+                .with_parameter("field", field.id)
+                .send::<List<Settings>>()
+                .await?
+                .items;
+
+            external_sources.insert(
+                source_key,
+                items
+                    .into_iter()
+                    .filter_map(|mut item| {
+                        (
+                            item.remove("_id")?,
+                            item.remove(field.id).unwrap_or_default(),
+                        )
+                            .into()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    // Secret values are never requested, for the keys listed in "redact" the
+    // server only reports whether a value is stored.
+    let secret_keys = current_schema
+        .fields
+        .values()
+        .filter(|field| matches!(field.typ_, Type::Secret))
+        .map(|field| field.id)
+        .collect::<Vec<_>>();
+
+    // Fetch settings
+    match current_schema.typ {
+        SchemaType::Record { prefix, .. } => {
+            if !is_create {
+                HttpRequest::get("/api/settings/list")
+                    .with_authorization(auth)
+                    .with_parameter("prefix", format!("{prefix}.{name}"))
+                    .with_parameter("redact", secret_keys.join(","))
+                    .send::<FetchSettings>()
+                    .await
+                    .map(|mut list| {
+                        if !list.items.is_empty() {
+                            list.items.insert("_id".to_string(), name.to_string());
+                            FetchResult::Update {
+                                settings: list.items,
+                                external_sources,
+                            }
+                        } else {
+                            FetchResult::NotFound
+                        }
+                    })
+            } else {
+                Ok(FetchResult::Create { external_sources })
+            }
+        }
+        SchemaType::Entry { prefix } => {
+            if !is_create {
+                HttpRequest::get("/api/settings/keys")
+                    .with_authorization(auth)
+                    .with_parameter("keys", format!("{prefix}.{name}"))
+                    .send::<AHashMap<String, Option<String>>>()
+                    .await
+                    .map(|list| {
+                        if let Some(value) = list.into_values().next().flatten() {
+                            let mut settings = Settings::new();
+                            settings.insert("_id".to_string(), name.to_string());
+                            settings.insert("_value".to_string(), value);
+                            FetchResult::Update {
+                                settings,
+                                external_sources,
+                            }
+                        } else {
+                            FetchResult::NotFound
+                        }
+                    })
+            } else {
+                Ok(FetchResult::Create { external_sources })
+            }
+        }
+        SchemaType::List => {
+            let mut keys = Vec::new();
+            let mut prefixes = Vec::new();
+
+            for field in current_schema.fields.values() {
+                if field.is_multivalue() {
+                    prefixes.push(field.id);
+                    keys.push(field.id);
+                } else if !matches!(field.typ_, Type::Secret) {
+                    keys.push(field.id);
+                }
+            }
+
+            HttpRequest::get("/api/settings/keys")
+                .with_authorization(auth)
+                .with_parameter("keys", keys.join(","))
+                .with_parameter("prefixes", prefixes.join(","))
+                .with_parameter("redact", secret_keys.join(","))
+                .send::<Settings>()
+                .await
+                .map(|mut list| {
+                    let mut settings = Settings::new();
+                    for (name, value) in list.drain() {
+                        settings.insert(name, value);
+                    }
+
+                    if !settings.is_empty() {
+                        FetchResult::Update {
+                            settings,
+                            external_sources,
+                        }
+                    } else {
+                        FetchResult::Create { external_sources }
+                    }
+                })
+        }
+    }
+}
+
+pub fn render_field(
+    schema: &Schema,
+    field: Arc<Field>,
+    data: RwSignal<FormData>,
+    is_disabled: bool,
+    renderers: &FieldRenderers,
+) -> View {
+    let field_ = field.clone();
+    let field_label = Signal::derive(move || field_.label_form_text());
+    let field_ = field.clone();
+    let help = Signal::derive(move || field_.help_text().unwrap_or_default());
+
+    match renderers.get(schema, &field) {
+        Some(renderer) => renderer(FieldContext {
+            element: FormElement::new(field.id, data),
+            field: field.clone(),
+            disabled: is_disabled,
+        }),
+        None => match field.typ_ {
+            Type::Input | Type::Custom(_) => view! {
+                <InputText
+                    element=FormElement::new(field.id, data)
+                    placeholder=create_memo(move |_| {
+                        field
+                            .placeholder(&data.get())
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Array => view! {
+                <StackedInput
+                    add_button_text="Add".to_string()
+                    element=FormElement::new(field.id, data)
+                    placeholder=create_memo(move |_| {
+                        field
+                            .placeholder(&data.get())
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+                />
+            }
+            .into_view(),
+            Type::Secret => view! {
+                <InputSecret
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Select {
+                typ: SelectType::Single,
+                ..
+            } => view! {
+                <Select
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Select {
+                typ: SelectType::Many,
+                ..
+            } => view! {
+                <CheckboxGroup
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Select {
+                typ: SelectType::ManyWithGroups(groups),
+                ..
+            } => view! {
+                <GroupedCheckboxGroup
+                    element=FormElement::new(field.id, data)
+                    groups=groups
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::Principals(_),
+            } => view! {
+                <SelectPrincipal
+                    element=FormElement::new(field.id, data)
+                    add_button_text="Add Item"
+                    color=Color::Green
+                />
+            }
+            .into_view(),
+            Type::Select {
+                typ: SelectType::ManyWithSearch,
+                ..
+            } => view! {
+                <StackedBadge
+                    element=FormElement::new(field.id, data)
+                    add_button_text="Add Item"
+                    color=Color::Green
+                />
+            }
+            .into_view(),
+            Type::Size => view! {
+                <InputSize
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                    range=field
+                        .integer_range(&data.get_untracked())
+                        .map(|(min, max)| (min.max(0) as u64, max.max(0) as u64))
+                />
+            }
+            .into_view(),
+            Type::Boolean => view! {
+                <InputSwitch
+                    label=field_label
+                    tooltip=help
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Duration => view! {
+                <InputDuration
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Rate => view! {
+                <InputRate
+                    element=FormElement::new(field.id, data)
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+            Type::Expression => view! {
+                <InputExpression element=FormElement::new(field.id, data)/>
+            }
+            .into_view(),
+            Type::Rules => view! {
+                <InputRules
+                    element=FormElement::new(field.id, data)
+                    placeholder=create_memo(move |_| {
+                        field
+                            .placeholder(&data.get())
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+                />
+            }
+            .into_view(),
+            Type::Cron => view! {
+                <SelectCron element=FormElement::new(field.id, data)/>
+            }
+            .into_view(),
+            Type::Text => view! {
+                <TextArea
+                    element=FormElement::new(field.id, data)
+                    placeholder=create_memo(move |_| {
+                        field
+                            .placeholder(&data.get())
+                            .unwrap_or_default()
+                            .to_string()
+                    })
+
+                    disabled=is_disabled
+                />
+            }
+            .into_view(),
+        },
+    }
+}
+
+#[component]
+fn EnvOverrideNotice(env: EnvOverride, is_secret: bool) -> impl IntoView {
+    let value = if is_secret {
+        "********".to_string()
+    } else {
+        env.value
+    };
+
+    view! {
+        <p class="inline-flex flex-wrap items-center gap-x-1 text-xs text-yellow-600 dark:text-yellow-500 mt-2">
+            <IconVariable size=14 attr:class="flex-shrink-0 size-3.5"/>
+            <span>Overridden by</span>
+            <code class="font-mono">{env.source}</code>
+            <span>with effective value</span>
+            <code class="font-mono">{value}</code>
+        </p>
+    }
+}
+
+impl DirectoryTestStep {
+    fn format(&self) -> String {
+        match (self.success, self.elapsed_ms, &self.error) {
+            (true, Some(elapsed_ms), _) => format!("Succeeded in {elapsed_ms} ms"),
+            (true, None, _) => "Succeeded".to_string(),
+            (false, _, Some(error)) => format!("Failed: {error}"),
+            (false, _, None) => "Failed".to_string(),
+        }
+    }
+}
+
 impl Schema {
+    pub fn setting_key(&self, id: &str, field: &str) -> Option<String> {
+        match self.typ {
+            SchemaType::Record { prefix, .. } if !id.is_empty() => {
+                format!("{prefix}.{id}.{field}").into()
+            }
+            SchemaType::Entry { prefix } if !id.is_empty() && field == "_value" => {
+                format!("{prefix}.{id}").into()
+            }
+            SchemaType::List => field.to_string().into(),
+            _ => None,
+        }
+    }
+
     fn list_path(&self) -> Option<String> {
         if !matches!(self.typ, SchemaType::List) {
             format!("/settings/{}", self.id).into()
@@ -0,0 +1,320 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::{IconArrowDownTray, IconArrowUpTray, IconRefresh},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+use super::{
+    cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+    snapshot::create_snapshot,
+    Schemas,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitSyncStatus {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub head: Option<String>,
+    #[serde(default)]
+    pub remote_head: Option<String>,
+    #[serde(default)]
+    pub last_push: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_pull: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub pending_changes: u64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct GitSyncResult {
+    #[serde(default)]
+    pub commit: Option<String>,
+    #[serde(default)]
+    pub changes: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GitAction {
+    Push,
+    Pull,
+}
+
+#[component]
+pub fn GitSyncPanel() -> impl IntoView {
+    let auth = use_authorization();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let alert = use_alerts();
+    let modal = use_modals();
+    let (pending, set_pending) = create_signal(false);
+    let cluster_results = create_rw_signal(None);
+
+    let status = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/settings/git/status")
+                    .with_authorization(&auth)
+                    .send::<GitSyncStatus>()
+                    .await
+            }
+        },
+    );
+
+    let sync = create_action(move |action: &GitAction| {
+        let action = *action;
+        let auth = auth.get();
+        let schemas = schemas.get_value();
+
+        async move {
+            set_pending.set(true);
+            let result = match action {
+                GitAction::Push => push(&auth).await.map(|result| (result, None)),
+                GitAction::Pull => pull(&auth, &schemas)
+                    .await
+                    .map(|(result, reload)| (result, Some(reload))),
+            };
+            set_pending.set(false);
+            status.refetch();
+
+            match result {
+                Ok((_, Some(ReloadOutcome::Local(reload)))) if !reload.errors.is_empty() => {
+                    alert.set(Alert::from_reload(reload, &schemas));
+                }
+                Ok((result, reload)) => {
+                    if let Some(ReloadOutcome::Cluster(results)) = reload {
+                        cluster_results.set(Some(results));
+                    }
+                    let commit = result
+                        .commit
+                        .as_deref()
+                        .map(short_commit)
+                        .unwrap_or("the current commit");
+                    alert.set(Alert::success(match action {
+                        GitAction::Push if result.changes == 0 => {
+                            "The repository is already up to date.".to_string()
+                        }
+                        GitAction::Push => format!(
+                            "Pushed {} as {commit}.",
+                            maybe_plural(result.changes, "change", "changes")
+                        ),
+                        GitAction::Pull => format!(
+                            "Applied {} from {commit}.",
+                            maybe_plural(result.changes, "change", "changes")
+                        ),
+                    }));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let current_status = move || status.get().and_then(|status| status.ok());
+    let is_enabled = move || current_status().is_some_and(|status| status.enabled);
+
+    view! {
+        <FormSection title="Sync Status".to_string()>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            {move || match status.get() {
+                None => {
+                    view! {
+                        <FormItem label="Status">
+                            <span class="text-sm text-gray-600 dark:text-gray-400">Loading...</span>
+                        </FormItem>
+                    }
+                        .into_view()
+                }
+                Some(Err(http::Error::Unauthorized)) => {
+                    use_navigate()("/login", Default::default());
+                    view! { <div></div> }.into_view()
+                }
+                Some(Err(err)) => {
+                    alert.set(Alert::from(err));
+                    view! { <div></div> }.into_view()
+                }
+                Some(Ok(status)) => view! { <GitSyncStatusView status=status/> }.into_view(),
+            }}
+            <FormItem label="Actions">
+                <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                    <Button
+                        text="Push now"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            sync.dispatch(GitAction::Push);
+                        }
+
+                        disabled=Signal::derive(move || pending.get() || !is_enabled())
+                    >
+
+                        <IconArrowUpTray/>
+                    </Button>
+                    <Button
+                        text="Pull and apply"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            modal
+                                .set(
+                                    Modal::with_title("Pull configuration")
+                                        .with_message(
+                                            concat!(
+                                                "The settings stored in the repository will replace the ",
+                                                "current configuration and be reloaded. A snapshot is taken ",
+                                                "first so the change can be rolled back.",
+                                            ),
+                                        )
+                                        .with_button("Pull and apply")
+                                        .with_dangerous_callback(move || {
+                                            sync.dispatch(GitAction::Pull);
+                                        }),
+                                );
+                        }
+
+                        disabled=Signal::derive(move || pending.get() || !is_enabled())
+                    >
+
+                        <IconArrowDownTray/>
+                    </Button>
+                    <Button
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            status.refetch();
+                        }
+
+                        disabled=pending
+                    >
+
+                        <IconRefresh/>
+                    </Button>
+                </div>
+            </FormItem>
+        </FormSection>
+    }
+}
+
+#[component]
+fn GitSyncStatusView(status: GitSyncStatus) -> impl IntoView {
+    let (color, label) = if !status.enabled {
+        (Color::Gray, "Disabled".to_string())
+    } else if status.error.is_some() {
+        (Color::Red, "Failing".to_string())
+    } else if status.pending_changes > 0 {
+        (
+            Color::Yellow,
+            format!(
+                "{} not pushed",
+                maybe_plural(status.pending_changes as usize, "change", "changes")
+            ),
+        )
+    } else if status.head.is_some() && status.head != status.remote_head {
+        (Color::Yellow, "Remote has new commits".to_string())
+    } else {
+        (Color::Green, "In sync".to_string())
+    };
+    let format_time = |time: Option<DateTime<Utc>>| {
+        time.map(|time| time.format_date_time())
+            .unwrap_or_else(|| "Never".to_string())
+    };
+    let commits = format!(
+        "Local {}, remote {}",
+        status.head.as_deref().map(short_commit).unwrap_or("none"),
+        status
+            .remote_head
+            .as_deref()
+            .map(short_commit)
+            .unwrap_or("none"),
+    );
+
+    view! {
+        <FormItem label="Status">
+            <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                <Badge color=color>{label}</Badge>
+                {status
+                    .error
+                    .map(|error| {
+                        view! { <span class="text-sm text-red-600 dark:text-red-500">{error}</span> }
+                    })}
+
+            </div>
+        </FormItem>
+        <FormItem label="Commits">
+            <span class="text-sm font-mono text-gray-600 dark:text-gray-400">{commits}</span>
+        </FormItem>
+        <FormItem label="Last push">
+            <span class="text-sm text-gray-600 dark:text-gray-400">
+                {format_time(status.last_push)}
+            </span>
+        </FormItem>
+        <FormItem label="Last pull">
+            <span class="text-sm text-gray-600 dark:text-gray-400">
+                {format_time(status.last_pull)}
+            </span>
+        </FormItem>
+    }
+}
+
+async fn push(auth: &AccessToken) -> http::Result<GitSyncResult> {
+    HttpRequest::post("/api/settings/git/push")
+        .with_authorization(auth)
+        .send::<GitSyncResult>()
+        .await
+}
+
+async fn pull(
+    auth: &AccessToken,
+    schemas: &Schemas,
+) -> http::Result<(GitSyncResult, ReloadOutcome)> {
+    create_snapshot(auth, schemas, "Before pulling configuration from Git").await?;
+
+    let result = HttpRequest::post("/api/settings/git/pull")
+        .with_authorization(auth)
+        .send::<GitSyncResult>()
+        .await?;
+    let reload = reload_settings(auth, "").await?;
+
+    Ok((result, reload))
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
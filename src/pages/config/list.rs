@@ -11,7 +11,7 @@ use leptos_router::*;
 
 use crate::{
     components::{
-        icon::{IconAdd, IconRefresh, IconTrash},
+        icon::{IconAdd, IconAdjustmentsHorizontal, IconRefresh, IconTrash},
         list::{
             header::ColumnList,
             pagination::Pagination,
@@ -33,7 +33,12 @@ use crate::{
         url::UrlBuilder,
     },
     pages::{
-        config::{ReloadSettings, SchemaType, Schemas, SettingsValues},
+        config::{
+            bulk::BulkEditPanel,
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            snapshot::create_snapshot,
+            SchemaType, Schemas, SettingsValues,
+        },
         maybe_plural, List,
     },
 };
@@ -42,7 +47,7 @@ use super::{Schema, Settings, UpdateSettings};
 
 #[component]
 pub fn SettingsList() -> impl IntoView {
-    let schemas = expect_context::<Arc<Schemas>>();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
     let query = use_query_map();
     let page = create_memo(move |_| {
         query
@@ -63,15 +68,18 @@ pub fn SettingsList() -> impl IntoView {
         })
     });
     let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    let bulk_edit = create_rw_signal(None::<ItemSelection>);
+    let cluster_results = create_rw_signal(None);
     let params = use_params_map();
     let current_schema = create_memo(move |_| {
         if let Some(schema) = params
             .get()
             .get("object")
-            .and_then(|id| schemas.schemas.get(id.as_str()))
+            .and_then(|id| schemas.get_value().schemas.get(id.as_str()).cloned())
         {
             selected.set(ItemSelection::None);
-            schema.clone()
+            bulk_edit.set(None);
+            schema
         } else {
             use_navigate()("/404", Default::default());
             Arc::new(Schema::default())
@@ -105,19 +113,16 @@ pub fn SettingsList() -> impl IntoView {
 
     let reload_config_action = create_action(move |()| {
         let schema = current_schema.get();
+        let schemas = schemas.get_value();
         let auth = auth.get();
 
         async move {
-            match HttpRequest::get(format!(
-                "/api/reload/{}",
-                schema.reload_prefix.unwrap_or_default()
-            ))
-            .with_authorization(&auth)
-            .send::<ReloadSettings>()
-            .await
-            {
-                Ok(result) => {
-                    alert.set(Alert::from(result));
+            match reload_settings(&auth, schema.reload_prefix.unwrap_or_default()).await {
+                Ok(ReloadOutcome::Local(result)) => {
+                    alert.set(Alert::from_reload(result, &schemas));
+                }
+                Ok(ReloadOutcome::Cluster(results)) => {
+                    cluster_results.set(Some(results));
                 }
                 Err(http::Error::Unauthorized) => {
                     use_navigate()("/login", Default::default());
@@ -174,6 +179,24 @@ pub fn SettingsList() -> impl IntoView {
                 ItemSelection::None => unreachable!(),
             };
 
+            if let Err(err) = create_snapshot(
+                &auth,
+                &schemas.get_value(),
+                format!(
+                    "Before deleting {}",
+                    maybe_plural(
+                        items.total_selected(total_results.get_untracked()),
+                        schema.name_singular,
+                        schema.name_plural,
+                    )
+                ),
+            )
+            .await
+            {
+                alert.set(Alert::from(err));
+                return;
+            }
+
             match HttpRequest::post("/api/settings")
                 .with_authorization(&auth)
                 .with_body(updates)
@@ -201,6 +224,36 @@ pub fn SettingsList() -> impl IntoView {
 
     view! {
         <ListSection>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=Signal::derive(move || {
+                    current_schema.get().reload_prefix.unwrap_or_default().to_string()
+                })
+                on_close=move |_| cluster_results.set(None)
+            />
+            {move || {
+                bulk_edit
+                    .get()
+                    .map(|selection| {
+                        view! {
+                            <BulkEditPanel
+                                schema=current_schema.get()
+                                selection
+                                filter=filter.get()
+                                total=total_results.get_untracked().unwrap_or_default()
+                                cluster_results
+                                on_close=move |changed| {
+                                    bulk_edit.set(None);
+                                    if changed {
+                                        selected.set(ItemSelection::None);
+                                        settings.refetch();
+                                    }
+                                }
+                            />
+                        }
+                    })
+            }}
+
             <ListTable
                 title=Signal::derive(move || { current_schema.get().list.title.to_string() })
                 subtitle=Signal::derive(move || { current_schema.get().list.subtitle.to_string() })
@@ -260,6 +313,39 @@ pub fn SettingsList() -> impl IntoView {
                         <IconTrash/>
                     </ToolbarButton>
 
+                    <Show when=move || {
+                        let schema = current_schema.get();
+                        schema.can_edit() && matches!(schema.typ, SchemaType::Record { .. })
+                    }>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                let ns = selected.get().total_selected(total_results.get());
+                                if ns > 0 {
+                                    format!("Bulk edit ({ns})")
+                                } else {
+                                    "Bulk edit".to_string()
+                                }
+                            })
+
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                if selected.get().total_selected(total_results.get()) > 0 {
+                                    bulk_edit.set(Some(selected.get()));
+                                } else {
+                                    alert
+                                        .set(
+                                            Alert::warning(
+                                                "Select the entries to edit from the list first.",
+                                            ),
+                                        );
+                                }
+                            })
+                        >
+
+                            <IconAdjustmentsHorizontal size=16 attr:class="flex-shrink-0 size-3"/>
+                        </ToolbarButton>
+                    </Show>
+
                     <ToolbarButton
                         text="Reload config"
 
@@ -408,14 +494,25 @@ fn SettingsItem(settings: Settings, schema: Arc<Schema>) -> impl IntoView {
         .unwrap_or_default();
     let edit_link = if schema.can_edit() {
         let edit_url = format!("/settings/{}/{}/edit", schema.id, setting_id);
+        let duplicate_url = UrlBuilder::new(format!("/settings/{}/edit", schema.id))
+            .with_parameter("from", setting_id.clone())
+            .finish();
         Some(view! {
             <ListItem subclass="px-6 py-1.5">
-                <a
-                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                    href=edit_url
-                >
-                    Edit
-                </a>
+                <div class="flex gap-x-3">
+                    <a
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        href=edit_url
+                    >
+                        Edit
+                    </a>
+                    <a
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        href=duplicate_url
+                    >
+                        Duplicate
+                    </a>
+                </div>
             </ListItem>
         })
     } else {
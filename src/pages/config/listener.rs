@@ -0,0 +1,324 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{net::SocketAddr, sync::Arc};
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        form::{
+            button::Button, renderer::use_field_renderers, Form, FormButtonBar, FormItem,
+            FormSection,
+        },
+        messages::alert::{use_alerts, Alert, Alerts},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::Schemas,
+    },
+    pages::{
+        config::{edit::render_field, Settings, SettingsValues},
+        List,
+    },
+};
+
+pub const LISTENER_SCHEMA_ID: &str = "listener";
+const BIND_FIELD: &str = "bind";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindConflict {
+    pub bind: SocketAddr,
+    pub existing: SocketAddr,
+    pub listener_id: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ListenerPreset {
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
+    protocol: &'static str,
+    port: u16,
+    implicit_tls: bool,
+}
+
+static PRESETS: &[ListenerPreset] = &[
+    ListenerPreset {
+        id: "smtp",
+        title: "SMTP",
+        description: "Mail from other servers, port 25 with STARTTLS",
+        protocol: "smtp",
+        port: 25,
+        implicit_tls: false,
+    },
+    ListenerPreset {
+        id: "submission",
+        title: "Submission",
+        description: "Mail from clients, port 587 with STARTTLS",
+        protocol: "smtp",
+        port: 587,
+        implicit_tls: false,
+    },
+    ListenerPreset {
+        id: "submissions",
+        title: "SMTPS",
+        description: "Mail from clients, port 465 with implicit TLS",
+        protocol: "smtp",
+        port: 465,
+        implicit_tls: true,
+    },
+    ListenerPreset {
+        id: "imap",
+        title: "IMAP",
+        description: "Mail access, port 143 with STARTTLS",
+        protocol: "imap",
+        port: 143,
+        implicit_tls: false,
+    },
+    ListenerPreset {
+        id: "imaptls",
+        title: "IMAPS",
+        description: "Mail access, port 993 with implicit TLS",
+        protocol: "imap",
+        port: 993,
+        implicit_tls: true,
+    },
+    ListenerPreset {
+        id: "https",
+        title: "HTTPS",
+        description: "JMAP, WebDAV and the web admin, port 443 with implicit TLS",
+        protocol: "http",
+        port: 443,
+        implicit_tls: true,
+    },
+    ListenerPreset {
+        id: "http",
+        title: "HTTP",
+        description: "Plain HTTP on port 8080, for use behind a reverse proxy",
+        protocol: "http",
+        port: 8080,
+        implicit_tls: false,
+    },
+];
+
+#[component]
+pub fn ListenerWizard() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schema = expect_context::<Arc<Schemas>>().get(LISTENER_SCHEMA_ID);
+    let renderers = store_value(use_field_renderers());
+    let data = FormData::from_settings(schema.clone(), None).into_signal();
+    let selected = create_rw_signal(None::<&'static str>);
+    let (pending, set_pending) = create_signal(false);
+
+    let apply_preset = move |preset: &ListenerPreset| {
+        selected.set(Some(preset.id));
+        data.update(|data| {
+            data.set("_id", preset.id);
+            data.set("protocol", preset.protocol);
+            data.array_set(BIND_FIELD, [format!("[::]:{}", preset.port)]);
+            data.set("tls.implicit", preset.implicit_tls.to_string());
+        });
+    };
+
+    let create_listener = create_action(move |_: &()| {
+        let auth = auth.get();
+        let form = data.get_untracked();
+        let updates = form.build_update();
+
+        async move {
+            set_pending.set(true);
+            let result = async {
+                let listeners = HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "server.listener")
+                    .with_parameter("suffix", "protocol")
+                    .send::<List<Settings>>()
+                    .await?
+                    .items;
+                let conflicts = find_conflicts(&form, &listeners);
+                if !conflicts.is_empty() {
+                    return Ok(Some(conflicts));
+                }
+
+                HttpRequest::post("/api/settings")
+                    .with_authorization(&auth)
+                    .with_body(updates)
+                    .unwrap()
+                    .send::<Option<String>>()
+                    .await
+                    .map(|_| None)
+            }
+            .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(Some(conflicts)) => {
+                    alert.set(
+                        Alert::error("The listener conflicts with existing listeners")
+                            .with_details(conflicts.join("\n")),
+                    );
+                }
+                Ok(None) => {
+                    alert.set(Alert::success(format!(
+                        "Listener {} created, restart the server to start accepting connections on it.",
+                        form.value_as_str("_id").unwrap_or_default()
+                    )));
+                    use_navigate()("/settings/listener", Default::default());
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let fields = ["_id", "protocol", "bind", "tls.implicit"]
+        .into_iter()
+        .filter_map(|id| schema.fields.get(id).cloned())
+        .map(|field| {
+            let label = field.label_form_text();
+            let help = field.help_text().unwrap_or_default();
+            let component = renderers
+                .with_value(|renderers| render_field(&schema, field, data, false, renderers));
+            view! {
+                <FormItem label tooltip=help>
+                    {component}
+                </FormItem>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <Form
+            title="Listener wizard"
+            subtitle="Create a listener for a common service, then adjust it to your needs"
+        >
+            <Alerts/>
+            <FormSection title="Service">
+                <div class="grid sm:grid-cols-2 lg:grid-cols-3 gap-3">
+                    {PRESETS
+                        .iter()
+                        .map(|preset| {
+                            view! {
+                                <button
+                                    type="button"
+                                    class="p-4 text-start bg-white border rounded-xl shadow-sm hover:shadow-md transition dark:bg-slate-900 dark:border-gray-700"
+                                    class:border-blue-600=move || selected.get() == Some(preset.id)
+                                    class:ring-1=move || selected.get() == Some(preset.id)
+                                    class:ring-blue-600=move || selected.get() == Some(preset.id)
+                                    on:click=move |_| apply_preset(preset)
+                                >
+                                    <span class="block font-semibold text-gray-800 dark:text-gray-200">
+                                        {format!("{} ({})", preset.title, preset.port)}
+                                    </span>
+                                    <span class="block mt-1 text-sm text-gray-500">
+                                        {preset.description}
+                                    </span>
+                                </button>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+            </FormSection>
+            <FormSection title="Listener">{fields}</FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/settings/listener", Default::default());
+                    }
+                />
+
+                <Button
+                    text="Create listener"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        if data.try_update(|data| data.validate_form()).unwrap_or_default() {
+                            create_listener.dispatch(());
+                        }
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+fn find_conflicts(form: &FormData, listeners: &[Settings]) -> Vec<String> {
+    let id = form.value_as_str("_id").unwrap_or_default();
+    let mut conflicts = Vec::new();
+
+    if listeners
+        .iter()
+        .any(|listener| listener.get("_id").is_some_and(|listener| listener == id))
+    {
+        conflicts.push(format!("A listener with id {id} already exists."));
+    }
+    for conflict in bind_conflicts(form, listeners) {
+        conflicts.push(format!(
+            "{} overlaps with {} used by listener {}.",
+            conflict.bind, conflict.existing, conflict.listener_id
+        ));
+    }
+
+    conflicts
+}
+
+// Addresses of the listener being edited that are already bound by one of
+// the other listeners. Validation only sees this listener, so the others have
+// to be loaded by the caller.
+pub fn bind_conflicts(form: &FormData, listeners: &[Settings]) -> Vec<BindConflict> {
+    let own_id = form.value_as_str("_id").unwrap_or_default();
+    let binds = form
+        .array_value(BIND_FIELD)
+        .filter_map(|bind| bind.trim().parse::<SocketAddr>().ok())
+        .collect::<Vec<_>>();
+    let mut conflicts = Vec::new();
+
+    for listener in listeners {
+        let listener_id = listener
+            .get("_id")
+            .map(|id| id.as_str())
+            .unwrap_or_default();
+        if listener_id == own_id {
+            continue;
+        }
+        for (_, existing) in listener.array_values(BIND_FIELD) {
+            let Ok(existing) = existing.trim().parse::<SocketAddr>() else {
+                continue;
+            };
+            for bind in &binds {
+                if binds_overlap(bind, &existing) {
+                    conflicts.push(BindConflict {
+                        bind: *bind,
+                        existing,
+                        listener_id: listener_id.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+// Two sockets overlap when they use the same port on the same address, or
+// when either of them binds every address.
+fn binds_overlap(a: &SocketAddr, b: &SocketAddr) -> bool {
+    a.port() == b.port() && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
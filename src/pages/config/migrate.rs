@@ -0,0 +1,687 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconArrowUpTray, IconCancel},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{config::snapshot::create_snapshot, maybe_plural},
+};
+
+use super::{
+    cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+    SchemaType, Schemas, UpdateSettings,
+};
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LegacySource {
+    Postfix,
+    Dovecot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Convert {
+    Copy,
+    Size,
+    Duration,
+    Bool,
+    InvertedBool,
+    Text,
+    // Any non-empty value enables the setting
+    Enabled,
+}
+
+struct Translation {
+    source: LegacySource,
+    directive: &'static str,
+    key: &'static str,
+    convert: Convert,
+}
+
+static TRANSLATIONS: &[Translation] = &[
+    // Postfix main.cf
+    Translation::postfix("myhostname", "lookup.default.hostname", Convert::Copy),
+    Translation::postfix("mydomain", "lookup.default.domain", Convert::Copy),
+    Translation::postfix(
+        "message_size_limit",
+        "session.data.limits.size",
+        Convert::Size,
+    ),
+    Translation::postfix(
+        "smtpd_recipient_limit",
+        "session.rcpt.max-recipients",
+        Convert::Copy,
+    ),
+    Translation::postfix(
+        "smtpd_hard_error_limit",
+        "session.rcpt.errors.total",
+        Convert::Copy,
+    ),
+    Translation::postfix("smtpd_banner", "session.connect.greeting", Convert::Text),
+    Translation::postfix("smtpd_timeout", "session.timeout", Convert::Duration),
+    Translation::postfix("smtpd_helo_required", "session.ehlo.require", Convert::Bool),
+    Translation::postfix(
+        "disable_vrfy_command",
+        "session.extensions.vrfy",
+        Convert::InvertedBool,
+    ),
+    Translation::postfix(
+        "recipient_delimiter",
+        "session.rcpt.sub-addressing",
+        Convert::Enabled,
+    ),
+    Translation::postfix(
+        "maximal_queue_lifetime",
+        "queue.schedule.expire",
+        Convert::Duration,
+    ),
+    Translation::postfix(
+        "smtp_connect_timeout",
+        "queue.outbound.timeouts.connect",
+        Convert::Duration,
+    ),
+    Translation::postfix(
+        "smtp_helo_timeout",
+        "queue.outbound.timeouts.ehlo",
+        Convert::Duration,
+    ),
+    Translation::postfix(
+        "smtp_mail_timeout",
+        "queue.outbound.timeouts.mail-from",
+        Convert::Duration,
+    ),
+    Translation::postfix(
+        "smtp_rcpt_timeout",
+        "queue.outbound.timeouts.rcpt-to",
+        Convert::Duration,
+    ),
+    Translation::postfix(
+        "smtp_data_done_timeout",
+        "queue.outbound.timeouts.data",
+        Convert::Duration,
+    ),
+    // Dovecot
+    Translation::dovecot("hostname", "lookup.default.hostname", Convert::Copy),
+    Translation::dovecot(
+        "disable_plaintext_auth",
+        "imap.auth.allow-plain-text",
+        Convert::InvertedBool,
+    ),
+    Translation::dovecot(
+        "imap_max_line_length",
+        "imap.request.max-size",
+        Convert::Size,
+    ),
+    Translation::dovecot(
+        "mail_max_userip_connections",
+        "imap.rate-limit.concurrent",
+        Convert::Copy,
+    ),
+    Translation::dovecot(
+        "sieve_max_script_size",
+        "sieve.untrusted.limits.script-size",
+        Convert::Size,
+    ),
+    Translation::dovecot(
+        "sieve_max_redirects",
+        "sieve.untrusted.limits.redirects",
+        Convert::Copy,
+    ),
+    Translation::dovecot(
+        "sieve_quota_max_scripts",
+        "sieve.untrusted.limits.max-scripts",
+        Convert::Copy,
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Directive {
+    line: usize,
+    section: String,
+    name: String,
+    value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Mapping {
+    Mapped {
+        key: &'static str,
+        value: String,
+        // Schema and label of the form field holding the setting
+        field: Option<(&'static str, &'static str)>,
+    },
+    Unmapped {
+        reason: String,
+    },
+}
+
+#[component]
+pub fn LegacyImport() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+
+    let source = create_rw_signal(LegacySource::Postfix);
+    let contents = create_rw_signal(String::new());
+    let rows = create_rw_signal(Vec::<(Directive, Mapping)>::new());
+    let cluster_results = create_rw_signal(None);
+    let mapped = create_memo(move |_| {
+        rows.with(|rows| {
+            rows.iter()
+                .filter_map(|(_, mapping)| match mapping {
+                    Mapping::Mapped { key, value, .. } => Some((key.to_string(), value.clone())),
+                    Mapping::Unmapped { .. } => None,
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let analyze = move || {
+        let source = source.get_untracked();
+        let directives = contents.with_untracked(|contents| match source {
+            LegacySource::Postfix => parse_postfix(contents),
+            LegacySource::Dovecot => parse_dovecot(contents),
+        });
+        if directives.is_empty() {
+            alert.set(Alert::warning(
+                "No directives were found in the pasted text.",
+            ));
+        } else {
+            rows.set(
+                directives
+                    .into_iter()
+                    .map(|directive| {
+                        let mapping =
+                            schemas.with_value(|schemas| translate(source, &directive, schemas));
+                        (directive, mapping)
+                    })
+                    .collect(),
+            );
+        }
+    };
+
+    let apply_action = create_action(move |values: &Vec<(String, String)>| {
+        let values = values.clone();
+        let auth = auth.get();
+        let source = source.get_untracked();
+
+        async move {
+            match apply_settings(&auth, &schemas.get_value(), source, values).await {
+                Ok(ReloadOutcome::Local(result)) if !result.errors.is_empty() => {
+                    alert.set(Alert::from_reload(result, &schemas.get_value()));
+                }
+                Ok(outcome) => {
+                    if let ReloadOutcome::Cluster(results) = outcome {
+                        cluster_results.set(Some(results));
+                    }
+                    rows.set(vec![]);
+                    contents.set(String::new());
+                    alert.set(Alert::success(concat!(
+                        "The mapped settings have been applied and the configuration reloaded. ",
+                        "A snapshot of the previous settings is available on the Snapshots page."
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <ListSection>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            <ListTable
+                title="Migration assistant"
+                subtitle="Translate Postfix and Dovecot configuration into Stalwart settings"
+            >
+                <Toolbar slot>
+                    <Show when=move || !rows.with(|rows| rows.is_empty())>
+                        <ToolbarButton
+                            text="Clear"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                rows.set(vec![]);
+                            })
+                        >
+
+                            <IconCancel/>
+                        </ToolbarButton>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                format!(
+                                    "Apply {}",
+                                    maybe_plural(mapped.get().len(), "setting", "settings"),
+                                )
+                            })
+
+                            color=Color::Blue
+                            on_click=Callback::new(move |_| {
+                                let values = mapped.get_untracked();
+                                if values.is_empty() || apply_action.pending().get_untracked() {
+                                    return;
+                                }
+                                modal
+                                    .set(
+                                        Modal::with_title("Apply settings")
+                                            .with_message(
+                                                format!(
+                                                    "{} will be written and the configuration reloaded. Unmapped directives are ignored. Do you want to continue?",
+                                                    maybe_plural(values.len(), "setting", "settings"),
+                                                ),
+                                            )
+                                            .with_button("Apply")
+                                            .with_callback(move || {
+                                                apply_action.dispatch(values.clone());
+                                            }),
+                                    );
+                            })
+                        >
+
+                            <IconArrowUpTray/>
+                        </ToolbarButton>
+                    </Show>
+                </Toolbar>
+
+                {move || {
+                    if rows.with(|rows| rows.is_empty()) {
+                        view! {
+                            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700 space-y-3">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    Paste the contents of a Postfix main.cf or a Dovecot configuration file.
+                                    Recognized directives are translated to the equivalent settings, the rest are listed so they can be reviewed by hand.
+                                </p>
+                                <select
+                                    class=INPUT_CLASS
+                                    on:change=move |ev| {
+                                        source
+                                            .set(
+                                                if event_target_value(&ev) == "dovecot" {
+                                                    LegacySource::Dovecot
+                                                } else {
+                                                    LegacySource::Postfix
+                                                },
+                                            );
+                                    }
+                                >
+                                    <option
+                                        value="postfix"
+                                        selected=move || source.get() == LegacySource::Postfix
+                                    >
+                                        Postfix (main.cf)
+                                    </option>
+                                    <option
+                                        value="dovecot"
+                                        selected=move || source.get() == LegacySource::Dovecot
+                                    >
+                                        Dovecot (dovecot.conf)
+                                    </option>
+                                </select>
+                                <textarea
+                                    class=INPUT_CLASS
+                                    rows="12"
+                                    placeholder="myhostname = mail.example.org"
+                                    prop:value=move || contents.get()
+                                    on:input=move |ev| contents.set(event_target_value(&ev))
+                                ></textarea>
+                                <button
+                                    type="button"
+                                    class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 disabled:pointer-events-none"
+                                    disabled=move || contents.with(|contents| contents.trim().is_empty())
+                                    on:click=move |_| analyze()
+                                >
+                                    Analyze
+                                </button>
+                            </div>
+                            <ZeroResults
+                                title="Nothing to import"
+                                subtitle="Paste a configuration file to preview how it translates."
+                            />
+                        }
+                            .into_view()
+                    } else {
+                        let rows = rows.get();
+                        let num_mapped = mapped.get().len();
+                        view! {
+                            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    {format!(
+                                        "{} found, {} mapped to settings.",
+                                        maybe_plural(rows.len(), "directive", "directives"),
+                                        num_mapped,
+                                    )}
+
+                                </p>
+                            </div>
+                            <ColumnList headers=vec![
+                                "Line".to_string(),
+                                "Directive".to_string(),
+                                "Setting".to_string(),
+                                "Status".to_string(),
+                            ]>
+
+                                {rows
+                                    .into_iter()
+                                    .map(|(directive, mapping)| {
+                                        view! { <DirectiveItem directive mapping/> }
+                                    })
+                                    .collect_view()}
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn DirectiveItem(directive: Directive, mapping: Mapping) -> impl IntoView {
+    let name = if directive.section.is_empty() {
+        directive.name
+    } else {
+        format!("{} › {}", directive.section, directive.name)
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">{directive.line}</span>
+            </ListItem>
+            <ListItem>
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {name}
+                </span>
+                <code class="block text-xs text-gray-500 break-all">{directive.value}</code>
+            </ListItem>
+            {match mapping {
+                Mapping::Mapped { key, value, field } => {
+                    view! {
+                        <ListItem>
+                            {field
+                                .map(|(schema_id, label)| {
+                                    view! {
+                                        <a
+                                            class="block text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                            href=format!("/settings/{schema_id}/edit")
+                                        >
+                                            {label}
+                                        </a>
+                                    }
+                                })}
+                            <code class="block text-xs text-gray-500">{format!("{key} = {value}")}</code>
+                        </ListItem>
+                        <ListItem>
+                            <Badge color=Color::Green>Mapped</Badge>
+                        </ListItem>
+                    }
+                        .into_view()
+                }
+                Mapping::Unmapped { reason } => {
+                    view! {
+                        <ListTextItem>{reason}</ListTextItem>
+                        <ListItem>
+                            <Badge color=Color::Gray>Unmapped</Badge>
+                        </ListItem>
+                    }
+                        .into_view()
+                }
+            }}
+
+        </tr>
+    }
+}
+
+async fn apply_settings(
+    auth: &AccessToken,
+    schemas: &Schemas,
+    source: LegacySource,
+    values: Vec<(String, String)>,
+) -> http::Result<ReloadOutcome> {
+    create_snapshot(
+        auth,
+        schemas,
+        match source {
+            LegacySource::Postfix => "Before importing Postfix settings",
+            LegacySource::Dovecot => "Before importing Dovecot settings",
+        },
+    )
+    .await?;
+
+    // Expressions store their conditions under the setting key, these are
+    // replaced by the imported value.
+    let mut updates = values
+        .iter()
+        .map(|(key, _)| UpdateSettings::Clear {
+            prefix: format!("{key}."),
+            filter: None,
+        })
+        .collect::<Vec<_>>();
+    updates.push(UpdateSettings::Insert {
+        prefix: None,
+        values,
+        assert_empty: false,
+    });
+
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(updates)?
+        .send::<serde_json::Value>()
+        .await?;
+    reload_settings(auth, "").await
+}
+
+fn parse_postfix(contents: &str) -> Vec<Directive> {
+    let mut directives: Vec<Directive> = Vec::new();
+
+    for (idx, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        } else if line.starts_with(char::is_whitespace) {
+            // Continuation of the previous parameter
+            if let Some(directive) = directives.last_mut() {
+                directive.value.push(' ');
+                directive.value.push_str(trimmed);
+                continue;
+            }
+        }
+
+        let (name, value) = trimmed.split_once('=').unwrap_or((trimmed, ""));
+        directives.push(Directive {
+            line: idx + 1,
+            section: String::new(),
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+
+    directives
+}
+
+fn parse_dovecot(contents: &str) -> Vec<Directive> {
+    let mut directives = Vec::new();
+    let mut sections: Vec<String> = Vec::new();
+
+    for (idx, line) in contents.lines().enumerate() {
+        let line = line.split_once(" #").map_or(line, |(line, _)| line).trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        } else if line == "}" {
+            sections.pop();
+        } else if let Some(section) = line.strip_suffix('{') {
+            sections.push(section.trim().to_string());
+        } else {
+            let (name, value) = line.split_once('=').unwrap_or((line, ""));
+            directives.push(Directive {
+                line: idx + 1,
+                section: sections.join(" › "),
+                name: name.trim().to_string(),
+                value: value.trim().trim_matches('"').to_string(),
+            });
+        }
+    }
+
+    directives
+}
+
+fn translate(source: LegacySource, directive: &Directive, schemas: &Schemas) -> Mapping {
+    let Some(translation) = TRANSLATIONS
+        .iter()
+        .find(|t| t.source == source && t.directive == directive.name)
+    else {
+        return Mapping::Unmapped {
+            reason: "No equivalent setting".to_string(),
+        };
+    };
+
+    match translation.convert.apply(source, &directive.value) {
+        Ok(value) => Mapping::Mapped {
+            key: translation.key,
+            value,
+            field: schemas.schemas.values().find_map(|schema| {
+                (schema.typ == SchemaType::List)
+                    .then(|| schema.fields.get(translation.key))
+                    .flatten()
+                    .map(|field| (schema.id, field.label_form))
+            }),
+        },
+        Err(reason) => Mapping::Unmapped { reason },
+    }
+}
+
+impl Translation {
+    const fn postfix(directive: &'static str, key: &'static str, convert: Convert) -> Self {
+        Translation {
+            source: LegacySource::Postfix,
+            directive,
+            key,
+            convert,
+        }
+    }
+
+    const fn dovecot(directive: &'static str, key: &'static str, convert: Convert) -> Self {
+        Translation {
+            source: LegacySource::Dovecot,
+            directive,
+            key,
+            convert,
+        }
+    }
+}
+
+impl Convert {
+    fn apply(&self, source: LegacySource, value: &str) -> Result<String, String> {
+        let variable = match source {
+            LegacySource::Postfix => '$',
+            LegacySource::Dovecot => '%',
+        };
+        if value.contains(variable) {
+            return Err("Refers to other parameters, translate by hand".to_string());
+        } else if value.is_empty() && *self != Convert::Enabled {
+            return Err("Empty value".to_string());
+        }
+
+        match self {
+            Convert::Copy => Ok(value.to_string()),
+            Convert::Size => parse_size(value).map(|size| size.to_string()),
+            Convert::Duration => parse_duration(value),
+            Convert::Bool | Convert::InvertedBool => {
+                let enabled = match value.to_ascii_lowercase().as_str() {
+                    "yes" | "true" | "on" | "1" => true,
+                    "no" | "false" | "off" | "0" => false,
+                    _ => return Err(format!("Invalid boolean {value:?}")),
+                };
+                Ok((enabled ^ (*self == Convert::InvertedBool)).to_string())
+            }
+            Convert::Text => Ok(format!("'{}'", value.replace('\'', "\\'"))),
+            Convert::Enabled => Ok((!value.is_empty()).to_string()),
+        }
+    }
+}
+
+fn split_unit(value: &str) -> (&str, String) {
+    let value = value.trim();
+    let pos = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    (&value[..pos], value[pos..].trim().to_ascii_lowercase())
+}
+
+// Plain byte counts in Postfix, Dovecot also accepts k, M and G suffixes
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, unit) = split_unit(value);
+    let multiplier = match unit.chars().next() {
+        None | Some('b') => 1,
+        Some('k') => 1024,
+        Some('m') => 1024 * 1024,
+        Some('g') => 1024 * 1024 * 1024,
+        Some(_) => return Err(format!("Invalid size {value:?}")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("Invalid size {value:?}"))
+}
+
+// Postfix uses single letter units and defaults to seconds, Dovecot spells
+// them out ("30 secs", "5 mins").
+fn parse_duration(value: &str) -> Result<String, String> {
+    let (number, unit) = split_unit(value);
+    let number = number
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid duration {value:?}"))?;
+
+    if unit.starts_with("ms") {
+        Ok(format!("{number}ms"))
+    } else {
+        match unit.chars().next() {
+            None | Some('s') => Ok(format!("{number}s")),
+            Some('m') => Ok(format!("{number}m")),
+            Some('h') => Ok(format!("{number}h")),
+            Some('d') => Ok(format!("{number}d")),
+            Some('w') => Ok(format!("{}d", number * 7)),
+            Some(_) => Err(format!("Invalid duration {value:?}")),
+        }
+    }
+}
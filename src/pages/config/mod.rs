@@ -4,12 +4,30 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod acme;
+pub mod auth_mechanisms;
+pub mod bulk;
+pub mod certificate;
+pub mod cluster;
+pub mod connection;
+pub mod deploy;
 pub mod edit;
+pub mod git;
 pub mod list;
+pub mod listener;
+pub mod migrate;
+pub mod pending;
+pub mod provenance;
+pub mod quick;
+pub mod retention;
+pub mod saml;
 pub mod schema;
+pub mod scim;
 pub mod search;
+pub mod snapshot;
+pub mod usage;
 
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
 
 use crate::{
     components::{
@@ -19,20 +37,31 @@ use crate::{
             IconInboxStack, IconKey, IconServer, IconServerStack, IconShieldCheck, IconSignal,
         },
         layout::{LayoutBuilder, MenuItem},
+        messages::alert::{use_alerts, Alert},
     },
     core::{
-        form::{FormData, FormValue},
+        command::Command,
+        form::{is_secret_reference, FormData, FormValue},
+        format::format_bytes,
+        http,
+        i18n::tr,
+        oauth::use_authorization,
         schema::*,
+        Permission, Permissions,
     },
 };
 use ahash::AHashMap;
-use humansize::{format_size, DECIMAL};
-use leptos::view;
+use cluster::{reload_settings, ClusterApplyResults, NodeApplyStatus, ReloadOutcome};
+use leptos::{
+    create_rw_signal, expect_context, spawn_local, store_value, view, SignalGetUntracked,
+    SignalSet, SignalUpdate,
+};
+use leptos_router::use_navigate;
 use serde::{Deserialize, Serialize};
 
 pub type Settings = AHashMap<String, String>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "camelCase")]
 pub enum UpdateSettings {
@@ -77,6 +106,194 @@ pub enum ConfigError {
     Macro { error: String },
 }
 
+// A configuration error or warning returned by a reload, with the form where
+// the offending setting can be edited when it belongs to a known schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadMessage {
+    pub is_error: bool,
+    pub text: String,
+    pub location: Option<SettingLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingLocation {
+    pub title: String,
+    pub url: String,
+}
+
+impl ReloadSettings {
+    // Settings are located with the schemas captured by the caller, results
+    // are usually processed after an await when the context is gone.
+    pub fn messages(&self, schemas: &Schemas) -> Vec<ReloadMessage> {
+        self.errors
+            .iter()
+            .map(|(key, error)| {
+                let (message, error) = match error {
+                    ConfigError::Parse { error } => ("reload-error-parse", error),
+                    ConfigError::Build { error } => ("reload-error-build", error),
+                    ConfigError::Macro { error } => ("reload-error-macro", error),
+                };
+                ReloadMessage {
+                    is_error: true,
+                    text: tr(
+                        message,
+                        &[("key", format!("{key:?}")), ("error", error.clone())],
+                    ),
+                    location: schemas.locate_setting(key),
+                }
+            })
+            .chain(self.warnings.iter().map(|(key, warning)| {
+                let key_arg = ("key", format!("{key:?}"));
+                ReloadMessage {
+                    is_error: false,
+                    text: match warning {
+                        ConfigWarning::Missing => tr("reload-warning-missing", &[key_arg]),
+                        ConfigWarning::AppliedDefault { default } => tr(
+                            "reload-warning-default",
+                            &[key_arg, ("value", format!("{default:?}"))],
+                        ),
+                        ConfigWarning::Unread { value } => tr(
+                            "reload-warning-unread",
+                            &[key_arg, ("value", format!("{value:?}"))],
+                        ),
+                        ConfigWarning::Build { error } => {
+                            tr("reload-warning-build", &[key_arg, ("error", error.clone())])
+                        }
+                        ConfigWarning::Parse { error } => {
+                            tr("reload-warning-parse", &[key_arg, ("error", error.clone())])
+                        }
+                    },
+                    location: schemas.locate_setting(key),
+                }
+            }))
+            .collect()
+    }
+}
+
+impl Schemas {
+    // Maps a setting key such as "store.rocksdb.path" back to the form
+    // editing it, preferring the schema with the most specific match.
+    pub fn locate_setting(&self, key: &str) -> Option<SettingLocation> {
+        self.schemas
+            .values()
+            .filter_map(|schema| schema.locate_setting(key))
+            .max_by(|(l_score, l_loc), (r_score, r_loc)| {
+                l_score.cmp(r_score).then_with(|| r_loc.url.cmp(&l_loc.url))
+            })
+            .map(|(_, location)| location)
+    }
+
+    // Whether a setting key holds a secret, such as a store password
+    pub fn is_secret_setting(&self, key: &str) -> bool {
+        self.schemas
+            .values()
+            .any(|schema| schema.is_secret_setting(key))
+    }
+}
+
+impl Schema {
+    pub fn is_secret_setting(&self, key: &str) -> bool {
+        let field_id = match self.typ {
+            SchemaType::List => key,
+            SchemaType::Record { prefix, .. } => {
+                match key
+                    .strip_prefix(prefix)
+                    .and_then(|key| key.strip_prefix('.'))
+                    .and_then(|key| key.split_once('.'))
+                {
+                    Some((_, field_id)) => field_id,
+                    None => return false,
+                }
+            }
+            SchemaType::Entry { .. } => return false,
+        };
+
+        self.fields
+            .get(field_id)
+            .is_some_and(|field| matches!(field.typ_, Type::Secret))
+    }
+
+    fn locate_setting(&self, key: &str) -> Option<(usize, SettingLocation)> {
+        let matches_field = |key: &str, field: &Field| {
+            key == field.id
+                || key
+                    .strip_prefix(field.id)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        };
+
+        match self.typ {
+            SchemaType::List => {
+                self.form
+                    .sections
+                    .iter()
+                    .enumerate()
+                    .find_map(|(section_idx, section)| {
+                        section
+                            .fields
+                            .iter()
+                            .find(|field| matches_field(key, field))
+                            .map(|field| {
+                                (
+                                    field.id.len(),
+                                    SettingLocation {
+                                        title: if !self.form.title.is_empty() {
+                                            format!("{} › {}", self.form.title, field.label_form)
+                                        } else {
+                                            field.label_form.to_string()
+                                        },
+                                        url: format!(
+                                            "/settings/{}/edit#section-{section_idx}",
+                                            self.id
+                                        ),
+                                    },
+                                )
+                            })
+                    })
+            }
+            SchemaType::Record { prefix, .. } => {
+                let (id, rest) = key
+                    .strip_prefix(prefix)?
+                    .strip_prefix('.')?
+                    .split_once('.')
+                    .filter(|(id, _)| !id.is_empty())?;
+                let field = self
+                    .form
+                    .sections
+                    .iter()
+                    .flat_map(|section| section.fields.iter())
+                    .find(|field| matches_field(rest, field));
+
+                Some((
+                    prefix.len() + field.map_or(0, |field| field.id.len() + 1),
+                    SettingLocation {
+                        title: match field {
+                            Some(field) => {
+                                format!("{} {id} › {}", self.name_singular, field.label_form)
+                            }
+                            None => format!("{} {id}", self.name_singular),
+                        },
+                        url: format!("/settings/{}/{id}/edit", self.id),
+                    },
+                ))
+            }
+            SchemaType::Entry { prefix } => {
+                let id = key
+                    .strip_prefix(prefix)?
+                    .strip_prefix('.')
+                    .filter(|id| !id.is_empty())?;
+
+                Some((
+                    prefix.len(),
+                    SettingLocation {
+                        title: format!("{} {id}", self.name_singular),
+                        url: format!("/settings/{}/{id}/edit", self.id),
+                    },
+                ))
+            }
+        }
+    }
+}
+
 impl FormData {
     pub fn build_update(&self) -> Vec<UpdateSettings> {
         let mut updates = Vec::new();
@@ -86,10 +303,22 @@ impl FormData {
         match &self.schema.typ {
             SchemaType::Record { prefix, .. } => {
                 if self.is_update {
-                    updates.push(UpdateSettings::Clear {
-                        prefix: format!("{prefix}.{}.", self.value_as_str("_id").unwrap()),
-                        filter: None,
-                    });
+                    let key_prefix = format!("{prefix}.{}.", self.value_as_str("_id").unwrap());
+                    if self
+                        .schema
+                        .fields
+                        .keys()
+                        .any(|id| self.secret_is_retained(id))
+                    {
+                        // Clearing the object would also remove the stored secrets,
+                        // which are never sent back, so fields are removed one by one.
+                        updates.extend(self.build_deletes(&key_prefix));
+                    } else {
+                        updates.push(UpdateSettings::Clear {
+                            prefix: key_prefix,
+                            filter: None,
+                        });
+                    }
                 } else {
                     assert_empty = true;
                 }
@@ -109,26 +338,119 @@ impl FormData {
             }
             SchemaType::List => {
                 if self.is_update {
-                    let mut delete_keys = Vec::new();
-                    for field in self.schema.fields.values() {
-                        if field.is_multivalue() {
-                            updates.push(UpdateSettings::Clear {
-                                prefix: format!("{}.", field.id),
-                                filter: None,
-                            });
-                            delete_keys.push(field.id.to_string());
-                        } else if self.value_is_empty(field.id) {
-                            delete_keys.push(field.id.to_string());
-                        }
-                    }
-
-                    if !delete_keys.is_empty() {
-                        updates.push(UpdateSettings::Delete { keys: delete_keys });
-                    }
+                    updates.extend(self.build_deletes(""));
                 }
             }
         }
 
+        let key_values = self.build_key_values();
+        if !key_values.is_empty() {
+            updates.push(UpdateSettings::Insert {
+                prefix: insert_prefix,
+                values: key_values,
+                assert_empty,
+            });
+        }
+
+        updates
+    }
+
+    // Removes the fields that are multivalued or empty, secrets that were not
+    // replaced are kept.
+    fn build_deletes(&self, key_prefix: &str) -> Vec<UpdateSettings> {
+        let mut updates = Vec::new();
+        let mut delete_keys = Vec::new();
+
+        for field in self.schema.fields.values() {
+            if field.id.starts_with('_') {
+                continue;
+            } else if field.is_multivalue() {
+                updates.push(UpdateSettings::Clear {
+                    prefix: format!("{key_prefix}{}.", field.id),
+                    filter: None,
+                });
+                delete_keys.push(format!("{key_prefix}{}", field.id));
+            } else if self.value_is_empty(field.id) && !self.secret_is_retained(field.id) {
+                delete_keys.push(format!("{key_prefix}{}", field.id));
+            }
+        }
+
+        if !delete_keys.is_empty() {
+            updates.push(UpdateSettings::Delete { keys: delete_keys });
+        }
+
+        updates
+    }
+
+    // Writes back only the given fields, other settings of the object are
+    // left untouched.
+    pub fn build_fields_update(&self, fields: &[&str]) -> Vec<UpdateSettings> {
+        let (key_prefix, insert_prefix) = match &self.schema.typ {
+            SchemaType::Record { prefix, .. } => {
+                let prefix = format!("{prefix}.{}", self.value_as_str("_id").unwrap_or_default());
+                (format!("{prefix}."), Some(prefix))
+            }
+            SchemaType::List => (String::new(), None),
+            SchemaType::Entry { .. } => return vec![],
+        };
+        let mut updates = Vec::new();
+        let mut delete_keys = Vec::new();
+
+        for field in fields
+            .iter()
+            .filter_map(|id| self.schema.fields.get(id))
+            .filter(|field| !self.secret_is_retained(field.id))
+        {
+            if field.is_multivalue() {
+                updates.push(UpdateSettings::Clear {
+                    prefix: format!("{key_prefix}{}.", field.id),
+                    filter: None,
+                });
+            }
+            delete_keys.push(format!("{key_prefix}{}", field.id));
+        }
+        if !delete_keys.is_empty() {
+            updates.push(UpdateSettings::Delete { keys: delete_keys });
+        }
+
+        let key_values = self
+            .build_key_values()
+            .into_iter()
+            .filter(|(key, _)| {
+                fields.iter().any(|field| {
+                    key == field
+                        || key
+                            .strip_prefix(field)
+                            .is_some_and(|suffix| suffix.starts_with('.'))
+                })
+            })
+            .collect::<Vec<_>>();
+        if !key_values.is_empty() {
+            updates.push(UpdateSettings::Insert {
+                prefix: insert_prefix,
+                values: key_values,
+                assert_empty: false,
+            });
+        }
+
+        updates
+    }
+
+    // Displayed secrets that were stored previously and left unchanged
+    pub fn retained_secrets(&self) -> Vec<String> {
+        self.schema
+            .fields
+            .values()
+            .filter(|field| {
+                matches!(field.typ_, Type::Secret)
+                    && self.secret_is_retained(field.id)
+                    && field.display(self)
+            })
+            .map(|field| field.id.to_string())
+            .collect()
+    }
+
+    pub fn build_key_values(&self) -> Vec<(String, String)> {
         let mut key_values = Vec::new();
         for (key, value) in &self.values {
             if key.starts_with('_') {
@@ -179,15 +501,7 @@ impl FormData {
             }
         }
 
-        if !key_values.is_empty() {
-            updates.push(UpdateSettings::Insert {
-                prefix: insert_prefix,
-                values: key_values,
-                assert_empty,
-            });
-        }
-
-        updates
+        key_values
     }
 }
 
@@ -246,6 +560,11 @@ impl SettingsValues for Settings {
                     "No".to_string()
                 }
             }
+            Type::Secret => match self.get(field.id) {
+                Some(value) if is_secret_reference(value) => value.to_string(),
+                Some(_) => "********".to_string(),
+                None => String::new(),
+            },
             Type::Duration => self
                 .get(field.id)
                 .and_then(|s| Duration::from_str(s).ok())
@@ -259,7 +578,7 @@ impl SettingsValues for Settings {
             Type::Size => self
                 .get(field.id)
                 .and_then(|s| s.parse::<u64>().ok())
-                .map(|s| format_size(s, DECIMAL))
+                .map(format_bytes)
                 .unwrap_or_default(),
             _ => self
                 .get(field.id)
@@ -288,10 +607,28 @@ impl LayoutBuilder {
             .create("System")
             .route("/system/edit")
             .insert(true)
+            .create("Snapshots")
+            .route("/snapshots")
+            .insert(true)
+            .create("Git sync")
+            .route("/git-sync/edit")
+            .insert(true)
+            .create("Pending changes")
+            .route("/pending-changes")
+            .insert(true)
+            .create("Quick settings")
+            .route("/quick")
+            .insert(true)
+            .create("Migration")
+            .route("/migrate")
+            .insert(true)
             // Listener
             .create("Listeners")
             .route("/listener")
             .insert(true)
+            .create("Listener wizard")
+            .route("/listener-wizard")
+            .insert(true)
             // TLS
             .create("TLS")
             .create("ACME Providers")
@@ -300,6 +637,9 @@ impl LayoutBuilder {
             .create("Certificates")
             .route("/certificate")
             .insert(true)
+            .create("Deployment")
+            .route("/acme-deploy")
+            .insert(true)
             .create("Defaults")
             .route("/tls/edit")
             .insert(true)
@@ -353,6 +693,12 @@ impl LayoutBuilder {
             .create("OpenID Connect")
             .route("/openid/edit")
             .insert(true)
+            .create("SCIM")
+            .route("/scim/edit")
+            .insert(true)
+            .create("SAML")
+            .route("/saml/edit")
+            .insert(true)
             .insert(true)
             // SMTP
             .create("SMTP")
@@ -367,6 +713,9 @@ impl LayoutBuilder {
             .create("AUTH stage")
             .route("/smtp-in-auth/edit")
             .insert(true)
+            .create("AUTH mechanisms")
+            .route("/smtp-auth-mechanisms")
+            .insert(true)
             .create("MAIL stage")
             .route("/smtp-in-mail/edit")
             .insert(true)
@@ -417,6 +766,9 @@ impl LayoutBuilder {
             .create("Remote Hosts")
             .route("/smtp-out-remote")
             .insert(true)
+            .create("Seed Mailboxes")
+            .route("/seed-mailbox")
+            .insert(true)
             .create("Throttles")
             .route("/smtp-out-throttle")
             .insert(true)
@@ -503,11 +855,25 @@ impl LayoutBuilder {
             .route("/tracing")
             .insert(true)
             .create("Metrics")
-            .route("/metrics/edit")
+            .create("OpenTelemetry")
+            .route("/metrics-otel/edit")
+            .insert(true)
+            .create("Prometheus")
+            .route("/metrics-prometheus/edit")
+            .insert(true)
+            .create("Exported metrics")
+            .route("/metrics-events/edit")
+            .insert(true)
+            .create("Rule hit counts")
+            .route("/metrics-rule-hits/edit")
+            .insert(true)
             .insert(true)
             .create("Alerts")
             .route("/alerts")
             .insert(true)
+            .create("Digest")
+            .route("/metrics-digest/edit")
+            .insert(true)
             .create("Webhooks")
             .route("/web-hooks")
             .insert(true)
@@ -595,3 +961,64 @@ impl LayoutBuilder {
             .menu_items
     }
 }
+
+pub fn commands(permissions: &Permissions) -> Vec<Command> {
+    let mut commands = Vec::new();
+    if permissions.has_access(Permission::SettingsReload) {
+        let auth = use_authorization();
+        let alert = use_alerts();
+        let schemas = store_value(expect_context::<Arc<Schemas>>());
+        commands.push(Command::run(
+            "Reload configuration",
+            "Apply the saved settings without restarting the server",
+            move |_| {
+                let auth = auth.get_untracked();
+                let schemas = schemas.get_value();
+                spawn_local(async move {
+                    match reload_settings(&auth, "").await {
+                        Ok(ReloadOutcome::Local(result)) if result.errors.is_empty() => {
+                            alert.set(Alert::success("Configuration reloaded."));
+                        }
+                        Ok(ReloadOutcome::Local(result)) => {
+                            alert.set(Alert::from_reload(result, &schemas));
+                        }
+                        // The palette has no page to show the results on, they
+                        // are listed in the alert instead
+                        Ok(ReloadOutcome::Cluster(results)) => {
+                            let has_failed = results
+                                .iter()
+                                .any(|result| matches!(result.status, NodeApplyStatus::Error(_)));
+                            let results = create_rw_signal(Some(results));
+                            let message = if has_failed {
+                                Alert::warning("Configuration not reloaded on every node.")
+                            } else {
+                                Alert::success("Configuration reloaded on every node.")
+                            };
+                            alert.set(message.without_timeout().with_details(view! {
+                                <ClusterApplyResults
+                                    results
+                                    reload_prefix=""
+                                    on_close=move |_| alert.update(|alert| alert.close())
+                                />
+                            }));
+                        }
+                        Err(http::Error::Unauthorized) => {
+                            use_navigate()("/login", Default::default());
+                        }
+                        Err(err) => {
+                            alert.set(Alert::from(err));
+                        }
+                    }
+                });
+            },
+        ));
+    }
+    if permissions.has_access_all(&[Permission::SettingsList, Permission::SettingsUpdate]) {
+        commands.push(Command::navigate(
+            "Review pending changes",
+            "Settings saved but not yet reloaded",
+            "/settings/pending-changes",
+        ));
+    }
+    commands
+}
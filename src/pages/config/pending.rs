@@ -0,0 +1,542 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        icon::{IconRefresh, IconTrash},
+        list::{
+            header::ColumnList, row::SelectItem, toolbar::ToolbarButton, Footer, ItemSelection,
+            ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest, ManagementApiError},
+        oauth::use_authorization,
+        poll::use_polling,
+        schema::Schema,
+        AccessToken,
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+use super::{
+    cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+    edit::{fetch_schema_settings, FetchResult},
+    Schemas, Settings, UpdateSettings,
+};
+
+pub const PENDING_STORAGE_KEY: &str = "webadmin_pending_changes";
+const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChange {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub title: String,
+    pub schema_id: String,
+    pub object_id: String,
+    pub version: u64,
+    pub reload_prefix: Option<String>,
+    pub changes: Vec<UpdateSettings>,
+}
+
+// Saves that failed because the server could not be reached are kept in local
+// storage so they can be retried once the connection is restored. Changes
+// that include secret values are never written to the browser.
+pub fn queue_pending_change(
+    auth: &AccessToken,
+    schema: &Schema,
+    object_id: &str,
+    version: u64,
+    changes: Vec<UpdateSettings>,
+    reload: bool,
+) -> Option<PendingChange> {
+    let has_secrets = changes.iter().any(|update| match update {
+        UpdateSettings::Insert { prefix, values, .. } => values.iter().any(|(key, _)| {
+            if let Some(prefix) = prefix {
+                schema.is_secret_setting(&format!("{prefix}.{key}"))
+            } else {
+                schema.is_secret_setting(key)
+            }
+        }),
+        _ => false,
+    });
+    if has_secrets {
+        return None;
+    }
+
+    let created = Utc::now();
+    let change = PendingChange {
+        id: created.timestamp_millis().to_string(),
+        created,
+        title: if !schema.form.title.is_empty() {
+            schema.form.title.to_string()
+        } else {
+            schema.name_plural.to_string()
+        },
+        schema_id: schema.id.to_string(),
+        object_id: object_id.to_string(),
+        version,
+        reload_prefix: reload.then(|| schema.reload_prefix.unwrap_or_default().to_string()),
+        changes,
+    };
+
+    let mut pending = stored_pending_changes(auth);
+    pending.push(change.clone());
+    store_pending_changes(auth, pending);
+
+    Some(change)
+}
+
+pub async fn apply_pending_change(
+    auth: &AccessToken,
+    schemas: &Schemas,
+    change: &PendingChange,
+) -> Result<Option<ReloadOutcome>, http::Error> {
+    // Refuse to overwrite settings that were modified after the change was queued
+    let schema = schemas
+        .schemas
+        .get(change.schema_id.as_str())
+        .cloned()
+        .ok_or_else(|| stale_change_error(change))?;
+    let current = match fetch_schema_settings(auth, schema, change.object_id.clone()).await {
+        Ok(FetchResult::Update { settings, .. }) => Some(settings),
+        Ok(_) | Err(http::Error::NotFound) => None,
+        Err(err) => return Err(err),
+    };
+    if settings_version(current.as_ref()) != change.version {
+        return Err(stale_change_error(change));
+    }
+
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(&change.changes)?
+        .send::<Option<String>>()
+        .await?;
+    discard_pending_change(auth, &change.id);
+
+    if let Some(prefix) = &change.reload_prefix {
+        reload_settings(auth, prefix).await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+// Fingerprint of the settings of an object as loaded from the server
+pub fn settings_version(settings: Option<&Settings>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let Some(settings) = settings {
+        let mut settings = settings.iter().collect::<Vec<_>>();
+        settings.sort_unstable();
+        settings.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn stale_change_error(change: &PendingChange) -> http::Error {
+    http::Error::Server(ManagementApiError::Other {
+        details: format!("{} changed on the server", change.title),
+        reason: Some(
+            concat!(
+                "The settings were modified after this change was queued. ",
+                "Discard it and edit the settings again."
+            )
+            .to_string(),
+        ),
+    })
+}
+
+fn storage_key(auth: &AccessToken) -> String {
+    format!("{PENDING_STORAGE_KEY}_{}@{}", auth.username, auth.base_url)
+}
+
+pub fn stored_pending_changes(auth: &AccessToken) -> Vec<PendingChange> {
+    LocalStorage::get::<Vec<PendingChange>>(storage_key(auth)).unwrap_or_default()
+}
+
+fn store_pending_changes(auth: &AccessToken, pending: Vec<PendingChange>) {
+    let result = if !pending.is_empty() {
+        LocalStorage::set(storage_key(auth), pending)
+    } else {
+        LocalStorage::delete(storage_key(auth));
+        Ok(())
+    };
+    if let Err(err) = result {
+        log::warn!("Failed to store pending changes: {err}");
+    }
+}
+
+fn discard_pending_change(auth: &AccessToken, id: &str) {
+    let mut pending = stored_pending_changes(auth);
+    pending.retain(|change| change.id != id);
+    store_pending_changes(auth, pending);
+}
+
+#[component]
+pub fn PendingChangesNotice() -> impl IntoView {
+    let auth = use_authorization();
+    let poller = use_polling(PROBE_INTERVAL);
+    let num_pending = create_rw_signal(stored_pending_changes(&auth.get_untracked()).len());
+
+    let reachable = create_local_resource(
+        move || poller.tick(),
+        move |_| {
+            let auth = auth.get_untracked();
+            num_pending.set(stored_pending_changes(&auth).len());
+
+            async move {
+                if num_pending.get_untracked() > 0 {
+                    !matches!(
+                        HttpRequest::get("/api/settings/keys")
+                            .with_authorization(&auth)
+                            .with_parameter("keys", "server.hostname")
+                            .send::<serde_json::Value>()
+                            .await,
+                        Err(http::Error::Network(_))
+                    )
+                } else {
+                    false
+                }
+            }
+        },
+    );
+
+    let show_notice =
+        Signal::derive(move || reachable.get().unwrap_or_default() && num_pending.get() != 0);
+
+    view! {
+        <Show when=move || show_notice.get()>
+            <div
+                class="mb-5 bg-yellow-50 border border-yellow-200 text-sm text-yellow-800 rounded-lg p-4 dark:bg-yellow-800/10 dark:border-yellow-900 dark:text-yellow-500"
+                role="alert"
+            >
+                <div class="flex">
+                    <div class="grow">
+                        <h3 class="font-semibold">Connection restored</h3>
+                        <p class="mt-1">
+                            {move || {
+                                format!(
+                                    "{} could not be saved while the server was unreachable.",
+                                    maybe_plural(num_pending.get(), "change", "changes"),
+                                )
+                            }}
+
+                        </p>
+                    </div>
+                    <a
+                        class="flex-shrink-0 font-semibold underline hover:text-yellow-900 dark:hover:text-yellow-400"
+                        href="/settings/pending-changes"
+                    >
+                        Retry pending changes
+                    </a>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+#[component]
+pub fn PendingChangesList() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let pending = create_rw_signal(stored_pending_changes(&auth.get_untracked()));
+    let total_results = Signal::derive(move || Some(pending.get().len() as u32));
+    let cluster_results = create_rw_signal(None);
+    let cluster_prefix = create_rw_signal(String::new());
+
+    let apply_action = create_action(move |changes: &Vec<PendingChange>| {
+        let changes = changes.clone();
+        let auth = auth.get();
+        let schemas = schemas.get_value();
+
+        async move {
+            let mut num_applied = 0;
+            for change in &changes {
+                match apply_pending_change(&auth, &schemas, change).await {
+                    Ok(Some(ReloadOutcome::Local(result)))
+                        if !result.errors.is_empty() || !result.warnings.is_empty() =>
+                    {
+                        pending.set(stored_pending_changes(&auth));
+                        alert.set(Alert::from_reload(result, &schemas));
+                        return;
+                    }
+                    Ok(Some(ReloadOutcome::Cluster(results))) => {
+                        cluster_prefix.set(change.reload_prefix.clone().unwrap_or_default());
+                        cluster_results.set(Some(results));
+                        num_applied += 1;
+                    }
+                    Ok(_) => {
+                        num_applied += 1;
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        pending.set(stored_pending_changes(&auth));
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+
+            pending.set(stored_pending_changes(&auth));
+            alert.set(Alert::success(format!(
+                "Applied {}.",
+                maybe_plural(num_applied, "pending change", "pending changes")
+            )));
+        }
+    });
+
+    let on_discard = Callback::new(move |change: PendingChange| {
+        modal.set(
+            Modal::with_title("Confirm discard")
+                .with_message(format!(
+                    concat!(
+                        "Are you sure you want to discard the changes to {} made on {}? ",
+                        "This action cannot be undone."
+                    ),
+                    change.title,
+                    change.created.format_date_time()
+                ))
+                .with_button("Discard changes")
+                .with_dangerous_callback(move || {
+                    let auth = auth.get_untracked();
+                    discard_pending_change(&auth, &change.id);
+                    pending.set(stored_pending_changes(&auth));
+                    alert.set(Alert::success("Pending change discarded."));
+                }),
+        )
+    });
+    let on_apply = Callback::new(move |change: PendingChange| {
+        apply_action.dispatch(vec![change]);
+    });
+
+    view! {
+        <ListSection>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=cluster_prefix
+                on_close=move |_| cluster_results.set(None)
+            />
+            <ListTable
+                title="Pending changes"
+                subtitle="Changes that could not be saved because the server was unreachable"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().total_selected(total_results.get());
+                            if ns > 0 { format!("Discard ({ns})") } else { "Discard".to_string() }
+                        })
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            let to_discard = selected.get().total_selected(total_results.get());
+                            if to_discard > 0 {
+                                let text = maybe_plural(to_discard, "pending change", "pending changes");
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm discard")
+                                            .with_message(
+                                                format!(
+                                                    "Are you sure you want to discard {text}? This action cannot be undone.",
+                                                ),
+                                            )
+                                            .with_button(format!("Discard {text}"))
+                                            .with_dangerous_callback(move || {
+                                                let items = selected
+                                                    .try_update(std::mem::take)
+                                                    .unwrap_or_default();
+                                                let auth = auth.get_untracked();
+                                                let mut list = stored_pending_changes(&auth);
+                                                match &items {
+                                                    ItemSelection::All => list.clear(),
+                                                    ItemSelection::Some(ids) => {
+                                                        list.retain(|c| !ids.contains(&c.id))
+                                                    }
+                                                    ItemSelection::None => {}
+                                                }
+                                                store_pending_changes(&auth, list);
+                                                pending.set(stored_pending_changes(&auth));
+                                                alert.set(Alert::success(format!("Discarded {text}.")));
+                                            }),
+                                    )
+                            }
+                        })
+                    >
+
+                        <IconTrash/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Apply all"
+                        color=Color::Blue
+                        on_click=Callback::new(move |_| {
+                            apply_action.dispatch(pending.get());
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                {move || {
+                    let list = pending.get();
+                    if !list.is_empty() {
+                        view! {
+                            <ColumnList
+                                headers=vec![
+                                    "Created".to_string(),
+                                    "Settings".to_string(),
+                                    "Changes".to_string(),
+                                    "".to_string(),
+                                ]
+
+                                has_select_all=true
+                            >
+
+                                <For
+                                    each=move || list.clone()
+                                    key=|change| change.id.clone()
+                                    let:change
+                                >
+                                    <PendingChangeItem change on_apply on_discard/>
+                                </For>
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    } else {
+                        view! {
+                            <ZeroResults
+                                title="No pending changes"
+                                subtitle="All your changes have been saved."
+                            />
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            Pending changes are stored in this browser only.
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn PendingChangeItem(
+    change: PendingChange,
+    on_apply: Callback<PendingChange>,
+    on_discard: Callback<PendingChange>,
+) -> impl IntoView {
+    let change_id = change.id.clone();
+    let created = change.created.format_date_time();
+    let title = change.title.clone();
+    let mutations = change
+        .changes
+        .iter()
+        .map(|update| view! { <li>{update.describe()}</li> })
+        .collect_view();
+    let change = Arc::new(change);
+    let change_ = change.clone();
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex">
+                    <SelectItem item_id=change_id/>
+
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListTextItem>{created}</ListTextItem>
+            <ListTextItem>{title}</ListTextItem>
+            <ListItem>
+                <ul class="text-sm text-gray-600 dark:text-gray-400 list-disc list-inside">
+                    {mutations}
+                </ul>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex gap-x-3">
+                    <button
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        on:click=move |_| {
+                            on_apply.call(change.as_ref().clone());
+                        }
+                    >
+
+                        Apply
+                    </button>
+                    <button
+                        class="inline-flex items-center gap-x-1 text-sm text-red-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        on:click=move |_| {
+                            on_discard.call(change_.as_ref().clone());
+                        }
+                    >
+
+                        Discard
+                    </button>
+                </div>
+            </ListItem>
+        </tr>
+    }
+}
+
+impl UpdateSettings {
+    pub fn describe(&self) -> String {
+        match self {
+            UpdateSettings::Delete { keys } => {
+                format!("Delete {}", describe_keys(keys.iter()))
+            }
+            UpdateSettings::Clear { prefix, .. } => format!("Remove all settings under {prefix}"),
+            UpdateSettings::Insert { prefix, values, .. } => {
+                let keys = describe_keys(values.iter().map(|(key, _)| key));
+                match prefix {
+                    Some(prefix) => format!("Set {keys} under {prefix}"),
+                    None => format!("Set {keys}"),
+                }
+            }
+        }
+    }
+}
+
+fn describe_keys<'x>(keys: impl ExactSizeIterator<Item = &'x String>) -> String {
+    if keys.len() > 3 {
+        maybe_plural(keys.len(), "setting", "settings")
+    } else {
+        keys.map(|key| key.as_str()).collect::<Vec<_>>().join(", ")
+    }
+}
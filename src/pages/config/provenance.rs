@@ -0,0 +1,315 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use leptos::{html::Div, *};
+use leptos_router::use_navigate;
+use leptos_use::on_click_outside;
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge, icon::IconDocumentMagnifyingGlass, messages::alert::Alert, time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::List,
+};
+
+const MAX_CHANGES: u32 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsChange {
+    timestamp: DateTime<Utc>,
+    actor: String,
+    action: String,
+    #[serde(default)]
+    object_id: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Provenance {
+    // Raw keys as stored on the server, sorted by name. Secret values are
+    // never retrieved.
+    values: Vec<(String, Option<String>)>,
+    // None when the audit log is not available to this admin
+    changes: Option<Vec<SettingsChange>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ValueOrigin {
+    Default,
+    Import,
+    Manual,
+    Unrecorded,
+}
+
+// Explains where the stored value of a field comes from: the raw keys behind
+// it, and who last changed them according to the audit log.
+#[component]
+pub fn FieldProvenance(
+    #[prop(into)] key: String,
+    #[prop(optional)] is_secret: bool,
+    #[prop(optional)] is_multivalue: bool,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let is_open = create_rw_signal(false);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    let key = store_value(key);
+    let inspect = create_action(move |_: &()| {
+        let auth = auth.get_untracked();
+        let key = key.get_value();
+
+        async move { fetch_provenance(&auth, key, is_secret, is_multivalue).await }
+    });
+    let result = inspect.value();
+    let pending = inspect.pending();
+
+    view! {
+        <div class="relative inline-block ms-3" node_ref=target>
+            <button
+                type="button"
+                class="inline-flex items-center gap-x-1 mt-2 text-xs text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-500"
+                on:click=move |_| {
+                    if !is_open.get_untracked() {
+                        inspect.dispatch(());
+                    }
+                    is_open.update(|open| *open = !*open);
+                }
+            >
+
+                <IconDocumentMagnifyingGlass size=14 attr:class="flex-shrink-0 size-3.5"/>
+                Inspect value
+            </button>
+            <div
+                class="absolute start-0 top-full mt-1 z-10 w-96 max-w-[90vw] p-3 space-y-3 bg-white shadow-md rounded-lg dark:bg-gray-800 dark:border dark:border-gray-700"
+                class:hidden=move || !is_open.get()
+            >
+                {move || {
+                    if pending.get() {
+                        return view! { <p class="text-sm text-gray-500">Loading...</p> }
+                            .into_view();
+                    }
+                    match result.get() {
+                        Some(Ok(provenance)) => {
+                            view! { <ProvenanceDetails provenance/> }.into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(err)) => {
+                            view! {
+                                <p class="text-sm text-red-600 dark:text-red-500">
+                                    {Alert::from(err).message}
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        None => ().into_view(),
+                    }
+                }}
+
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ProvenanceDetails(provenance: Provenance) -> impl IntoView {
+    let origin = provenance.origin();
+    let (color, label) = origin.badge();
+    let last_change = provenance
+        .changes
+        .as_ref()
+        .and_then(|changes| changes.first().cloned());
+
+    view! {
+        <div class="flex items-center justify-between gap-x-2">
+            <span class="text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                Value origin
+            </span>
+            <Badge color>{label}</Badge>
+        </div>
+        <p class="text-sm text-gray-600 dark:text-gray-400">{origin.description()}</p>
+        {last_change
+            .map(|change| {
+                view! {
+                    <p class="text-sm text-gray-800 dark:text-gray-200">
+                        "Last modified by "
+                        <span class="font-semibold">{change.actor}</span>
+                        " "
+                        <Timestamp date=change.timestamp relative=true/>
+                    </p>
+                }
+            })}
+        <div>
+            <span class="block text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                Stored keys
+            </span>
+            {if provenance.values.is_empty() {
+                view! { <p class="mt-1 text-sm text-gray-500">None</p> }.into_view()
+            } else {
+                view! {
+                    <ul class="mt-1 space-y-1 max-h-40 overflow-y-auto">
+                        {provenance
+                            .values
+                            .into_iter()
+                            .map(|(key, value)| {
+                                let value = value.unwrap_or_else(|| "(secret)".to_string());
+                                view! {
+                                    <li class="text-xs font-mono break-all text-gray-800 dark:text-gray-200">
+                                        <span class="text-gray-500">{key}</span>
+                                        " = "
+                                        {value}
+                                    </li>
+                                }
+                            })
+                            .collect_view()}
+                    </ul>
+                }
+                    .into_view()
+            }}
+
+        </div>
+        {provenance
+            .changes
+            .filter(|changes| changes.len() > 1)
+            .map(|changes| {
+                view! {
+                    <div>
+                        <span class="block text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                            Earlier changes
+                        </span>
+                        <ul class="mt-1 space-y-1">
+                            {changes
+                                .into_iter()
+                                .skip(1)
+                                .map(|change| {
+                                    view! {
+                                        <li class="flex justify-between gap-x-2 text-xs text-gray-600 dark:text-gray-400">
+                                            <span>
+                                                {format!("{} ({})", change.actor, change.action)}
+                                            </span>
+                                            <Timestamp date=change.timestamp relative=true/>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+                    </div>
+                }
+            })}
+    }
+}
+
+async fn fetch_provenance(
+    auth: &AccessToken,
+    key: String,
+    is_secret: bool,
+    is_multivalue: bool,
+) -> http::Result<Provenance> {
+    let mut request = HttpRequest::get("/api/settings/keys")
+        .with_authorization(auth)
+        .with_parameter("keys", key.clone());
+    if is_multivalue {
+        request = request.with_parameter("prefixes", key.clone());
+    }
+    // For secrets the server only reports which keys are stored
+    if is_secret {
+        request = request.with_parameter("redact", key.clone());
+    }
+    let mut values = request
+        .send::<AHashMap<String, Option<String>>>()
+        .await?
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?)))
+        .map(|(key, value)| (key, (!is_secret).then_some(value)))
+        .collect::<Vec<_>>();
+    values.sort();
+
+    // Admins without access to the audit log still get the stored keys
+    let changes = match HttpRequest::get("/api/audit")
+        .with_authorization(auth)
+        .with_parameter("objectType", "settings")
+        .with_parameter("objectId", key.clone())
+        .with_parameter("limit", MAX_CHANGES.to_string())
+        .send::<List<SettingsChange>>()
+        .await
+    {
+        Ok(list) => {
+            let prefix = format!("{key}.");
+            let mut changes = list
+                .items
+                .into_iter()
+                .filter(|change| {
+                    change
+                        .object_id
+                        .as_ref()
+                        .is_some_and(|id| id == &key || id.starts_with(&prefix))
+                })
+                .collect::<Vec<_>>();
+            changes.sort_by_key(|change| std::cmp::Reverse(change.timestamp));
+            Some(changes)
+        }
+        Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+        Err(_) => None,
+    };
+
+    Ok(Provenance { values, changes })
+}
+
+impl Provenance {
+    fn origin(&self) -> ValueOrigin {
+        if self.values.is_empty() {
+            return ValueOrigin::Default;
+        }
+        match self.changes.as_ref().and_then(|changes| changes.first()) {
+            Some(change) if change.is_import() => ValueOrigin::Import,
+            Some(_) => ValueOrigin::Manual,
+            None => ValueOrigin::Unrecorded,
+        }
+    }
+}
+
+impl SettingsChange {
+    fn is_import(&self) -> bool {
+        self.action.eq_ignore_ascii_case("import")
+    }
+}
+
+impl ValueOrigin {
+    fn badge(&self) -> (Color, &'static str) {
+        match self {
+            ValueOrigin::Default => (Color::Gray, "Default"),
+            ValueOrigin::Import => (Color::Blue, "Import"),
+            ValueOrigin::Manual => (Color::Green, "Manual edit"),
+            ValueOrigin::Unrecorded => (Color::Yellow, "Not recorded"),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ValueOrigin::Default => {
+                "Nothing is stored for this field, the built-in default applies."
+            }
+            ValueOrigin::Import => "The value was last written by a settings import.",
+            ValueOrigin::Manual => "The value was last changed by an administrator.",
+            ValueOrigin::Unrecorded => {
+                "The value is stored but the audit log has no record of it, \
+                 it may come from the configuration file or predate auditing."
+            }
+        }
+    }
+}
@@ -0,0 +1,410 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button, renderer::use_field_renderers, Form, FormButtonBar, FormItem,
+            FormSection,
+        },
+        icon::{IconBookmark, IconRefresh},
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::Field,
+        AccessToken,
+    },
+    pages::config::{
+        cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+        edit::{fetch_schema_settings, render_field, FetchResult},
+        Schema, SchemaType, Schemas, Type, UpdateSettings,
+    },
+};
+
+const PINNED_FIELDS_STORAGE_KEY: &str = "webadmin_pinned_fields";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedField {
+    pub schema: String,
+    // Empty for schemas that are not lists of records
+    #[serde(default)]
+    pub id: String,
+    pub field: String,
+}
+
+struct QuickSettingsGroup {
+    schema: Arc<Schema>,
+    id: String,
+    fields: Vec<Arc<Field>>,
+    data: RwSignal<FormData>,
+}
+
+// Pins are personal, they are kept per admin in this browser.
+pub fn pinned_fields(auth: &AccessToken) -> Vec<PinnedField> {
+    LocalStorage::get(pinned_fields_key(auth)).unwrap_or_default()
+}
+
+fn store_pinned_fields(auth: &AccessToken, pinned: &[PinnedField]) {
+    let key = pinned_fields_key(auth);
+    if pinned.is_empty() {
+        LocalStorage::delete(key);
+    } else if let Err(err) = LocalStorage::set(key, pinned) {
+        log::warn!("Failed to store pinned fields: {err}");
+    }
+}
+
+fn pinned_fields_key(auth: &AccessToken) -> String {
+    format!("{PINNED_FIELDS_STORAGE_KEY}:{}", auth.username)
+}
+
+pub fn is_pinnable(schema: &Schema, id: &str, field: &Field) -> bool {
+    !matches!(field.typ_, Type::Secret)
+        && match schema.typ {
+            SchemaType::Record { .. } => !id.is_empty(),
+            SchemaType::List => true,
+            SchemaType::Entry { .. } => false,
+        }
+}
+
+#[component]
+pub fn PinFieldButton(
+    schema: &'static str,
+    #[prop(into)] id: String,
+    field: &'static str,
+    #[prop(optional, into)] on_change: Option<Callback<bool>>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let pin = PinnedField {
+        schema: schema.to_string(),
+        id,
+        field: field.to_string(),
+    };
+    let is_pinned = create_rw_signal(pinned_fields(&auth.get_untracked()).contains(&pin));
+    let pin = store_value(pin);
+
+    view! {
+        <button
+            type="button"
+            class="inline-flex items-center gap-x-1 mt-2 text-xs text-gray-500 hover:text-blue-600 dark:text-gray-400 dark:hover:text-blue-500"
+            class:text-blue-600=move || is_pinned.get()
+            on:click=move |_| {
+                let auth = auth.get_untracked();
+                let mut pinned = pinned_fields(&auth);
+                let pin = pin.get_value();
+                let was_pinned = pinned.contains(&pin);
+                if was_pinned {
+                    pinned.retain(|item| item != &pin);
+                } else {
+                    pinned.push(pin);
+                }
+                store_pinned_fields(&auth, &pinned);
+                is_pinned.set(!was_pinned);
+                if let Some(on_change) = on_change {
+                    on_change.call(!was_pinned);
+                }
+            }
+        >
+
+            <IconBookmark size=14 attr:class="flex-shrink-0 size-3.5"/>
+            {move || if is_pinned.get() { "Unpin from quick settings" } else { "Pin to quick settings" }}
+        </button>
+    }
+}
+
+#[component]
+pub fn QuickSettings() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let renderers = store_value(use_field_renderers());
+    let (pending, set_pending) = create_signal(false);
+    let cluster_results = create_rw_signal(None);
+    let groups = store_value(Vec::<QuickSettingsGroup>::new());
+
+    let fetch_settings = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let schemas = schemas.get_value();
+
+            async move {
+                // Fields are grouped by the object they belong to, in the
+                // order they were pinned.
+                let mut objects: Vec<(Arc<Schema>, String, Vec<Arc<Field>>)> = Vec::new();
+                for pin in pinned_fields(&auth) {
+                    let Some(schema) = schemas.schemas.get(pin.schema.as_str()) else {
+                        continue;
+                    };
+                    let Some(field) = schema.fields.get(pin.field.as_str()) else {
+                        continue;
+                    };
+                    match objects
+                        .iter_mut()
+                        .find(|(s, id, _)| s.id == schema.id && id == &pin.id)
+                    {
+                        Some((_, _, fields)) => fields.push(field.clone()),
+                        None => objects.push((schema.clone(), pin.id, vec![field.clone()])),
+                    }
+                }
+
+                let mut results = Vec::with_capacity(objects.len());
+                for (schema, id, fields) in objects {
+                    let data =
+                        match fetch_schema_settings(&auth, schema.clone(), id.clone()).await? {
+                            FetchResult::Update {
+                                settings,
+                                external_sources,
+                            } => FormData::from_settings(schema.clone(), Some(settings))
+                                .with_external_sources(external_sources),
+                            FetchResult::Create { external_sources } => {
+                                FormData::from_settings(schema.clone(), None)
+                                    .with_external_sources(external_sources)
+                            }
+                            // The object was deleted since the field was pinned
+                            FetchResult::NotFound => continue,
+                        };
+                    results.push((schema, id, fields, data));
+                }
+
+                Ok(results)
+            }
+        },
+    );
+
+    let save_changes = create_action(
+        move |(changes, reload): &(Arc<Vec<UpdateSettings>>, bool)| {
+            let changes = changes.clone();
+            let reload = *reload;
+            let auth = auth.get();
+            let schemas = schemas.get_value();
+
+            async move {
+                set_pending.set(true);
+                let result = HttpRequest::post("/api/settings")
+                    .with_authorization(&auth)
+                    .with_body(changes)
+                    .unwrap()
+                    .send::<Option<String>>()
+                    .await
+                    .map(|_| ());
+                let result = match result {
+                    Ok(_) if reload => reload_settings(&auth, "").await.map(Some),
+                    Ok(_) => Ok(None),
+                    Err(err) => Err(err),
+                };
+                set_pending.set(false);
+
+                match result {
+                    Ok(Some(ReloadOutcome::Local(result))) if !result.errors.is_empty() => {
+                        alert.set(Alert::from_reload(result, &schemas));
+                    }
+                    Ok(Some(outcome)) => {
+                        if let ReloadOutcome::Cluster(results) = outcome {
+                            cluster_results.set(Some(results));
+                        }
+                        alert.set(Alert::success("Settings saved and configuration reloaded."));
+                    }
+                    Ok(None) => {
+                        alert.set(Alert::success(
+                            "Settings saved, reload the configuration to apply them.",
+                        ));
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    let save = move |reload: bool| {
+        let mut changes = Vec::new();
+        let mut is_valid = true;
+        groups.with_value(|groups| {
+            for group in groups {
+                group.data.update(|data| {
+                    if data.validate_form() {
+                        let fields = group.fields.iter().map(|f| f.id).collect::<Vec<_>>();
+                        changes.extend(data.build_fields_update(&fields));
+                    } else {
+                        is_valid = false;
+                    }
+                });
+            }
+        });
+
+        if !is_valid {
+            alert.set(Alert::error(
+                "Some settings are not valid, open their full form to review them.",
+            ));
+        } else if !changes.is_empty() {
+            save_changes.dispatch((Arc::new(changes), reload));
+        }
+    };
+
+    view! {
+        <Form
+            title="Quick settings"
+            subtitle="Settings pinned from any form, for routine changes"
+        >
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            <Transition fallback=Skeleton set_pending>
+
+                {move || match fetch_settings.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok(results)) if results.is_empty() => {
+                        Some(
+                            view! {
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    No settings have been pinned yet. Use the "Pin to quick settings" link below any field of a settings form to add it here.
+                                </p>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(results)) => {
+                        groups
+                            .set_value(
+                                results
+                                    .into_iter()
+                                    .map(|(schema, id, fields, data)| QuickSettingsGroup {
+                                        schema,
+                                        id,
+                                        fields,
+                                        data: data.into_signal(),
+                                    })
+                                    .collect(),
+                            );
+                        let is_enterprise = auth.get_untracked().is_enterprise();
+                        Some(
+                            groups
+                                .with_value(|groups| {
+                                    groups
+                                        .iter()
+                                        .map(|group| {
+                                            let schema = group.schema.clone();
+                                            let data = group.data;
+                                            let edit_url = if group.id.is_empty() {
+                                                format!("/settings/{}/edit", schema.id)
+                                            } else {
+                                                format!("/settings/{}/{}/edit", schema.id, group.id)
+                                            };
+                                            let title = if group.id.is_empty() {
+                                                schema.form.title.to_string()
+                                            } else {
+                                                format!("{} ({})", schema.form.title, group.id)
+                                            };
+                                            let components = group
+                                                .fields
+                                                .iter()
+                                                .map(|field| {
+                                                    let is_disabled = !is_enterprise && field.enterprise;
+                                                    let is_switch = matches!(field.typ_, Type::Boolean);
+                                                    let field_ = field.clone();
+                                                    let field_label = Signal::derive(move || {
+                                                        if is_switch { String::new() } else { field_.label_form_text() }
+                                                    });
+                                                    let field_ = field.clone();
+                                                    let help = Signal::derive(move || {
+                                                        if is_switch {
+                                                            String::new()
+                                                        } else {
+                                                            field_.help_text().unwrap_or_default()
+                                                        }
+                                                    });
+                                                    let field_id = field.id;
+                                                    let component = renderers
+                                                        .with_value(|r| render_field(&schema, field.clone(), data, is_disabled, r));
+                                                    let pin = view! {
+                                                        <PinFieldButton
+                                                            schema=schema.id
+                                                            id=group.id.clone()
+                                                            field=field_id
+                                                            on_change=Callback::new(move |_| fetch_settings.refetch())
+                                                        />
+                                                    };
+                                                    view! {
+                                                        <FormItem
+                                                            label=field_label
+                                                            tooltip=help
+                                                        >
+                                                            {component}
+                                                            {pin}
+                                                        </FormItem>
+                                                    }
+                                                })
+                                                .collect_view();
+
+                                            view! {
+                                                <FormSection title=title>
+                                                    {components}
+                                                    <div class="sm:col-span-12 text-end">
+                                                        <a
+                                                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                            href=edit_url
+                                                        >
+                                                            Open full form
+                                                        </a>
+                                                    </div>
+                                                </FormSection>
+                                            }
+                                        })
+                                        .collect_view()
+                                }),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <FormButtonBar>
+                <Button
+                    text="Save & Reload"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| save(true))
+                    disabled=pending
+                >
+
+                    <IconRefresh/>
+                </Button>
+
+                <Button
+                    text="Save changes"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| save(false))
+                    disabled=pending
+                />
+            </FormButtonBar>
+        </Form>
+    }
+}
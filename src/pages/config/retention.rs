@@ -0,0 +1,201 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        form::{button::Button, FormItem, FormSection},
+        icon::IconTrash,
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        cron::SimpleCron,
+        expr::ParseValue,
+        form::FormData,
+        format::{format_bytes, format_count},
+        http,
+        oauth::use_authorization,
+        task::{start_task, TaskKind},
+    },
+};
+
+// Rough figures used for the estimate, the actual number of series depends on
+// the events being collected and on the number of listeners and stores.
+const ESTIMATED_SERIES: u64 = 150;
+const BYTES_PER_SAMPLE: u64 = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+struct ResolutionEstimate {
+    name: &'static str,
+    retention: Option<String>,
+    samples: u64,
+}
+
+#[component]
+pub fn MetricsRetentionPanel(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let (pending, set_pending) = create_signal(false);
+
+    let estimates = create_memo(move |_| {
+        data.with(|data| {
+            // Raw samples are taken each time the collection schedule fires
+            let raw_per_day = match data
+                .value::<SimpleCron>("metrics.history.interval")
+                .unwrap_or_default()
+            {
+                SimpleCron::Hour { .. } => 24.0,
+                SimpleCron::Day { .. } => 1.0,
+                SimpleCron::Week { .. } => 1.0 / 7.0,
+            };
+
+            [
+                ("Raw samples", "metrics.history.retention", raw_per_day),
+                ("5 minute rollups", "metrics.history.rollup.5m", 288.0),
+                ("1 hour rollups", "metrics.history.rollup.1h", 24.0),
+            ]
+            .into_iter()
+            .map(|(name, id, per_day)| {
+                let retention = data.value_as_str(id).filter(|value| !value.is_empty());
+                let days = retention
+                    .and_then(Duration::parse_value)
+                    .map_or(0.0, |retention| retention.as_secs_f64() / 86400.0);
+                ResolutionEstimate {
+                    name,
+                    retention: retention.map(|value| value.to_string()),
+                    samples: (per_day * days).ceil() as u64,
+                }
+            })
+            .collect::<Vec<_>>()
+        })
+    });
+    let total_size = create_memo(move |_| {
+        estimates.with(|estimates| {
+            estimates
+                .iter()
+                .map(|estimate| estimate.samples * ESTIMATED_SERIES * BYTES_PER_SAMPLE)
+                .sum::<u64>()
+        })
+    });
+
+    let purge = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = start_task(&auth, TaskKind::PurgeMetrics).await;
+            set_pending.set(false);
+
+            match result {
+                Ok(_) => {
+                    alert.set(Alert::success(
+                        "Purge started, its progress can be followed on the Tasks page.",
+                    ));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Storage Impact".to_string()>
+            <FormItem label="Estimated size">
+                <div class="space-y-2">
+                    <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                        <thead>
+                            <tr class="text-start text-xs font-medium uppercase text-gray-500">
+                                <th class="py-2 pe-4 text-start">Resolution</th>
+                                <th class="py-2 pe-4 text-start">Kept for</th>
+                                <th class="py-2 pe-4 text-end">Samples per series</th>
+                                <th class="py-2 text-end">Size</th>
+                            </tr>
+                        </thead>
+                        <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                            {move || {
+                                estimates
+                                    .get()
+                                    .into_iter()
+                                    .map(|estimate| {
+                                        let size = estimate.samples * ESTIMATED_SERIES
+                                            * BYTES_PER_SAMPLE;
+                                        view! {
+                                            <tr class="text-gray-800 dark:text-gray-200">
+                                                <td class="py-2 pe-4">{estimate.name}</td>
+                                                <td class="py-2 pe-4">
+                                                    {estimate
+                                                        .retention
+                                                        .unwrap_or_else(|| "Disabled".to_string())}
+                                                </td>
+                                                <td class="py-2 pe-4 text-end">
+                                                    {format_count(estimate.samples)}
+                                                </td>
+                                                <td class="py-2 text-end">{format_bytes(size)}</td>
+                                            </tr>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </tbody>
+                    </table>
+                    <p class="text-sm text-gray-500 dark:text-gray-400">
+                        {move || {
+                            format!(
+                                "About {} in total, assuming {} metrics of {} bytes per sample.",
+                                format_bytes(total_size.get()),
+                                ESTIMATED_SERIES,
+                                BYTES_PER_SAMPLE,
+                            )
+                        }}
+
+                    </p>
+                </div>
+            </FormItem>
+            <FormItem label="Maintenance">
+                <Button
+                    text="Purge old metrics"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        modal
+                            .set(
+                                Modal::with_title("Purge old metrics")
+                                    .with_message(
+                                        concat!(
+                                            "Metrics older than the saved retention periods will be ",
+                                            "permanently deleted. Unsaved changes to this form are ",
+                                            "not taken into account.",
+                                        ),
+                                    )
+                                    .with_button("Purge metrics")
+                                    .with_dangerous_callback(move || {
+                                        purge.dispatch(());
+                                    }),
+                            );
+                    }
+
+                    disabled=pending
+                >
+
+                    <IconTrash/>
+                </Button>
+            </FormItem>
+        </FormSection>
+    }
+}
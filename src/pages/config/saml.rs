@@ -0,0 +1,485 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::Utc;
+use leptos::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::IconBeaker,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        expr::ParseValue,
+        form::FormData,
+        oauth::use_authorization,
+        saml::{CertificateInfo, IdpMetadata, SamlAssertion},
+    },
+    pages::FormatDateTime,
+};
+
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[component]
+pub fn SamlPanel(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let base_url = move || {
+        let base_url = auth.get().base_url.to_string();
+        if base_url.is_empty() {
+            window().location().origin().unwrap_or_default()
+        } else {
+            base_url
+        }
+    };
+    let certificate = create_memo(move |_| {
+        data.get()
+            .value::<String>("saml.idp.certificate")
+            .filter(|cert| !cert.trim().is_empty())
+            .map(|cert| CertificateInfo::parse(&cert))
+    });
+
+    let import_metadata = move |ev: web_sys::Event| {
+        let Some(file) = event_target::<HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        spawn_local(async move {
+            let xml = JsFuture::from(file.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+                .unwrap_or_default();
+
+            match IdpMetadata::parse(&xml) {
+                Ok(metadata) => {
+                    data.update(|data| {
+                        data.update("saml.idp.entity-id", metadata.entity_id.clone());
+                        for (id, value) in [
+                            ("saml.idp.sso-url", metadata.sso_url),
+                            ("saml.idp.slo-url", metadata.slo_url),
+                            ("saml.idp.certificate", metadata.certificate),
+                        ] {
+                            if let Some(value) = value {
+                                data.update(id, value);
+                            }
+                        }
+                        if let Some(format) = metadata.name_id_formats.first() {
+                            if data
+                                .select_sources("saml.name-id-format")
+                                .iter()
+                                .any(|(id, _)| id == format)
+                            {
+                                data.update("saml.name-id-format", format.clone());
+                            }
+                        }
+                    });
+                    alert.set(Alert::success(format!(
+                        "Imported the metadata of {}, save the settings to apply it.",
+                        metadata.entity_id
+                    )));
+                }
+                Err(err) => {
+                    alert.set(Alert::error("Invalid metadata").with_details(err));
+                }
+            }
+        });
+    };
+
+    view! {
+        <FormSection title="Identity Provider Metadata".to_string()>
+            <FormItem
+                label="Assertion consumer URL"
+                tooltip="The URL the identity provider posts SAML responses to"
+            >
+                <input
+                    type="text"
+                    class="py-2 px-3 pe-11 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    readonly=true
+                    prop:value=move || format!("{}/auth/saml/acs", base_url())
+                />
+            </FormItem>
+            <FormItem
+                label="Service provider metadata"
+                tooltip="Metadata describing this server, to import into the identity provider"
+            >
+                <input
+                    type="text"
+                    class="py-2 px-3 pe-11 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    readonly=true
+                    prop:value=move || format!("{}/auth/saml/metadata", base_url())
+                />
+            </FormItem>
+            <FormItem
+                label="Import metadata"
+                tooltip="Fills in the identity provider settings from its metadata XML"
+            >
+                <input
+                    type="file"
+                    class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-neutral-500"
+                    accept=".xml,application/xml,application/samlmetadata+xml"
+                    on:change=import_metadata
+                />
+            </FormItem>
+        </FormSection>
+
+        <FormSection title="Signing Certificate".to_string()>
+            {move || match certificate.get() {
+                None => {
+                    view! {
+                        <FormItem label="Certificate">
+                            <span class="text-sm text-gray-600 dark:text-gray-400">
+                                No certificate configured
+                            </span>
+                        </FormItem>
+                    }
+                        .into_view()
+                }
+                Some(Err(err)) => {
+                    view! {
+                        <FormItem label="Certificate">
+                            <span class="text-sm text-red-600 dark:text-red-500">{err}</span>
+                        </FormItem>
+                    }
+                        .into_view()
+                }
+                Some(Ok(cert)) => view! { <CertificateView cert=cert/> }.into_view(),
+            }}
+
+        </FormSection>
+
+        <AssertionDebugger data=data/>
+    }
+}
+
+#[component]
+fn CertificateView(cert: CertificateInfo) -> impl IntoView {
+    let (color, status) = match cert.not_after {
+        _ if cert.is_expired() => (Color::Red, "Expired".to_string()),
+        Some(not_after) if (not_after - Utc::now()).num_days() < EXPIRY_WARNING_DAYS => (
+            Color::Yellow,
+            format!("Expires in {} days", (not_after - Utc::now()).num_days()),
+        ),
+        _ => (Color::Green, "Valid".to_string()),
+    };
+    let validity = format!(
+        "{} to {}",
+        cert.not_before
+            .map(|time| time.format_date())
+            .unwrap_or_else(|| "?".to_string()),
+        cert.not_after
+            .map(|time| time.format_date())
+            .unwrap_or_else(|| "?".to_string()),
+    );
+
+    view! {
+        <FormItem label="Subject">
+            <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                <span class="text-sm text-gray-800 dark:text-gray-200">{cert.subject}</span>
+                <Badge color=color>{status}</Badge>
+            </div>
+        </FormItem>
+        <FormItem label="Issuer">
+            <span class="text-sm text-gray-600 dark:text-gray-400">{cert.issuer}</span>
+        </FormItem>
+        <FormItem label="Validity">
+            <span class="text-sm text-gray-600 dark:text-gray-400">{validity}</span>
+        </FormItem>
+        <FormItem label="SHA-256 fingerprint">
+            <span class="text-xs font-mono break-all text-gray-600 dark:text-gray-400">
+                {cert.fingerprint}
+            </span>
+        </FormItem>
+    }
+}
+
+#[component]
+fn AssertionDebugger(data: RwSignal<FormData>) -> impl IntoView {
+    let response = create_rw_signal(String::new());
+    let result = create_rw_signal(None::<Result<SamlAssertion, String>>);
+
+    view! {
+        <FormSection title="Assertion Debugger".to_string()>
+            <FormItem
+                label="SAML response"
+                tooltip="Paste a base64 encoded SAMLResponse or the assertion XML, it is decoded in the browser"
+            >
+                <div class="space-y-3">
+                    <textarea
+                        class="py-3 px-4 block w-full border-gray-200 rounded-lg text-xs font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        rows=6
+                        prop:value=move || response.get()
+                        on:input=move |ev| response.set(event_target_value(&ev))
+                    ></textarea>
+                    <Button
+                        text="Decode"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            result.set(Some(SamlAssertion::parse(&response.get_untracked())));
+                        }
+
+                        disabled=Signal::derive(move || response.get().trim().is_empty())
+                    >
+
+                        <IconBeaker/>
+                    </Button>
+                </div>
+            </FormItem>
+
+            {move || match result.get() {
+                None => ().into_view(),
+                Some(Err(err)) => {
+                    view! {
+                        <FormItem label="Result">
+                            <span class="text-sm text-red-600 dark:text-red-500">{err}</span>
+                        </FormItem>
+                    }
+                        .into_view()
+                }
+                Some(Ok(assertion)) => {
+                    view! { <AssertionReport assertion=assertion data=data.get_untracked()/> }
+                        .into_view()
+                }
+            }}
+
+        </FormSection>
+    }
+}
+
+#[component]
+fn AssertionReport(assertion: SamlAssertion, data: FormData) -> impl IntoView {
+    let setting = |id: &str| data.value::<String>(id).filter(|value| !value.is_empty());
+    let mut checks = Vec::new();
+
+    checks.push(match assertion.status.as_deref() {
+        Some("Success") => (Color::Green, "Status".to_string(), "Success".to_string()),
+        Some(status) => (Color::Red, "Status".to_string(), status.to_string()),
+        None => (
+            Color::Gray,
+            "Status".to_string(),
+            "No status, a bare assertion was provided".to_string(),
+        ),
+    });
+    checks.push(
+        match (assertion.issuer.as_deref(), setting("saml.idp.entity-id")) {
+            (Some(issuer), Some(expected)) if issuer == expected => {
+                (Color::Green, "Issuer".to_string(), issuer.to_string())
+            }
+            (Some(issuer), Some(expected)) => (
+                Color::Red,
+                "Issuer".to_string(),
+                format!("{issuer} does not match the configured entity ID {expected}"),
+            ),
+            (Some(issuer), None) => (
+                Color::Yellow,
+                "Issuer".to_string(),
+                format!("{issuer}, no identity provider entity ID is configured"),
+            ),
+            (None, _) => (Color::Red, "Issuer".to_string(), "Missing".to_string()),
+        },
+    );
+    if let Some(expected) = setting("saml.sp.entity-id") {
+        checks.push(if assertion.audiences.contains(&expected) {
+            (Color::Green, "Audience".to_string(), expected)
+        } else {
+            (
+                Color::Red,
+                "Audience".to_string(),
+                format!(
+                    "{} does not include {expected}",
+                    if assertion.audiences.is_empty() {
+                        "The audience restriction".to_string()
+                    } else {
+                        assertion.audiences.join(", ")
+                    }
+                ),
+            )
+        });
+    }
+
+    let skew = setting("saml.clock-skew")
+        .and_then(|skew| Duration::parse_value(&skew))
+        .and_then(|skew| chrono::Duration::from_std(skew).ok())
+        .unwrap_or_default();
+    let now = Utc::now();
+    checks.push(match (assertion.not_before, assertion.not_on_or_after) {
+        (Some(not_before), _) if now + skew < not_before => (
+            Color::Red,
+            "Validity".to_string(),
+            format!("Not valid before {}", not_before.format_date_time()),
+        ),
+        (_, Some(not_on_or_after)) if now - skew >= not_on_or_after => (
+            Color::Red,
+            "Validity".to_string(),
+            format!("Expired on {}", not_on_or_after.format_date_time()),
+        ),
+        (None, None) => (
+            Color::Yellow,
+            "Validity".to_string(),
+            "No validity period".to_string(),
+        ),
+        (_, not_on_or_after) => (
+            Color::Green,
+            "Validity".to_string(),
+            not_on_or_after
+                .map(|time| format!("Valid until {}", time.format_date_time()))
+                .unwrap_or_else(|| "Valid".to_string()),
+        ),
+    });
+    checks.push(
+        match (
+            assertion.is_signed,
+            setting("saml.require-signed-assertions").as_deref() != Some("false"),
+        ) {
+            (true, _) => (
+                Color::Green,
+                "Signature".to_string(),
+                "Signed, the signature is verified by the server on sign-in".to_string(),
+            ),
+            (false, true) => (
+                Color::Red,
+                "Signature".to_string(),
+                "Unsigned, the response will be rejected".to_string(),
+            ),
+            (false, false) => (
+                Color::Yellow,
+                "Signature".to_string(),
+                "Unsigned".to_string(),
+            ),
+        },
+    );
+    if assertion.is_encrypted {
+        checks.push((
+            Color::Yellow,
+            "Encryption".to_string(),
+            "The assertion is encrypted and its contents cannot be inspected here".to_string(),
+        ));
+    }
+
+    let mut mapped = Vec::new();
+    for (id, label) in [
+        ("saml.attributes.name", "Login name"),
+        ("saml.attributes.email", "Email"),
+        ("saml.attributes.description", "Display name"),
+        ("saml.attributes.groups", "Groups"),
+    ] {
+        if let Some(attribute) = setting(id) {
+            let values = assertion.attribute(&attribute).unwrap_or_default();
+            mapped.push((
+                if !values.is_empty() {
+                    Color::Green
+                } else if id == "saml.attributes.name" {
+                    Color::Red
+                } else {
+                    Color::Yellow
+                },
+                label.to_string(),
+                if !values.is_empty() {
+                    values.join(", ")
+                } else {
+                    format!("Attribute {attribute} not found")
+                },
+            ));
+        }
+    }
+    if let Some(group) = setting("saml.admin-group") {
+        let is_admin = setting("saml.attributes.groups")
+            .and_then(|attribute| assertion.attribute(&attribute))
+            .is_some_and(|groups| groups.contains(&group));
+        mapped.push(if is_admin {
+            (
+                Color::Green,
+                "Admin role".to_string(),
+                format!("Granted through {group}"),
+            )
+        } else {
+            (
+                Color::Gray,
+                "Admin role".to_string(),
+                format!("Not a member of {group}"),
+            )
+        });
+    }
+
+    let attributes = assertion
+        .name_id
+        .iter()
+        .map(|name_id| {
+            (
+                "NameID".to_string(),
+                match &assertion.name_id_format {
+                    Some(format) => format!(
+                        "{name_id} ({})",
+                        format.rsplit(':').next().unwrap_or_default()
+                    ),
+                    None => name_id.clone(),
+                },
+            )
+        })
+        .chain(assertion.attributes.iter().map(|attribute| {
+            (
+                match &attribute.friendly_name {
+                    Some(friendly_name) if *friendly_name != attribute.name => {
+                        format!("{friendly_name} ({})", attribute.name)
+                    }
+                    _ => attribute.name.clone(),
+                },
+                attribute.values.join(", "),
+            )
+        }))
+        .collect::<Vec<_>>();
+
+    view! {
+        {checks
+            .into_iter()
+            .chain(mapped)
+            .map(|(color, label, detail)| {
+                view! {
+                    <FormItem label=label>
+                        <div class="flex flex-wrap items-center gap-x-3 gap-y-2">
+                            <Badge color=color>
+                                {match color {
+                                    Color::Green => "Pass",
+                                    Color::Red => "Fail",
+                                    Color::Yellow => "Warning",
+                                    _ => "Info",
+                                }}
+
+                            </Badge>
+                            <span class="text-sm text-gray-600 dark:text-gray-400 break-all">
+                                {detail}
+                            </span>
+                        </div>
+                    </FormItem>
+                }
+            })
+            .collect_view()}
+        <FormItem label="Attributes">
+            <dl class="grid grid-cols-1 sm:grid-cols-3 gap-x-4 gap-y-1 text-sm">
+                {attributes
+                    .into_iter()
+                    .map(|(name, value)| {
+                        view! {
+                            <dt class="font-medium text-gray-800 dark:text-gray-200 break-all">{name}</dt>
+                            <dd class="sm:col-span-2 text-gray-600 dark:text-gray-400 break-all">{value}</dd>
+                        }
+                    })
+                    .collect_view()}
+            </dl>
+        </FormItem>
+    }
+}
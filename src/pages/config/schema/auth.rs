@@ -280,11 +280,16 @@ impl Builder<Schemas, ()> {
                 "'rsa-' + key_get('default', 'domain')",
             ))
             .label("Signature")
-            .help(concat!("List of DKIM signatures to use for sealing"))
+            .help(concat!(
+                "DKIM signature whose key, domain and selector are used to seal ",
+                "forwarded messages. Add conditions to only seal some messages, ",
+                "or return false to disable sealing"
+            ))
             .input_check(
                 [],
                 [Validator::Required, Validator::IsValidExpression(rcpt_vars)],
             )
+            .async_validator(AsyncValidator::IsSignature)
             .build()
             .new_form_section()
             .title("ARC Verification")
@@ -491,6 +496,8 @@ impl Builder<Schemas, ()> {
                 "Maximum size of the DMARC aggregate report in bytes"
             ))
             .default("26214400")
+            .typ(Type::Size)
+            .input_check([Transformer::Trim], [Validator::Required])
             .new_field("report.dmarc.aggregate.send")
             .label("Frequency")
             .help(concat!(
@@ -498,6 +505,7 @@ impl Builder<Schemas, ()> {
                 "are hourly, daily, weekly, or never to disable reporting"
             ))
             .default("daily")
+            .typ(Type::Expression)
             .input_check(
                 [],
                 [
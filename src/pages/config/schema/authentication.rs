@@ -193,5 +193,294 @@ impl Builder<Schemas, ()> {
             .fields(["oauth.oidc.signature-algorithm", "oauth.oidc.signature-key"])
             .build()
             .build()
+            // SCIM
+            .new_schema("scim")
+            .new_field("scim.enable")
+            .label("Enable SCIM provisioning")
+            .help(concat!(
+                "Whether to allow identity providers such as Entra ID or Okta ",
+                "to provision accounts using SCIM 2.0"
+            ))
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("scim.directory")
+            .label("Directory")
+            .help("The directory where provisioned accounts and groups are stored")
+            .typ(Type::Select {
+                source: Source::Dynamic {
+                    schema: "directory",
+                    field: "type",
+                    filter: Default::default(),
+                },
+                typ: SelectType::Single,
+            })
+            .display_if_eq("scim.enable", ["true"])
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("scim.deprovision")
+            .label("Deprovisioning")
+            .help("What to do with an account when the identity provider removes or deactivates it")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("disable", "Disable account"),
+                    ("delete", "Delete account"),
+                ]),
+            })
+            .default("disable")
+            .display_if_eq("scim.enable", ["true"])
+            .build()
+            .new_field("scim.attributes.name")
+            .label("Login name")
+            .help("SCIM attribute that contains the account login name")
+            .typ(Type::Input)
+            .default("userName")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("scim.attributes.email")
+            .label("Email")
+            .help("SCIM attribute that contains the primary email address")
+            .typ(Type::Input)
+            .default("emails[primary eq true].value")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("scim.attributes.description")
+            .label("Display name")
+            .help("SCIM attribute that contains the full name of the user")
+            .typ(Type::Input)
+            .default("displayName")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("scim.attributes.groups")
+            .label("Groups")
+            .help("SCIM attribute that contains the groups the user is a member of")
+            .typ(Type::Input)
+            .default("groups")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("scim.defaults.quota")
+            .label("Disk quota")
+            .help("Disk quota assigned to accounts created through SCIM")
+            .typ(Type::Size)
+            .default("1073741824")
+            .input_check([], [Validator::Required])
+            .display_if_eq("scim.enable", ["true"])
+            .build()
+            .new_field("scim.defaults.roles")
+            .label("Roles")
+            .help("Roles assigned to accounts created through SCIM")
+            .typ(Type::Array)
+            .default(&["user"][..])
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("scim.enable", ["true"])
+            .build()
+            .new_field("scim.activity.retention")
+            .label("Activity retention")
+            .help("How long SCIM operations are kept in the activity log")
+            .typ(Type::Duration)
+            .default("30d")
+            .input_check([], [Validator::Required])
+            .display_if_eq("scim.enable", ["true"])
+            .build()
+            .new_field("scim.activity.payloads")
+            .label("Store payloads")
+            .help(concat!(
+                "Whether the request and response bodies of each operation are ",
+                "stored so they can be inspected in the activity log"
+            ))
+            .typ(Type::Boolean)
+            .default("true")
+            .display_if_eq("scim.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("SCIM Provisioning")
+            .fields(["scim.enable", "scim.directory", "scim.deprovision"])
+            .build()
+            .new_form_section()
+            .title("Provisioning Defaults")
+            .display_if_eq("scim.enable", ["true"])
+            .fields(["scim.defaults.quota", "scim.defaults.roles"])
+            .build()
+            .new_form_section()
+            .title("Activity Log")
+            .display_if_eq("scim.enable", ["true"])
+            .fields(["scim.activity.retention", "scim.activity.payloads"])
+            .build()
+            .new_form_section()
+            .title("Attribute Mapping")
+            .display_if_eq("scim.enable", ["true"])
+            .fields([
+                "scim.attributes.name",
+                "scim.attributes.email",
+                "scim.attributes.description",
+                "scim.attributes.groups",
+            ])
+            .build()
+            .build()
+            // SAML
+            .new_schema("saml")
+            .new_field("saml.enable")
+            .label("Enable SAML sign-in")
+            .help("Whether administrators can sign in using a SAML 2.0 identity provider")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("saml.sp.entity-id")
+            .label("Service provider entity ID")
+            .help(concat!(
+                "Entity ID this server presents to the identity provider, ",
+                "defaults to the URL of the service provider metadata"
+            ))
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.name-id-format")
+            .label("NameID format")
+            .help("Format of the subject identifier requested from the identity provider")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    (
+                        "urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified",
+                        "Unspecified",
+                    ),
+                    (
+                        "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress",
+                        "Email address",
+                    ),
+                    (
+                        "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent",
+                        "Persistent",
+                    ),
+                    (
+                        "urn:oasis:names:tc:SAML:2.0:nameid-format:transient",
+                        "Transient",
+                    ),
+                ]),
+            })
+            .default("urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified")
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.sign-requests")
+            .label("Sign authentication requests")
+            .help("Whether authentication requests sent to the identity provider are signed")
+            .typ(Type::Boolean)
+            .default("false")
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.require-signed-assertions")
+            .label("Require signed assertions")
+            .help("Reject responses from the identity provider that do not carry a valid signature")
+            .typ(Type::Boolean)
+            .default("true")
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.clock-skew")
+            .label("Allowed clock skew")
+            .help("Tolerance applied when checking the validity period of assertions")
+            .typ(Type::Duration)
+            .default("2m")
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.idp.entity-id")
+            .label("Entity ID")
+            .help("Entity ID of the identity provider, checked against the issuer of each response")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.idp.sso-url")
+            .label("Sign-in URL")
+            .help("Single sign-on endpoint of the identity provider")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.idp.slo-url")
+            .label("Sign-out URL")
+            .help("Single logout endpoint of the identity provider")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::IsUrl])
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.idp.certificate")
+            .label("Signing certificate")
+            .help("PEM encoded certificate the identity provider signs its responses with")
+            .typ(Type::Text)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("saml.enable", ["true"])
+            .build()
+            .new_field("saml.attributes.name")
+            .label("Login name")
+            .help(concat!(
+                "SAML attribute that contains the account login name, ",
+                "use NameID to take it from the subject of the assertion"
+            ))
+            .typ(Type::Input)
+            .default("NameID")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("saml.attributes.email")
+            .label("Email")
+            .help("SAML attribute that contains the email address")
+            .typ(Type::Input)
+            .default("email")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("saml.attributes.description")
+            .label("Display name")
+            .help("SAML attribute that contains the full name of the user")
+            .typ(Type::Input)
+            .default("displayName")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("saml.attributes.groups")
+            .label("Groups")
+            .help("SAML attribute that contains the groups the user is a member of")
+            .typ(Type::Input)
+            .default("groups")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("saml.admin-group")
+            .label("Administrators group")
+            .help("Members of this group are granted the admin role when signing in")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_form_section()
+            .title("SAML Single Sign-On")
+            .fields([
+                "saml.enable",
+                "saml.sp.entity-id",
+                "saml.name-id-format",
+                "saml.sign-requests",
+                "saml.require-signed-assertions",
+                "saml.clock-skew",
+            ])
+            .build()
+            .new_form_section()
+            .title("Identity Provider")
+            .display_if_eq("saml.enable", ["true"])
+            .fields([
+                "saml.idp.entity-id",
+                "saml.idp.sso-url",
+                "saml.idp.slo-url",
+                "saml.idp.certificate",
+            ])
+            .build()
+            .new_form_section()
+            .title("Attribute Mapping")
+            .display_if_eq("saml.enable", ["true"])
+            .fields([
+                "saml.attributes.name",
+                "saml.attributes.email",
+                "saml.attributes.description",
+                "saml.attributes.groups",
+                "saml.admin-group",
+            ])
+            .build()
+            .build()
     }
 }
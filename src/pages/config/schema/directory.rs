@@ -6,6 +6,8 @@
 
 use crate::core::schema::*;
 
+use super::store::BALANCING_FIELDS;
+
 impl Builder<Schemas, ()> {
     pub fn build_directory(self) -> Self {
         self.new_schema("directory")
@@ -264,6 +266,9 @@ impl Builder<Schemas, ()> {
             .typ(Type::Input)
             .display_if_eq("bind.auth.enable", ["true"])
             .placeholder("cn=?,ou=svcaccts,dc=example,dc=org")
+            .example("uid=?,ou=people,dc=example,dc=org")
+            .example("?@example.org")
+            .docs("https://stalw.art/docs/auth/backend/ldap")
             .input_check([Transformer::Trim], [Validator::Required])
             .build()
             .new_field("bind.auth.search")
@@ -283,6 +288,9 @@ impl Builder<Schemas, ()> {
             .label("Name")
             .default("(&(|(objectClass=posixAccount)(objectClass=posixGroup))(uid=?))")
             .help("Filter used to search for objects based on the account name")
+            .example("(&(objectClass=inetOrgPerson)(uid=?))")
+            .example("(&(objectClass=user)(sAMAccountName=?))")
+            .docs("https://stalw.art/docs/auth/backend/ldap")
             .new_field("filter.email")
             .label("E-mail")
             .default(concat!(
@@ -293,6 +301,9 @@ impl Builder<Schemas, ()> {
                 "Searches for objects associated with a specific primary ",
                 "addresses, alias or mailing lists address"
             ))
+            .example("(&(objectClass=inetOrgPerson)(|(mail=?)(mailAlias=?)))")
+            .example("(&(objectClass=user)(|(mail=?)(proxyAddresses=smtp:?)))")
+            .docs("https://stalw.art/docs/auth/backend/ldap")
             .new_field("attributes.name")
             .label("Name")
             .help("LDAP attribute for the user's account name")
@@ -444,6 +455,129 @@ impl Builder<Schemas, ()> {
             .typ(Type::Secret)
             .display_if_eq("auth.method", ["basic"])
             .build()
+            .new_field("fields.groups")
+            .enterprise_feature()
+            .label("Groups field")
+            .help(concat!(
+                "Field name in the OpenID Connect provider response ",
+                "that contains the groups the user belongs to."
+            ))
+            .display_if_eq("type", ["oidc"])
+            .placeholder("groups")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .build()
+            // OIDC provider
+            .new_field("discovery")
+            .enterprise_feature()
+            .label("Endpoints")
+            .help(concat!(
+                "Whether to discover the provider endpoints from the issuer ",
+                "or to configure them manually."
+            ))
+            .default("auto")
+            .display_if_eq("type", ["oidc"])
+            .typ(Type::Select {
+                source: Source::Static(&[("auto", "Discover from issuer"), ("manual", "Manual")]),
+                typ: SelectType::Single,
+            })
+            .build()
+            .new_field("issuer.url")
+            .enterprise_feature()
+            .label("Issuer URL")
+            .help(concat!(
+                "URL of the OpenID Connect issuer. When discovery is enabled, ",
+                "the provider endpoints are obtained from its ",
+                "/.well-known/openid-configuration document."
+            ))
+            .display_if_eq("type", ["oidc"])
+            .placeholder("https://accounts.example.org")
+            .typ(Type::Input)
+            .input_check_if_eq(
+                "discovery",
+                ["manual"],
+                [Transformer::Trim],
+                [Validator::IsUrl],
+            )
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("endpoint.authorization")
+            .enterprise_feature()
+            .label("Authorization URL")
+            .help("URL of the provider's authorization endpoint.")
+            .display_if_eq("discovery", ["manual"])
+            .placeholder("https://accounts.example.org/authorize")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("endpoint.token")
+            .enterprise_feature()
+            .label("Token URL")
+            .help("URL of the provider's token endpoint.")
+            .display_if_eq("discovery", ["manual"])
+            .placeholder("https://accounts.example.org/token")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("endpoint.jwks")
+            .enterprise_feature()
+            .label("JWKS URL")
+            .help("URL of the JSON Web Key Set used to verify the provider's ID tokens.")
+            .display_if_eq("discovery", ["manual"])
+            .placeholder("https://accounts.example.org/.well-known/jwks.json")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            // OIDC client
+            .new_field("client.id")
+            .enterprise_feature()
+            .label("Client ID")
+            .help("Client identifier registered with the OpenID Connect provider.")
+            .display_if_eq("type", ["oidc"])
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("client.auth-method")
+            .enterprise_feature()
+            .label("Token endpoint auth")
+            .help("Method used by the client to authenticate with the token endpoint.")
+            .default("client-secret-basic")
+            .display_if_eq("type", ["oidc"])
+            .typ(Type::Select {
+                source: Source::Static(&[
+                    ("client-secret-basic", "Client secret (HTTP Basic)"),
+                    ("client-secret-post", "Client secret (POST body)"),
+                    ("private-key-jwt", "Private key JWT"),
+                    ("none", "None (public client)"),
+                ]),
+                typ: SelectType::Single,
+            })
+            .build()
+            .new_field("client.secret")
+            .enterprise_feature()
+            .label("Client secret")
+            .help("Client secret issued by the OpenID Connect provider.")
+            .display_if_ne("client.auth-method", ["private-key-jwt", "none"])
+            .typ(Type::Secret)
+            .build()
+            .new_field("client.private-key")
+            .enterprise_feature()
+            .label("Private key")
+            .help("PEM encoded private key used to sign the client assertion.")
+            .display_if_eq("client.auth-method", ["private-key-jwt"])
+            .typ(Type::Secret)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("client.scopes")
+            .enterprise_feature()
+            .label("Scopes")
+            .help("Scopes requested from the OpenID Connect provider.")
+            .display_if_eq("type", ["oidc"])
+            .default(&["openid", "email", "profile"][..])
+            .typ(Type::Array)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .add_balancing_fields(&["ldap"])
             // Form layouts
             .new_form_section()
             .title("Configuration")
@@ -477,6 +611,27 @@ impl Builder<Schemas, ()> {
             .fields(["tls.enable", "tls.allow-invalid-certs"])
             .build()
             .new_form_section()
+            .title("Identity Provider")
+            .display_if_eq("type", ["oidc"])
+            .fields(["issuer.url", "discovery"])
+            .build()
+            .new_form_section()
+            .title("Provider Endpoints")
+            .display_if_eq("discovery", ["manual"])
+            .fields(["endpoint.authorization", "endpoint.token", "endpoint.jwks"])
+            .build()
+            .new_form_section()
+            .title("Client")
+            .display_if_eq("type", ["oidc"])
+            .fields([
+                "client.id",
+                "client.auth-method",
+                "client.secret",
+                "client.private-key",
+                "client.scopes",
+            ])
+            .build()
+            .new_form_section()
             .title("Endpoint Authentication")
             .display_if_eq("endpoint.method", ["introspect"])
             .fields(["auth.method", "auth.token", "auth.username", "auth.secret"])
@@ -484,7 +639,12 @@ impl Builder<Schemas, ()> {
             .new_form_section()
             .title("Field Mappings")
             .display_if_eq("type", ["oidc"])
-            .fields(["fields.email", "fields.username", "fields.full-name"])
+            .fields([
+                "fields.email",
+                "fields.username",
+                "fields.full-name",
+                "fields.groups",
+            ])
             .build()
             .new_form_section()
             .title("Column Mappings")
@@ -531,6 +691,11 @@ impl Builder<Schemas, ()> {
             .fields(["limits.auth-errors", "limits.rcpt"])
             .build()
             .new_form_section()
+            .title("Load Balancing")
+            .display_if_eq("type", ["ldap"])
+            .fields(BALANCING_FIELDS.iter().copied())
+            .build()
+            .new_form_section()
             .title("Connection Pools")
             .display_if_eq("type", ["imap", "smtp", "lmtp", "ldap"])
             .fields([
@@ -42,6 +42,7 @@ impl Builder<Schemas, ()> {
                 "server will accept"
             ))
             .default("10000000")
+            .typ(Type::Size)
             .new_field("jmap.protocol.request.max-calls")
             .label("Method Calls")
             .help(concat!(
@@ -49,6 +50,7 @@ impl Builder<Schemas, ()> {
                 " a single request"
             ))
             .default("16")
+            .typ(Type::Input)
             .new_field("jmap.protocol.query.max-results")
             .label("Query")
             .help(concat!(
@@ -61,12 +63,14 @@ impl Builder<Schemas, ()> {
                 "Defines the maximum file size for file uploads to the server"
             ))
             .default("50000000")
+            .typ(Type::Size)
             .new_field("jmap.protocol.upload.max-concurrent")
             .label("Max Concurrent")
             .help(concat!(
                 "Restricts the number of concurrent file uploads a user can perform"
             ))
             .default("4")
+            .typ(Type::Input)
             .new_field("jmap.protocol.upload.quota.files")
             .label("Total Files")
             .help(concat!(
@@ -81,6 +85,7 @@ impl Builder<Schemas, ()> {
                 "Defines the total size of files that a user can upload within a ",
                 "certain period"
             ))
+            .typ(Type::Size)
             .new_field("jmap.protocol.changes.max-results")
             .label("Changes")
             .help(concat!(
@@ -88,6 +93,7 @@ impl Builder<Schemas, ()> {
                 " method can return"
             ))
             .default("5000")
+            .typ(Type::Input)
             .new_field("jmap.mailbox.max-depth")
             .label("Max Depth")
             .help(concat!(
@@ -105,6 +111,7 @@ impl Builder<Schemas, ()> {
                 "Specifies the maximum size for an email attachment"
             ))
             .default("50000000")
+            .typ(Type::Size)
             .new_field("jmap.email.max-size")
             .label("E-mail Size")
             .help(concat!("Determines the maximum size for an email message"))
@@ -116,6 +123,7 @@ impl Builder<Schemas, ()> {
                 "an email message"
             ))
             .default("10")
+            .typ(Type::Input)
             .build()
             .new_field("jmap.protocol.upload.ttl")
             .label("Expire after")
@@ -192,7 +200,7 @@ impl Builder<Schemas, ()> {
             ))
             .default("15 * *")
             .typ(Type::Cron)
-            .input_check([], [Validator::Required])
+            .input_check([], [Validator::Required, Validator::IsCron])
             .build()
             .new_form_section()
             .title("JMAP Session")
@@ -6,6 +6,8 @@
 
 use crate::core::schema::*;
 
+use super::CONNECTION_VARS;
+
 impl Builder<Schemas, ()> {
     pub fn build_listener(self) -> Self {
         self.new_schema("listener")
@@ -75,6 +77,7 @@ impl Builder<Schemas, ()> {
             // Add common fields
             .add_network_fields(true)
             .add_tls_fields(true)
+            .add_limit_fields()
             // Forms
             .new_form_section()
             .title("Listener settings")
@@ -92,6 +95,16 @@ impl Builder<Schemas, ()> {
             ])
             .build()
             .new_form_section()
+            .title("Connection limits")
+            .fields([
+                "limits.override",
+                "limits.max-connections",
+                "limits.max-connections-per-ip",
+                "limits.rate",
+                "limits.throttle",
+            ])
+            .build()
+            .new_form_section()
             .title("Proxy protocol")
             .fields(["proxy.override", "proxy.trusted-networks"])
             .build()
@@ -116,3 +129,58 @@ impl Builder<Schemas, ()> {
             .build()
     }
 }
+
+impl Builder<Schemas, Schema> {
+    pub fn add_limit_fields(self) -> Self {
+        // Override limits
+        self.new_field("limits.override")
+            .label("Override connection limits")
+            .help("Override the server wide connection limits for this listener")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            // Max connections
+            .new_field("limits.max-connections")
+            .label("Max connections")
+            .help("The maximum number of concurrent connections accepted by this listener")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::MinValue(1.into())])
+            .display_if_eq("limits.override", ["true"])
+            .build()
+            // Per-IP concurrency
+            .new_field("limits.max-connections-per-ip")
+            .label("Max connections per IP")
+            .help("The maximum number of concurrent connections from a single IP address")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::MinValue(1.into())])
+            .display_if_eq("limits.override", ["true"])
+            .build()
+            // Connection rate
+            .new_field("limits.rate")
+            .label("Connection rate")
+            .help(
+                "The number of new connections a single IP address can open over a period of time",
+            )
+            .typ(Type::Rate)
+            .display_if_eq("limits.override", ["true"])
+            .build()
+            // Throttle rules
+            .new_field("limits.throttle")
+            .label("Throttle rules")
+            .help(concat!(
+                "Connection rates to apply instead of the default when ",
+                "the remote IP, listener or remote host matches a rule"
+            ))
+            .typ(Type::Rules)
+            .placeholder("100/1m")
+            .input_check(
+                [],
+                [Validator::IsValidExpression(ExpressionValidator::new(
+                    CONNECTION_VARS,
+                    &[],
+                ))],
+            )
+            .display_if_eq("limits.override", ["true"])
+            .build()
+    }
+}
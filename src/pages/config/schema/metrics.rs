@@ -0,0 +1,278 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use crate::core::schema::*;
+
+use super::tracing::EVENT_NAMES;
+
+impl Builder<Schemas, ()> {
+    pub fn build_metrics(self) -> Self {
+        // OpenTelemetry push
+        self.new_schema("metrics-otel")
+            .new_field("metrics.open-telemetry.transport")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("disabled", "Disabled"),
+                    ("http", "HTTP"),
+                    ("grpc", "gRPC"),
+                ]),
+            })
+            .label("Transport")
+            .help("The transport protocol for Open Telemetry")
+            .input_check([], [Validator::Required])
+            .default("disabled")
+            .build()
+            .new_field("metrics.open-telemetry.endpoint")
+            .typ(Type::Input)
+            .label("Endpoint")
+            .help("The endpoint for Open Telemetry")
+            .placeholder("https://tracing.example.com/v1/otel")
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
+            .build()
+            .new_field("metrics.open-telemetry.headers")
+            .typ(Type::Array)
+            .label("Headers")
+            .help(concat!(
+                "The headers to be sent with OpenTelemetry requests, ",
+                "for example 'Authorization: Bearer <token>'"
+            ))
+            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
+            .build()
+            .new_field("metrics.open-telemetry.timeout")
+            .label("Timeout")
+            .help(concat!(
+                "Maximum amount of time that Stalwart will wait for a response ",
+                "from the OpenTelemetry endpoint"
+            ))
+            .default("10s")
+            .typ(Type::Duration)
+            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("metrics.open-telemetry.interval")
+            .label("Push interval")
+            .help(concat!(
+                "The minimum amount of time that must pass between ",
+                "each push request to the OpenTelemetry endpoint"
+            ))
+            .default("1m")
+            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_form_section()
+            .title("OpenTelemetry Push Metrics")
+            .fields([
+                "metrics.open-telemetry.transport",
+                "metrics.open-telemetry.endpoint",
+                "metrics.open-telemetry.timeout",
+                "metrics.open-telemetry.interval",
+                "metrics.open-telemetry.headers",
+            ])
+            .build()
+            .build()
+            // Prometheus scrape endpoint
+            .new_schema("metrics-prometheus")
+            .new_field("metrics.prometheus.enable")
+            .typ(Type::Boolean)
+            .label("Enable endpoint")
+            .help("Enable the Prometheus metrics endpoint")
+            .default("false")
+            .build()
+            .new_field("metrics.prometheus.path")
+            .typ(Type::Input)
+            .label("Path")
+            .help("The HTTP path where Prometheus can scrape the metrics from")
+            .default("/metrics/prometheus")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("metrics.prometheus.enable", ["true"])
+            .build()
+            .new_field("metrics.prometheus.auth.username")
+            .label("Username")
+            .help("The Prometheus endpoint's username for Basic authentication")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("metrics.prometheus.enable", ["true"])
+            .build()
+            .new_field("metrics.prometheus.auth.secret")
+            .label("Secret")
+            .help("The Prometheus endpoint's secret for Basic authentication")
+            .typ(Type::Secret)
+            .display_if_eq("metrics.prometheus.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Prometheus Pull Metrics")
+            .fields([
+                "metrics.prometheus.enable",
+                "metrics.prometheus.path",
+                "metrics.prometheus.auth.username",
+                "metrics.prometheus.auth.secret",
+            ])
+            .build()
+            .build()
+            // Exported metrics
+            .new_schema("metrics-events")
+            .new_field("metrics.disabled-events")
+            .label("Disabled Metrics")
+            .help("Which events are not collected or exported as metrics")
+            .typ(Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::StaticId(EVENT_NAMES),
+            })
+            .build()
+            .new_form_section()
+            .title("Exported metrics")
+            .fields(["metrics.disabled-events"])
+            .build()
+            .build()
+            // Rule hit counts
+            .new_schema("metrics-rule-hits")
+            .new_field("metrics.rule-hits.enable")
+            .typ(Type::Boolean)
+            .label("Enable hit counting")
+            .help(concat!(
+                "Whether to count how many times each routing, spam filter ",
+                "and Sieve rule matches"
+            ))
+            .default("false")
+            .build()
+            .new_field("metrics.rule-hits.rules")
+            .label("Rule types")
+            .help("Which types of rules are instrumented")
+            .typ(Type::Select {
+                typ: SelectType::Many,
+                source: Source::Static(RULE_TYPES),
+            })
+            .input_check([], [Validator::Required])
+            .display_if_eq("metrics.rule-hits.enable", ["true"])
+            .build()
+            .new_field("metrics.rule-hits.retention")
+            .label("Retention")
+            .help(concat!(
+                "How long hit counts are kept before being reset, rules that ",
+                "do not match during this period are reported as never fired"
+            ))
+            .default("30d")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .display_if_eq("metrics.rule-hits.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Rule hit counts")
+            .fields([
+                "metrics.rule-hits.enable",
+                "metrics.rule-hits.rules",
+                "metrics.rule-hits.retention",
+            ])
+            .build()
+            .build()
+            // Summary digest
+            .new_schema("metrics-digest")
+            .new_field("metrics.digest.enable")
+            .typ(Type::Boolean)
+            .label("Enable digest")
+            .help(concat!(
+                "Whether to periodically e-mail a summary of the server ",
+                "activity to the administrators"
+            ))
+            .default("false")
+            .build()
+            .new_field("metrics.digest.schedule")
+            .label("Send at")
+            .help(concat!(
+                "When the digest is generated and sent, each digest covers the ",
+                "time since the previous one, so a schedule on a single day of ",
+                "the week sends a weekly digest"
+            ))
+            .default("0 7 *")
+            .typ(Type::Cron)
+            .input_check([], [Validator::Required, Validator::IsCron])
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_field("metrics.digest.sections")
+            .label("Sections")
+            .help("Which sections are included in the digest")
+            .typ(Type::Select {
+                typ: SelectType::Many,
+                source: Source::Static(DIGEST_SECTIONS),
+            })
+            .default(&["queue", "errors", "certificates", "storage"][..])
+            .input_check([], [Validator::Required])
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_field("metrics.digest.from-name")
+            .typ(Type::Input)
+            .label("From Name")
+            .help("The name of the sender")
+            .placeholder("Server digest")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_field("metrics.digest.from-addr")
+            .typ(Type::Input)
+            .label("From")
+            .help("The email address of the sender")
+            .placeholder("postmaster@example.com")
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_field("metrics.digest.to")
+            .typ(Type::Array)
+            .label("To")
+            .help("The email address of the recipient(s)")
+            .placeholder("admin@example.com")
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_field("metrics.digest.subject")
+            .typ(Type::Input)
+            .label("Subject")
+            .help("The subject of the email")
+            .default("Server summary")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("metrics.digest.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Summary digest")
+            .fields([
+                "metrics.digest.enable",
+                "metrics.digest.schedule",
+                "metrics.digest.sections",
+            ])
+            .build()
+            .new_form_section()
+            .title("E-mail")
+            .fields([
+                "metrics.digest.from-name",
+                "metrics.digest.from-addr",
+                "metrics.digest.to",
+                "metrics.digest.subject",
+            ])
+            .build()
+            .build()
+    }
+}
+
+pub static DIGEST_SECTIONS: &[(&str, &str)] = &[
+    ("queue", "Queue statistics"),
+    ("errors", "Top errors"),
+    ("certificates", "Certificate expirations"),
+    ("storage", "Storage growth"),
+];
+
+pub static RULE_TYPES: &[(&str, &str)] = &[
+    ("routing", "Routing"),
+    ("spam-filter", "Spam filter"),
+    ("sieve", "Sieve"),
+];
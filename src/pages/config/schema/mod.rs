@@ -10,6 +10,7 @@ pub mod directory;
 pub mod imap;
 pub mod jmap;
 pub mod listener;
+pub mod metrics;
 pub mod server;
 pub mod sieve;
 pub mod smtp;
@@ -23,6 +23,7 @@ impl Builder<Schemas, ()> {
                 [Transformer::Trim],
                 [Validator::Required, Validator::IsHost],
             )
+            .async_validator(AsyncValidator::HasReverseDns)
             // Max connections
             .new_field("server.max-connections")
             .label("Max connections")
@@ -628,7 +629,7 @@ impl Builder<Schemas, ()> {
             .label("Events")
             .help("Which events should trigger this webhook")
             .typ(Type::Select {
-                typ: SelectType::ManyWithSearch,
+                typ: SelectType::ManyWithGroups(WEBHOOK_EVENT_GROUPS),
                 source: Source::StaticId(EVENT_NAMES),
             })
             .build()
@@ -693,6 +694,97 @@ impl Builder<Schemas, ()> {
             .fields(["enterprise.logo-url"])
             .build()
             .build()
+            // Configuration repository
+            .new_schema("git-sync")
+            .new_field("config.git.enable")
+            .label("Enable Git sync")
+            .help(concat!(
+                "Whether to keep the configuration in sync with a ",
+                "remote Git repository"
+            ))
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("config.git.url")
+            .label("Repository URL")
+            .help("The SSH or HTTPS URL of the remote repository")
+            .placeholder("git@github.com:example/mail-config.git")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.branch")
+            .label("Branch")
+            .help("The branch configuration exports are committed to and pulled from")
+            .typ(Type::Input)
+            .default("main")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.path")
+            .label("Path")
+            .help("Directory inside the repository where the configuration is stored")
+            .typ(Type::Input)
+            .placeholder("stalwart")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.deploy-key")
+            .label("Deploy key")
+            .help(concat!(
+                "Private SSH key with write access to the repository, ",
+                "usually given as a file reference"
+            ))
+            .typ(Type::Secret)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.auto-push")
+            .label("Commit on change")
+            .help(concat!(
+                "Whether to commit and push a configuration export ",
+                "every time the settings are changed"
+            ))
+            .typ(Type::Boolean)
+            .default("true")
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.author.name")
+            .label("Author name")
+            .help("Name used as the author of the commits")
+            .typ(Type::Input)
+            .default("Stalwart")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_field("config.git.author.email")
+            .label("Author e-mail")
+            .help("E-mail address used as the author of the commits")
+            .typ(Type::Input)
+            .placeholder("postmaster@example.org")
+            .input_check([Transformer::Trim], [Validator::IsEmail])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Repository")
+            .fields([
+                "config.git.enable",
+                "config.git.url",
+                "config.git.branch",
+                "config.git.path",
+                "config.git.deploy-key",
+            ])
+            .build()
+            .new_form_section()
+            .title("Commits")
+            .fields([
+                "config.git.auto-push",
+                "config.git.author.name",
+                "config.git.author.email",
+            ])
+            .display_if_eq("config.git.enable", ["true"])
+            .build()
+            .build()
             // Contact form settings
             .new_schema("form")
             .new_field("form.deliver-to")
@@ -1024,8 +1116,14 @@ impl Builder<Schemas, Schema> {
         })
         .label("Send buffer")
         .help("The size of the buffer used for sending data")
-        .typ(Type::Input)
-        .input_check([Transformer::Trim], [Validator::MinValue(1.into())])
+        .typ(Type::Size)
+        .input_check(
+            [Transformer::Trim],
+            [
+                Validator::MinValue(1024.into()),
+                Validator::MaxValue(67108864.into()),
+            ],
+        )
         .display_if_eq("socket.override", do_override.iter().copied())
         .build()
         // Receive buf size
@@ -1037,8 +1135,14 @@ impl Builder<Schemas, Schema> {
         .label("Receive buffer")
         .help("The size of the buffer used for receiving data")
         .default("")
-        .typ(Type::Input)
-        .input_check([Transformer::Trim], [Validator::MinValue(1.into())])
+        .typ(Type::Size)
+        .input_check(
+            [Transformer::Trim],
+            [
+                Validator::MinValue(1024.into()),
+                Validator::MaxValue(67108864.into()),
+            ],
+        )
         .display_if_eq("socket.override", do_override.iter().copied())
         .build()
         // No delay
@@ -1085,3 +1189,46 @@ impl Builder<Schemas, Schema> {
         .build()
     }
 }
+
+pub static WEBHOOK_EVENT_GROUPS: &[(&str, &[&str])] = &[
+    ("Authentication", &["auth."]),
+    (
+        "Delivery",
+        &[
+            "delivery.",
+            "queue.",
+            "message-ingest.",
+            "incoming-report.",
+            "outgoing-report.",
+            "tls-rpt.",
+            "mta-sts.",
+            "dane.",
+        ],
+    ),
+    (
+        "Security",
+        &[
+            "security.",
+            "limit.",
+            "spam.",
+            "tls.",
+            "mail-auth.",
+            "dkim.",
+            "spf.",
+            "dmarc.",
+            "arc.",
+            "iprev.",
+        ],
+    ),
+    (
+        "Audit",
+        &[
+            "manage.",
+            "config.",
+            "server.",
+            "cluster.",
+            "purge.",
+            "housekeeper.",
+        ],
+    ),
+];
@@ -206,10 +206,12 @@ impl Builder<Schemas, ()> {
             .label("Variable Size")
             .help(concat!("Maximum size of a variable"))
             .default("4096")
+            .typ(Type::Size)
             .new_field("sieve.untrusted.limits.nested-blocks")
             .label("Nested Blocks")
             .help(concat!("Maximum number of nested blocks"))
             .default("15")
+            .typ(Type::Input)
             .new_field("sieve.untrusted.limits.nested-tests")
             .label("Nested Tests")
             .help(concat!("Maximum number of nested tests"))
@@ -230,10 +232,12 @@ impl Builder<Schemas, ()> {
             .label("Header Size")
             .help(concat!("Maximum size of a header"))
             .default("1024")
+            .typ(Type::Size)
             .new_field("sieve.untrusted.limits.includes")
             .label("Includes")
             .help(concat!("Maximum number of includes"))
             .default("3")
+            .typ(Type::Input)
             .new_field("sieve.untrusted.limits.nested-includes")
             .label("Nested Includes")
             .help(concat!("Maximum number of nested includes"))
@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use crate::core::{form::Expression, schema::*};
+use crate::core::{form::Expression, schedule::ScheduleKind, schema::*};
 
 use super::*;
 
@@ -85,6 +85,16 @@ impl Builder<Schemas, ()> {
                 "queue.schedule.notify",
                 "queue.schedule.expire",
             ])
+            .schedule(
+                "queue.schedule.retry",
+                ScheduleKind::Intervals,
+                "queue.schedule.expire",
+            )
+            .schedule(
+                "queue.schedule.notify",
+                ScheduleKind::Offsets,
+                "queue.schedule.expire",
+            )
             .build()
             .new_form_section()
             .title("Delivery Status Notifications (DSN) Reports")
@@ -250,6 +260,8 @@ impl Builder<Schemas, ()> {
             .label("Max Report Size")
             .help(concat!("Maximum size of the TLS aggregate report in bytes"))
             .default("26214400")
+            .typ(Type::Size)
+            .input_check([Transformer::Trim], [Validator::Required])
             .new_field("report.tls.aggregate.send")
             .label("Frequency")
             .help(concat!(
@@ -257,6 +269,7 @@ impl Builder<Schemas, ()> {
                 "are hourly, daily, weekly, or never to disable reporting"
             ))
             .default("daily")
+            .typ(Type::Expression)
             .input_check(
                 [],
                 [
@@ -577,6 +590,81 @@ impl Builder<Schemas, ()> {
             .list_subtitle("Manage remote SMTP and LMTP servers for message delivery")
             .list_fields(["_id", "protocol", "address", "port"])
             .build()
+            // Seed mailboxes
+            .new_schema("seed-mailbox")
+            .prefix("troubleshoot.seed")
+            .suffix("address")
+            .names("seed mailbox", "seed mailboxes")
+            .new_id_field()
+            .label("Seed ID")
+            .help("Unique identifier for the seed mailbox")
+            .build()
+            .new_field("address")
+            .label("Address")
+            .help(concat!(
+                "The address of a mailbox hosted by an external provider, such ",
+                "as a Gmail or Outlook test account, that probe messages are sent to"
+            ))
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .placeholder("seed@gmail.com")
+            .build()
+            .new_field("imap.host")
+            .label("IMAP Server")
+            .help("The IMAP server used to fetch the probe messages back")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsHost])
+            .placeholder("imap.gmail.com")
+            .build()
+            .new_field("imap.port")
+            .label("Port")
+            .help("The port number of the IMAP server")
+            .typ(Type::Input)
+            .input_check([], [Validator::Required, Validator::IsPort])
+            .default("993")
+            .build()
+            .new_field("imap.tls.implicit")
+            .label("Implicit TLS")
+            .help("Whether to use TLS encryption for all connections to the IMAP server")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .new_field("auth.username")
+            .label("Username")
+            .help("The username to use when logging in to the IMAP server")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("auth.secret")
+            .label("Secret")
+            .help(concat!(
+                "The password or app password to use when logging in to the ",
+                "IMAP server"
+            ))
+            .typ(Type::Secret)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_form_section()
+            .title("Seed Mailbox")
+            .fields(["_id", "address"])
+            .build()
+            .new_form_section()
+            .title("IMAP")
+            .fields([
+                "imap.host",
+                "imap.port",
+                "imap.tls.implicit",
+                "auth.username",
+                "auth.secret",
+            ])
+            .build()
+            .list_title("Seed Mailboxes")
+            .list_subtitle("External mailboxes used to test deliverability to other providers")
+            .list_fields(["_id", "address", "imap.host"])
+            .build()
             // Outbound throttle
             .new_schema("smtp-out-throttle")
             .prefix("queue.throttle")
@@ -851,10 +939,20 @@ impl Builder<Schemas, ()> {
             .label("Bytes Limit")
             .default("262144000")
             .help("The maximum number of bytes that can be transferred per session")
+            .typ(Type::Size)
+            .input_check([Transformer::Trim], [Validator::Required])
             .new_field("session.duration")
             .label("Duration")
             .default("10m")
             .help("The maximum duration of a session")
+            .typ(Type::Expression)
+            .input_check(
+                [],
+                [
+                    Validator::Required,
+                    Validator::IsValidExpression(has_conn_vars),
+                ],
+            )
             .build()
             .new_form_section()
             .title("SMTP Session Limits")
@@ -1060,7 +1158,7 @@ impl Builder<Schemas, ()> {
             .help(concat!(
                 "A list of SASL authentication mechanisms offered to clients, or an ",
                 "empty list to disable authentication. Stalwart SMTP currently supports PLAIN, ",
-                "LOGIN, and OAUTHBEARER mechanisms"
+                "LOGIN, OAUTHBEARER, XOAUTH2 and SCRAM mechanisms"
             ))
             .default(Expression::new(
                 [
@@ -1232,6 +1330,8 @@ impl Builder<Schemas, ()> {
             .label("Size")
             .help("Maximum size of a message in bytes")
             .default("104857600")
+            .typ(Type::Size)
+            .input_check([Transformer::Trim], [Validator::Required])
             .new_field("session.data.limits.received-headers")
             .label("Received Headers")
             .help(concat!(
@@ -1239,6 +1339,14 @@ impl Builder<Schemas, ()> {
                 "which helps to prevent message loops"
             ))
             .default("50")
+            .typ(Type::Expression)
+            .input_check(
+                [],
+                [
+                    Validator::Required,
+                    Validator::IsValidExpression(has_rcpt_vars),
+                ],
+            )
             .new_field("session.data.add-headers.received")
             .label("Received")
             .help("Whether to add a Received header to the message")
@@ -1862,7 +1970,14 @@ impl Builder<Schemas, ()> {
 
 pub const VERIFY_CONSTANTS: &[&str] =
     &["relaxed", "strict", "disable", "disabled", "never", "none"];
-pub const AUTH_CONSTANTS: &[&str] = &["plain", "login", "xoauth2", "oauthbearer"];
+pub const AUTH_CONSTANTS: &[&str] = &[
+    "plain",
+    "login",
+    "xoauth2",
+    "oauthbearer",
+    "scram_sha_1",
+    "scram_sha_256",
+];
 pub const IP_STRATEGY_CONSTANTS: &[&str] =
     &["ipv4_only", "ipv6_only", "ipv6_then_ipv4", "ipv4_then_ipv6"];
 pub const REQUIRE_OPTIONAL_CONSTANTS: &[&str] = &[
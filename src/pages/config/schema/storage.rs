@@ -147,7 +147,7 @@ impl Builder<Schemas, ()> {
             ))
             .default("0 0 *")
             .typ(Type::Cron)
-            .input_check([], [Validator::Required])
+            .input_check([], [Validator::Required, Validator::IsCron])
             .build()
             .new_field("jmap.protocol.changes.max-history")
             .label("Changes history")
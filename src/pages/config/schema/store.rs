@@ -159,11 +159,14 @@ impl Builder<Schemas, ()> {
             // Purge frequency
             .new_field("purge.frequency")
             .label("Purge Frequency")
-            .help("How often to purge the database. Expects a cron expression")
+            .help("How often to purge the database")
             .display_if_ne("type", ["redis", "memory", "elasticsearch"])
             .default("0 3 *")
             .typ(Type::Cron)
-            .input_check([Transformer::Trim], [Validator::Required])
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsCron],
+            )
             .build()
             // Workers
             .new_field("pool.workers")
@@ -601,6 +604,7 @@ impl Builder<Schemas, ()> {
             .source_filter(&["redis"])
             .input_check([], [Validator::Required])
             .build()
+            .add_balancing_fields(&["postgresql", "mysql"])
             // Form layouts
             .new_form_section()
             .title("Configuration")
@@ -685,6 +689,11 @@ impl Builder<Schemas, ()> {
             .fields(["tls.enable", "tls.allow-invalid-certs"])
             .build()
             .new_form_section()
+            .title("Load Balancing")
+            .display_if_eq("type", ["postgresql", "mysql"])
+            .fields(BALANCING_FIELDS.iter().copied())
+            .build()
+            .new_form_section()
             .title("Pools")
             .display_if_eq("type", ["rocksdb", "sqlite", "postgresql", "mysql"])
             .fields([
@@ -894,3 +903,82 @@ impl Builder<Schemas, ()> {
             .build()
     }
 }
+
+// Replicated backends list their servers in `balance.endpoints`, one entry per
+// endpoint written as "<url> <weight> <priority>".
+pub static BALANCING_FIELDS: &[&str] = &[
+    "balance.strategy",
+    "balance.endpoints",
+    "balance.health-check.interval",
+    "balance.health-check.timeout",
+    "balance.health-check.failures",
+];
+
+impl Builder<Schemas, Schema> {
+    pub fn add_balancing_fields(self, types: &'static [&'static str]) -> Self {
+        self.new_field("balance.strategy")
+            .label("Strategy")
+            .help(concat!(
+                "How requests are distributed when the backend is replicated ",
+                "across several servers"
+            ))
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("single", "Single server"),
+                    ("weighted", "Weighted round-robin"),
+                    ("failover", "Failover in priority order"),
+                ]),
+            })
+            .default("single")
+            .display_if_eq("type", types.iter().copied())
+            .build()
+            .new_field("balance.endpoints")
+            .label("Endpoints")
+            .help(concat!(
+                "Servers requests are sent to, endpoints with a higher weight receive ",
+                "more requests and endpoints with a lower priority are tried first"
+            ))
+            .typ(Type::Array)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsEndpoint],
+            )
+            .display_if_eq("balance.strategy", ["weighted", "failover"])
+            .build()
+            .new_field("balance.health-check.interval")
+            .label("Check interval")
+            .help("How often each endpoint is checked for availability")
+            .typ(Type::Duration)
+            .default("30s")
+            .input_check([], [Validator::Required])
+            .display_if_eq("balance.strategy", ["weighted", "failover"])
+            .build()
+            .new_field("balance.health-check.timeout")
+            .label("Check timeout")
+            .help("Maximum time to wait for an endpoint to answer a health check")
+            .typ(Type::Duration)
+            .default("5s")
+            .input_check([], [Validator::Required])
+            .display_if_eq("balance.strategy", ["weighted", "failover"])
+            .build()
+            .new_field("balance.health-check.failures")
+            .label("Failure threshold")
+            .help(concat!(
+                "Number of consecutive failed checks after which an endpoint ",
+                "stops receiving requests"
+            ))
+            .typ(Type::Input)
+            .default("3")
+            .input_check(
+                [Transformer::Trim],
+                [
+                    Validator::Required,
+                    Validator::MinValue(1.into()),
+                    Validator::MaxValue(100.into()),
+                ],
+            )
+            .display_if_eq("balance.strategy", ["weighted", "failover"])
+            .build()
+    }
+}
@@ -236,6 +236,8 @@ impl Builder<Schemas, ()> {
             .new_form_section()
             .title("External Account Binding")
             .fields(["eab.kid", "eab.hmac-key"])
+            .required_with("eab.kid", "eab.hmac-key")
+            .required_with("eab.hmac-key", "eab.kid")
             .build()
             .new_form_section()
             .title("DNS settings")
@@ -253,12 +255,266 @@ impl Builder<Schemas, ()> {
                 "ttl",
                 "timeout",
             ])
+            .greater_than("propagation-timeout", "polling-interval")
             .build()
             .new_form_section()
             .title("Certificate")
             .fields(["account-key", "cert"])
             .build()
             .build()
+            // ---- Certificate deployment ----
+            .new_schema("acme-deploy")
+            .names("deployment target", "deployment targets")
+            .prefix("acme-deploy")
+            .suffix("type")
+            // Id
+            .new_id_field()
+            .label("Target Id")
+            .help("Unique identifier for the deployment target")
+            .build()
+            // Type
+            .new_field("type")
+            .typ(Type::Select {
+                source: Source::Static(&[
+                    ("webhook", "Webhook"),
+                    ("s3", "S3 object"),
+                    ("sftp", "SFTP"),
+                ]),
+                typ: SelectType::Single,
+            })
+            .label("Type")
+            .help("Where renewed certificates are pushed to")
+            .input_check([], [Validator::Required])
+            .default("webhook")
+            .build()
+            // Providers
+            .new_field("providers")
+            .typ(Type::Select {
+                source: Source::Dynamic {
+                    schema: "acme",
+                    field: "challenge",
+                    filter: Default::default(),
+                },
+                typ: SelectType::Many,
+            })
+            .label("ACME providers")
+            .help(concat!(
+                "Certificates renewed by these providers are pushed to this ",
+                "target, leave empty to push certificates from all providers"
+            ))
+            .build()
+            // Enable
+            .new_field("enable")
+            .typ(Type::Boolean)
+            .label("Enable")
+            .help("Whether to push certificates to this target after each renewal")
+            .default("true")
+            .build()
+            // Private key
+            .new_field("include-key")
+            .typ(Type::Boolean)
+            .label("Include private key")
+            .help(concat!(
+                "Whether the private key is pushed along with the certificate ",
+                "chain, only disable it when the target already has the key"
+            ))
+            .default("true")
+            .build()
+            // Webhook
+            .new_field("url")
+            .label("Endpoint URL")
+            .help("URL the PEM encoded certificate is posted to")
+            .placeholder("https://proxy.example.org/certificates")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .display_if_eq("type", ["webhook"])
+            .build()
+            .new_field("signature-key")
+            .label("Signature Key")
+            .help(concat!(
+                "The HMAC key used to sign the request body, so the ",
+                "receiving service can verify its origin"
+            ))
+            .typ(Type::Secret)
+            .display_if_eq("type", ["webhook"])
+            .build()
+            .new_field("headers")
+            .typ(Type::Array)
+            .label("HTTP Headers")
+            .help("The headers to be sent with each request")
+            .display_if_eq("type", ["webhook"])
+            .build()
+            .new_field("allow-invalid-certs")
+            .label("Allow Invalid Certs")
+            .help("Whether to connect to an endpoint that has an invalid TLS certificate")
+            .default("false")
+            .typ(Type::Boolean)
+            .display_if_eq("type", ["webhook"])
+            .build()
+            // S3
+            .new_field("s3.bucket")
+            .label("Bucket")
+            .help("Name of the bucket the certificate is uploaded to")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("type", ["s3"])
+            .build()
+            .new_field("s3.region")
+            .label("Region")
+            .help("The region of the bucket")
+            .placeholder("us-east-1")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("type", ["s3"])
+            .build()
+            .new_field("s3.endpoint")
+            .label("Endpoint")
+            .help("Custom endpoint for S3 compatible services, leave empty for AWS")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::IsUrl])
+            .display_if_eq("type", ["s3"])
+            .build()
+            .new_field("s3.access-key")
+            .label("Access Key")
+            .help("The access key ID used to authenticate with the service")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("type", ["s3"])
+            .build()
+            .new_field("s3.secret-key")
+            .label("Secret Key")
+            .help("The secret access key used to authenticate with the service")
+            .typ(Type::Secret)
+            .display_if_eq("type", ["s3"])
+            .build()
+            .new_field("s3.key")
+            .label("Object Key")
+            .help(concat!(
+                "Key of the uploaded object, where {domain} is replaced ",
+                "with the primary subject name of the certificate"
+            ))
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .default("certificates/{domain}.pem")
+            .display_if_eq("type", ["s3"])
+            .build()
+            // SFTP
+            .new_field("sftp.host")
+            .label("Host")
+            .help("Hostname or IP address of the SFTP server")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.port")
+            .label("Port")
+            .help("Port of the SFTP server")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsPort],
+            )
+            .default("22")
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.host-key")
+            .label("Host Key Fingerprint")
+            .help(concat!(
+                "SHA256 fingerprint of the SFTP server host key, as shown by ",
+                "ssh-keygen -lf. The upload is aborted when the server presents a different key"
+            ))
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsHostKeyFingerprint],
+            )
+            .placeholder("SHA256:...")
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.user")
+            .label("Username")
+            .help("The username used to log in to the SFTP server")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.password")
+            .label("Password")
+            .help("The password used to log in, leave empty when using a private key")
+            .typ(Type::Secret)
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.private-key")
+            .label("Private Key")
+            .help("SSH private key in PEM format used to log in")
+            .typ(Type::Secret)
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("sftp.path")
+            .label("Remote Path")
+            .help(concat!(
+                "Path the certificate is written to, where {domain} is ",
+                "replaced with the primary subject name of the certificate"
+            ))
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .default("/etc/ssl/private/{domain}.pem")
+            .display_if_eq("type", ["sftp"])
+            .build()
+            .new_field("timeout")
+            .label("Timeout")
+            .help("Maximum amount of time to wait for the target to accept the certificate")
+            .default("30s")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            // Lists
+            .list_title("Certificate deployment")
+            .list_subtitle("Push renewed ACME certificates to external services")
+            .list_fields(["_id", "type", "providers", "enable"])
+            // Form
+            .new_form_section()
+            .title("Deployment target")
+            .fields([
+                "_id",
+                "type",
+                "providers",
+                "enable",
+                "include-key",
+                "timeout",
+            ])
+            .build()
+            .new_form_section()
+            .title("Webhook")
+            .display_if_eq("type", ["webhook"])
+            .fields(["url", "signature-key", "headers", "allow-invalid-certs"])
+            .build()
+            .new_form_section()
+            .title("S3 bucket")
+            .display_if_eq("type", ["s3"])
+            .fields([
+                "s3.bucket",
+                "s3.region",
+                "s3.endpoint",
+                "s3.access-key",
+                "s3.secret-key",
+                "s3.key",
+            ])
+            .build()
+            .new_form_section()
+            .title("SFTP server")
+            .display_if_eq("type", ["sftp"])
+            .fields([
+                "sftp.host",
+                "sftp.port",
+                "sftp.host-key",
+                "sftp.user",
+                "sftp.password",
+                "sftp.private-key",
+                "sftp.path",
+            ])
+            .build()
+            .build()
             // ---- TLS certificates ----
             .new_schema("certificate")
             .reload_prefix("certificate")
@@ -277,117 +277,6 @@ impl Builder<Schemas, ()> {
             .list_subtitle("Manage custom event logging levels")
             .list_fields(["_id", "_value"])
             .build()
-            // Metrics
-            .new_schema("metrics")
-            // OT Transport
-            .new_field("metrics.open-telemetry.transport")
-            .typ(Type::Select {
-                typ: SelectType::Single,
-                source: Source::Static(&[
-                    ("disabled", "Disabled"),
-                    ("http", "HTTP"),
-                    ("grpc", "gRPC"),
-                ]),
-            })
-            .label("Transport")
-            .help("The transport protocol for Open Telemetry")
-            .input_check([], [Validator::Required])
-            .default("disabled")
-            .build()
-            // OT Endpoint
-            .new_field("metrics.open-telemetry.endpoint")
-            .typ(Type::Input)
-            .label("Endpoint")
-            .help("The endpoint for Open Telemetry")
-            .placeholder("https://tracing.example.com/v1/otel")
-            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
-            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
-            .build()
-            // OT Headers
-            .new_field("metrics.open-telemetry.headers")
-            .typ(Type::Array)
-            .label("HTTP Headers")
-            .help("The headers to be sent with OpenTelemetry requests")
-            .display_if_eq("metrics.open-telemetry.transport", ["http"])
-            .build()
-            // OT Timeout
-            .new_field("metrics.open-telemetry.timeout")
-            .label("Timeout")
-            .help(concat!(
-                "Maximum amount of time that Stalwart will wait for a response ",
-                "from the OpenTelemetry endpoint"
-            ))
-            .default("10s")
-            .typ(Type::Duration)
-            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
-            .input_check([], [Validator::Required])
-            .build()
-            // OT Throttle
-            .new_field("metrics.open-telemetry.interval")
-            .label("Push interval")
-            .help(concat!(
-                "The minimum amount of time that must pass between ",
-                "each push request to the OpenTelemetry endpoint"
-            ))
-            .default("1m")
-            .display_if_eq("metrics.open-telemetry.transport", ["http", "grpc"])
-            .typ(Type::Duration)
-            .input_check([], [Validator::Required])
-            .build()
-            // Prometheus auth
-            .new_field("metrics.prometheus.enable")
-            .typ(Type::Boolean)
-            .label("Enable endpoint")
-            .help("Enable the Prometheus metrics endpoint")
-            .default("false")
-            .build()
-            .new_field("metrics.prometheus.auth.username")
-            .label("Username")
-            .help(concat!(
-                "The Prometheus endpoint's username for Basic authentication"
-            ))
-            .typ(Type::Input)
-            .input_check([Transformer::Trim], [])
-            .build()
-            .new_field("metrics.prometheus.auth.secret")
-            .label("Secret")
-            .help(concat!(
-                "The Prometheus endpoint's secret for Basic authentication"
-            ))
-            .typ(Type::Secret)
-            .build()
-            // Disabled events
-            .new_field("metrics.disabled-events")
-            .label("Disabled Metrics")
-            .help("Which events to disable for metrics")
-            .typ(Type::Select {
-                typ: SelectType::ManyWithSearch,
-                source: Source::StaticId(EVENT_NAMES),
-            })
-            .build()
-            .new_form_section()
-            .title("OpenTelemetry Push Metrics")
-            .fields([
-                "metrics.open-telemetry.transport",
-                "metrics.open-telemetry.endpoint",
-                "metrics.open-telemetry.timeout",
-                "metrics.open-telemetry.interval",
-                "metrics.open-telemetry.headers",
-            ])
-            .build()
-            .new_form_section()
-            .title("Prometheus Pull Metrics")
-            .fields([
-                "metrics.prometheus.auth.username",
-                "metrics.prometheus.auth.secret",
-                "metrics.prometheus.enable",
-            ])
-            .build()
-            .new_form_section()
-            .title("Override metrics")
-            .fields(["metrics.disabled-events"])
-            .build()
-            .build()
             .new_schema("telemetry-history")
             .new_field("tracing.history.store")
             .label("Tracing Store")
@@ -456,14 +345,33 @@ impl Builder<Schemas, ()> {
             .enterprise_feature()
             .build()
             .new_field("metrics.history.retention")
-            .label("Retention period")
+            .label("Raw samples")
             .help(concat!(
-                "How long to keep metrics history before it is permanently deleted.",
-                "(Enterprise feature)"
+                "How long to keep metrics samples at the collection frequency ",
+                "before they are permanently deleted. (Enterprise feature)"
             ))
             .default("90d")
             .typ(Type::Duration)
             .enterprise_feature()
+            .new_field("metrics.history.rollup.5m")
+            .label("5 minute rollups")
+            .help(concat!(
+                "How long to keep metrics aggregated over 5 minute windows, ",
+                "leave empty to disable this resolution. (Enterprise feature)"
+            ))
+            .typ(Type::Duration)
+            .enterprise_feature()
+            .build()
+            .new_field("metrics.history.rollup.1h")
+            .label("1 hour rollups")
+            .help(concat!(
+                "How long to keep metrics aggregated over 1 hour windows, ",
+                "used by the longer dashboard periods. Leave empty to disable ",
+                "this resolution. (Enterprise feature)"
+            ))
+            .default("365d")
+            .typ(Type::Duration)
+            .enterprise_feature()
             .new_field("metrics.history.enable")
             .label("Enable metrics history")
             .help(concat!(
@@ -480,7 +388,7 @@ impl Builder<Schemas, ()> {
             ))
             .default("0 * *")
             .typ(Type::Cron)
-            .input_check([], [Validator::Required])
+            .input_check([], [Validator::Required, Validator::IsCron])
             .enterprise_feature()
             .build()
             .new_form_section()
@@ -496,10 +404,17 @@ impl Builder<Schemas, ()> {
             .fields([
                 "metrics.history.store",
                 "metrics.history.interval",
-                "metrics.history.retention",
                 "metrics.history.enable",
             ])
             .build()
+            .new_form_section()
+            .title("Metrics Retention")
+            .fields([
+                "metrics.history.retention",
+                "metrics.history.rollup.5m",
+                "metrics.history.rollup.1h",
+            ])
+            .build()
             .build()
             // Alerts
             .new_schema("alerts")
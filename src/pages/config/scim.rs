@@ -0,0 +1,194 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::{use_navigate, A};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{button::Button, FormItem, FormSection},
+        icon::IconKey,
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::FormatDateTime,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct ScimToken {
+    #[serde(rename = "createdAt")]
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "lastUsedAt")]
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[component]
+pub fn ScimEndpoint() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let (pending, set_pending) = create_signal(false);
+
+    let base_url = move || {
+        let base_url = auth.get().base_url.to_string();
+        let base_url = if base_url.is_empty() {
+            window().location().origin().unwrap_or_default()
+        } else {
+            base_url
+        };
+        format!("{base_url}/scim/v2")
+    };
+
+    // The token itself is never returned by the server, only whether one exists.
+    let token = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match HttpRequest::get("/api/scim/token")
+                    .with_authorization(&auth)
+                    .send::<ScimToken>()
+                    .await
+                {
+                    Ok(token) => Ok(Some(token)),
+                    Err(http::Error::NotFound) => Ok(None),
+                    Err(err) => Err(err),
+                }
+            }
+        },
+    );
+
+    let generate_token = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/scim/token")
+                .with_authorization(&auth)
+                .send::<String>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(secret) => {
+                    token.refetch();
+                    modal.set(
+                        Modal::with_title("SCIM token generated")
+                            .with_message(concat!(
+                                "Copy this token into your identity provider now. ",
+                                "For security reasons it will not be displayed again."
+                            ))
+                            .with_details([("Tenant URL", base_url()), ("Secret token", secret)])
+                            .with_button("Done"),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let has_token = move || token.get().and_then(|token| token.ok()).flatten().is_some();
+    let token_status = move || match token.get() {
+        None => "Loading...".to_string(),
+        Some(Ok(Some(token))) => {
+            let created = token
+                .created_at
+                .map(|created_at| format!("Created {}", created_at.format_date_time()))
+                .unwrap_or_else(|| "Active".to_string());
+            match token.last_used_at {
+                Some(last_used_at) => {
+                    format!("{created}, last used {}", last_used_at.format_date_time())
+                }
+                None => format!("{created}, never used"),
+            }
+        }
+        Some(Ok(None)) => "No token has been generated yet".to_string(),
+        Some(Err(http::Error::Unauthorized)) => {
+            use_navigate()("/login", Default::default());
+            String::new()
+        }
+        Some(Err(err)) => {
+            alert.set(Alert::from(err));
+            "Unavailable".to_string()
+        }
+    };
+
+    view! {
+        <FormSection title="Provisioning Endpoint".to_string()>
+            <FormItem
+                label="Tenant URL"
+                tooltip="The SCIM base URL to configure in your identity provider"
+            >
+                <input
+                    type="text"
+                    class="py-2 px-3 pe-11 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    readonly=true
+                    prop:value=base_url
+                />
+            </FormItem>
+            <FormItem label="Secret token">
+                <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                    <span class="text-sm text-gray-600 dark:text-gray-400">{token_status}</span>
+                    <Button
+                        text=Signal::derive(move || {
+                            if has_token() { "Rotate token" } else { "Generate token" }.to_string()
+                        })
+
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if has_token() {
+                                modal.set(
+                                    Modal::with_title("Rotate SCIM token")
+                                        .with_message(concat!(
+                                            "The current token will stop working immediately and ",
+                                            "provisioning will fail until the new token is ",
+                                            "configured in your identity provider."
+                                        ))
+                                        .with_button("Rotate token")
+                                        .with_dangerous_callback(move || {
+                                            generate_token.dispatch(());
+                                        }),
+                                );
+                            } else {
+                                generate_token.dispatch(());
+                            }
+                        }
+
+                        disabled=pending
+                    >
+
+                        <IconKey/>
+                    </Button>
+                </div>
+            </FormItem>
+            <FormItem label="Activity">
+                <A
+                    class="text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                    href="/manage/scim-activity"
+                >
+                    View recent provisioning operations
+                </A>
+            </FormItem>
+        </FormSection>
+    }
+}
@@ -10,11 +10,30 @@ use leptos::*;
 use leptos_router::use_query_map;
 
 use crate::{
-    components::{list::ZeroResults, report::ReportView},
+    components::{layout::MenuItem, list::ZeroResults, report::ReportView},
+    core::command::is_subsequence,
     pages::config::Schemas,
 };
 
-use super::{Field, Form, Section};
+use super::{Field, Form, SchemaType, Section};
+
+// Every field of the schemas reachable from the settings menu, built once at
+// startup for the search palette.
+pub struct SettingsIndex {
+    entries: Vec<IndexEntry>,
+}
+
+pub struct IndexEntry {
+    pub path: String,
+    pub label: &'static str,
+    pub help: Option<&'static str>,
+    pub key: String,
+    pub url: String,
+    terms: [String; 4],
+}
+
+// Label, setting key, menu path and help text, in decreasing weight
+const TERM_WEIGHTS: [u32; 4] = [6, 4, 2, 1];
 
 #[component]
 pub fn SettingsSearch() -> impl IntoView {
@@ -112,6 +131,129 @@ pub fn SettingsSearch() -> impl IntoView {
     }
 }
 
+impl SettingsIndex {
+    pub fn build(schemas: &Schemas, menu: &[MenuItem]) -> Self {
+        let mut index = SettingsIndex { entries: vec![] };
+        index.add_menu(schemas, menu, &[]);
+        index
+    }
+
+    fn add_menu(&mut self, schemas: &Schemas, menu: &[MenuItem], parents: &[&str]) {
+        for item in menu {
+            let mut path = parents.to_vec();
+            path.push(item.name.as_str());
+
+            if let Some(schema) = item
+                .route
+                .as_deref()
+                .and_then(|route| route.strip_prefix("/settings/"))
+                .and_then(|route| schemas.schemas.get(route.trim_end_matches("/edit")))
+            {
+                for (section_idx, section) in schema.form.sections.iter().enumerate() {
+                    let mut path = path.join(" › ");
+                    if let Some(title) = section.title.filter(|title| !title.is_empty()) {
+                        path = format!("{path} › {title}");
+                    }
+
+                    // Records are opened from their list, only singleton
+                    // forms can link to a section.
+                    let url = match schema.typ {
+                        SchemaType::List => {
+                            format!("/settings/{}/edit#section-{section_idx}", schema.id)
+                        }
+                        SchemaType::Record { .. } | SchemaType::Entry { .. } => {
+                            format!("/settings/{}", schema.id)
+                        }
+                    };
+
+                    for field in &section.fields {
+                        let key = match schema.typ {
+                            SchemaType::Record { prefix, .. } => {
+                                format!("{prefix}.<id>.{}", field.id)
+                            }
+                            SchemaType::Entry { prefix } => format!("{prefix}.<id>"),
+                            SchemaType::List => field.id.to_string(),
+                        };
+                        let terms = [
+                            field.label_form.to_lowercase(),
+                            key.to_lowercase(),
+                            path.to_lowercase(),
+                            field.help.unwrap_or_default().to_lowercase(),
+                        ];
+                        self.entries.push(IndexEntry {
+                            path: path.clone(),
+                            label: field.label_form,
+                            help: field.help,
+                            key,
+                            url: url.clone(),
+                            terms,
+                        });
+                    }
+                }
+            }
+
+            self.add_menu(schemas, &item.children, &path);
+        }
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&IndexEntry> {
+        let words = query
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return vec![];
+        }
+
+        let mut results = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                words
+                    .iter()
+                    .map(|word| entry.score(word))
+                    .sum::<Option<u32>>()
+                    .map(|score| (score, entry))
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|(l_score, l_entry), (r_score, r_entry)| {
+            r_score
+                .cmp(l_score)
+                .then_with(|| l_entry.label.cmp(r_entry.label))
+        });
+        results
+            .into_iter()
+            .take(limit)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+impl IndexEntry {
+    fn score(&self, word: &str) -> Option<u32> {
+        self.terms
+            .iter()
+            .zip(TERM_WEIGHTS)
+            .enumerate()
+            .filter_map(|(idx, (term, weight))| {
+                if let Some(pos) = term.find(word) {
+                    let is_word_start = term[..pos]
+                        .chars()
+                        .next_back()
+                        .is_none_or(|ch| !ch.is_alphanumeric());
+                    Some(weight * if is_word_start { 3 } else { 2 })
+                } else if idx < 2 && is_subsequence(term, word) {
+                    // Typos and abbreviations only match labels and keys,
+                    // help texts are long enough to match anything.
+                    Some(weight)
+                } else {
+                    None
+                }
+            })
+            .max()
+    }
+}
+
 trait ContainsString {
     fn contains_string(&self, query: &[String]) -> Option<&'static str>;
 }
@@ -0,0 +1,747 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+
+use crate::{
+    components::{
+        form::button::Button,
+        icon::{IconAdd, IconArrowDownTray, IconArrowUpTray, IconTrash},
+        list::{
+            header::ColumnList, row::SelectItem, toolbar::ToolbarButton, Footer, ItemSelection,
+            ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        crypto::{decrypt_with_passphrase, encrypt_with_passphrase, EncryptedData},
+        download::download_file,
+        http::{self, HttpRequest, ManagementApiError},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+use super::{
+    cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+    Schemas, Settings, UpdateSettings,
+};
+
+pub const SNAPSHOT_STORAGE_KEY: &str = "webadmin_snapshots";
+pub const MAX_SNAPSHOTS: usize = 10;
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub reason: String,
+    pub settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FetchSettings {
+    items: Settings,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SettingsExport {
+    exported: DateTime<Utc>,
+    settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExportFile {
+    Encrypted { encrypted: EncryptedData },
+    Plain(SettingsExport),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transfer {
+    Export,
+    Import,
+}
+
+// Snapshots are kept in the browser, so secrets are left out of them and
+// restoring a snapshot does not change the stored secrets.
+pub async fn create_snapshot(
+    auth: &AccessToken,
+    schemas: &Schemas,
+    reason: impl Into<String>,
+) -> Result<Snapshot, http::Error> {
+    let created = Utc::now();
+    let mut settings = fetch_settings(auth).await?;
+    settings.retain(|key, _| !schemas.is_secret_setting(key));
+    let snapshot = Snapshot {
+        id: created.timestamp_millis().to_string(),
+        created,
+        reason: reason.into(),
+        settings,
+    };
+
+    push_snapshot(auth, snapshot.clone())?;
+
+    Ok(snapshot)
+}
+
+pub async fn restore_snapshot(
+    auth: &AccessToken,
+    schemas: &Schemas,
+    snapshot: &Snapshot,
+) -> Result<ReloadOutcome, http::Error> {
+    let current = create_snapshot(
+        auth,
+        schemas,
+        format!(
+            "Before restoring snapshot from {}",
+            snapshot.created.format_date_time()
+        ),
+    )
+    .await?;
+
+    let delete_keys = current
+        .settings
+        .keys()
+        .filter(|key| !snapshot.settings.contains_key(*key))
+        .cloned()
+        .collect::<Vec<_>>();
+    let insert_values = snapshot
+        .settings
+        .iter()
+        .filter(|(key, value)| current.settings.get(*key) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<Vec<_>>();
+
+    let mut updates = Vec::with_capacity(2);
+    if !delete_keys.is_empty() {
+        updates.push(UpdateSettings::Delete { keys: delete_keys });
+    }
+    if !insert_values.is_empty() {
+        updates.push(UpdateSettings::Insert {
+            prefix: None,
+            values: insert_values,
+            assert_empty: false,
+        });
+    }
+
+    if !updates.is_empty() {
+        HttpRequest::post("/api/settings")
+            .with_authorization(auth)
+            .with_body(updates)?
+            .send::<serde_json::Value>()
+            .await?;
+    }
+
+    reload_settings(auth, "").await
+}
+
+fn storage_key(auth: &AccessToken) -> String {
+    format!("{SNAPSHOT_STORAGE_KEY}_{}@{}", auth.username, auth.base_url)
+}
+
+pub fn stored_snapshots(auth: &AccessToken) -> Vec<Snapshot> {
+    LocalStorage::get::<Vec<Snapshot>>(storage_key(auth)).unwrap_or_default()
+}
+
+fn push_snapshot(auth: &AccessToken, snapshot: Snapshot) -> Result<(), http::Error> {
+    let mut snapshots = stored_snapshots(auth);
+    snapshots.insert(0, snapshot);
+    snapshots.truncate(MAX_SNAPSHOTS);
+    store_snapshots(auth, snapshots)
+}
+
+fn store_snapshots(auth: &AccessToken, snapshots: Vec<Snapshot>) -> Result<(), http::Error> {
+    LocalStorage::set(storage_key(auth), snapshots).map_err(|err| {
+        http::Error::Server(ManagementApiError::Other {
+            details: "Failed to store snapshot".to_string(),
+            reason: Some(err.to_string()),
+        })
+    })
+}
+
+async fn fetch_settings(auth: &AccessToken) -> Result<BTreeMap<String, String>, http::Error> {
+    HttpRequest::get("/api/settings/list")
+        .with_authorization(auth)
+        .send::<FetchSettings>()
+        .await
+        .map(|list| list.items.into_iter().collect())
+}
+
+#[component]
+pub fn SnapshotList() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+    let schemas = expect_context::<Arc<Schemas>>();
+
+    let snapshots = create_rw_signal(stored_snapshots(&auth.get_untracked()));
+    // Imported settings may contain secrets, so they are only kept in memory
+    let imported = create_rw_signal(None::<Snapshot>);
+    let total_results = Signal::derive(move || {
+        Some((snapshots.get().len() + imported.with(|imported| imported.iter().count())) as u32)
+    });
+    let transfer = create_rw_signal(None::<Transfer>);
+    let cluster_results = create_rw_signal(None);
+
+    let schemas_ = schemas.clone();
+    let snapshot_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let schemas = schemas_.clone();
+
+        async move {
+            match create_snapshot(&auth, &schemas, "Manual snapshot").await {
+                Ok(_) => {
+                    snapshots.set(stored_snapshots(&auth));
+                    alert.set(Alert::success("Snapshot created."));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let restore_action = create_action(move |snapshot: &Snapshot| {
+        let snapshot = snapshot.clone();
+        let auth = auth.get();
+        let schemas = schemas.clone();
+
+        async move {
+            match restore_snapshot(&auth, &schemas, &snapshot).await {
+                Ok(outcome) => {
+                    snapshots.set(stored_snapshots(&auth));
+                    if imported.with_untracked(|imported| {
+                        imported
+                            .as_ref()
+                            .is_some_and(|imported| imported.id == snapshot.id)
+                    }) {
+                        imported.set(None);
+                    }
+                    match outcome {
+                        ReloadOutcome::Local(result)
+                            if !result.errors.is_empty() || !result.warnings.is_empty() =>
+                        {
+                            alert.set(Alert::from_reload(result, &schemas));
+                        }
+                        outcome => {
+                            if let ReloadOutcome::Cluster(results) = outcome {
+                                cluster_results.set(Some(results));
+                            }
+                            alert.set(Alert::success(format!(
+                                "Restored snapshot from {}.",
+                                snapshot.created.format_date_time()
+                            )));
+                        }
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let on_restore = Callback::new(move |snapshot: Snapshot| {
+        modal.set(
+            Modal::with_title("Confirm restore")
+                .with_message(format!(
+                    concat!(
+                        "Are you sure you want to restore the snapshot from {}? ",
+                        "The current settings will be snapshotted before they are replaced."
+                    ),
+                    snapshot.created.format_date_time()
+                ))
+                .with_button("Restore snapshot")
+                .with_dangerous_callback(move || {
+                    restore_action.dispatch(snapshot.clone());
+                }),
+        )
+    });
+
+    view! {
+        <ListSection>
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            <ListTable
+                title="Snapshots"
+                subtitle="Restore the settings saved before a risky change"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().total_selected(total_results.get());
+                            if ns > 0 { format!("Delete ({ns})") } else { "Delete".to_string() }
+                        })
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            let to_delete = selected.get().total_selected(total_results.get());
+                            if to_delete > 0 {
+                                let text = maybe_plural(to_delete, "snapshot", "snapshots");
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm deletion")
+                                            .with_message(
+                                                format!(
+                                                    "Are you sure you want to delete {text}? This action cannot be undone.",
+                                                ),
+                                            )
+                                            .with_button(format!("Delete {text}"))
+                                            .with_dangerous_callback(move || {
+                                                let items = selected
+                                                    .try_update(std::mem::take)
+                                                    .unwrap_or_default();
+                                                let auth = auth.get_untracked();
+                                                let mut list = stored_snapshots(&auth);
+                                                match &items {
+                                                    ItemSelection::All => {
+                                                        list.clear();
+                                                        imported.set(None);
+                                                    }
+                                                    ItemSelection::Some(ids) => {
+                                                        list.retain(|s| !ids.contains(&s.id));
+                                                        imported
+                                                            .update(|imported| {
+                                                                if imported
+                                                                    .as_ref()
+                                                                    .is_some_and(|s| ids.contains(&s.id))
+                                                                {
+                                                                    *imported = None;
+                                                                }
+                                                            });
+                                                    }
+                                                    ItemSelection::None => {}
+                                                }
+                                                match store_snapshots(&auth, list) {
+                                                    Ok(_) => {
+                                                        alert.set(Alert::success(format!("Deleted {text}.")));
+                                                    }
+                                                    Err(err) => {
+                                                        alert.set(Alert::from(err));
+                                                    }
+                                                }
+                                                snapshots.set(stored_snapshots(&auth));
+                                            }),
+                                    )
+                            }
+                        })
+                    >
+
+                        <IconTrash/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Export"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            transfer.set(Some(Transfer::Export));
+                        })
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Import"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            transfer.set(Some(Transfer::Import));
+                        })
+                    >
+
+                        <IconArrowUpTray/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Create snapshot"
+                        color=Color::Blue
+                        on_click=Callback::new(move |_| {
+                            snapshot_action.dispatch(());
+                        })
+                    >
+
+                        <IconAdd size=16 attr:class="flex-shrink-0 size-3"/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                {move || {
+                    let list = imported
+                        .get()
+                        .into_iter()
+                        .chain(snapshots.get())
+                        .collect::<Vec<_>>();
+                    if !list.is_empty() {
+                        view! {
+                            <ColumnList
+                                headers=vec![
+                                    "Created".to_string(),
+                                    "Reason".to_string(),
+                                    "Settings".to_string(),
+                                    "".to_string(),
+                                ]
+
+                                has_select_all=true
+                            >
+
+                                <For
+                                    each=move || list.clone()
+                                    key=|snapshot| snapshot.id.clone()
+                                    let:snapshot
+                                >
+                                    <SnapshotItem snapshot on_restore/>
+                                </For>
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    } else {
+                        view! {
+                            <ZeroResults
+                                title="No snapshots"
+                                subtitle="Snapshots are created automatically before risky operations."
+                                button_text="Create snapshot"
+                                button_action=Callback::new(move |_| {
+                                    snapshot_action.dispatch(());
+                                })
+                            />
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    {move || match transfer.get() {
+                        Some(Transfer::Export) => view! { <ExportPanel transfer/> }.into_view(),
+                        Some(Transfer::Import) => {
+                            view! {
+                                <ImportPanel
+                                    transfer
+                                    on_import=Callback::new(move |snapshot| {
+                                        imported.set(Some(snapshot));
+                                    })
+                                />
+                            }
+                                .into_view()
+                        }
+                        None => ().into_view(),
+                    }}
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {format!("Only the last {MAX_SNAPSHOTS} snapshots are kept.")}
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn SnapshotItem(snapshot: Snapshot, on_restore: Callback<Snapshot>) -> impl IntoView {
+    let snapshot_id = snapshot.id.clone();
+    let created = snapshot.created.format_date_time();
+    let reason = snapshot.reason.clone();
+    let num_settings = snapshot.settings.len().to_string();
+    let snapshot = Arc::new(snapshot);
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex">
+                    <SelectItem item_id=snapshot_id/>
+
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListTextItem>{created}</ListTextItem>
+            <ListTextItem>{reason}</ListTextItem>
+            <ListTextItem>{num_settings}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    on:click=move |_| {
+                        on_restore.call(snapshot.as_ref().clone());
+                    }
+                >
+
+                    Restore
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
+
+#[component]
+fn ExportPanel(transfer: RwSignal<Option<Transfer>>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let passphrase = create_rw_signal(String::new());
+    let confirm = create_rw_signal(String::new());
+    let mismatch =
+        create_memo(move |_| !confirm.get().is_empty() && passphrase.get() != confirm.get());
+
+    let export_action = create_action(move |passphrase: &String| {
+        let passphrase = passphrase.clone();
+        let auth = auth.get();
+
+        async move {
+            let settings = match fetch_settings(&auth).await {
+                Ok(settings) => settings,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+            let exported = Utc::now();
+            let contents = SettingsExport { exported, settings };
+            let file = if passphrase.is_empty() {
+                ExportFile::Plain(contents)
+            } else {
+                match encrypt_with_passphrase(
+                    &passphrase,
+                    &serde_json::to_vec(&contents).unwrap_or_default(),
+                )
+                .await
+                {
+                    Ok(encrypted) => ExportFile::Encrypted { encrypted },
+                    Err(err) => {
+                        alert.set(Alert::error("Encryption failed").with_details(err));
+                        return;
+                    }
+                }
+            };
+
+            download_file(
+                &format!("settings-{}.json", exported.format("%Y%m%d-%H%M%S")),
+                "application/json",
+                &serde_json::to_string_pretty(&file).unwrap_or_default(),
+            );
+            transfer.set(None);
+        }
+    });
+
+    view! {
+        <div class="px-6 py-4 space-y-3 border-t border-gray-200 dark:border-gray-700">
+            <p class="text-sm text-gray-600 dark:text-gray-400">
+                The export contains every setting, including passwords and keys. Enter a passphrase to encrypt the file in the browser before it is saved.
+            </p>
+            <div class="grid gap-3 sm:grid-cols-2">
+                <input
+                    type="password"
+                    class=INPUT_CLASS
+                    placeholder="Passphrase (optional)"
+                    autocomplete="new-password"
+                    prop:value=passphrase
+                    on:input=move |ev| passphrase.set(event_target_value(&ev))
+                />
+                <input
+                    type="password"
+                    class=INPUT_CLASS
+                    placeholder="Confirm passphrase"
+                    autocomplete="new-password"
+                    prop:value=confirm
+                    on:input=move |ev| confirm.set(event_target_value(&ev))
+                />
+            </div>
+            <p class="text-xs text-red-600" class:hidden=move || !mismatch.get()>
+                The passphrases do not match.
+            </p>
+            <div class="flex gap-x-2">
+                <Button
+                    text=Signal::derive(move || {
+                        if passphrase.get().is_empty() {
+                            "Export unencrypted"
+                        } else {
+                            "Export encrypted"
+                        }
+                            .to_string()
+                    })
+
+                    color=Color::Blue
+                    on_click=move |_| {
+                        export_action.dispatch(passphrase.get_untracked());
+                    }
+
+                    disabled=Signal::derive(move || {
+                        export_action.pending().get()
+                            || (!passphrase.get().is_empty() && passphrase.get() != confirm.get())
+                    })
+                />
+
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        transfer.set(None);
+                    }
+                />
+
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn ImportPanel(
+    transfer: RwSignal<Option<Transfer>>,
+    on_import: Callback<Snapshot>,
+) -> impl IntoView {
+    let alert = use_alerts();
+    let file = create_rw_signal(None::<(String, ExportFile)>);
+    let passphrase = create_rw_signal(String::new());
+    let is_encrypted =
+        create_memo(move |_| matches!(file.get(), Some((_, ExportFile::Encrypted { .. }))));
+
+    let import = move |name: String, contents: SettingsExport| {
+        let num_settings = contents.settings.len();
+        let created = Utc::now();
+        on_import.call(Snapshot {
+            id: format!("import-{}", created.timestamp_millis()),
+            created,
+            reason: format!(
+                "Imported from {name} (exported {}), not stored",
+                contents.exported.format_date_time()
+            ),
+            settings: contents.settings,
+        });
+        transfer.set(None);
+        alert.set(Alert::success(format!(
+            "Imported {}, restore the import to apply them.",
+            maybe_plural(num_settings, "setting", "settings")
+        )));
+    };
+
+    let decrypt_action = create_action(move |passphrase: &String| {
+        let passphrase = passphrase.clone();
+
+        async move {
+            let Some((name, ExportFile::Encrypted { encrypted })) = file.get_untracked() else {
+                return;
+            };
+            match decrypt_with_passphrase(&passphrase, &encrypted)
+                .await
+                .and_then(|contents| {
+                    serde_json::from_slice::<SettingsExport>(&contents)
+                        .map_err(|err| format!("Invalid settings export: {err}"))
+                }) {
+                Ok(contents) => import(name, contents),
+                Err(err) => {
+                    alert.set(Alert::error("Import failed").with_details(err));
+                }
+            }
+        }
+    });
+
+    let select_file = move |ev: web_sys::Event| {
+        let Some(selected) = event_target::<HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        spawn_local(async move {
+            let name = selected.name();
+            let text = JsFuture::from(selected.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+                .unwrap_or_default();
+
+            match serde_json::from_str::<ExportFile>(&text) {
+                Ok(ExportFile::Plain(contents)) => import(name, contents),
+                Ok(encrypted) => {
+                    file.set(Some((name, encrypted)));
+                }
+                Err(_) => {
+                    alert.set(
+                        Alert::error("Import failed")
+                            .with_details("The file is not a settings export."),
+                    );
+                }
+            }
+        });
+    };
+
+    view! {
+        <div class="px-6 py-4 space-y-3 border-t border-gray-200 dark:border-gray-700">
+            <p class="text-sm text-gray-600 dark:text-gray-400">
+                Imported settings are listed with the snapshots until you leave this page, nothing is changed on the server until the import is restored.
+            </p>
+            <input
+                type="file"
+                class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-neutral-500"
+                accept=".json,application/json"
+                on:change=select_file
+            />
+            <Show when=move || is_encrypted.get()>
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    This export is encrypted, enter the passphrase used when it was created.
+                </p>
+                <input
+                    type="password"
+                    class=INPUT_CLASS
+                    placeholder="Passphrase"
+                    prop:value=passphrase
+                    on:input=move |ev| passphrase.set(event_target_value(&ev))
+                />
+            </Show>
+            <div class="flex gap-x-2">
+                <Show when=move || is_encrypted.get()>
+                    <Button
+                        text="Decrypt and import"
+                        color=Color::Blue
+                        on_click=move |_| {
+                            decrypt_action.dispatch(passphrase.get_untracked());
+                        }
+
+                        disabled=Signal::derive(move || {
+                            decrypt_action.pending().get() || passphrase.get().is_empty()
+                        })
+                    />
+                </Show>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        transfer.set(None);
+                    }
+                />
+
+            </div>
+        </div>
+    }
+}
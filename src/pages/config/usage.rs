@@ -0,0 +1,233 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        report::{ReportSection, ReportView},
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{config::Schemas, List},
+};
+
+const HISTORY_DAYS: i64 = 30;
+const HISTORY_PAGE_SIZE: usize = 500;
+const MAX_SETTINGS: usize = 10;
+// Settings changed more often than this in the last week are flagged
+const FREQUENT_CHANGES_PER_WEEK: usize = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsChange {
+    timestamp: DateTime<Utc>,
+    #[serde(default)]
+    object_id: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SettingUsage {
+    title: String,
+    url: Option<String>,
+    total: usize,
+    last_week: usize,
+    last_changed: DateTime<Utc>,
+}
+
+// Settings changed most often in this deployment, taken from the audit log
+// so that changes made by every admin are counted.
+#[component]
+pub fn FrequentSettingsPanel() -> impl IntoView {
+    let auth = use_authorization();
+    let schemas = expect_context::<Arc<Schemas>>();
+
+    let usage = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let schemas = schemas.clone();
+
+            async move {
+                let changes = fetch_changes(&auth).await?;
+
+                Ok(summarize(&schemas, changes))
+            }
+        },
+    );
+
+    view! {
+        {move || match usage.get() {
+            Some(Err(http::Error::Unauthorized)) => {
+                use_navigate()("/login", Default::default());
+                None
+            }
+            Some(Ok(usage)) if !usage.is_empty() => {
+                let frequent = usage
+                    .iter()
+                    .filter(|usage| usage.last_week > FREQUENT_CHANGES_PER_WEEK)
+                    .count();
+                Some(
+                    view! {
+                        <ReportView>
+                            <ReportSection title="Frequently adjusted settings">
+                                <p class="sm:col-span-12 text-sm text-gray-500 dark:text-gray-400">
+                                    {format!("Most changed settings in the last {HISTORY_DAYS} days.")}
+                                    {(frequent > 0)
+                                        .then(|| {
+                                            format!(
+                                                " Settings changed more than {FREQUENT_CHANGES_PER_WEEK} times in a week may be worth automating or alerting on.",
+                                            )
+                                        })}
+
+                                </p>
+                                <table class="sm:col-span-12 min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                                    <thead>
+                                        <tr class="text-xs font-medium uppercase text-gray-500">
+                                            <th class="py-2 pe-4 text-start">Setting</th>
+                                            <th class="py-2 pe-4 text-end">Changes</th>
+                                            <th class="py-2 pe-4 text-end">Last 7 days</th>
+                                            <th class="py-2 text-start">Last changed</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                        {usage
+                                            .into_iter()
+                                            .map(|usage| {
+                                                let is_frequent = usage.last_week
+                                                    > FREQUENT_CHANGES_PER_WEEK;
+                                                view! {
+                                                    <tr class="text-gray-800 dark:text-gray-200">
+                                                        <td class="py-2 pe-4">
+                                                            {match usage.url {
+                                                                Some(url) => {
+                                                                    view! {
+                                                                        <a
+                                                                            class="text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                                                                            href=url
+                                                                        >
+                                                                            {usage.title}
+                                                                        </a>
+                                                                    }
+                                                                        .into_view()
+                                                                }
+                                                                None => usage.title.into_view(),
+                                                            }}
+                                                            {is_frequent
+                                                                .then(|| {
+                                                                    view! {
+                                                                        <span class="ms-2">
+                                                                            <Badge color=Color::Yellow>
+                                                                                Frequent
+                                                                            </Badge>
+                                                                        </span>
+                                                                    }
+                                                                })}
+
+                                                        </td>
+                                                        <td class="py-2 pe-4 text-end">{usage.total}</td>
+                                                        <td class="py-2 pe-4 text-end">
+                                                            {usage.last_week}
+                                                        </td>
+                                                        <td class="py-2 whitespace-nowrap">
+                                                            <Timestamp
+                                                                date=usage.last_changed
+                                                                relative=true
+                                                            />
+                                                        </td>
+                                                    </tr>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </tbody>
+                                </table>
+                            </ReportSection>
+                        </ReportView>
+                    }
+                        .into_view(),
+                )
+            }
+            // Nothing changed yet, or the audit log is not available to this admin
+            _ => None,
+        }}
+    }
+}
+
+async fn fetch_changes(auth: &AccessToken) -> http::Result<Vec<SettingsChange>> {
+    let since =
+        (Utc::now() - Duration::days(HISTORY_DAYS)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut changes = Vec::new();
+    let mut page = 1;
+    loop {
+        let list = HttpRequest::get("/api/audit")
+            .with_authorization(auth)
+            .with_parameter("objectType", "settings")
+            .with_parameter("since", &since)
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", HISTORY_PAGE_SIZE.to_string())
+            .send::<List<SettingsChange>>()
+            .await?;
+        let num_items = list.items.len();
+        changes.extend(list.items);
+        if num_items < HISTORY_PAGE_SIZE || changes.len() as u64 >= list.total {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(changes)
+}
+
+fn summarize(schemas: &Schemas, changes: Vec<SettingsChange>) -> Vec<SettingUsage> {
+    let week_ago = Utc::now() - Duration::days(7);
+    let mut usage: AHashMap<String, SettingUsage> = AHashMap::new();
+
+    for change in changes {
+        let Some(key) = change.object_id.filter(|key| !key.is_empty()) else {
+            continue;
+        };
+        // Changes to different keys of the same object are counted together
+        let location = schemas.locate_setting(&key);
+        let (id, title, url) = match location {
+            Some(location) => (location.url.clone(), location.title, Some(location.url)),
+            None => (key.clone(), key, None),
+        };
+        let entry = usage.entry(id).or_insert_with(|| SettingUsage {
+            title,
+            url,
+            total: 0,
+            last_week: 0,
+            last_changed: change.timestamp,
+        });
+        entry.total += 1;
+        if change.timestamp >= week_ago {
+            entry.last_week += 1;
+        }
+        entry.last_changed = entry.last_changed.max(change.timestamp);
+    }
+
+    let mut usage = usage.into_values().collect::<Vec<_>>();
+    usage.sort_by(|a, b| {
+        b.last_week
+            .cmp(&a.last_week)
+            .then_with(|| b.total.cmp(&a.total))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    usage.truncate(MAX_SETTINGS);
+    usage
+}
@@ -0,0 +1,530 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::{AHashMap, AHashSet};
+use leptos::*;
+use leptos_router::{use_navigate, use_params_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{button::Button, Form, FormButtonBar, FormItem, FormSection},
+        icon::IconTrash,
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{
+        directory::{Principal, PrincipalField, PrincipalType, PrincipalUpdate, PrincipalValue},
+        List,
+    },
+};
+
+const PRINCIPALS_PAGE_SIZE: usize = 100;
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const INPUT_ERROR_CLASS: &str = "py-2 px-3 block w-full border-red-500 shadow-sm rounded-lg text-sm focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct AliasRule {
+    pattern: String,
+    target: String,
+}
+
+// Addresses on the domain that are routed through special principal e-mails,
+// either the "@domain" catch-all or local parts containing wildcards.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct DomainAddresses {
+    catch_all: String,
+    rules: Vec<AliasRule>,
+}
+
+#[component]
+pub fn DomainAddressRules() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let params = use_params_map();
+    let domain = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+
+    let (pending, set_pending) = create_signal(false);
+    let catch_all = create_rw_signal(String::new());
+    let rules = create_rw_signal(Vec::<AliasRule>::new());
+    let errors = create_rw_signal(AHashMap::<usize, String>::new());
+    let catch_all_error = create_rw_signal(None::<String>);
+    let current = store_value(DomainAddresses::default());
+
+    let fetch_addresses = create_resource(
+        move || domain.get(),
+        move |domain| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_domain_addresses(&auth, &domain).await }
+        },
+    );
+
+    let save_changes = create_action(move |changes: &DomainAddresses| {
+        let changes = changes.clone();
+        let auth = auth.get();
+        let domain = domain.get();
+
+        async move {
+            set_pending.set(true);
+
+            // Make sure every target is an existing principal that can receive mail
+            let mut targets = AHashMap::new();
+            for target in [changes.catch_all.as_str()]
+                .into_iter()
+                .chain(changes.rules.iter().map(|rule| rule.target.as_str()))
+                .filter(|target| !target.is_empty())
+                .collect::<AHashSet<_>>()
+            {
+                let result = match HttpRequest::get(("/api/principal", target))
+                    .with_authorization(&auth)
+                    .send::<Principal>()
+                    .await
+                {
+                    Ok(principal) => match principal.typ {
+                        Some(
+                            PrincipalType::Individual | PrincipalType::Group | PrincipalType::List,
+                        ) => Ok(()),
+                        _ => Err(format!("{target} is not an account, group or mailing list")),
+                    },
+                    Err(http::Error::NotFound) => Err(format!("{target} does not exist")),
+                    Err(http::Error::Unauthorized) => {
+                        set_pending.set(false);
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        set_pending.set(false);
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                };
+                targets.insert(target.to_string(), result);
+            }
+
+            let mut has_errors = false;
+            if let Some(Err(err)) = targets.get(&changes.catch_all) {
+                catch_all_error.set(Some(err.clone()));
+                has_errors = true;
+            }
+            errors.update(|errors| {
+                for (idx, rule) in changes.rules.iter().enumerate() {
+                    if let Some(Err(err)) = targets.get(&rule.target) {
+                        errors.insert(idx, err.clone());
+                        has_errors = true;
+                    }
+                }
+            });
+            if has_errors {
+                set_pending.set(false);
+                return;
+            }
+
+            // Additions go first and each principal receives its additions and
+            // removals in a single update, so that a failed save never leaves
+            // an address without a principal
+            let previous = current.get_value().assignments(&domain);
+            let next = changes.assignments(&domain);
+            let mut additions: AHashMap<&str, Vec<PrincipalUpdate>> = AHashMap::new();
+            let mut removals: AHashMap<&str, Vec<PrincipalUpdate>> = AHashMap::new();
+            for (address, principal) in &next {
+                if !previous.contains(&(address.clone(), principal.clone())) {
+                    additions.entry(principal.as_str()).or_default().push(
+                        PrincipalUpdate::add_item(
+                            PrincipalField::Emails,
+                            PrincipalValue::String(address.clone()),
+                        ),
+                    );
+                }
+            }
+            for (address, principal) in &previous {
+                if !next.contains(&(address.clone(), principal.clone())) {
+                    removals.entry(principal.as_str()).or_default().push(
+                        PrincipalUpdate::remove_item(
+                            PrincipalField::Emails,
+                            PrincipalValue::String(address.clone()),
+                        ),
+                    );
+                }
+            }
+            let mut updates = additions
+                .into_iter()
+                .map(|(principal, mut updates)| {
+                    updates.extend(removals.remove(principal).unwrap_or_default());
+                    (principal, updates)
+                })
+                .collect::<Vec<_>>();
+            updates.extend(removals);
+
+            let mut result = Ok(());
+            for (principal, updates) in updates {
+                result = HttpRequest::patch(("/api/principal", principal))
+                    .with_authorization(&auth)
+                    .with_body(updates)
+                    .unwrap()
+                    .send::<serde_json::Value>()
+                    .await
+                    .map(|_| ());
+                if result.is_err() {
+                    break;
+                }
+            }
+            set_pending.set(false);
+
+            match result {
+                Ok(_) => {
+                    fetch_addresses.refetch();
+                    alert.set(Alert::success("Address rules saved.").with_details(format!(
+                        "Catch-all and alias rules for {domain} have been updated."
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    fetch_addresses.refetch();
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title=Signal::derive(move || format!("Address rules for {}", domain.get()))
+            subtitle="Route mail for unmatched and wildcard addresses to existing accounts, groups or lists"
+        >
+
+            <Transition fallback=Skeleton>
+
+                {move || match fetch_addresses.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok(addresses)) => {
+                        catch_all.set(addresses.catch_all.clone());
+                        rules.set(addresses.rules.clone());
+                        errors.set(AHashMap::new());
+                        catch_all_error.set(None);
+                        let currently = addresses.catch_all.clone();
+                        current.set_value(addresses);
+                        Some(
+                            view! {
+                                <FormSection title="Catch-all".to_string()>
+                                    <FormItem
+                                        label="Deliver unmatched mail to"
+                                        tooltip="Account, group or mailing list that receives mail sent to addresses that do not exist on this domain"
+                                        is_optional=true
+                                    >
+                                        <input
+                                            type="text"
+                                            class=move || {
+                                                if catch_all_error.get().is_none() {
+                                                    INPUT_CLASS
+                                                } else {
+                                                    INPUT_ERROR_CLASS
+                                                }
+                                            }
+
+                                            autocapitalize="off"
+                                            placeholder="Leave empty to reject unknown recipients"
+                                            prop:value=catch_all
+                                            on:change=move |ev| {
+                                                catch_all.set(event_target_value(&ev).trim().to_lowercase());
+                                                catch_all_error.set(None);
+                                            }
+                                        />
+
+                                        <p class="text-xs text-gray-500 dark:text-gray-400 mt-2">
+                                            {if currently.is_empty() {
+                                                "Unmatched addresses are currently rejected.".to_string()
+                                            } else {
+                                                format!("Unmatched addresses are currently delivered to {currently}.")
+                                            }}
+
+                                        </p>
+                                        {move || {
+                                            catch_all_error
+                                                .get()
+                                                .map(|error| {
+                                                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                                                })
+                                        }}
+
+                                    </FormItem>
+                                </FormSection>
+
+                                <FormSection title="Alias Rules".to_string()>
+                                    <FormItem
+                                        label="Wildcard aliases"
+                                        tooltip="Local part patterns, such as sales-* or *.billing, delivered to the given principal"
+                                        is_optional=true
+                                    >
+                                        <div class="space-y-3">
+                                            <For
+                                                each=move || {
+                                                    rules.get().into_iter().enumerate().collect::<Vec<_>>()
+                                                }
+
+                                                key=|(idx, rule)| format!("{idx}_{}_{}", rule.pattern, rule.target)
+                                                children=move |(idx, rule)| {
+                                                    let error = create_memo(move |_| {
+                                                        errors.get().get(&idx).cloned()
+                                                    });
+                                                    view! {
+                                                        <AliasRuleItem
+                                                            idx=idx
+                                                            rule=rule
+                                                            domain=domain
+                                                            rules=rules
+                                                            errors=errors
+                                                            error=error
+                                                        />
+                                                    }
+                                                }
+                                            />
+
+                                            <button
+                                                type="button"
+                                                class="py-1.5 px-2 inline-flex items-center gap-x-1 text-xs font-medium rounded-full border border-dashed border-gray-200 bg-white text-gray-800 hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300 dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                                on:click=move |_| {
+                                                    rules.update(|rules| rules.push(AliasRule::default()));
+                                                }
+                                            >
+
+                                                "+ Add rule"
+                                            </button>
+                                        </div>
+                                    </FormItem>
+                                </FormSection>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <FormButtonBar>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/manage/directory/domains", Default::default());
+                    }
+                />
+
+                <Button
+                    text="Save changes"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        let changes = DomainAddresses {
+                            catch_all: catch_all.get(),
+                            rules: rules.get(),
+                        };
+                        let rule_errors = changes.validate();
+                        let is_valid = rule_errors.is_empty();
+                        errors.set(rule_errors);
+                        if is_valid {
+                            save_changes.dispatch(changes);
+                        }
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+fn AliasRuleItem(
+    idx: usize,
+    rule: AliasRule,
+    domain: Memo<String>,
+    rules: RwSignal<Vec<AliasRule>>,
+    errors: RwSignal<AHashMap<usize, String>>,
+    error: Memo<Option<String>>,
+) -> impl IntoView {
+    let update_rule = move |update: &dyn Fn(&mut AliasRule)| {
+        rules.update(|rules| {
+            if let Some(rule) = rules.get_mut(idx) {
+                update(rule);
+            }
+        });
+        errors.update(|errors| {
+            errors.remove(&idx);
+        });
+    };
+    let input_class = move || {
+        if error.get().is_none() {
+            INPUT_CLASS
+        } else {
+            INPUT_ERROR_CLASS
+        }
+    };
+
+    view! {
+        <div>
+            <div class="flex items-center gap-x-2">
+                <div class="flex flex-1 rounded-lg shadow-sm">
+                    <input
+                        type="text"
+                        class=input_class
+                        autocapitalize="off"
+                        placeholder="sales-*"
+                        prop:value=rule.pattern
+                        on:change=move |ev| {
+                            let pattern = event_target_value(&ev).trim().to_lowercase();
+                            update_rule(&move |rule| rule.pattern = pattern.clone());
+                        }
+                    />
+
+                    <span class="px-3 inline-flex items-center min-w-fit rounded-e-md border border-s-0 border-gray-200 bg-gray-50 text-sm text-gray-500 dark:bg-gray-700 dark:border-gray-700 dark:text-gray-400">
+                        {move || format!("@{}", domain.get())}
+                    </span>
+                </div>
+                <span class="text-sm text-gray-500 dark:text-gray-400">"→"</span>
+                <input
+                    type="text"
+                    class=move || format!("flex-1 {}", input_class())
+                    autocapitalize="off"
+                    placeholder="Account, group or list name"
+                    prop:value=rule.target
+                    on:change=move |ev| {
+                        let target = event_target_value(&ev).trim().to_lowercase();
+                        update_rule(&move |rule| rule.target = target.clone());
+                    }
+                />
+
+                <button
+                    type="button"
+                    class="p-2 inline-flex items-center rounded-lg text-gray-500 hover:bg-gray-100 dark:text-gray-400 dark:hover:bg-gray-800"
+                    on:click=move |_| {
+                        rules
+                            .update(|rules| {
+                                if idx < rules.len() {
+                                    rules.remove(idx);
+                                }
+                            });
+                        errors.set(AHashMap::new());
+                    }
+                >
+
+                    <span class="sr-only">Remove</span>
+                    <IconTrash attr:class="flex-shrink-0 size-4"/>
+                </button>
+            </div>
+            {move || {
+                error.get().map(|error| view! { <p class="text-xs text-red-600 mt-2">{error}</p> })
+            }}
+
+        </div>
+    }
+}
+
+async fn fetch_domain_addresses(auth: &AccessToken, domain: &str) -> http::Result<DomainAddresses> {
+    let suffix = format!("@{domain}");
+    let mut addresses = DomainAddresses::default();
+    let mut num_principals = 0;
+    let mut page = 1;
+
+    loop {
+        let list = HttpRequest::get("/api/principal")
+            .with_authorization(auth)
+            .with_parameter("types", "individual,group,list")
+            .with_parameter("fields", "name,emails")
+            .with_parameter("filter", &suffix)
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", PRINCIPALS_PAGE_SIZE.to_string())
+            .send::<List<Principal>>()
+            .await?;
+        let num_items = list.items.len();
+        num_principals += num_items;
+
+        for principal in list.items {
+            let name = principal.name.unwrap_string();
+            for email in principal.emails.unwrap_string_list() {
+                if email == suffix {
+                    addresses.catch_all = name.clone();
+                } else if let Some(local) = email
+                    .strip_suffix(&suffix)
+                    .filter(|local| local.contains('*'))
+                {
+                    addresses.rules.push(AliasRule {
+                        pattern: local.to_string(),
+                        target: name.clone(),
+                    });
+                }
+            }
+        }
+
+        if num_items < PRINCIPALS_PAGE_SIZE || num_principals as u64 >= list.total {
+            break;
+        }
+        page += 1;
+    }
+    addresses.rules.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+
+    Ok(addresses)
+}
+
+impl DomainAddresses {
+    fn validate(&self) -> AHashMap<usize, String> {
+        let mut errors = AHashMap::new();
+        let mut patterns = AHashSet::new();
+
+        for (idx, rule) in self.rules.iter().enumerate() {
+            let error = if rule.pattern.is_empty() {
+                "Enter an address pattern"
+            } else if !rule.pattern.contains('*') {
+                "Patterns must contain a * wildcard, add plain aliases from the principal instead"
+            } else if rule.pattern == "*" {
+                "Use the catch-all setting to match every address"
+            } else if !rule
+                .pattern
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '*' | '.' | '-' | '_' | '+'))
+            {
+                "Patterns may only contain letters, digits, '.', '-', '_', '+' and '*'"
+            } else if !patterns.insert(rule.pattern.as_str()) {
+                "This pattern is already in use"
+            } else if rule.target.is_empty() {
+                "Enter the account, group or list that receives this mail"
+            } else {
+                continue;
+            };
+            errors.insert(idx, error.to_string());
+        }
+
+        errors
+    }
+
+    fn assignments(&self, domain: &str) -> Vec<(String, String)> {
+        (!self.catch_all.is_empty())
+            .then(|| (format!("@{domain}"), self.catch_all.clone()))
+            .into_iter()
+            .chain(
+                self.rules
+                    .iter()
+                    .map(|rule| (format!("{}@{domain}", rule.pattern), rule.target.clone())),
+            )
+            .collect()
+    }
+}
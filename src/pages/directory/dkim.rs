@@ -0,0 +1,712 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{NaiveDate, Utc};
+use leptos::*;
+use leptos_router::{use_navigate, use_query_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, select::Select, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::{IconAdd, IconRefresh},
+        list::{
+            header::ColumnList,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert, Alerts},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+        AccessToken, Permission,
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            Settings, UpdateSettings,
+        },
+        directory::dns::{DnsRecord, DnsRecordStatus},
+        List,
+    },
+};
+
+const SIGNATURES_PAGE_SIZE: usize = 100;
+// Rotated keys are staged under the id of the key they replace plus this suffix
+const NEXT_KEY_SUFFIX: &str = "-next";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Algorithm {
+    #[default]
+    Rsa,
+    Ed25519,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DkimSignature {
+    pub id: Option<String>,
+    pub algorithm: Algorithm,
+    pub domain: String,
+    pub selector: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DkimKey {
+    id: String,
+    domain: String,
+    selector: String,
+    algorithm: String,
+    record: Option<DnsRecord>,
+    // Id of the key this staged key will replace once activated
+    replaces: Option<String>,
+    has_next: bool,
+}
+
+#[derive(Debug, Clone)]
+enum KeyAction {
+    Generate(DkimSignature),
+    Rotate(DkimKey),
+    Activate(DkimKey),
+    Delete(DkimKey),
+}
+
+#[component]
+pub fn DkimKeys() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let query = use_query_map();
+    let filter = create_rw_signal(
+        query
+            .get_untracked()
+            .get("domain")
+            .cloned()
+            .unwrap_or_default(),
+    );
+    let can_manage = create_memo(move |_| {
+        auth.get()
+            .permissions()
+            .has_access_all(&[Permission::DkimSignatureCreate, Permission::SettingsUpdate])
+    });
+
+    let (pending, set_pending) = create_signal(false);
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let mut data = schemas.with_value(|schemas| schemas.build_form("dkim-generate"));
+    data.apply_defaults(false);
+    data.set("domain", filter.get_untracked());
+    let data = data.into_signal();
+
+    let keys = create_resource(
+        move || filter.get(),
+        move |filter| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_keys(&auth, &filter).await.map(Arc::new) }
+        },
+    );
+
+    let cluster_results = create_rw_signal(None);
+    let key_action = create_action(move |action: &KeyAction| {
+        let action = action.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let (result, message) = match action {
+                KeyAction::Generate(signature) => (
+                    create_key(&auth, &signature).await,
+                    format!("A new DKIM key was generated for {}.", signature.domain),
+                ),
+                KeyAction::Rotate(key) => {
+                    // The new key is staged next to the current one, which keeps
+                    // signing until the new DNS record is live.
+                    let selector = new_selector(key.algorithm());
+                    (
+                        create_key(
+                            &auth,
+                            &DkimSignature {
+                                id: format!("{}{NEXT_KEY_SUFFIX}", key.id).into(),
+                                algorithm: key.algorithm(),
+                                domain: key.domain.clone(),
+                                selector: selector.clone().into(),
+                            },
+                        )
+                        .await,
+                        format!(
+                            concat!(
+                                "A new key with selector {} was created for {}. Publish its ",
+                                "DNS record and activate it once the record is verified."
+                            ),
+                            selector, key.domain
+                        ),
+                    )
+                }
+                KeyAction::Activate(key) => (
+                    activate_key(&auth, &key).await,
+                    format!(
+                        concat!(
+                            "Messages from {} are now signed with selector {}. Keep the ",
+                            "previous DNS record published until messages signed with it ",
+                            "have been delivered."
+                        ),
+                        key.domain, key.selector
+                    ),
+                ),
+                KeyAction::Delete(key) => (
+                    delete_key(&auth, &key).await,
+                    format!("Key {} was deleted.", key.id),
+                ),
+            };
+            let result = match result {
+                Ok(_) => reload_settings(&auth, "").await,
+                Err(err) => Err(err),
+            };
+            set_pending.set(false);
+
+            match result {
+                Ok(ReloadOutcome::Local(result)) if !result.errors.is_empty() => {
+                    keys.refetch();
+                    alert.set(Alert::from_reload(result, &schemas.get_value()));
+                }
+                Ok(outcome) => {
+                    if let ReloadOutcome::Cluster(results) = outcome {
+                        cluster_results.set(Some(results));
+                    }
+                    keys.refetch();
+                    alert.set(Alert::success(message));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let on_rotate = move |key: DkimKey| {
+        modal.set(
+            Modal::with_title("Rotate DKIM key")
+                .with_message(format!(
+                    concat!(
+                        "A new private key and selector will be created for key {}. ",
+                        "Signing continues with the current key until the new DNS ",
+                        "record has been published and the new key is activated."
+                    ),
+                    key.id
+                ))
+                .with_button("Rotate key")
+                .with_dangerous_callback(move || {
+                    key_action.dispatch(KeyAction::Rotate(key.clone()));
+                }),
+        )
+    };
+    let on_activate = move |key: DkimKey| {
+        modal.set(
+            Modal::with_title("Activate DKIM key")
+                .with_message(format!(
+                    concat!(
+                        "Messages from {} will be signed with selector {} and key {} ",
+                        "will be retired. Keep its DNS record published until messages ",
+                        "signed with it have been delivered."
+                    ),
+                    key.domain,
+                    key.selector,
+                    key.replaces.as_deref().unwrap_or_default()
+                ))
+                .with_button("Activate key")
+                .with_dangerous_callback(move || {
+                    key_action.dispatch(KeyAction::Activate(key.clone()));
+                }),
+        )
+    };
+    let on_delete = move |key: DkimKey| {
+        modal.set(
+            Modal::with_title("Delete DKIM key")
+                .with_message(format!(
+                    concat!(
+                        "Are you sure you want to delete key {}? Messages sent from ",
+                        "{} will no longer be signed with it."
+                    ),
+                    key.id, key.domain
+                ))
+                .with_button("Delete key")
+                .with_dangerous_callback(move || {
+                    key_action.dispatch(KeyAction::Delete(key.clone()));
+                }),
+        )
+    };
+
+    view! {
+        <div class="max-w-[85rem] px-4 py-10 sm:px-6 lg:px-8 lg:py-14 mx-auto">
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+            <Alerts/>
+            <ListTable title="DKIM Keys" subtitle="Manage the keys used to sign outgoing messages">
+                <Toolbar slot>
+                    <SearchBox
+                        value=Signal::derive(move || Some(filter.get()))
+                        on_search=move |value: String| {
+                            data.update(|data| data.update("domain", value.clone()));
+                            filter.set(value);
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Verify DNS"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            keys.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match keys.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(keys)) if !keys.is_empty() => {
+                            let keys = keys.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Domain".to_string(),
+                                        "Selector".to_string(),
+                                        "Algorithm".to_string(),
+                                        "Key size".to_string(),
+                                        "Created".to_string(),
+                                        "DNS record".to_string(),
+                                        "Status".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || keys.as_ref().clone()
+                                            key=|key| (key.id.clone(), key.selector.clone())
+                                            let:key
+                                        >
+                                            <KeyItem key can_manage on_rotate on_activate on_delete/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No DKIM keys"
+                                        subtitle="Generate a key below to start signing outgoing messages."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+
+            <div
+                class="mt-8 bg-white rounded-xl shadow p-4 sm:p-7 dark:bg-slate-900"
+                class:hidden=move || !can_manage.get()
+            >
+                <FormSection title="Generate Key".to_string()>
+                    <FormItem label="Domain" tooltip="Domain that will sign messages with this key">
+                        <InputText element=FormElement::new("domain", data)/>
+                    </FormItem>
+                    <FormItem label="Algorithm">
+                        <Select element=FormElement::new("algorithm", data)/>
+                    </FormItem>
+                    <FormItem
+                        label="Selector"
+                        tooltip="Leave empty to generate a date-based selector"
+                        is_optional=true
+                    >
+                        <InputText element=FormElement::new("selector", data)/>
+                    </FormItem>
+                </FormSection>
+                <FormButtonBar>
+                    <Button
+                        text="Generate key"
+                        color=Color::Blue
+                        on_click=Callback::new(move |_| {
+                            data.update(|data| {
+                                if data.validate_form() {
+                                    let algorithm = match data.value_as_str("algorithm") {
+                                        Some("rsa") => Algorithm::Rsa,
+                                        _ => Algorithm::Ed25519,
+                                    };
+                                    let selector = data
+                                        .value::<String>("selector")
+                                        .unwrap_or_else(|| new_selector(algorithm));
+                                    key_action
+                                        .dispatch(
+                                            KeyAction::Generate(DkimSignature {
+                                                id: None,
+                                                algorithm,
+                                                domain: data.value("domain").unwrap_or_default(),
+                                                selector: selector.into(),
+                                            }),
+                                        );
+                                }
+                            });
+                        })
+
+                        disabled=pending
+                    >
+                        <IconAdd/>
+                    </Button>
+                </FormButtonBar>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn KeyItem(
+    key: DkimKey,
+    #[prop(into)] can_manage: Signal<bool>,
+    on_rotate: impl Fn(DkimKey) + 'static,
+    on_activate: impl Fn(DkimKey) + 'static,
+    on_delete: impl Fn(DkimKey) + 'static,
+) -> impl IntoView {
+    let status = key
+        .record
+        .as_ref()
+        .map(|record| record.status)
+        .unwrap_or(DnsRecordStatus::Missing);
+    let record = key
+        .record
+        .as_ref()
+        .map(|record| format!("{} IN TXT \"{}\"", record.name, record.content))
+        .unwrap_or_else(|| "Record not available".to_string());
+    let key_size = key
+        .key_size()
+        .map(|size| size.to_string())
+        .unwrap_or_else(|| "-".to_string());
+    let created = key.created().unwrap_or_else(|| "-".to_string());
+    let algorithm = key.algorithm_label();
+    let is_staged = key.replaces.is_some();
+    let has_next = key.has_next;
+    let key_rotate = key.clone();
+    let key_delete = key.clone();
+
+    view! {
+        <tr>
+            <ListItem subclass="ps-6 pe-6 py-3">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {key.domain}
+                </span>
+                <span class="block text-xs text-gray-500">{key.id}</span>
+            </ListItem>
+            <ListTextItem>{key.selector}</ListTextItem>
+            <ListTextItem>{algorithm}</ListTextItem>
+            <ListTextItem>{key_size}</ListTextItem>
+            <ListTextItem>{created}</ListTextItem>
+            <ListItem class="max-w-md" subclass="ps-6 py-3">
+                <textarea
+                    class="py-1 px-2 block w-full min-w-64 border-gray-200 rounded-lg text-xs font-mono dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                    readonly=true
+                    rows=2
+                    prop:value=record
+                ></textarea>
+            </ListItem>
+            <ListItem>
+                <Badge color=status.color()>{status.label()}</Badge>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex gap-x-3" class:hidden=move || !can_manage.get()>
+                    {if is_staged {
+                        view! {
+                            <button
+                                type="button"
+                                class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                disabled=status != DnsRecordStatus::Verified
+                                title="Publish the DNS record before activating the key"
+                                on:click=move |_| on_activate(key_rotate.clone())
+                            >
+                                Activate
+                            </button>
+                        }
+                    } else {
+                        view! {
+                            <button
+                                type="button"
+                                class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                disabled=has_next
+                                on:click=move |_| on_rotate(key_rotate.clone())
+                            >
+                                Rotate
+                            </button>
+                        }
+                    }}
+                    <button
+                        type="button"
+                        class="text-sm text-red-600 decoration-2 hover:underline font-medium"
+                        on:click=move |_| on_delete(key_delete.clone())
+                    >
+                        Delete
+                    </button>
+                </div>
+            </ListItem>
+        </tr>
+    }
+}
+
+async fn fetch_keys(auth: &AccessToken, filter: &str) -> http::Result<Vec<DkimKey>> {
+    let mut signatures = Vec::new();
+    let mut page = 1;
+    loop {
+        let list = HttpRequest::get("/api/settings/group")
+            .with_authorization(auth)
+            .with_parameter("prefix", "signature")
+            .with_parameter("suffix", "algorithm")
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", SIGNATURES_PAGE_SIZE.to_string())
+            .with_optional_parameter("filter", Some(filter).filter(|f| !f.is_empty()))
+            .send::<List<Settings>>()
+            .await?;
+        let num_items = list.items.len();
+        signatures.extend(list.items);
+        if num_items < SIGNATURES_PAGE_SIZE || signatures.len() as u64 >= list.total {
+            break;
+        }
+        page += 1;
+    }
+
+    // The DNS records endpoint already includes the DKIM public keys,
+    // so fetch it once per domain and match the records by selector.
+    let mut records: AHashMap<String, Vec<DnsRecord>> = AHashMap::new();
+    let mut keys = Vec::with_capacity(signatures.len());
+    for mut signature in signatures {
+        let domain = signature.remove("domain").unwrap_or_default();
+        let selector = signature.remove("selector").unwrap_or_default();
+        if !records.contains_key(&domain) {
+            let domain_records = HttpRequest::get(("/api/dns/records", &domain))
+                .with_authorization(auth)
+                .try_send::<Vec<DnsRecord>>()
+                .await?
+                .unwrap_or_default();
+            records.insert(domain.clone(), domain_records);
+        }
+        let record_name = format!("{selector}._domainkey.{domain}.");
+        let record = records.get(&domain).and_then(|records| {
+            records
+                .iter()
+                .find(|record| record.typ == "TXT" && record.name == record_name)
+                .cloned()
+        });
+
+        keys.push(DkimKey {
+            id: signature.remove("_id").unwrap_or_default(),
+            algorithm: signature.remove("algorithm").unwrap_or_default(),
+            domain,
+            selector,
+            record,
+            replaces: None,
+            has_next: false,
+        });
+    }
+    let ids = keys
+        .iter()
+        .map(|key| key.id.clone())
+        .collect::<AHashSet<_>>();
+    for key in &mut keys {
+        key.replaces = key
+            .id
+            .strip_suffix(NEXT_KEY_SUFFIX)
+            .filter(|id| ids.contains(*id))
+            .map(|id| id.to_string());
+        key.has_next = ids.contains(&format!("{}{NEXT_KEY_SUFFIX}", key.id));
+    }
+    keys.sort_by(|a, b| a.domain.cmp(&b.domain).then_with(|| a.id.cmp(&b.id)));
+
+    Ok(keys)
+}
+
+async fn create_key(auth: &AccessToken, signature: &DkimSignature) -> http::Result<()> {
+    HttpRequest::post("/api/dkim")
+        .with_authorization(auth)
+        .with_body(signature)?
+        .send::<serde_json::Value>()
+        .await
+        .map(|_| ())
+}
+
+// The server moves the staged key, including its private key, to the id used
+// by the signing rules, replacing the previous key in a single step.
+async fn activate_key(auth: &AccessToken, key: &DkimKey) -> http::Result<()> {
+    HttpRequest::post(format!("/api/dkim/{}/activate", key.id))
+        .with_authorization(auth)
+        .send::<serde_json::Value>()
+        .await
+        .map(|_| ())
+}
+
+async fn delete_key(auth: &AccessToken, key: &DkimKey) -> http::Result<()> {
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(vec![UpdateSettings::Clear {
+            prefix: format!("signature.{}.", key.id),
+            filter: None,
+        }])?
+        .send::<Option<String>>()
+        .await
+        .map(|_| ())
+}
+
+pub(super) async fn create_domain_keys(auth: &AccessToken, domain: &str) -> http::Result<()> {
+    for algorithm in [Algorithm::Ed25519, Algorithm::Rsa] {
+        create_key(
+            auth,
+            &DkimSignature {
+                algorithm,
+                domain: domain.to_string(),
+                ..Default::default()
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub(super) async fn delete_domain_keys(auth: &AccessToken, domain: &str) -> http::Result<()> {
+    for key in fetch_keys(auth, domain).await? {
+        if key.domain == domain {
+            delete_key(auth, &key).await?;
+        }
+    }
+    Ok(())
+}
+
+// Selectors follow the server's own YYYYMM + algorithm convention,
+// with the day added so that keys can be rotated more than once a month.
+fn new_selector(algorithm: Algorithm) -> String {
+    format!(
+        "{}{}",
+        Utc::now().format("%Y%m%d"),
+        match algorithm {
+            Algorithm::Rsa => 'r',
+            Algorithm::Ed25519 => 'e',
+        }
+    )
+}
+
+impl DkimKey {
+    fn algorithm(&self) -> Algorithm {
+        if self.algorithm.starts_with("rsa") {
+            Algorithm::Rsa
+        } else {
+            Algorithm::Ed25519
+        }
+    }
+
+    fn algorithm_label(&self) -> &'static str {
+        match self.algorithm.as_str() {
+            "ed25519-sha256" => "Ed25519",
+            "rsa-sha256" => "RSA",
+            "rsa-sha1" => "RSA (SHA-1)",
+            _ => "Unknown",
+        }
+    }
+
+    // Key sizes are not stored in the settings, they are derived from the
+    // length of the public key published in the DNS record.
+    fn key_size(&self) -> Option<usize> {
+        match self.algorithm() {
+            Algorithm::Ed25519 => Some(256),
+            Algorithm::Rsa => {
+                let record = self.record.as_ref()?;
+                let public_key = record
+                    .content
+                    .split(';')
+                    .find_map(|part| part.trim().strip_prefix("p="))?;
+                let len = STANDARD.decode(public_key.trim()).ok()?.len();
+                let bits = len.checked_sub(38)? * 8;
+                Some(((bits + 512) / 1024).max(1) * 1024)
+            }
+        }
+    }
+
+    fn created(&self) -> Option<String> {
+        let digits = self
+            .selector
+            .chars()
+            .take_while(|ch| ch.is_ascii_digit())
+            .collect::<String>();
+        match digits.len() {
+            8 => NaiveDate::parse_from_str(&digits, "%Y%m%d")
+                .ok()
+                .map(|date| date.format("%Y-%m-%d").to_string()),
+            6 => NaiveDate::parse_from_str(&format!("{digits}01"), "%Y%m%d")
+                .ok()
+                .map(|date| date.format("%Y-%m").to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_dkim(self) -> Self {
+        self.new_schema("dkim-generate")
+            .new_field("domain")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsDomain],
+            )
+            .placeholder("example.org")
+            .build()
+            .new_field("algorithm")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[("ed25519", "Ed25519"), ("rsa", "RSA")]),
+            })
+            .default("ed25519")
+            .build()
+            .new_field("selector")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::IsId])
+            .build()
+            .build()
+    }
+}
@@ -10,9 +10,10 @@ use std::ops::Add;
 
 use crate::{
     components::{
+        badge::Badge,
         card::{Card, CardItem},
         form::button::Button,
-        icon::{IconEnvelope, IconShieldCheck, IconUserGroup},
+        icon::{IconEnvelope, IconPrinter, IconShieldCheck, IconUserGroup},
         list::table::{Table, TableRow},
         messages::alert::{use_alerts, Alert, Alerts},
         report::ReportView,
@@ -26,14 +27,31 @@ use crate::{
     pages::{directory::Principal, List},
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct DnsRecord {
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DnsRecord {
     #[serde(rename = "type")]
-    typ: String,
-    name: String,
-    content: String,
+    pub typ: String,
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub ttl: Option<u64>,
+    #[serde(default)]
+    pub status: DnsRecordStatus,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DnsRecordStatus {
+    Verified,
+    Missing,
+    Mismatch,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+const DEFAULT_TTL: u64 = 3600;
+
 fn format_zonefile(records: &[DnsRecord], domain: &str) -> String {
     let formatted_records: Vec<[&str; 3]> = records
         .iter()
@@ -142,83 +160,102 @@ pub fn DnsDisplay() -> impl IntoView {
                         ),
                     );
                     log::debug!("zomefile: {}", zonefile);
+                    let sheet_records = records.clone();
                     Some(
                         view! {
-                            <Card>
-                                <CardItem
-                                    title="Domain"
-                                    contents=Signal::derive(move || {
-                                        params.get().get("id").cloned().unwrap_or_default()
-                                    })
-                                >
-
-                                    <IconEnvelope attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
-                                </CardItem>
-                                <CardItem title="Accounts" contents=user_count.to_string()>
-                                    <IconUserGroup attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
-                                </CardItem>
-                                <CardItem title="DKIM Signatures" contents=signature_count>
-                                    <IconShieldCheck attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
-                                </CardItem>
-                            </Card>
-
-                            <ReportView>
-
-                                <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
-                                    <div class="sm:col-span-12 pb-4">
-                                        <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
-                                            DNS Records
-                                        </h2>
+                            <DnsSetupSheet
+                                domain=params.get().get("id").cloned().unwrap_or_default()
+                                records=sheet_records
+                            />
+                            <div class="print:hidden">
+                                <Card>
+                                    <CardItem
+                                        title="Domain"
+                                        contents=Signal::derive(move || {
+                                            params.get().get("id").cloned().unwrap_or_default()
+                                        })
+                                    >
+
+                                        <IconEnvelope attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+                                    </CardItem>
+                                    <CardItem title="Accounts" contents=user_count.to_string()>
+                                        <IconUserGroup attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+                                    </CardItem>
+                                    <CardItem title="DKIM Signatures" contents=signature_count>
+                                        <IconShieldCheck attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+                                    </CardItem>
+                                </Card>
+
+                                <ReportView>
+
+                                    <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+                                        <div class="sm:col-span-12 pb-4">
+                                            <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                                                DNS Records
+                                            </h2>
+                                        </div>
+                                        <Table headers=vec![
+                                            "Type".to_string(),
+                                            "Name".to_string(),
+                                            "Contents".to_string(),
+                                        ]>
+                                            {records
+                                                .into_iter()
+                                                .map(|record| {
+                                                    view! {
+                                                        <TableRow>
+                                                            <span>{record.typ}</span>
+                                                            <span>{record.name}</span>
+                                                            <span>{record.content}</span>
+
+                                                        </TableRow>
+                                                    }
+                                                })
+                                                .collect_view()}
+
+                                        </Table>
+                                        <div class="sm:col-span-12 pb-4 pt-10">
+                                            <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                                                Zonefile
+                                            </h2>
+                                        </div>
+                                        <textarea
+                                            class="py-3 px-4 block w-full border-gray-200 rounded-lg text-sm font-mono text-nowrap focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                            readonly=true
+                                            rows=20
+                                            prop:value=zonefile
+                                        ></textarea>
+
                                     </div>
-                                    <Table headers=vec![
-                                        "Type".to_string(),
-                                        "Name".to_string(),
-                                        "Contents".to_string(),
-                                    ]>
-                                        {records
-                                            .into_iter()
-                                            .map(|record| {
-                                                view! {
-                                                    <TableRow>
-                                                        <span>{record.typ}</span>
-                                                        <span>{record.name}</span>
-                                                        <span>{record.content}</span>
-
-                                                    </TableRow>
+
+                                    <div class="flex justify-end gap-x-2">
+
+                                        <Button
+                                            text="Print setup sheet"
+                                            color=Color::Gray
+                                            on_click=move |_| {
+                                                if let Err(err) = window().print() {
+                                                    log::warn!("Failed to print setup sheet: {err:?}");
                                                 }
-                                            })
-                                            .collect_view()}
-
-                                    </Table>
-                                    <div class="sm:col-span-12 pb-4 pt-10">
-                                        <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
-                                            Zonefile
-                                        </h2>
+                                            }
+                                        >
+
+                                            <IconPrinter/>
+                                        </Button>
+                                        <Button
+                                            text="Close"
+                                            color=Color::Blue
+                                            on_click=move |_| {
+                                                use_navigate()(
+                                                    "/manage/directory/domains",
+                                                    Default::default(),
+                                                );
+                                            }
+                                        />
+
                                     </div>
-                                    <textarea
-                                        class="py-3 px-4 block w-full border-gray-200 rounded-lg text-sm font-mono text-nowrap focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                                        readonly=true
-                                        rows=20
-                                        prop:value=zonefile
-                                    ></textarea>
-
-                                </div>
-
-                                <div class="flex justify-end">
-
-                                    <Button
-                                        text="Close"
-                                        color=Color::Blue
-                                        on_click=move |_| {
-                                            use_navigate()(
-                                                "/manage/directory/domains",
-                                                Default::default(),
-                                            );
-                                        }
-                                    />
-
-                                </div>
-                            </ReportView>
+                                </ReportView>
+                            </div>
                         }
                             .into_view(),
                     )
@@ -228,3 +265,73 @@ pub fn DnsDisplay() -> impl IntoView {
         </Transition>
     }
 }
+
+#[component]
+fn DnsSetupSheet(domain: String, records: Vec<DnsRecord>) -> impl IntoView {
+    let generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+
+    view! {
+        <div class="hidden print:block text-black bg-white">
+            <h1 class="text-2xl font-bold">DNS setup for {domain.clone()}</h1>
+            <p class="mt-1 text-sm">
+                "Please publish the following records in the DNS zone of "
+                <span class="font-semibold">{domain}</span>
+                ". Records marked as missing or mismatched have not yet been detected in DNS."
+            </p>
+            <p class="mt-1 text-xs text-gray-600">Generated on {generated_at}</p>
+            <table class="mt-6 w-full table-fixed border-collapse text-xs">
+                <thead>
+                    <tr class="border-b-2 border-black text-left">
+                        <th class="w-16 py-1 pe-2">Type</th>
+                        <th class="w-1/4 py-1 pe-2">Name</th>
+                        <th class="py-1 pe-2">Value</th>
+                        <th class="w-14 py-1 pe-2">TTL</th>
+                        <th class="w-20 py-1">Status</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {records
+                        .into_iter()
+                        .map(|record| {
+                            view! {
+                                <tr class="border-b border-gray-300 align-top break-inside-avoid">
+                                    <td class="py-1 pe-2 font-semibold">{record.typ}</td>
+                                    <td class="py-1 pe-2 font-mono break-all">{record.name}</td>
+                                    <td class="py-1 pe-2 font-mono break-all">{record.content}</td>
+                                    <td class="py-1 pe-2">
+                                        {record.ttl.unwrap_or(DEFAULT_TTL).to_string()}
+                                    </td>
+                                    <td class="py-1">
+                                        <Badge color=record.status.color()>
+                                            {record.status.label()}
+                                        </Badge>
+                                    </td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+impl DnsRecordStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DnsRecordStatus::Verified => "Verified",
+            DnsRecordStatus::Missing => "Missing",
+            DnsRecordStatus::Mismatch => "Mismatch",
+            DnsRecordStatus::Unknown => "Not checked",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            DnsRecordStatus::Verified => Color::Green,
+            DnsRecordStatus::Missing => Color::Red,
+            DnsRecordStatus::Mismatch => Color::Yellow,
+            DnsRecordStatus::Unknown => Color::Gray,
+        }
+    }
+}
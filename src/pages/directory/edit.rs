@@ -4,68 +4,77 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{sync::Arc, vec};
+use std::{sync::Arc, time::Duration, vec};
 
-use ahash::{AHashMap, AHashSet};
+use ahash::AHashMap;
 use base64::{engine::general_purpose, Engine};
-use humansize::{format_size, DECIMAL};
+use chrono::{DateTime, Utc};
 use leptos::*;
 use leptos_router::{use_navigate, use_params_map};
 use pwhash::sha512_crypt;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
-use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        badge::Badge,
         form::{
             button::Button,
-            input::{InputPassword, InputSize, InputText},
-            select::Select,
+            draft::{use_form_draft, DraftRestoreBanner},
+            input::{InputDuration, InputPassword, InputSize, InputText},
+            permissions::PermissionTree,
+            principal::SelectPrincipal,
+            select::{CheckboxGroup, Select},
             stacked_badge::StackedBadge,
             stacked_input::StackedInput,
             tab::Tab,
             Form, FormButtonBar, FormElement, FormItem, FormSection, ValidateCb,
         },
-        messages::alert::{use_alerts, Alert},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
         skeleton::Skeleton,
         Color,
     },
     core::{
+        command::remember_recent_object,
+        expr::ParseValue,
         form::FormData,
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
-        schema::{Builder, Schemas, Transformer, Type, Validator},
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
         Permission,
     },
     pages::{
-        directory::{Principal, PrincipalType, PrincipalValue, PERMISSIONS},
-        List,
+        directory::{Principal, PrincipalField, PrincipalType, PrincipalUpdate, PrincipalValue},
+        FormatDateTime, List,
     },
 };
 
-use super::{build_app_password, parse_app_password, SpecialSecrets};
+use super::{
+    build_app_password,
+    dkim::{Algorithm, DkimSignature},
+    parse_app_password, SpecialSecrets,
+};
 
 type PrincipalMap = AHashMap<PrincipalType, Vec<(String, String)>>;
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-enum Algorithm {
-    #[default]
-    Rsa,
-    Ed25519,
-}
-
-#[derive(Debug, Serialize, Deserialize, Default)]
-struct DkimSignature {
-    id: Option<String>,
-    algorithm: Algorithm,
-    domain: String,
-    selector: Option<String>,
-}
+static OAUTH_GRANT_TYPES: &[(&str, &str)] = &[
+    ("authorization_code", "Authorization code"),
+    ("refresh_token", "Refresh token"),
+    ("client_credentials", "Client credentials"),
+    (
+        "urn:ietf:params:oauth:grant-type:device_code",
+        "Device authorization",
+    ),
+];
 
 #[component]
 pub fn PrincipalEdit() -> impl IntoView {
     let auth = use_authorization();
     let alert = use_alerts();
+    let modal = use_modals();
     let params = use_params_map();
     let selected_type = create_memo(move |_| {
         match params
@@ -100,49 +109,26 @@ pub fn PrincipalEdit() -> impl IntoView {
             let selected_type = selected_type.get();
 
             let needed_types = match selected_type {
-                PrincipalType::Individual => &[
-                    PrincipalType::Role,
-                    PrincipalType::Group,
-                    PrincipalType::Tenant,
-                    PrincipalType::List,
-                ][..],
-                PrincipalType::Group => &[
-                    PrincipalType::Individual,
-                    PrincipalType::Group,
-                    PrincipalType::Tenant,
-                    PrincipalType::List,
-                ][..],
-                PrincipalType::Domain => &[PrincipalType::Tenant][..],
+                PrincipalType::Individual => &[PrincipalType::Role, PrincipalType::Tenant][..],
+                PrincipalType::Group | PrincipalType::Domain | PrincipalType::List => {
+                    &[PrincipalType::Tenant][..]
+                }
                 PrincipalType::Tenant | PrincipalType::ApiKey => &[PrincipalType::Role][..],
                 PrincipalType::Role => &[PrincipalType::Role, PrincipalType::Tenant][..],
-                PrincipalType::List => &[
-                    PrincipalType::Individual,
-                    PrincipalType::Group,
-                    PrincipalType::Tenant,
-                ][..],
                 PrincipalType::Resource
                 | PrincipalType::Location
                 | PrincipalType::Other
                 | PrincipalType::OauthClient => &[][..],
             };
-            let mut fetch_types = String::new();
-            for typ in needed_types {
-                let permission = match typ {
-                    PrincipalType::Individual => Permission::IndividualList,
-                    PrincipalType::Group => Permission::GroupList,
-                    PrincipalType::List => Permission::MailingListList,
-                    PrincipalType::Domain => Permission::DomainList,
-                    PrincipalType::Tenant if is_enterprise => Permission::TenantList,
-                    PrincipalType::Role => Permission::RoleList,
-                    _ => continue,
-                };
-                if permissions.has_access(permission) {
-                    if !fetch_types.is_empty() {
-                        fetch_types.push(',');
-                    }
-                    fetch_types.push_str(typ.id());
-                }
-            }
+            let fetch_types = needed_types
+                .iter()
+                .filter(|typ| {
+                    list_permission(**typ, is_enterprise)
+                        .is_some_and(|permission| permissions.has_access(permission))
+                })
+                .map(|typ| typ.id())
+                .collect::<Vec<_>>()
+                .join(",");
 
             async move {
                 // Fetch principal
@@ -179,6 +165,12 @@ pub fn PrincipalEdit() -> impl IntoView {
                             principal.enabled_permissions =
                                 PrincipalValue::StringList(vec!["authenticate".to_string()]);
                         }
+                        PrincipalType::OauthClient => {
+                            principal.grant_types = PrincipalValue::StringList(vec![
+                                "authorization_code".to_string(),
+                                "refresh_token".to_string(),
+                            ]);
+                        }
                         _ => {}
                     }
 
@@ -231,9 +223,28 @@ pub fn PrincipalEdit() -> impl IntoView {
     );
     let (pending, set_pending) = create_signal(false);
     let current_principal = create_rw_signal(Principal::default());
+    let principal_name = Signal::derive(move || {
+        current_principal.with(|principal| principal.name().map(|name| name.to_string()))
+    });
+    let principal_tenant = Signal::derive(move || {
+        current_principal.with(|principal| principal.tenant.as_str().map(|t| t.to_string()))
+    });
+    let searchable_types = move |types: &[PrincipalType]| {
+        let auth = auth.get_untracked();
+        let permissions = auth.permissions();
+        types
+            .iter()
+            .filter(|typ| {
+                list_permission(**typ, is_enterprise)
+                    .is_some_and(|permission| permissions.has_access(permission))
+            })
+            .map(|typ| typ.id())
+            .collect::<Vec<_>>()
+    };
     let data = expect_context::<Arc<Schemas>>()
         .build_form("principals")
         .into_signal();
+    let draft = use_form_draft(data, &["otpauth_url"]);
 
     let save_changes = create_action(move |changes: &Principal| {
         let current = current_principal.get();
@@ -243,7 +254,19 @@ pub fn PrincipalEdit() -> impl IntoView {
 
         async move {
             set_pending.set(true);
-            let result = if !current.is_blank() {
+            let is_create = current.is_blank();
+            let created_key = (is_create && selected_type == PrincipalType::ApiKey).then(|| {
+                api_key(
+                    changes.name().unwrap_or_default(),
+                    changes
+                        .secrets
+                        .as_string_list()
+                        .first()
+                        .map(|s| s.as_str())
+                        .unwrap_or_default(),
+                )
+            });
+            let result = if !is_create {
                 let name = current.name().unwrap_or_default().to_string();
                 let updates = current.into_updates(changes);
 
@@ -291,14 +314,78 @@ pub fn PrincipalEdit() -> impl IntoView {
                 result
             };
             set_pending.set(false);
+            if result.is_ok() {
+                draft.clear();
+            }
 
-            match result {
-                Ok(_) => {
+            match (result, created_key) {
+                (Ok(_), Some(key)) => {
+                    modal.set(
+                        Modal::with_title("API key created")
+                            .with_message("Copy the key now, it will not be displayed again.")
+                            .with_details([("Key", key)])
+                            .with_button("Done")
+                            .with_callback(move || {
+                                use_navigate()(
+                                    &format!("/manage/directory/{}", selected_type.resource_name()),
+                                    Default::default(),
+                                );
+                            }),
+                    );
+                }
+                (Ok(_), None) => {
                     use_navigate()(
                         &format!("/manage/directory/{}", selected_type.resource_name()),
                         Default::default(),
                     );
                 }
+                (Err(err), _) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let regenerate_secret = create_action(move |name: &String| {
+        let name = name.clone();
+        let auth = auth.get();
+        let secret = thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect::<String>();
+
+        async move {
+            match HttpRequest::patch(("/api/principal", &name))
+                .with_authorization(&auth)
+                .with_body(vec![PrincipalUpdate::set(
+                    PrincipalField::Secrets,
+                    PrincipalValue::StringList(vec![secret.clone()]),
+                )])
+                .unwrap()
+                .send::<serde_json::Value>()
+                .await
+            {
+                Ok(_) => {
+                    // Keep the form in sync so saving afterwards does not restore the old secret
+                    current_principal.update(|principal| {
+                        principal.secrets = PrincipalValue::StringList(vec![secret.clone()]);
+                    });
+                    data.update(|data| {
+                        data.set("api_secret", secret.clone());
+                    });
+                    modal.set(
+                        Modal::with_title("Client secret")
+                            .with_message(
+                                "Copy the new secret now, it will not be displayed again.",
+                            )
+                            .with_details([("Client Id", name), ("Client secret", secret)])
+                            .with_button("Done"),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
                 Err(err) => {
                     alert.set(Alert::from(err));
                 }
@@ -353,6 +440,7 @@ pub fn PrincipalEdit() -> impl IntoView {
 
     view! {
         <Form title=title subtitle="".to_string()>
+            <DraftRestoreBanner draft/>
 
             <Transition fallback=Skeleton set_pending>
 
@@ -375,9 +463,24 @@ pub fn PrincipalEdit() -> impl IntoView {
                         Some(view! { <div></div> }.into_view())
                     }
                     Some(Ok(principal)) => {
-                        data.update(|data| {
-                            data.from_principal(&principal, selected_type.get());
-                        });
+                        if let Some(name) = params.get_untracked().get("id").filter(|id| !id.is_empty()) {
+                            remember_recent_object(
+                                &auth.get_untracked(),
+                                format!("{name} ({})", selected_type.get().item_name(false)),
+                                format!(
+                                    "/manage/directory/{}/{name}/edit",
+                                    selected_type.get().resource_name(),
+                                ),
+                            );
+                        }
+                        draft
+                            .load(
+                                selected_type.get().resource_name(),
+                                principal.name().unwrap_or_default(),
+                                |data| {
+                                    data.from_principal(&principal, selected_type.get());
+                                },
+                            );
                         let used_quota = principal.used_quota.as_int().unwrap_or_default();
                         let total_quota = principal.quota.as_int().unwrap_or_default();
                         current_principal.set(principal);
@@ -574,6 +677,47 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             />
                                         </FormItem>
 
+                                        <FormItem
+                                            stacked=true
+                                            label="Grant types"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::OauthClient)
+                                            })
+                                        >
+
+                                            <CheckboxGroup element=FormElement::new("grant-types", data)/>
+                                        </FormItem>
+
+                                        <FormItem
+                                            stacked=true
+                                            label="Access token lifetime"
+                                            is_optional=true
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::OauthClient)
+                                            })
+                                        >
+
+                                            <InputDuration element=FormElement::new(
+                                                "access-token-expiry",
+                                                data,
+                                            )/>
+                                        </FormItem>
+
+                                        <FormItem
+                                            stacked=true
+                                            label="Refresh token lifetime"
+                                            is_optional=true
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::OauthClient)
+                                            })
+                                        >
+
+                                            <InputDuration element=FormElement::new(
+                                                "refresh-token-expiry",
+                                                data,
+                                            )/>
+                                        </FormItem>
+
                                     </FormSection>
 
                                     <FormSection stacked=true>
@@ -628,27 +772,110 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             })
                                         >
 
-                                            <span class="block font-semibold font-mono text-gray-800 dark:text-gray-200">
+                                            <span class="block text-sm text-gray-600 dark:text-gray-400">
                                                 {move || {
-                                                    let data = data.get();
-                                                    let name = data.value::<String>("name").unwrap_or_default();
-                                                    let secret = data
-                                                        .value::<String>("api_secret")
-                                                        .unwrap_or_default();
-                                                    (!name.is_empty() && !secret.is_empty())
-                                                        .then(|| {
-                                                            format!(
-                                                                "api_{}",
-                                                                general_purpose::STANDARD
-                                                                    .encode(format!("{}:{}", name, secret).as_bytes()),
-                                                            )
-                                                        })
+                                                    if principal_name.get().is_none() {
+                                                        "The key is displayed once, right after it is created."
+                                                    } else {
+                                                        "The key was only displayed when it was created."
+                                                    }
                                                 }}
 
                                             </span>
 
                                         </FormItem>
 
+                                        <FormItem
+                                            stacked=true
+                                            label="Expiration"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::ApiKey)
+                                            })
+                                        >
+
+                                            {move || {
+                                                if principal_name.get().is_none() {
+                                                    view! {
+                                                        <Select element=FormElement::new("expiration", data)/>
+                                                    }
+                                                        .into_view()
+                                                } else {
+                                                    let expires_at = data
+                                                        .get_untracked()
+                                                        .value::<String>("expires-at")
+                                                        .and_then(|value| {
+                                                            DateTime::parse_from_rfc3339(&value).ok()
+                                                        })
+                                                        .map(|value| value.with_timezone(&Utc));
+                                                    match expires_at {
+                                                        Some(expires_at) => {
+                                                            view! {
+                                                                <div class="flex items-center gap-x-3">
+                                                                    <span class="text-sm text-gray-800 dark:text-gray-200">
+                                                                        {expires_at.format_date_time()}
+                                                                    </span>
+                                                                    <Show when=move || expires_at < Utc::now()>
+                                                                        <Badge color=Color::Red>Expired</Badge>
+                                                                    </Show>
+                                                                </div>
+                                                            }
+                                                                .into_view()
+                                                        }
+                                                        None => {
+                                                            view! {
+                                                                <span class="text-sm text-gray-800 dark:text-gray-200">
+                                                                    Never
+                                                                </span>
+                                                            }
+                                                                .into_view()
+                                                        }
+                                                    }
+                                                }
+                                            }}
+
+                                        </FormItem>
+
+                                        <FormItem
+                                            stacked=true
+                                            label="Client secret"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::OauthClient)
+                                                    || principal_name.get().is_none()
+                                            })
+                                        >
+
+                                            <div class="flex flex-wrap items-center gap-x-3 gap-y-2">
+                                                <Button
+                                                    text="Regenerate secret"
+                                                    color=Color::Gray
+                                                    on_click=move |_| {
+                                                        let name = principal_name.get_untracked().unwrap_or_default();
+                                                        modal
+                                                            .set(
+                                                                Modal::with_title("Regenerate secret")
+                                                                    .with_message(
+                                                                        format!(
+                                                                            "Applications using the current secret of '{name}' will no longer be able to obtain tokens. Do you want to continue?",
+                                                                        ),
+                                                                    )
+                                                                    .with_button("Regenerate")
+                                                                    .with_dangerous_callback(move || {
+                                                                        regenerate_secret.dispatch(name.clone());
+                                                                    }),
+                                                            );
+                                                    }
+
+                                                    disabled=Signal::derive(move || {
+                                                        regenerate_secret.pending().get()
+                                                    })
+                                                />
+
+                                                <span class="text-xs text-gray-500 dark:text-gray-400">
+                                                    The secret is only displayed once, right after it is generated.
+                                                </span>
+                                            </div>
+                                        </FormItem>
+
                                     </FormSection>
 
                                     <FormSection stacked=true>
@@ -672,11 +899,11 @@ pub fn PrincipalEdit() -> impl IntoView {
                                                             {if total_quota > 0 {
                                                                 format!(
                                                                     "{} used ({:.1}%)",
-                                                                    format_size(used_quota, DECIMAL),
+                                                                    format_bytes(used_quota),
                                                                     (used_quota as f64 / total_quota as f64) * 100.0,
                                                                 )
                                                             } else {
-                                                                format!("{} used", format_size(used_quota, DECIMAL))
+                                                                format!("{} used", format_bytes(used_quota))
                                                             }}
 
                                                         </label>
@@ -762,13 +989,11 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             })
                                         >
 
-                                            <StackedBadge
+                                            <SelectPrincipal
                                                 color=Color::Green
                                                 element=FormElement::new("members", data)
                                                 add_button_text="Add member".to_string()
-                                                options=create_memo(move |_| {
-                                                    let principals = principals.get();
-                                                    let mut results = Vec::new();
+                                                types=Signal::derive(move || {
                                                     let types = match selected_type.get() {
                                                         PrincipalType::Group | PrincipalType::List => {
                                                             &[PrincipalType::Individual, PrincipalType::Group][..]
@@ -778,16 +1003,10 @@ pub fn PrincipalEdit() -> impl IntoView {
                                                         }
                                                         _ => &[][..],
                                                     };
-                                                    for typ in types {
-                                                        if let Some(principals) = principals.get(typ) {
-                                                            for (id, name) in principals {
-                                                                results
-                                                                    .push((id.clone(), format!("{} - {name}", typ.name())));
-                                                            }
-                                                        }
-                                                    }
-                                                    results
+                                                    searchable_types(types)
                                                 })
+                                                tenant=principal_tenant
+                                                exclude=principal_name
                                             />
 
                                         </FormItem>
@@ -802,17 +1021,15 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             })
                                         >
 
-                                            <StackedBadge
+                                            <SelectPrincipal
                                                 color=Color::Blue
                                                 element=FormElement::new("member-of", data)
                                                 add_button_text="Add to group".to_string()
-                                                options=create_memo(move |_| {
-                                                    principals
-                                                        .get()
-                                                        .get(&PrincipalType::Group)
-                                                        .cloned()
-                                                        .unwrap_or_default()
+                                                types=Signal::derive(move || {
+                                                    searchable_types(&[PrincipalType::Group])
                                                 })
+                                                tenant=principal_tenant
+                                                exclude=principal_name
                                             />
 
                                         </FormItem>
@@ -827,17 +1044,15 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             })
                                         >
 
-                                            <StackedBadge
+                                            <SelectPrincipal
                                                 color=Color::Blue
                                                 element=FormElement::new("lists", data)
                                                 add_button_text="Add to list".to_string()
-                                                options=create_memo(move |_| {
-                                                    principals
-                                                        .get()
-                                                        .get(&PrincipalType::List)
-                                                        .cloned()
-                                                        .unwrap_or_default()
+                                                types=Signal::derive(move || {
+                                                    searchable_types(&[PrincipalType::List])
                                                 })
+                                                tenant=principal_tenant
+                                                exclude=principal_name
                                             />
 
                                         </FormItem>
@@ -865,6 +1080,59 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             />
 
                                         </FormItem>
+
+                                        <FormItem
+                                            label="Posting policy"
+                                            tooltip="Who is allowed to send messages to this list"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::List)
+                                            })
+                                        >
+                                            <Select element=FormElement::new("posting-policy", data)/>
+                                        </FormItem>
+
+                                        <FormItem
+                                            label="Moderators"
+                                            tooltip="Accounts allowed to post when the list is moderated"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::List)
+                                            })
+                                        >
+
+                                            <SelectPrincipal
+                                                color=Color::Yellow
+                                                element=FormElement::new("moderators", data)
+                                                add_button_text="Add moderator".to_string()
+                                                types=Signal::derive(move || {
+                                                    searchable_types(&[PrincipalType::Individual])
+                                                })
+                                                tenant=principal_tenant
+                                            />
+
+                                        </FormItem>
+
+                                        <FormItem
+                                            label="Reply-To"
+                                            tooltip="Whether replies are directed to the list or to the original sender"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::List)
+                                            })
+                                        >
+                                            <Select element=FormElement::new("reply-to", data)/>
+                                        </FormItem>
+
+                                        <FormItem
+                                            label="Subject prefix"
+                                            tooltip="Text prepended to the subject of messages distributed by the list"
+                                            hide=Signal::derive(move || {
+                                                !matches!(selected_type.get(), PrincipalType::List)
+                                            })
+                                        >
+                                            <InputText
+                                                element=FormElement::new("subject-prefix", data)
+                                                placeholder="[list]"
+                                            />
+                                        </FormItem>
                                     </FormSection>
 
                                     <FormSection>
@@ -910,106 +1178,16 @@ pub fn PrincipalEdit() -> impl IntoView {
                                             })
                                         >
 
-                                            <div class="grid space-y-2">
-                                                {move || {
-                                                    let form_data = data.get();
-                                                    let enabled_permissions = form_data
-                                                        .array_value("enabled-permissions")
-                                                        .collect::<AHashSet<_>>();
-                                                    let disabled_permissions = form_data
-                                                        .array_value("disabled-permissions")
-                                                        .collect::<AHashSet<_>>();
-                                                    PERMISSIONS
-                                                        .iter()
-                                                        .map(|(id, name)| {
-                                                            view! {
-                                                                <div class="flex flex-col space-y-3 p-3 w-full bg-white border border-gray-200 rounded-lg focus:border-blue-500 focus:ring-blue-500 dark:bg-neutral-900 dark:border-neutral-700">
-                                                                    <div class="flex justify-between items-center">
-                                                                        <span
-                                                                            id="hs-radio-delete-description"
-                                                                            class="block text-sm text-gray-600 dark:text-neutral-500"
-                                                                        >
-                                                                            {name.to_string()}
-                                                                        </span>
-
-                                                                        <div class="flex gap-x-6">
-                                                                            <div class="flex">
-                                                                                <input
-                                                                                    type="radio"
-                                                                                    name=id.to_string()
-                                                                                    class="shrink-0 mt-0.5 border-gray-200 rounded-full text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-neutral-800 dark:border-neutral-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
-                                                                                    id=format!("{id}-on")
-                                                                                    prop:checked=enabled_permissions.contains(id)
-                                                                                    on:input=move |_| {
-                                                                                        data.update_untracked(|data| {
-                                                                                            data.array_push(
-                                                                                                "enabled-permissions",
-                                                                                                id.to_string(),
-                                                                                                true,
-                                                                                            );
-                                                                                            data.array_delete_item("disabled-permissions", id);
-                                                                                        });
-                                                                                    }
-                                                                                />
-
-                                                                                <label class="text-sm text-gray-500 ms-2 dark:text-neutral-400">
-                                                                                    On
-                                                                                </label>
-                                                                            </div>
-
-                                                                            <div class="flex">
-                                                                                <input
-                                                                                    type="radio"
-                                                                                    name=id.to_string()
-                                                                                    class="shrink-0 mt-0.5 border-gray-200 rounded-full text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-neutral-800 dark:border-neutral-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
-                                                                                    id=format!("{id}-off")
-                                                                                    prop:checked=disabled_permissions.contains(id)
-                                                                                    on:input=move |_| {
-                                                                                        data.update_untracked(|data| {
-                                                                                            data.array_push(
-                                                                                                "disabled-permissions",
-                                                                                                id.to_string(),
-                                                                                                true,
-                                                                                            );
-                                                                                            data.array_delete_item("enabled-permissions", id);
-                                                                                        });
-                                                                                    }
-                                                                                />
-
-                                                                                <label class="text-sm text-gray-500 ms-2 dark:text-neutral-400">
-                                                                                    Off
-                                                                                </label>
-                                                                            </div>
-
-                                                                            <div class="flex">
-                                                                                <input
-                                                                                    type="radio"
-                                                                                    name=id.to_string()
-                                                                                    class="shrink-0 mt-0.5 border-gray-200 rounded-full text-blue-600 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-neutral-800 dark:border-neutral-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
-                                                                                    id=format!("{id}-inherit")
-                                                                                    prop:checked=!enabled_permissions.contains(id)
-                                                                                        && !disabled_permissions.contains(id)
-                                                                                    on:input=move |_| {
-                                                                                        data.update_untracked(|data| {
-                                                                                            data.array_delete_item("disabled-permissions", id);
-                                                                                            data.array_delete_item("enabled-permissions", id);
-                                                                                        });
-                                                                                    }
-                                                                                />
-
-                                                                                <label class="text-sm text-gray-500 ms-2 dark:text-neutral-400">
-                                                                                    Default
-                                                                                </label>
-                                                                            </div>
-                                                                        </div>
-                                                                    </div>
-                                                                </div>
-                                                            }
-                                                        })
-                                                        .collect_view()
-                                                }}
+                                            {move || {
+                                                view! {
+                                                    <PermissionTree
+                                                        enabled=FormElement::new("enabled-permissions", data)
+                                                        disabled=FormElement::new("disabled-permissions", data)
+                                                        restrict=selected_type.get() == PrincipalType::ApiKey
+                                                    />
+                                                }
+                                            }}
 
-                                            </div>
                                         </FormItem>
 
                                     </FormSection>
@@ -1063,6 +1241,18 @@ impl FormData {
             ("picture", principal.picture.as_str()),
             ("tenant", principal.tenant.as_str()),
             ("email", principal.emails.as_str()),
+            ("posting-policy", principal.posting_policy.as_str()),
+            ("reply-to", principal.reply_to.as_str()),
+            ("subject-prefix", principal.subject_prefix.as_str()),
+            (
+                "access-token-expiry",
+                principal.access_token_expiry.as_str(),
+            ),
+            (
+                "refresh-token-expiry",
+                principal.refresh_token_expiry.as_str(),
+            ),
+            ("expires-at", principal.expires_at.as_str()),
         ] {
             if let Some(value) = field {
                 self.set(key, value.to_string());
@@ -1123,6 +1313,8 @@ impl FormData {
                 "external-members",
                 principal.external_members.as_string_list(),
             ),
+            ("moderators", principal.moderators.as_string_list()),
+            ("grant-types", principal.grant_types.as_string_list()),
         ] {
             self.array_set(key, list.iter());
         }
@@ -1133,7 +1325,10 @@ impl FormData {
                 app_passwords.push(app);
             } else if secret.is_otp_auth() {
                 self.set("otpauth_url", secret);
-            } else if default_type == PrincipalType::ApiKey {
+            } else if matches!(
+                default_type,
+                PrincipalType::ApiKey | PrincipalType::OauthClient
+            ) {
                 self.set("api_secret", secret);
             }
         }
@@ -1186,12 +1381,45 @@ impl FormData {
                 ("disabled-permissions", &mut principal.disabled_permissions),
                 ("urls", &mut principal.urls),
                 ("external-members", &mut principal.external_members),
+                ("moderators", &mut principal.moderators),
             ] {
                 *list = PrincipalValue::StringList(
                     self.array_value(key).map(|m| m.to_string()).collect(),
                 );
             }
 
+            if typ == PrincipalType::List {
+                for (key, value) in [
+                    ("posting-policy", &mut principal.posting_policy),
+                    ("reply-to", &mut principal.reply_to),
+                    ("subject-prefix", &mut principal.subject_prefix),
+                ] {
+                    *value = PrincipalValue::String(self.value(key).unwrap_or_default());
+                }
+            } else if typ == PrincipalType::ApiKey {
+                let expires_at = match self
+                    .value::<String>("expiration")
+                    .and_then(|value| Duration::parse_value(&value))
+                    .and_then(|value| chrono::Duration::from_std(value).ok())
+                {
+                    Some(expiration) => (Utc::now() + expiration).to_rfc3339(),
+                    None => self.value("expires-at").unwrap_or_default(),
+                };
+                principal.expires_at = PrincipalValue::String(expires_at);
+            } else if typ == PrincipalType::OauthClient {
+                principal.grant_types = PrincipalValue::StringList(
+                    self.array_value("grant-types")
+                        .map(|m| m.to_string())
+                        .collect(),
+                );
+                for (key, value) in [
+                    ("access-token-expiry", &mut principal.access_token_expiry),
+                    ("refresh-token-expiry", &mut principal.refresh_token_expiry),
+                ] {
+                    *value = PrincipalValue::String(self.value(key).unwrap_or_default());
+                }
+            }
+
             Some(principal)
         } else {
             None
@@ -1225,6 +1453,25 @@ impl FormData {
     }
 }
 
+fn api_key(name: &str, secret: &str) -> String {
+    format!(
+        "api_{}",
+        general_purpose::STANDARD.encode(format!("{}:{}", name, secret).as_bytes()),
+    )
+}
+
+fn list_permission(typ: PrincipalType, is_enterprise: bool) -> Option<Permission> {
+    match typ {
+        PrincipalType::Individual => Some(Permission::IndividualList),
+        PrincipalType::Group => Some(Permission::GroupList),
+        PrincipalType::List => Some(Permission::MailingListList),
+        PrincipalType::Domain => Some(Permission::DomainList),
+        PrincipalType::Tenant if is_enterprise => Some(Permission::TenantList),
+        PrincipalType::Role => Some(Permission::RoleList),
+        _ => None,
+    }
+}
+
 impl Builder<Schemas, ()> {
     pub fn build_principals(self) -> Self {
         self.new_schema("principals")
@@ -1249,6 +1496,57 @@ impl Builder<Schemas, ()> {
                 [Validator::IsEmail],
             )
             .build()
+            .new_field("members")
+            .typ(Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::Principals(&["individual", "group", "role"]),
+            })
+            .build()
+            .new_field("member-of")
+            .typ(Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::Principals(&["group"]),
+            })
+            .build()
+            .new_field("lists")
+            .typ(Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::Principals(&["list"]),
+            })
+            .build()
+            .new_field("posting-policy")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("anyone", "Anyone"),
+                    ("members", "Members only"),
+                    ("moderators", "Moderators only"),
+                ]),
+            })
+            .default("anyone")
+            .build()
+            .new_field("moderators")
+            .typ(Type::Select {
+                typ: SelectType::ManyWithSearch,
+                source: Source::Principals(&["individual"]),
+            })
+            .input_check_if_eq("posting-policy", ["moderators"], [], [Validator::Required])
+            .build()
+            .new_field("reply-to")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("", "Leave unchanged"),
+                    ("list", "Reply to the list"),
+                    ("sender", "Reply to the sender"),
+                ]),
+            })
+            .default("")
+            .build()
+            .new_field("subject-prefix")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .build()
             .new_field("external-members")
             .typ(Type::Array)
             .input_check(
@@ -1260,6 +1558,31 @@ impl Builder<Schemas, ()> {
             .typ(Type::Array)
             .input_check([Transformer::Trim], [Validator::IsUrl])
             .build()
+            .new_field("grant-types")
+            .typ(Type::Select {
+                typ: SelectType::Many,
+                source: Source::Static(OAUTH_GRANT_TYPES),
+            })
+            .build()
+            .new_field("access-token-expiry")
+            .typ(Type::Duration)
+            .build()
+            .new_field("refresh-token-expiry")
+            .typ(Type::Duration)
+            .build()
+            .new_field("expiration")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("", "Never"),
+                    ("7d", "7 days"),
+                    ("30d", "30 days"),
+                    ("90d", "90 days"),
+                    ("365d", "1 year"),
+                ]),
+            })
+            .default("")
+            .build()
             .new_field("description")
             .typ(Type::Input)
             .input_check([Transformer::Trim], [])
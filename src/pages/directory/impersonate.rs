@@ -0,0 +1,309 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::TextArea, select::Select, Form, FormButtonBar, FormElement,
+            FormItem, FormSection,
+        },
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::SearchBox, Footer, ListSection,
+            ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert, Alerts},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http,
+        impersonate::{
+            end_impersonation, fetch_impersonations, start_impersonation, ImpersonationSession,
+        },
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+        url::UrlBuilder,
+    },
+    pages::FormatDateTime,
+};
+
+const PAGE_SIZE: u32 = 20;
+
+#[component]
+pub fn ImpersonateAccount() -> impl IntoView {
+    let params = use_params_map();
+    let name = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let (pending, set_pending) = create_signal(false);
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("impersonate")
+        .into_signal();
+
+    let start_session = create_action(move |(reason, duration): &(String, u64)| {
+        let reason = reason.clone();
+        let duration = *duration;
+        let impersonator = auth.get();
+        let account = name.get();
+
+        async move {
+            set_pending.set(true);
+            let result =
+                start_impersonation(auth, &impersonator, &account, &reason, duration).await;
+            set_pending.set(false);
+
+            match result {
+                Ok(_) => {
+                    let default_url = auth.get_untracked().default_url();
+                    if !default_url.is_empty() {
+                        use_navigate()(default_url, Default::default());
+                    } else {
+                        end_impersonation(auth).await;
+                        alert.set(Alert::error(format!(
+                            "The account {account} does not have access to the self-service portal."
+                        )));
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title=Signal::derive(move || format!("Impersonate {}", name.get()))
+            subtitle="Open the self-service portal as this account to reproduce an issue"
+        >
+            <FormSection>
+                <FormItem
+                    label="Reason"
+                    tooltip="Recorded in the audit log together with the session"
+                >
+                    <TextArea
+                        placeholder="Ticket number or description of the issue"
+                        element=FormElement::new("reason", data)
+                    />
+                </FormItem>
+                <FormItem label="Duration">
+                    <Select element=FormElement::new("duration", data)/>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/manage/directory/accounts", Default::default());
+                    }
+                />
+
+                <Button
+                    text="Start session"
+                    color=Color::Red
+                    disabled=pending
+                    on_click=move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                let reason = data.value::<String>("reason").unwrap_or_default();
+                                let duration = data.value::<u64>("duration").unwrap_or(1800);
+                                start_session.dispatch((reason, duration));
+                            }
+                        });
+                    }
+                />
+
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+pub fn ImpersonationLog() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("account")
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let sessions = create_resource(
+        move || (page.get(), filter.get()),
+        move |(page, filter)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                fetch_impersonations(&auth, page, PAGE_SIZE, filter)
+                    .await
+                    .map(Arc::new)
+            }
+        },
+    );
+
+    view! {
+        <ListSection>
+            <Alerts/>
+            <ListTable
+                title="Impersonation sessions"
+                subtitle="Audit log of sessions opened on behalf of other accounts"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value: String| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/impersonations")
+                                    .with_parameter("account", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match sessions.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(sessions)) if !sessions.items.is_empty() => {
+                            let sessions_ = sessions.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Account".to_string(),
+                                        "Impersonator".to_string(),
+                                        "Reason".to_string(),
+                                        "Started".to_string(),
+                                        "Ended".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || sessions_.items.clone()
+                                            key=|session| (session.id.clone(), session.ended_at)
+                                            let:session
+                                        >
+                                            <ImpersonationItem session/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No sessions found"
+                                        subtitle="No accounts have been impersonated yet."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=Signal::derive(move || {
+                            sessions
+                                .get()
+                                .and_then(|sessions| sessions.ok())
+                                .map(|sessions| sessions.total as u32)
+                        })
+
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/impersonations")
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("account", filter.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn ImpersonationItem(session: ImpersonationSession) -> impl IntoView {
+    let started = session.started_at.format_date_time();
+    let ended = match session.ended_at {
+        Some(ended) => view! { <span>{ended.format_date_time()}</span> }.into_view(),
+        None => view! { <Badge color=Color::Red>Active</Badge> }.into_view(),
+    };
+
+    view! {
+        <tr>
+            <ListTextItem>{session.account}</ListTextItem>
+            <ListTextItem>{session.impersonator}</ListTextItem>
+            <ListTextItem>{session.reason}</ListTextItem>
+            <ListTextItem>{started}</ListTextItem>
+            <ListTextItem>{ended}</ListTextItem>
+        </tr>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_impersonate(self) -> Self {
+        self.new_schema("impersonate")
+            .new_field("reason")
+            .typ(Type::Text)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("duration")
+            .default("1800")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("900", "15 minutes"),
+                    ("1800", "30 minutes"),
+                    ("3600", "1 hour"),
+                ]),
+            })
+            .build()
+            .build()
+    }
+}
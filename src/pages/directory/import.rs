@@ -0,0 +1,834 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::VecDeque;
+
+use ahash::AHashMap;
+use chrono::Local;
+use leptos::*;
+use leptos_router::use_navigate;
+use pwhash::sha512_crypt;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconArrowDownTray, IconArrowUpTray, IconCancel},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        download::{download_file, escape_csv},
+        format::format_bytes,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        AccessToken,
+    },
+    pages::{
+        directory::{Principal, PrincipalType, PrincipalValue},
+        maybe_plural,
+    },
+};
+
+// Number of accounts created in parallel
+const BATCH_SIZE: usize = 5;
+const PASSWORD_LENGTH: usize = 16;
+const UNIT_KB: u64 = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Column {
+    Name,
+    Email,
+    Quota,
+    Password,
+    Aliases,
+    Description,
+}
+
+// Column order used when the file has no header row
+static DEFAULT_COLUMNS: &[Column] = &[
+    Column::Name,
+    Column::Email,
+    Column::Quota,
+    Column::Password,
+    Column::Aliases,
+    Column::Description,
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ImportRow {
+    line: usize,
+    name: String,
+    emails: Vec<String>,
+    description: String,
+    quota: u64,
+    // None when the password is to be generated
+    password: Option<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ImportStatus {
+    Invalid,
+    Pending,
+    Running,
+    Created { generated: Option<String> },
+    Failed(String),
+}
+
+#[component]
+pub fn AccountImport() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let file_name = create_rw_signal(String::new());
+    let rows = create_rw_signal(Vec::<ImportRow>::new());
+    let statuses = create_rw_signal(Vec::<ImportStatus>::new());
+    let is_running = create_rw_signal(false);
+    let queue = store_value(VecDeque::<usize>::new());
+    let workers = store_value(0usize);
+
+    let num_valid = create_memo(move |_| {
+        rows.with(|rows| rows.iter().filter(|row| row.errors.is_empty()).count())
+    });
+    let progress = create_memo(move |_| {
+        statuses.with(|statuses| {
+            statuses
+                .iter()
+                .fold((0, 0, 0), |(done, failed, total), status| match status {
+                    ImportStatus::Invalid => (done, failed, total),
+                    ImportStatus::Pending | ImportStatus::Running => (done, failed, total + 1),
+                    ImportStatus::Created { .. } => (done + 1, failed, total + 1),
+                    ImportStatus::Failed(_) => (done + 1, failed + 1, total + 1),
+                })
+        })
+    });
+    let is_finished = create_memo(move |_| {
+        let (done, _, total) = progress.get();
+        total > 0 && done == total && !is_running.get()
+    });
+
+    let worker = move |auth: AccessToken| async move {
+        while let Some(idx) = queue.try_update_value(|queue| queue.pop_front()).flatten() {
+            let row = rows.with_untracked(|rows| rows[idx].clone());
+            let password = row.password.clone().unwrap_or_else(generate_password);
+            statuses.update(|statuses| statuses[idx] = ImportStatus::Running);
+
+            let status = match create_account(&auth, &row, &password).await {
+                Ok(_) => ImportStatus::Created {
+                    generated: row.password.is_none().then_some(password),
+                },
+                Err(http::Error::Unauthorized) => {
+                    queue.update_value(|queue| queue.clear());
+                    use_navigate()("/login", Default::default());
+                    ImportStatus::Failed("Unauthorized".to_string())
+                }
+                Err(err) => ImportStatus::Failed(Alert::from(err).message),
+            };
+            statuses.update(|statuses| statuses[idx] = status);
+        }
+
+        workers.update_value(|workers| *workers -= 1);
+        if workers.get_value() == 0 {
+            is_running.set(false);
+            let (done, failed, _) = progress.get_untracked();
+            if failed == 0 {
+                alert.set(Alert::success(format!(
+                    "Created {}.",
+                    maybe_plural(done, "account", "accounts")
+                )));
+            } else {
+                alert.set(Alert::warning(format!(
+                    "Created {}, {} could not be created.",
+                    maybe_plural(done - failed, "account", "accounts"),
+                    failed
+                )));
+            }
+        }
+    };
+
+    let start_import = move || {
+        let pending = statuses.with_untracked(|statuses| {
+            statuses
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, status)| {
+                    matches!(status, ImportStatus::Pending | ImportStatus::Failed(_)).then_some(idx)
+                })
+                .collect::<VecDeque<_>>()
+        });
+        if pending.is_empty() {
+            return;
+        }
+        statuses.update(|statuses| {
+            for idx in &pending {
+                statuses[*idx] = ImportStatus::Pending;
+            }
+        });
+        let num_workers = pending.len().min(BATCH_SIZE);
+        queue.set_value(pending);
+        workers.set_value(num_workers);
+        is_running.set(true);
+
+        let auth = auth.get_untracked();
+        for _ in 0..num_workers {
+            spawn_local(worker(auth.clone()));
+        }
+    };
+
+    let select_file = move |ev: web_sys::Event| {
+        let Some(selected) = event_target::<HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        spawn_local(async move {
+            let name = selected.name();
+            let text = JsFuture::from(selected.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+                .unwrap_or_default();
+            let parsed = parse_rows(&text);
+
+            if parsed.is_empty() {
+                alert.set(
+                    Alert::error("Nothing to import")
+                        .with_details("The file does not contain any accounts."),
+                );
+                return;
+            }
+            statuses.set(
+                parsed
+                    .iter()
+                    .map(|row| {
+                        if row.errors.is_empty() {
+                            ImportStatus::Pending
+                        } else {
+                            ImportStatus::Invalid
+                        }
+                    })
+                    .collect(),
+            );
+            rows.set(parsed);
+            file_name.set(name);
+        });
+    };
+
+    let download_report = move || {
+        let report = rows.with_untracked(|rows| {
+            statuses.with_untracked(|statuses| format_report(rows, statuses))
+        });
+        download_file(
+            &format!("import-{}.csv", Local::now().format("%Y%m%d-%H%M%S")),
+            "text/csv",
+            &report,
+        );
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Import accounts"
+                subtitle="Create accounts and aliases in bulk from a CSV or TSV file"
+            >
+                <Toolbar slot>
+                    <Show when=move || is_finished.get()>
+                        <ToolbarButton
+                            text="Download report"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| download_report())
+                        >
+
+                            <IconArrowDownTray/>
+                        </ToolbarButton>
+                    </Show>
+                    <Show when=move || !rows.with(|rows| rows.is_empty())>
+                        <ToolbarButton
+                            text="Clear"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                if !is_running.get_untracked() {
+                                    rows.set(vec![]);
+                                    statuses.set(vec![]);
+                                    file_name.set(String::new());
+                                }
+                            })
+                        >
+
+                            <IconCancel/>
+                        </ToolbarButton>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                if is_running.get() {
+                                    "Importing...".to_string()
+                                } else if progress.get().1 > 0 {
+                                    "Retry failed".to_string()
+                                } else {
+                                    "Import".to_string()
+                                }
+                            })
+
+                            color=Color::Blue
+                            on_click=Callback::new(move |_| {
+                                if is_running.get_untracked() {
+                                    return;
+                                }
+                                let (done, failed, total) = progress.get_untracked();
+                                let num_accounts = total - done + failed;
+                                if num_accounts == 0 {
+                                    return;
+                                }
+                                let num_invalid = rows
+                                    .with_untracked(|rows| rows.len())
+                                    .saturating_sub(num_valid.get_untracked());
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm import")
+                                            .with_message(
+                                                if num_invalid > 0 {
+                                                    format!(
+                                                        "{} will be created, {} with errors will be skipped. Do you want to continue?",
+                                                        maybe_plural(num_accounts, "account", "accounts"),
+                                                        maybe_plural(num_invalid, "row", "rows"),
+                                                    )
+                                                } else {
+                                                    format!(
+                                                        "{} will be created. Do you want to continue?",
+                                                        maybe_plural(num_accounts, "account", "accounts"),
+                                                    )
+                                                },
+                                            )
+                                            .with_button("Import")
+                                            .with_callback(move || {
+                                                start_import();
+                                            }),
+                                    );
+                            })
+                        >
+
+                            <IconArrowUpTray/>
+                        </ToolbarButton>
+                    </Show>
+                </Toolbar>
+
+                {move || {
+                    if rows.with(|rows| rows.is_empty()) {
+                        view! {
+                            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700 space-y-3">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    Each row describes one account: name, email, quota, password and optionally aliases and a description.
+                                    Use "generate" or leave the password empty to create a random one, quotas accept units such as 500MB or 2GB.
+                                    A header row can be used to list the columns in a different order.
+                                </p>
+                                <input
+                                    type="file"
+                                    class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-neutral-500"
+                                    accept=".csv,.tsv,.txt,text/csv,text/tab-separated-values"
+                                    on:change=select_file
+                                />
+                            </div>
+                            <ZeroResults
+                                title="No file selected"
+                                subtitle="Select a CSV or TSV file to preview the accounts before importing them."
+                            />
+                        }
+                            .into_view()
+                    } else {
+                        let rows = rows.get().into_iter().enumerate().collect::<Vec<_>>();
+                        view! {
+                            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700 space-y-3">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    {move || {
+                                        let num_rows = statuses.with(|statuses| statuses.len());
+                                        let num_valid = num_valid.get();
+                                        format!(
+                                            "{}: {} found, {} valid.",
+                                            file_name.get(),
+                                            maybe_plural(num_rows, "row", "rows"),
+                                            num_valid,
+                                        )
+                                    }}
+
+                                </p>
+                                <Show when=move || { progress.get().0 > 0 || is_running.get() }>
+                                    <ImportProgressBar progress/>
+                                </Show>
+                            </div>
+                            <ColumnList headers=vec![
+                                "Line".to_string(),
+                                "Name".to_string(),
+                                "Email".to_string(),
+                                "Quota".to_string(),
+                                "Password".to_string(),
+                                "Status".to_string(),
+                            ]>
+
+                                <For
+                                    each=move || rows.clone()
+                                    key=|(idx, _)| *idx
+                                    children=move |(idx, row)| {
+                                        let status = create_memo(move |_| {
+                                            statuses
+                                                .with(|statuses| {
+                                                    statuses.get(idx).cloned().unwrap_or(ImportStatus::Invalid)
+                                                })
+                                        });
+                                        view! { <ImportRowItem row status/> }
+                                    }
+                                />
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn ImportRowItem(row: ImportRow, status: Memo<ImportStatus>) -> impl IntoView {
+    let errors = row.errors.clone();
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">{row.line}</span>
+            </ListItem>
+            <ListItem>
+                <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {row.name}
+                </span>
+                <span class="block text-xs text-gray-500">{row.description}</span>
+            </ListItem>
+            <ListTextItem>
+                {row
+                    .emails
+                    .into_iter()
+                    .map(|email| view! { <span class="block">{email}</span> })
+                    .collect_view()}
+            </ListTextItem>
+            <ListTextItem>
+                {if row.quota > 0 { format_bytes(row.quota) } else { String::new() }}
+            </ListTextItem>
+            <ListTextItem>
+                {if row.password.is_some() { "Provided" } else { "Generated" }}
+            </ListTextItem>
+            <ListItem>
+                {move || {
+                    let (color, label) = status.get().badge();
+                    view! { <Badge color=color>{label}</Badge> }
+                }}
+                {errors
+                    .iter()
+                    .map(|error| {
+                        view! { <span class="block text-xs text-red-600 mt-1">{error.clone()}</span> }
+                    })
+                    .collect_view()}
+
+            </ListItem>
+        </tr>
+    }
+}
+
+#[component]
+fn ImportProgressBar(progress: Memo<(usize, usize, usize)>) -> impl IntoView {
+    let percent = move || {
+        let (done, _, total) = progress.get();
+        (done * 100).checked_div(total).unwrap_or_default()
+    };
+
+    view! {
+        <div class="flex items-center gap-x-3 whitespace-nowrap">
+            <div
+                class="flex w-full h-2 bg-gray-200 rounded-full overflow-hidden dark:bg-neutral-700"
+                role="progressbar"
+                aria-valuenow=percent
+                aria-valuemin="0"
+                aria-valuemax="100"
+            >
+                <div
+                    class="flex flex-col justify-center rounded-full overflow-hidden transition duration-500"
+                    class:bg-blue-600=move || progress.get().1 == 0
+                    class:bg-red-600=move || { progress.get().1 > 0 }
+                    style=move || format!("width: {}%", percent())
+                ></div>
+            </div>
+            <div class="w-20 text-end">
+                <span class="text-sm text-gray-800 dark:text-white tabular-nums">
+                    {move || {
+                        let (done, _, total) = progress.get();
+                        format!("{done} / {total}")
+                    }}
+
+                </span>
+            </div>
+        </div>
+    }
+}
+
+async fn create_account(auth: &AccessToken, row: &ImportRow, password: &str) -> http::Result<()> {
+    let secret = sha512_crypt::hash(password).unwrap();
+    HttpRequest::post("/api/principal")
+        .with_authorization(auth)
+        .with_body(Principal {
+            typ: Some(PrincipalType::Individual),
+            name: PrincipalValue::String(row.name.clone()),
+            description: if !row.description.is_empty() {
+                PrincipalValue::String(row.description.clone())
+            } else {
+                PrincipalValue::default()
+            },
+            emails: PrincipalValue::StringList(row.emails.clone()),
+            quota: if row.quota > 0 {
+                PrincipalValue::Integer(row.quota)
+            } else {
+                PrincipalValue::default()
+            },
+            secrets: PrincipalValue::StringList(vec![secret]),
+            ..Default::default()
+        })?
+        .send::<u32>()
+        .await
+        .map(|_| ())
+}
+
+fn parse_rows(text: &str) -> Vec<ImportRow> {
+    let mut records = parse_delimited(text, detect_delimiter(text))
+        .into_iter()
+        .peekable();
+
+    let columns = match records.peek() {
+        Some((_, header)) if header.iter().any(|cell| parse_column(cell).is_some()) => {
+            let columns = header
+                .iter()
+                .map(|cell| parse_column(cell))
+                .collect::<Vec<_>>();
+            records.next();
+            columns
+        }
+        _ => DEFAULT_COLUMNS.iter().copied().map(Some).collect(),
+    };
+
+    let mut names = AHashMap::new();
+    let mut addresses = AHashMap::new();
+    records
+        .map(|(line, record)| {
+            let mut row = ImportRow {
+                line,
+                name: String::new(),
+                emails: vec![],
+                description: String::new(),
+                quota: 0,
+                password: None,
+                errors: vec![],
+            };
+            let mut aliases = vec![];
+
+            for (column, value) in columns.iter().zip(record) {
+                let value = value.trim();
+                match column {
+                    Some(Column::Name) => row.name = value.to_string(),
+                    Some(Column::Email) if !value.is_empty() => {
+                        row.emails.insert(0, value.to_lowercase());
+                    }
+                    Some(Column::Aliases) => {
+                        aliases.extend(
+                            value
+                                .split([';', ',', ' '])
+                                .filter(|alias| !alias.is_empty())
+                                .map(|alias| alias.to_lowercase()),
+                        );
+                    }
+                    Some(Column::Quota) => match parse_quota(value) {
+                        Some(quota) => row.quota = quota,
+                        None => row.errors.push(format!("Invalid quota {value:?}")),
+                    },
+                    Some(Column::Password)
+                        if !value.is_empty() && !value.eq_ignore_ascii_case("generate") =>
+                    {
+                        row.password = Some(value.to_string());
+                    }
+                    Some(Column::Description) => row.description = value.to_string(),
+                    _ => {}
+                }
+            }
+            row.emails.extend(aliases);
+
+            if row.name.is_empty() {
+                row.errors.push("Missing account name".to_string());
+            } else if row.name.contains(char::is_whitespace) {
+                row.errors
+                    .push("The account name cannot contain spaces".to_string());
+            } else if let Some(other) = names.insert(row.name.to_lowercase(), line) {
+                row.errors
+                    .push(format!("Duplicate account name, also used on line {other}"));
+            }
+            if row.emails.is_empty() {
+                row.errors.push("Missing email address".to_string());
+            }
+            for email in &row.emails {
+                if !is_valid_email(email) {
+                    row.errors.push(format!("Invalid email address {email:?}"));
+                } else if let Some(other) = addresses.insert(email.clone(), line) {
+                    row.errors.push(format!(
+                        "Duplicate address {email}, also used on line {other}"
+                    ));
+                }
+            }
+
+            row
+        })
+        .collect()
+}
+
+pub fn detect_delimiter(text: &str) -> char {
+    let first_line = text.lines().next().unwrap_or_default();
+    if first_line.contains('\t') {
+        '\t'
+    } else if first_line.contains(';') && !first_line.contains(',') {
+        ';'
+    } else {
+        ','
+    }
+}
+
+// Minimal RFC 4180 parser, quoted fields may contain delimiters and line breaks
+pub fn parse_delimited(text: &str, delimiter: char) -> Vec<(usize, Vec<String>)> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            // Whitespace left after the delimiter is not part of a quoted field
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            '\n' if in_quotes => {
+                field.push(ch);
+                line += 1;
+            }
+            '\r' if !in_quotes => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.trim().is_empty()) {
+                    records.push((record_line, std::mem::take(&mut record)));
+                } else {
+                    record.clear();
+                }
+                line += 1;
+                record_line = line;
+            }
+            ch if ch == delimiter && !in_quotes => {
+                record.push(std::mem::take(&mut field));
+            }
+            ch => field.push(ch),
+        }
+    }
+    record.push(field);
+    if record.iter().any(|field| !field.trim().is_empty()) {
+        records.push((record_line, record));
+    }
+
+    records
+}
+
+fn parse_column(name: &str) -> Option<Column> {
+    match name.trim().to_lowercase().as_str() {
+        "name" | "login" | "username" | "account" => Some(Column::Name),
+        "email" | "e-mail" | "address" => Some(Column::Email),
+        "quota" => Some(Column::Quota),
+        "password" | "secret" => Some(Column::Password),
+        "aliases" | "alias" => Some(Column::Aliases),
+        "description" | "full name" | "display name" => Some(Column::Description),
+        _ => None,
+    }
+}
+
+fn parse_quota(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Some(0);
+    }
+    let pos = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(pos);
+    let multiplier = match unit.trim().to_lowercase().as_str() {
+        "" | "b" | "bytes" => 1,
+        "k" | "kb" => UNIT_KB,
+        "m" | "mb" => UNIT_KB * UNIT_KB,
+        "g" | "gb" => UNIT_KB * UNIT_KB * UNIT_KB,
+        "t" | "tb" => UNIT_KB * UNIT_KB * UNIT_KB * UNIT_KB,
+        _ => return None,
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(multiplier))
+}
+
+fn is_valid_email(email: &str) -> bool {
+    email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !domain.contains('@')
+    })
+}
+
+fn generate_password() -> String {
+    thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(PASSWORD_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+fn format_report(rows: &[ImportRow], statuses: &[ImportStatus]) -> String {
+    let mut csv = String::from("line,name,email,status,generated password,details\n");
+    for (row, status) in rows.iter().zip(statuses) {
+        let (label, password, details) = match status {
+            ImportStatus::Invalid => ("skipped", "", row.errors.join("; ")),
+            ImportStatus::Pending | ImportStatus::Running => ("not imported", "", String::new()),
+            ImportStatus::Created { generated } => (
+                "created",
+                generated.as_deref().unwrap_or_default(),
+                String::new(),
+            ),
+            ImportStatus::Failed(err) => ("failed", "", err.clone()),
+        };
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.line,
+            escape_csv(&row.name),
+            escape_csv(
+                row.emails
+                    .first()
+                    .map(|email| email.as_str())
+                    .unwrap_or_default()
+            ),
+            label,
+            escape_csv(password),
+            escape_csv(&details),
+        ));
+    }
+    csv
+}
+
+impl ImportStatus {
+    fn badge(&self) -> (Color, String) {
+        match self {
+            ImportStatus::Invalid => (Color::Red, "Invalid".to_string()),
+            ImportStatus::Pending => (Color::Gray, "Ready".to_string()),
+            ImportStatus::Running => (Color::Blue, "Creating".to_string()),
+            ImportStatus::Created { .. } => (Color::Green, "Created".to_string()),
+            ImportStatus::Failed(err) => (Color::Red, err.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_delimited_records() {
+        assert_eq!(
+            parse_delimited("name,email\r\njohn,john@example.org\n\n  \njane,\n", ','),
+            [
+                (1, vec!["name".to_string(), "email".to_string()]),
+                (2, vec!["john".to_string(), "john@example.org".to_string()]),
+                (5, vec!["jane".to_string(), "".to_string()]),
+            ]
+        );
+        assert_eq!(
+            parse_delimited("a\tb c\td", '\t'),
+            [(1, vec!["a".to_string(), "b c".to_string(), "d".to_string()])]
+        );
+    }
+
+    #[test]
+    fn parse_delimited_quotes() {
+        assert_eq!(
+            parse_delimited("a, \"b,c\",\"say \"\"hi\"\"\"", ','),
+            [(
+                1,
+                vec!["a".to_string(), "b,c".to_string(), "say \"hi\"".to_string()]
+            )]
+        );
+        assert_eq!(
+            parse_delimited("\"multi\nline\",x\ny,z", ','),
+            [
+                (1, vec!["multi\nline".to_string(), "x".to_string()]),
+                (3, vec!["y".to_string(), "z".to_string()]),
+            ]
+        );
+        assert_eq!(
+            parse_delimited("5\" disk,a\"b", ','),
+            [(1, vec!["5\" disk".to_string(), "a\"b".to_string()])]
+        );
+    }
+
+    #[test]
+    fn parse_quota_units() {
+        assert_eq!(parse_quota(""), Some(0));
+        assert_eq!(parse_quota("512"), Some(512));
+        assert_eq!(parse_quota(" 10 KB "), Some(10 * 1024));
+        assert_eq!(parse_quota("2m"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_quota("1GB"), Some(1024 * 1024 * 1024));
+        assert_eq!(parse_quota("3 bytes"), Some(3));
+        assert_eq!(parse_quota("1 PB"), None);
+        assert_eq!(parse_quota("GB"), None);
+        assert_eq!(parse_quota("-1"), None);
+        assert_eq!(parse_quota("1.5GB"), None);
+        assert_eq!(parse_quota("99999999999 TB"), None);
+    }
+
+    #[test]
+    fn valid_email() {
+        assert!(is_valid_email("john@example.org"));
+        assert!(is_valid_email("john.doe+tag@mail.example.org"));
+        assert!(!is_valid_email("john"));
+        assert!(!is_valid_email("@example.org"));
+        assert!(!is_valid_email("john@localhost"));
+        assert!(!is_valid_email("john@.example.org"));
+        assert!(!is_valid_email("john@example.org."));
+        assert!(!is_valid_email("john@doe@example.org"));
+    }
+}
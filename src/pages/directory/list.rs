@@ -7,14 +7,14 @@
 use std::sync::Arc;
 
 use ahash::AHashSet;
-use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::*;
 
 use crate::{
     components::{
         badge::Badge,
-        icon::{IconAdd, IconThreeDots, IconTrash},
+        download::{use_signed_download, SignedDownload, SignedDownloadLink},
+        icon::{IconAdd, IconArrowDownTray, IconThreeDots, IconTrash},
         list::{
             header::ColumnList,
             pagination::Pagination,
@@ -31,17 +31,27 @@ use crate::{
         Color,
     },
     core::{
+        download::{download_file, escape_csv, Export},
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
+        AccessToken, Permission,
     },
     pages::{
-        directory::{Principal, PrincipalType},
+        directory::{Principal, PrincipalType, PrincipalValue},
         maybe_plural, List,
     },
 };
 
 const PAGE_SIZE: u32 = 10;
+const EXPORT_PAGE_SIZE: u32 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
 
 #[component]
 pub fn PrincipalList() -> impl IntoView {
@@ -230,7 +240,52 @@ pub fn PrincipalList() -> impl IntoView {
         }
         .to_string()
     });
+    let export_action = create_action(move |format: &ExportFormat| {
+        let format = *format;
+        let auth = auth.get();
+        let filter = filter.get();
+        let selected_type = selected_type.get();
+
+        async move {
+            match fetch_all_principals(&auth, selected_type, filter).await {
+                Ok(principals) => {
+                    let (contents, mime_type, extension) = match format {
+                        ExportFormat::Csv => {
+                            (format_csv(selected_type, &principals), "text/csv", "csv")
+                        }
+                        ExportFormat::Json => (
+                            serde_json::to_string_pretty(&principals).unwrap_or_default(),
+                            "application/json",
+                            "json",
+                        ),
+                    };
+                    download_file(
+                        &format!("{}.{extension}", selected_type.resource_name()),
+                        mime_type,
+                        &contents,
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let is_exportable = Signal::derive(move || {
+        matches!(
+            selected_type.get(),
+            PrincipalType::Individual
+                | PrincipalType::Group
+                | PrincipalType::List
+                | PrincipalType::Domain
+        )
+    });
+
     let show_dropdown = RwSignal::new(String::new());
+    let download = use_signed_download();
 
     view! {
         <ListSection>
@@ -294,6 +349,41 @@ pub fn PrincipalList() -> impl IntoView {
                         <IconTrash/>
                     </ToolbarButton>
 
+                    <Show when=move || is_exportable.get()>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                if export_action.pending().get() {
+                                    "Exporting...".to_string()
+                                } else {
+                                    "CSV".to_string()
+                                }
+                            })
+
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                if !export_action.pending().get_untracked() {
+                                    export_action.dispatch(ExportFormat::Csv);
+                                }
+                            })
+                        >
+
+                            <IconArrowDownTray/>
+                        </ToolbarButton>
+
+                        <ToolbarButton
+                            text="JSON"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                if !export_action.pending().get_untracked() {
+                                    export_action.dispatch(ExportFormat::Json);
+                                }
+                            })
+                        >
+
+                            <IconArrowDownTray/>
+                        </ToolbarButton>
+                    </Show>
+
                     <ToolbarButton
                         text=create_memo(move |_| {
                             format!("Create {}", selected_type.get().item_name(false))
@@ -316,6 +406,8 @@ pub fn PrincipalList() -> impl IntoView {
 
                 </Toolbar>
 
+                <SignedDownloadLink download/>
+
                 <Transition fallback=Skeleton>
                     {move || match principals.get() {
                         None => None,
@@ -416,6 +508,7 @@ pub fn PrincipalList() -> impl IntoView {
                                                     api_action,
                                                     modal,
                                                     show_dropdown,
+                                                    download,
                                                 }
                                             />
 
@@ -483,12 +576,86 @@ pub fn PrincipalList() -> impl IntoView {
     }
 }
 
+// Exports cover the whole filtered result set, not only the visible page
+async fn fetch_all_principals(
+    auth: &AccessToken,
+    selected_type: PrincipalType,
+    filter: Option<String>,
+) -> http::Result<Vec<Principal>> {
+    let mut principals = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let result = HttpRequest::get("/api/principal")
+            .with_authorization(auth)
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", EXPORT_PAGE_SIZE.to_string())
+            .with_parameter("types", selected_type.id())
+            .with_optional_parameter("filter", filter.clone())
+            .send::<List<Principal>>()
+            .await?;
+        let is_last = result.items.len() < EXPORT_PAGE_SIZE as usize;
+        principals.extend(result.items.into_iter().map(|mut principal| {
+            principal.secrets = PrincipalValue::default();
+            principal
+        }));
+        if is_last || principals.len() as u64 >= result.total {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(principals)
+}
+
+fn format_csv(selected_type: PrincipalType, principals: &[Principal]) -> String {
+    let mut csv = String::from(match selected_type {
+        PrincipalType::Individual => "name,description,emails,quota,used_quota,member_of,roles\n",
+        PrincipalType::Group => "name,description,emails,members,member_of\n",
+        PrincipalType::List => "name,description,emails,members,external_members\n",
+        _ => "name,description\n",
+    });
+    let list = |value: &PrincipalValue| escape_csv(&value.as_string_list().join(" "));
+
+    for principal in principals {
+        let name = escape_csv(principal.name().unwrap_or_default());
+        let description = escape_csv(principal.description().unwrap_or_default());
+        let line = match selected_type {
+            PrincipalType::Individual => format!(
+                "{name},{description},{},{},{},{},{}",
+                list(&principal.emails),
+                principal.quota.as_int().unwrap_or_default(),
+                principal.used_quota.as_int().unwrap_or_default(),
+                list(&principal.member_of),
+                list(&principal.roles),
+            ),
+            PrincipalType::Group => format!(
+                "{name},{description},{},{},{}",
+                list(&principal.emails),
+                list(&principal.members),
+                list(&principal.member_of),
+            ),
+            PrincipalType::List => format!(
+                "{name},{description},{},{},{}",
+                list(&principal.emails),
+                list(&principal.members),
+                list(&principal.external_members),
+            ),
+            _ => format!("{name},{description}"),
+        };
+        csv.push_str(&line);
+        csv.push('\n');
+    }
+    csv
+}
+
 struct Parameters {
     selected_type: PrincipalType,
     delete_action: Action<Arc<ItemSelection>, ()>,
     api_action: Action<ApiAction, ()>,
     modal: RwSignal<Modal>,
     show_dropdown: RwSignal<String>,
+    download: SignedDownload,
 }
 
 enum ApiAction {
@@ -501,6 +668,10 @@ fn PrincipalItem(principal: Principal, params: Parameters) -> impl IntoView {
     let selected_type = params.selected_type;
     let show_dropdown = params.show_dropdown;
     let principal = RwSignal::new(principal);
+    let permissions = use_authorization().get_untracked().permissions().clone();
+    let can_impersonate = permissions.has_access(Permission::Impersonate);
+    let can_browse = permissions.has_access(Permission::MailboxBrowse);
+    let can_inspect_sieve = permissions.has_access(Permission::SieveInspect);
 
     view! {
         <tr>
@@ -586,11 +757,11 @@ fn PrincipalItem(principal: Principal, params: Parameters) -> impl IntoView {
                         (Some(quota), Some(used_quota)) => {
                             format!(
                                 "{} ({}%)",
-                                format_size(used_quota, DECIMAL),
+                                format_bytes(used_quota),
                                 (used_quota as f64 / quota as f64 * 100.0).round() as u8,
                             )
                         }
-                        (_, Some(used_quota)) => format_size(used_quota, DECIMAL).to_string(),
+                        (_, Some(used_quota)) => format_bytes(used_quota),
                         _ => "N/A".to_string(),
                     }}
 
@@ -714,6 +885,54 @@ fn PrincipalItem(principal: Principal, params: Parameters) -> impl IntoView {
 
                                 View DNS records
                             </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    format!(
+                                        "/manage/dns/{}/mta-sts",
+                                        principal.get_untracked().name().unwrap_or_default(),
+                                    )
+                                }
+
+                                class:hidden=move || {
+                                    !matches!(selected_type, PrincipalType::Domain)
+                                }
+                            >
+
+                                MTA-STS policy
+                            </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    format!(
+                                        "/manage/dns/{}/addresses",
+                                        principal.get_untracked().name().unwrap_or_default(),
+                                    )
+                                }
+
+                                class:hidden=move || {
+                                    !matches!(selected_type, PrincipalType::Domain)
+                                }
+                            >
+
+                                Catch-all & aliases
+                            </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    format!(
+                                        "/manage/dkim?domain={}",
+                                        principal.get_untracked().name().unwrap_or_default(),
+                                    )
+                                }
+
+                                class:hidden=move || {
+                                    !matches!(selected_type, PrincipalType::Domain)
+                                }
+                            >
+
+                                DKIM keys
+                            </a>
                             <a
                                 class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
                                 href=move || {
@@ -812,6 +1031,81 @@ fn PrincipalItem(principal: Principal, params: Parameters) -> impl IntoView {
 
                                 Undelete emails
                             </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                on:click=move |_| {
+                                    show_dropdown.set(String::new());
+                                    params
+                                        .download
+                                        .request(Export::Mailbox {
+                                            account: principal.get_untracked().name_or_empty(),
+                                        });
+                                }
+
+                                class:hidden=move || {
+                                    !matches!(selected_type, PrincipalType::Individual)
+                                }
+                            >
+
+                                Export mailbox
+                            </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    UrlBuilder::new("/manage/impersonate")
+                                        .with_subpath(
+                                            principal.get_untracked().name().unwrap_or_default(),
+                                        )
+                                        .finish()
+                                }
+
+                                class:hidden=move || {
+                                    !can_impersonate
+                                        || !matches!(selected_type, PrincipalType::Individual)
+                                }
+                            >
+
+                                Impersonate
+                            </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    UrlBuilder::new("/manage/mailbox")
+                                        .with_subpath(
+                                            principal.get_untracked().name().unwrap_or_default(),
+                                        )
+                                        .finish()
+                                }
+
+                                class:hidden=move || {
+                                    !can_browse
+                                        || !matches!(
+                                            selected_type,
+                                            PrincipalType::Individual | PrincipalType::Group
+                                        )
+                                }
+                            >
+
+                                Browse mailbox
+                            </a>
+                            <a
+                                class="flex items-center gap-x-3 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:ring-2 focus:ring-blue-500 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300"
+                                href=move || {
+                                    UrlBuilder::new("/manage/sieve")
+                                        .with_subpath(
+                                            principal.get_untracked().name().unwrap_or_default(),
+                                        )
+                                        .finish()
+                                }
+
+                                class:hidden=move || {
+                                    !can_inspect_sieve
+                                        || !matches!(selected_type, PrincipalType::Individual)
+                                }
+                            >
+
+                                Sieve scripts
+                            </a>
                         </div>
                         <div class="py-2 first:pt-0 last:pb-0">
                             <a
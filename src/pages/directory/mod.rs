@@ -13,9 +13,18 @@ use serde::{
 
 use base64::{engine::general_purpose::STANDARD, Engine};
 
+use crate::core::{command::Command, Permission, Permissions};
+
+pub mod addresses;
+pub mod dkim;
 pub mod dns;
 pub mod edit;
+pub mod impersonate;
+pub mod import;
 pub mod list;
+pub mod mta_sts;
+pub mod quota;
+pub mod sandbox;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Principal {
@@ -81,6 +90,38 @@ pub struct Principal {
     #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
     #[serde(rename = "externalMembers")]
     pub external_members: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "postingPolicy")]
+    pub posting_policy: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "moderators")]
+    pub moderators: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "replyTo")]
+    pub reply_to: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "subjectPrefix")]
+    pub subject_prefix: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "grantTypes")]
+    pub grant_types: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "accessTokenExpiry")]
+    pub access_token_expiry: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "refreshTokenExpiry")]
+    pub refresh_token_expiry: PrincipalValue,
+
+    #[serde(default, skip_serializing_if = "PrincipalValue::is_none")]
+    #[serde(rename = "expiresAt")]
+    pub expires_at: PrincipalValue,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -122,6 +163,14 @@ pub enum PrincipalField {
     Picture,
     Urls,
     ExternalMembers,
+    PostingPolicy,
+    Moderators,
+    ReplyTo,
+    SubjectPrefix,
+    GrantTypes,
+    AccessTokenExpiry,
+    RefreshTokenExpiry,
+    ExpiresAt,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -131,6 +180,31 @@ pub struct PrincipalUpdate {
     value: PrincipalValue,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrincipalUsage {
+    #[serde(default)]
+    pub mailboxes: Vec<MailboxUsage>,
+    #[serde(default)]
+    pub blobs: Vec<BlobUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MailboxUsage {
+    pub name: String,
+    pub size: u64,
+    #[serde(default)]
+    pub messages: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobUsage {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub size: u64,
+    #[serde(default)]
+    pub count: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum PrincipalAction {
     #[serde(rename = "set")]
@@ -150,6 +224,32 @@ pub enum PrincipalValue {
     IntegerList(Vec<u64>),
 }
 
+impl PrincipalUpdate {
+    pub fn set(field: PrincipalField, value: PrincipalValue) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::Set,
+            field,
+            value,
+        }
+    }
+
+    pub fn add_item(field: PrincipalField, value: PrincipalValue) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::AddItem,
+            field,
+            value,
+        }
+    }
+
+    pub fn remove_item(field: PrincipalField, value: PrincipalValue) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::RemoveItem,
+            field,
+            value,
+        }
+    }
+}
+
 impl Principal {
     pub fn is_blank(&self) -> bool {
         self.id.is_none()
@@ -174,6 +274,32 @@ impl Principal {
             ),
             (current.tenant, changes.tenant, PrincipalField::Tenant),
             (current.picture, changes.picture, PrincipalField::Picture),
+            (
+                current.posting_policy,
+                changes.posting_policy,
+                PrincipalField::PostingPolicy,
+            ),
+            (current.reply_to, changes.reply_to, PrincipalField::ReplyTo),
+            (
+                current.subject_prefix,
+                changes.subject_prefix,
+                PrincipalField::SubjectPrefix,
+            ),
+            (
+                current.access_token_expiry,
+                changes.access_token_expiry,
+                PrincipalField::AccessTokenExpiry,
+            ),
+            (
+                current.refresh_token_expiry,
+                changes.refresh_token_expiry,
+                PrincipalField::RefreshTokenExpiry,
+            ),
+            (
+                current.expires_at,
+                changes.expires_at,
+                PrincipalField::ExpiresAt,
+            ),
         ] {
             let current = current.unwrap_string();
             let change = change.unwrap_string();
@@ -269,6 +395,16 @@ impl Principal {
                 changes.disabled_permissions,
             ),
             (PrincipalField::Urls, current.urls, changes.urls),
+            (
+                PrincipalField::Moderators,
+                current.moderators,
+                changes.moderators,
+            ),
+            (
+                PrincipalField::GrantTypes,
+                current.grant_types,
+                changes.grant_types,
+            ),
         ] {
             let current = current.unwrap_string_list();
             let change = change.unwrap_string_list();
@@ -690,6 +826,10 @@ pub static PERMISSIONS: &[(&str, &str)] = &[
     ("settings-update", "Modify system settings"),
     ("settings-delete", "Remove system settings"),
     ("settings-reload", "Refresh system settings"),
+    (
+        "settings-secrets-read",
+        "Reveal the clear-text value of stored secrets",
+    ),
     ("individual-list", "View list of user accounts"),
     ("individual-get", "Retrieve specific account information"),
     ("individual-update", "Modify user account information"),
@@ -919,4 +1059,37 @@ pub static PERMISSIONS: &[(&str, &str)] = &[
     ("oauth-client-override", "Override OAuth client settings"),
     ("ai-model-interact", "Interact with AI models"),
     ("troubleshoot", "Perform troubleshooting"),
+    ("mailbox-browse", "Browse the mailboxes of other accounts"),
+    ("store-query", "Run the lookup queries of SQL stores"),
+    (
+        "mailbox-migrate",
+        "Migrate mailboxes from other mail servers",
+    ),
+    (
+        "sieve-inspect",
+        "View and deactivate the Sieve scripts of other accounts",
+    ),
+    (
+        "session-list",
+        "View the connections currently open on the server",
+    ),
+    ("session-terminate", "Terminate open client connections"),
 ];
+
+pub fn commands(permissions: &Permissions) -> Vec<Command> {
+    [
+        (Permission::IndividualCreate, "Create account", "accounts"),
+        (Permission::GroupCreate, "Create group", "groups"),
+        (Permission::DomainCreate, "Create domain", "domains"),
+    ]
+    .into_iter()
+    .filter(|(permission, _, _)| permissions.has_access(*permission))
+    .map(|(_, title, object)| {
+        Command::navigate(
+            title,
+            "Directory",
+            format!("/manage/directory/{object}/edit"),
+        )
+    })
+    .collect()
+}
@@ -0,0 +1,446 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use leptos::*;
+use leptos_router::{use_navigate, use_params_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputDuration, select::Select, stacked_input::StackedInput,
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        expr::ParseValue,
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            Settings, UpdateSettings,
+        },
+        directory::dns::{DnsRecord, DnsRecordStatus},
+    },
+};
+
+const POLICY_PREFIX: &str = "session.mta-sts.policy";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FetchSettings {
+    items: Settings,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct PublishedPolicy {
+    #[serde(default)]
+    policy: Option<String>,
+    #[serde(default)]
+    record: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Policy {
+    text: String,
+    id: String,
+}
+
+#[component]
+pub fn MtaStsPolicy() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let params = use_params_map();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let schema = store_value(schemas.with_value(|schemas| schemas.get("mta-sts-policy")));
+    let domain = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+
+    let (pending, set_pending) = create_signal(false);
+    let data = FormData::default().into_signal();
+
+    let fetch_policy = create_resource(
+        move || domain.get(),
+        move |domain| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let settings = HttpRequest::get("/api/settings/list")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", format!("{POLICY_PREFIX}.{domain}"))
+                    .send::<FetchSettings>()
+                    .await?
+                    .items;
+                if !settings.is_empty() {
+                    let mut settings = settings;
+                    settings.insert("_id".to_string(), domain);
+                    Ok((Some(settings), vec![]))
+                } else {
+                    // New policies start with the MX hosts currently published for the domain.
+                    let mx = HttpRequest::get(("/api/dns/records", &domain))
+                        .with_authorization(&auth)
+                        .send::<Vec<DnsRecord>>()
+                        .await?
+                        .into_iter()
+                        .filter(|record| record.typ == "MX")
+                        .filter_map(|record| {
+                            record
+                                .content
+                                .split_whitespace()
+                                .last()
+                                .map(|host| host.trim_end_matches('.').to_lowercase())
+                        })
+                        .collect::<Vec<_>>();
+                    Ok((None, mx))
+                }
+            }
+        },
+    );
+
+    // Browsers cannot fetch the policy from the well-known URL due to CORS,
+    // the server performs the lookup on our behalf.
+    let published = create_resource(
+        move || domain.get(),
+        move |domain| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get(("/api/dns/mta-sts", &domain))
+                    .with_authorization(&auth)
+                    .try_send::<PublishedPolicy>()
+                    .await
+            }
+        },
+    );
+
+    let policy = create_memo(move |_| {
+        data.with(|data| {
+            build_policy(
+                data.value_as_str("mode").unwrap_or("none"),
+                data.value_as_str("max-age")
+                    .and_then(Duration::parse_value)
+                    .unwrap_or_default(),
+                data.array_value("mx"),
+            )
+        })
+    });
+
+    let cluster_results = create_rw_signal(None);
+    let save_changes = create_action(move |changes: &Arc<Vec<UpdateSettings>>| {
+        let changes = changes.clone();
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = match HttpRequest::post("/api/settings")
+                .with_authorization(&auth)
+                .with_body(changes)
+                .unwrap()
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(_) => reload_settings(&auth, "").await,
+                Err(err) => Err(err),
+            };
+            set_pending.set(false);
+
+            match result {
+                Ok(ReloadOutcome::Local(result)) if !result.errors.is_empty() => {
+                    alert.set(Alert::from_reload(result, &schemas.get_value()));
+                }
+                Ok(outcome) => {
+                    if let ReloadOutcome::Cluster(results) = outcome {
+                        cluster_results.set(Some(results));
+                    }
+                    fetch_policy.refetch();
+                    published.refetch();
+                    alert.set(Alert::success("MTA-STS policy saved.").without_timeout());
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title=Signal::derive(move || format!("MTA-STS policy for {}", domain.get()))
+            subtitle="Require TLS for incoming mail from senders that support MTA-STS"
+        >
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=""
+                on_close=move |_| cluster_results.set(None)
+            />
+
+            <Transition fallback=Skeleton>
+
+                {move || match fetch_policy.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok((settings, mx))) => {
+                        let is_create = settings.is_none();
+                        let mut form = FormData::from_settings(
+                            schema.get_value(),
+                            settings,
+                        );
+                        if is_create {
+                            form.set("_id", domain.get_untracked());
+                            if !mx.is_empty() {
+                                form.set("mx", mx);
+                            }
+                        }
+                        data.set(form);
+                        Some(
+                            view! {
+                                <FormSection title="Policy".to_string()>
+                                    <FormItem
+                                        label="Mode"
+                                        tooltip="How sending servers should treat delivery failures caused by TLS errors"
+                                    >
+                                        <Select element=FormElement::new("mode", data)/>
+                                    </FormItem>
+                                    <FormItem
+                                        label="Max age"
+                                        tooltip="How long sending servers may cache this policy"
+                                    >
+                                        <InputDuration element=FormElement::new("max-age", data)/>
+                                    </FormItem>
+                                    <FormItem
+                                        label="MX patterns"
+                                        tooltip="Hostnames allowed to receive mail for this domain, wildcards such as *.example.org are allowed"
+                                    >
+                                        <StackedInput
+                                            element=FormElement::new("mx", data)
+                                            add_button_text="Add MX".to_string()
+                                        />
+                                    </FormItem>
+                                </FormSection>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <FormSection title="Publishing".to_string()>
+                <FormItem
+                    label="Policy file"
+                    tooltip="Contents served over HTTPS at the well-known MTA-STS location"
+                >
+                    <div class="flex flex-col gap-y-2">
+                        <input
+                            type="text"
+                            class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            readonly=true
+                            prop:value=move || {
+                                format!("https://mta-sts.{}/.well-known/mta-sts.txt", domain.get())
+                            }
+                        />
+                        <textarea
+                            class="py-3 px-4 block w-full border-gray-200 rounded-lg text-sm font-mono text-nowrap focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            readonly=true
+                            rows=6
+                            prop:value=move || policy.get().text
+                        ></textarea>
+                        <PublishedStatus status=Signal::derive(move || {
+                            match published.get() {
+                                Some(Ok(Some(published))) => {
+                                    published.policy.as_deref().map_or(
+                                            DnsRecordStatus::Missing,
+                                            |text| {
+                                                policy.get().matches_text(text)
+                                            },
+                                        )
+                                }
+                                _ => DnsRecordStatus::Unknown,
+                            }
+                        })/>
+                    </div>
+                </FormItem>
+                <FormItem label="DNS record" tooltip="TXT record announcing the current policy id">
+                    <div class="flex flex-col gap-y-2">
+                        <input
+                            type="text"
+                            class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            readonly=true
+                            prop:value=move || {
+                                format!(
+                                    "_mta-sts.{}. IN TXT \"v=STSv1; id={}\"",
+                                    domain.get(),
+                                    policy.get().id,
+                                )
+                            }
+                        />
+                        <PublishedStatus status=Signal::derive(move || {
+                            match published.get() {
+                                Some(Ok(Some(published))) => {
+                                    published.record.as_deref().map_or(
+                                            DnsRecordStatus::Missing,
+                                            |record| {
+                                                policy.get().matches_record(record)
+                                            },
+                                        )
+                                }
+                                _ => DnsRecordStatus::Unknown,
+                            }
+                        })/>
+                    </div>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/manage/directory/domains", Default::default());
+                    }
+                />
+
+                <Button
+                    text="Save changes"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                save_changes.dispatch(Arc::new(data.build_update()));
+                            }
+                        });
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+fn PublishedStatus(#[prop(into)] status: Signal<DnsRecordStatus>) -> impl IntoView {
+    view! {
+        <div class="flex items-center gap-x-2">
+            <span class="text-sm text-gray-600 dark:text-gray-400">Published:</span>
+            {move || {
+                let status = status.get();
+                view! { <Badge color=status.color()>{status.label()}</Badge> }
+            }}
+        </div>
+    }
+}
+
+fn build_policy<'x>(mode: &str, max_age: Duration, mx: impl Iterator<Item = &'x str>) -> Policy {
+    let mut text = format!("version: STSv1\nmode: {mode}\n");
+    for mx in mx {
+        text.push_str(&format!("mx: {mx}\n"));
+    }
+    text.push_str(&format!("max_age: {}\n", max_age.as_secs()));
+
+    Policy {
+        id: policy_id(&text),
+        text,
+    }
+}
+
+// The id only has to change whenever the policy does, so it is derived from
+// the policy contents rather than stored alongside it.
+fn policy_id(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+impl Policy {
+    fn matches_text(&self, published: &str) -> DnsRecordStatus {
+        let normalize = |text: &str| {
+            let mut lines = text
+                .lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            lines.sort_unstable();
+            lines
+        };
+
+        if normalize(&self.text) == normalize(published) {
+            DnsRecordStatus::Verified
+        } else {
+            DnsRecordStatus::Mismatch
+        }
+    }
+
+    fn matches_record(&self, published: &str) -> DnsRecordStatus {
+        let id = published
+            .trim_matches('"')
+            .split(';')
+            .filter_map(|part| part.trim().strip_prefix("id="))
+            .next();
+
+        if id == Some(self.id.as_str()) {
+            DnsRecordStatus::Verified
+        } else {
+            DnsRecordStatus::Mismatch
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_mta_sts(self) -> Self {
+        self.new_schema("mta-sts-policy")
+            .prefix(POLICY_PREFIX)
+            .suffix("mode")
+            .names("MTA-STS policy", "MTA-STS policies")
+            .new_id_field()
+            .label("Domain")
+            .build()
+            .new_field("mode")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("enforce", "Enforce"),
+                    ("testing", "Testing"),
+                    ("none", "None"),
+                ]),
+            })
+            .input_check([], [Validator::Required])
+            .default("testing")
+            .build()
+            .new_field("max-age")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .default("7d")
+            .build()
+            .new_field("mx")
+            .typ(Type::Array)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required],
+            )
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,656 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        form::{button::Button, input::InputSize, FormElement, FormItem},
+        icon::IconScale,
+        list::{
+            header::{ColumnList, ColumnSort, SortOrder},
+            pagination::Pagination,
+            row::SelectItem,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ItemSelection, ListItem, ListSection, ListTable, ListTextItem, Toolbar,
+            ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        format::format_bytes,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Type},
+        url::UrlBuilder,
+        AccessToken, Permission,
+    },
+    pages::{
+        directory::{
+            Principal, PrincipalField, PrincipalType, PrincipalUpdate, PrincipalUsage,
+            PrincipalValue,
+        },
+        maybe_plural, List,
+    },
+};
+
+const PAGE_SIZE: u32 = 20;
+const DEFAULT_SORT: &str = "usedQuota";
+
+#[component]
+pub fn StorageQuotas() -> impl IntoView {
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter").and_then(|s| {
+                let s = s.trim();
+                if !s.is_empty() {
+                    Some(s.to_string())
+                } else {
+                    None
+                }
+            })
+        })
+    });
+    let sort = create_memo(move |_| {
+        query.with(|q| {
+            let key = q
+                .get("sort")
+                .filter(|key| SORT_KEYS.contains(&key.as_str()))
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_SORT.to_string());
+            let order = if q.get("order").is_some_and(|order| order == "asc") {
+                SortOrder::Ascending
+            } else {
+                SortOrder::Descending
+            };
+            (key, order)
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let show_bulk = create_rw_signal(false);
+    let (pending, set_pending) = create_signal(false);
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("bulk-quota")
+        .into_signal();
+
+    let accounts = create_resource(
+        move || (page.get(), filter.get(), sort.get()),
+        move |(page, filter, (sort, order))| {
+            let auth = auth.get_untracked();
+
+            async move {
+                fetch_accounts(&auth, page, PAGE_SIZE, filter, &sort, order)
+                    .await
+                    .map(Arc::new)
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<u32>);
+    let set_quota_action = create_action(move |(items, quota): &(Arc<ItemSelection>, u64)| {
+        let items = items.clone();
+        let quota = *quota;
+        let auth = auth.get();
+        let filter = filter.get();
+
+        async move {
+            set_pending.set(true);
+            let names = match items.as_ref() {
+                ItemSelection::Some(items) => Ok(items.iter().cloned().collect::<Vec<_>>()),
+                ItemSelection::All => {
+                    let total = total_results.get_untracked().unwrap_or_default().max(1);
+                    fetch_accounts(&auth, 1, total, filter, DEFAULT_SORT, SortOrder::Descending)
+                        .await
+                        .map(|list| list.items.iter().map(|p| p.name_or_empty()).collect())
+                }
+                ItemSelection::None => Ok(vec![]),
+            };
+            let result = match names {
+                Ok(names) => {
+                    let mut result = Ok(names.len());
+                    for name in &names {
+                        if let Err(err) = HttpRequest::patch(("/api/principal", name))
+                            .with_authorization(&auth)
+                            .with_body(vec![PrincipalUpdate::set(
+                                PrincipalField::Quota,
+                                PrincipalValue::Integer(quota),
+                            )])
+                            .unwrap()
+                            .send::<serde_json::Value>()
+                            .await
+                        {
+                            result = Err(err);
+                            break;
+                        }
+                    }
+                    result
+                }
+                Err(err) => Err(err),
+            };
+            set_pending.set(false);
+
+            match result {
+                Ok(updated) => {
+                    show_bulk.set(false);
+                    selected.set(ItemSelection::None);
+                    accounts.refetch();
+                    alert.set(Alert::success(format!(
+                        "Updated the quota of {}.",
+                        maybe_plural(updated, "account", "accounts")
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    accounts.refetch();
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let navigate_to =
+        move |page: u32, filter: Option<String>, (sort, order): (String, SortOrder)| {
+            use_navigate()(
+                &UrlBuilder::new("/manage/quotas")
+                    .with_parameter("page", page.to_string())
+                    .with_optional_parameter("filter", filter)
+                    .with_parameter("sort", sort)
+                    .with_parameter(
+                        "order",
+                        match order {
+                            SortOrder::Ascending => "asc",
+                            SortOrder::Descending => "desc",
+                        },
+                    )
+                    .finish(),
+                Default::default(),
+            );
+        };
+    let column_sort = store_value(ColumnSort {
+        keys: vec![
+            Some("name"),
+            Some("quota"),
+            Some("usedQuota"),
+            Some("usage"),
+            None,
+        ],
+        current: sort.into(),
+        on_sort: Callback::new(move |(key, order): (&'static str, SortOrder)| {
+            navigate_to(1, filter.get(), (key.to_string(), order));
+        }),
+    });
+
+    view! {
+        <ListSection>
+            <ListTable title="Storage quotas" subtitle="Review the storage used by each account">
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value: String| {
+                            navigate_to(1, Some(value), sort.get());
+                        }
+                    />
+
+                    <Show when=move || {
+                        auth.get().permissions().has_access(Permission::IndividualUpdate)
+                    }>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                let ns = selected.get().total_selected(total_results.get());
+                                if ns > 0 {
+                                    format!("Set quota ({ns})")
+                                } else {
+                                    "Set quota".to_string()
+                                }
+                            })
+
+                            color=Color::Blue
+                            on_click=Callback::new(move |_| {
+                                if selected.get().total_selected(total_results.get()) > 0 {
+                                    show_bulk.update(|show| *show = !*show);
+                                } else {
+                                    alert.set(Alert::warning("Select the accounts to update first."));
+                                }
+                            })
+                        >
+
+                            <IconScale attr:class="flex-shrink-0 size-4"/>
+                        </ToolbarButton>
+                    </Show>
+
+                </Toolbar>
+
+                <Show when=move || show_bulk.get()>
+                    <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                        <FormItem label="New quota" tooltip="Leave empty to remove the quota">
+                            <InputSize element=FormElement::new("quota", data)/>
+                        </FormItem>
+                        <div class="mt-3 flex justify-end gap-x-2">
+                            <Button
+                                text="Cancel"
+                                color=Color::Gray
+                                on_click=move |_| {
+                                    show_bulk.set(false);
+                                }
+                            />
+
+                            <Button
+                                text=Signal::derive(move || {
+                                    format!(
+                                        "Apply to {}",
+                                        maybe_plural(
+                                            selected.get().total_selected(total_results.get()),
+                                            "account",
+                                            "accounts",
+                                        ),
+                                    )
+                                })
+
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            let quota = data.value::<u64>("quota").unwrap_or_default();
+                                            set_quota_action
+                                                .dispatch((Arc::new(selected.get()), quota));
+                                        }
+                                    });
+                                }
+                            />
+
+                        </div>
+                    </div>
+                </Show>
+
+                <Transition fallback=Skeleton>
+                    {move || match accounts.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(accounts)) if !accounts.items.is_empty() => {
+                            total_results.set(Some(accounts.total as u32));
+                            let accounts_ = accounts.clone();
+                            Some(
+                                view! {
+                                    <ColumnList
+                                        headers=vec![
+                                            "Account".to_string(),
+                                            "Quota".to_string(),
+                                            "Used".to_string(),
+                                            "Usage".to_string(),
+                                            "".to_string(),
+                                        ]
+
+                                        has_select_all=true
+                                        sort=column_sort.get_value()
+                                    >
+
+                                        <For
+                                            each=move || accounts_.items.clone()
+                                            key=|principal| principal.name_or_empty()
+                                            let:principal
+                                        >
+                                            <AccountQuotaItem principal/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="Your search did not yield any results."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            navigate_to(page, filter.get(), sort.get());
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn AccountQuotaItem(principal: Principal) -> impl IntoView {
+    let name = principal.name_or_empty();
+    let item_id = name.clone();
+    let description = principal
+        .description_or_name()
+        .unwrap_or_default()
+        .to_string();
+    let quota = principal.quota.as_int_non_zero();
+    let used = principal.used_quota.as_int().unwrap_or_default();
+    let details_url = UrlBuilder::new("/manage/quotas")
+        .with_subpath(&name)
+        .finish();
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex">
+                    <SelectItem item_id/>
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListItem subclass="ps-6 lg:ps-3 xl:ps-0 pe-6 py-3">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {description}
+                </span>
+                <span class="block text-sm text-gray-500">{name}</span>
+            </ListItem>
+            <ListTextItem>
+                {quota
+                    .map(format_bytes)
+                    .unwrap_or_else(|| "Unlimited".to_string())}
+            </ListTextItem>
+            <ListTextItem>{format_bytes(used)}</ListTextItem>
+            <ListItem subclass="px-6 py-3 min-w-48">
+                <UsageBar used total=quota/>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <a
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    href=details_url
+                >
+                    Details
+                </a>
+            </ListItem>
+        </tr>
+    }
+}
+
+#[component]
+pub fn AccountUsage() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let params = use_params_map();
+    let name = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+
+    let usage = create_resource(
+        move || name.get(),
+        move |name| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let principal = HttpRequest::get(("/api/principal", &name))
+                    .with_authorization(&auth)
+                    .send::<Principal>()
+                    .await?;
+                let usage = fetch_usage(&auth, &name).await?;
+
+                Ok::<_, http::Error>(Arc::new((principal, usage)))
+            }
+        },
+    );
+
+    view! {
+        <ListSection>
+            <Transition fallback=Skeleton>
+                {move || match usage.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        use_navigate()("/manage/quotas", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok(result)) => {
+                        let (principal, usage) = result.as_ref().clone();
+                        let quota = principal.quota.as_int_non_zero();
+                        let used = principal.used_quota.as_int().unwrap_or_default();
+                        let title = format!(
+                            "Storage used by {}",
+                            principal.description_or_name().unwrap_or_default(),
+                        );
+                        let subtitle = match quota {
+                            Some(quota) => {
+                                format!(
+                                    "{} of {} used",
+                                    format_bytes(used),
+                                    format_bytes(quota),
+                                )
+                            }
+                            None => format!("{} used, no quota set", format_bytes(used)),
+                        };
+                        let mut mailboxes = usage.mailboxes;
+                        mailboxes.sort_by_key(|mailbox| std::cmp::Reverse(mailbox.size));
+                        let mut blobs = usage.blobs;
+                        blobs.sort_by_key(|blob| std::cmp::Reverse(blob.size));
+                        let has_mailboxes = !mailboxes.is_empty();
+                        let has_blobs = !blobs.is_empty();
+                        let mailbox_rows = mailboxes
+                            .into_iter()
+                            .map(|mailbox| {
+                                view! {
+                                    <tr>
+                                        <ListTextItem>{mailbox.name}</ListTextItem>
+                                        <ListTextItem>{mailbox.messages.to_string()}</ListTextItem>
+                                        <ListTextItem>{format_bytes(mailbox.size)}</ListTextItem>
+                                        <ListItem subclass="px-6 py-3 min-w-48">
+                                            <UsageBar used=mailbox.size total=Some(used)/>
+                                        </ListItem>
+                                    </tr>
+                                }
+                            })
+                            .collect_view();
+                        let blob_rows = blobs
+                            .into_iter()
+                            .map(|blob| {
+                                view! {
+                                    <tr>
+                                        <ListTextItem>{blob.typ}</ListTextItem>
+                                        <ListTextItem>{blob.count.to_string()}</ListTextItem>
+                                        <ListTextItem>{format_bytes(blob.size)}</ListTextItem>
+                                        <ListItem subclass="px-6 py-3 min-w-48">
+                                            <UsageBar used=blob.size total=Some(used)/>
+                                        </ListItem>
+                                    </tr>
+                                }
+                            })
+                            .collect_view();
+
+                        Some(
+                            view! {
+                                <div class="mb-6 bg-white border border-gray-200 rounded-xl shadow-sm p-4 md:p-5 dark:bg-slate-900 dark:border-gray-700">
+                                    <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                                        {title}
+                                    </h2>
+                                    <p class="mb-3 text-sm text-gray-600 dark:text-gray-400">
+                                        {subtitle}
+                                    </p>
+                                    <UsageBar used total=quota/>
+                                </div>
+
+                                <ListTable title="Mailboxes" subtitle="Storage used by each mailbox">
+                                    <Toolbar slot>
+                                        <span></span>
+                                    </Toolbar>
+                                    <ColumnList headers=vec![
+                                        "Mailbox".to_string(),
+                                        "Messages".to_string(),
+                                        "Size".to_string(),
+                                        "Share".to_string(),
+                                    ]>{mailbox_rows}</ColumnList>
+                                    <Footer slot>
+                                        <Show when=move || !has_mailboxes>
+                                            <ZeroResults
+                                                title="No mailboxes"
+                                                subtitle="This account does not have any mailboxes."
+                                            />
+                                        </Show>
+                                    </Footer>
+                                </ListTable>
+
+                                <div class="mt-6">
+                                    <ListTable title="Blobs" subtitle="Storage used by blob type">
+                                        <Toolbar slot>
+                                            <span></span>
+                                        </Toolbar>
+                                        <ColumnList headers=vec![
+                                            "Type".to_string(),
+                                            "Objects".to_string(),
+                                            "Size".to_string(),
+                                            "Share".to_string(),
+                                        ]>{blob_rows}</ColumnList>
+                                        <Footer slot>
+                                            <Show when=move || !has_blobs>
+                                                <ZeroResults
+                                                    title="No blobs"
+                                                    subtitle="This account is not storing any blobs."
+                                                />
+                                            </Show>
+                                        </Footer>
+                                    </ListTable>
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </ListSection>
+    }
+}
+
+#[component]
+fn UsageBar(used: u64, total: Option<u64>) -> impl IntoView {
+    let percent = total
+        .filter(|&total| total > 0)
+        .map(|total| ((used as f64 / total as f64) * 100.0).round() as u64);
+    let (width, bar_class) = match percent {
+        Some(percent) if percent >= 90 => (percent.min(100), "bg-red-600"),
+        Some(percent) if percent >= 75 => (percent, "bg-yellow-500"),
+        Some(percent) => (percent, "bg-blue-600"),
+        None => (0, "bg-blue-600"),
+    };
+
+    view! {
+        <div class="flex items-center gap-x-3 whitespace-nowrap">
+            <div
+                class="flex w-full h-2 bg-gray-200 rounded-full overflow-hidden dark:bg-neutral-700"
+                role="progressbar"
+                aria-valuenow=width
+                aria-valuemin="0"
+                aria-valuemax="100"
+            >
+                <div
+                    class=format!("flex flex-col justify-center rounded-full overflow-hidden {bar_class}")
+                    style=format!("width: {width}%")
+                ></div>
+            </div>
+            <div class="w-12 text-end">
+                <span class="text-sm text-gray-800 dark:text-white tabular-nums">
+                    {percent.map(|percent| format!("{percent}%")).unwrap_or_else(|| "—".to_string())}
+                </span>
+            </div>
+        </div>
+    }
+}
+
+async fn fetch_accounts(
+    auth: &AccessToken,
+    page: u32,
+    limit: u32,
+    filter: Option<String>,
+    sort: &str,
+    order: SortOrder,
+) -> http::Result<List<Principal>> {
+    HttpRequest::get("/api/principal")
+        .with_authorization(auth)
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .with_parameter("types", PrincipalType::Individual.id())
+        .with_parameter("sort", sort)
+        .with_parameter(
+            "order",
+            match order {
+                SortOrder::Ascending => "asc",
+                SortOrder::Descending => "desc",
+            },
+        )
+        .with_optional_parameter("filter", filter)
+        .send::<List<Principal>>()
+        .await
+}
+
+pub async fn fetch_usage(auth: &AccessToken, name: &str) -> http::Result<PrincipalUsage> {
+    HttpRequest::get(
+        UrlBuilder::new("/api/principal")
+            .with_subpath(name)
+            .with_subpath("usage")
+            .finish(),
+    )
+    .with_authorization(auth)
+    .send::<PrincipalUsage>()
+    .await
+}
+
+static SORT_KEYS: &[&str] = &["name", "quota", "usedQuota", "usage"];
+
+impl Builder<Schemas, ()> {
+    pub fn build_bulk_quota(self) -> Self {
+        self.new_schema("bulk-quota")
+            .new_field("quota")
+            .typ(Type::Size)
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,470 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{LocalStorage, Storage};
+use leptos::*;
+use leptos_router::use_navigate;
+use pwhash::sha512_crypt;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{button::Button, input::InputText, select::Select, FormElement, FormItem},
+        icon::{IconAdd, IconAdjustmentsHorizontal},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type},
+        url::UrlBuilder,
+        AccessToken, Permission,
+    },
+    pages::{
+        directory::{
+            dkim::{create_domain_keys, delete_domain_keys},
+            Principal, PrincipalType, PrincipalValue,
+        },
+        maybe_plural, FormatDateTime,
+    },
+};
+
+pub const SANDBOX_STORAGE_KEY: &str = "webadmin_sandboxes";
+const DEFAULT_PARENT: &str = "test";
+const DEFAULT_ACCOUNTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sandbox {
+    pub domain: String,
+    pub created: DateTime<Utc>,
+    pub accounts: Vec<SandboxAccount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxAccount {
+    pub name: String,
+    pub email: String,
+    // Only shown right after creation, never written to local storage
+    #[serde(skip)]
+    pub password: Option<String>,
+}
+
+pub fn stored_sandboxes() -> Vec<Sandbox> {
+    LocalStorage::get::<Vec<Sandbox>>(SANDBOX_STORAGE_KEY).unwrap_or_default()
+}
+
+fn store_sandboxes(sandboxes: &[Sandbox]) {
+    let result = if sandboxes.is_empty() {
+        LocalStorage::delete(SANDBOX_STORAGE_KEY);
+        Ok(())
+    } else {
+        LocalStorage::set(SANDBOX_STORAGE_KEY, sandboxes)
+    };
+    if let Err(err) = result {
+        log::error!("Failed to save sandboxes to local storage: {}", err);
+    }
+}
+
+// Sandboxes live under a reserved TLD by default so that nothing created
+// here can ever receive real mail. The accounts get random passwords that
+// are shown once, only the sandbox principals are kept in local storage.
+async fn create_sandbox(
+    auth: &AccessToken,
+    parent: &str,
+    num_accounts: usize,
+) -> http::Result<Sandbox> {
+    let token = random_string(6).to_lowercase();
+    let mut sandbox = Sandbox {
+        domain: format!("sandbox-{token}.{parent}"),
+        created: Utc::now(),
+        accounts: Vec::with_capacity(num_accounts),
+    };
+
+    let result = async {
+        create_principal(
+            auth,
+            &Principal {
+                typ: Some(PrincipalType::Domain),
+                name: PrincipalValue::String(sandbox.domain.clone()),
+                description: PrincipalValue::String("Sandbox domain".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        if auth
+            .permissions()
+            .has_access(Permission::DkimSignatureCreate)
+        {
+            create_domain_keys(auth, &sandbox.domain).await?;
+        }
+
+        for num in 1..=num_accounts {
+            let password = random_string(16);
+            let secret = sha512_crypt::hash(&password).unwrap();
+            let account = SandboxAccount {
+                name: format!("test{num}-{token}"),
+                email: format!("test{num}@{}", sandbox.domain),
+                password: Some(password),
+            };
+            create_principal(
+                auth,
+                &Principal {
+                    typ: Some(PrincipalType::Individual),
+                    name: PrincipalValue::String(account.name.clone()),
+                    description: PrincipalValue::String(format!("Sandbox test account {num}")),
+                    emails: PrincipalValue::StringList(vec![account.email.clone()]),
+                    secrets: PrincipalValue::StringList(vec![secret]),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            sandbox.accounts.push(account);
+        }
+
+        Ok(())
+    }
+    .await;
+
+    match result {
+        Ok(_) => Ok(sandbox),
+        Err(err) => {
+            // Do not leave half provisioned sandboxes behind
+            let _ = teardown_sandbox(auth, &sandbox).await;
+            Err(err)
+        }
+    }
+}
+
+async fn teardown_sandbox(auth: &AccessToken, sandbox: &Sandbox) -> http::Result<()> {
+    for account in &sandbox.accounts {
+        delete_principal(auth, &account.name).await?;
+    }
+    if auth
+        .permissions()
+        .has_access(Permission::DkimSignatureCreate)
+    {
+        delete_domain_keys(auth, &sandbox.domain).await?;
+    }
+    delete_principal(auth, &sandbox.domain).await
+}
+
+async fn create_principal(auth: &AccessToken, principal: &Principal) -> http::Result<()> {
+    HttpRequest::post("/api/principal")
+        .with_authorization(auth)
+        .with_body(principal)?
+        .send::<u32>()
+        .await
+        .map(|_| ())
+}
+
+async fn delete_principal(auth: &AccessToken, name: &str) -> http::Result<()> {
+    match HttpRequest::delete(("/api/principal", name))
+        .with_authorization(auth)
+        .send::<serde_json::Value>()
+        .await
+    {
+        Ok(_) | Err(http::Error::NotFound) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn random_string(len: usize) -> String {
+    thread_rng()
+        .sample_iter(Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[component]
+pub fn SandboxDomains() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let sandboxes = create_rw_signal(stored_sandboxes());
+    let show_options = create_rw_signal(false);
+    let (pending, set_pending) = create_signal(false);
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("sandbox")
+        .into_signal();
+
+    let create_sandbox_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let (parent, num_accounts) = data.with_untracked(|data| {
+            (
+                data.value::<String>("parent")
+                    .filter(|parent| !parent.is_empty())
+                    .unwrap_or_else(|| DEFAULT_PARENT.to_string()),
+                data.value::<usize>("accounts").unwrap_or(DEFAULT_ACCOUNTS),
+            )
+        });
+
+        async move {
+            set_pending.set(true);
+            let result = create_sandbox(&auth, &parent, num_accounts).await;
+            set_pending.set(false);
+
+            match result {
+                Ok(sandbox) => {
+                    alert.set(Alert::success(format!(
+                        "Sandbox {} created with {}.",
+                        sandbox.domain,
+                        maybe_plural(sandbox.accounts.len(), "test account", "test accounts")
+                    )));
+                    sandboxes.update(|sandboxes| {
+                        sandboxes.insert(0, sandbox);
+                        store_sandboxes(sandboxes);
+                    });
+                    show_options.set(false);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let teardown_action = create_action(move |sandbox: &Sandbox| {
+        let sandbox = sandbox.clone();
+        let auth = auth.get();
+
+        async move {
+            match teardown_sandbox(&auth, &sandbox).await {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "Sandbox {} was torn down.",
+                        sandbox.domain
+                    )));
+                    sandboxes.update(|sandboxes| {
+                        sandboxes.retain(|s| s.domain != sandbox.domain);
+                        store_sandboxes(sandboxes);
+                    });
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Sandbox domains"
+                subtitle="Throwaway domains with test accounts for experimenting with routing and filters"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Options"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            show_options.update(|show| *show = !*show);
+                        })
+                    >
+
+                        <IconAdjustmentsHorizontal/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if pending.get() {
+                                "Creating...".to_string()
+                            } else {
+                                "Create sandbox".to_string()
+                            }
+                        })
+
+                        color=Color::Blue
+                        on_click=Callback::new(move |_| {
+                            if !pending.get()
+                                && data.try_update(|data| data.validate_form()).unwrap_or_default()
+                            {
+                                create_sandbox_action.dispatch(());
+                            }
+                        })
+                    >
+
+                        <IconAdd size=16 attr:class="flex-shrink-0 size-3"/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Show when=move || show_options.get()>
+                    <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700 space-y-3">
+                        <FormItem
+                            label="Parent domain"
+                            tooltip="Sandboxes are created as subdomains of this domain"
+                        >
+                            <InputText
+                                placeholder=DEFAULT_PARENT
+                                element=FormElement::new("parent", data)
+                            />
+                        </FormItem>
+                        <FormItem label="Test accounts">
+                            <Select element=FormElement::new("accounts", data)/>
+                        </FormItem>
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Gray
+                                on_click=move |_| {
+                                    show_options.set(false);
+                                }
+                            />
+
+                        </div>
+                    </div>
+                </Show>
+
+                {move || {
+                    let sandboxes = sandboxes.get();
+                    if !sandboxes.is_empty() {
+                        view! {
+                            <ColumnList headers=vec![
+                                "Domain".to_string(),
+                                "Test accounts".to_string(),
+                                "Created".to_string(),
+                                "".to_string(),
+                            ]>
+
+                                <For
+                                    each=move || sandboxes.clone()
+                                    key=|sandbox| sandbox.domain.clone()
+                                    let:sandbox
+                                >
+                                    <SandboxItem
+                                        sandbox
+                                        on_teardown=Callback::new(move |sandbox: Sandbox| {
+                                            modal
+                                                .set(
+                                                    Modal::with_title("Confirm teardown")
+                                                        .with_message(
+                                                            format!(
+                                                                "Are you sure you want to delete {} and its test accounts? This action cannot be undone.",
+                                                                sandbox.domain,
+                                                            ),
+                                                        )
+                                                        .with_button("Tear down")
+                                                        .with_dangerous_callback(move || {
+                                                            teardown_action.dispatch(sandbox.clone());
+                                                        }),
+                                                );
+                                        })
+                                    />
+
+                                </For>
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    } else {
+                        view! {
+                            <ZeroResults
+                                title="No sandboxes"
+                                subtitle="Create a sandbox domain to experiment safely without touching real accounts."
+                            />
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn SandboxItem(sandbox: Sandbox, on_teardown: Callback<Sandbox>) -> impl IntoView {
+    let dns_url = UrlBuilder::new("/manage/dns")
+        .with_subpath(&sandbox.domain)
+        .with_subpath("view")
+        .finish();
+    let accounts = sandbox
+        .accounts
+        .iter()
+        .map(|account| {
+            view! {
+                <span class="block text-sm font-mono text-gray-800 dark:text-gray-200">
+                    {account.email.clone()}
+                    {account
+                        .password
+                        .clone()
+                        .map(|password| {
+                            view! { <span class="text-gray-500">" / " {password}</span> }
+                        })}
+                </span>
+            }
+        })
+        .collect_view();
+    let created = sandbox.created.format_date_time();
+    let domain = sandbox.domain.clone();
+    let sandbox = store_value(sandbox);
+
+    view! {
+        <tr>
+            <ListTextItem>{domain}</ListTextItem>
+            <ListItem subclass="px-6 py-3">{accounts}</ListItem>
+            <ListTextItem>{created}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex gap-x-3">
+                    <a
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium"
+                        href=dns_url
+                    >
+                        DNS records
+                    </a>
+                    <button
+                        type="button"
+                        class="inline-flex items-center gap-x-1 text-sm text-red-600 decoration-2 hover:underline font-medium"
+                        on:click=move |_| on_teardown.call(sandbox.get_value())
+                    >
+                        Tear down
+                    </button>
+                </div>
+            </ListItem>
+        </tr>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_sandbox(self) -> Self {
+        self.new_schema("sandbox")
+            .new_field("parent")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim, Transformer::Lowercase], [])
+            .build()
+            .new_field("accounts")
+            .default("3")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[("1", "1"), ("3", "3"), ("5", "5")]),
+            })
+            .build()
+            .build()
+    }
+}
@@ -13,7 +13,6 @@ use ahash::AHashMap;
 use chrono::{DateTime, Duration, SecondsFormat, Utc};
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 use codee::string::JsonSerdeCodec;
-use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_chartistry::*;
 use leptos_meta::Style;
@@ -36,10 +35,16 @@ use crate::{
         report::ReportView,
     },
     core::{
+        format::format_bytes,
+        health::use_health,
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
     },
+    pages::enterprise::{
+        health::{Aggregate, ThresholdEditor, Widget, WidgetUnit},
+        storage::StoreHealthPanel,
+    },
 };
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -63,6 +68,7 @@ enum Period {
     Last7Days,
     Last30Days,
     Last90Days,
+    LastYear,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -487,76 +493,42 @@ pub fn Dashboard() -> impl IntoView {
 
                         Last 90 days
                     </a>
+                    <a
+                        class="flex items-center gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:outline-none focus:bg-gray-100 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300 dark:focus:bg-neutral-700"
+                        href="#"
+                        on:click=move |_| {
+                            period.set(Period::LastYear);
+                            show_period.set(false);
+                        }
+                    >
+
+                        Last year
+                    </a>
                 </div>
             </div>
         </div>
         <Show when=move || { section.get() == Section::Overview }>
             <CardSimple>
-                <CardSimpleItem
-                    title="Total Users"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["user.count"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="user-count" summary=live_summary>
                     <IconUserGroup attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Total Domains"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["domain.count"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="domain-count" summary=live_summary>
                     <IconBuildingOffice attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Server Memory"
-                    contents=Signal::derive(move || {
-                        format_size(live_summary.get().sum(&["server.memory"]) as u64, DECIMAL)
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="server-memory" summary=live_summary>
                     <IconServer attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
             <CardSimple>
-                <CardSimpleItem
-                    title="Received Messages"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["queue.queue-message"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="messages-received" summary=summary>
                     <IconInboxArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Sent Messages"
-                    contents=Signal::derive(move || {
-                        summary
-                            .get()
-                            .sum(
-                                &[
-                                    "queue.queue-message-authenticated",
-                                    "queue.queue-dsn",
-                                    "queue.queue-report",
-                                ],
-                            )
-                            .to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="messages-sent-total" summary=summary>
                     <IconPaperAirplane attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Pending Messages"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["queue.count"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="queue-size" summary=live_summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
             <DashboardChart
@@ -568,80 +540,32 @@ pub fn Dashboard() -> impl IntoView {
         </Show>
         <Show when=move || { section.get() == Section::Network }>
             <CardSimple>
-                <CardSimpleItem
-                    title="SMTP active"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["smtp.active-connections"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="smtp-active" summary=live_summary>
                     <IconPhoneArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="IMAP active"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["imap.active-connections"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="imap-active" summary=live_summary>
                     <IconPhoneArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="POP3 active"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["pop3.active-connections"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="pop3-active" summary=live_summary>
                     <IconPhoneArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="HTTP active"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["http.active-connections"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="http-active" summary=live_summary>
                     <IconPhoneArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
             <CardSimple>
-                <CardSimpleItem
-                    title="SMTP total"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["smtp.connection-start"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="smtp-total" summary=summary>
                     <IconPhone attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="IMAP total"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["imap.connection-start"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="imap-total" summary=summary>
                     <IconPhone attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="POP3 total"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["pop3.connection-start"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="pop3-total" summary=summary>
                     <IconPhone attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="HTTP total"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["http.connection-start"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="http-total" summary=summary>
                     <IconPhone attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
             <DashboardChart
@@ -658,75 +582,26 @@ pub fn Dashboard() -> impl IntoView {
         </Show>
         <Show when=move || { section.get() == Section::Security }>
             <CardSimple>
-                <CardSimpleItem
-                    title="Threats blocked"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["security.ip-blocked"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="threats-blocked" summary=summary>
                     <IconNoSymbol attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="IPs banned"
-                    contents=Signal::derive(move || {
-                        summary
-                            .get()
-                            .sum(
-                                &[
-                                    "security.authentication-ban",
-                                    "security.abuse-ban",
-                                    "security.scan-ban",
-                                    "security.loiter-ban",
-                                ],
-                            )
-                            .to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="ips-banned" summary=summary>
                     <IconShieldExclamation attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Auth Failures"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["auth.failed"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="auth-failures" summary=summary>
                     <IconExclamationCircle attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
             <CardSimple>
-                <CardSimpleItem
-                    title="Spam blocked"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["message-ingest.spam"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="spam-blocked" summary=summary>
                     <IconTrash attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="DMARC Warnings"
-                    contents=Signal::derive(move || {
-                        summary
-                            .get()
-                            .sum(&["incoming-report.dmarc-report-with-warnings"])
-                            .to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="dmarc-warnings" summary=summary>
                     <IconExclamationTriangle attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="TLS Warnings"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["incoming-report.tls-report-with-warnings"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="tls-warnings" summary=summary>
                     <IconExclamationTriangle attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
             <DashboardChart
@@ -739,81 +614,33 @@ pub fn Dashboard() -> impl IntoView {
 
         <Show when=move || { section.get() == Section::Delivery }>
             <CardSimple>
-                <CardSimpleItem
-                    title="Queued Messages"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["queue.count"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="queue-size" summary=live_summary>
                     <IconQueueList attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Active Sessions"
-                    contents=Signal::derive(move || {
-                        live_summary.get().sum(&["delivery.active-connections"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="delivery-sessions" summary=live_summary>
                     <IconSignal attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Session Time"
-                    contents=Signal::derive(move || {
-                        duration(summary.get().average(&["delivery.attempt-time"]))
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="session-time" summary=summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Delivery Time"
-                    contents=Signal::derive(move || {
-                        duration(summary.get().average(&["delivery.total-time"]))
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="delivery-time" summary=summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
             <CardSimple>
-                <CardSimpleItem
-                    title="Messages Received"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["queue.queue-message"]).to_string()
-                    })
-                >
-
+                <DashboardWidget id="messages-received" summary=summary>
                     <IconInboxArrowDown attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Messages Sent"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["queue.queue-message-authenticated"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="messages-sent" summary=summary>
                     <IconPaperAirplane attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="DSN Sent"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["queue.queue-dsn"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="dsn-sent" summary=summary>
                     <IconDocumentText attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Reports Sent"
-                    contents=Signal::derive(move || {
-                        summary.get().sum(&["queue.queue-report"]).to_string()
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="reports-sent" summary=summary>
                     <IconDocumentChartBar attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
             <DashboardChart
@@ -833,44 +660,22 @@ pub fn Dashboard() -> impl IntoView {
 
         <Show when=move || { section.get() == Section::Performance }>
             <CardSimple>
-                <CardSimpleItem
-                    title="Ingestion Time"
-                    contents=Signal::derive(move || {
-                        duration(summary.get().average(&["message-ingest.time"]))
-                    })
-                >
-
+                <DashboardWidget id="ingestion-time" summary=summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Indexing Time"
-                    contents=Signal::derive(move || {
-                        duration(summary.get().average(&["message-ingest.index-time"]))
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="indexing-time" summary=summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="DNS Lookup Time"
-                    contents=Signal::derive(move || {
-                        duration(summary.get().average(&["dns.lookup-time"]))
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="dns-lookup-time" summary=summary>
                     <IconClock attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
-                <CardSimpleItem
-                    title="Server Memory"
-                    contents=Signal::derive(move || {
-                        format_size(live_summary.get().sum(&["server.memory"]) as u64, DECIMAL)
-                    })
-                >
-
+                </DashboardWidget>
+                <DashboardWidget id="server-memory" summary=live_summary>
                     <IconServer attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
-                </CardSimpleItem>
+                </DashboardWidget>
             </CardSimple>
 
+            <StoreHealthPanel/>
+
             <DashboardChart
                 title="Database performance"
                 labels=&["ingest", "index"]
@@ -881,6 +686,30 @@ pub fn Dashboard() -> impl IntoView {
     }
 }
 
+#[component]
+fn DashboardWidget(
+    id: &'static str,
+    summary: RwSignal<MetricSummaries>,
+    children: Children,
+) -> impl IntoView {
+    let health = use_health();
+    let widget = Widget::get(id).expect("unknown dashboard widget");
+    let value = create_memo(move |_| summary.with(|summary| summary.value(widget)));
+
+    view! {
+        <div class="relative">
+            <CardSimpleItem
+                title=widget.title
+                contents=Signal::derive(move || format_value(widget, value.get()))
+                level=Signal::derive(move || health.level(widget.id, widget.scaled(value.get())))
+            >
+                {children()}
+            </CardSimpleItem>
+            <ThresholdEditor widget/>
+        </div>
+    }
+}
+
 #[component]
 fn DashboardChart(
     title: impl Into<String> + 'static,
@@ -935,6 +764,7 @@ impl Bucket {
             Period::Last7Days => vec![DataPoint::default(); 7],
             Period::Last30Days => vec![DataPoint::default(); 30],
             Period::Last90Days => vec![DataPoint::default(); 90],
+            Period::LastYear => vec![DataPoint::default(); 52],
         };
 
         for (x, item) in bucket.iter_mut().enumerate() {
@@ -987,6 +817,8 @@ impl Bucket {
             Period::Last7Days if num_days < 7 => 6 - num_days,
             Period::Last30Days if num_days < 30 => 29 - num_days,
             Period::Last90Days if num_days < 90 => 89 - num_days,
+            // Weekly buckets, a data point per day would not fit in the chart
+            Period::LastYear if num_days < 364 => 51 - num_days / 7,
             _ => return,
         };
 
@@ -1037,6 +869,7 @@ impl Period {
             Period::Last7Days => Duration::days(7),
             Period::Last30Days => Duration::days(30),
             Period::Last90Days => Duration::days(90),
+            Period::LastYear => Duration::days(364),
         };
 
         (Utc::now() - diff - Duration::hours(1) - Duration::seconds(15))
@@ -1045,7 +878,7 @@ impl Period {
 }
 
 impl Metric {
-    fn id(&self) -> &str {
+    pub fn id(&self) -> &str {
         match self {
             Metric::Counter { id, .. } => id,
             Metric::Histogram { id, .. } => id,
@@ -1073,6 +906,13 @@ impl MetricSummaries {
         }
     }
 
+    fn value(&self, widget: &Widget) -> u128 {
+        match widget.aggregate {
+            Aggregate::Sum => self.sum(widget.metrics),
+            Aggregate::Average => self.average(widget.metrics),
+        }
+    }
+
     fn sum(&self, ids: &[&str]) -> u128 {
         let mut sum = 0;
 
@@ -1086,6 +926,14 @@ impl MetricSummaries {
     }
 }
 
+fn format_value(widget: &Widget, value: u128) -> String {
+    match widget.unit {
+        WidgetUnit::Count => value.to_string(),
+        WidgetUnit::Bytes => format_bytes(value as u64),
+        WidgetUnit::Millis => duration(value),
+    }
+}
+
 fn duration(time: u128) -> String {
     HumanTime::from(Duration::from_std(std::time::Duration::from_millis(time as u64)).unwrap_or_default())
         .to_text_en(Accuracy::Precise, Tense::Present)
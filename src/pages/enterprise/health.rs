@@ -0,0 +1,685 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: LicenseRef-SEL
+ *
+ * This file is subject to the Stalwart Enterprise License Agreement (SEL) and
+ * is not open source software. It must not be modified or distributed without
+ * explicit permission from Stalwart Labs Ltd.
+ * Unauthorized use, modification, or distribution is strictly prohibited.
+ */
+
+use std::time::Duration as StdDuration;
+
+use ahash::AHashMap;
+use chrono::{Duration, SecondsFormat, Utc};
+use leptos::{html::Div, *};
+use leptos_router::use_navigate;
+use leptos_use::on_click_outside;
+
+use crate::{
+    components::{
+        icon::{IconAdjustmentsHorizontal, IconHeart},
+        messages::alert::{use_alerts, Alert},
+    },
+    core::{
+        health::{fetch_thresholds, save_threshold, use_health, HealthLevel, Threshold},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+        Permission,
+    },
+    pages::enterprise::dashboard::Metric,
+};
+
+const HEALTH_INTERVAL: StdDuration = StdDuration::from_secs(60);
+// The header indicator evaluates thresholds over this window
+const HEALTH_WINDOW_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetSource {
+    // Gauges streamed by the live telemetry endpoint
+    Live,
+    // Counters and histograms over the selected period
+    History,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Average,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetUnit {
+    Count,
+    Bytes,
+    Millis,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Widget {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub metrics: &'static [&'static str],
+    pub source: WidgetSource,
+    pub aggregate: Aggregate,
+    pub unit: WidgetUnit,
+}
+
+pub static WIDGETS: &[Widget] = &[
+    widget(
+        "user-count",
+        "Total Users",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["user.count"],
+    ),
+    widget(
+        "domain-count",
+        "Total Domains",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["domain.count"],
+    ),
+    widget(
+        "server-memory",
+        "Server Memory",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Bytes,
+        &["server.memory"],
+    ),
+    widget(
+        "queue-size",
+        "Queued Messages",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["queue.count"],
+    ),
+    widget(
+        "messages-received",
+        "Received Messages",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["queue.queue-message"],
+    ),
+    widget(
+        "messages-sent-total",
+        "Sent Messages",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &[
+            "queue.queue-message-authenticated",
+            "queue.queue-dsn",
+            "queue.queue-report",
+        ],
+    ),
+    widget(
+        "smtp-active",
+        "SMTP active",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["smtp.active-connections"],
+    ),
+    widget(
+        "imap-active",
+        "IMAP active",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["imap.active-connections"],
+    ),
+    widget(
+        "pop3-active",
+        "POP3 active",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["pop3.active-connections"],
+    ),
+    widget(
+        "http-active",
+        "HTTP active",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["http.active-connections"],
+    ),
+    widget(
+        "smtp-total",
+        "SMTP total",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["smtp.connection-start"],
+    ),
+    widget(
+        "imap-total",
+        "IMAP total",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["imap.connection-start"],
+    ),
+    widget(
+        "pop3-total",
+        "POP3 total",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["pop3.connection-start"],
+    ),
+    widget(
+        "http-total",
+        "HTTP total",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["http.connection-start"],
+    ),
+    widget(
+        "threats-blocked",
+        "Threats blocked",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["security.ip-blocked"],
+    ),
+    widget(
+        "ips-banned",
+        "IPs banned",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &[
+            "security.authentication-ban",
+            "security.abuse-ban",
+            "security.scan-ban",
+            "security.loiter-ban",
+        ],
+    ),
+    widget(
+        "auth-failures",
+        "Auth Failures",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["auth.failed"],
+    ),
+    widget(
+        "spam-blocked",
+        "Spam blocked",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["message-ingest.spam"],
+    ),
+    widget(
+        "dmarc-warnings",
+        "DMARC Warnings",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["incoming-report.dmarc-report-with-warnings"],
+    ),
+    widget(
+        "tls-warnings",
+        "TLS Warnings",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["incoming-report.tls-report-with-warnings"],
+    ),
+    widget(
+        "delivery-sessions",
+        "Active Sessions",
+        WidgetSource::Live,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["delivery.active-connections"],
+    ),
+    widget(
+        "session-time",
+        "Session Time",
+        WidgetSource::History,
+        Aggregate::Average,
+        WidgetUnit::Millis,
+        &["delivery.attempt-time"],
+    ),
+    widget(
+        "delivery-time",
+        "Delivery Time",
+        WidgetSource::History,
+        Aggregate::Average,
+        WidgetUnit::Millis,
+        &["delivery.total-time"],
+    ),
+    widget(
+        "messages-sent",
+        "Messages Sent",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["queue.queue-message-authenticated"],
+    ),
+    widget(
+        "dsn-sent",
+        "DSN Sent",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["queue.queue-dsn"],
+    ),
+    widget(
+        "reports-sent",
+        "Reports Sent",
+        WidgetSource::History,
+        Aggregate::Sum,
+        WidgetUnit::Count,
+        &["queue.queue-report"],
+    ),
+    widget(
+        "ingestion-time",
+        "Ingestion Time",
+        WidgetSource::History,
+        Aggregate::Average,
+        WidgetUnit::Millis,
+        &["message-ingest.time"],
+    ),
+    widget(
+        "indexing-time",
+        "Indexing Time",
+        WidgetSource::History,
+        Aggregate::Average,
+        WidgetUnit::Millis,
+        &["message-ingest.index-time"],
+    ),
+    widget(
+        "dns-lookup-time",
+        "DNS Lookup Time",
+        WidgetSource::History,
+        Aggregate::Average,
+        WidgetUnit::Millis,
+        &["dns.lookup-time"],
+    ),
+];
+
+// Evaluates the widgets that have thresholds on every page and shows the
+// worst level in the header.
+#[component]
+pub fn HealthIndicator() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let health = use_health();
+    let poller = use_polling(HEALTH_INTERVAL);
+    let is_open = create_rw_signal(false);
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    // Thresholds are shared by all administrators, reload them whenever a
+    // different user logs in.
+    let thresholds = create_local_resource(
+        move || auth.with(|auth| auth.username.to_string()),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if !auth.permissions().has_access(Permission::SettingsList) {
+                    return Ok(Default::default());
+                }
+                fetch_thresholds(&auth).await
+            }
+        },
+    );
+
+    create_effect(move |_| match thresholds.get() {
+        Some(Ok(thresholds)) => health.set_thresholds(thresholds),
+        Some(Err(http::Error::Unauthorized)) => use_navigate()("/login", Default::default()),
+        Some(Err(err)) => alert.set(Alert::from(err)),
+        None => {}
+    });
+
+    let metrics = create_local_resource(
+        move || (health.thresholds.with(|t| t.is_empty()), poller.tick()),
+        move |(is_empty, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if is_empty {
+                    return None;
+                }
+                match HttpRequest::get("/api/telemetry/metrics")
+                    .with_authorization(&auth)
+                    .with_parameter(
+                        "after",
+                        (Utc::now() - Duration::minutes(HEALTH_WINDOW_MINUTES))
+                            .to_rfc3339_opts(SecondsFormat::Secs, true),
+                    )
+                    .send::<Vec<Metric>>()
+                    .await
+                {
+                    Ok(metrics) => Some(metrics),
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Err(err) => {
+                        poller.pause();
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                }
+            }
+        },
+    );
+
+    create_effect(move |_| {
+        let Some(metrics) = metrics.get().flatten() else {
+            return;
+        };
+        let levels = health.thresholds.with(|thresholds| {
+            WIDGETS
+                .iter()
+                .filter_map(|widget| {
+                    let threshold = thresholds.get(widget.id)?;
+                    let value = widget.scaled(widget.evaluate(&metrics));
+                    Some((widget.id.to_string(), threshold.level(value)))
+                })
+                .collect::<AHashMap<_, _>>()
+        });
+        health.levels.set(levels);
+    });
+
+    let level = Signal::derive(move || health.overall());
+    let alerts = Signal::derive(move || {
+        health.levels.with(|levels| {
+            let mut alerts = levels
+                .iter()
+                .filter(|(_, level)| **level != HealthLevel::Normal)
+                .filter_map(|(id, level)| Some((Widget::get(id)?, *level)))
+                .collect::<Vec<_>>();
+            alerts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.title.cmp(b.0.title)));
+            alerts
+        })
+    });
+
+    view! {
+        <Show when=move || !health.levels.with(|levels| levels.is_empty())>
+            <div class="relative inline-flex" node_ref=target>
+                <button
+                    type="button"
+                    class="relative w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 dark:text-white dark:hover:bg-gray-700"
+                    title=move || level.get().label()
+                    aria-haspopup="true"
+                    aria-expanded=move || is_open.get().to_string()
+                    on:click=move |_| is_open.update(|open| *open = !*open)
+                >
+
+                    <IconHeart/>
+                    <span
+                        class="absolute top-1.5 end-1.5 size-2.5 rounded-full"
+                        class:bg-green-500=move || level.get() == HealthLevel::Normal
+                        class:bg-yellow-400=move || level.get() == HealthLevel::Warning
+                        class:bg-red-500=move || level.get() == HealthLevel::Critical
+                    ></span>
+                </button>
+                <div
+                    class="absolute end-0 top-full mt-2 z-10 min-w-64 bg-white shadow-md rounded-lg p-2 dark:bg-gray-800 dark:border dark:border-gray-700"
+                    class:hidden=move || !is_open.get()
+                >
+                    <span class="block py-2 px-3 text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                        {move || format!("Server health: {}", level.get().label())}
+                    </span>
+                    {move || {
+                        let alerts = alerts.get();
+                        if alerts.is_empty() {
+                            view! {
+                                <p class="py-2 px-3 text-sm text-gray-600 dark:text-gray-400">
+                                    All monitored metrics are within their thresholds.
+                                </p>
+                            }
+                                .into_view()
+                        } else {
+                            alerts
+                                .into_iter()
+                                .map(|(widget, level)| {
+                                    view! {
+                                        <div class="flex justify-between items-center gap-x-3 py-2 px-3 text-sm text-gray-800 dark:text-gray-300">
+                                            <span>{widget.title}</span>
+                                            <span
+                                                class="text-xs font-semibold"
+                                                class:text-yellow-600=level == HealthLevel::Warning
+                                                class:text-red-600=level == HealthLevel::Critical
+                                            >
+                                                {level.label()}
+                                            </span>
+                                        </div>
+                                    }
+                                })
+                                .collect_view()
+                        }
+                    }}
+                    <a
+                        class="block py-2 px-3 rounded-lg text-sm text-blue-600 hover:bg-gray-100 dark:text-blue-500 dark:hover:bg-gray-700"
+                        href="/manage/dashboard/overview"
+                        on:click=move |_| is_open.set(false)
+                    >
+                        Open dashboard
+                    </a>
+                </div>
+            </div>
+        </Show>
+    }
+}
+
+#[component]
+pub fn ThresholdEditor(widget: &'static Widget) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let health = use_health();
+    let is_open = create_rw_signal(false);
+    let warning = create_rw_signal(String::new());
+    let critical = create_rw_signal(String::new());
+    let target: NodeRef<Div> = create_node_ref::<Div>();
+    on_cleanup(on_click_outside(target, move |_| is_open.set(false)));
+
+    let open = move |_| {
+        let threshold = health
+            .thresholds
+            .with_untracked(|thresholds| thresholds.get(widget.id).copied())
+            .unwrap_or_default();
+        warning.set(threshold.warning.map(|v| v.to_string()).unwrap_or_default());
+        critical.set(
+            threshold
+                .critical
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        );
+        is_open.update(|open| *open = !*open);
+    };
+    let save = create_action(move |threshold: &Threshold| {
+        let threshold = *threshold;
+        let auth = auth.get();
+
+        async move {
+            match save_threshold(&auth, widget.id, threshold).await {
+                Ok(_) => {
+                    health.set_threshold(widget.id, threshold);
+                    is_open.set(false);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let can_edit = move || {
+        auth.get()
+            .permissions()
+            .has_access(Permission::SettingsUpdate)
+    };
+
+    view! {
+        <div class="absolute top-2 end-2" node_ref=target class:hidden=move || !can_edit()>
+            <button
+                type="button"
+                class="p-1 rounded-md text-gray-400 hover:text-gray-600 hover:bg-gray-100 dark:hover:text-gray-300 dark:hover:bg-gray-700"
+                title="Thresholds"
+                on:click=open
+            >
+                <IconAdjustmentsHorizontal attr:class="size-4"/>
+            </button>
+            <div
+                class="absolute end-0 top-full mt-1 z-10 w-64 p-3 space-y-2 bg-white shadow-md rounded-lg dark:bg-gray-800 dark:border dark:border-gray-700"
+                class:hidden=move || !is_open.get()
+            >
+                <span class="block text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                    {format!("Thresholds ({})", widget.unit.label())}
+                </span>
+                <label class="block text-sm text-gray-800 dark:text-gray-300">
+                    Warning
+                    <input
+                        type="number"
+                        min="0"
+                        class="mt-1 py-1.5 px-2 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                        prop:value=move || warning.get()
+                        on:input=move |ev| warning.set(event_target_value(&ev))
+                    />
+                </label>
+                <label class="block text-sm text-gray-800 dark:text-gray-300">
+                    Critical
+                    <input
+                        type="number"
+                        min="0"
+                        class="mt-1 py-1.5 px-2 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                        prop:value=move || critical.get()
+                        on:input=move |ev| critical.set(event_target_value(&ev))
+                    />
+                </label>
+                <p class="text-xs text-gray-500">
+                    The header indicator checks these values over the last hour.
+                </p>
+                <div class="flex justify-end gap-x-2">
+                    <button
+                        type="button"
+                        class="py-1.5 px-2.5 text-sm rounded-lg border border-gray-200 text-gray-800 hover:bg-gray-50 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                        on:click=move |_| save.dispatch(Threshold::default())
+                    >
+                        Clear
+                    </button>
+                    <button
+                        type="button"
+                        class="py-1.5 px-2.5 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700"
+                        on:click=move |_| {
+                            save.dispatch(Threshold {
+                                warning: warning.get_untracked().trim().parse().ok(),
+                                critical: critical.get_untracked().trim().parse().ok(),
+                            })
+                        }
+                    >
+                        Save
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+const fn widget(
+    id: &'static str,
+    title: &'static str,
+    source: WidgetSource,
+    aggregate: Aggregate,
+    unit: WidgetUnit,
+    metrics: &'static [&'static str],
+) -> Widget {
+    Widget {
+        id,
+        title,
+        metrics,
+        source,
+        aggregate,
+        unit,
+    }
+}
+
+impl Widget {
+    pub fn get(id: &str) -> Option<&'static Widget> {
+        WIDGETS.iter().find(|widget| widget.id == id)
+    }
+
+    // Converts a raw reading to the unit thresholds are entered in
+    pub fn scaled(&self, value: u128) -> f64 {
+        match self.unit {
+            WidgetUnit::Count => value as f64,
+            WidgetUnit::Bytes => value as f64 / 1_000_000.0,
+            WidgetUnit::Millis => value as f64 / 1000.0,
+        }
+    }
+
+    // Gauges use their latest reading, counters and histograms are
+    // aggregated over all readings.
+    fn evaluate(&self, metrics: &[Metric]) -> u128 {
+        let mut latest = AHashMap::new();
+        let mut sum = 0u128;
+        let mut count = 0u128;
+
+        for metric in metrics
+            .iter()
+            .filter(|metric| self.metrics.contains(&metric.id()))
+        {
+            match metric {
+                Metric::Gauge {
+                    id,
+                    timestamp,
+                    value,
+                } => {
+                    let entry = latest.entry(id.as_str()).or_insert((*timestamp, *value));
+                    if *timestamp >= entry.0 {
+                        *entry = (*timestamp, *value);
+                    }
+                }
+                Metric::Counter { value, .. } => {
+                    sum = sum.saturating_add(*value as u128);
+                    count += 1;
+                }
+                Metric::Histogram {
+                    sum: total,
+                    count: num,
+                    ..
+                } => {
+                    sum = sum.saturating_add(*total as u128);
+                    count += *num as u128;
+                }
+            }
+        }
+
+        if self.source == WidgetSource::Live {
+            latest.values().map(|(_, value)| *value as u128).sum()
+        } else if self.aggregate == Aggregate::Average {
+            sum.checked_div(count).unwrap_or_default()
+        } else {
+            sum
+        }
+    }
+}
+
+impl WidgetUnit {
+    fn label(&self) -> &'static str {
+        match self {
+            WidgetUnit::Count => "count",
+            WidgetUnit::Bytes => "MB",
+            WidgetUnit::Millis => "seconds",
+        }
+    }
+}
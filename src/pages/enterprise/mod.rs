@@ -10,5 +10,8 @@
  */
 
 pub mod dashboard;
+pub mod health;
+pub mod monitor;
+pub mod storage;
 pub mod tracing;
 pub mod undelete;
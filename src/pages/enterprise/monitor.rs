@@ -0,0 +1,362 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: LicenseRef-SEL
+ *
+ * This file is subject to the Stalwart Enterprise License Agreement (SEL) and
+ * is not open source software. It must not be modified or distributed without
+ * explicit permission from Stalwart Labs Ltd.
+ * Unauthorized use, modification, or distribution is strictly prohibited.
+ */
+
+use chrono::{Duration, Local, SecondsFormat, Utc};
+use leptos::*;
+use leptos_meta::Style;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        chart::{TimeSeriesChart, TimeSeriesPoint},
+        icon::IconThreeDots,
+        messages::alert::{use_alerts, Alert, Alerts},
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+    },
+    pages::enterprise::dashboard::Metric,
+};
+
+const NUM_BUCKETS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum Window {
+    Last15Minutes,
+    LastHour,
+    Last6Hours,
+    LastDay,
+}
+
+#[component]
+pub fn LiveMonitor() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let show_window = RwSignal::new(false);
+    let window = RwSignal::new(Window::LastHour);
+    let poller = use_polling(Signal::derive(move || window.get().refresh_interval()));
+
+    let metrics = create_resource(
+        move || (window.get(), poller.tick()),
+        move |(window, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/telemetry/metrics")
+                    .with_authorization(&auth)
+                    .with_parameter("after", window.as_timestamp())
+                    .send::<Vec<Metric>>()
+                    .await
+                    .map(|metrics| (metrics, window))
+            }
+        },
+    );
+
+    let throughput = RwSignal::new(Vec::<TimeSeriesPoint>::new());
+    let queue_size = RwSignal::new(Vec::<TimeSeriesPoint>::new());
+    let auth_failures = RwSignal::new(Vec::<TimeSeriesPoint>::new());
+    let connections = RwSignal::new(Vec::<TimeSeriesPoint>::new());
+    let memory_usage = RwSignal::new(Vec::<TimeSeriesPoint>::new());
+
+    create_effect(move |_| match metrics.get() {
+        Some(Ok((metrics, window))) => {
+            throughput.set(
+                TimeBucket::create(window, 2)
+                    .add_readings(
+                        &metrics,
+                        &[
+                            &["queue.queue-message"],
+                            &[
+                                "queue.queue-message-authenticated",
+                                "queue.queue-dsn",
+                                "queue.queue-report",
+                            ],
+                        ],
+                    )
+                    .finish_sum(),
+            );
+            queue_size.set(
+                TimeBucket::create(window, 1)
+                    .add_readings(&metrics, &[&["queue.count"]])
+                    .finish_avg(1.0),
+            );
+            auth_failures.set(
+                TimeBucket::create(window, 2)
+                    .add_readings(
+                        &metrics,
+                        &[&["auth.failed"], &["security.authentication-ban"]],
+                    )
+                    .finish_sum(),
+            );
+            connections.set(
+                TimeBucket::create(window, 3)
+                    .add_readings(
+                        &metrics,
+                        &[
+                            &["smtp.active-connections"],
+                            &["imap.active-connections", "pop3.active-connections"],
+                            &["http.active-connections"],
+                        ],
+                    )
+                    .finish_avg(1.0),
+            );
+            memory_usage.set(
+                TimeBucket::create(window, 1)
+                    .add_readings(&metrics, &[&["server.memory"]])
+                    .finish_avg((1024 * 1024) as f64),
+            );
+        }
+        Some(Err(http::Error::Unauthorized)) => {
+            use_navigate()("/login", Default::default());
+        }
+        Some(Err(err)) => {
+            poller.pause();
+            alert.set(Alert::from(err));
+        }
+        None => {}
+    });
+
+    view! {
+        <Style>
+            "
+            .dash-theme ._chartistry_rotated_label {
+                font-family: Inter, sans-serif;
+            }
+            .dash-theme ._chartistry_snippet {
+                font-family: Inter, sans-serif;
+            }
+            .dash-theme ._chartistry_tick_label {
+                font-family: Inter, sans-serif;
+            }
+            "
+        </Style>
+
+        <Alerts/>
+
+        <div class="max-w-[85rem] px-4 sm:px-6 lg:px-8 mx-auto flex justify-end items-center gap-x-2">
+            <span class="text-sm text-gray-500 dark:text-neutral-400">
+                {move || {
+                    if poller.is_paused() {
+                        "Paused".to_string()
+                    } else {
+                        format!(
+                            "{}, refreshing every {}s",
+                            window.get().label(),
+                            window.get().refresh_interval().as_secs(),
+                        )
+                    }
+                }}
+
+            </span>
+            <button
+                type="button"
+                class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                on:click=move |_| {
+                    if poller.is_paused() {
+                        poller.resume();
+                        poller.refresh();
+                    } else {
+                        poller.pause();
+                    }
+                }
+            >
+
+                {move || if poller.is_paused() { "Resume" } else { "Pause" }}
+            </button>
+            <div class="m-1 hs-dropdown [--trigger:hover] relative inline-flex">
+                <button
+                    type="button"
+                    class="hs-dropdown-toggle flex justify-center items-center size-9 text-sm font-semibold rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 focus:outline-none focus:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-neutral-900 dark:border-neutral-700 dark:text-white dark:hover:bg-neutral-800 dark:focus:bg-neutral-800"
+                    aria-haspopup="menu"
+                    aria-expanded="false"
+                    aria-label="Dropdown"
+                    on:click=move |_| {
+                        show_window
+                            .update(|v| {
+                                *v = !*v;
+                            });
+                    }
+                >
+
+                    <IconThreeDots attr:class="flex-none size-4 text-gray-600 dark:text-neutral-500"/>
+                </button>
+
+                <div
+                    class=move || {
+                        if show_window.get() {
+                            "hs-dropdown-menu transition-[opacity,margin] absolute top-full right-0 duration opacity-100 open block min-w-60 bg-white shadow-md rounded-lg p-1 space-y-0.5 mt-2 z-50 dark:bg-neutral-800 dark:border dark:border-neutral-700 dark:divide-neutral-700"
+                        } else {
+                            "hs-dropdown-menu transition-[opacity,margin] duration hs-dropdown-open:opacity-100 opacity-0 hidden min-w-60 bg-white shadow-md rounded-lg p-1 space-y-0.5 mt-2 dark:bg-neutral-800 dark:border dark:border-neutral-700 dark:divide-neutral-700"
+                        }
+                    }
+
+                    role="menu"
+                    aria-orientation="vertical"
+                >
+
+                    {[Window::Last15Minutes, Window::LastHour, Window::Last6Hours, Window::LastDay]
+                        .into_iter()
+                        .map(|item| {
+                            view! {
+                                <a
+                                    class="flex items-center gap-x-3.5 py-2 px-3 rounded-lg text-sm text-gray-800 hover:bg-gray-100 focus:outline-none focus:bg-gray-100 dark:text-neutral-400 dark:hover:bg-neutral-700 dark:hover:text-neutral-300 dark:focus:bg-neutral-700"
+                                    href="#"
+                                    on:click=move |ev| {
+                                        ev.prevent_default();
+                                        window.set(item);
+                                        show_window.set(false);
+                                        poller.resume();
+                                    }
+                                >
+
+                                    {item.label()}
+                                </a>
+                            }
+                        })
+                        .collect_view()}
+
+                </div>
+            </div>
+        </div>
+
+        <TimeSeriesChart
+            title="Message throughput"
+            labels=&["received", "sent"]
+            data=throughput
+        />
+        <TimeSeriesChart title="Queue size" labels=&["messages"] data=queue_size/>
+        <TimeSeriesChart
+            title="Authentication failures"
+            labels=&["failed", "banned"]
+            data=auth_failures
+        />
+        <TimeSeriesChart
+            title="Open connections"
+            labels=&["smtp", "imap/pop3", "http"]
+            data=connections
+        />
+        <TimeSeriesChart title="Memory usage" labels=&["MB"] data=memory_usage/>
+    }
+}
+
+struct TimeBucket {
+    start: chrono::DateTime<Utc>,
+    width: Duration,
+    value: Vec<Vec<f64>>,
+    count: Vec<Vec<f64>>,
+}
+
+impl TimeBucket {
+    fn create(window: Window, num_series: usize) -> Self {
+        let width = window.duration() / NUM_BUCKETS as i32;
+        TimeBucket {
+            start: Utc::now() - window.duration(),
+            width,
+            value: vec![vec![0.0; num_series]; NUM_BUCKETS as usize],
+            count: vec![vec![0.0; num_series]; NUM_BUCKETS as usize],
+        }
+    }
+
+    fn add_readings(mut self, metrics: &[Metric], ids: &[&[&str]]) -> Self {
+        for metric in metrics {
+            if let Some(y_num) = ids.iter().position(|ids| ids.contains(&metric.id())) {
+                self.add_reading(metric, y_num);
+            }
+        }
+        self
+    }
+
+    fn add_reading(&mut self, metric: &Metric, y_num: usize) {
+        let (value, count, timestamp) = match metric {
+            Metric::Counter {
+                value, timestamp, ..
+            }
+            | Metric::Gauge {
+                value, timestamp, ..
+            } => (*value, 1, timestamp),
+            Metric::Histogram {
+                sum,
+                count,
+                timestamp,
+                ..
+            } => (*sum, *count, timestamp),
+        };
+
+        let offset = (*timestamp - self.start).num_milliseconds();
+        let width = self.width.num_milliseconds().max(1);
+        if offset < 0 || offset / width >= NUM_BUCKETS {
+            return;
+        }
+        let index = (offset / width) as usize;
+        self.value[index][y_num] += value as f64;
+        self.count[index][y_num] += count as f64;
+    }
+
+    fn finish_sum(self) -> Vec<TimeSeriesPoint> {
+        let (start, width) = (self.start, self.width);
+        self.value
+            .into_iter()
+            .enumerate()
+            .map(|(idx, y)| TimeSeriesPoint {
+                x: (start + width * idx as i32).with_timezone(&Local),
+                y,
+            })
+            .collect()
+    }
+
+    fn finish_avg(mut self, divisor: f64) -> Vec<TimeSeriesPoint> {
+        for (values, counts) in self.value.iter_mut().zip(self.count.iter()) {
+            for (value, count) in values.iter_mut().zip(counts.iter()) {
+                if *count > 0.0 {
+                    *value /= count;
+                }
+                *value /= divisor;
+            }
+        }
+        self.finish_sum()
+    }
+}
+
+impl Window {
+    fn duration(&self) -> Duration {
+        match self {
+            Window::Last15Minutes => Duration::minutes(15),
+            Window::LastHour => Duration::hours(1),
+            Window::Last6Hours => Duration::hours(6),
+            Window::LastDay => Duration::days(1),
+        }
+    }
+
+    fn refresh_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(match self {
+            Window::Last15Minutes => 15,
+            Window::LastHour => 30,
+            Window::Last6Hours => 60,
+            Window::LastDay => 120,
+        })
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Window::Last15Minutes => "Last 15 minutes",
+            Window::LastHour => "Last hour",
+            Window::Last6Hours => "Last 6 hours",
+            Window::LastDay => "Last 24 hours",
+        }
+    }
+
+    fn as_timestamp(&self) -> String {
+        (Utc::now() - self.duration()).to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
@@ -0,0 +1,244 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: LicenseRef-SEL
+ *
+ * This file is subject to the Stalwart Enterprise License Agreement (SEL) and
+ * is not open source software. It must not be modified or distributed without
+ * explicit permission from Stalwart Labs Ltd.
+ * Unauthorized use, modification, or distribution is strictly prohibited.
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{badge::Badge, icon::IconCircleStack, messages::alert::Alert, Color},
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+    },
+};
+
+const HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreHealth {
+    id: String,
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(default)]
+    roles: Vec<StoreRole>,
+    state: StoreState,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    latency: Option<StoreLatency>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum StoreRole {
+    Data,
+    Blob,
+    Fts,
+    Lookup,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum StoreState {
+    Connected,
+    Degraded,
+    Disconnected,
+    #[serde(other)]
+    Unknown,
+}
+
+// Latency percentiles in milliseconds over the server's sampling window
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct StoreLatency {
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    #[serde(default)]
+    samples: u64,
+}
+
+#[component]
+pub fn StoreHealthPanel() -> impl IntoView {
+    let auth = use_authorization();
+    let poller = use_polling(HEALTH_INTERVAL);
+
+    let stores = create_resource(
+        move || poller.tick(),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/store/health")
+                    .with_authorization(&auth)
+                    .send::<Vec<StoreHealth>>()
+                    .await
+            }
+        },
+    );
+
+    view! {
+        <div class="max-w-[85rem] px-4 py-2 sm:px-6 lg:px-8 lg:py-2 mx-auto">
+            <Transition>
+                {move || match stores.get() {
+                    Some(Ok(stores)) if !stores.is_empty() => {
+                        view! {
+                            <div class="grid sm:grid-cols-2 lg:grid-cols-3 gap-4 sm:gap-6">
+                                {stores
+                                    .into_iter()
+                                    .map(|store| view! { <StoreHealthCard store/> })
+                                    .collect_view()}
+                            </div>
+                        }
+                            .into_view()
+                    }
+                    Some(Ok(_)) => {
+                        view! {
+                            <p class="text-sm text-gray-600 dark:text-gray-400">
+                                No stores are configured.
+                            </p>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        ().into_view()
+                    }
+                    Some(Err(err)) => {
+                        view! {
+                            <p class="text-sm text-red-600 dark:text-red-500">
+                                {Alert::from(err).message}
+                            </p>
+                        }
+                            .into_view()
+                    }
+                    None => ().into_view(),
+                }}
+
+            </Transition>
+        </div>
+    }
+}
+
+#[component]
+fn StoreHealthCard(store: StoreHealth) -> impl IntoView {
+    let (color, state) = store.state.badge();
+
+    view! {
+        <a
+            class="flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-neutral-900 dark:border-neutral-800"
+            href=format!("/settings/store/{}/edit", store.id)
+        >
+            <div class="p-4 md:p-5 flex gap-x-4">
+                <div class="shrink-0 flex justify-center items-center size-[46px] bg-gray-100 rounded-lg dark:bg-neutral-800">
+                    <IconCircleStack attr:class="shrink-0 size-5 text-gray-600 dark:text-neutral-400"/>
+                </div>
+                <div class="grow space-y-2">
+                    <div class="flex justify-between items-center gap-x-2">
+                        <p class="text-xs uppercase tracking-wide text-gray-500 dark:text-neutral-500">
+                            {store.typ.clone()}
+                        </p>
+                        <Badge color>{state}</Badge>
+                    </div>
+                    <h3 class="text-xl font-medium text-gray-800 dark:text-neutral-200">
+                        {store.id.clone()}
+                    </h3>
+                    <div class="flex flex-wrap gap-1">
+                        {store
+                            .roles
+                            .into_iter()
+                            .map(|role| view! { <Badge color=Color::Blue>{role.label()}</Badge> })
+                            .collect_view()}
+                    </div>
+                    {match store.latency {
+                        Some(latency) if latency.samples > 0 || latency.p50 > 0.0 => {
+                            view! {
+                                <dl class="grid grid-cols-3 gap-2 text-xs">
+                                    <LatencyValue label="p50" value=latency.p50/>
+                                    <LatencyValue label="p95" value=latency.p95/>
+                                    <LatencyValue label="p99" value=latency.p99/>
+                                </dl>
+                            }
+                                .into_view()
+                        }
+                        _ => {
+                            view! {
+                                <p class="text-xs text-gray-500">No recent operations</p>
+                            }
+                                .into_view()
+                        }
+                    }}
+                    {store
+                        .error
+                        .map(|error| {
+                            view! {
+                                <p class="text-xs text-red-600 break-all dark:text-red-500">
+                                    {error}
+                                </p>
+                            }
+                        })}
+
+                </div>
+            </div>
+        </a>
+    }
+}
+
+#[component]
+fn LatencyValue(label: &'static str, value: f64) -> impl IntoView {
+    view! {
+        <div>
+            <dt class="uppercase text-gray-500 dark:text-neutral-500">{label}</dt>
+            <dd class="font-medium tabular-nums text-gray-800 dark:text-neutral-200">
+                {format_latency(value)}
+            </dd>
+        </div>
+    }
+}
+
+impl StoreRole {
+    fn label(&self) -> &'static str {
+        match self {
+            StoreRole::Data => "Data",
+            StoreRole::Blob => "Blob",
+            StoreRole::Fts => "Full-text",
+            StoreRole::Lookup => "Lookup",
+            StoreRole::Other => "Other",
+        }
+    }
+}
+
+impl StoreState {
+    fn badge(&self) -> (Color, &'static str) {
+        match self {
+            StoreState::Connected => (Color::Green, "Connected"),
+            StoreState::Degraded => (Color::Yellow, "Degraded"),
+            StoreState::Disconnected => (Color::Red, "Disconnected"),
+            StoreState::Unknown => (Color::Gray, "Unknown"),
+        }
+    }
+}
+
+fn format_latency(millis: f64) -> String {
+    if millis >= 1000.0 {
+        format!("{:.2} s", millis / 1000.0)
+    } else if millis >= 10.0 {
+        format!("{millis:.0} ms")
+    } else {
+        format!("{millis:.1} ms")
+    }
+}
@@ -13,7 +13,6 @@ use std::vec;
 
 use chrono::{DateTime, Utc};
 use chrono_humanize::{Accuracy, HumanTime, Tense};
-use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::{use_navigate, use_params_map};
 
@@ -32,6 +31,7 @@ use crate::{
         Color,
     },
     core::{
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
     },
@@ -366,7 +366,7 @@ fn format_value(key: Key, value: Value) -> View {
             }.into_view()
         }
         (Key::Size, Value::Int(v)) => {
-            view! { <b>{format_size(v, DECIMAL)}</b> }.into_view()
+            view! { <b>{format_bytes(v)}</b> }.into_view()
         }
         (_, Value::Int(v)) => view! { <b>{v}</b> }.into_view(),
         (_, Value::Float(v)) => view! { <b>{v}</b> }.into_view(),
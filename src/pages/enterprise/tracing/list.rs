@@ -9,7 +9,6 @@
  * Unauthorized use, modification, or distribution is strictly prohibited.
  */
 
-use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::*;
 use serde::{Deserialize, Serialize};
@@ -24,13 +23,15 @@ use crate::{
         },
         messages::alert::{use_alerts, Alert},
         skeleton::Skeleton,
+        time::Timestamp,
     },
     core::{
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
     },
-    pages::{FormatDateTime, List},
+    pages::List,
 };
 
 const PAGE_SIZE: u32 = 10;
@@ -212,9 +213,8 @@ pub fn SpanList() -> impl IntoView {
 
 #[component]
 fn HistoryItem(span: Event) -> impl IntoView {
-    let timestamp = span.created_at.format_date_time();
     let from = span.get_as_str(Key::From).unwrap_or_default().to_string();
-    let size = format_size(span.get_as_int(Key::Size).unwrap_or_default(), DECIMAL);
+    let size = format_bytes(span.get_as_int(Key::Size).unwrap_or_default());
     let mut to = String::new();
     let mut to_count = 0;
     for (pos, rcpt) in span.get_as_str_list(Key::To).enumerate() {
@@ -234,7 +234,9 @@ fn HistoryItem(span: Event) -> impl IntoView {
 
     view! {
         <tr>
-            <ListTextItem>{timestamp}</ListTextItem>
+            <ListTextItem>
+                <Timestamp date=span.created_at relative=true/>
+            </ListTextItem>
             <ListTextItem>{from}</ListTextItem>
             <ListTextItem>{to}</ListTextItem>
             <ListTextItem>{size}</ListTextItem>
@@ -11,9 +11,7 @@
 
 use std::sync::Arc;
 
-use chrono::{DateTime, Utc};
-use chrono_humanize::HumanTime;
-use humansize::{format_size, DECIMAL};
+use chrono::{DateTime, Duration, Utc};
 use leptos::*;
 use leptos_router::*;
 use serde::{Deserialize, Serialize};
@@ -31,9 +29,11 @@ use crate::{
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
@@ -42,6 +42,13 @@ use crate::{
 };
 
 const PAGE_SIZE: u32 = 20;
+// Time windows in days, empty for the entire retention period
+static WINDOWS: &[(&str, &str)] = &[
+    ("", "Entire retention period"),
+    ("1", "Last 24 hours"),
+    ("7", "Last 7 days"),
+    ("30", "Last 30 days"),
+];
 
 #[derive(Clone, Serialize, Deserialize, Default)]
 struct DeletedBlob {
@@ -52,6 +59,13 @@ struct DeletedBlob {
     #[serde(rename = "expiresAt")]
     pub expires_at: DateTime<Utc>,
     pub collection: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(rename = "receivedAt")]
+    #[serde(default)]
+    pub received_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -63,6 +77,8 @@ struct UndeleteRequest {
     #[serde(rename = "cancelDeletion")]
     #[serde(default)]
     cancel_deletion: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mailbox: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -84,6 +100,12 @@ pub fn UndeleteList() -> impl IntoView {
             .filter(|&page| page > 0)
             .unwrap_or(1)
     });
+    let window = create_memo(move |_| {
+        query
+            .with(|q| q.get("window").and_then(|days| days.parse::<i64>().ok()))
+            .filter(|&days| days > 0)
+    });
+    let mailbox = create_rw_signal(String::new());
 
     let auth = use_authorization();
     let alert = use_alerts();
@@ -96,16 +118,19 @@ pub fn UndeleteList() -> impl IntoView {
             (
                 page.get(),
                 params.get().get("id").cloned().unwrap_or_default(),
+                window.get(),
             )
         },
-        move |(page, account)| {
+        move |(page, account, window)| {
             let auth = auth.get_untracked();
+            let since = window.map(|days| (Utc::now() - Duration::days(days)).to_rfc3339());
 
             async move {
                 HttpRequest::get(("/api/store/undelete", account))
                     .with_authorization(&auth)
                     .with_parameter("page", page.to_string())
                     .with_parameter("limit", PAGE_SIZE.to_string())
+                    .with_optional_parameter("since", since)
                     .send::<List<DeletedBlob>>()
                     .await
                     .map(Arc::new)
@@ -169,12 +194,21 @@ pub fn UndeleteList() -> impl IntoView {
         let account = params.get().get("id").cloned().unwrap_or_default();
         let auth = auth.get();
         let results = results.get_untracked();
+        let since = window
+            .get_untracked()
+            .map(|days| (Utc::now() - Duration::days(days)).to_rfc3339());
+        let mailbox =
+            Some(mailbox.get_untracked().trim().to_string()).filter(|mailbox| !mailbox.is_empty());
 
         async move {
             let response = match items.as_ref() {
+                // Everything in the time window is restored by the server, not
+                // only the current page
                 ItemSelection::All => {
                     HttpRequest::post(("/api/store/undelete", account))
                         .with_authorization(&auth)
+                        .with_optional_parameter("since", since)
+                        .with_optional_parameter("mailbox", mailbox)
                         .with_body(())
                         .unwrap()
                         .send::<Vec<UndeleteResponse>>()
@@ -193,6 +227,7 @@ pub fn UndeleteList() -> impl IntoView {
                                 collection: blob.collection.clone(),
                                 time: blob.deleted_at,
                                 cancel_deletion: blob.expires_at,
+                                mailbox: mailbox.clone(),
                             });
                         }
                     }
@@ -265,6 +300,58 @@ pub fn UndeleteList() -> impl IntoView {
             <ListSection>
                 <ListTable title="Restore deleted blobs" subtitle="View and restore deleted blobs">
                     <Toolbar slot>
+                        <select
+                            class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            on:change=move |ev| {
+                                let value = event_target_value(&ev);
+                                use_navigate()(
+                                    &UrlBuilder::new("/manage/undelete")
+                                        .with_subpath(
+                                            params
+                                                .get()
+                                                .get("id")
+                                                .map(|s| s.as_str())
+                                                .unwrap_or_default(),
+                                        )
+                                        .with_optional_parameter(
+                                            "window",
+                                            Some(value).filter(|value| !value.is_empty()),
+                                        )
+                                        .finish(),
+                                    Default::default(),
+                                );
+                            }
+                        >
+
+                            {WINDOWS
+                                .iter()
+                                .map(|(days, label)| {
+                                    view! {
+                                        <option
+                                            value=*days
+                                            selected=move || {
+                                                window.get().map(|days| days.to_string()).unwrap_or_default()
+                                                    == *days
+                                            }
+                                        >
+
+                                            {*label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+
+                        <input
+                            type="text"
+                            class="py-2 px-3 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            placeholder="Restore to original folder"
+                            prop:value=move || mailbox.get()
+                            on:input=move |ev| {
+                                mailbox.set(event_target_value(&ev));
+                            }
+                        />
+
                         <ToolbarButton
                             text=Signal::derive(move || {
                                 let ns = selected.get().total_selected(total_results.get());
@@ -326,6 +413,7 @@ pub fn UndeleteList() -> impl IntoView {
                                     view! {
                                         <ColumnList
                                             headers=vec![
+                                                "Message".to_string(),
                                                 "Type".to_string(),
                                                 "Size".to_string(),
                                                 "Deleted".to_string(),
@@ -385,6 +473,10 @@ pub fn UndeleteList() -> impl IntoView {
                                                 .map(|s| s.as_str())
                                                 .unwrap_or_default(),
                                         )
+                                        .with_optional_parameter(
+                                            "window",
+                                            window.get().map(|days| days.to_string()),
+                                        )
                                         .with_parameter("page", page.to_string())
                                         .finish(),
                                     Default::default(),
@@ -488,12 +580,22 @@ fn UndeleteItem(blob: DeletedBlob, blob_hash: RwSignal<String>) -> impl IntoView
                 </label>
             </ListItem>
 
+            <ListItem class="h-px w-72 min-w-72">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200 truncate">
+                    {blob.subject}
+                </span>
+                <span class="block text-sm text-gray-500 truncate">{blob.from}</span>
+                <span class="block text-xs text-gray-500">
+                    {blob.received_at.map(|date| date.format_date_time())}
+                </span>
+            </ListItem>
+
             <ListItem>
                 <span class="text-sm text-gray-500">{blob.collection}</span>
             </ListItem>
 
             <ListItem>
-                <span class="text-sm text-gray-500">{format_size(blob.size, DECIMAL)}</span>
+                <span class="text-sm text-gray-500">{format_bytes(blob.size as u64)}</span>
             </ListItem>
 
             <ListItem>
@@ -501,9 +603,11 @@ fn UndeleteItem(blob: DeletedBlob, blob_hash: RwSignal<String>) -> impl IntoView
             </ListItem>
 
             <ListItem>
-                <span class="text-sm text-gray-500">
-                    {HumanTime::from(blob.expires_at).to_string()}
-                </span>
+                <Timestamp
+                    date=blob.expires_at
+                    relative=true
+                    attr:class="text-sm text-gray-500"
+                />
             </ListItem>
 
             <ListItem subclass="px-6 py-1.5">
@@ -0,0 +1,644 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button, input::TextArea, select::Select, Form, FormButtonBar, FormElement,
+            FormItem, FormSection,
+        },
+        icon::IconTrash,
+        list::{
+            header::ColumnList,
+            pagination::Pagination,
+            row::SelectItem,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ItemSelection, ListItem, ListSection, ListTable, ListTextItem, Toolbar,
+            ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, InputCheck, Schemas, SelectType, Source, Transformer, Type, Validator},
+        url::UrlBuilder,
+        AccessToken,
+    },
+    pages::{
+        config::{
+            cluster::{reload_settings, ClusterApplyResults, ReloadOutcome},
+            snapshot::create_snapshot,
+            Settings, UpdateSettings,
+        },
+        maybe_plural, FormatDateTime, List,
+    },
+};
+
+const PAGE_SIZE: u32 = 20;
+
+struct AccessListType {
+    id: &'static str,
+    title: &'static str,
+    subtitle: &'static str,
+    prefix: &'static str,
+    reload_prefix: &'static str,
+    placeholder: &'static str,
+    validators: &'static [Validator],
+}
+
+static ACCESS_LISTS: &[AccessListType] = &[
+    AccessListType {
+        id: "blocked-ip",
+        title: "Blocked IP addresses",
+        subtitle: "IP addresses and networks that are not allowed to connect",
+        prefix: "server.blocked-ip",
+        reload_prefix: "server.blocked-ip",
+        placeholder: "192.0.2.1\n198.51.100.0/24",
+        validators: &[Validator::IsIpOrMask],
+    },
+    AccessListType {
+        id: "allowed-ip",
+        title: "Allowed IP addresses",
+        subtitle: "IP addresses and networks that are never banned",
+        prefix: "server.allowed-ip",
+        reload_prefix: "server.allowed-ip",
+        placeholder: "192.0.2.1\n198.51.100.0/24",
+        validators: &[Validator::IsIpOrMask],
+    },
+    AccessListType {
+        id: "blocked-domains",
+        title: "Blocked domains and senders",
+        subtitle: "Domains and sender addresses that are always treated as spam",
+        prefix: "lookup.blocked-domains",
+        reload_prefix: "lookup",
+        placeholder: "example.org\nsender@example.com",
+        validators: &[Validator::IsDomain],
+    },
+    AccessListType {
+        id: "trusted-domains",
+        title: "Trusted domains and senders",
+        subtitle: "Domains and sender addresses that bypass the spam filter",
+        prefix: "lookup.trusted-domains",
+        reload_prefix: "lookup",
+        placeholder: "example.org\nsender@example.com",
+        validators: &[Validator::IsDomain],
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct AccessEntry {
+    value: String,
+    expires: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FetchSettings {
+    items: Settings,
+}
+
+#[component]
+pub fn AccessList() -> impl IntoView {
+    let query = use_query_map();
+    let params = use_params_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter").and_then(|s| {
+                let s = s.trim();
+                if !s.is_empty() {
+                    Some(s.to_lowercase())
+                } else {
+                    None
+                }
+            })
+        })
+    });
+    let list_type = create_memo(move |_| {
+        let id = params.get().get("object").cloned().unwrap_or_default();
+        ACCESS_LISTS
+            .iter()
+            .position(|list| list.id == id)
+            .unwrap_or_else(|| {
+                use_navigate()("/404", Default::default());
+                0
+            })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
+    let mut data = schemas.with_value(|schemas| schemas.build_form("access-list"));
+    data.apply_defaults(false);
+    let data = data.into_signal();
+    let cluster_results = create_rw_signal(None);
+
+    let entries = create_resource(
+        move || (list_type.get(), page.get(), filter.get()),
+        move |(list_type, page, filter)| {
+            let auth = auth.get_untracked();
+            let list = &ACCESS_LISTS[list_type];
+
+            async move {
+                // The server does not track expirations, entries are removed
+                // once they expire
+                let (expired, mut items): (Vec<_>, Vec<_>) = fetch_entries(&auth, list)
+                    .await?
+                    .into_iter()
+                    .partition(|entry| entry.is_expired());
+                if !expired.is_empty() {
+                    if let ReloadOutcome::Cluster(results) = remove_entries(
+                        &auth,
+                        list,
+                        expired.into_iter().map(|entry| entry.value).collect(),
+                    )
+                    .await?
+                    {
+                        cluster_results.set(Some(results));
+                    }
+                }
+                if let Some(filter) = &filter {
+                    items.retain(|entry| entry.matches(filter));
+                }
+                let total = items.len() as u64;
+                let items = items
+                    .into_iter()
+                    .skip((PAGE_SIZE * page.saturating_sub(1)) as usize)
+                    .take(PAGE_SIZE as usize)
+                    .collect();
+
+                Ok(Arc::new(List { items, total }))
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<u32>);
+    let update_action = create_action(
+        move |(updates, message, reason): &(Arc<Vec<UpdateSettings>>, String, String)| {
+            let updates = updates.clone();
+            let message = message.clone();
+            let reason = reason.clone();
+            let auth = auth.get();
+            let list = &ACCESS_LISTS[list_type.get()];
+
+            async move {
+                if let Err(err) = create_snapshot(&auth, &schemas.get_value(), reason).await {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+
+                let result = match HttpRequest::post("/api/settings")
+                    .with_authorization(&auth)
+                    .with_body(updates.as_ref())
+                    .unwrap()
+                    .send::<serde_json::Value>()
+                    .await
+                {
+                    Ok(_) => reload_settings(&auth, list.reload_prefix).await,
+                    Err(err) => Err(err),
+                };
+
+                match result {
+                    Ok(ReloadOutcome::Local(result)) if !result.errors.is_empty() => {
+                        entries.refetch();
+                        alert.set(Alert::from_reload(result, &schemas.get_value()));
+                    }
+                    Ok(outcome) => {
+                        if let ReloadOutcome::Cluster(results) = outcome {
+                            cluster_results.set(Some(results));
+                        }
+                        data.update(|data| data.reset());
+                        entries.refetch();
+                        alert.set(Alert::success(message));
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    let delete_entries = move |items: Vec<String>| {
+        let list = &ACCESS_LISTS[list_type.get()];
+        let text = maybe_plural(items.len(), "entry", "entries");
+        update_action.dispatch((
+            Arc::new(vec![UpdateSettings::Delete {
+                keys: items
+                    .into_iter()
+                    .map(|item| format!("{}.{item}", list.prefix))
+                    .collect(),
+            }]),
+            format!("Removed {text}."),
+            format!("Before removing {text} from {}", list.title),
+        ));
+    };
+
+    // Selecting all removes every entry matching the search, not only the
+    // ones on the current page
+    let delete_selected = create_action(move |selection: &ItemSelection| {
+        let selection = selection.clone();
+        let auth = auth.get();
+        let list = &ACCESS_LISTS[list_type.get()];
+        let filter = filter.get();
+
+        async move {
+            let items = match selection {
+                ItemSelection::Some(items) => items.into_iter().collect::<Vec<_>>(),
+                ItemSelection::All => match fetch_entries(&auth, list).await {
+                    Ok(items) => items
+                        .into_iter()
+                        .filter(|entry| filter.as_ref().is_none_or(|filter| entry.matches(filter)))
+                        .map(|entry| entry.value)
+                        .collect(),
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                },
+                ItemSelection::None => return,
+            };
+            if !items.is_empty() {
+                delete_entries(items);
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title=Signal::derive(move || ACCESS_LISTS[list_type.get()].title.to_string())
+            subtitle=Signal::derive(move || ACCESS_LISTS[list_type.get()].subtitle.to_string())
+        >
+            <ClusterApplyResults
+                results=cluster_results
+                reload_prefix=Signal::derive(move || {
+                    ACCESS_LISTS[list_type.get()].reload_prefix.to_string()
+                })
+                on_close=move |_| cluster_results.set(None)
+            />
+            <FormSection title="Add entries".to_string()>
+                <FormItem
+                    label="Entries"
+                    tooltip="One entry per line, or separated by commas or spaces"
+                >
+                    <TextArea
+                        element=FormElement::new("entries", data)
+                        placeholder=Signal::derive(move || {
+                            ACCESS_LISTS[list_type.get()].placeholder.to_string()
+                        })
+                    />
+                </FormItem>
+                <FormItem label="Expires" tooltip="When the entries should be removed">
+                    <Select element=FormElement::new("expires", data)/>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Add"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        let list = &ACCESS_LISTS[list_type.get()];
+                        let mut values = Vec::new();
+                        let mut invalid = Vec::new();
+                        data.update(|data| {
+                            if !data.validate_form() {
+                                return;
+                            }
+                            let expires = data
+                                .value::<String>("expires")
+                                .and_then(|expires| parse_expiration(&expires))
+                                .map(|expires| {
+                                    (Utc::now() + expires)
+                                        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+                                })
+                                .unwrap_or_default();
+                            for entry in data
+                                .value::<String>("entries")
+                                .unwrap_or_default()
+                                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                                .filter(|entry| !entry.is_empty())
+                            {
+                                match list.check_entry(entry) {
+                                    Ok(entry) => {
+                                        values.push((entry, expires.clone()));
+                                    }
+                                    Err(_) => {
+                                        invalid.push(entry.to_string());
+                                    }
+                                }
+                            }
+                            if !invalid.is_empty() {
+                                data.new_error(
+                                    "entries",
+                                    format!("Invalid entries: {}", invalid.join(", ")),
+                                );
+                            } else if values.is_empty() {
+                                data.new_error("entries", "This field is required");
+                            }
+                        });
+                        if invalid.is_empty() && !values.is_empty() {
+                            let text = maybe_plural(values.len(), "entry", "entries");
+                            update_action
+                                .dispatch((
+                                    Arc::new(
+                                        vec![
+                                            UpdateSettings::Insert {
+                                                prefix: Some(list.prefix.to_string()),
+                                                values,
+                                                assert_empty: false,
+                                            },
+                                        ],
+                                    ),
+                                    format!("Added {text}."),
+                                    format!("Before adding {text} to {}", list.title),
+                                ));
+                        }
+                    })
+
+                    disabled=update_action.pending()
+                />
+            </FormButtonBar>
+        </Form>
+
+        <ListSection>
+            <ListTable title="Entries" subtitle="Search, review and remove list entries">
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/access-list")
+                                    .with_subpath(ACCESS_LISTS[list_type.get()].id)
+                                    .with_parameter("filter", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().total_selected(total_results.get());
+                            if ns > 0 { format!("Remove ({ns})") } else { "Remove".to_string() }
+                        })
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            let to_delete = selected.get();
+                            let total = to_delete.total_selected(total_results.get());
+                            if total > 0 {
+                                let text = maybe_plural(total, "entry", "entries");
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm removal")
+                                            .with_message(
+                                                format!("Are you sure you want to remove {text}?"),
+                                            )
+                                            .with_button(format!("Remove {text}"))
+                                            .with_dangerous_callback(move || {
+                                                selected.set(ItemSelection::None);
+                                                delete_selected.dispatch(to_delete.clone());
+                                            }),
+                                    )
+                            }
+                        })
+                    >
+
+                        <IconTrash/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match entries.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(entries)) if !entries.items.is_empty() => {
+                            total_results.set(Some(entries.total as u32));
+                            Some(
+                                view! {
+                                    <ColumnList
+                                        headers=vec!["Entry".to_string(), "Expires".to_string()]
+                                        has_select_all=true
+                                    >
+
+                                        <For
+                                            each=move || entries.items.clone()
+                                            key=|entry| entry.value.clone()
+                                            let:entry
+                                        >
+                                            <AccessEntryItem entry/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="This list is empty or your search did not yield any results."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/access-list")
+                                    .with_subpath(ACCESS_LISTS[list_type.get()].id)
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("filter", filter.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn AccessEntryItem(entry: AccessEntry) -> impl IntoView {
+    let entry_id = entry.value.clone();
+    let expires = match entry.expires {
+        Some(expires) => expires.format_date_time(),
+        None => "Never".to_string(),
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex">
+                    <SelectItem item_id=entry_id/>
+
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListTextItem>{entry.value}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">{expires}</ListItem>
+        </tr>
+    }
+}
+
+async fn fetch_entries(
+    auth: &AccessToken,
+    list: &AccessListType,
+) -> Result<Vec<AccessEntry>, http::Error> {
+    HttpRequest::get("/api/settings/list")
+        .with_authorization(auth)
+        .with_parameter("prefix", list.prefix)
+        .send::<FetchSettings>()
+        .await
+        .map(|response| {
+            let mut items = response
+                .items
+                .into_iter()
+                .map(|(value, expires)| AccessEntry {
+                    value,
+                    expires: DateTime::parse_from_rfc3339(&expires)
+                        .ok()
+                        .map(|dt| dt.with_timezone(&Utc)),
+                })
+                .collect::<Vec<_>>();
+            items.sort_by(|a, b| a.value.cmp(&b.value));
+            items
+        })
+}
+
+async fn remove_entries(
+    auth: &AccessToken,
+    list: &AccessListType,
+    items: Vec<String>,
+) -> Result<ReloadOutcome, http::Error> {
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(vec![UpdateSettings::Delete {
+            keys: items
+                .into_iter()
+                .map(|item| format!("{}.{item}", list.prefix))
+                .collect(),
+        }])?
+        .send::<serde_json::Value>()
+        .await?;
+    reload_settings(auth, list.reload_prefix).await
+}
+
+fn parse_expiration(value: &str) -> Option<Duration> {
+    let (num, unit) = value.split_at(value.len().checked_sub(1)?);
+    let num = num.parse::<i64>().ok()?;
+    match unit {
+        "h" => Some(Duration::hours(num)),
+        "d" => Some(Duration::days(num)),
+        _ => None,
+    }
+}
+
+impl AccessEntry {
+    fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| expires <= Utc::now())
+    }
+
+    fn matches(&self, filter: &str) -> bool {
+        self.value.contains(filter)
+    }
+}
+
+impl AccessListType {
+    fn check_entry(&self, entry: &str) -> Result<String, std::borrow::Cow<'static, str>> {
+        let check = InputCheck {
+            transformers: vec![Transformer::Trim, Transformer::Lowercase],
+            validators: self.validators.to_vec(),
+        };
+        match check.check_value(entry.to_string()) {
+            Ok(value) => Ok(value),
+            Err(_) if self.reload_prefix == "lookup" && entry.contains('@') => InputCheck {
+                transformers: vec![Transformer::Trim, Transformer::Lowercase],
+                validators: vec![Validator::IsEmail],
+            }
+            .check_value(entry.to_string()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_access_lists(self) -> Self {
+        self.new_schema("access-list")
+            .new_field("entries")
+            .typ(Type::Text)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("expires")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("", "Never"),
+                    ("1h", "In 1 hour"),
+                    ("1d", "In 1 day"),
+                    ("7d", "In 7 days"),
+                    ("30d", "In 30 days"),
+                ]),
+            })
+            .default("")
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,659 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button,
+            input::{InputText, TextArea},
+            select::Select,
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        list::{
+            header::ColumnList, toolbar::SearchBox, Footer, ListItem, ListTable, ListTextItem,
+            Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{
+            Builder, Schema, SchemaType, Schemas, SelectType, Source, Transformer, Type, Validator,
+        },
+    },
+    pages::config::UpdateSettings,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Endpoint {
+    group: String,
+    method: &'static str,
+    path: String,
+    description: String,
+    body: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ApiResponse {
+    method: String,
+    path: String,
+    is_error: bool,
+    body: String,
+}
+
+#[component]
+pub fn ApiExplorer() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = expect_context::<Arc<Schemas>>();
+
+    let (pending, set_pending) = create_signal(false);
+    let response = create_rw_signal(None::<ApiResponse>);
+    let filter = create_rw_signal(String::new());
+    let endpoints = store_value(known_endpoints(&schemas));
+
+    let mut data = schemas.build_form("api-explorer");
+    data.apply_defaults(false);
+    let data = data.into_signal();
+
+    let filtered_endpoints = create_memo(move |_| {
+        let filter = filter.get().to_lowercase();
+        endpoints.with_value(|endpoints| {
+            endpoints
+                .iter()
+                .filter(|endpoint| {
+                    filter.is_empty()
+                        || endpoint.path.to_lowercase().contains(&filter)
+                        || endpoint.group.to_lowercase().contains(&filter)
+                        || endpoint.description.to_lowercase().contains(&filter)
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let send_request = create_action(
+        move |(method, path, body): &(String, String, Option<String>)| {
+            let auth = auth.get();
+            let method = method.clone();
+            let path = path.clone();
+            let body = body.clone();
+
+            async move {
+                set_pending.set(true);
+                response.set(None);
+                let request = match method.as_str() {
+                    "POST" => HttpRequest::post(path.as_str()),
+                    "PUT" => HttpRequest::put(path.as_str()),
+                    "PATCH" => HttpRequest::patch(path.as_str()),
+                    "DELETE" => HttpRequest::delete(path.as_str()),
+                    _ => HttpRequest::get(path.as_str()),
+                }
+                .with_authorization(&auth);
+                let request = if let Some(body) = body {
+                    request
+                        .with_raw_body(body)
+                        .with_header("Content-Type", "application/json")
+                } else {
+                    request
+                };
+                let result = request.send_raw().await;
+                set_pending.set(false);
+
+                let (is_error, body) = match result {
+                    Ok(bytes) => {
+                        // Management errors are returned with a successful status code
+                        // and an "error" property.
+                        match serde_json::from_slice::<serde_json::Value>(&bytes) {
+                            Ok(value) => (
+                                value.get("error").is_some(),
+                                serde_json::to_string_pretty(&value).unwrap_or_default(),
+                            ),
+                            Err(_) => (false, String::from_utf8_lossy(&bytes).into_owned()),
+                        }
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err @ http::Error::Network(_)) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                    Err(err) => (true, serde_json::to_string_pretty(&err).unwrap_or_default()),
+                };
+
+                response.set(Some(ApiResponse {
+                    method,
+                    path,
+                    is_error,
+                    body,
+                }));
+            }
+        },
+    );
+
+    let try_endpoint = move |endpoint: Endpoint| {
+        data.update(|data| {
+            data.update("method", endpoint.method);
+            data.update("path", endpoint.path);
+            data.update("body", endpoint.body.unwrap_or_default());
+        });
+        response.set(None);
+        window().scroll_to_with_x_and_y(0.0, 0.0);
+    };
+
+    view! {
+        <Form title="API Explorer" subtitle="Send requests to the management API using the current session">
+
+            <FormSection title="Request".to_string()>
+                <FormItem label="Method">
+                    <Select element=FormElement::new("method", data)/>
+                </FormItem>
+                <FormItem label="Path" tooltip="Request path including any query parameters">
+                    <InputText element=FormElement::new("path", data)/>
+                </FormItem>
+                <FormItem label="Body" tooltip="JSON request body, leave empty to send no body">
+                    <TextArea element=FormElement::new("body", data)/>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+
+                <Button
+                    text="Send"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        data.update(|data| {
+                            if !data.validate_form() {
+                                return;
+                            }
+                            let method = data.value::<String>("method").unwrap_or_default();
+                            let path = data.value::<String>("path").unwrap_or_default();
+                            let body = data
+                                .value::<String>("body")
+                                .filter(|body| !body.trim().is_empty());
+                            if let Some(body) = &body {
+                                if let Err(err) = serde_json::from_str::<serde_json::Value>(body) {
+                                    data.new_error("body", format!("Invalid JSON: {err}"));
+                                    return;
+                                }
+                            }
+                            send_request.dispatch((method, path, body));
+                        });
+                    })
+
+                    disabled=pending
+                />
+            </FormButtonBar>
+
+            {move || {
+                response
+                    .get()
+                    .map(|response| {
+                        view! {
+                            <FormSection title="Response".to_string()>
+                                <div class="flex items-center gap-x-2 mb-2">
+                                    <Badge color=if response.is_error {
+                                        Color::Red
+                                    } else {
+                                        Color::Green
+                                    }>{if response.is_error { "Error" } else { "Success" }}</Badge>
+                                    <span class="text-sm font-mono text-gray-600 dark:text-gray-400">
+                                        {format!("{} {}", response.method, response.path)}
+                                    </span>
+                                </div>
+                                <pre class="p-4 max-h-[32rem] overflow-auto bg-gray-50 border border-gray-200 rounded-lg text-xs font-mono text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                                    {response.body}
+                                </pre>
+                            </FormSection>
+                        }
+                    })
+            }}
+
+        </Form>
+
+        <div class="max-w-[85rem] px-4 pb-10 sm:px-6 lg:px-8 lg:pb-14 mx-auto">
+            <ListTable title="Endpoints" subtitle="Management API endpoints known to this interface">
+                <Toolbar slot>
+                    <SearchBox
+                        value=Signal::derive(move || Some(filter.get()))
+                        on_search=move |value| filter.set(value)
+                    />
+                </Toolbar>
+
+                <Show
+                    when=move || !filtered_endpoints.get().is_empty()
+                    fallback=|| {
+                        view! {
+                            <ZeroResults
+                                title="No endpoints found"
+                                subtitle="Try a different search term."
+                            />
+                        }
+                    }
+                >
+
+                    <ColumnList headers=vec![
+                        "Method".to_string(),
+                        "Path".to_string(),
+                        "Section".to_string(),
+                        "Description".to_string(),
+                        "".to_string(),
+                    ]>
+
+                        <For
+                            each=move || filtered_endpoints.get()
+                            key=|endpoint| (endpoint.method, endpoint.path.clone(), endpoint.description.clone())
+                            let:endpoint
+                        >
+                            <EndpointItem endpoint on_try=try_endpoint/>
+                        </For>
+
+                    </ColumnList>
+                </Show>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </div>
+    }
+}
+
+#[component]
+fn EndpointItem(endpoint: Endpoint, on_try: impl Fn(Endpoint) + 'static) -> impl IntoView {
+    let color = match endpoint.method {
+        "GET" => Color::Green,
+        "POST" => Color::Blue,
+        "DELETE" => Color::Red,
+        _ => Color::Yellow,
+    };
+    let endpoint_ = endpoint.clone();
+
+    view! {
+        <tr>
+            <ListItem subclass="ps-6 pe-6 py-3">
+                <Badge color=color>{endpoint.method}</Badge>
+            </ListItem>
+            <ListItem>
+                <span class="block text-sm font-mono text-gray-800 dark:text-gray-200">
+                    {endpoint.path}
+                </span>
+            </ListItem>
+            <ListTextItem>{endpoint.group}</ListTextItem>
+            <ListTextItem>{endpoint.description}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    type="button"
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    on:click=move |_| on_try(endpoint_.clone())
+                >
+                    Try it
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
+
+fn known_endpoints(schemas: &Schemas) -> Vec<Endpoint> {
+    let mut endpoints = STATIC_ENDPOINTS
+        .iter()
+        .map(|(group, method, path, description, body)| Endpoint {
+            group: group.to_string(),
+            method,
+            path: path.to_string(),
+            description: description.to_string(),
+            body: body.map(|body| {
+                serde_json::from_str::<serde_json::Value>(body)
+                    .and_then(|body| serde_json::to_string_pretty(&body))
+                    .unwrap_or_else(|_| body.to_string())
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    let mut schemas = schemas.schemas.values().collect::<Vec<_>>();
+    schemas.sort_by(|a, b| a.id.cmp(b.id));
+    for schema in schemas {
+        endpoints.extend(schema.endpoints());
+    }
+
+    endpoints
+}
+
+impl Schema {
+    fn endpoints(&self) -> Vec<Endpoint> {
+        let group = if !self.name_plural.is_empty() {
+            self.name_plural
+        } else {
+            self.id
+        };
+        let endpoint =
+            |method, path: String, description: String, body: Option<serde_json::Value>| Endpoint {
+                group: group.to_string(),
+                method,
+                path,
+                description,
+                body: body.and_then(|body| serde_json::to_string_pretty(&body).ok()),
+            };
+        let mut field_ids = self
+            .fields
+            .keys()
+            .filter(|id| !id.starts_with('_'))
+            .copied()
+            .collect::<Vec<_>>();
+        field_ids.sort_unstable();
+
+        match &self.typ {
+            SchemaType::Record { prefix, suffix } => vec![
+                endpoint(
+                    "GET",
+                    format!("/api/settings/group?prefix={prefix}&suffix={suffix}&page=1&limit=10"),
+                    format!("List {}", self.name_plural),
+                    None,
+                ),
+                endpoint(
+                    "GET",
+                    format!("/api/settings/list?prefix={prefix}.{{id}}"),
+                    format!("Fetch a {}", self.name_singular),
+                    None,
+                ),
+                endpoint(
+                    "POST",
+                    "/api/settings".to_string(),
+                    format!("Create a {}", self.name_singular),
+                    serde_json::to_value([UpdateSettings::Insert {
+                        prefix: Some(format!("{prefix}.{{id}}")),
+                        values: field_ids
+                            .iter()
+                            .map(|id| (id.to_string(), String::new()))
+                            .collect(),
+                        assert_empty: true,
+                    }])
+                    .ok(),
+                ),
+                endpoint(
+                    "POST",
+                    "/api/settings".to_string(),
+                    format!("Delete a {}", self.name_singular),
+                    serde_json::to_value([UpdateSettings::Clear {
+                        prefix: format!("{prefix}.{{id}}."),
+                        filter: None,
+                    }])
+                    .ok(),
+                ),
+            ],
+            SchemaType::Entry { prefix } => vec![
+                endpoint(
+                    "GET",
+                    format!("/api/settings/list?prefix={prefix}"),
+                    format!("List {}", self.name_plural),
+                    None,
+                ),
+                endpoint(
+                    "POST",
+                    "/api/settings".to_string(),
+                    format!("Create a {}", self.name_singular),
+                    serde_json::to_value([UpdateSettings::Insert {
+                        prefix: None,
+                        values: vec![(format!("{prefix}.{{id}}"), String::new())],
+                        assert_empty: true,
+                    }])
+                    .ok(),
+                ),
+                endpoint(
+                    "POST",
+                    "/api/settings".to_string(),
+                    format!("Delete a {}", self.name_singular),
+                    serde_json::to_value([UpdateSettings::Delete {
+                        keys: vec![format!("{prefix}.{{id}}")],
+                    }])
+                    .ok(),
+                ),
+            ],
+            // Forms that are not backed by settings, such as the spam
+            // classifier test, use plain field names without a namespace.
+            SchemaType::List if field_ids.iter().any(|id| id.contains('.')) => vec![
+                endpoint(
+                    "GET",
+                    format!("/api/settings/keys?keys={}", field_ids.join(",")),
+                    format!("Fetch {} settings", self.name_singular),
+                    None,
+                ),
+                endpoint(
+                    "POST",
+                    "/api/settings".to_string(),
+                    format!("Update {} settings", self.name_singular),
+                    serde_json::to_value([UpdateSettings::Insert {
+                        prefix: None,
+                        values: field_ids
+                            .iter()
+                            .map(|id| (id.to_string(), String::new()))
+                            .collect(),
+                        assert_empty: false,
+                    }])
+                    .ok(),
+                ),
+            ],
+            SchemaType::List => vec![],
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_api_explorer(self) -> Self {
+        self.new_schema("api-explorer")
+            .new_field("method")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(API_METHODS),
+            })
+            .default("GET")
+            .build()
+            .new_field("path")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .placeholder("/api/principal")
+            .build()
+            .new_field("body")
+            .typ(Type::Text)
+            .input_check([Transformer::Trim], [])
+            .build()
+            .build()
+    }
+}
+
+static API_METHODS: &[(&str, &str)] = &[
+    ("GET", "GET"),
+    ("POST", "POST"),
+    ("PUT", "PUT"),
+    ("PATCH", "PATCH"),
+    ("DELETE", "DELETE"),
+];
+
+#[allow(clippy::type_complexity)]
+static STATIC_ENDPOINTS: &[(&str, &str, &str, &str, Option<&str>)] = &[
+    (
+        "Principals",
+        "GET",
+        "/api/principal?types=individual&page=1&limit=10",
+        "List principals",
+        None,
+    ),
+    (
+        "Principals",
+        "GET",
+        "/api/principal/{name}",
+        "Fetch a principal",
+        None,
+    ),
+    (
+        "Principals",
+        "POST",
+        "/api/principal",
+        "Create a principal",
+        Some(
+            r#"{"type": "individual", "name": "jane", "emails": ["jane@example.org"], "secrets": ["changeme"]}"#,
+        ),
+    ),
+    (
+        "Principals",
+        "PATCH",
+        "/api/principal/{name}",
+        "Update a principal",
+        Some(r#"[{"action": "set", "field": "description", "value": "Jane Doe"}]"#),
+    ),
+    (
+        "Principals",
+        "DELETE",
+        "/api/principal/{name}",
+        "Delete a principal",
+        None,
+    ),
+    (
+        "DNS",
+        "GET",
+        "/api/dns/records/{domain}",
+        "DNS records for a domain",
+        None,
+    ),
+    (
+        "DNS",
+        "POST",
+        "/api/dkim",
+        "Create a DKIM signature",
+        Some(r#"{"id": null, "algorithm": "Ed25519", "domain": "example.org", "selector": null}"#),
+    ),
+    (
+        "Settings",
+        "GET",
+        "/api/settings/list?prefix=server",
+        "List settings by prefix",
+        None,
+    ),
+    (
+        "Settings",
+        "GET",
+        "/api/settings/keys?keys=server.hostname",
+        "Fetch settings by key",
+        None,
+    ),
+    (
+        "Settings",
+        "GET",
+        "/api/reload/?dry-run=true",
+        "Validate the configuration",
+        None,
+    ),
+    (
+        "Settings",
+        "GET",
+        "/api/reload/",
+        "Reload the configuration",
+        None,
+    ),
+    (
+        "Queue",
+        "GET",
+        "/api/queue/messages?page=1&limit=10",
+        "List queued messages",
+        None,
+    ),
+    (
+        "Queue",
+        "GET",
+        "/api/queue/messages/{id}",
+        "Fetch a queued message",
+        None,
+    ),
+    (
+        "Queue",
+        "PATCH",
+        "/api/queue/messages/{id}",
+        "Retry delivery of a message",
+        None,
+    ),
+    (
+        "Queue",
+        "DELETE",
+        "/api/queue/messages/{id}",
+        "Cancel delivery of a message",
+        None,
+    ),
+    (
+        "Queue",
+        "GET",
+        "/api/queue/reports?page=1&limit=10",
+        "List queued reports",
+        None,
+    ),
+    (
+        "Reports",
+        "GET",
+        "/api/reports/dmarc?page=1&limit=10",
+        "List DMARC aggregate reports",
+        None,
+    ),
+    (
+        "Reports",
+        "GET",
+        "/api/reports/tls?page=1&limit=10",
+        "List TLS aggregate reports",
+        None,
+    ),
+    (
+        "Reports",
+        "GET",
+        "/api/reports/arf?page=1&limit=10",
+        "List failure reports",
+        None,
+    ),
+    (
+        "Telemetry",
+        "GET",
+        "/api/telemetry/traces?page=1&limit=10",
+        "Search delivery traces",
+        None,
+    ),
+    (
+        "Telemetry",
+        "GET",
+        "/api/telemetry/trace/{id}",
+        "Fetch a trace",
+        None,
+    ),
+    (
+        "Telemetry",
+        "GET",
+        "/api/logs?page=1&limit=10",
+        "Query server logs",
+        None,
+    ),
+    (
+        "Security",
+        "GET",
+        "/api/security/bans",
+        "List banned addresses",
+        None,
+    ),
+    (
+        "Spam filter",
+        "POST",
+        "/api/spam-filter/classify",
+        "Classify a message",
+        Some(
+            r#"{"message": "Subject: test\r\n\r\ntest", "remoteIp": "127.0.0.1", "ehloDomain": "localhost", "authenticatedAs": null, "isTls": true, "envFrom": "sender@example.org", "envFromFlags": 0, "envRcptTo": ["rcpt@example.org"]}"#,
+        ),
+    ),
+];
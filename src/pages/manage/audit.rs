@@ -0,0 +1,401 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        download::{use_signed_download, SignedDownloadLink},
+        icon::IconArrowDownTray,
+        list::{
+            header::ColumnList,
+            pagination::Pagination,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        download::Export,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+    },
+    pages::List,
+};
+
+const PAGE_SIZE: u32 = 50;
+const AUDIT_URL: &str = "/manage/audit";
+
+static OBJECT_TYPES: &[(&str, &str)] = &[
+    ("settings", "Settings"),
+    ("principal", "Principals"),
+    ("dkim", "DKIM signatures"),
+    ("queue", "Message queue"),
+    ("report", "Reports"),
+    ("store", "Store maintenance"),
+];
+
+static DATE_INPUT_CLASS: &str = "py-2 px-3 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditEntry {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub actor: String,
+    #[serde(default)]
+    pub remote_ip: Option<String>,
+    pub action: String,
+    pub object_type: String,
+    #[serde(default)]
+    pub object_id: Option<String>,
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct AuditFilter {
+    actor: Option<String>,
+    object_type: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[component]
+pub fn AuditLog() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            let param = |name: &str| {
+                q.get(name)
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            AuditFilter {
+                actor: param("actor"),
+                object_type: param("type")
+                    .filter(|typ| OBJECT_TYPES.iter().any(|(id, _)| id == typ)),
+                from: param("from").filter(|date| parse_date(date).is_some()),
+                to: param("to").filter(|date| parse_date(date).is_some()),
+            }
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let entries = create_resource(
+        move || (page.get(), filter.get()),
+        move |(page, filter)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let (since, until) = filter.range();
+                HttpRequest::get("/api/audit")
+                    .with_authorization(&auth)
+                    .with_parameter("page", page.to_string())
+                    .with_parameter("limit", PAGE_SIZE.to_string())
+                    .with_optional_parameter("actor", filter.actor)
+                    .with_optional_parameter("objectType", filter.object_type)
+                    .with_optional_parameter("since", since)
+                    .with_optional_parameter("until", until)
+                    .send::<List<AuditEntry>>()
+                    .await
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<u32>);
+    let download = use_signed_download();
+    let navigate_with = move |update: &dyn Fn(&mut AuditFilter)| {
+        let mut filter = filter.get();
+        update(&mut filter);
+        use_navigate()(&filter.url(None), Default::default());
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Audit log"
+                subtitle="Administrative changes made to settings, accounts and server data"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=Signal::derive(move || filter.get().actor)
+                        on_search=move |value: String| {
+                            navigate_with(
+                                &|filter| {
+                                    filter.actor = (!value.is_empty()).then(|| value.clone());
+                                },
+                            );
+                        }
+                    />
+
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(
+                                &|filter| {
+                                    filter.object_type = (!value.is_empty()).then(|| value.clone());
+                                },
+                            );
+                        }
+                    >
+
+                        <option value="" selected=move || filter.get().object_type.is_none()>
+                            All objects
+                        </option>
+                        {OBJECT_TYPES
+                            .iter()
+                            .map(|(id, name)| {
+                                view! {
+                                    <option
+                                        value=*id
+                                        selected=move || {
+                                            filter.get().object_type.is_some_and(|typ| typ == *id)
+                                        }
+                                    >
+
+                                        {*name}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <input
+                        type="date"
+                        class=DATE_INPUT_CLASS
+                        title="From"
+                        prop:value=move || filter.get().from.unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(
+                                &|filter| {
+                                    filter.from = (!value.is_empty()).then(|| value.clone());
+                                },
+                            );
+                        }
+                    />
+
+                    <input
+                        type="date"
+                        class=DATE_INPUT_CLASS
+                        title="To"
+                        prop:value=move || filter.get().to.unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(
+                                &|filter| {
+                                    filter.to = (!value.is_empty()).then(|| value.clone());
+                                },
+                            );
+                        }
+                    />
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if download.is_pending() {
+                                "Preparing...".to_string()
+                            } else {
+                                "Export CSV".to_string()
+                            }
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            let filter = filter.get();
+                            let (since, until) = filter.range();
+                            download
+                                .request(Export::AuditLog {
+                                    actor: filter.actor,
+                                    object_type: filter.object_type,
+                                    since,
+                                    until,
+                                });
+                        })
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <SignedDownloadLink download/>
+
+                <Transition fallback=Skeleton>
+                    {move || match entries.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Audit log is not available"
+                                        subtitle="This server does not record administrative actions."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(entries)) if !entries.items.is_empty() => {
+                            total_results.set(Some(entries.total as u32));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Time".to_string(),
+                                        "Actor".to_string(),
+                                        "Action".to_string(),
+                                        "Object".to_string(),
+                                        "Details".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || entries.items.clone()
+                                            key=|entry| entry.id.clone()
+                                            let:entry
+                                        >
+                                            <AuditItem entry/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="No administrative actions were found with the selected criteria."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(&filter.get().url(Some(page)), Default::default());
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn AuditItem(entry: AuditEntry) -> impl IntoView {
+    let color = match entry.action.as_str() {
+        "create" | "insert" => Color::Green,
+        "update" | "reload" => Color::Blue,
+        "delete" | "purge" => Color::Red,
+        _ => Color::Gray,
+    };
+    let object_type = OBJECT_TYPES
+        .iter()
+        .find_map(|(id, name)| (*id == entry.object_type).then_some(*name))
+        .unwrap_or(entry.object_type.as_str())
+        .to_string();
+
+    view! {
+        <tr>
+            <ListItem>
+                <Timestamp
+                    date=entry.timestamp
+                    relative=true
+                    attr:class="text-sm text-gray-500"
+                />
+            </ListItem>
+            <ListItem>
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {entry.actor}
+                </span>
+                <span class="block text-xs font-mono text-gray-500">
+                    {entry.remote_ip.unwrap_or_default()}
+                </span>
+            </ListItem>
+            <ListItem>
+                <Badge color=color>{entry.action}</Badge>
+            </ListItem>
+            <ListItem>
+                <span class="block text-sm text-gray-800 dark:text-gray-200">{object_type}</span>
+                <span class="block text-xs font-mono text-gray-500 break-all">
+                    {entry.object_id.unwrap_or_default()}
+                </span>
+            </ListItem>
+            <ListTextItem>{entry.details.unwrap_or_default()}</ListTextItem>
+        </tr>
+    }
+}
+
+impl AuditFilter {
+    // Dates are picked in the browser's time zone, the server expects UTC instants
+    fn range(&self) -> (Option<String>, Option<String>) {
+        let bound = |date: &Option<String>, time: NaiveTime| {
+            date.as_deref()
+                .and_then(parse_date)
+                .and_then(|date| date.and_time(time).and_local_timezone(Local).earliest())
+                .map(|date| date.with_timezone(&Utc).to_rfc3339())
+        };
+        (
+            bound(&self.from, NaiveTime::MIN),
+            bound(
+                &self.to,
+                NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN),
+            ),
+        )
+    }
+
+    fn url(&self, page: Option<u32>) -> String {
+        UrlBuilder::new(AUDIT_URL)
+            .with_optional_parameter("actor", self.actor.clone())
+            .with_optional_parameter("type", self.object_type.clone())
+            .with_optional_parameter("from", self.from.clone())
+            .with_optional_parameter("to", self.to.clone())
+            .with_optional_parameter("page", page.map(|page| page.to_string()))
+            .finish()
+    }
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
@@ -0,0 +1,546 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconDocumentMagnifyingGlass,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        Color,
+    },
+    core::{
+        dns::dns_lookup,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        AccessToken,
+    },
+    pages::directory::dns::DnsRecordStatus,
+};
+
+const AUTOCONFIG_PATH: &str = "/mail/config-v1.1.xml";
+const AUTODISCOVER_PATH: &str = "/autodiscover/autodiscover.xml";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AutoconfigReport {
+    address: String,
+    domain: String,
+    autoconfig: ServedConfig,
+    autodiscover: ServedConfig,
+    records: Vec<RecordCheck>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ServedConfig {
+    // Public URL the client requests, the document itself is fetched from
+    // this server as clients reach it through the DNS records below.
+    url: String,
+    document: Result<String, String>,
+    servers: Vec<ClientServer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ClientServer {
+    protocol: String,
+    hostname: String,
+    port: String,
+    security: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RecordCheck {
+    name: String,
+    typ: &'static str,
+    purpose: &'static str,
+    records: Vec<String>,
+    status: DnsRecordStatus,
+}
+
+// Renders the account settings documents served to Thunderbird and Outlook for
+// an address, and checks the DNS records these clients use to find them.
+#[component]
+pub fn TroubleshootAutoconfig() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("troubleshoot-autoconfig")
+        .into_signal();
+    let report = create_rw_signal(None::<AutoconfigReport>);
+
+    let preview = create_action(move |target: &String| {
+        let auth = auth.get();
+        let target = target.clone();
+
+        async move {
+            match build_report(&auth, &target).await {
+                Ok(result) => report.set(Some(result)),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = preview.pending();
+
+    view! {
+        {move || match report.get() {
+            Some(result) => {
+                view! {
+                    <ReportView>
+                        <AutoconfigResult report=result/>
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Blue
+                                on_click=move |_| {
+                                    report.set(None);
+                                }
+                            />
+
+                        </div>
+                    </ReportView>
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="Client Autoconfiguration"
+                        subtitle="Preview the settings served to mail clients and check the DNS records they rely on"
+                    >
+                        <Alerts/>
+                        <FormSection>
+                            <FormItem
+                                label="E-mail Address"
+                                tooltip=concat!(
+                                    "Address or domain to preview. When only a domain ",
+                                    "is given, the documents are rendered for its ",
+                                    "postmaster address.",
+                                )
+                            >
+
+                                <InputText
+                                    element=FormElement::new("target", data)
+                                    placeholder="john@example.org"
+                                />
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Preview"
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=Callback::new(move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            preview
+                                                .dispatch(
+                                                    data.value::<String>("target").unwrap_or_default(),
+                                                );
+                                        }
+                                    });
+                                })
+                            >
+
+                                <IconDocumentMagnifyingGlass/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn AutoconfigResult(report: AutoconfigReport) -> impl IntoView {
+    view! {
+        <ReportSection title="Request">
+            <ReportItem label="Address">
+                <ReportTextValue value=report.address/>
+            </ReportItem>
+            <ReportItem label="Domain">
+                <ReportTextValue value=report.domain/>
+            </ReportItem>
+        </ReportSection>
+        <ServedConfigResult title="Thunderbird Autoconfig" config=report.autoconfig/>
+        <ServedConfigResult title="Outlook Autodiscover" config=report.autodiscover/>
+        <ReportSection title="DNS Records">
+            {report
+                .records
+                .into_iter()
+                .map(|check| {
+                    view! {
+                        <ReportItem label=check.name>
+                            <div class="mt-2.5 space-y-1 text-sm">
+                                <div class="flex items-center gap-x-2">
+                                    <Badge color=check.status.color()>{check.status.label()}</Badge>
+                                    <span class="font-mono text-gray-800 dark:text-gray-200">
+                                        {check.typ}
+                                    </span>
+                                </div>
+                                <span class="block text-xs text-gray-500">{check.purpose}</span>
+                                {check
+                                    .records
+                                    .into_iter()
+                                    .map(|record| {
+                                        view! {
+                                            <span class="block text-xs font-mono break-all text-gray-600 dark:text-gray-400">
+                                                {record}
+                                            </span>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        </ReportItem>
+                    }
+                })
+                .collect_view()}
+        </ReportSection>
+    }
+}
+
+#[component]
+fn ServedConfigResult(#[prop(into)] title: String, config: ServedConfig) -> impl IntoView {
+    let (color, status) = match &config.document {
+        Ok(_) if config.servers.is_empty() => (Color::Yellow, "No servers"),
+        Ok(_) => (Color::Green, "Served"),
+        Err(_) => (Color::Red, "Not served"),
+    };
+
+    view! {
+        <ReportSection title>
+            <ReportItem label="Client URL">
+                <ReportTextValue value=config.url/>
+            </ReportItem>
+            <ReportItem label="Result">
+                <div class="mt-2.5">
+                    <Badge color>{status}</Badge>
+                </div>
+            </ReportItem>
+            <ReportItem label="Servers" hide=config.servers.is_empty()>
+                <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                        {config
+                            .servers
+                            .into_iter()
+                            .map(|server| {
+                                view! {
+                                    <tr>
+                                        <td class="py-1.5 pe-4 font-medium uppercase text-gray-800 dark:text-gray-200">
+                                            {server.protocol}
+                                        </td>
+                                        <td class="py-1.5 pe-4 font-mono text-gray-600 dark:text-gray-400">
+                                            {format!("{}:{}", server.hostname, server.port)}
+                                        </td>
+                                        <td class="py-1.5 text-gray-600 dark:text-gray-400">
+                                            {server.security}
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </ReportItem>
+            {match config.document {
+                Ok(document) => {
+                    view! {
+                        <ReportItem label="Document">
+                            <pre class="mt-2.5 p-3 max-h-96 overflow-auto rounded-lg bg-gray-50 text-xs font-mono text-gray-800 dark:bg-gray-800 dark:text-gray-200">
+                                {document}
+                            </pre>
+                        </ReportItem>
+                    }
+                }
+                Err(error) => {
+                    view! {
+                        <ReportItem label="Error">
+                            <ReportTextValue value=error/>
+                        </ReportItem>
+                    }
+                }
+            }}
+
+        </ReportSection>
+    }
+}
+
+async fn build_report(auth: &AccessToken, target: &str) -> http::Result<AutoconfigReport> {
+    let (address, domain) = match target.split_once('@') {
+        Some((_, domain)) => (target.to_string(), domain.to_string()),
+        None => (format!("postmaster@{target}"), target.to_string()),
+    };
+
+    let autoconfig = match HttpRequest::get(AUTOCONFIG_PATH)
+        .with_base_url(auth)
+        .with_parameter("emailaddress", address.clone())
+        .send_raw()
+        .await
+    {
+        Ok(response) => {
+            let document = String::from_utf8_lossy(&response).into_owned();
+            let servers = autoconfig_servers(&document);
+            Ok((document, servers))
+        }
+        Err(err) => Err(err),
+    };
+    let autodiscover = match HttpRequest::post(AUTODISCOVER_PATH)
+        .with_base_url(auth)
+        .with_header("Content-Type", "text/xml")
+        .with_raw_body(autodiscover_request(&address))
+        .send_raw()
+        .await
+    {
+        Ok(response) => {
+            let document = String::from_utf8_lossy(&response).into_owned();
+            let servers = autodiscover_servers(&document);
+            Ok((document, servers))
+        }
+        Err(err) => Err(err),
+    };
+
+    // Records are compared against the hostnames the documents point clients to
+    let hostnames = [&autoconfig, &autodiscover]
+        .into_iter()
+        .filter_map(|result| result.as_ref().ok())
+        .flat_map(|(_, servers)| servers.iter().map(|server| server.hostname.clone()))
+        .collect::<Vec<_>>();
+
+    let mut records = Vec::new();
+    for (name, typ, purpose) in [
+        (
+            format!("autoconfig.{domain}"),
+            "CNAME",
+            "Thunderbird requests the autoconfig document from this host",
+        ),
+        (
+            format!("autodiscover.{domain}"),
+            "CNAME",
+            "Outlook requests the autodiscover document from this host",
+        ),
+        (
+            format!("_autodiscover._tcp.{domain}"),
+            "SRV",
+            "Outlook falls back to this record when the autodiscover host does not resolve",
+        ),
+        (
+            format!("_imaps._tcp.{domain}"),
+            "SRV",
+            "Announces the IMAP server to clients that support service records",
+        ),
+        (
+            format!("_submissions._tcp.{domain}"),
+            "SRV",
+            "Announces the submission server to clients that support service records",
+        ),
+    ] {
+        records.push(check_record(auth, name, typ, purpose, &hostnames).await?);
+    }
+
+    Ok(AutoconfigReport {
+        autoconfig: served_config(
+            format!("https://autoconfig.{domain}{AUTOCONFIG_PATH}?emailaddress={address}"),
+            autoconfig,
+        )?,
+        autodiscover: served_config(
+            format!("https://autodiscover.{domain}{AUTODISCOVER_PATH}"),
+            autodiscover,
+        )?,
+        address,
+        domain,
+        records,
+    })
+}
+
+fn served_config(
+    url: String,
+    result: http::Result<(String, Vec<ClientServer>)>,
+) -> http::Result<ServedConfig> {
+    match result {
+        Ok((document, servers)) => Ok(ServedConfig {
+            url,
+            document: Ok(document),
+            servers,
+        }),
+        Err(http::Error::Unauthorized) => Err(http::Error::Unauthorized),
+        Err(http::Error::NotFound) => Ok(ServedConfig {
+            url,
+            document: Err("The server does not serve this document.".to_string()),
+            servers: vec![],
+        }),
+        Err(err) => Ok(ServedConfig {
+            url,
+            document: Err(Alert::from(err).message),
+            servers: vec![],
+        }),
+    }
+}
+
+// Host names are resolved as a CNAME first, an address record pointing at
+// this server works as well.
+async fn check_record(
+    auth: &AccessToken,
+    name: String,
+    typ: &'static str,
+    purpose: &'static str,
+    hostnames: &[String],
+) -> http::Result<RecordCheck> {
+    let mut records = dns_lookup(auth, &name, typ).await?;
+    let mut typ = typ;
+    if records.is_empty() && typ == "CNAME" {
+        records = dns_lookup(auth, &name, "A").await?;
+        typ = "A";
+    }
+
+    let points_to_server = records.iter().any(|record| {
+        record
+            .split_whitespace()
+            .last()
+            .map(|target| target.trim_end_matches('.'))
+            .is_some_and(|target| {
+                hostnames
+                    .iter()
+                    .any(|hostname| hostname.eq_ignore_ascii_case(target))
+            })
+    });
+    let status = if records.is_empty() {
+        DnsRecordStatus::Missing
+    } else if typ == "A" || hostnames.is_empty() || points_to_server {
+        DnsRecordStatus::Verified
+    } else {
+        DnsRecordStatus::Mismatch
+    };
+
+    Ok(RecordCheck {
+        name,
+        typ,
+        purpose,
+        records,
+        status,
+    })
+}
+
+fn autodiscover_request(address: &str) -> String {
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n",
+            "<Autodiscover xmlns=\"http://schemas.microsoft.com/exchange/autodiscover/",
+            "outlook/requestschema/2006\">\n",
+            "  <Request>\n",
+            "    <EMailAddress>{}</EMailAddress>\n",
+            "    <AcceptableResponseSchema>http://schemas.microsoft.com/exchange/",
+            "autodiscover/outlook/responseschema/2006a</AcceptableResponseSchema>\n",
+            "  </Request>\n",
+            "</Autodiscover>\n",
+        ),
+        address
+    )
+}
+
+fn autoconfig_servers(document: &str) -> Vec<ClientServer> {
+    ["incomingServer", "outgoingServer"]
+        .into_iter()
+        .flat_map(|tag| xml_elements(document, tag))
+        .map(|(attributes, contents)| ClientServer {
+            protocol: xml_attribute(attributes, "type").unwrap_or_default(),
+            hostname: xml_text(contents, "hostname").unwrap_or_default(),
+            port: xml_text(contents, "port").unwrap_or_default(),
+            security: xml_text(contents, "socketType").unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn autodiscover_servers(document: &str) -> Vec<ClientServer> {
+    xml_elements(document, "Protocol")
+        .into_iter()
+        .filter_map(|(_, contents)| {
+            Some(ClientServer {
+                protocol: xml_text(contents, "Type")?,
+                hostname: xml_text(contents, "Server")?,
+                port: xml_text(contents, "Port").unwrap_or_default(),
+                security: match xml_text(contents, "Encryption") {
+                    Some(encryption) => encryption,
+                    None if xml_text(contents, "SSL").as_deref() == Some("off") => {
+                        "None".to_string()
+                    }
+                    None => "SSL".to_string(),
+                },
+            })
+        })
+        .collect()
+}
+
+// Both documents are small and machine generated, a lenient scan for the few
+// elements shown is enough and avoids pulling in an XML parser.
+fn xml_elements<'x>(document: &'x str, tag: &str) -> Vec<(&'x str, &'x str)> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut elements = Vec::new();
+    let mut rest = document;
+
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        if !after.starts_with(['>', ' ', '\t', '\r', '\n']) {
+            rest = after;
+            continue;
+        }
+        let Some(tag_end) = after.find('>') else {
+            break;
+        };
+        let Some(end) = after[tag_end..].find(&close) else {
+            break;
+        };
+        elements.push((&after[..tag_end], &after[tag_end + 1..tag_end + end]));
+        rest = &after[tag_end + end + close.len()..];
+    }
+
+    elements
+}
+
+fn xml_text(document: &str, tag: &str) -> Option<String> {
+    xml_elements(document, tag)
+        .first()
+        .map(|(_, contents)| contents.trim().to_string())
+        .filter(|contents| !contents.is_empty())
+}
+
+fn xml_attribute(attributes: &str, name: &str) -> Option<String> {
+    let (_, value) = attributes.split_once(&format!("{name}="))?;
+    let quote = value.chars().next().filter(|ch| matches!(ch, '"' | '\''))?;
+    value[1..]
+        .split(quote)
+        .next()
+        .map(|value| value.to_string())
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_autoconfig(self) -> Self {
+        self.new_schema("troubleshoot-autoconfig")
+            .new_field("target")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required],
+            )
+            .typ(Type::Input)
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,344 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::TextArea, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconShieldCheck,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        Color,
+    },
+    core::{
+        dkim::{DkimResult, DkimSignature, Message, SignedHeader},
+        dns::dns_lookup,
+        http,
+        oauth::use_authorization,
+        schema::Schemas,
+    },
+};
+
+// Header fields that should always be covered by the signature
+static IMPORTANT_HEADERS: &[&str] = &[
+    "From",
+    "To",
+    "Cc",
+    "Subject",
+    "Date",
+    "Reply-To",
+    "Message-ID",
+    "Content-Type",
+    "MIME-Version",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SignatureReport {
+    signature: Option<DkimSignature>,
+    signed_headers: Vec<SignedHeader>,
+    unsigned_headers: Vec<String>,
+    key_records: Vec<String>,
+    result: DkimResult,
+}
+
+#[component]
+pub fn TroubleshootDkim() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("troubleshoot-dkim")
+        .into_signal();
+    let reports = create_rw_signal(None::<Vec<SignatureReport>>);
+
+    let verify = create_action(move |raw: &String| {
+        let raw = raw.clone();
+        let auth = auth.get();
+
+        async move {
+            let message = match Message::parse(&raw) {
+                Ok(message) => message,
+                Err(err) => {
+                    alert.set(Alert::error("Failed to parse message").with_details(err));
+                    return;
+                }
+            };
+
+            let mut results = Vec::new();
+            for signature in message.signatures() {
+                let signature = match signature {
+                    Ok(signature) => signature,
+                    Err(err) => {
+                        results.push(SignatureReport {
+                            signature: None,
+                            signed_headers: vec![],
+                            unsigned_headers: vec![],
+                            key_records: vec![],
+                            result: DkimResult::PermError(err),
+                        });
+                        continue;
+                    }
+                };
+
+                let key_name = signature.key_name();
+                let (key_records, result) = match dns_lookup(&auth, &key_name, "TXT").await {
+                    Ok(records) => {
+                        let result = signature.verify(&message, &records).await;
+                        (records, result)
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => (
+                        vec![],
+                        DkimResult::TempError(format!(
+                            "Failed to fetch the key from {key_name}: {}",
+                            Alert::from(err).message
+                        )),
+                    ),
+                };
+
+                let signed_headers = signature.signed_header_values(&message);
+                let unsigned_headers = IMPORTANT_HEADERS
+                    .iter()
+                    .filter_map(|name| {
+                        let present = message
+                            .headers
+                            .iter()
+                            .filter(|header| header.name.eq_ignore_ascii_case(name))
+                            .count();
+                        let signed = signed_headers
+                            .iter()
+                            .filter(|header| {
+                                header.value.is_some() && header.name.eq_ignore_ascii_case(name)
+                            })
+                            .count();
+                        (present > signed).then(|| {
+                            if signed > 0 {
+                                format!("{name} (an additional instance is not signed)")
+                            } else {
+                                name.to_string()
+                            }
+                        })
+                    })
+                    .collect();
+
+                results.push(SignatureReport {
+                    signature: Some(signature),
+                    signed_headers,
+                    unsigned_headers,
+                    key_records,
+                    result,
+                });
+            }
+
+            if results.is_empty() {
+                alert.set(Alert::warning(
+                    "The message does not contain any DKIM signatures.",
+                ));
+            } else {
+                reports.set(Some(results));
+            }
+        }
+    });
+    let pending = verify.pending();
+
+    view! {
+        {move || match reports.get() {
+            Some(results) => {
+                view! {
+                    <ReportView>
+                        {results
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, report)| view! { <SignatureResult idx report/> })
+                            .collect_view()}
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Blue
+                                on_click=move |_| {
+                                    reports.set(None);
+                                }
+                            />
+
+                        </div>
+                    </ReportView>
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="DKIM Verifier"
+                        subtitle="Check the DKIM signatures of a message and see which header fields they cover"
+                    >
+                        <Alerts/>
+                        <FormSection>
+                            <FormItem
+                                label="Raw Message"
+                                tooltip=concat!(
+                                    "The complete message including headers, as ",
+                                    "received. Any change to signed header fields or to ",
+                                    "the body will break the signature.",
+                                )
+                            >
+
+                                <TextArea
+                                    element=FormElement::new("message", data)
+                                    placeholder="DKIM-Signature: v=1; a=rsa-sha256; ..."
+                                />
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Verify"
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=Callback::new(move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            verify
+                                                .dispatch(
+                                                    data.value::<String>("message").unwrap_or_default(),
+                                                );
+                                        }
+                                    });
+                                })
+                            >
+
+                                <IconShieldCheck/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn SignatureResult(idx: usize, report: SignatureReport) -> impl IntoView {
+    let (color, result, reason) = match report.result {
+        DkimResult::Pass => (Color::Green, "Pass", None),
+        DkimResult::Fail(reason) => (Color::Red, "Fail", Some(reason)),
+        DkimResult::PermError(reason) => (Color::Red, "Permanent Error", Some(reason)),
+        DkimResult::TempError(reason) => (Color::Yellow, "Temporary Error", Some(reason)),
+    };
+    let title = match &report.signature {
+        Some(signature) => format!("Signature #{} ({})", idx + 1, signature.domain),
+        None => format!("Signature #{}", idx + 1),
+    };
+
+    view! {
+        <ReportSection title>
+            <ReportItem label="Result">
+                <Badge color>{result}</Badge>
+            </ReportItem>
+            {reason
+                .map(|reason| {
+                    view! {
+                        <ReportItem label="Reason">
+                            <ReportTextValue value=reason/>
+                        </ReportItem>
+                    }
+                })}
+            {report
+                .signature
+                .map(|signature| {
+                    let key_name = signature.key_name();
+                    let algorithm = signature.algorithm_name();
+                    let canonicalization = signature.canonicalization_name();
+                    let body_length = signature.body_length.map(|length| {
+                        format!(
+                            "Only the first {length} bytes of the body are signed, content can be appended without breaking the signature",
+                        )
+                    });
+                    view! {
+                        <ReportItem label="Signing Domain">
+                            <ReportTextValue value=signature.domain/>
+                        </ReportItem>
+                        <ReportItem label="Selector">
+                            <ReportTextValue value=signature.selector/>
+                        </ReportItem>
+                        <ReportItem label="Algorithm">
+                            <ReportTextValue value=algorithm/>
+                        </ReportItem>
+                        <ReportItem label="Canonicalization">
+                            <ReportTextValue value=canonicalization/>
+                        </ReportItem>
+                        <ReportItem label="Identity" hide=signature.identity.is_none()>
+                            <ReportTextValue value=signature.identity.unwrap_or_default()/>
+                        </ReportItem>
+                        <ReportItem label="Body Length" hide=body_length.is_none()>
+                            <ReportTextValue value=body_length.unwrap_or_default()/>
+                        </ReportItem>
+                        <ReportItem label="Key Record">
+                            <div class="text-sm text-gray-800 dark:text-gray-200">
+                                <span class="block font-mono">{key_name}</span>
+                                {report
+                                    .key_records
+                                    .into_iter()
+                                    .map(|record| {
+                                        view! {
+                                            <span class="block mt-1 text-xs font-mono break-all text-gray-500">
+                                                {record}
+                                            </span>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        </ReportItem>
+                    }
+                })}
+            <ReportItem label="Signed Header Fields" hide=report.signed_headers.is_empty()>
+                <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                        {report
+                            .signed_headers
+                            .into_iter()
+                            .map(|header| {
+                                let is_present = header.value.is_some();
+                                view! {
+                                    <tr class="align-top">
+                                        <td class="py-1.5 pe-4 font-medium whitespace-nowrap text-gray-800 dark:text-gray-200">
+                                            {header.name}
+                                        </td>
+                                        <td
+                                            class="py-1.5 break-all"
+                                            class:text-gray-600=is_present
+                                            class:text-gray-400=!is_present
+                                            class:italic=!is_present
+                                        >
+                                            {header
+                                                .value
+                                                .unwrap_or_else(|| {
+                                                    "Not present, adding this field would break the signature"
+                                                        .to_string()
+                                                })}
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </ReportItem>
+            <ReportItem label="Not Signed" hide=report.unsigned_headers.is_empty()>
+                <ReportTextValue value=report.unsigned_headers.join(", ")/>
+            </ReportItem>
+        </ReportSection>
+    }
+}
@@ -0,0 +1,105 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+
+use crate::{
+    components::{
+        form::{input::InputText, select::Select, Form, FormElement, FormItem, FormSection},
+        upload::FileUpload,
+    },
+    core::{
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+        upload::UploadKind,
+    },
+};
+
+#[component]
+pub fn ImportUpload() -> impl IntoView {
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("import")
+        .into_signal();
+    let kind = Signal::derive(move || {
+        match data
+            .get()
+            .value::<String>("type")
+            .unwrap_or_default()
+            .as_str()
+        {
+            "migration-bundle" => UploadKind::MigrationBundle,
+            "license" => UploadKind::License,
+            _ => UploadKind::Mbox,
+        }
+    });
+    let account = Signal::derive(move || {
+        if kind.get() == UploadKind::Mbox {
+            data.get().value::<String>("account")
+        } else {
+            None
+        }
+    });
+
+    view! {
+        <Form
+            title="Import"
+            subtitle="Upload mailbox archives, migration bundles and license files"
+        >
+            <FormSection>
+                <FormItem label="Type">
+                    <Select element=FormElement::new("type", data)/>
+                </FormItem>
+                <FormItem
+                    label="Account"
+                    tooltip="Account the messages in the archive are imported into"
+                    hide=Signal::derive(move || kind.get() != UploadKind::Mbox)
+                >
+                    <InputText placeholder="john" element=FormElement::new("account", data)/>
+                </FormItem>
+                <FormItem label="File">
+                    <FileUpload
+                        kind=kind
+                        account=account
+                        on_validate=move |_| {
+                            data.try_update(|data| data.validate_form()).unwrap_or_default()
+                        }
+
+                        on_complete=move |_| {
+                            data.update(|data| {
+                                data.reset();
+                            });
+                        }
+                    />
+
+                </FormItem>
+            </FormSection>
+        </Form>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_import(self) -> Self {
+        self.new_schema("import")
+            .new_field("type")
+            .default("mbox")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("mbox", "Mailbox archive (mbox)"),
+                    ("migration-bundle", "Migration bundle"),
+                    ("license", "License file"),
+                ]),
+            })
+            .build()
+            .new_field("account")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("type", ["mbox"])
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,346 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde_json::Value;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconDocumentMagnifyingGlass,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        Color,
+    },
+    core::{
+        http,
+        jmap::{fetch_session, JmapAccount, JmapSession},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+};
+
+// Objects deeper than this start collapsed in the tree
+const EXPANDED_DEPTH: usize = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SessionReport {
+    account: String,
+    session: JmapSession,
+    raw: Value,
+}
+
+// Shows the JMAP session object an account receives, which is what clients
+// use to discover capabilities, account ids and endpoints.
+#[component]
+pub fn TroubleshootJmap() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("troubleshoot-jmap")
+        .into_signal();
+    let report = create_rw_signal(None::<SessionReport>);
+
+    let inspect = create_action(move |account: &String| {
+        let auth = auth.get();
+        let account = account.clone();
+
+        async move {
+            match fetch_session(&auth, &account).await {
+                Ok((session, raw)) => report.set(Some(SessionReport {
+                    account,
+                    session,
+                    raw,
+                })),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::error(format!("Account {account} does not exist.")));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = inspect.pending();
+
+    view! {
+        {move || match report.get() {
+            Some(result) => {
+                view! {
+                    <ReportView>
+                        <SessionResult report=result/>
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Blue
+                                on_click=move |_| {
+                                    report.set(None);
+                                }
+                            />
+
+                        </div>
+                    </ReportView>
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="JMAP Session"
+                        subtitle="Inspect the capabilities, accounts and endpoints announced to JMAP clients"
+                    >
+                        <Alerts/>
+                        <FormSection>
+                            <FormItem
+                                label="Account"
+                                tooltip="The session is rendered as the server returns it to this account"
+                            >
+                                <InputText
+                                    element=FormElement::new("account", data)
+                                    placeholder="john"
+                                />
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Inspect"
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=Callback::new(move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            inspect
+                                                .dispatch(
+                                                    data.value::<String>("account").unwrap_or_default(),
+                                                );
+                                        }
+                                    });
+                                })
+                            >
+
+                                <IconDocumentMagnifyingGlass/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn SessionResult(report: SessionReport) -> impl IntoView {
+    let session = report.session;
+    let endpoints = [
+        ("API", session.api_url),
+        ("Download", session.download_url),
+        ("Upload", session.upload_url),
+        ("Event Source", session.event_source_url),
+    ];
+    let primary_accounts = session.primary_accounts;
+
+    view! {
+        <ReportSection title="Session">
+            <ReportItem label="Account">
+                <ReportTextValue value=report.account/>
+            </ReportItem>
+            <ReportItem label="Username">
+                <ReportTextValue value=session.username/>
+            </ReportItem>
+            <ReportItem label="State">
+                <ReportTextValue value=session.state/>
+            </ReportItem>
+        </ReportSection>
+        <ReportSection title="Endpoints">
+            {endpoints
+                .into_iter()
+                .map(|(label, url)| {
+                    let is_missing = url.is_empty();
+                    view! {
+                        <ReportItem label>
+                            <div class="mt-2.5 flex items-center gap-x-2">
+                                <span class="text-sm font-mono break-all text-gray-800 dark:text-gray-200">
+                                    {url}
+                                </span>
+                                {is_missing
+                                    .then(|| view! { <Badge color=Color::Red>Missing</Badge> })}
+                            </div>
+                        </ReportItem>
+                    }
+                })
+                .collect_view()}
+        </ReportSection>
+        <ReportSection title="Capabilities">
+            {session
+                .capabilities
+                .into_iter()
+                .map(|(capability, options)| {
+                    view! {
+                        <ReportItem label=capability>
+                            <div class="mt-2.5">{json_tree(&options, 0)}</div>
+                        </ReportItem>
+                    }
+                })
+                .collect_view()}
+        </ReportSection>
+        <ReportSection title="Accounts">
+            {session
+                .accounts
+                .into_iter()
+                .map(|(id, account)| {
+                    let primary_for = primary_accounts
+                        .iter()
+                        .filter(|(_, primary)| *primary == &id)
+                        .map(|(capability, _)| capability.clone())
+                        .collect::<Vec<_>>();
+                    view! { <SessionAccount id account primary_for/> }
+                })
+                .collect_view()}
+        </ReportSection>
+        <ReportSection title="Raw Session">
+            <div class="sm:col-span-12">{json_tree(&report.raw, 0)}</div>
+        </ReportSection>
+    }
+}
+
+#[component]
+fn SessionAccount(id: String, account: JmapAccount, primary_for: Vec<String>) -> impl IntoView {
+    let is_primary = !primary_for.is_empty();
+
+    view! {
+        <ReportItem label=id>
+            <div class="mt-2.5 space-y-2 text-sm">
+                <div class="flex flex-wrap items-center gap-2">
+                    <span class="font-semibold text-gray-800 dark:text-gray-200">
+                        {account.name}
+                    </span>
+                    {account.is_personal.then(|| view! { <Badge color=Color::Blue>Personal</Badge> })}
+                    {account
+                        .is_read_only
+                        .then(|| view! { <Badge color=Color::Yellow>Read-only</Badge> })}
+                    {is_primary.then(|| view! { <Badge color=Color::Green>Primary</Badge> })}
+                </div>
+                {is_primary
+                    .then(|| {
+                        view! {
+                            <span class="block text-xs text-gray-500">
+                                {format!("Primary account for {}", primary_for.join(", "))}
+                            </span>
+                        }
+                    })}
+                <div>
+                    <span class="block text-xs font-medium uppercase text-gray-400 dark:text-gray-500">
+                        Account Capabilities
+                    </span>
+                    {account
+                        .account_capabilities
+                        .into_iter()
+                        .map(|(capability, options)| {
+                            view! {
+                                <details class="mt-1">
+                                    <summary class="cursor-pointer font-mono text-gray-600 dark:text-gray-400">
+                                        {capability}
+                                    </summary>
+                                    <div class="ps-4 pt-1">{json_tree(&options, 1)}</div>
+                                </details>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+            </div>
+        </ReportItem>
+    }
+}
+
+// Renders a JSON value as nested collapsible lists, objects and arrays deeper
+// than EXPANDED_DEPTH start collapsed.
+fn json_tree(value: &Value, depth: usize) -> View {
+    match value {
+        Value::Object(map) if map.is_empty() => json_scalar("{}"),
+        Value::Array(list) if list.is_empty() => json_scalar("[]"),
+        Value::Object(map) => {
+            json_children(map.iter().map(|(key, value)| (key.clone(), value)), depth)
+        }
+        Value::Array(list) => json_children(
+            list.iter()
+                .enumerate()
+                .map(|(idx, value)| (idx.to_string(), value)),
+            depth,
+        ),
+        value => json_scalar(value.to_string()),
+    }
+}
+
+fn json_children<'x>(children: impl Iterator<Item = (String, &'x Value)>, depth: usize) -> View {
+    view! {
+        <ul class="space-y-0.5 text-xs font-mono">
+            {children
+                .map(|(key, value)| {
+                    match value {
+                        Value::Object(map) if !map.is_empty() => {
+                            json_branch(key, format!("{{{}}}", map.len()), value, depth)
+                        }
+                        Value::Array(list) if !list.is_empty() => {
+                            json_branch(key, format!("[{}]", list.len()), value, depth)
+                        }
+                        _ => {
+                            view! {
+                                <li>
+                                    <span class="text-gray-500">{key}": "</span>
+                                    {json_tree(value, depth + 1)}
+                                </li>
+                            }
+                                .into_view()
+                        }
+                    }
+                })
+                .collect_view()}
+        </ul>
+    }
+    .into_view()
+}
+
+fn json_branch(key: String, summary: String, value: &Value, depth: usize) -> View {
+    view! {
+        <li>
+            <details open={ depth < EXPANDED_DEPTH }>
+                <summary class="cursor-pointer">
+                    <span class="text-gray-800 dark:text-gray-200">{key}</span>
+                    <span class="ms-1 text-gray-400">{summary}</span>
+                </summary>
+                <div class="ps-4">{json_tree(value, depth + 1)}</div>
+            </details>
+        </li>
+    }
+    .into_view()
+}
+
+fn json_scalar(text: impl Into<String>) -> View {
+    let text = text.into();
+    view! { <span class="break-all text-blue-700 dark:text-blue-400">{text}</span> }.into_view()
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_jmap_session(self) -> Self {
+        self.new_schema("troubleshoot-jmap")
+            .new_field("account")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .typ(Type::Input)
+            .build()
+            .build()
+    }
+}
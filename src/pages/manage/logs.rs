@@ -14,20 +14,26 @@ use crate::pages::queue::messages::deserialize_datetime;
 use crate::{
     components::{
         badge::Badge,
+        download::{use_signed_download, SignedDownloadLink},
+        icon::IconArrowDownTray,
         list::{
-            header::ColumnList, pagination::Pagination, toolbar::SearchBox, Footer, ListItem,
-            ListSection, ListTable, Toolbar, ZeroResults,
+            header::ColumnList,
+            pagination::Pagination,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, Toolbar, ZeroResults,
         },
         messages::alert::{use_alerts, Alert},
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
+        download::Export,
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
     },
-    pages::{FormatDateTime, List},
+    pages::List,
 };
 
 const PAGE_SIZE: u32 = 50;
@@ -84,6 +90,7 @@ pub fn Logs() -> impl IntoView {
     );
 
     let total_results = create_rw_signal(None::<u32>);
+    let download = use_signed_download();
 
     view! {
         <ListSection>
@@ -101,8 +108,28 @@ pub fn Logs() -> impl IntoView {
                         }
                     />
 
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if download.is_pending() {
+                                "Preparing...".to_string()
+                            } else {
+                                "Export".to_string()
+                            }
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            download.request(Export::Logs { filter: filter.get() });
+                        })
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+
                 </Toolbar>
 
+                <SignedDownloadLink download/>
+
                 <Transition fallback=Skeleton>
                     {move || match logs.get() {
                         None => None,
@@ -180,12 +207,14 @@ pub fn Logs() -> impl IntoView {
 
 #[component]
 fn LogItem(log: LogEntry) -> impl IntoView {
-    let timestamp = log.timestamp.format_date_time();
-
     view! {
         <tr>
             <ListItem>
-                <span class="text-sm text-gray-500">{timestamp}</span>
+                <Timestamp
+                    date=log.timestamp
+                    relative=true
+                    attr:class="text-sm text-gray-500"
+                />
             </ListItem>
 
             <ListItem>
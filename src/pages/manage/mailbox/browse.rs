@@ -0,0 +1,438 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::{IconInbox, IconRefresh},
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::ToolbarButton, Footer, ListItem,
+            ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        format::format_bytes,
+        http,
+        jmap::{fetch_emails, fetch_mailboxes, open_browse_session, EmailSummary},
+        oauth::use_authorization,
+        schema::Schemas,
+        url::UrlBuilder,
+    },
+    pages::{
+        directory::quota::fetch_usage,
+        manage::mailbox::{mailbox_tree, MailboxNode},
+        FormatDateTime,
+    },
+};
+
+const PAGE_SIZE: u32 = 25;
+
+#[component]
+pub fn MailboxBrowseOpen() -> impl IntoView {
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("mailbox-browse")
+        .into_signal();
+
+    view! {
+        <Form
+            title="Browse mailbox"
+            subtitle="Inspect the mailbox tree and message headers of an account"
+        >
+            <FormSection>
+                <FormItem
+                    label="Account"
+                    tooltip="Opening a mailbox is recorded in the audit log"
+                >
+                    <InputText placeholder="john" element=FormElement::new("account", data)/>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Open"
+                    color=Color::Blue
+                    on_click=move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                use_navigate()(
+                                    &UrlBuilder::new("/manage/mailbox")
+                                        .with_subpath(
+                                            data.value::<String>("account").unwrap_or_default(),
+                                        )
+                                        .finish(),
+                                    Default::default(),
+                                );
+                            }
+                        });
+                    }
+                />
+
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+pub fn MailboxBrowser() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let name = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let session = create_local_resource(
+        move || name.get(),
+        move |name| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let session = open_browse_session(&auth, &name).await?;
+                let mailboxes = fetch_mailboxes(&auth, &session.account_id).await?;
+                // Sizes are informative only, the tree is still shown without them
+                let sizes = fetch_usage(&auth, &name)
+                    .await
+                    .map(|usage| {
+                        usage
+                            .mailboxes
+                            .into_iter()
+                            .map(|mailbox| (mailbox.name, mailbox.size))
+                            .collect::<AHashMap<_, _>>()
+                    })
+                    .unwrap_or_default();
+
+                Ok::<_, http::Error>(Arc::new((
+                    session.account_id,
+                    mailbox_tree(mailboxes),
+                    sizes,
+                )))
+            }
+        },
+    );
+    let account_id = create_memo(move |_| {
+        session
+            .get()
+            .and_then(|result| result.ok())
+            .map(|result| result.0.clone())
+    });
+    let selected = create_memo(move |_| {
+        let selected = query.with(|q| q.get("mailbox").cloned());
+        session.with(|result| {
+            let tree = &result.as_ref()?.as_ref().ok()?.1;
+            tree.iter()
+                .find(|node| Some(&node.mailbox.id) == selected.as_ref())
+                .or_else(|| {
+                    tree.iter()
+                        .find(|node| node.mailbox.role.as_deref() == Some("inbox"))
+                })
+                .or_else(|| tree.first())
+                .cloned()
+        })
+    });
+
+    let emails = create_local_resource(
+        move || {
+            (
+                account_id.get(),
+                selected.get().map(|node| node.mailbox.id),
+                page.get(),
+            )
+        },
+        move |(account_id, mailbox_id, page)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match (account_id, mailbox_id) {
+                    (Some(account_id), Some(mailbox_id)) => {
+                        fetch_emails(&auth, &account_id, &mailbox_id, page, PAGE_SIZE)
+                            .await
+                            .map(|(emails, total)| Some(Arc::new((emails, total))))
+                    }
+                    _ => Ok(None),
+                }
+            }
+        },
+    );
+
+    let mailbox_url = move |mailbox_id: &str, page: u32| {
+        UrlBuilder::new("/manage/mailbox")
+            .with_subpath(name.get())
+            .with_parameter("mailbox", mailbox_id)
+            .with_parameter("page", page.to_string())
+            .finish()
+    };
+
+    view! {
+        <ListSection>
+            <div class="grid lg:grid-cols-4 gap-6">
+                <div class="lg:col-span-1">
+                    <div class="bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-slate-900 dark:border-gray-700">
+                        <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                                Mailboxes
+                            </h2>
+                            <p class="text-sm text-gray-600 dark:text-gray-400">
+                                {move || name.get()}
+                            </p>
+                        </div>
+                        <Transition fallback=Skeleton>
+                            {move || match session.get() {
+                                None => None,
+                                Some(Err(http::Error::Unauthorized)) => {
+                                    use_navigate()("/login", Default::default());
+                                    Some(view! { <div></div> }.into_view())
+                                }
+                                Some(Err(http::Error::NotFound)) => {
+                                    alert
+                                        .set(
+                                            Alert::error(
+                                                format!("Account {} does not exist.", name.get()),
+                                            ),
+                                        );
+                                    use_navigate()("/manage/mailbox", Default::default());
+                                    Some(view! { <div></div> }.into_view())
+                                }
+                                Some(Err(err)) => {
+                                    alert.set(Alert::from(err));
+                                    Some(view! { <Skeleton/> }.into_view())
+                                }
+                                Some(Ok(result)) => {
+                                    let sizes = result.2.clone();
+                                    let nodes = result
+                                        .1
+                                        .iter()
+                                        .cloned()
+                                        .map(|node| {
+                                            let size = sizes
+                                                .get(&node.path)
+                                                .or_else(|| sizes.get(&node.mailbox.name))
+                                                .map(|size| format_bytes(*size))
+                                                .unwrap_or_default();
+                                            view! {
+                                                <MailboxTreeItem
+                                                    node=node.clone()
+                                                    size=size
+                                                    href=mailbox_url(&node.mailbox.id, 1)
+                                                    is_selected=Signal::derive(move || {
+                                                        selected
+                                                            .get()
+                                                            .is_some_and(|selected| {
+                                                                selected.mailbox.id == node.mailbox.id
+                                                            })
+                                                    })
+                                                />
+                                            }
+                                        })
+                                        .collect_view();
+                                    Some(view! { <nav class="p-2 space-y-0.5">{nodes}</nav> }.into_view())
+                                }
+                            }}
+
+                        </Transition>
+                    </div>
+                </div>
+
+                <div class="lg:col-span-3">
+                    <ListTable
+                        title=Signal::derive(move || {
+                            selected.get().map(|node| node.path).unwrap_or_default()
+                        })
+
+                        subtitle=Signal::derive(move || {
+                            selected
+                                .get()
+                                .map(|node| {
+                                    format!(
+                                        "{} messages, {} unread",
+                                        node.mailbox.total_emails,
+                                        node.mailbox.unread_emails,
+                                    )
+                                })
+                                .unwrap_or_default()
+                        })
+                    >
+
+                        <Toolbar slot>
+                            <ToolbarButton
+                                text="Refresh"
+                                color=Color::Gray
+                                on_click=Callback::new(move |_| {
+                                    emails.refetch();
+                                })
+                            >
+
+                                <IconRefresh/>
+                            </ToolbarButton>
+                        </Toolbar>
+
+                        <Transition fallback=Skeleton>
+                            {move || match emails.get() {
+                                None => None,
+                                Some(Err(http::Error::Unauthorized)) => {
+                                    use_navigate()("/login", Default::default());
+                                    Some(view! { <div></div> }.into_view())
+                                }
+                                Some(Err(err)) => {
+                                    alert.set(Alert::from(err));
+                                    Some(view! { <Skeleton/> }.into_view())
+                                }
+                                Some(Ok(Some(result))) if !result.0.is_empty() => {
+                                    let emails = result.0.clone();
+                                    Some(
+                                        view! {
+                                            <ColumnList headers=vec![
+                                                "Subject".to_string(),
+                                                "From".to_string(),
+                                                "Received".to_string(),
+                                                "Size".to_string(),
+                                                "".to_string(),
+                                            ]>
+
+                                                <For
+                                                    each=move || emails.clone()
+                                                    key=|email| email.id.clone()
+                                                    let:email
+                                                >
+                                                    <EmailItem
+                                                        email
+                                                        account=name.get_untracked()
+                                                    />
+                                                </For>
+
+                                            </ColumnList>
+                                        }
+                                            .into_view(),
+                                    )
+                                }
+                                Some(Ok(_)) => {
+                                    Some(
+                                        view! {
+                                            <ZeroResults
+                                                title="No messages"
+                                                subtitle="This mailbox is empty."
+                                            />
+                                        }
+                                            .into_view(),
+                                    )
+                                }
+                            }}
+
+                        </Transition>
+
+                        <Footer slot>
+
+                            <Pagination
+                                current_page=page
+                                total_results=Signal::derive(move || {
+                                    emails
+                                        .get()
+                                        .and_then(|result| result.ok().flatten())
+                                        .map(|result| result.1 as u32)
+                                })
+
+                                page_size=PAGE_SIZE
+                                on_page_change=move |page: u32| {
+                                    if let Some(node) = selected.get() {
+                                        use_navigate()(
+                                            &mailbox_url(&node.mailbox.id, page),
+                                            Default::default(),
+                                        );
+                                    }
+                                }
+                            />
+
+                        </Footer>
+                    </ListTable>
+                </div>
+            </div>
+        </ListSection>
+    }
+}
+
+#[component]
+fn MailboxTreeItem(
+    node: MailboxNode,
+    size: String,
+    href: String,
+    is_selected: Signal<bool>,
+) -> impl IntoView {
+    let padding = format!("padding-left: {}rem", 0.75 + node.depth as f32);
+
+    view! {
+        <a
+            class="flex items-center gap-x-2 py-2 pe-3 rounded-lg text-sm text-gray-700 hover:bg-gray-100 dark:text-neutral-400 dark:hover:bg-neutral-700"
+            class=("bg-gray-100", move || is_selected.get())
+            class=("dark:bg-neutral-700", move || is_selected.get())
+            style=padding
+            href=href
+        >
+            <IconInbox attr:class="flex-shrink-0 size-4"/>
+            <span class="grow truncate">{node.mailbox.name}</span>
+            <span class="flex flex-col items-end text-xs text-gray-500 tabular-nums">
+                <span>{node.mailbox.total_emails.to_string()}</span>
+                <span>{size}</span>
+            </span>
+        </a>
+    }
+}
+
+#[component]
+fn EmailItem(email: EmailSummary, account: String) -> impl IntoView {
+    let from = email
+        .from
+        .as_ref()
+        .and_then(|from| from.first())
+        .map(|from| from.display())
+        .unwrap_or_default();
+    let subject = email
+        .subject
+        .clone()
+        .filter(|subject| !subject.is_empty())
+        .unwrap_or_else(|| "(no subject)".to_string());
+    let headers_url = UrlBuilder::new("/manage/mailbox")
+        .with_subpath(account)
+        .with_subpath(&email.id)
+        .finish();
+
+    view! {
+        <tr>
+            <ListItem class="h-px w-72 min-w-72">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200 truncate">
+                    {subject}
+                </span>
+            </ListItem>
+            <ListTextItem>{from}</ListTextItem>
+            <ListTextItem>{email.received_at.format_date_time()}</ListTextItem>
+            <ListTextItem>{format_bytes(email.size)}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <a
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    href=headers_url
+                >
+                    Headers
+                </a>
+            </ListItem>
+        </tr>
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        card::{Card, CardItem},
+        icon::{IconArrowLeft, IconClock, IconEnvelope, IconScale},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        format::format_bytes,
+        http,
+        jmap::{fetch_email_headers, open_browse_session},
+        oauth::use_authorization,
+        url::UrlBuilder,
+    },
+    pages::FormatDateTime,
+};
+
+#[component]
+pub fn MessageHeaders() -> impl IntoView {
+    let params = use_params_map();
+    let name = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+    let message_id = create_memo(move |_| params.get().get("message").cloned().unwrap_or_default());
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let email = create_local_resource(
+        move || (name.get(), message_id.get()),
+        move |(name, message_id)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let session = open_browse_session(&auth, &name).await?;
+                fetch_email_headers(&auth, &session.account_id, &message_id)
+                    .await?
+                    .map(Arc::new)
+                    .ok_or(http::Error::NotFound)
+            }
+        },
+    );
+
+    let go_back = move || {
+        use_navigate()(
+            &UrlBuilder::new("/manage/mailbox")
+                .with_subpath(name.get())
+                .finish(),
+            Default::default(),
+        );
+    };
+
+    view! {
+        <ListSection>
+            <Transition fallback=Skeleton>
+                {move || match email.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        alert.set(Alert::error("Message not found."));
+                        go_back();
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok(email)) => {
+                        let headers = email.headers.clone();
+                        let subject = email.subject.clone().unwrap_or_default();
+                        let rows = headers
+                            .into_iter()
+                            .map(|header| {
+                                view! {
+                                    <tr>
+                                        <ListTextItem>{header.name}</ListTextItem>
+                                        <ListItem class="h-px w-full">
+                                            <span class="block px-6 py-3 text-sm font-mono text-gray-800 dark:text-gray-200 break-all whitespace-pre-wrap">
+                                                {header.value.trim().to_string()}
+                                            </span>
+                                        </ListItem>
+                                    </tr>
+                                }
+                            })
+                            .collect_view();
+
+                        Some(
+                            view! {
+                                <Card>
+                                    <CardItem
+                                        title="Subject"
+                                        contents=subject
+                                        subcontents=name.get()
+                                    >
+
+                                        <IconEnvelope attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                    </CardItem>
+                                    <CardItem
+                                        title="Received"
+                                        contents=email.received_at.format_relative()
+                                        subcontents=email.received_at.format_date_time()
+                                    >
+
+                                        <IconClock attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                    </CardItem>
+                                    <CardItem title="Size" contents=format_bytes(email.size)>
+
+                                        <IconScale attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                    </CardItem>
+                                </Card>
+
+                                <div class="mt-6">
+                                    <ListTable
+                                        title="Headers"
+                                        subtitle="Message headers as stored on the server"
+                                    >
+                                        <Toolbar slot>
+                                            <ToolbarButton
+                                                text="Back"
+                                                color=Color::Gray
+                                                on_click=Callback::new(move |_| {
+                                                    go_back();
+                                                })
+                                            >
+
+                                                <IconArrowLeft/>
+                                            </ToolbarButton>
+                                        </Toolbar>
+
+                                        <ColumnList headers=vec![
+                                            "Name".to_string(),
+                                            "Value".to_string(),
+                                        ]>{rows}</ColumnList>
+
+                                        <Footer slot>
+                                            <div></div>
+                                        </Footer>
+                                    </ListTable>
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </ListSection>
+    }
+}
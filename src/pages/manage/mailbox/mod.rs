@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+pub mod browse;
+pub mod headers;
+
+use ahash::AHashMap;
+
+use crate::core::{
+    jmap::Mailbox,
+    schema::{Builder, Schemas, Transformer, Type, Validator},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailboxNode {
+    pub mailbox: Mailbox,
+    pub path: String,
+    pub depth: usize,
+}
+
+// Flattens the JMAP mailbox list into display order, children following
+// their parent sorted by sort order and name.
+pub fn mailbox_tree(mailboxes: Vec<Mailbox>) -> Vec<MailboxNode> {
+    let mut children: AHashMap<Option<String>, Vec<Mailbox>> = AHashMap::new();
+    for mailbox in mailboxes {
+        children
+            .entry(mailbox.parent_id.clone())
+            .or_default()
+            .push(mailbox);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| {
+            a.sort_order
+                .cmp(&b.sort_order)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+    }
+
+    let mut tree = Vec::new();
+    let mut stack = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .rev()
+        .map(|mailbox| (mailbox, String::new(), 0))
+        .collect::<Vec<_>>();
+    while let Some((mailbox, parent_path, depth)) = stack.pop() {
+        let path = if parent_path.is_empty() {
+            mailbox.name.clone()
+        } else {
+            format!("{parent_path}/{}", mailbox.name)
+        };
+        if let Some(siblings) = children.remove(&Some(mailbox.id.clone())) {
+            stack.extend(
+                siblings
+                    .into_iter()
+                    .rev()
+                    .map(|child| (child, path.clone(), depth + 1)),
+            );
+        }
+        tree.push(MailboxNode {
+            mailbox,
+            path,
+            depth,
+        });
+    }
+
+    tree
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_mailbox_browse(self) -> Self {
+        self.new_schema("mailbox-browse")
+            .new_field("account")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .build()
+    }
+}
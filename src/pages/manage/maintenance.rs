@@ -4,23 +4,45 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
 use leptos::*;
-use leptos_router::use_navigate;
+use leptos_router::*;
+use serde::Deserialize;
 
 use crate::{
     components::{
+        badge::Badge,
+        download::{use_signed_download, SignedDownloadLink},
         icon::{
-            IconCancel, IconCheckCircle, IconComputerDesktop, IconDocumentMagnifyingGlass,
-            IconPower, IconRefresh, IconShieldCheck,
+            IconArrowDownTray, IconCancel, IconCheckCircle, IconCircleStack, IconComputerDesktop,
+            IconDocumentMagnifyingGlass, IconPower, IconRefresh, IconShieldCheck, IconTrash,
+        },
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::ToolbarButton, Footer, ListItem,
+            ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert, Alerts},
+            modal::{use_modals, Modal},
         },
-        messages::alert::{use_alerts, Alert, Alerts},
+        progress::TaskProgressBar,
+        skeleton::Skeleton,
+        time::Timestamp,
+        Color,
     },
     core::{
+        download::Export,
         http::{self, HttpRequest},
         oauth::use_authorization,
+        poll::use_polling,
+        schema::Schemas,
+        task::{fetch_task, fetch_tasks, start_task, Task, TaskKind, TaskStatus},
+        url::UrlBuilder,
         Permission,
     },
-    pages::config::ReloadSettings,
+    pages::{config::ReloadSettings, format_countdown, FormatDateTime},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -97,6 +119,7 @@ const ACTIONS: &[Action] = &[
 pub fn Maintenance() -> impl IntoView {
     let auth = use_authorization();
     let alert = use_alerts();
+    let schemas = store_value(expect_context::<Arc<Schemas>>());
     let (pending, set_pending) = create_signal(false);
 
     let execute = create_action(move |idx: &usize| {
@@ -117,7 +140,7 @@ pub fn Maintenance() -> impl IntoView {
                         if result.errors.is_empty() && result.warnings.is_empty() {
                             alert.set(Alert::success(action.success_message).without_timeout());
                         } else {
-                            alert.set(Alert::from(result));
+                            alert.set(Alert::from_reload(result, &schemas.get_value()));
                         }
                         return;
                     }
@@ -190,6 +213,11 @@ pub fn Maintenance() -> impl IntoView {
 
     }).collect_view();
 
+    let can_restart = permissions.has_access(Permission::Restart);
+    let can_run_tasks = TaskKind::LAUNCHABLE
+        .iter()
+        .any(|kind| permissions.has_access(kind.permission()));
+
     view! {
         <div class="max-w-5xl px-4 py-10 sm:px-6 lg:px-8 lg:py-14 mx-auto">
             <Alerts/>
@@ -199,6 +227,609 @@ pub fn Maintenance() -> impl IntoView {
 
             </div>
 
+            <Show when=move || can_restart>
+                <ComponentControls/>
+            </Show>
+
+        </div>
+
+        <Show when=move || can_run_tasks>
+            <MaintenanceTasks/>
+        </Show>
+    }
+}
+
+// Components that can be restarted on their own, without interrupting the rest
+// of the server.
+const COMPONENTS: &[(&str, &str, &str)] = &[
+    (
+        "smtp",
+        "SMTP listeners",
+        "Rebinds the SMTP listeners, open SMTP sessions are closed.",
+    ),
+    (
+        "fts",
+        "FTS indexer",
+        "Restarts the full-text indexing task, pending messages are indexed afterwards.",
+    ),
+    (
+        "acme",
+        "ACME manager",
+        "Restarts certificate renewal and reloads the ACME account settings.",
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentStatus {
+    id: String,
+    #[serde(default)]
+    started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    last_error: Option<ComponentError>,
+    // Actions the server supports for this component
+    #[serde(default)]
+    actions: Vec<ComponentAction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct ComponentError {
+    timestamp: DateTime<Utc>,
+    message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ComponentAction {
+    Restart,
+    Reinitialize,
+}
+
+impl ComponentAction {
+    fn id(&self) -> &'static str {
+        match self {
+            ComponentAction::Restart => "restart",
+            ComponentAction::Reinitialize => "reinitialize",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ComponentAction::Restart => "Restart",
+            ComponentAction::Reinitialize => "Reinitialize",
+        }
+    }
+}
+
+#[component]
+fn ComponentControls() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+
+    let components = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/components")
+                    .with_authorization(&auth)
+                    .send::<Vec<ComponentStatus>>()
+                    .await
+            }
+        },
+    );
+
+    let execute = create_action(move |(id, action): &(String, ComponentAction)| {
+        let auth = auth.get();
+        let id = id.clone();
+        let action = *action;
+
+        async move {
+            match HttpRequest::post(format!("/api/components/{id}/{}", action.id()))
+                .with_authorization(&auth)
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "{} of {} requested.",
+                        action.title(),
+                        component_title(&id)
+                    )));
+                    components.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = execute.pending();
+
+    view! {
+        {move || match components.get() {
+            Some(Err(http::Error::Unauthorized)) => {
+                use_navigate()("/login", Default::default());
+                None
+            }
+            Some(Ok(components)) if !components.is_empty() => {
+                Some(
+                    view! {
+                        <div class="mt-10">
+                            <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                                Components
+                            </h2>
+                            <p class="text-sm text-gray-500">
+                                Restart individual components instead of the whole server.
+                            </p>
+                            <div class="mt-4 flex flex-col divide-y divide-gray-200 bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-800 dark:divide-gray-700">
+                                {components
+                                    .into_iter()
+                                    .map(|component| {
+                                        let title = component_title(&component.id);
+                                        let description = COMPONENTS
+                                            .iter()
+                                            .find(|(id, _, _)| *id == component.id)
+                                            .map(|(_, _, description)| *description)
+                                            .unwrap_or_default();
+                                        let uptime = component
+                                            .started_at
+                                            .map(|started_at| {
+                                                format_countdown(
+                                                    (Utc::now() - started_at).num_seconds().max(0) as u64,
+                                                )
+                                            });
+                                        let id = component.id;
+                                        view! {
+                                            <div class="p-4 md:p-5 flex flex-col sm:flex-row sm:items-start gap-4">
+                                                <div class="grow">
+                                                    <h3 class="font-semibold text-gray-800 dark:text-gray-200">
+                                                        {title.clone()}
+                                                        <span class="ms-2">
+                                                            {match uptime {
+                                                                Some(uptime) => {
+                                                                    view! {
+                                                                        <Badge color=Color::Green>
+                                                                            {format!("Up {uptime}")}
+                                                                        </Badge>
+                                                                    }
+                                                                }
+                                                                None => {
+                                                                    view! {
+                                                                        <Badge color=Color::Red>Stopped</Badge>
+                                                                    }
+                                                                }
+                                                            }}
+
+                                                        </span>
+                                                    </h3>
+                                                    <p class="text-sm text-gray-500">{description}</p>
+                                                    {component
+                                                        .last_error
+                                                        .map(|error| {
+                                                            view! {
+                                                                <p class="mt-2 text-sm text-red-600 dark:text-red-500">
+                                                                    <span class="font-medium">Last error</span>
+                                                                    " ("
+                                                                    <Timestamp date=error.timestamp relative=true/>
+                                                                    "): "
+                                                                    {error.message}
+                                                                </p>
+                                                            }
+                                                        })}
+
+                                                </div>
+                                                <div class="flex gap-x-2 flex-shrink-0">
+                                                    {component
+                                                        .actions
+                                                        .into_iter()
+                                                        .map(|action| {
+                                                            let id = id.clone();
+                                                            let title = title.clone();
+                                                            view! {
+                                                                <button
+                                                                    type="button"
+                                                                    class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                                                                    disabled=move || pending.get()
+                                                                    on:click=move |_| {
+                                                                        let id = id.clone();
+                                                                        modal
+                                                                            .set(
+                                                                                Modal::with_title(
+                                                                                        format!("{} {title}", action.title()),
+                                                                                    )
+                                                                                    .with_message(
+                                                                                        "Work in progress in this component will be interrupted.",
+                                                                                    )
+                                                                                    .with_button(action.title())
+                                                                                    .with_dangerous_callback(move || {
+                                                                                        execute.dispatch((id.clone(), action));
+                                                                                    }),
+                                                                            );
+                                                                    }
+                                                                >
+
+                                                                    {action.title()}
+                                                                </button>
+                                                            }
+                                                        })
+                                                        .collect_view()}
+                                                </div>
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        </div>
+                    }
+                        .into_view(),
+                )
+            }
+            Some(Err(err)) if !matches!(err, http::Error::NotFound) => {
+                alert.set(Alert::from(err));
+                None
+            }
+            // Servers without per-component controls return not found
+            _ => None,
+        }}
+    }
+}
+
+fn component_title(id: &str) -> String {
+    COMPONENTS
+        .iter()
+        .find(|(component, _, _)| *component == id)
+        .map(|(_, title, _)| title.to_string())
+        .unwrap_or_else(|| id.to_string())
+}
+
+const PAGE_SIZE: u32 = 10;
+const ACTIVE_INTERVAL: Duration = Duration::from_secs(2);
+const IDLE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[component]
+fn MaintenanceTasks() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let (pending, set_pending) = create_signal(false);
+    let active = create_rw_signal(None::<Task>);
+    let download = use_signed_download();
+
+    // Poll quickly while a launched task is running, otherwise only refresh
+    // the history now and then.
+    let poller = use_polling(Signal::derive(move || {
+        if active.with(|task| task.as_ref().is_some_and(|t| !t.status.is_finished())) {
+            ACTIVE_INTERVAL
+        } else {
+            IDLE_INTERVAL
+        }
+    }));
+
+    let tasks = create_resource(
+        move || (page.get(), poller.tick()),
+        move |(page, _)| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_tasks(&auth, page, PAGE_SIZE).await.map(Arc::new) }
+        },
+    );
+
+    create_effect(move |_| {
+        poller.tick();
+        let Some(task) = active.get_untracked().filter(|t| !t.status.is_finished()) else {
+            return;
+        };
+        let auth = auth.get_untracked();
+
+        spawn_local(async move {
+            match fetch_task(&auth, &task.id).await {
+                Ok(task) => {
+                    match task.status {
+                        TaskStatus::Completed => {
+                            alert.set(Alert::success(format!(
+                                "{} completed successfully.",
+                                task.kind.label()
+                            )));
+                        }
+                        TaskStatus::Failed => {
+                            alert.set(
+                                Alert::error(format!("{} failed", task.kind.label()))
+                                    .with_details(task.error.clone().unwrap_or_default()),
+                            );
+                        }
+                        _ => {}
+                    }
+                    active.set(Some(task));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    active.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    });
+
+    let launch = create_action(move |kind: &TaskKind| {
+        let kind = *kind;
+        let auth = auth.get();
+
+        async move {
+            set_pending.set(true);
+            let result = start_task(&auth, kind).await;
+            set_pending.set(false);
+
+            match result {
+                Ok(task) => {
+                    active.set(Some(task));
+                    tasks.refetch();
+                    alert.set(Alert::success(format!("{} started.", kind.label())));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let permissions = auth.get_untracked().permissions().clone();
+    let launchers = TaskKind::LAUNCHABLE
+        .iter()
+        .copied()
+        .filter(|kind| permissions.has_access(kind.permission()))
+        .map(|kind| {
+            let icon_class = "mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200";
+            let icon = match kind {
+                TaskKind::FtsReindex => {
+                    view! { <IconDocumentMagnifyingGlass attr:class=icon_class/> }
+                }
+                TaskKind::PurgeBlobs | TaskKind::PurgeMetrics => {
+                    view! { <IconTrash attr:class=icon_class/> }
+                }
+                TaskKind::CompactStore | TaskKind::MailboxMigration | TaskKind::Other => {
+                    view! { <IconCircleStack attr:class=icon_class/> }
+                }
+            };
+
+            view! {
+                <a
+                    class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
+                    href="#"
+                    on:click=move |ev| {
+                        ev.prevent_default();
+                        if pending.get() {
+                            return;
+                        }
+                        modal
+                            .set(
+                                Modal::with_title(kind.label())
+                                    .with_message(
+                                        format!(
+                                            "{} Do you want to start this task now?",
+                                            kind.description(),
+                                        ),
+                                    )
+                                    .with_button("Start task")
+                                    .with_dangerous_callback(move || {
+                                        launch.dispatch(kind);
+                                    }),
+                            );
+                    }
+                >
+
+                    <div class="p-4 md:p-5">
+                        <div class="flex">
+                            {icon} <div class="grow ms-5">
+                                <h3 class="group-hover:text-blue-600 font-semibold text-gray-800 dark:group-hover:text-gray-400 dark:text-gray-200">
+                                    {kind.label()}
+                                </h3>
+                                <p class="text-sm text-gray-500">{kind.description()}</p>
+                            </div>
+                        </div>
+                    </div>
+                </a>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="max-w-5xl px-4 sm:px-6 lg:px-8 mx-auto">
+            <h2 class="mt-10 text-lg font-semibold text-gray-800 dark:text-gray-200">Tasks</h2>
+            <p class="text-sm text-gray-500">
+                Long-running tasks are tracked by the server until they finish.
+            </p>
+            <div class="mt-4 grid sm:grid-cols-2 lg:grid-cols-3 gap-3 sm:gap-6">{launchers}</div>
+
+            {move || {
+                active
+                    .get()
+                    .map(|task| {
+                        view! {
+                            <div class="mt-6 bg-white border shadow-sm rounded-xl p-4 md:p-5 dark:bg-slate-900 dark:border-gray-800">
+                                <div class="flex justify-between items-center mb-2">
+                                    <h3 class="font-semibold text-gray-800 dark:text-gray-200">
+                                        {task.kind.label()}
+                                    </h3>
+                                    <Badge color=task.status.color()>{task.status.label()}</Badge>
+                                </div>
+                                <TaskProgressBar status=task.status progress=task.progress/>
+                            </div>
+                        }
+                    })
+            }}
+
         </div>
+
+        <ListSection>
+            <ListTable title="Task history" subtitle="Maintenance tasks previously run on the server">
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            poller.refresh();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if download.is_pending() {
+                                "Preparing...".to_string()
+                            } else {
+                                "Download backup".to_string()
+                            }
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            download.request(Export::Backup);
+                        })
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <SignedDownloadLink download/>
+
+                <Transition fallback=Skeleton>
+                    {move || match tasks.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(tasks)) if !tasks.items.is_empty() => {
+                            let tasks_ = tasks.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Task".to_string(),
+                                        "Status".to_string(),
+                                        "Progress".to_string(),
+                                        "Started".to_string(),
+                                        "Finished".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || tasks_.items.clone()
+                                            key=|task| (task.id.clone(), task.status, task.progress)
+                                            let:task
+                                        >
+                                            <TaskItem task/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No tasks"
+                                        subtitle="No maintenance tasks have been run yet."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=Signal::derive(move || {
+                            tasks
+                                .get()
+                                .and_then(|tasks| tasks.ok())
+                                .map(|tasks| tasks.total as u32)
+                        })
+
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/maintenance")
+                                    .with_parameter("page", page.to_string())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn TaskItem(task: Task) -> impl IntoView {
+    let started = task.created_at.format_date_time();
+    let finished = task
+        .finished_at
+        .map(|finished| finished.format_date_time())
+        .unwrap_or_default();
+    let error = task.error.clone().unwrap_or_default();
+
+    view! {
+        <tr>
+            <ListTextItem>
+                {if task.kind == TaskKind::MailboxMigration {
+                    view! {
+                        <a
+                            class="text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                            href=format!("/manage/migration?task={}", task.id)
+                        >
+                            {task.kind.label()}
+                        </a>
+                    }
+                        .into_view()
+                } else {
+                    task.kind.label().into_view()
+                }}
+
+            </ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <span title=error>
+                    <Badge color=task.status.color()>{task.status.label()}</Badge>
+                </span>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5 min-w-40">
+                <TaskProgressBar status=task.status progress=task.progress/>
+            </ListItem>
+            <ListTextItem>{started}</ListTextItem>
+            <ListTextItem>{finished}</ListTextItem>
+        </tr>
     }
 }
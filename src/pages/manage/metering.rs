@@ -0,0 +1,388 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Datelike, Local, Months, NaiveDate};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        icon::{IconArrowDownTray, IconRefresh},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        download::escape_csv,
+        format::{format_bytes, format_count},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+        AccessToken,
+    },
+    pages::{directory::Principal, maybe_plural, List},
+};
+
+const DOMAINS_PAGE_SIZE: usize = 100;
+const MONTHS_AVAILABLE: u32 = 12;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct DomainUsage {
+    pub month: String,
+    pub domain: String,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(flatten)]
+    pub usage: Usage,
+    // Set when the usage of this domain could not be fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    pub accounts: u64,
+    #[serde(rename = "storageBytes")]
+    #[serde(default)]
+    pub storage_bytes: u64,
+    #[serde(rename = "messagesSent")]
+    #[serde(default)]
+    pub messages_sent: u64,
+    #[serde(rename = "messagesReceived")]
+    #[serde(default)]
+    pub messages_received: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[component]
+pub fn UsageMetering() -> impl IntoView {
+    let query = use_query_map();
+    let months = available_months();
+    let default_month = months.first().cloned().unwrap_or_default();
+    let month = create_memo(move |_| {
+        query
+            .with(|q| q.get("month").cloned())
+            .filter(|month| NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").is_ok())
+            .unwrap_or_else(|| default_month.clone())
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let download: NodeRef<html::A> = create_node_ref();
+
+    let usage = create_resource(
+        move || month.get(),
+        move |month| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_usage(&auth, &month).await.map(Arc::new) }
+        },
+    );
+
+    let export = move |format: ExportFormat| {
+        let Some(Ok(usage)) = usage.get() else {
+            return;
+        };
+        let (contents, mime_type, extension) = match format {
+            ExportFormat::Csv => (format_csv(&usage), "text/csv", "csv"),
+            ExportFormat::Json => (
+                serde_json::to_string_pretty(usage.as_ref()).unwrap_or_default(),
+                "application/json",
+                "json",
+            ),
+        };
+        if let Some(link) = download.get_untracked() {
+            let _ = link.set_attribute(
+                "href",
+                &format!("data:{mime_type};base64,{}", STANDARD.encode(contents)),
+            );
+            let _ = link.set_attribute(
+                "download",
+                &format!("usage-{}.{extension}", month.get_untracked()),
+            );
+            link.click();
+        }
+    };
+
+    view! {
+        <a class="hidden" node_ref=download></a>
+        <ListSection>
+            <ListTable
+                title="Usage metering"
+                subtitle="Monthly usage per domain for billing and capacity planning"
+            >
+                <Toolbar slot>
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/metering")
+                                    .with_parameter("month", event_target_value(&ev))
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    >
+
+                        {months
+                            .into_iter()
+                            .map(|value| {
+                                let value_ = value.clone();
+                                view! {
+                                    <option
+                                        value=value.clone()
+                                        selected=move || month.get() == value_
+                                    >
+                                        {value}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            usage.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="CSV"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| export(ExportFormat::Csv))
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="JSON"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| export(ExportFormat::Json))
+                    >
+
+                        <IconArrowDownTray/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match usage.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(usage)) if !usage.is_empty() => {
+                            let failed = usage.iter().filter(|item| item.error.is_some()).count();
+                            if failed > 0 {
+                                alert
+                                    .set(
+                                        Alert::warning(
+                                            format!(
+                                                "Usage could not be fetched for {}.",
+                                                maybe_plural(failed, "domain", "domains"),
+                                            ),
+                                        ),
+                                    );
+                            }
+                            let total = usage
+                                .iter()
+                                .fold(Usage::default(), |acc, item| acc.add(&item.usage));
+                            let usage = usage.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Domain".to_string(),
+                                        "Tenant".to_string(),
+                                        "Accounts".to_string(),
+                                        "Storage".to_string(),
+                                        "Sent".to_string(),
+                                        "Received".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || usage.as_ref().clone()
+                                            key=|item| item.domain.clone()
+                                            let:item
+                                        >
+                                            <UsageItem
+                                                name=item.domain
+                                                tenant=item.tenant.unwrap_or_default()
+                                                usage=item.usage
+                                                error=item.error
+                                            />
+                                        </For>
+                                        <UsageItem
+                                            name="Total".to_string()
+                                            tenant=String::new()
+                                            usage=total
+                                            error=None
+                                        />
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No usage data"
+                                        subtitle="No domains were found for the selected month."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn UsageItem(name: String, tenant: String, usage: Usage, error: Option<String>) -> impl IntoView {
+    view! {
+        <tr>
+            <ListItem subclass="ps-6 pe-6 py-3">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {name}
+                </span>
+                {error.map(|error| view! { <span class="block text-xs text-red-600">{error}</span> })}
+            </ListItem>
+            <ListTextItem>{tenant}</ListTextItem>
+            <ListTextItem>{format_count(usage.accounts)}</ListTextItem>
+            <ListTextItem>{format_bytes(usage.storage_bytes)}</ListTextItem>
+            <ListTextItem>{format_count(usage.messages_sent)}</ListTextItem>
+            <ListTextItem>{format_count(usage.messages_received)}</ListTextItem>
+        </tr>
+    }
+}
+
+// Billing systems need every domain, so the domain list is walked page by
+// page and the usage endpoint is queried once per domain. A domain that
+// fails is reported in its row instead of aborting the whole export.
+async fn fetch_usage(auth: &AccessToken, month: &str) -> http::Result<Vec<DomainUsage>> {
+    let mut domains = Vec::new();
+    let mut page = 1;
+    loop {
+        let list = HttpRequest::get("/api/principal")
+            .with_authorization(auth)
+            .with_parameter("types", "domain")
+            .with_parameter("fields", "name,tenant")
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", DOMAINS_PAGE_SIZE.to_string())
+            .send::<List<Principal>>()
+            .await?;
+        let num_items = list.items.len();
+        domains.extend(list.items.into_iter().filter_map(|principal| {
+            let tenant = principal.tenant.as_str().map(|tenant| tenant.to_string());
+            principal
+                .name
+                .try_unwrap_string()
+                .map(|name| (name, tenant))
+        }));
+        if num_items < DOMAINS_PAGE_SIZE || domains.len() as u64 >= list.total {
+            break;
+        }
+        page += 1;
+    }
+
+    let mut results = Vec::with_capacity(domains.len());
+    for (domain, tenant) in domains {
+        let (usage, error) = match HttpRequest::get(("/api/usage", &domain))
+            .with_authorization(auth)
+            .with_parameter("month", month)
+            .send::<Usage>()
+            .await
+        {
+            Ok(usage) => (usage, None),
+            Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+            Err(err) => (Usage::default(), Some(Alert::from(err).message)),
+        };
+        results.push(DomainUsage {
+            month: month.to_string(),
+            domain,
+            tenant,
+            usage,
+            error,
+        });
+    }
+    results.sort_by(|a, b| a.domain.cmp(&b.domain));
+
+    Ok(results)
+}
+
+fn format_csv(usage: &[DomainUsage]) -> String {
+    let mut csv = String::from(
+        "month,domain,tenant,accounts,storage_bytes,messages_sent,messages_received,error\n",
+    );
+    for item in usage {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            item.month,
+            escape_csv(&item.domain),
+            escape_csv(item.tenant.as_deref().unwrap_or_default()),
+            item.usage.accounts,
+            item.usage.storage_bytes,
+            item.usage.messages_sent,
+            item.usage.messages_received,
+            escape_csv(item.error.as_deref().unwrap_or_default())
+        ));
+    }
+    csv
+}
+
+fn available_months() -> Vec<String> {
+    let today = Local::now().date_naive();
+    let current = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today);
+    (0..MONTHS_AVAILABLE)
+        .filter_map(|num| current.checked_sub_months(Months::new(num)))
+        .map(|month| month.format("%Y-%m").to_string())
+        .collect()
+}
+
+impl Usage {
+    fn add(self, other: &Usage) -> Usage {
+        Usage {
+            accounts: self.accounts + other.accounts,
+            storage_bytes: self.storage_bytes + other.storage_bytes,
+            messages_sent: self.messages_sent + other.messages_sent,
+            messages_received: self.messages_received + other.messages_received,
+        }
+    }
+}
@@ -0,0 +1,333 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::{AHashMap, AHashSet};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+
+use crate::{
+    components::{
+        form::{button::Button, FormButtonBar, FormSection},
+        icon::{IconPlus, IconTrash},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    pages::{
+        directory::import::{detect_delimiter, parse_delimited},
+        maybe_plural,
+    },
+};
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountMapping {
+    pub source: String,
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MappingRow {
+    // Stable key while rows are added and removed
+    id: usize,
+    source: String,
+    target: String,
+    password: String,
+}
+
+#[component]
+pub fn AccountMappingStep(
+    rows: RwSignal<Vec<MappingRow>>,
+    #[prop(into)] needs_password: Signal<bool>,
+    #[prop(into)] pending: Signal<bool>,
+    #[prop(into)] on_back: Callback<()>,
+    #[prop(into)] on_next: Callback<Vec<AccountMapping>>,
+) -> impl IntoView {
+    let alert = use_alerts();
+    let errors = create_memo(move |_| rows.with(|rows| validate(rows, needs_password.get())));
+    let num_errors = create_memo(move |_| errors.with(|errors| errors.len()));
+
+    let add_row = move |_| {
+        rows.update(|rows| {
+            let id = next_id(rows);
+            rows.push(MappingRow {
+                id,
+                ..Default::default()
+            });
+        });
+    };
+    let update_row = move |id: usize, update: &dyn Fn(&mut MappingRow)| {
+        rows.update(|rows| {
+            if let Some(row) = rows.iter_mut().find(|row| row.id == id) {
+                update(row);
+            }
+        });
+    };
+
+    let select_file = move |ev: web_sys::Event| {
+        let Some(selected) = event_target::<HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        spawn_local(async move {
+            let text = JsFuture::from(selected.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+                .unwrap_or_default();
+            let parsed = parse_rows(&text);
+
+            if parsed.is_empty() {
+                alert.set(
+                    Alert::error("Nothing to import")
+                        .with_details("The file does not contain any accounts."),
+                );
+            } else {
+                alert.set(Alert::success(format!(
+                    "Loaded {} from {}.",
+                    maybe_plural(parsed.len(), "account", "accounts"),
+                    selected.name()
+                )));
+                rows.set(parsed);
+            }
+        });
+    };
+
+    view! {
+        <FormSection title="Accounts">
+            <div class="sm:col-span-12 space-y-3">
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    {move || {
+                        if needs_password.get() {
+                            "Enter the accounts to migrate with their password on the source server, or load them from a CSV file with the columns source, target and password."
+                        } else {
+                            "Enter the accounts to migrate, or load them from a CSV file with the columns source and target."
+                        }
+                    }}
+                    " When no target is given the account is migrated into the account with the same name."
+                </p>
+                <input
+                    type="file"
+                    class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-neutral-500"
+                    accept=".csv,.tsv,.txt,text/csv,text/tab-separated-values"
+                    on:change=select_file
+                />
+                <table class="min-w-full text-sm">
+                    <thead>
+                        <tr class="text-xs font-medium uppercase text-gray-500">
+                            <th class="py-2 pe-2 text-start">Source account</th>
+                            <th class="py-2 pe-2 text-start">Target account</th>
+                            <th class="py-2 pe-2 text-start" class:hidden=move || !needs_password.get()>
+                                Password
+                            </th>
+                            <th class="py-2"></th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        <For
+                            each=move || rows.get()
+                            key=|row| row.id
+                            children=move |row| {
+                                let id = row.id;
+                                let row_errors = move || {
+                                    errors
+                                        .with(|errors| errors.get(&id).cloned())
+                                        .unwrap_or_default()
+                                };
+                                view! {
+                                    <tr class="align-top">
+                                        <td class="py-1 pe-2">
+                                            <input
+                                                type="text"
+                                                class=INPUT_CLASS
+                                                placeholder="john@example.org"
+                                                prop:value=row.source
+                                                on:change=move |ev| {
+                                                    let value = event_target_value(&ev).trim().to_string();
+                                                    update_row(id, &|row| row.source = value.clone());
+                                                }
+                                            />
+
+                                        </td>
+                                        <td class="py-1 pe-2">
+                                            <input
+                                                type="text"
+                                                class=INPUT_CLASS
+                                                placeholder="Same as source"
+                                                prop:value=row.target
+                                                on:change=move |ev| {
+                                                    let value = event_target_value(&ev).trim().to_string();
+                                                    update_row(id, &|row| row.target = value.clone());
+                                                }
+                                            />
+
+                                        </td>
+                                        <td class="py-1 pe-2" class:hidden=move || !needs_password.get()>
+                                            <input
+                                                type="password"
+                                                class=INPUT_CLASS
+                                                autocomplete="off"
+                                                prop:value=row.password
+                                                on:change=move |ev| {
+                                                    let value = event_target_value(&ev);
+                                                    update_row(id, &|row| row.password = value.clone());
+                                                }
+                                            />
+
+                                        </td>
+                                        <td class="py-1 text-end">
+                                            <button
+                                                type="button"
+                                                class="p-2 rounded-lg text-gray-500 hover:text-red-600 hover:bg-gray-100 dark:hover:bg-gray-800"
+                                                title="Remove"
+                                                on:click=move |_| {
+                                                    rows.update(|rows| rows.retain(|row| row.id != id));
+                                                }
+                                            >
+
+                                                <IconTrash attr:class="size-4"/>
+                                            </button>
+                                        </td>
+                                    </tr>
+                                    <tr class:hidden=move || row_errors().is_empty()>
+                                        <td colspan="4" class="pb-2">
+                                            {move || {
+                                                row_errors()
+                                                    .into_iter()
+                                                    .map(|error| {
+                                                        view! {
+                                                            <span class="block text-xs text-red-600">{error}</span>
+                                                        }
+                                                    })
+                                                    .collect_view()
+                                            }}
+
+                                        </td>
+                                    </tr>
+                                }
+                            }
+                        />
+
+                    </tbody>
+                </table>
+                <button
+                    type="button"
+                    class="inline-flex items-center gap-x-1 text-sm font-semibold text-blue-600 hover:text-blue-700 dark:text-blue-500"
+                    on:click=add_row
+                >
+                    <IconPlus attr:class="size-4"/>
+                    Add account
+                </button>
+            </div>
+        </FormSection>
+        <FormButtonBar>
+            <Button text="Back" color=Color::Gray on_click=move |_| on_back.call(())/>
+            <Button
+                text="Run dry run"
+                color=Color::Blue
+                disabled=Signal::derive(move || {
+                    pending.get() || num_errors.get() > 0 || rows.with(|rows| rows.is_empty())
+                })
+
+                on_click=Callback::new(move |_| {
+                    on_next.call(rows.with_untracked(|rows| rows.iter().map(AccountMapping::from).collect()));
+                })
+            />
+
+        </FormButtonBar>
+    }
+}
+
+impl MappingRow {
+    pub fn from_accounts(accounts: Vec<String>) -> Vec<MappingRow> {
+        accounts
+            .into_iter()
+            .enumerate()
+            .map(|(id, source)| MappingRow {
+                id,
+                source,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+impl From<&MappingRow> for AccountMapping {
+    fn from(row: &MappingRow) -> Self {
+        AccountMapping {
+            source: row.source.clone(),
+            target: if row.target.is_empty() {
+                row.source.clone()
+            } else {
+                row.target.clone()
+            },
+            password: (!row.password.is_empty()).then(|| row.password.clone()),
+        }
+    }
+}
+
+// Errors of each row, keyed by row id
+fn validate(rows: &[MappingRow], needs_password: bool) -> AHashMap<usize, Vec<String>> {
+    let mut errors: AHashMap<usize, Vec<String>> = AHashMap::new();
+    let mut sources = AHashSet::new();
+
+    for row in rows {
+        let mut row_errors = vec![];
+        if row.source.is_empty() {
+            row_errors.push("The source account is required".to_string());
+        } else if !sources.insert(row.source.as_str()) {
+            row_errors.push(format!("{} is listed more than once", row.source));
+        }
+        if needs_password && row.password.is_empty() {
+            row_errors.push("The password on the source server is required".to_string());
+        }
+        if !row_errors.is_empty() {
+            errors.insert(row.id, row_errors);
+        }
+    }
+
+    errors
+}
+
+fn parse_rows(text: &str) -> Vec<MappingRow> {
+    let mut records = parse_delimited(text, detect_delimiter(text))
+        .into_iter()
+        .peekable();
+    if records.peek().is_some_and(|(_, header)| {
+        header.first().is_some_and(|cell| {
+            matches!(
+                cell.trim().to_lowercase().as_str(),
+                "source" | "source account" | "from" | "account"
+            )
+        })
+    }) {
+        records.next();
+    }
+
+    records
+        .enumerate()
+        .map(|(id, (_, record))| {
+            let mut cells = record.into_iter().map(|cell| cell.trim().to_string());
+            MappingRow {
+                id,
+                source: cells.next().unwrap_or_default(),
+                target: cells.next().unwrap_or_default(),
+                password: cells.next().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+fn next_id(rows: &[MappingRow]) -> usize {
+    rows.iter().map(|row| row.id + 1).max().unwrap_or_default()
+}
@@ -0,0 +1,536 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+pub mod accounts;
+pub mod progress;
+pub mod report;
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::{use_navigate, use_query_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button,
+            input::{InputPassword, InputSwitch, InputText},
+            select::Select,
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+        task::{fetch_task, start_task_with, Task, TaskKind},
+    },
+};
+
+use self::{
+    accounts::{AccountMapping, AccountMappingStep, MappingRow},
+    progress::MigrationProgress,
+    report::{DryRunReport, DryRunReportStep},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum MigrationStep {
+    Source,
+    Accounts,
+    DryRun,
+    Progress,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    Imap,
+    Maildir,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceAuth {
+    // A master user that can log in as any account on the source server
+    Master,
+    // Each account logs in with its own password, given in the mapping
+    Account,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationSource {
+    #[serde(rename = "type")]
+    pub typ: SourceType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    pub tls: bool,
+    pub auth: SourceAuth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MigrationRequest {
+    pub source: MigrationSource,
+    pub accounts: Vec<AccountMapping>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+struct VerifyResult {
+    // Accounts found on the source, only reported for Maildir trees
+    #[serde(default)]
+    accounts: Vec<String>,
+}
+
+// Guides the administrator through copying mailboxes from another IMAP
+// server or a Maildir tree: the source is verified, accounts are mapped,
+// a dry run reports what will be copied and the server then runs the
+// migration as a task. Opening the page with ?task=<id> resumes following
+// a migration that is already running.
+#[component]
+pub fn MailboxMigration() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let query = use_query_map();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("migration-source")
+        .into_signal();
+    let step = create_rw_signal(MigrationStep::Source);
+    let source = create_rw_signal(None::<MigrationSource>);
+    let rows = create_rw_signal(Vec::<MappingRow>::new());
+    let report = create_rw_signal(None::<DryRunReport>);
+    let task = create_rw_signal(None::<Task>);
+    let (pending, set_pending) = create_signal(false);
+
+    if let Some(id) = query.with_untracked(|q| q.get("task").cloned()) {
+        step.set(MigrationStep::Progress);
+        spawn_local(async move {
+            match fetch_task(&auth.get_untracked(), &id).await {
+                Ok(found) => {
+                    task.set(Some(found));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    step.set(MigrationStep::Source);
+                    alert.set(Alert::from(err));
+                }
+            }
+        });
+    }
+
+    let verify = create_action(move |request: &MigrationSource| {
+        let auth = auth.get();
+        let request = request.clone();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/migration/verify")
+                .with_authorization(&auth)
+                .with_body(&request)
+                .unwrap()
+                .send::<VerifyResult>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(result) => {
+                    // Maildir trees list their accounts, use them as a starting point
+                    if rows.with_untracked(|rows| rows.is_empty()) {
+                        rows.set(MappingRow::from_accounts(result.accounts));
+                    }
+                    source.set(Some(request));
+                    step.set(MigrationStep::Accounts);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err).without_timeout());
+                }
+            }
+        }
+    });
+
+    let dry_run = create_action(move |request: &MigrationRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/migration/dry-run")
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<DryRunReport>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(result) => {
+                    report.set(Some(result));
+                    step.set(MigrationStep::DryRun);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let launch = create_action(move |request: &MigrationRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+
+        async move {
+            set_pending.set(true);
+            let result = start_task_with(&auth, TaskKind::MailboxMigration, request).await;
+            set_pending.set(false);
+
+            match result {
+                Ok(started) => {
+                    use_navigate()(
+                        &format!("/manage/migration?task={}", started.id),
+                        Default::default(),
+                    );
+                    task.set(Some(started));
+                    step.set(MigrationStep::Progress);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let request = move |accounts: Vec<AccountMapping>| {
+        source
+            .get_untracked()
+            .map(|source| MigrationRequest { source, accounts })
+    };
+    let run_dry_run = Callback::new(move |accounts: Vec<AccountMapping>| {
+        if let Some(request) = request(accounts) {
+            dry_run.dispatch(request);
+        }
+    });
+    // Accounts that failed the dry run are left out
+    let start_migration = Callback::new(move |sources: Vec<String>| {
+        let accounts = rows.with_untracked(|rows| {
+            rows.iter()
+                .map(AccountMapping::from)
+                .filter(|account| sources.contains(&account.source))
+                .collect()
+        });
+        if let Some(request) = request(accounts) {
+            launch.dispatch(request);
+        }
+    });
+    let needs_password = Signal::derive(move || {
+        source.with(|source| {
+            source
+                .as_ref()
+                .is_some_and(|source| source.auth == SourceAuth::Account)
+        })
+    });
+    let is_imap = Signal::derive(move || {
+        data.with(|data| data.value::<String>("type").unwrap_or_default() == "imap")
+    });
+    let is_master = Signal::derive(move || {
+        data.with(|data| data.value::<String>("auth").unwrap_or_default() == "master")
+    });
+
+    view! {
+        <Form
+            title="Mailbox migration"
+            subtitle="Copy the mailboxes of accounts on another mail server into this one"
+        >
+            <MigrationSteps step/>
+
+            <Show when=move || step.get() == MigrationStep::Source>
+                <FormSection title="Source">
+                    <FormItem label="Type">
+                        <Select element=FormElement::new("type", data)/>
+                    </FormItem>
+                    <FormItem label="Host" hide=Signal::derive(move || !is_imap.get())>
+                        <InputText
+                            placeholder="imap.example.org"
+                            element=FormElement::new("host", data)
+                        />
+                    </FormItem>
+                    <FormItem label="Port" hide=Signal::derive(move || !is_imap.get())>
+                        <InputText placeholder="993" element=FormElement::new("port", data)/>
+                    </FormItem>
+                    <FormItem label="" hide=Signal::derive(move || !is_imap.get())>
+                        <InputSwitch
+                            label="Use implicit TLS"
+                            element=FormElement::new("tls", data)
+                        />
+                    </FormItem>
+                    <FormItem
+                        label="Path"
+                        tooltip="Path of the Maildir tree on this server, %u is replaced by the account name"
+                        hide=Signal::derive(move || is_imap.get())
+                    >
+                        <InputText
+                            placeholder="/var/mail/%u/Maildir"
+                            element=FormElement::new("path", data)
+                        />
+                    </FormItem>
+                </FormSection>
+                <FormSection
+                    title="Authentication"
+                    hide=Signal::derive(move || !is_imap.get())
+                >
+                    <FormItem label="Method">
+                        <Select element=FormElement::new("auth", data)/>
+                    </FormItem>
+                    <FormItem
+                        label="Master user"
+                        tooltip="User allowed to log in as every account on the source server"
+                        hide=Signal::derive(move || !is_master.get())
+                    >
+                        <InputText element=FormElement::new("username", data)/>
+                    </FormItem>
+                    <FormItem label="Password" hide=Signal::derive(move || !is_master.get())>
+                        <InputPassword element=FormElement::new("secret", data)/>
+                    </FormItem>
+                </FormSection>
+                <FormButtonBar>
+                    <Button
+                        text="Connect"
+                        color=Color::Blue
+                        disabled=pending
+                        on_click=Callback::new(move |_| {
+                            let request = data
+                                .try_update(|data| {
+                                    data.validate_form().then(|| MigrationSource::from(&*data))
+                                })
+                                .flatten();
+                            if let Some(request) = request {
+                                verify.dispatch(request);
+                            }
+                        })
+                    />
+
+                </FormButtonBar>
+            </Show>
+
+            <Show when=move || step.get() == MigrationStep::Accounts>
+                <AccountMappingStep
+                    rows
+                    needs_password
+                    pending
+                    on_back=move |_| step.set(MigrationStep::Source)
+                    on_next=run_dry_run
+                />
+
+            </Show>
+
+            <Show when=move || step.get() == MigrationStep::DryRun>
+                {move || {
+                    report
+                        .get()
+                        .map(|report| {
+                            view! {
+                                <DryRunReportStep
+                                    report
+                                    pending
+                                    on_back=move |_| step.set(MigrationStep::Accounts)
+                                    on_start=start_migration
+                                />
+                            }
+                        })
+                }}
+
+            </Show>
+
+            <Show when=move || step.get() == MigrationStep::Progress>
+                <MigrationProgress
+                    task
+                    on_restart=move |_| {
+                        task.set(None);
+                        report.set(None);
+                        step.set(MigrationStep::Source);
+                        use_navigate()("/manage/migration", Default::default());
+                    }
+                />
+
+            </Show>
+        </Form>
+    }
+}
+
+#[component]
+fn MigrationSteps(step: RwSignal<MigrationStep>) -> impl IntoView {
+    view! {
+        <ol class="mb-8 flex items-center gap-x-2 text-sm">
+            {[
+                MigrationStep::Source,
+                MigrationStep::Accounts,
+                MigrationStep::DryRun,
+                MigrationStep::Progress,
+            ]
+                .into_iter()
+                .enumerate()
+                .map(|(idx, item)| {
+                    view! {
+                        <li class="flex items-center gap-x-2 grow">
+                            <span class=move || {
+                                if step.get() >= item {
+                                    "size-7 flex justify-center items-center shrink-0 rounded-full font-medium bg-blue-600 text-white"
+                                } else {
+                                    "size-7 flex justify-center items-center shrink-0 rounded-full font-medium bg-gray-100 text-gray-800 dark:bg-gray-700 dark:text-white"
+                                }
+                            }>{idx + 1}</span>
+                            <span class=move || {
+                                if step.get() == item {
+                                    "font-medium text-gray-800 dark:text-gray-200"
+                                } else {
+                                    "font-medium text-gray-500"
+                                }
+                            }>{item.label()}</span>
+                            <span class="w-full h-px flex-1 bg-gray-200 dark:bg-gray-700"></span>
+                        </li>
+                    }
+                })
+                .collect_view()}
+        </ol>
+    }
+}
+
+impl MigrationStep {
+    fn label(&self) -> &'static str {
+        match self {
+            MigrationStep::Source => "Source",
+            MigrationStep::Accounts => "Accounts",
+            MigrationStep::DryRun => "Dry run",
+            MigrationStep::Progress => "Migration",
+        }
+    }
+}
+
+impl From<&FormData> for MigrationSource {
+    fn from(data: &FormData) -> Self {
+        let value = |id: &str| data.value::<String>(id).filter(|value| !value.is_empty());
+
+        if data.value::<String>("type").unwrap_or_default() == "maildir" {
+            MigrationSource {
+                typ: SourceType::Maildir,
+                host: None,
+                port: None,
+                tls: false,
+                auth: SourceAuth::Account,
+                username: None,
+                secret: None,
+                path: value("path"),
+            }
+        } else {
+            let auth = if data.value::<String>("auth").unwrap_or_default() == "master" {
+                SourceAuth::Master
+            } else {
+                SourceAuth::Account
+            };
+            MigrationSource {
+                typ: SourceType::Imap,
+                host: value("host"),
+                port: data.value::<u16>("port"),
+                tls: data.value::<bool>("tls").unwrap_or_default(),
+                auth,
+                username: (auth == SourceAuth::Master)
+                    .then(|| value("username"))
+                    .flatten(),
+                secret: (auth == SourceAuth::Master)
+                    .then(|| value("secret"))
+                    .flatten(),
+                path: None,
+            }
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_migration(self) -> Self {
+        self.new_schema("migration-source")
+            .new_field("type")
+            .default("imap")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[("imap", "IMAP server"), ("maildir", "Maildir tree")]),
+            })
+            .build()
+            .new_field("host")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsHost],
+            )
+            .display_if_eq("type", ["imap"])
+            .build()
+            .new_field("port")
+            .default("993")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::IsPort],
+            )
+            .display_if_eq("type", ["imap"])
+            .build()
+            .new_field("tls")
+            .default("true")
+            .typ(Type::Boolean)
+            .display_if_eq("type", ["imap"])
+            .build()
+            .new_field("path")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("type", ["maildir"])
+            .build()
+            .new_field("auth")
+            .default("master")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("master", "Master user"),
+                    ("account", "Password of each account"),
+                ]),
+            })
+            .display_if_eq("type", ["imap"])
+            .build()
+            // Maildir sources keep the last authentication method selected
+            // but do not use it
+            .new_field("username")
+            .typ(Type::Input)
+            .input_check_if_eq("type", ["imap"], [Transformer::Trim], [Validator::Required])
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("auth", ["master"])
+            .build()
+            .new_field("secret")
+            .typ(Type::Input)
+            .input_check_if_eq("type", ["imap"], [], [Validator::Required])
+            .input_check([], [])
+            .display_if_eq("auth", ["master"])
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,220 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormButtonBar, FormSection},
+        messages::alert::{use_alerts, Alert},
+        progress::TaskProgressBar,
+        Color,
+    },
+    core::{
+        http,
+        oauth::use_authorization,
+        task::{retry_task_steps, use_task_tracker, Task, TaskStatus, TaskStep},
+    },
+    pages::maybe_plural,
+};
+
+#[component]
+pub fn MigrationProgress(
+    task: RwSignal<Option<Task>>,
+    #[prop(into)] on_restart: Callback<()>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    use_task_tracker(task);
+
+    // Report the outcome once, when a running migration finishes
+    create_effect(move |was_running: Option<bool>| {
+        let Some((status, failed)) = task.with(|task| {
+            task.as_ref()
+                .map(|task| (task.status, task.failed_steps().len()))
+        }) else {
+            return false;
+        };
+        if was_running.unwrap_or_default() && status.is_finished() {
+            alert.set(match (status, failed) {
+                (TaskStatus::Completed, 0) => Alert::success("Migration completed successfully."),
+                (TaskStatus::Cancelled, _) => Alert::warning("Migration cancelled."),
+                _ => Alert::error(format!(
+                    "Migration finished, {} could not be migrated.",
+                    maybe_plural(failed.max(1), "account", "accounts")
+                )),
+            });
+        }
+        !status.is_finished()
+    });
+
+    let retry = create_action(move |steps: &Vec<String>| {
+        let auth = auth.get();
+        let steps = steps.clone();
+        let id = task.with_untracked(|task| task.as_ref().map(|task| task.id.clone()));
+
+        async move {
+            let Some(id) = id else {
+                return;
+            };
+            match retry_task_steps(&auth, &id, steps).await {
+                Ok(updated) => {
+                    task.set(Some(updated));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = retry.pending();
+    let failed = create_memo(move |_| {
+        task.with(|task| {
+            task.as_ref()
+                .map(|task| task.failed_steps())
+                .unwrap_or_default()
+        })
+    });
+    let is_finished = create_memo(move |_| {
+        task.with(|task| task.as_ref().is_some_and(|task| task.status.is_finished()))
+    });
+
+    view! {
+        <FormSection title="Migration">
+            <div class="sm:col-span-12 space-y-4">
+                {move || match task.get() {
+                    Some(task) => {
+                        let done = task
+                            .steps
+                            .iter()
+                            .filter(|step| step.status == TaskStatus::Completed)
+                            .count();
+                        let summary = format!(
+                            "{} of {} migrated{}.",
+                            done,
+                            maybe_plural(task.steps.len(), "account", "accounts"),
+                            if failed.with(|failed| failed.is_empty()) {
+                                String::new()
+                            } else {
+                                format!(", {} failed", failed.with(|failed| failed.len()))
+                            },
+                        );
+                        view! {
+                            <div class="flex justify-between items-center gap-x-2">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">{summary}</p>
+                                <Badge color=task.status.color()>{task.status.label()}</Badge>
+                            </div>
+                            <TaskProgressBar status=task.status progress=task.progress/>
+                            <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                                <thead>
+                                    <tr class="text-xs font-medium uppercase text-gray-500">
+                                        <th class="py-2 pe-4 text-start">Account</th>
+                                        <th class="py-2 pe-4 text-start">Status</th>
+                                        <th class="py-2 pe-4 text-start min-w-40">Messages</th>
+                                        <th class="py-2"></th>
+                                    </tr>
+                                </thead>
+                                <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                    {task
+                                        .steps
+                                        .into_iter()
+                                        .map(|step| {
+                                            view! {
+                                                <MigrationStepItem
+                                                    step
+                                                    pending
+                                                    on_retry=move |id: String| retry.dispatch(vec![id])
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+                                </tbody>
+                            </table>
+                        }
+                            .into_view()
+                    }
+                    None => view! { <p class="text-sm text-gray-500">Loading...</p> }.into_view(),
+                }}
+
+            </div>
+        </FormSection>
+        <FormButtonBar>
+            <Button
+                text="New migration"
+                color=Color::Gray
+                disabled=Signal::derive(move || !is_finished.get())
+                on_click=move |_| on_restart.call(())
+            />
+            <Button
+                text="Retry failed accounts"
+                color=Color::Blue
+                disabled=Signal::derive(move || {
+                    pending.get() || !is_finished.get() || failed.with(|failed| failed.is_empty())
+                })
+
+                on_click=Callback::new(move |_| {
+                    retry.dispatch(failed.get_untracked());
+                })
+            />
+
+        </FormButtonBar>
+    }
+}
+
+#[component]
+fn MigrationStepItem(
+    step: TaskStep,
+    #[prop(into)] pending: Signal<bool>,
+    #[prop(into)] on_retry: Callback<String>,
+) -> impl IntoView {
+    let is_failed = step.status == TaskStatus::Failed;
+    let id = step.id.clone();
+
+    view! {
+        <tr class="align-top text-gray-800 dark:text-gray-200">
+            <td class="py-2 pe-4 font-medium">{step.id}</td>
+            <td class="py-2 pe-4">
+                <Badge color=step.status.color()>{step.status.label()}</Badge>
+                {step
+                    .error
+                    .map(|error| view! { <span class="block text-xs text-red-600 mt-1">{error}</span> })}
+            </td>
+            <td class="py-2 pe-4">
+                <TaskProgressBar status=step.status progress=step.progress/>
+            </td>
+            <td class="py-2 text-end">
+                {is_failed
+                    .then(|| {
+                        view! {
+                            <button
+                                type="button"
+                                class="text-sm font-semibold text-blue-600 hover:text-blue-700 disabled:opacity-50 dark:text-blue-500"
+                                disabled=move || pending.get()
+                                on:click=move |_| on_retry.call(id.clone())
+                            >
+                                Retry
+                            </button>
+                        }
+                    })}
+            </td>
+        </tr>
+    }
+}
+
+impl Task {
+    fn failed_steps(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .filter(|step| step.status == TaskStatus::Failed)
+            .map(|step| step.id.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormButtonBar, FormSection},
+        messages::modal::{use_modals, Modal},
+        Color,
+    },
+    core::format::{format_bytes, format_count},
+    pages::maybe_plural,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DryRunReport {
+    #[serde(default)]
+    pub accounts: Vec<DryRunAccount>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunAccount {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub mailboxes: u64,
+    #[serde(default)]
+    pub messages: u64,
+    #[serde(default)]
+    pub size: u64,
+    // The target account is created when it does not exist yet
+    #[serde(default)]
+    pub target_exists: bool,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[component]
+pub fn DryRunReportStep(
+    report: DryRunReport,
+    #[prop(into)] pending: Signal<bool>,
+    #[prop(into)] on_back: Callback<()>,
+    // Source accounts to migrate, accounts that failed the dry run are left out
+    #[prop(into)] on_start: Callback<Vec<String>>,
+) -> impl IntoView {
+    let modal = use_modals();
+    let ready = report
+        .accounts
+        .iter()
+        .filter(|account| account.error.is_none())
+        .map(|account| account.source.clone())
+        .collect::<Vec<_>>();
+    let num_failed = report.accounts.len() - ready.len();
+    let (messages, size) = report
+        .accounts
+        .iter()
+        .filter(|account| account.error.is_none())
+        .fold((0, 0), |(messages, size), account| {
+            (messages + account.messages, size + account.size)
+        });
+    let summary = format!(
+        "{} ready to migrate with {} messages ({}){}.",
+        maybe_plural(ready.len(), "account", "accounts"),
+        format_count(messages),
+        format_bytes(size),
+        if num_failed > 0 {
+            format!(
+                ", {} cannot be migrated",
+                maybe_plural(num_failed, "account", "accounts")
+            )
+        } else {
+            String::new()
+        }
+    );
+    let has_ready = !ready.is_empty();
+    let ready = store_value(ready);
+
+    view! {
+        <FormSection title="Dry run">
+            <div class="sm:col-span-12 space-y-3">
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    {summary}
+                    " Nothing has been copied yet."
+                </p>
+                <div class="overflow-x-auto">
+                    <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                        <thead>
+                            <tr class="text-xs font-medium uppercase text-gray-500">
+                                <th class="py-2 pe-4 text-start">Account</th>
+                                <th class="py-2 pe-4 text-end">Mailboxes</th>
+                                <th class="py-2 pe-4 text-end">Messages</th>
+                                <th class="py-2 pe-4 text-end">Size</th>
+                                <th class="py-2 text-start">Status</th>
+                            </tr>
+                        </thead>
+                        <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                            {report
+                                .accounts
+                                .into_iter()
+                                .map(|account| view! { <DryRunAccountItem account/> })
+                                .collect_view()}
+                        </tbody>
+                    </table>
+                </div>
+            </div>
+        </FormSection>
+        <FormButtonBar>
+            <Button text="Back" color=Color::Gray on_click=move |_| on_back.call(())/>
+            <Button
+                text="Start migration"
+                color=Color::Blue
+                disabled=Signal::derive(move || pending.get() || !has_ready)
+                on_click=Callback::new(move |_| {
+                    let ready = ready.get_value();
+                    let message = if num_failed > 0 {
+                        format!(
+                            "{} will be migrated, {} that failed the dry run will be skipped. Do you want to start the migration?",
+                            maybe_plural(ready.len(), "account", "accounts"),
+                            maybe_plural(num_failed, "account", "accounts"),
+                        )
+                    } else {
+                        format!(
+                            "{} will be migrated. Do you want to start the migration?",
+                            maybe_plural(ready.len(), "account", "accounts"),
+                        )
+                    };
+                    modal
+                        .set(
+                            Modal::with_title("Start migration")
+                                .with_message(message)
+                                .with_button("Start migration")
+                                .with_callback(move || {
+                                    on_start.call(ready.clone());
+                                }),
+                        );
+                })
+            />
+
+        </FormButtonBar>
+    }
+}
+
+#[component]
+fn DryRunAccountItem(account: DryRunAccount) -> impl IntoView {
+    let (color, status) = match (&account.error, account.target_exists) {
+        (Some(_), _) => (Color::Red, "Failed"),
+        (None, true) => (Color::Green, "Ready"),
+        (None, false) => (Color::Blue, "Account created"),
+    };
+    let target = (account.target != account.source).then(|| {
+        view! { <span class="block text-xs text-gray-500">{format!("to {}", account.target)}</span> }
+    });
+
+    view! {
+        <tr class="align-top text-gray-800 dark:text-gray-200">
+            <td class="py-2 pe-4">
+                <span class="block font-medium">{account.source}</span>
+                {target}
+            </td>
+            <td class="py-2 pe-4 text-end tabular-nums">{format_count(account.mailboxes)}</td>
+            <td class="py-2 pe-4 text-end tabular-nums">{format_count(account.messages)}</td>
+            <td class="py-2 pe-4 text-end tabular-nums">{format_bytes(account.size)}</td>
+            <td class="py-2">
+                <Badge color>{status}</Badge>
+                {account
+                    .error
+                    .map(|error| view! { <span class="block text-xs text-red-600 mt-1">{error}</span> })}
+                {account
+                    .warnings
+                    .into_iter()
+                    .map(|warning| {
+                        view! { <span class="block text-xs text-yellow-600 mt-1">{warning}</span> }
+                    })
+                    .collect_view()}
+            </td>
+        </tr>
+    }
+}
@@ -4,7 +4,28 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod access_list;
+pub mod api_explorer;
+pub mod audit;
+pub mod autoconfig;
+pub mod dkim;
+pub mod import;
+pub mod jmap;
 pub mod logs;
+pub mod mailbox;
 pub mod maintenance;
+pub mod metering;
+pub mod migration;
+pub mod probe;
+pub mod recording;
+pub mod reputation;
+pub mod rule_hits;
+pub mod runbook;
+pub mod scim_log;
+pub mod security;
+pub mod sessions;
+pub mod sieve;
 pub mod spam;
+pub mod store_query;
+pub mod trace;
 pub mod troubleshoot;
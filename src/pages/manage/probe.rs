@@ -0,0 +1,393 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, Form, FormButtonBar, FormItem, FormSection},
+        icon::IconPaperAirplane,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+    },
+    pages::{config::Settings, format_countdown, List},
+};
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+// Authentication methods shown as badges, in this order
+static AUTH_METHODS: &[&str] = &["spf", "dkim", "dmarc", "arc"];
+
+#[derive(Clone, Debug, Serialize)]
+struct ProbeRequest {
+    seed: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Probe {
+    id: String,
+    address: String,
+    sent_at: DateTime<Utc>,
+    #[serde(flatten)]
+    state: ProbeState,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "state")]
+#[serde(rename_all = "camelCase")]
+enum ProbeState {
+    Pending,
+    #[serde(rename_all = "camelCase")]
+    Arrived {
+        arrived_at: DateTime<Utc>,
+        folder: String,
+        #[serde(default)]
+        authentication_results: Vec<String>,
+    },
+    NotReceived,
+    Failed {
+        reason: String,
+    },
+}
+
+// Sends a message to a mailbox at an external provider and fetches it back
+// over IMAP, to check whether it was accepted and where it was filed.
+#[component]
+pub fn TroubleshootProbe() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let poller = use_polling(PROBE_INTERVAL);
+    let seed = create_rw_signal(String::new());
+    let probe_id = create_rw_signal(None::<String>);
+
+    let seeds = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "troubleshoot.seed")
+                    .with_parameter("suffix", "address")
+                    .send::<List<Settings>>()
+                    .await
+                    .map(|list| {
+                        list.items
+                            .into_iter()
+                            .filter_map(|mut item| {
+                                Some((item.remove("_id")?, item.remove("address")?))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }
+        },
+    );
+
+    let probe = create_local_resource(
+        move || (probe_id.get(), poller.tick()),
+        move |(id, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let id = id?;
+                match HttpRequest::get(("/api/troubleshoot/probe", &id))
+                    .with_authorization(&auth)
+                    .send::<Probe>()
+                    .await
+                {
+                    Ok(probe) => {
+                        if probe.state != ProbeState::Pending {
+                            poller.pause();
+                        }
+                        Some(probe)
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Err(err) => {
+                        poller.pause();
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                }
+            }
+        },
+    );
+
+    let start_probe = create_action(move |seed: &String| {
+        let auth = auth.get();
+        let request = ProbeRequest { seed: seed.clone() };
+
+        async move {
+            match HttpRequest::post("/api/troubleshoot/probe")
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<Probe>()
+                .await
+            {
+                Ok(probe) => {
+                    poller.resume();
+                    probe_id.set(Some(probe.id));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = start_probe.pending();
+
+    view! {
+        {move || match probe_id.get().and(probe.get().flatten()) {
+            Some(probe) => {
+                view! {
+                    <ReportView>
+                        <ProbeResult probe/>
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Blue
+                                on_click=move |_| {
+                                    probe_id.set(None);
+                                }
+                            />
+
+                        </div>
+                    </ReportView>
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="Deliverability Probe"
+                        subtitle="Send a test message to a mailbox at an external provider and check where it lands"
+                    >
+                        <Alerts/>
+                        <FormSection>
+                            <FormItem
+                                label="Seed Mailbox"
+                                tooltip="Seed mailboxes are configured under Settings › SMTP › Outbound."
+                            >
+                                {move || match seeds.get() {
+                                    Some(Ok(seeds)) if !seeds.is_empty() => {
+                                        if seed.get_untracked().is_empty() {
+                                            seed.set(seeds[0].0.clone());
+                                        }
+                                        view! {
+                                            <select
+                                                class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                                on:change=move |ev| {
+                                                    seed.set(event_target_value(&ev));
+                                                }
+                                            >
+
+                                                {seeds
+                                                    .into_iter()
+                                                    .map(|(id, address)| {
+                                                        let selected = seed.get_untracked() == id;
+                                                        view! {
+                                                            <option value=id selected=selected>
+                                                                {address}
+                                                            </option>
+                                                        }
+                                                    })
+                                                    .collect_view()}
+                                            </select>
+                                        }
+                                            .into_view()
+                                    }
+                                    Some(Err(http::Error::Unauthorized)) => {
+                                        use_navigate()("/login", Default::default());
+                                        ().into_view()
+                                    }
+                                    Some(_) => {
+                                        view! {
+                                            <p class="text-sm text-gray-600 dark:text-gray-400">
+                                                "No seed mailboxes are configured, "
+                                                <a
+                                                    class="text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                                                    href="/settings/seed-mailbox/edit"
+                                                >
+                                                    add one
+                                                </a>
+                                                " first."
+                                            </p>
+                                        }
+                                            .into_view()
+                                    }
+                                    None => ().into_view(),
+                                }}
+
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Send probe"
+                                color=Color::Blue
+                                disabled=Signal::derive(move || {
+                                    pending.get() || seed.with(|seed| seed.is_empty())
+                                        || (probe_id.get().is_some() && probe.loading().get())
+                                })
+
+                                on_click=Callback::new(move |_| {
+                                    start_probe.dispatch(seed.get_untracked());
+                                })
+                            >
+
+                                <IconPaperAirplane/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn ProbeResult(probe: Probe) -> impl IntoView {
+    let (color, result) = match &probe.state {
+        ProbeState::Pending => (Color::Gray, "Waiting for the message".to_string()),
+        ProbeState::Arrived { folder, .. } if is_spam_folder(folder) => {
+            (Color::Yellow, "Delivered to spam".to_string())
+        }
+        ProbeState::Arrived { .. } => (Color::Green, "Delivered to inbox".to_string()),
+        ProbeState::NotReceived => (Color::Red, "Not received".to_string()),
+        ProbeState::Failed { .. } => (Color::Red, "Probe failed".to_string()),
+    };
+
+    view! {
+        <ReportSection title="Deliverability Probe">
+            <ReportItem label="Seed Mailbox">
+                <ReportTextValue value=probe.address/>
+            </ReportItem>
+            <ReportItem label="Sent">
+                <Timestamp date=probe.sent_at attr:class="text-sm text-gray-500"/>
+            </ReportItem>
+            <ReportItem label="Result">
+                <Badge color>{result}</Badge>
+            </ReportItem>
+            {match probe.state {
+                ProbeState::Arrived { arrived_at, folder, authentication_results } => {
+                    let delay = format_countdown(
+                        (arrived_at - probe.sent_at).num_seconds().max(0) as u64,
+                    );
+                    let methods = auth_methods(&authentication_results);
+                    view! {
+                        <ReportItem label="Folder">
+                            <ReportTextValue value=folder/>
+                        </ReportItem>
+                        <ReportItem label="Delivery Time">
+                            <ReportTextValue value=delay/>
+                        </ReportItem>
+                        <ReportItem label="Authentication" hide=methods.is_empty()>
+                            <div class="flex flex-wrap gap-2">
+                                {methods
+                                    .into_iter()
+                                    .map(|(method, result)| {
+                                        let color = match result.as_str() {
+                                            "pass" => Color::Green,
+                                            "fail" | "permerror" => Color::Red,
+                                            "none" => Color::Gray,
+                                            _ => Color::Yellow,
+                                        };
+                                        view! {
+                                            <Badge color>
+                                                {format!("{}: {result}", method.to_uppercase())}
+                                            </Badge>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        </ReportItem>
+                        <ReportItem
+                            label="Authentication-Results"
+                            hide=authentication_results.is_empty()
+                        >
+                            <div class="text-xs font-mono break-all text-gray-500 space-y-1">
+                                {authentication_results
+                                    .into_iter()
+                                    .map(|header| view! { <span class="block">{header}</span> })
+                                    .collect_view()}
+                            </div>
+                        </ReportItem>
+                    }
+                        .into_view()
+                }
+                ProbeState::NotReceived => {
+                    view! {
+                        <ReportItem label="Reason">
+                            <ReportTextValue value="The message was not found in any folder of the seed mailbox."/>
+                        </ReportItem>
+                    }
+                        .into_view()
+                }
+                ProbeState::Failed { reason } => {
+                    view! {
+                        <ReportItem label="Reason">
+                            <ReportTextValue value=reason/>
+                        </ReportItem>
+                    }
+                        .into_view()
+                }
+                ProbeState::Pending => ().into_view(),
+            }}
+
+        </ReportSection>
+    }
+}
+
+fn is_spam_folder(folder: &str) -> bool {
+    let folder = folder.to_lowercase();
+    ["spam", "junk", "bulk"]
+        .iter()
+        .any(|name| folder.contains(name))
+}
+
+// Extracts the result of each method from Authentication-Results headers, such
+// as "mx.google.com; spf=pass smtp.mailfrom=...; dkim=pass header.d=...".
+fn auth_methods(headers: &[String]) -> Vec<(&'static str, String)> {
+    AUTH_METHODS
+        .iter()
+        .filter_map(|method| {
+            headers
+                .iter()
+                .flat_map(|header| header.split(';'))
+                .find_map(|part| {
+                    let (name, value) = part.trim().split_once('=')?;
+                    name.trim().eq_ignore_ascii_case(method).then(|| {
+                        value
+                            .split_whitespace()
+                            .next()
+                            .unwrap_or_default()
+                            .to_lowercase()
+                    })
+                })
+                .map(|result| (*method, result))
+        })
+        .collect()
+}
@@ -0,0 +1,384 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::HtmlInputElement;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconArrowDownTray, IconCancel, IconPlayCircle, IconTrash},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http,
+        oauth::use_authorization,
+        poll::use_polling,
+        recording::{
+            current_recording, download_recording, replay_call, start_recording, stop_recording,
+            RecordedCall, Recording,
+        },
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ReplayStatus {
+    Pending,
+    Running,
+    Done,
+    Failed(String),
+    Skipped,
+}
+
+#[component]
+pub fn ChangeRecording() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let poller = use_polling(Duration::from_secs(2));
+    let recording = create_memo(move |_| {
+        poller.tick();
+        current_recording()
+    });
+    let script = create_rw_signal(None::<(String, Recording)>);
+    let statuses = create_rw_signal(Vec::<ReplayStatus>::new());
+    let stop_on_error = create_rw_signal(true);
+
+    let replay_action = create_action(move |_: &()| {
+        let auth = auth.get_untracked();
+        let calls = script
+            .get_untracked()
+            .map(|(_, script)| script.calls)
+            .unwrap_or_default();
+        let stop_on_error = stop_on_error.get_untracked();
+
+        async move {
+            let mut failed = 0;
+            for (idx, call) in calls.iter().enumerate() {
+                if failed > 0 && stop_on_error {
+                    statuses.update(|statuses| statuses[idx] = ReplayStatus::Skipped);
+                    continue;
+                }
+                statuses.update(|statuses| statuses[idx] = ReplayStatus::Running);
+                let status = match replay_call(&auth, call).await {
+                    Ok(_) => ReplayStatus::Done,
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        failed += 1;
+                        ReplayStatus::Failed(Alert::from(err).message)
+                    }
+                };
+                statuses.update(|statuses| statuses[idx] = status);
+            }
+
+            if failed == 0 {
+                alert.set(Alert::success(format!(
+                    "Replayed {} successfully.",
+                    maybe_plural(calls.len(), "API call", "API calls")
+                )));
+            } else {
+                alert.set(Alert::error(format!(
+                    "{} could not be replayed.",
+                    maybe_plural(failed, "API call", "API calls")
+                )));
+            }
+        }
+    });
+
+    let select_file = move |ev: web_sys::Event| {
+        let Some(selected) = event_target::<HtmlInputElement>(&ev)
+            .files()
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+
+        spawn_local(async move {
+            let name = selected.name();
+            let text = JsFuture::from(selected.text())
+                .await
+                .ok()
+                .and_then(|text| text.as_string())
+                .unwrap_or_default();
+
+            match serde_json::from_str::<Recording>(&text) {
+                Ok(recording) => {
+                    statuses.set(vec![ReplayStatus::Pending; recording.calls.len()]);
+                    script.set(Some((name, recording)));
+                }
+                Err(_) => {
+                    alert.set(
+                        Alert::error("Invalid script")
+                            .with_details("The file is not a recorded change script."),
+                    );
+                }
+            }
+        });
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Change recording"
+                subtitle="Record the changes made in this session and replay them on another server"
+            >
+                <Toolbar slot>
+                    <Show
+                        when=move || recording.with(|recording| recording.is_some())
+                        fallback=move || {
+                            view! {
+                                <ToolbarButton
+                                    text="Start recording"
+                                    color=Color::Blue
+                                    on_click=Callback::new(move |_| {
+                                        start_recording(&auth.get_untracked());
+                                        poller.refresh();
+                                        alert
+                                            .set(
+                                                Alert::success(
+                                                    "Recording started, changes made from now on will be captured.",
+                                                ),
+                                            );
+                                    })
+                                >
+
+                                    <IconPlayCircle/>
+                                </ToolbarButton>
+                            }
+                        }
+                    >
+
+                        <ToolbarButton
+                            text="Discard"
+                            color=Color::Red
+                            on_click=Callback::new(move |_| {
+                                stop_recording();
+                                poller.refresh();
+                            })
+                        >
+
+                            <IconTrash/>
+                        </ToolbarButton>
+                        <ToolbarButton
+                            text="Stop and download"
+                            color=Color::Blue
+                            on_click=Callback::new(move |_| {
+                                if let Some(recording) = stop_recording() {
+                                    download_recording(&recording);
+                                }
+                                poller.refresh();
+                            })
+                        >
+
+                            <IconArrowDownTray/>
+                        </ToolbarButton>
+                    </Show>
+                </Toolbar>
+
+                {move || match script.get() {
+                    Some((name, script)) => {
+                        let calls = script.calls.into_iter().enumerate().collect::<Vec<_>>();
+                        view! {
+                            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    {format!(
+                                        "{name}: {} recorded on {} at {}.",
+                                        maybe_plural(calls.len(), "API call", "API calls"),
+                                        script.server,
+                                        script.started_at.format_date_time(),
+                                    )}
+
+                                </p>
+                            </div>
+                            <ColumnList headers=vec![
+                                "#".to_string(),
+                                "Method".to_string(),
+                                "Path".to_string(),
+                                "Status".to_string(),
+                            ]>
+
+                                <For
+                                    each=move || calls.clone()
+                                    key=|(idx, _)| *idx
+                                    children=move |(idx, call)| {
+                                        let status = create_memo(move |_| {
+                                            statuses
+                                                .with(|statuses| {
+                                                    statuses.get(idx).cloned().unwrap_or(ReplayStatus::Pending)
+                                                })
+                                        });
+                                        view! { <CallItem idx call status/> }
+                                    }
+                                />
+
+                            </ColumnList>
+                        }
+                            .into_view()
+                    }
+                    None => {
+                        view! {
+                            <ZeroResults
+                                title="No script loaded"
+                                subtitle="Load a recorded script to review and replay its API calls on this server."
+                            />
+                        }
+                            .into_view()
+                    }
+                }}
+
+                <Footer slot>
+                    <div class="flex flex-wrap items-center gap-x-4 gap-y-2">
+                        <input
+                            type="file"
+                            class="block text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-neutral-500"
+                            accept=".json,application/json"
+                            on:change=select_file
+                        />
+                        <Show when=move || script.with(|script| script.is_some())>
+                            <div class="flex items-center">
+                                <input
+                                    type="checkbox"
+                                    id="stop-on-error"
+                                    class="shrink-0 mt-0.5 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                    prop:checked=stop_on_error
+                                    on:change=move |_| stop_on_error.update(|value| *value = !*value)
+                                />
+                                <label
+                                    for="stop-on-error"
+                                    class="text-sm text-gray-500 ms-2 dark:text-gray-400"
+                                >
+                                    Stop on first error
+                                </label>
+                            </div>
+                            <ToolbarButton
+                                text=Signal::derive(move || {
+                                    if replay_action.pending().get() {
+                                        "Replaying...".to_string()
+                                    } else {
+                                        "Replay".to_string()
+                                    }
+                                })
+
+                                color=Color::Blue
+                                on_click=Callback::new(move |_| {
+                                    if replay_action.pending().get_untracked() {
+                                        return;
+                                    }
+                                    if recording.get_untracked().is_some() {
+                                        alert
+                                            .set(
+                                                Alert::error("Recording in progress")
+                                                    .with_details(
+                                                        "Stop the current recording before replaying a script.",
+                                                    ),
+                                            );
+                                        return;
+                                    }
+                                    let num_calls = statuses.with_untracked(|statuses| statuses.len());
+                                    let server = auth.get_untracked().base_url.to_string();
+                                    modal
+                                        .set(
+                                            Modal::with_title("Confirm replay")
+                                                .with_message(
+                                                    format!(
+                                                        "Are you sure you want to replay {} against {}? The changes will be applied immediately.",
+                                                        maybe_plural(num_calls, "API call", "API calls"),
+                                                        if server.is_empty() { "this server" } else { &server },
+                                                    ),
+                                                )
+                                                .with_button("Replay")
+                                                .with_dangerous_callback(move || {
+                                                    statuses
+                                                        .update(|statuses| {
+                                                            statuses.fill(ReplayStatus::Pending);
+                                                        });
+                                                    replay_action.dispatch(());
+                                                }),
+                                        );
+                                })
+                            >
+
+                                <IconPlayCircle/>
+                            </ToolbarButton>
+                            <ToolbarButton
+                                text="Close"
+                                color=Color::Gray
+                                on_click=Callback::new(move |_| {
+                                    if !replay_action.pending().get_untracked() {
+                                        script.set(None);
+                                        statuses.set(vec![]);
+                                    }
+                                })
+                            >
+
+                                <IconCancel/>
+                            </ToolbarButton>
+                        </Show>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn CallItem(idx: usize, call: RecordedCall, status: Memo<ReplayStatus>) -> impl IntoView {
+    let color = match call.method.as_str() {
+        "POST" => Color::Green,
+        "PUT" | "PATCH" => Color::Blue,
+        "DELETE" => Color::Red,
+        _ => Color::Gray,
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">{idx + 1}</span>
+            </ListItem>
+            <ListItem>
+                <Badge color=color>{call.method}</Badge>
+            </ListItem>
+            <ListTextItem>
+                <span class="font-mono break-all">{call.path}</span>
+            </ListTextItem>
+            <ListItem>
+                {move || {
+                    let (color, label) = status.get().badge();
+                    view! { <Badge color=color>{label}</Badge> }
+                }}
+
+            </ListItem>
+        </tr>
+    }
+}
+
+impl ReplayStatus {
+    fn badge(&self) -> (Color, String) {
+        match self {
+            ReplayStatus::Pending => (Color::Gray, "Pending".to_string()),
+            ReplayStatus::Running => (Color::Blue, "Running".to_string()),
+            ReplayStatus::Done => (Color::Green, "Applied".to_string()),
+            ReplayStatus::Failed(err) => (Color::Red, err.clone()),
+            ReplayStatus::Skipped => (Color::Yellow, "Skipped".to_string()),
+        }
+    }
+}
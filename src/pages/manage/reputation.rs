@@ -0,0 +1,641 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{net::IpAddr, sync::Arc};
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, stacked_input::StackedInput, Form, FormButtonBar,
+            FormElement, FormItem, FormSection,
+        },
+        icon::IconShieldCheck,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        Color,
+    },
+    core::{
+        dns::{dns_lookup, reverse_ip, reverse_name},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        AccessToken,
+    },
+    pages::maybe_plural,
+};
+
+struct Blocklist {
+    zone: &'static str,
+    name: &'static str,
+    delist: &'static str,
+}
+
+// Public lists checked by default, the zones can be changed before each check
+static BLOCKLISTS: &[Blocklist] = &[
+    Blocklist {
+        zone: "zen.spamhaus.org",
+        name: "Spamhaus ZEN",
+        delist: "https://check.spamhaus.org/",
+    },
+    Blocklist {
+        zone: "b.barracudacentral.org",
+        name: "Barracuda",
+        delist: "https://www.barracudacentral.org/rbl/removal-request",
+    },
+    Blocklist {
+        zone: "bl.spamcop.net",
+        name: "SpamCop",
+        delist: "https://www.spamcop.net/bl.shtml",
+    },
+    Blocklist {
+        zone: "bl.mailspike.net",
+        name: "Mailspike",
+        delist: "https://mailspike.org/anubis/lookup.html",
+    },
+    Blocklist {
+        zone: "psbl.surriel.com",
+        name: "PSBL",
+        delist: "https://psbl.org/remove",
+    },
+    Blocklist {
+        zone: "dnsbl.dronebl.org",
+        name: "DroneBL",
+        delist: "https://dronebl.org/lookup",
+    },
+    Blocklist {
+        zone: "dnsbl-1.uceprotect.net",
+        name: "UCEPROTECT Level 1",
+        delist: "https://www.uceprotect.net/en/rblcheck.php",
+    },
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Listing {
+    NotListed,
+    Listed {
+        codes: Vec<String>,
+        reason: Option<String>,
+    },
+    // Some lists refuse queries coming from large public resolvers
+    Refused(String),
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Check {
+    color: Color,
+    status: &'static str,
+    summary: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AddressReport {
+    ip: IpAddr,
+    reverse_dns: Check,
+    helo: Check,
+    listings: Vec<(String, Listing)>,
+}
+
+#[derive(Clone, Debug)]
+struct CheckRequest {
+    ips: Vec<IpAddr>,
+    hostname: Option<String>,
+    zones: Vec<String>,
+}
+
+// Checks the outbound addresses against public DNS blocklists, and that their
+// reverse DNS matches the hostname announced in EHLO, the two most common
+// reasons for remote servers rejecting messages.
+#[component]
+pub fn TroubleshootReputation() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("troubleshoot-reputation")
+        .into_signal();
+    let reports = create_rw_signal(None::<Vec<AddressReport>>);
+
+    data.update(|data| {
+        data.array_set("zones", BLOCKLISTS.iter().map(|list| list.zone));
+    });
+
+    let defaults = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_outbound(&auth).await }
+        },
+    );
+    create_effect(move |_| match defaults.get() {
+        Some(Ok((ips, hostname))) => {
+            data.update(|data| {
+                if !data.has_value("ips") {
+                    data.array_set("ips", ips.iter().map(|ip| ip.to_string()));
+                }
+                if let Some(hostname) = hostname.filter(|_| !data.has_value("hostname")) {
+                    data.set("hostname", hostname);
+                }
+            });
+        }
+        Some(Err(http::Error::Unauthorized)) => {
+            use_navigate()("/login", Default::default());
+        }
+        Some(Err(err)) => {
+            alert.set(Alert::from(err));
+        }
+        None => {}
+    });
+
+    let check = create_action(move |request: &Arc<CheckRequest>| {
+        let request = request.clone();
+        let auth = auth.get();
+
+        async move {
+            let mut results = Vec::with_capacity(request.ips.len());
+            for ip in &request.ips {
+                match check_address(&auth, *ip, request.hostname.as_deref(), &request.zones).await {
+                    Ok(report) => results.push(report),
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+            reports.set(Some(results));
+        }
+    });
+    let pending = check.pending();
+
+    view! {
+        {move || match reports.get() {
+            Some(results) => {
+                view! {
+                    <ReportView>
+                        <ReputationSummary reports=results.clone()/>
+                        {results
+                            .into_iter()
+                            .map(|report| view! { <AddressResult report/> })
+                            .collect_view()}
+                        <div class="flex justify-end">
+                            <Button
+                                text="Close"
+                                color=Color::Blue
+                                on_click=move |_| {
+                                    reports.set(None);
+                                }
+                            />
+
+                        </div>
+                    </ReportView>
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="IP Reputation"
+                        subtitle="Check the outbound addresses against DNS blocklists and verify their reverse DNS"
+                    >
+                        <Alerts/>
+                        <FormSection>
+                            <FormItem
+                                label="Outbound Addresses"
+                                tooltip=concat!(
+                                    "Addresses used to deliver messages to remote servers. ",
+                                    "Defaults to the configured source addresses, or to the ",
+                                    "addresses of the server hostname.",
+                                )
+                            >
+
+                                <StackedInput
+                                    element=FormElement::new("ips", data)
+                                    add_button_text="Add address".to_string()
+                                    placeholder="192.0.2.1"
+                                />
+                            </FormItem>
+                            <FormItem
+                                label="EHLO Hostname"
+                                tooltip="Hostname announced to remote servers, it should match the reverse DNS of every outbound address."
+                            >
+                                <InputText
+                                    element=FormElement::new("hostname", data)
+                                    placeholder="mx.example.org"
+                                />
+                            </FormItem>
+                            <FormItem
+                                label="Blocklists"
+                                tooltip="DNS zones of the blocklists to query."
+                            >
+                                <StackedInput
+                                    element=FormElement::new("zones", data)
+                                    add_button_text="Add blocklist".to_string()
+                                    placeholder="zen.spamhaus.org"
+                                />
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Check"
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=Callback::new(move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            check
+                                                .dispatch(
+                                                    Arc::new(CheckRequest {
+                                                        ips: data
+                                                            .array_value("ips")
+                                                            .filter_map(|ip| ip.parse().ok())
+                                                            .collect(),
+                                                        hostname: data.value::<String>("hostname"),
+                                                        zones: data
+                                                            .array_value("zones")
+                                                            .map(|zone| zone.trim_end_matches('.').to_string())
+                                                            .collect(),
+                                                    }),
+                                                );
+                                        }
+                                    });
+                                })
+                            >
+
+                                <IconShieldCheck/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn ReputationSummary(reports: Vec<AddressReport>) -> impl IntoView {
+    let listed = reports
+        .iter()
+        .filter(|report| {
+            report
+                .listings
+                .iter()
+                .any(|(_, listing)| matches!(listing, Listing::Listed { .. }))
+        })
+        .count();
+    let dns_problems = reports
+        .iter()
+        .filter(|report| {
+            report.reverse_dns.color != Color::Green || report.helo.color != Color::Green
+        })
+        .count();
+    let total = maybe_plural(reports.len(), "address", "addresses");
+    let listed_text = format!("{listed} of {total}");
+    let dns_problems_text = format!("{dns_problems} of {total}");
+    let (color, status) = if listed > 0 {
+        (Color::Red, "Listed")
+    } else if dns_problems > 0 {
+        (Color::Yellow, "Warning")
+    } else {
+        (Color::Green, "Clean")
+    };
+
+    view! {
+        <ReportSection title="Summary">
+            <ReportItem label="Result">
+                <Badge color>{status}</Badge>
+            </ReportItem>
+            <ReportItem label="Listed">
+                <ReportTextValue value=listed_text/>
+            </ReportItem>
+            <ReportItem label="DNS Problems">
+                <ReportTextValue value=dns_problems_text/>
+            </ReportItem>
+        </ReportSection>
+    }
+}
+
+#[component]
+fn AddressResult(report: AddressReport) -> impl IntoView {
+    view! {
+        <ReportSection title=report.ip.to_string()>
+            <CheckItem label="Reverse DNS" check=report.reverse_dns/>
+            <CheckItem label="EHLO Hostname" check=report.helo/>
+            <ReportItem label="Blocklists">
+                <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                        {report
+                            .listings
+                            .into_iter()
+                            .map(|(zone, listing)| {
+                                let blocklist = BLOCKLISTS.iter().find(|list| list.zone == zone);
+                                let name = blocklist
+                                    .map(|list| list.name.to_string())
+                                    .unwrap_or_else(|| zone.clone());
+                                let delist = blocklist
+                                    .filter(|_| matches!(listing, Listing::Listed { .. }))
+                                    .map(|list| list.delist);
+                                let (color, status, details) = match listing {
+                                    Listing::NotListed => (Color::Green, "Not listed", None),
+                                    Listing::Listed { codes, reason } => {
+                                        (
+                                            Color::Red,
+                                            "Listed",
+                                            Some(reason.unwrap_or_else(|| codes.join(", "))),
+                                        )
+                                    }
+                                    Listing::Refused(details) => {
+                                        (Color::Gray, "Refused", Some(details))
+                                    }
+                                    Listing::Failed(details) => {
+                                        (Color::Yellow, "Error", Some(details))
+                                    }
+                                };
+                                view! {
+                                    <tr class="align-top">
+                                        <td class="py-1.5 pe-4 whitespace-nowrap">
+                                            <span class="block font-medium text-gray-800 dark:text-gray-200">
+                                                {name}
+                                            </span>
+                                            <span class="block text-xs font-mono text-gray-500">
+                                                {zone}
+                                            </span>
+                                        </td>
+                                        <td class="py-1.5 pe-4">
+                                            <Badge color>{status}</Badge>
+                                        </td>
+                                        <td class="py-1.5 break-all text-gray-600 dark:text-gray-400">
+                                            {details}
+                                            {delist
+                                                .map(|url| {
+                                                    view! {
+                                                        <a
+                                                            class="block mt-1 text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                            href=url
+                                                            target="_blank"
+                                                            rel="noopener"
+                                                        >
+                                                            Request removal
+                                                        </a>
+                                                    }
+                                                })}
+                                        </td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </ReportItem>
+        </ReportSection>
+    }
+}
+
+#[component]
+fn CheckItem(label: &'static str, check: Check) -> impl IntoView {
+    view! {
+        <ReportItem label>
+            <div class="mt-2 flex items-start gap-x-2">
+                <Badge color=check.color>{check.status}</Badge>
+                <span class="text-sm text-gray-600 dark:text-gray-400">{check.summary}</span>
+            </div>
+        </ReportItem>
+    }
+}
+
+async fn check_address(
+    auth: &AccessToken,
+    ip: IpAddr,
+    hostname: Option<&str>,
+    zones: &[String],
+) -> http::Result<AddressReport> {
+    let ptr = dns_lookup(auth, &reverse_name(ip), "PTR")
+        .await?
+        .into_iter()
+        .map(|name| name.trim_end_matches('.').to_lowercase())
+        .collect::<Vec<_>>();
+    let record_type = if ip.is_ipv4() { "A" } else { "AAAA" };
+
+    // Receivers expect the PTR name to resolve back to the same address
+    let mut confirmed = None;
+    for name in &ptr {
+        if resolves_to(auth, name, record_type, ip).await? {
+            confirmed = Some(name.clone());
+            break;
+        }
+    }
+    let reverse_dns = match (&confirmed, ptr.first()) {
+        (Some(name), _) => Check {
+            color: Color::Green,
+            status: "Pass",
+            summary: format!("{ip} points to {name}, which resolves back to {ip}."),
+        },
+        (None, Some(name)) => Check {
+            color: Color::Yellow,
+            status: "Warning",
+            summary: format!(
+                "{ip} points to {name}, which does not resolve back to {ip}. Many receivers require forward-confirmed reverse DNS."
+            ),
+        },
+        (None, None) => Check {
+            color: Color::Red,
+            status: "Fail",
+            summary: format!(
+                "{ip} has no PTR record. Most receivers reject messages from addresses without reverse DNS."
+            ),
+        },
+    };
+
+    let helo = match hostname.map(|hostname| hostname.trim_end_matches('.').to_lowercase()) {
+        Some(hostname) if ptr.contains(&hostname) => Check {
+            color: Color::Green,
+            status: "Pass",
+            summary: format!("{hostname} matches the reverse DNS of {ip}."),
+        },
+        Some(hostname) if resolves_to(auth, &hostname, record_type, ip).await? => Check {
+            color: Color::Yellow,
+            status: "Warning",
+            summary: format!(
+                "{hostname} resolves to {ip} but does not match its reverse DNS{}.",
+                ptr.first()
+                    .map(|name| format!(" ({name})"))
+                    .unwrap_or_default()
+            ),
+        },
+        Some(hostname) => Check {
+            color: Color::Red,
+            status: "Fail",
+            summary: format!(
+                "{hostname} does not resolve to {ip}. Receivers may reject the EHLO command."
+            ),
+        },
+        None => Check {
+            color: Color::Gray,
+            status: "Skipped",
+            summary: "No EHLO hostname was provided.".to_string(),
+        },
+    };
+
+    let mut listings = Vec::with_capacity(zones.len());
+    for zone in zones {
+        let name = reverse_ip(ip, zone);
+        let listing = match dns_lookup(auth, &name, "A").await {
+            Ok(codes) if codes.is_empty() => Listing::NotListed,
+            Ok(codes) => match classify(&codes) {
+                Some(listing) => listing,
+                None => Listing::Listed {
+                    reason: dns_lookup(auth, &name, "TXT")
+                        .await
+                        .ok()
+                        .and_then(|reasons| reasons.into_iter().next()),
+                    codes,
+                },
+            },
+            Err(http::Error::NotFound) => Listing::NotListed,
+            Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+            Err(err) => Listing::Failed(Alert::from(err).message),
+        };
+        listings.push((zone.clone(), listing));
+    }
+
+    Ok(AddressReport {
+        ip,
+        reverse_dns,
+        helo,
+        listings,
+    })
+}
+
+// Listings are returned as 127.0.0.0/8 addresses, answers outside that range
+// or in 127.255.255.0/24 are errors rather than listings.
+fn classify(codes: &[String]) -> Option<Listing> {
+    if codes.iter().any(|code| code.starts_with("127.255.255.")) {
+        Some(Listing::Refused(format!(
+            "The list refused the query ({}), it may not accept queries from the resolver used by the server.",
+            codes.join(", ")
+        )))
+    } else if !codes.iter().any(|code| code.starts_with("127.")) {
+        Some(Listing::Failed(format!(
+            "Unexpected answer {}, the zone may no longer be operated.",
+            codes.join(", ")
+        )))
+    } else {
+        None
+    }
+}
+
+async fn resolves_to(
+    auth: &AccessToken,
+    name: &str,
+    record_type: &str,
+    ip: IpAddr,
+) -> http::Result<bool> {
+    match dns_lookup(auth, name, record_type).await {
+        Ok(addresses) => Ok(addresses
+            .iter()
+            .any(|address| address.parse::<IpAddr>().is_ok_and(|address| address == ip))),
+        Err(http::Error::NotFound) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+// Source addresses are expressions, only literal addresses are picked up.
+// When none are configured, the addresses of the server hostname are used.
+async fn fetch_outbound(auth: &AccessToken) -> http::Result<(Vec<IpAddr>, Option<String>)> {
+    let settings = HttpRequest::get("/api/settings/keys")
+        .with_authorization(auth)
+        .with_parameter(
+            "keys",
+            "lookup.default.hostname,queue.outbound.hostname,queue.outbound.source-ip.v4,queue.outbound.source-ip.v6",
+        )
+        .with_parameter(
+            "prefixes",
+            "queue.outbound.source-ip.v4,queue.outbound.source-ip.v6",
+        )
+        .send::<AHashMap<String, Option<String>>>()
+        .await?;
+
+    let mut ips = Vec::new();
+    for (key, value) in &settings {
+        if let Some(value) = value
+            .as_deref()
+            .filter(|_| key.starts_with("queue.outbound.source-ip"))
+        {
+            for ip in value
+                .split(|ch: char| !(ch.is_ascii_hexdigit() || ch == '.' || ch == ':'))
+                .filter_map(|token| token.parse::<IpAddr>().ok())
+            {
+                if !ip.is_unspecified() && !ip.is_loopback() && !ips.contains(&ip) {
+                    ips.push(ip);
+                }
+            }
+        }
+    }
+
+    let literal = |key: &str| {
+        settings
+            .get(key)
+            .and_then(|value| value.as_deref())
+            .map(|value| value.trim().trim_matches(|ch| ch == '\'' || ch == '"'))
+            .filter(|value| {
+                value.contains('.')
+                    && value
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || ch == '.' || ch == '-')
+            })
+            .map(|value| value.to_lowercase())
+    };
+    let hostname =
+        literal("queue.outbound.hostname").or_else(|| literal("lookup.default.hostname"));
+
+    if ips.is_empty() {
+        if let Some(hostname) = &hostname {
+            for record_type in ["A", "AAAA"] {
+                for address in dns_lookup(auth, hostname, record_type).await? {
+                    if let Ok(ip) = address.parse::<IpAddr>() {
+                        ips.push(ip);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((ips, hostname))
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_reputation(self) -> Self {
+        self.new_schema("troubleshoot-reputation")
+            .new_field("ips")
+            .typ(Type::Array)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsIp])
+            .build()
+            .new_field("hostname")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Lowercase, Transformer::Trim],
+                [Validator::IsHost],
+            )
+            .build()
+            .new_field("zones")
+            .typ(Type::Array)
+            .input_check(
+                [Transformer::Lowercase, Transformer::Trim],
+                [Validator::Required, Validator::IsDomain],
+            )
+            .build()
+            .build()
+    }
+}
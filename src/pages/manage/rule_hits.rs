@@ -0,0 +1,337 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{cmp::Reverse, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconAdjustmentsHorizontal, IconRefresh},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+    },
+    pages::{config::schema::metrics::RULE_TYPES, FormatDateTime},
+};
+
+const SETTINGS_URL: &str = "/settings/metrics-rule-hits/edit";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleHitReport {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub rules: Vec<RuleHits>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleHits {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub id: String,
+    #[serde(default)]
+    pub hits: u64,
+    #[serde(default)]
+    pub last_hit: Option<DateTime<Utc>>,
+}
+
+#[component]
+pub fn RuleHitList() -> impl IntoView {
+    let query = use_query_map();
+    let rule_type = create_memo(move |_| {
+        query
+            .with(|q| q.get("type").cloned())
+            .filter(|typ| RULE_TYPES.iter().any(|(id, _)| id == typ))
+    });
+    let unused = create_memo(move |_| query.with(|q| q.get("show").is_some_and(|s| s == "unused")));
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let report = create_resource(
+        move || rule_type.get(),
+        move |rule_type| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/telemetry/rule-hits")
+                    .with_authorization(&auth)
+                    .with_optional_parameter("type", rule_type)
+                    .send::<RuleHitReport>()
+                    .await
+                    .map(|mut report| {
+                        report
+                            .rules
+                            .sort_by_key(|rule| (Reverse(rule.hits), rule.id.clone()));
+                        Arc::new(report)
+                    })
+            }
+        },
+    );
+
+    let navigate_to = move |typ: Option<String>, show: Option<String>| {
+        use_navigate()(
+            &UrlBuilder::new("/manage/rule-hits")
+                .with_optional_parameter("type", typ.filter(|typ| !typ.is_empty()))
+                .with_optional_parameter("show", show.filter(|show| show == "unused"))
+                .finish(),
+            Default::default(),
+        );
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Rule hits"
+                subtitle=Signal::derive(move || {
+                    match report.get().and_then(|report| report.ok()).and_then(|report| report.since)
+                    {
+                        Some(since) => format!("Rule matches counted since {}", since.format_date()),
+                        None => "Which routing, spam filter and Sieve rules match most often".to_string(),
+                    }
+                })
+            >
+
+                <Toolbar slot>
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            navigate_to(
+                                Some(event_target_value(&ev)),
+                                unused.get_untracked().then(|| "unused".to_string()),
+                            );
+                        }
+                    >
+
+                        <option value="" selected=move || rule_type.get().is_none()>
+                            All rules
+                        </option>
+                        {RULE_TYPES
+                            .iter()
+                            .map(|(id, name)| {
+                                view! {
+                                    <option
+                                        value=*id
+                                        selected=move || rule_type.get().is_some_and(|typ| typ == *id)
+                                    >
+                                        {*name}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            navigate_to(rule_type.get_untracked(), Some(event_target_value(&ev)));
+                        }
+                    >
+
+                        <option value="all" selected=move || !unused.get()>
+                            Most fired
+                        </option>
+                        <option value="unused" selected=move || unused.get()>
+                            Never fired
+                        </option>
+                    </select>
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            report.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Settings"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            use_navigate()(SETTINGS_URL, Default::default());
+                        })
+                    >
+
+                        <IconAdjustmentsHorizontal/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match report.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(report)) if !report.enabled => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Hit counting is disabled"
+                                        subtitle="Enable rule hit counting to find out which rules fire and which never do."
+                                        button_text="Enable"
+                                        button_action=Callback::new(move |_| {
+                                            use_navigate()(SETTINGS_URL, Default::default());
+                                        })
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(report)) => {
+                            let total = report.rules.iter().map(|rule| rule.hits).sum::<u64>();
+                            let show_unused = unused.get();
+                            let rules = report
+                                .rules
+                                .iter()
+                                .filter(|rule| !show_unused || rule.hits == 0)
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            if rules.is_empty() {
+                                let (title, subtitle) = if show_unused {
+                                    ("No dead rules", "Every instrumented rule matched at least once.")
+                                } else {
+                                    ("No rules", "No instrumented rules were found.")
+                                };
+                                return Some(
+                                    view! { <ZeroResults title=title subtitle=subtitle/> }
+                                        .into_view(),
+                                );
+                            }
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Rule".to_string(),
+                                        "Type".to_string(),
+                                        "Hits".to_string(),
+                                        "Share".to_string(),
+                                        "Last hit".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || rules.clone()
+                                            key=|rule| (rule.typ.clone(), rule.id.clone())
+                                            let:rule
+                                        >
+                                            <RuleHitItem rule=rule total=total/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn RuleHitItem(rule: RuleHits, total: u64) -> impl IntoView {
+    let typ = RULE_TYPES
+        .iter()
+        .find_map(|(id, name)| (*id == rule.typ).then_some(*name))
+        .unwrap_or(rule.typ.as_str())
+        .to_string();
+    let share = if total > 0 {
+        format!("{:.1}%", rule.hits as f64 * 100.0 / total as f64)
+    } else {
+        "0%".to_string()
+    };
+    let edit_url = match rule.typ.as_str() {
+        "spam-filter" => Some(
+            UrlBuilder::new("/settings/spam-rule")
+                .with_subpath(&rule.id)
+                .with_subpath("edit")
+                .finish(),
+        ),
+        "routing" => Some("/settings/smtp-out-routing/edit".to_string()),
+        _ => None,
+    };
+    let name = match edit_url {
+        Some(url) => view! {
+            <A
+                class="text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                href=url
+            >
+                {rule.id}
+            </A>
+        }
+        .into_view(),
+        None => view! {
+            <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                {rule.id}
+            </span>
+        }
+        .into_view(),
+    };
+
+    view! {
+        <tr>
+            <ListItem subclass="ps-6 pe-6 py-3">{name}</ListItem>
+            <ListTextItem>{typ}</ListTextItem>
+            <ListTextItem>{rule.hits.to_string()}</ListTextItem>
+            <ListTextItem>{share}</ListTextItem>
+            <ListItem>
+                {match rule.last_hit.filter(|_| rule.hits > 0) {
+                    Some(last_hit) => {
+                        view! {
+                            <Timestamp
+                                date=last_hit
+                                relative=true
+                                attr:class="text-sm text-gray-500"
+                            />
+                        }
+                            .into_view()
+                    }
+                    None => {
+                        view! {
+                            <Badge color=Color::Yellow>
+                                Never fired
+                            </Badge>
+                        }
+                            .into_view()
+                    }
+                }}
+
+            </ListItem>
+        </tr>
+    }
+}
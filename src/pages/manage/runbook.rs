@@ -0,0 +1,923 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::{use_navigate, use_query_map};
+use leptos_use::{use_clipboard, UseClipboardReturn};
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconPlayCircle,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        Color,
+    },
+    core::{
+        dns::dns_lookup,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        AccessToken,
+    },
+    pages::{
+        config::{Settings, SettingsValues},
+        directory::Principal,
+        maybe_plural,
+        queue::messages::{deserialize_datetime, Message, Status},
+        FormatDateTime, List,
+    },
+};
+
+const LOG_LIMIT: u32 = 100;
+const BAN_LIMIT: u32 = 100;
+const MAX_DETAILS: usize = 10;
+// Certificates are renewed well before they expire, one that is this close
+// to its expiry date is most likely failing to renew.
+const RENEWAL_WARNING_DAYS: i64 = 14;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Runbook {
+    DeferredMail,
+    Login,
+    Certificate,
+}
+
+static RUNBOOKS: &[Runbook] = &[Runbook::DeferredMail, Runbook::Login, Runbook::Certificate];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warning,
+    Fail,
+    Info,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CheckResult {
+    title: &'static str,
+    status: CheckStatus,
+    summary: String,
+    details: Vec<String>,
+    link: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RunbookReport {
+    runbook: Runbook,
+    target: String,
+    started_at: DateTime<Utc>,
+    checks: Vec<CheckResult>,
+    finished: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct LogEntry {
+    #[serde(deserialize_with = "deserialize_datetime")]
+    timestamp: DateTime<Utc>,
+    level: String,
+    event: String,
+    details: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct BannedAddress {
+    ip: String,
+    reason: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateStatus {
+    #[serde(default)]
+    subjects: Vec<String>,
+    #[serde(default)]
+    not_after: Option<DateTime<Utc>>,
+}
+
+// Walks the administrator through a fixed sequence of checks for a common
+// problem, the results can be copied as plain text and attached to a support
+// request.
+#[component]
+pub fn TroubleshootRunbooks() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let query = use_query_map();
+    let selected = create_rw_signal(
+        query
+            .get_untracked()
+            .get("runbook")
+            .and_then(|id| Runbook::parse(id))
+            .unwrap_or(Runbook::DeferredMail),
+    );
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("troubleshoot-runbook")
+        .into_signal();
+    let report = create_rw_signal(None::<RunbookReport>);
+
+    if let Some(target) = query.get_untracked().get("target") {
+        data.update(|data| data.set("target", target));
+    }
+
+    let run = create_action(move |(runbook, target): &(Runbook, String)| {
+        let runbook = *runbook;
+        let target = target.clone();
+        let auth = auth.get();
+
+        async move {
+            report.set(Some(RunbookReport {
+                runbook,
+                target: target.clone(),
+                started_at: Utc::now(),
+                checks: vec![],
+                finished: false,
+            }));
+            let result = match runbook {
+                Runbook::DeferredMail => run_deferred_mail(&auth, &target, report).await,
+                Runbook::Login => run_login(&auth, &target, report).await,
+                Runbook::Certificate => run_certificate(&auth, &target, report).await,
+            };
+            match result {
+                Ok(_) => {
+                    report.update(|report| {
+                        if let Some(report) = report {
+                            report.finished = true;
+                        }
+                    });
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    report.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = run.pending();
+
+    view! {
+        {move || match report.get() {
+            Some(current) => {
+                view! {
+                    <RunbookResult
+                        report=current
+                        on_close=move |_| {
+                            report.set(None);
+                        }
+                    />
+                }
+                    .into_view()
+            }
+            None => {
+                view! {
+                    <Form
+                        title="Guided Troubleshooting"
+                        subtitle="Run a series of checks for a common problem and share the results with support"
+                    >
+                        <Alerts/>
+                        <FormSection title="Problem">
+                            <div class="grid sm:grid-cols-2 lg:grid-cols-3 gap-3">
+                                {RUNBOOKS
+                                    .iter()
+                                    .map(|runbook| {
+                                        let runbook = *runbook;
+                                        view! {
+                                            <button
+                                                type="button"
+                                                class="p-4 text-start bg-white border rounded-xl shadow-sm hover:shadow-md transition dark:bg-slate-900 dark:border-gray-700"
+                                                class:border-blue-600=move || selected.get() == runbook
+                                                class:ring-1=move || selected.get() == runbook
+                                                class:ring-blue-600=move || selected.get() == runbook
+                                                on:click=move |_| selected.set(runbook)
+                                            >
+                                                <span class="block font-semibold text-gray-800 dark:text-gray-200">
+                                                    {runbook.title()}
+                                                </span>
+                                                <span class="block mt-1 text-sm text-gray-500">
+                                                    {runbook.description()}
+                                                </span>
+                                            </button>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        </FormSection>
+                        <FormSection>
+                            <FormItem label=Signal::derive(move || {
+                                selected.get().target_label().to_string()
+                            })>
+                                <InputText
+                                    element=FormElement::new("target", data)
+                                    placeholder=Signal::derive(move || {
+                                        selected.get().placeholder().to_string()
+                                    })
+                                />
+                            </FormItem>
+                        </FormSection>
+                        <FormButtonBar>
+                            <Button
+                                text="Start"
+                                color=Color::Blue
+                                disabled=pending
+                                on_click=Callback::new(move |_| {
+                                    data.update(|data| {
+                                        if data.validate_form() {
+                                            run.dispatch((
+                                                selected.get_untracked(),
+                                                data.value::<String>("target").unwrap_or_default(),
+                                            ));
+                                        }
+                                    });
+                                })
+                            >
+
+                                <IconPlayCircle/>
+                            </Button>
+                        </FormButtonBar>
+                    </Form>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
+
+#[component]
+fn RunbookResult(report: RunbookReport, #[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let UseClipboardReturn {
+        is_supported,
+        copied,
+        copy,
+        ..
+    } = use_clipboard();
+    let text = report.to_text();
+    let finished = report.finished;
+
+    view! {
+        <ReportView>
+            <ReportSection title=report.runbook.title()>
+                <ReportItem label="Checked">
+                    <ReportTextValue value=report.target.clone()/>
+                </ReportItem>
+                <ReportItem label="Started">
+                    <ReportTextValue value=report.started_at.format_date_time()/>
+                </ReportItem>
+                {report
+                    .checks
+                    .into_iter()
+                    .map(|check| {
+                        let (color, label) = check.status.badge();
+                        view! {
+                            <ReportItem label=check.title>
+                                <div class="mt-2 space-y-1">
+                                    <div class="flex flex-wrap items-center gap-2">
+                                        <Badge color>{label}</Badge>
+                                        <span class="text-sm text-gray-800 dark:text-gray-200">
+                                            {check.summary}
+                                        </span>
+                                    </div>
+                                    <div class="text-xs font-mono break-all text-gray-500 space-y-1">
+                                        {check
+                                            .details
+                                            .into_iter()
+                                            .map(|detail| view! { <span class="block">{detail}</span> })
+                                            .collect_view()}
+                                    </div>
+                                    {check
+                                        .link
+                                        .map(|link| {
+                                            view! {
+                                                <a
+                                                    class="text-sm text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                                                    href=link
+                                                >
+                                                    Open
+                                                </a>
+                                            }
+                                        })}
+
+                                </div>
+                            </ReportItem>
+                        }
+                    })
+                    .collect_view()}
+                <ReportItem label="Status" hide=finished>
+                    <ReportTextValue value="Running checks..."/>
+                </ReportItem>
+            </ReportSection>
+            <div class="flex justify-end gap-x-2">
+                <Button
+                    text=Signal::derive(move || {
+                        if copied.get() { "Copied" } else { "Copy report" }.to_string()
+                    })
+                    color=Color::Gray
+                    disabled=Signal::derive(move || !finished || !is_supported.get())
+                    on_click=move |_| copy(&text)
+                />
+                <Button
+                    text="Close"
+                    color=Color::Blue
+                    on_click=move |_| {
+                        on_close.call(());
+                    }
+                />
+            </div>
+        </ReportView>
+    }
+}
+
+async fn run_deferred_mail(
+    auth: &AccessToken,
+    address: &str,
+    report: RwSignal<Option<RunbookReport>>,
+) -> http::Result<()> {
+    let domain = address
+        .rsplit_once('@')
+        .map_or(address, |(_, domain)| domain);
+
+    record(report, "Queue", check_queue(auth, address, domain).await)?;
+    record(report, "MX Records", check_mx(auth, domain).await)?;
+    record(report, "Server Logs", check_logs(auth, domain).await)?;
+    record(
+        report,
+        "Delivery Test",
+        Ok(CheckResult::new(
+            CheckStatus::Info,
+            "Run the delivery troubleshooter to follow a live delivery attempt step by step.",
+        )
+        .with_link(format!("/manage/troubleshoot/delivery?target={address}"))),
+    )
+}
+
+async fn run_login(
+    auth: &AccessToken,
+    name: &str,
+    report: RwSignal<Option<RunbookReport>>,
+) -> http::Result<()> {
+    record(report, "Account", check_account(auth, name).await)?;
+    record(report, "Banned Addresses", check_bans(auth).await)?;
+    record(report, "Server Logs", check_logs(auth, name).await)
+}
+
+async fn run_certificate(
+    auth: &AccessToken,
+    domain: &str,
+    report: RwSignal<Option<RunbookReport>>,
+) -> http::Result<()> {
+    let provider = match find_acme_provider(auth, domain).await {
+        Ok(Some(provider)) => {
+            record(
+                report,
+                "ACME Provider",
+                Ok(CheckResult::new(
+                    CheckStatus::Pass,
+                    format!("{domain} is managed by the ACME provider {provider}."),
+                )
+                .with_link(format!("/settings/acme/{provider}/edit"))),
+            )?;
+            Some(provider)
+        }
+        Ok(None) => {
+            record(
+                report,
+                "ACME Provider",
+                Ok(CheckResult::new(
+                    CheckStatus::Fail,
+                    format!("No ACME provider lists {domain} in its domains."),
+                )
+                .with_link("/settings/acme".to_string())),
+            )?;
+            None
+        }
+        Err(err) => {
+            record(report, "ACME Provider", Err(err))?;
+            None
+        }
+    };
+
+    if let Some(provider) = provider {
+        record(
+            report,
+            "Certificate",
+            check_certificate(auth, &provider, domain).await,
+        )?;
+    }
+    record(report, "DNS Records", check_domain_dns(auth, domain).await)?;
+    record(report, "Server Logs", check_logs(auth, "acme").await)
+}
+
+async fn check_queue(auth: &AccessToken, address: &str, domain: &str) -> http::Result<CheckResult> {
+    let messages = HttpRequest::get("/api/queue/messages")
+        .with_authorization(auth)
+        .with_parameter("text", address)
+        .with_parameter("values", "1")
+        .with_parameter("limit", LOG_LIMIT.to_string())
+        .send::<List<Message>>()
+        .await?
+        .items;
+
+    let mut details = Vec::new();
+    let mut temp_failures = 0;
+    let mut perm_failures = 0;
+    for message in &messages {
+        for item in message
+            .domains
+            .iter()
+            .filter(|item| item.name.eq_ignore_ascii_case(domain))
+        {
+            let status = match &item.status {
+                Status::Scheduled => "waiting for the next attempt".to_string(),
+                Status::Completed(response) => format!("delivered ({response})"),
+                Status::TemporaryFailure(reason) => {
+                    temp_failures += 1;
+                    format!("temporary failure: {reason}")
+                }
+                Status::PermanentFailure(reason) => {
+                    perm_failures += 1;
+                    format!("permanent failure: {reason}")
+                }
+            };
+            details.push(format!(
+                "Message {} from {}, attempt {}: {status}",
+                message.id,
+                message.return_path(),
+                item.retry_num + 1
+            ));
+        }
+    }
+    details.truncate(MAX_DETAILS);
+
+    let link = Some(format!("/manage/queue/messages?filter={address}"));
+    let check = if messages.is_empty() {
+        CheckResult::new(
+            CheckStatus::Pass,
+            format!("There are no queued messages for {address}."),
+        )
+    } else if perm_failures > 0 {
+        CheckResult::new(
+            CheckStatus::Fail,
+            format!(
+                "{} could not be delivered and will be returned to the sender.",
+                maybe_plural(perm_failures, "message", "messages")
+            ),
+        )
+    } else if temp_failures > 0 {
+        CheckResult::new(
+            CheckStatus::Warning,
+            format!(
+                "{} deferred after a temporary failure.",
+                maybe_plural(temp_failures, "message was", "messages were")
+            ),
+        )
+    } else {
+        CheckResult::new(
+            CheckStatus::Info,
+            format!(
+                "{} waiting for delivery.",
+                maybe_plural(messages.len(), "message is", "messages are")
+            ),
+        )
+    };
+
+    Ok(CheckResult {
+        details,
+        link,
+        ..check
+    })
+}
+
+async fn check_mx(auth: &AccessToken, domain: &str) -> http::Result<CheckResult> {
+    let records = dns_lookup(auth, domain, "MX").await?;
+    if !records.is_empty() {
+        Ok(CheckResult::new(
+            CheckStatus::Pass,
+            format!(
+                "{domain} publishes {}.",
+                maybe_plural(records.len(), "MX record", "MX records")
+            ),
+        )
+        .with_details(records))
+    } else if !dns_lookup(auth, domain, "A").await?.is_empty() {
+        Ok(CheckResult::new(
+            CheckStatus::Warning,
+            format!("{domain} has no MX records, mail is delivered to the host in its A record."),
+        ))
+    } else {
+        Ok(CheckResult::new(
+            CheckStatus::Fail,
+            format!("{domain} has no MX or A records, mail to it cannot be delivered."),
+        ))
+    }
+}
+
+async fn check_account(auth: &AccessToken, name: &str) -> http::Result<CheckResult> {
+    let principal = match HttpRequest::get(("/api/principal", name))
+        .with_authorization(auth)
+        .try_send::<Principal>()
+        .await?
+    {
+        Some(principal) => principal,
+        None => {
+            return Ok(CheckResult::new(
+                CheckStatus::Fail,
+                format!("There is no account named {name}."),
+            )
+            .with_link("/manage/directory/accounts".to_string()));
+        }
+    };
+
+    let mut problems = Vec::new();
+    if principal.secrets.len() == 0 {
+        problems.push("The account has no password or app passwords.".to_string());
+    }
+    if principal
+        .disabled_permissions
+        .as_string_list()
+        .iter()
+        .any(|permission| permission == "authenticate")
+    {
+        problems.push("The authenticate permission is disabled for the account.".to_string());
+    }
+    if let Some(expires_at) = principal
+        .expires_at
+        .as_str()
+        .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|value| value.with_timezone(&Utc))
+        .filter(|expires_at| *expires_at < Utc::now())
+    {
+        problems.push(format!(
+            "The account expired on {}.",
+            expires_at.format_date_time()
+        ));
+    }
+
+    let link = format!("/manage/directory/accounts/{name}/edit");
+    Ok(if problems.is_empty() {
+        CheckResult::new(
+            CheckStatus::Pass,
+            format!("{name} exists and is allowed to authenticate."),
+        )
+    } else {
+        CheckResult::new(CheckStatus::Fail, format!("{name} cannot authenticate."))
+            .with_details(problems)
+    }
+    .with_link(link))
+}
+
+async fn check_bans(auth: &AccessToken) -> http::Result<CheckResult> {
+    let banned = HttpRequest::get("/api/security/bans")
+        .with_authorization(auth)
+        .with_parameter("page", "1")
+        .with_parameter("limit", BAN_LIMIT.to_string())
+        .send::<List<BannedAddress>>()
+        .await?
+        .items
+        .into_iter()
+        .filter(|ban| ban.reason == "authFailure")
+        .map(|ban| ban.ip)
+        .collect::<Vec<_>>();
+
+    Ok(if banned.is_empty() {
+        CheckResult::new(
+            CheckStatus::Pass,
+            "No addresses are banned for authentication failures.",
+        )
+    } else {
+        CheckResult::new(
+            CheckStatus::Warning,
+            format!(
+                "{} banned for authentication failures, the user may be connecting from one of them.",
+                maybe_plural(banned.len(), "address is", "addresses are")
+            ),
+        )
+        .with_details(banned.into_iter().take(MAX_DETAILS).collect())
+    }
+    .with_link("/manage/security/bans".to_string()))
+}
+
+async fn find_acme_provider(auth: &AccessToken, domain: &str) -> http::Result<Option<String>> {
+    let parent = domain
+        .split_once('.')
+        .map(|(_, parent)| format!("*.{parent}"));
+
+    Ok(HttpRequest::get("/api/settings/group")
+        .with_authorization(auth)
+        .with_parameter("prefix", "acme")
+        .with_parameter("suffix", "directory")
+        .send::<List<Settings>>()
+        .await?
+        .items
+        .into_iter()
+        .find(|provider| {
+            provider.array_values("domains").iter().any(|(_, name)| {
+                name.eq_ignore_ascii_case(domain)
+                    || parent
+                        .as_deref()
+                        .is_some_and(|parent| name.eq_ignore_ascii_case(parent))
+            })
+        })
+        .and_then(|mut provider| provider.remove("_id")))
+}
+
+async fn check_certificate(
+    auth: &AccessToken,
+    provider: &str,
+    domain: &str,
+) -> http::Result<CheckResult> {
+    let status = HttpRequest::get("/api/certificate/status")
+        .with_authorization(auth)
+        .with_parameter("source", "acme")
+        .with_parameter("id", provider)
+        .try_send::<CertificateStatus>()
+        .await?;
+    let link = Some(format!("/settings/acme/{provider}/edit"));
+
+    let check = match status.and_then(|status| Some((status.not_after?, status.subjects))) {
+        Some((not_after, subjects)) => {
+            let remaining = (not_after - Utc::now()).num_days();
+            let check = if not_after < Utc::now() {
+                CheckResult::new(
+                    CheckStatus::Fail,
+                    format!(
+                        "The certificate expired on {}.",
+                        not_after.format_date_time()
+                    ),
+                )
+            } else if remaining < RENEWAL_WARNING_DAYS {
+                CheckResult::new(
+                    CheckStatus::Warning,
+                    format!(
+                        "The certificate expires in {}, it should have been renewed by now.",
+                        maybe_plural(remaining as usize, "day", "days")
+                    ),
+                )
+            } else {
+                CheckResult::new(
+                    CheckStatus::Pass,
+                    format!(
+                        "The certificate is valid until {}.",
+                        not_after.format_date_time()
+                    ),
+                )
+            };
+            if subjects
+                .iter()
+                .any(|subject| subject.eq_ignore_ascii_case(domain))
+            {
+                check.with_details(subjects)
+            } else {
+                CheckResult {
+                    status: CheckStatus::Fail,
+                    summary: format!("The certificate does not cover {domain}."),
+                    ..check
+                }
+                .with_details(subjects)
+            }
+        }
+        None => CheckResult::new(
+            CheckStatus::Warning,
+            "No certificate has been issued by this provider yet.",
+        ),
+    };
+
+    Ok(CheckResult { link, ..check })
+}
+
+async fn check_domain_dns(auth: &AccessToken, domain: &str) -> http::Result<CheckResult> {
+    let mut records = dns_lookup(auth, domain, "A").await?;
+    records.extend(dns_lookup(auth, domain, "AAAA").await?);
+    let caa = dns_lookup(auth, domain, "CAA").await?;
+
+    let check = if records.is_empty() {
+        CheckResult::new(
+            CheckStatus::Fail,
+            format!(
+                "{domain} does not resolve, the certificate authority cannot reach this server to validate it."
+            ),
+        )
+    } else if !caa.is_empty() {
+        CheckResult::new(
+            CheckStatus::Warning,
+            format!(
+                "{domain} has CAA records, make sure they allow the certificate authority used by the ACME provider."
+            ),
+        )
+    } else {
+        CheckResult::new(
+            CheckStatus::Pass,
+            format!("{domain} resolves and has no CAA restrictions."),
+        )
+    };
+
+    records.extend(caa.into_iter().map(|record| format!("CAA {record}")));
+    Ok(check.with_details(records))
+}
+
+async fn check_logs(auth: &AccessToken, filter: &str) -> http::Result<CheckResult> {
+    let entries = HttpRequest::get("/api/logs")
+        .with_authorization(auth)
+        .with_parameter("page", "1")
+        .with_parameter("limit", LOG_LIMIT.to_string())
+        .with_parameter("filter", filter)
+        .send::<List<LogEntry>>()
+        .await?
+        .items
+        .into_iter()
+        .filter(|entry| matches!(entry.level.as_str(), "ERROR" | "WARN"))
+        .collect::<Vec<_>>();
+
+    let link = Some(format!("/manage/logs?filter={filter}"));
+    let check = if entries.is_empty() {
+        CheckResult::new(
+            CheckStatus::Pass,
+            format!("No recent warnings or errors mention {filter}."),
+        )
+    } else {
+        CheckResult::new(
+            CheckStatus::Warning,
+            format!(
+                "{} recently mention {filter}.",
+                maybe_plural(entries.len(), "warning or error", "warnings or errors")
+            ),
+        )
+        .with_details(
+            entries
+                .into_iter()
+                .take(MAX_DETAILS)
+                .map(|entry| {
+                    format!(
+                        "{} {} {}: {}",
+                        entry.timestamp.format_date_time(),
+                        entry.level,
+                        entry.event,
+                        entry.details
+                    )
+                })
+                .collect(),
+        )
+    };
+
+    Ok(CheckResult { link, ..check })
+}
+
+// Adds the outcome of a check to the report. A check that could not be run is
+// reported as failed so that the remaining checks still run, only an expired
+// session stops the runbook.
+fn record(
+    report: RwSignal<Option<RunbookReport>>,
+    title: &'static str,
+    result: http::Result<CheckResult>,
+) -> http::Result<()> {
+    let check = match result {
+        Ok(check) => CheckResult { title, ..check },
+        Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+        Err(err) => CheckResult {
+            title,
+            ..CheckResult::new(
+                CheckStatus::Fail,
+                format!("The check could not be run: {}", Alert::from(err).message),
+            )
+        },
+    };
+    report.update(|report| {
+        if let Some(report) = report {
+            report.checks.push(check);
+        }
+    });
+    Ok(())
+}
+
+impl Runbook {
+    fn id(&self) -> &'static str {
+        match self {
+            Runbook::DeferredMail => "deferred",
+            Runbook::Login => "login",
+            Runbook::Certificate => "certificate",
+        }
+    }
+
+    fn parse(id: &str) -> Option<Self> {
+        RUNBOOKS.iter().find(|runbook| runbook.id() == id).copied()
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Runbook::DeferredMail => "Mail is deferred",
+            Runbook::Login => "User cannot log in",
+            Runbook::Certificate => "Certificate does not renew",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Runbook::DeferredMail => {
+                "Check the queue, the recipient's MX records and recent delivery errors"
+            }
+            Runbook::Login => {
+                "Check the account, banned addresses and recent authentication errors"
+            }
+            Runbook::Certificate => {
+                "Check the ACME provider, the current certificate and the domain's DNS records"
+            }
+        }
+    }
+
+    fn target_label(&self) -> &'static str {
+        match self {
+            Runbook::DeferredMail => "Recipient",
+            Runbook::Login => "Account",
+            Runbook::Certificate => "Domain",
+        }
+    }
+
+    fn placeholder(&self) -> &'static str {
+        match self {
+            Runbook::DeferredMail => "user@example.org",
+            Runbook::Login => "john",
+            Runbook::Certificate => "mail.example.org",
+        }
+    }
+}
+
+impl CheckStatus {
+    fn badge(&self) -> (Color, &'static str) {
+        match self {
+            CheckStatus::Pass => (Color::Green, "Pass"),
+            CheckStatus::Warning => (Color::Yellow, "Warning"),
+            CheckStatus::Fail => (Color::Red, "Fail"),
+            CheckStatus::Info => (Color::Blue, "Info"),
+        }
+    }
+}
+
+impl CheckResult {
+    fn new(status: CheckStatus, summary: impl Into<String>) -> Self {
+        CheckResult {
+            title: "",
+            status,
+            summary: summary.into(),
+            details: vec![],
+            link: None,
+        }
+    }
+
+    fn with_details(mut self, details: Vec<String>) -> Self {
+        self.details = details;
+        self
+    }
+
+    fn with_link(mut self, link: String) -> Self {
+        self.link = Some(link);
+        self
+    }
+}
+
+impl RunbookReport {
+    fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\nChecked: {}\nStarted: {}\n",
+            self.runbook.title(),
+            self.target,
+            self.started_at.to_rfc3339()
+        );
+        for check in &self.checks {
+            text.push_str(&format!(
+                "\n[{}] {}: {}\n",
+                check.status.badge().1.to_uppercase(),
+                check.title,
+                check.summary
+            ));
+            for detail in &check.details {
+                text.push_str(&format!("    {detail}\n"));
+            }
+        }
+        text
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_runbooks(self) -> Self {
+        self.new_schema("troubleshoot-runbook")
+            .new_field("target")
+            .input_check(
+                [Transformer::Lowercase, Transformer::Trim],
+                [Validator::Required],
+            )
+            .typ(Type::Input)
+            .build()
+            .build()
+    }
+}
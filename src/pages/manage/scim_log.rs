@@ -0,0 +1,386 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconAdjustmentsHorizontal, IconRefresh},
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::ToolbarButton, Footer, ListItem,
+            ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+        Permission,
+    },
+    pages::List,
+};
+
+const PAGE_SIZE: u32 = 25;
+const SETTINGS_URL: &str = "/settings/scim/edit";
+
+static OPERATIONS: &[(&str, &str)] = &[
+    ("create", "Create"),
+    ("update", "Update"),
+    ("deactivate", "Deactivate"),
+    ("delete", "Delete"),
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScimOperation {
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    pub operation: String,
+    #[serde(default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub resource_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub request: Option<serde_json::Value>,
+    #[serde(default)]
+    pub response: Option<serde_json::Value>,
+}
+
+#[component]
+pub fn ScimActivityList() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let operation = create_memo(move |_| {
+        query
+            .with(|q| q.get("operation").cloned())
+            .filter(|operation| OPERATIONS.iter().any(|(id, _)| id == operation))
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let inspected = create_rw_signal(None::<String>);
+
+    let operations = create_resource(
+        move || (page.get(), operation.get()),
+        move |(page, operation)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/scim/activity")
+                    .with_authorization(&auth)
+                    .with_parameter("page", page.to_string())
+                    .with_parameter("limit", PAGE_SIZE.to_string())
+                    .with_optional_parameter("operation", operation)
+                    .send::<List<ScimOperation>>()
+                    .await
+                    .map(Arc::new)
+            }
+        },
+    );
+    let total_results = create_rw_signal(None::<u32>);
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="SCIM activity"
+                subtitle="Recent provisioning operations received from identity providers"
+            >
+                <Toolbar slot>
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            let operation = event_target_value(&ev);
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/scim-activity")
+                                    .with_optional_parameter(
+                                        "operation",
+                                        (!operation.is_empty()).then_some(operation),
+                                    )
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    >
+
+                        <option value="" selected=move || operation.get().is_none()>
+                            All operations
+                        </option>
+                        {OPERATIONS
+                            .iter()
+                            .map(|(id, name)| {
+                                view! {
+                                    <option
+                                        value=*id
+                                        selected=move || operation.get().is_some_and(|op| op == *id)
+                                    >
+                                        {*name}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            operations.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Settings"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            use_navigate()(SETTINGS_URL, Default::default());
+                        })
+                    >
+
+                        <IconAdjustmentsHorizontal/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match operations.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="SCIM is not available"
+                                        subtitle="This server does not record SCIM provisioning activity."
+                                        button_text="Settings"
+                                        button_action=Callback::new(move |_| {
+                                            use_navigate()(SETTINGS_URL, Default::default());
+                                        })
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(operations)) if !operations.items.is_empty() => {
+                            total_results.set(Some(operations.total as u32));
+                            let operations_ = operations.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Time".to_string(),
+                                        "Operation".to_string(),
+                                        "Resource".to_string(),
+                                        "Status".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || operations_.items.clone()
+                                            key=|operation| operation.id.clone()
+                                            let:operation
+                                        >
+                                            <ScimOperationItem operation inspected/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No activity"
+                                        subtitle="No SCIM operations have been received yet."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/scim-activity")
+                                    .with_optional_parameter("operation", operation.get())
+                                    .with_parameter("page", page.to_string())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn ScimOperationItem(
+    operation: ScimOperation,
+    inspected: RwSignal<Option<String>>,
+) -> impl IntoView {
+    let id = operation.id.clone();
+    let is_inspected = create_memo(move |_| inspected.get().as_deref() == Some(id.as_str()));
+    let label = OPERATIONS
+        .iter()
+        .find_map(|(id, name)| (*id == operation.operation).then_some(*name))
+        .unwrap_or(operation.operation.as_str())
+        .to_string();
+    let color = match operation.operation.as_str() {
+        "create" => Color::Green,
+        "update" => Color::Blue,
+        _ => Color::Yellow,
+    };
+    let resource = match (&operation.name, &operation.resource_id) {
+        (Some(name), _) => format!("{} {name}", operation.resource_type),
+        (None, Some(id)) => format!("{} {id}", operation.resource_type),
+        (None, None) => operation.resource_type.clone(),
+    };
+    let is_error = operation.status >= 400 || operation.error.is_some();
+    let status = match (&operation.error, operation.status) {
+        (Some(error), 0) => error.clone(),
+        (Some(error), status) => format!("{status} {error}"),
+        (None, 0) => "Unknown".to_string(),
+        (None, status) => status.to_string(),
+    };
+    // Payloads carry user PII, only admins allowed to view logs may inspect them
+    let can_inspect = use_authorization()
+        .get_untracked()
+        .permissions()
+        .has_access(Permission::LogsView);
+    let payloads = [
+        ("Request", operation.request.filter(|_| can_inspect)),
+        ("Response", operation.response.filter(|_| can_inspect)),
+    ]
+    .into_iter()
+    .filter_map(|(title, payload)| {
+        payload.map(|payload| {
+            (
+                title,
+                serde_json::to_string_pretty(&payload).unwrap_or_default(),
+            )
+        })
+    })
+    .collect::<Vec<_>>();
+    let has_payload = !payloads.is_empty();
+    let id = operation.id;
+
+    view! {
+        <tr>
+            <ListItem>
+                <Timestamp
+                    date=operation.timestamp
+                    relative=true
+                    attr:class="text-sm text-gray-500"
+                />
+            </ListItem>
+            <ListItem>
+                <Badge color=color>{label}</Badge>
+            </ListItem>
+            <ListTextItem>{resource}</ListTextItem>
+            <ListItem>
+                <span class=if is_error {
+                    "text-sm text-red-600 dark:text-red-500"
+                } else {
+                    "text-sm text-gray-500"
+                }>{status}</span>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5 text-end">
+                <Show when=move || has_payload>
+                    <button
+                        type="button"
+                        class="text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                        on:click={
+                            let id = id.clone();
+                            move |_| {
+                                let id = id.clone();
+                                inspected
+                                    .update(|inspected| {
+                                        *inspected = if inspected.as_ref() == Some(&id) {
+                                            None
+                                        } else {
+                                            Some(id)
+                                        };
+                                    });
+                            }
+                        }
+                    >
+
+                        {move || if is_inspected.get() { "Hide" } else { "Inspect" }}
+                    </button>
+                </Show>
+            </ListItem>
+        </tr>
+        <Show when=move || is_inspected.get()>
+            <tr>
+                <td colspan="5" class="px-6 py-3 bg-gray-50 dark:bg-slate-800">
+                    <div class="grid gap-4 lg:grid-cols-2">
+                        {payloads
+                            .clone()
+                            .into_iter()
+                            .map(|(title, payload)| {
+                                view! {
+                                    <div>
+                                        <p class="text-xs font-semibold uppercase text-gray-600 dark:text-gray-400 mb-1">
+                                            {title}
+                                        </p>
+                                        <pre class="text-xs font-mono whitespace-pre-wrap break-all text-gray-800 dark:text-gray-200">
+                                            {payload}
+                                        </pre>
+                                    </div>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                </td>
+            </tr>
+        </Show>
+    }
+}
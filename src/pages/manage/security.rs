@@ -0,0 +1,400 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Local, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        chart::{TimeSeriesChart, TimeSeriesPoint},
+        icon::{IconRefresh, IconShieldExclamation},
+        list::{
+            header::ColumnList, pagination::Pagination, row::SelectItem, toolbar::ToolbarButton,
+            Footer, ItemSelection, ListItem, ListSection, ListTable, ListTextItem, Toolbar,
+            ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        poll::use_polling,
+        url::UrlBuilder,
+    },
+    pages::{format_countdown, maybe_plural, FormatDateTime, List},
+};
+
+const PAGE_SIZE: u32 = 20;
+const HISTORY_HOURS: u32 = 24;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct BannedAddress {
+    pub ip: String,
+    pub reason: BanReason,
+    #[serde(rename = "bannedAt")]
+    pub banned_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum BanReason {
+    AuthFailure,
+    Loitering,
+    Scanner,
+    Manual,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct BanHistory {
+    pub timestamp: DateTime<Utc>,
+    pub count: u64,
+}
+
+#[component]
+pub fn BannedAddresses() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+
+    let poller = use_polling(Duration::from_secs(30));
+    let clock = use_polling(Duration::from_secs(1));
+    let now = create_memo(move |_| {
+        clock.tick();
+        Utc::now()
+    });
+
+    let bans = create_resource(
+        move || (page.get(), poller.tick()),
+        move |(page, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/security/bans")
+                    .with_authorization(&auth)
+                    .with_parameter("page", page.to_string())
+                    .with_parameter("limit", PAGE_SIZE.to_string())
+                    .send::<List<BannedAddress>>()
+                    .await
+                    .map(Arc::new)
+            }
+        },
+    );
+    let history = create_resource(
+        move || poller.tick(),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/security/bans/history")
+                    .with_authorization(&auth)
+                    .with_parameter("hours", HISTORY_HOURS.to_string())
+                    .send::<Vec<BanHistory>>()
+                    .await
+            }
+        },
+    );
+    let bans_per_hour = Signal::derive(move || {
+        let current_hour = Utc::now().timestamp() - Utc::now().timestamp().rem_euclid(3600);
+        let mut buckets = (0..HISTORY_HOURS as i64)
+            .rev()
+            .map(|hour| TimeSeriesPoint {
+                x: DateTime::from_timestamp(current_hour - hour * 3600, 0)
+                    .unwrap_or_default()
+                    .with_timezone(&Local),
+                y: vec![0.0],
+            })
+            .collect::<Vec<_>>();
+        if let Some(Ok(history)) = history.get() {
+            for entry in history {
+                let x = entry.timestamp.with_timezone(&Local);
+                if let Some(bucket) = buckets.iter_mut().rev().find(|bucket| bucket.x <= x) {
+                    bucket.y[0] += entry.count as f64;
+                }
+            }
+        }
+        buckets
+    });
+
+    let total_results = create_rw_signal(None::<u32>);
+    let unban_action = create_action(move |items: &Arc<Vec<String>>| {
+        let items = items.clone();
+        let auth = auth.get();
+
+        async move {
+            for item in items.iter() {
+                if let Err(err) = HttpRequest::delete(("/api/security/bans", item))
+                    .with_authorization(&auth)
+                    .send::<serde_json::Value>()
+                    .await
+                {
+                    match err {
+                        http::Error::Unauthorized => {
+                            use_navigate()("/login", Default::default());
+                        }
+                        err => {
+                            alert.set(Alert::from(err));
+                        }
+                    }
+                    return;
+                }
+            }
+            bans.refetch();
+            history.refetch();
+            alert.set(Alert::success(format!(
+                "Unbanned {}.",
+                maybe_plural(items.len(), "address", "addresses")
+            )));
+        }
+    });
+    let on_unban = Callback::new(move |items: Vec<String>| {
+        let text = maybe_plural(items.len(), "address", "addresses");
+        modal.set(
+            Modal::with_title("Confirm unban")
+                .with_message(format!(
+                    "Are you sure you want to unban {text}? They will be able to connect again immediately."
+                ))
+                .with_button(format!("Unban {text}"))
+                .with_dangerous_callback(move || {
+                    selected.set(ItemSelection::None);
+                    unban_action.dispatch(Arc::new(items.clone()));
+                }),
+        )
+    });
+
+    view! {
+        <TimeSeriesChart title="Bans per hour" labels=&["bans"] data=bans_per_hour/>
+
+        <ListSection>
+            <ListTable
+                title="Banned addresses"
+                subtitle="IP addresses automatically banned for abusive behaviour"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            poller.refresh();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().total_selected(total_results.get());
+                            if ns > 0 { format!("Unban ({ns})") } else { "Unban".to_string() }
+                        })
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            let to_unban = match selected.get() {
+                                ItemSelection::Some(items) => items.into_iter().collect::<Vec<_>>(),
+                                ItemSelection::All => {
+                                    bans.get()
+                                        .and_then(|bans| bans.ok())
+                                        .map(|bans| {
+                                            bans.items.iter().map(|ban| ban.ip.clone()).collect()
+                                        })
+                                        .unwrap_or_default()
+                                }
+                                ItemSelection::None => Vec::new(),
+                            };
+                            if !to_unban.is_empty() {
+                                on_unban.call(to_unban);
+                            }
+                        })
+                    >
+
+                        <IconShieldExclamation/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match bans.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(bans)) if !bans.items.is_empty() => {
+                            total_results.set(Some(bans.total as u32));
+                            let bans_ = bans.clone();
+                            Some(
+                                view! {
+                                    <ColumnList
+                                        headers=vec![
+                                            "Address".to_string(),
+                                            "Reason".to_string(),
+                                            "Banned".to_string(),
+                                            "Expires".to_string(),
+                                            "".to_string(),
+                                        ]
+
+                                        has_select_all=true
+                                    >
+
+                                        <For
+                                            each=move || bans_.items.clone()
+                                            key=|ban| ban.ip.clone()
+                                            let:ban
+                                        >
+                                            <BannedAddressItem ban now on_unban/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No banned addresses"
+                                        subtitle="No IP addresses are currently banned."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/security/bans")
+                                    .with_parameter("page", page.to_string())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn BannedAddressItem(
+    ban: BannedAddress,
+    now: Memo<DateTime<Utc>>,
+    on_unban: Callback<Vec<String>>,
+) -> impl IntoView {
+    let ip = ban.ip.clone();
+    let item_id = ban.ip.clone();
+    let banned_at = ban.banned_at.format_date_time();
+    let expires_at = ban.expires_at;
+    let reason = ban.reason;
+    let countdown = move || match expires_at {
+        Some(expires_at) => {
+            let remaining = (expires_at - now.get()).num_seconds();
+            if remaining > 0 {
+                view! {
+                    <span title=expires_at.format_date_time()>
+                        {format_countdown(remaining as u64)}
+                    </span>
+                }
+                .into_view()
+            } else {
+                view! { <Badge color=Color::Gray>Expired</Badge> }.into_view()
+            }
+        }
+        None => view! { <Badge color=Color::Red>Permanent</Badge> }.into_view(),
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex">
+                    <SelectItem item_id/>
+
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListTextItem>{ban.ip}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <Badge color=reason.color()>{reason.label()}</Badge>
+            </ListItem>
+            <ListTextItem>{banned_at}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5 tabular-nums">{countdown}</ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    on:click=move |_| {
+                        on_unban.call(vec![ip.clone()]);
+                    }
+                >
+
+                    Unban
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
+
+impl BanReason {
+    fn label(&self) -> &'static str {
+        match self {
+            BanReason::AuthFailure => "Authentication abuse",
+            BanReason::Loitering => "Loitering",
+            BanReason::Scanner => "Scanner",
+            BanReason::Manual => "Manual",
+            BanReason::Other => "Other",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            BanReason::AuthFailure => Color::Red,
+            BanReason::Loitering => Color::Yellow,
+            BanReason::Scanner => Color::Blue,
+            BanReason::Manual | BanReason::Other => Color::Gray,
+        }
+    }
+}
@@ -0,0 +1,425 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconPauseCircle, IconPlayCircle, IconPower, IconRefresh},
+        list::{
+            header::ColumnList,
+            pagination::Pagination,
+            row::SelectItem,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ItemSelection, ListItem, ListSection, ListTable, ListTextItem, Toolbar,
+            ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        format::format_bytes,
+        http,
+        monitor::{
+            fetch_client_sessions, terminate_client_session, ClientSession, SessionProtocol,
+            SESSION_PROTOCOLS,
+        },
+        oauth::use_authorization,
+        poll::use_polling,
+        url::UrlBuilder,
+        Permission,
+    },
+    pages::{format_countdown, maybe_plural, FormatDateTime},
+};
+
+const PAGE_SIZE: u32 = 25;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[component]
+pub fn ActiveSessions() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| q.get("filter").filter(|filter| !filter.is_empty()).cloned())
+    });
+    let protocol = create_memo(move |_| {
+        query.with(|q| q.get("protocol").and_then(|id| SessionProtocol::parse(id)))
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let can_terminate = auth
+        .get_untracked()
+        .permissions()
+        .has_access(Permission::SessionTerminate);
+    let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
+    provide_context(selected);
+
+    let poller = use_polling(REFRESH_INTERVAL);
+    let clock = use_polling(Duration::from_secs(1));
+    let now = create_memo(move |_| {
+        clock.tick();
+        Utc::now()
+    });
+
+    let sessions = create_resource(
+        move || (page.get(), filter.get(), protocol.get(), poller.tick()),
+        move |(page, filter, protocol, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                fetch_client_sessions(&auth, filter, protocol, page, PAGE_SIZE)
+                    .await
+                    .map(Arc::new)
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<u32>);
+    let terminate_action = create_action(move |items: &Arc<Vec<String>>| {
+        let items = items.clone();
+        let auth = auth.get();
+
+        async move {
+            for item in items.iter() {
+                match terminate_client_session(&auth, item).await {
+                    // Sessions that closed in the meantime are already gone
+                    Ok(_) | Err(http::Error::NotFound) => {}
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+            poller.refresh();
+            alert.set(Alert::success(format!(
+                "Terminated {}.",
+                maybe_plural(items.len(), "session", "sessions")
+            )));
+        }
+    });
+    let on_terminate = Callback::new(move |items: Vec<String>| {
+        let text = maybe_plural(items.len(), "session", "sessions");
+        modal.set(
+            Modal::with_title("Confirm termination")
+                .with_message(format!(
+                    "Are you sure you want to terminate {text}? Clients will be disconnected immediately and may reconnect."
+                ))
+                .with_button(format!("Terminate {text}"))
+                .with_dangerous_callback(move || {
+                    selected.set(ItemSelection::None);
+                    terminate_action.dispatch(Arc::new(items.clone()));
+                }),
+        )
+    });
+
+    let navigate_with = move |filter: Option<String>, protocol: Option<SessionProtocol>| {
+        use_navigate()(
+            &UrlBuilder::new("/manage/sessions")
+                .with_optional_parameter("filter", filter)
+                .with_optional_parameter("protocol", protocol.map(|protocol| protocol.id()))
+                .finish(),
+            Default::default(),
+        );
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Active sessions"
+                subtitle="Connections currently open on the SMTP, IMAP, POP3, ManageSieve and HTTP listeners"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value: String| {
+                            navigate_with((!value.is_empty()).then_some(value), protocol.get());
+                        }
+                    />
+
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(filter.get(), SessionProtocol::parse(&value));
+                        }
+                    >
+
+                        <option value="" selected=move || protocol.get().is_none()>
+                            All protocols
+                        </option>
+                        {SESSION_PROTOCOLS
+                            .iter()
+                            .map(|item| {
+                                view! {
+                                    <option
+                                        value=item.id()
+                                        selected=move || protocol.get() == Some(*item)
+                                    >
+                                        {item.label()}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if poller.is_paused() { "Resume" } else { "Pause" }.to_string()
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            if poller.is_paused() {
+                                poller.resume();
+                                poller.refresh();
+                            } else {
+                                poller.pause();
+                            }
+                        })
+                    >
+
+                        {move || {
+                            if poller.is_paused() {
+                                view! { <IconPlayCircle/> }
+                            } else {
+                                view! { <IconPauseCircle/> }
+                            }
+                        }}
+
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            poller.refresh();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <Show when=move || can_terminate>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                let ns = selected.get().total_selected(total_results.get());
+                                if ns > 0 {
+                                    format!("Terminate ({ns})")
+                                } else {
+                                    "Terminate".to_string()
+                                }
+                            })
+
+                            color=Color::Red
+                            on_click=Callback::new(move |_| {
+                                let to_terminate = match selected.get() {
+                                    ItemSelection::Some(items) => {
+                                        items.into_iter().collect::<Vec<_>>()
+                                    }
+                                    ItemSelection::All => {
+                                        sessions
+                                            .get()
+                                            .and_then(|sessions| sessions.ok())
+                                            .map(|sessions| {
+                                                sessions
+                                                    .items
+                                                    .iter()
+                                                    .map(|session| session.id.clone())
+                                                    .collect()
+                                            })
+                                            .unwrap_or_default()
+                                    }
+                                    ItemSelection::None => Vec::new(),
+                                };
+                                if !to_terminate.is_empty() {
+                                    on_terminate.call(to_terminate);
+                                }
+                            })
+                        >
+
+                            <IconPower/>
+                        </ToolbarButton>
+                    </Show>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match sessions.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            poller.pause();
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(sessions)) if !sessions.items.is_empty() => {
+                            total_results.set(Some(sessions.total as u32));
+                            let sessions_ = sessions.clone();
+                            Some(
+                                view! {
+                                    <ColumnList
+                                        headers=vec![
+                                            "Remote address".to_string(),
+                                            "Protocol".to_string(),
+                                            "Account".to_string(),
+                                            "Duration".to_string(),
+                                            "Received".to_string(),
+                                            "Sent".to_string(),
+                                            "".to_string(),
+                                        ]
+
+                                        has_select_all=can_terminate
+                                    >
+
+                                        <For
+                                            each=move || sessions_.items.clone()
+                                            key=|session| session.id.clone()
+                                            let:session
+                                        >
+                                            <SessionItem session now can_terminate on_terminate/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No sessions"
+                                        subtitle="No connections matching the filter are currently open."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/sessions")
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("filter", filter.get())
+                                    .with_optional_parameter(
+                                        "protocol",
+                                        protocol.get().map(|protocol| protocol.id()),
+                                    )
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn SessionItem(
+    session: ClientSession,
+    now: Memo<DateTime<Utc>>,
+    can_terminate: bool,
+    on_terminate: Callback<Vec<String>>,
+) -> impl IntoView {
+    let id = session.id.clone();
+    let started_at = session.started_at;
+    let protocol = session.protocol;
+    let remote = if session.remote_port > 0 {
+        format!("{}:{}", session.remote_ip, session.remote_port)
+    } else {
+        session.remote_ip
+    };
+    let duration = move || {
+        let elapsed = (now.get() - started_at).num_seconds().max(0);
+        format_countdown(elapsed as u64)
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <label class="flex" class:hidden=!can_terminate>
+                    <SelectItem item_id=session.id/>
+
+                    <span class="sr-only">Checkbox</span>
+                </label>
+            </ListItem>
+            <ListItem subclass="px-6 py-3">
+                <span class="block text-sm font-mono text-gray-800 dark:text-gray-200">
+                    {remote}
+                </span>
+                <span class="block text-xs text-gray-500">{session.listener}</span>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex items-center gap-x-1">
+                    <Badge color=protocol.color()>{protocol.label()}</Badge>
+                    {session.is_tls.then(|| view! { <Badge color=Color::Gray>TLS</Badge> })}
+                </div>
+            </ListItem>
+            <ListTextItem>
+                {session.account.unwrap_or_else(|| "Not authenticated".to_string())}
+            </ListTextItem>
+            <ListItem subclass="px-6 py-1.5 tabular-nums">
+                <span
+                    class="text-sm text-gray-600 dark:text-gray-400"
+                    title=started_at.format_date_time()
+                >
+                    {duration}
+                </span>
+            </ListItem>
+            <ListTextItem>{format_bytes(session.bytes_received)}</ListTextItem>
+            <ListTextItem>{format_bytes(session.bytes_sent)}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    class="inline-flex items-center gap-x-1 text-sm text-red-600 decoration-2 hover:underline font-medium dark:text-red-500 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    class:hidden=!can_terminate
+                    on:click=move |_| {
+                        on_terminate.call(vec![id.clone()]);
+                    }
+                >
+
+                    Terminate
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
@@ -0,0 +1,395 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconCodeBracket,
+        list::{ListSection, ZeroResults},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        form::FormData,
+        format::format_bytes,
+        http,
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        sieve::{
+            deactivate_sieve_script, fetch_sieve_script, fetch_sieve_scripts, SieveScript,
+            SieveScriptContent,
+        },
+        url::UrlBuilder,
+    },
+    pages::FormatDateTime,
+};
+
+#[component]
+pub fn SieveScriptsOpen() -> impl IntoView {
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("sieve-inspect")
+        .into_signal();
+
+    view! {
+        <Form
+            title="Sieve scripts"
+            subtitle="Review the filtering scripts of an account and deactivate a faulty one"
+        >
+            <FormSection>
+                <FormItem
+                    label="Account"
+                    tooltip="Viewing and deactivating scripts is recorded in the audit log"
+                >
+                    <InputText placeholder="john" element=FormElement::new("account", data)/>
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Open"
+                    color=Color::Blue
+                    on_click=move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                use_navigate()(
+                                    &UrlBuilder::new("/manage/sieve")
+                                        .with_subpath(
+                                            data.value::<String>("account").unwrap_or_default(),
+                                        )
+                                        .finish(),
+                                    Default::default(),
+                                );
+                            }
+                        });
+                    }
+                />
+
+            </FormButtonBar>
+        </Form>
+    }
+}
+
+#[component]
+pub fn SieveScripts() -> impl IntoView {
+    let params = use_params_map();
+    let query = use_query_map();
+    let name = create_memo(move |_| params.get().get("id").cloned().unwrap_or_default());
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let reason = expect_context::<Arc<Schemas>>()
+        .build_form("sieve-deactivate")
+        .into_signal();
+
+    let scripts = create_local_resource(
+        move || name.get(),
+        move |name| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_sieve_scripts(&auth, &name).await.map(Arc::new) }
+        },
+    );
+    let selected = create_memo(move |_| {
+        let selected = query.with(|q| q.get("script").cloned());
+        scripts.with(|result| {
+            let scripts = result.as_ref()?.as_ref().ok()?;
+            scripts
+                .iter()
+                .find(|script| Some(&script.name) == selected.as_ref())
+                .or_else(|| scripts.iter().find(|script| script.is_active))
+                .or_else(|| scripts.first())
+                .map(|script| script.name.clone())
+        })
+    });
+    let content = create_local_resource(
+        move || (name.get(), selected.get()),
+        move |(name, script)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match script {
+                    Some(script) => fetch_sieve_script(&auth, &name, &script)
+                        .await
+                        .map(|script| Some(Arc::new(script))),
+                    None => Ok(None),
+                }
+            }
+        },
+    );
+
+    let deactivate = create_action(move |script: &String| {
+        let auth = auth.get();
+        let script = script.clone();
+        let account = name.get_untracked();
+        let reason = reason
+            .get_untracked()
+            .value::<String>("reason")
+            .filter(|reason| !reason.is_empty());
+
+        async move {
+            match deactivate_sieve_script(&auth, &account, &script, reason).await {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "Script {script} deactivated for {account}."
+                    )));
+                    scripts.refetch();
+                    content.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = deactivate.pending();
+
+    let script_url = move |script: &str| {
+        UrlBuilder::new("/manage/sieve")
+            .with_subpath(name.get())
+            .with_parameter("script", script)
+            .finish()
+    };
+
+    view! {
+        <ListSection>
+            <div class="grid lg:grid-cols-4 gap-6">
+                <div class="lg:col-span-1">
+                    <div class="bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-slate-900 dark:border-gray-700">
+                        <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                                Scripts
+                            </h2>
+                            <p class="text-sm text-gray-600 dark:text-gray-400">
+                                {move || name.get()}
+                            </p>
+                        </div>
+                        <Transition fallback=Skeleton>
+                            {move || match scripts.get() {
+                                None => None,
+                                Some(Err(http::Error::Unauthorized)) => {
+                                    use_navigate()("/login", Default::default());
+                                    Some(view! { <div></div> }.into_view())
+                                }
+                                Some(Err(http::Error::NotFound)) => {
+                                    alert
+                                        .set(
+                                            Alert::error(
+                                                format!("Account {} does not exist.", name.get()),
+                                            ),
+                                        );
+                                    use_navigate()("/manage/sieve", Default::default());
+                                    Some(view! { <div></div> }.into_view())
+                                }
+                                Some(Err(err)) => {
+                                    alert.set(Alert::from(err));
+                                    Some(view! { <Skeleton/> }.into_view())
+                                }
+                                Some(Ok(scripts)) if scripts.is_empty() => {
+                                    Some(
+                                        view! {
+                                            <p class="px-6 py-4 text-sm text-gray-500">
+                                                This account has no Sieve scripts.
+                                            </p>
+                                        }
+                                            .into_view(),
+                                    )
+                                }
+                                Some(Ok(scripts)) => {
+                                    let items = scripts
+                                        .iter()
+                                        .cloned()
+                                        .map(|script| {
+                                            let script_name = script.name.clone();
+                                            view! {
+                                                <SieveScriptItem
+                                                    href=script_url(&script.name)
+                                                    script
+                                                    is_selected=Signal::derive(move || {
+                                                        selected.get().as_ref() == Some(&script_name)
+                                                    })
+                                                />
+                                            }
+                                        })
+                                        .collect_view();
+                                    Some(view! { <nav class="p-2 space-y-0.5">{items}</nav> }.into_view())
+                                }
+                            }}
+
+                        </Transition>
+                    </div>
+                </div>
+
+                <div class="lg:col-span-3">
+                    <Transition fallback=Skeleton>
+                        {move || match content.get() {
+                            None => None,
+                            Some(Err(http::Error::Unauthorized)) => {
+                                use_navigate()("/login", Default::default());
+                                Some(view! { <div></div> }.into_view())
+                            }
+                            Some(Err(err)) => {
+                                alert.set(Alert::from(err));
+                                Some(view! { <Skeleton/> }.into_view())
+                            }
+                            Some(Ok(None)) => {
+                                Some(
+                                    view! {
+                                        <ZeroResults
+                                            title="No scripts"
+                                            subtitle="Incoming mail for this account is not filtered by Sieve."
+                                        />
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                            Some(Ok(Some(script))) => {
+                                Some(
+                                    view! {
+                                        <SieveScriptView
+                                            script
+                                            reason
+                                            pending
+                                            on_deactivate=move |script: String| {
+                                                let account = name.get_untracked();
+                                                modal
+                                                    .set(
+                                                        Modal::with_title("Deactivate script")
+                                                            .with_message(
+                                                                format!(
+                                                                    "Are you sure you want to deactivate {script} for {account}?",
+                                                                ),
+                                                            )
+                                                            .with_button("Deactivate")
+                                                            .with_dangerous_callback(move || {
+                                                                deactivate.dispatch(script.clone());
+                                                            }),
+                                                    );
+                                            }
+                                        />
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                        }}
+
+                    </Transition>
+                </div>
+            </div>
+        </ListSection>
+    }
+}
+
+#[component]
+fn SieveScriptView(
+    script: Arc<SieveScriptContent>,
+    reason: RwSignal<FormData>,
+    #[prop(into)] pending: Signal<bool>,
+    #[prop(into)] on_deactivate: Callback<String>,
+) -> impl IntoView {
+    let script_name = script.name.clone();
+
+    view! {
+        <div class="bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-slate-900 dark:border-gray-700">
+            <div class="px-6 py-4 flex justify-between items-center gap-x-3 border-b border-gray-200 dark:border-gray-700">
+                <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200 truncate">
+                    {script.name.clone()}
+                </h2>
+                {if script.is_active {
+                    view! { <Badge color=Color::Green>Active</Badge> }
+                } else {
+                    view! { <Badge color=Color::Gray>Inactive</Badge> }
+                }}
+
+            </div>
+            <pre class="px-6 py-4 max-h-[36rem] overflow-auto text-sm font-mono text-gray-800 dark:text-gray-200 whitespace-pre">
+                {script.content.clone()}
+            </pre>
+            <div
+                class="px-6 py-4 space-y-3 border-t border-gray-200 dark:border-gray-700"
+                class:hidden=!script.is_active
+            >
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    "Deactivating the script stops all filtering for this account, incoming mail "
+                    "is delivered to the inbox until a script is activated again."
+                </p>
+                <InputText
+                    placeholder="Reason recorded in the audit log"
+                    element=FormElement::new("reason", reason)
+                />
+                <div class="flex justify-end">
+                    <Button
+                        text="Deactivate"
+                        color=Color::Red
+                        disabled=pending
+                        on_click=move |_| on_deactivate.call(script_name.clone())
+                    />
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn SieveScriptItem(script: SieveScript, href: String, is_selected: Signal<bool>) -> impl IntoView {
+    let details = [
+        Some(format_bytes(script.size)),
+        script.updated_at.map(|updated_at| updated_at.format_date()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    view! {
+        <a
+            class="flex items-center gap-x-2 py-2 px-3 rounded-lg text-sm text-gray-700 hover:bg-gray-100 dark:text-neutral-400 dark:hover:bg-neutral-700"
+            class=("bg-gray-100", move || is_selected.get())
+            class=("dark:bg-neutral-700", move || is_selected.get())
+            href=href
+        >
+            <IconCodeBracket attr:class="flex-shrink-0 size-4"/>
+            <span class="grow min-w-0">
+                <span class="block truncate">{script.name}</span>
+                <span class="block text-xs text-gray-500">{details}</span>
+            </span>
+            {script.is_active.then(|| view! { <Badge color=Color::Green>Active</Badge> })}
+        </a>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_sieve_inspect(self) -> Self {
+        self.new_schema("sieve-inspect")
+            .new_field("account")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .build()
+            .new_schema("sieve-deactivate")
+            .new_field("reason")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .build()
+            .build()
+    }
+}
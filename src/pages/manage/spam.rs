@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        badge::Badge,
         form::{
             button::Button,
             input::{InputText, TextArea},
@@ -24,6 +25,7 @@ use crate::{
             Form, FormButtonBar, FormElement, FormItem, FormSection,
         },
         messages::alert::{use_alerts, Alert},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
         Color,
     },
     core::{
@@ -56,7 +58,7 @@ pub struct SpamClassifyRequest {
     pub env_rcpt_to: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SpamClassifyResponse {
     pub score: f64,
@@ -64,7 +66,7 @@ pub struct SpamClassifyResponse {
     pub disposition: SpamFilterDisposition<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "action")]
 pub enum SpamFilterDisposition<T> {
@@ -79,6 +81,7 @@ pub fn SpamTest() -> impl IntoView {
     let alert = use_alerts();
 
     let (pending, set_pending) = create_signal(false);
+    let results = create_rw_signal(None::<Arc<SpamClassifyResponse>>);
 
     let mut data = expect_context::<Arc<Schemas>>().build_form("spam-test");
     data.apply_defaults(false);
@@ -90,6 +93,7 @@ pub fn SpamTest() -> impl IntoView {
 
         async move {
             set_pending.set(true);
+            results.set(None);
             let result = HttpRequest::post("/api/spam-filter/classify")
                 .with_authorization(&auth)
                 .with_body(req.as_ref())
@@ -101,40 +105,7 @@ pub fn SpamTest() -> impl IntoView {
 
             match result {
                 Ok(response) => {
-                    let title = match response.disposition {
-                        SpamFilterDisposition::Allow { value } => {
-                            format!(
-                                "Message was classified as {} with a score of {:.2}",
-                                if value.contains(": Yes") {
-                                    "SPAM"
-                                } else {
-                                    "HAM"
-                                },
-                                response.score
-                            )
-                        }
-                        SpamFilterDisposition::Discard => format!(
-                            "Message was discarded with a score of {:.2}",
-                            response.score
-                        ),
-                        SpamFilterDisposition::Reject => {
-                            format!("Message was rejected with a score of {:.2}", response.score)
-                        }
-                    };
-
-                    alert.set(
-                        Alert::success(title)
-                            .with_details_list(response.tags.into_iter().map(|(name, score)| {
-                                match score {
-                                    SpamFilterDisposition::Allow { value } => {
-                                        format!("{name}: {value:.2}")
-                                    }
-                                    SpamFilterDisposition::Discard => format!("{name}: DISCARD"),
-                                    SpamFilterDisposition::Reject => format!("{name}: REJECT"),
-                                }
-                            }))
-                            .without_timeout(),
-                    );
+                    results.set(Some(Arc::new(response)));
                 }
                 Err(Error::Unauthorized) => {
                     use_navigate()("/login", Default::default());
@@ -212,9 +183,153 @@ pub fn SpamTest() -> impl IntoView {
             </FormButtonBar>
 
         </Form>
+
+        {move || {
+            results.get().map(|response| view! { <SpamTestResults response/> })
+        }}
     }
 }
 
+#[component]
+fn SpamTestResults(response: Arc<SpamClassifyResponse>) -> impl IntoView {
+    let (title, color) = match &response.disposition {
+        SpamFilterDisposition::Allow { value } if value.contains(": Yes") => {
+            ("Classified as SPAM", Color::Red)
+        }
+        SpamFilterDisposition::Allow { .. } => ("Classified as HAM", Color::Green),
+        SpamFilterDisposition::Discard => ("Discarded", Color::Red),
+        SpamFilterDisposition::Reject => ("Rejected", Color::Red),
+    };
+
+    let score = format!("{:.2}", response.score);
+    let num_tags = response.tags.len().to_string();
+
+    let mut modules: BTreeMap<usize, Vec<(String, SpamFilterDisposition<f64>)>> = BTreeMap::new();
+    for (tag, disposition) in response.tags.iter() {
+        modules
+            .entry(spam_tag_module(tag))
+            .or_default()
+            .push((tag.clone(), disposition.clone()));
+    }
+
+    let sections = modules
+        .into_iter()
+        .map(|(module, tags)| {
+            let subtotal: f64 = tags
+                .iter()
+                .filter_map(|(_, disposition)| match disposition {
+                    SpamFilterDisposition::Allow { value } => Some(*value),
+                    _ => None,
+                })
+                .sum();
+            let items = tags
+                .into_iter()
+                .map(|(tag, disposition)| {
+                    let (text, color) = match disposition {
+                        SpamFilterDisposition::Allow { value } if value > 0.0 => {
+                            (format!("{value:.2}"), Color::Red)
+                        }
+                        SpamFilterDisposition::Allow { value } if value < 0.0 => {
+                            (format!("{value:.2}"), Color::Green)
+                        }
+                        SpamFilterDisposition::Allow { value } => {
+                            (format!("{value:.2}"), Color::Gray)
+                        }
+                        SpamFilterDisposition::Discard => ("DISCARD".to_string(), Color::Red),
+                        SpamFilterDisposition::Reject => ("REJECT".to_string(), Color::Red),
+                    };
+                    view! {
+                        <ReportItem label=tag>
+                            <Badge color=color>{text}</Badge>
+                        </ReportItem>
+                    }
+                })
+                .collect_view();
+
+            view! {
+                <ReportSection title=format!(
+                    "{} ({subtotal:.2})",
+                    SPAM_MODULES[module].1,
+                )>{items}</ReportSection>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <ReportView>
+            <ReportSection title="Result">
+                <ReportItem label="Disposition">
+                    <Badge color=color large=true>
+                        {title}
+                    </Badge>
+                </ReportItem>
+                <ReportItem label="Score">
+                    <ReportTextValue value=score/>
+                </ReportItem>
+                <ReportItem label="Matched tags">
+                    <ReportTextValue value=num_tags/>
+                </ReportItem>
+            </ReportSection>
+            {sections}
+        </ReportView>
+    }
+}
+
+static SPAM_MODULES: &[(&[&str], &str)] = &[
+    (
+        &[
+            "RBL_",
+            "RCVD_IN_",
+            "DNSWL_",
+            "URIBL_",
+            "SURBL_",
+            "DBL_",
+            "SEM_",
+            "MSBL_",
+            "ABUSE_",
+            "SPAMHAUS_",
+        ],
+        "DNS blocklists",
+    ),
+    (&["BAYES_"], "Bayes classifier"),
+    (&["PYZOR"], "Pyzor"),
+    (&["LLM_"], "LLM classifier"),
+    (
+        &[
+            "SPF_", "DKIM_", "DMARC_", "ARC_", "R_SPF_", "R_DKIM_", "AUTH_",
+        ],
+        "Authentication",
+    ),
+    (&["REPUTATION_", "ASN_", "IP_REPUTATION"], "Reputation"),
+    (&["URI_", "URL_", "REDIRECTOR_", "HAS_LINK"], "URLs"),
+    (
+        &[
+            "FROM_", "TO_", "RCPT_", "REPLYTO_", "ENVFROM_", "SUBJ_", "MID_", "DATE_", "HEADER_",
+        ],
+        "Headers",
+    ),
+    (
+        &[
+            "HTML_",
+            "MIME_",
+            "CTE_",
+            "BODY_",
+            "HAS_",
+            "MULTIPART_",
+            "CT_",
+        ],
+        "Content",
+    ),
+    (&[], "Other"),
+];
+
+fn spam_tag_module(tag: &str) -> usize {
+    SPAM_MODULES
+        .iter()
+        .position(|(prefixes, _)| prefixes.iter().any(|prefix| tag.starts_with(prefix)))
+        .unwrap_or(SPAM_MODULES.len() - 1)
+}
+
 #[component]
 pub fn SpamTrain() -> impl IntoView {
     let auth = use_authorization();
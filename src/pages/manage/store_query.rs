@@ -0,0 +1,358 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::IconPlayCircle,
+        messages::alert::{use_alerts, Alert, Alerts},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        Permission,
+    },
+    pages::{config::Settings, maybe_plural, List},
+};
+
+static SQL_STORES: &[&str] = &["postgresql", "mysql", "sqlite"];
+// Lookup queries of SQL directories and the parameter each one expects
+static QUERIES: &[(&str, &str, &str)] = &[
+    ("name", "Account by Name", "Login name"),
+    ("members", "Members by Name", "Account name"),
+    ("recipients", "Name by E-mail", "E-mail address"),
+    ("emails", "E-mails by Name", "Account name"),
+    ("secrets", "Passwords by Name", "Account name"),
+];
+// Returns password hashes, only offered to accounts that can read secrets
+const SECRETS_QUERY: &str = "secrets";
+
+#[derive(Clone, Debug, Serialize)]
+struct QueryRequest {
+    query: String,
+    parameter: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct QueryResult {
+    #[serde(default)]
+    columns: Vec<String>,
+    #[serde(default)]
+    rows: Vec<Vec<serde_json::Value>>,
+}
+
+// Runs the lookup queries configured for an SQL store with a sample parameter.
+// Only the configured queries can be run, the console does not accept SQL.
+// Secret values in the results are masked by the server.
+#[component]
+pub fn StoreQueryConsole() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("store-query")
+        .into_signal();
+    let store_id = create_rw_signal(String::new());
+    let query_id = create_rw_signal(QUERIES[0].0);
+    let result = create_rw_signal(None::<QueryResult>);
+    let show_secrets = auth
+        .get_untracked()
+        .permissions()
+        .has_access(Permission::SettingsSecretsRead);
+
+    let stores = create_local_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "store")
+                    .with_parameter("suffix", "type")
+                    .send::<List<Settings>>()
+                    .await
+                    .map(|list| {
+                        list.items
+                            .into_iter()
+                            .filter(|store| {
+                                store
+                                    .get("type")
+                                    .is_some_and(|typ| SQL_STORES.contains(&typ.as_str()))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }
+        },
+    );
+    let query_text = create_memo(move |_| {
+        let key = format!("query.{}", query_id.get());
+        let id = store_id.get();
+        stores.with(|stores| {
+            stores
+                .as_ref()
+                .and_then(|stores| stores.as_ref().ok())
+                .and_then(|stores| {
+                    stores
+                        .iter()
+                        .find(|store| store.get("_id").is_some_and(|store| store == &id))
+                })
+                .and_then(|store| store.get(&key))
+                .filter(|query| !query.trim().is_empty())
+                .cloned()
+        })
+    });
+
+    let run_query = create_action(move |request: &QueryRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+        let store_id = store_id.get_untracked();
+
+        async move {
+            match HttpRequest::post(format!("/api/store/{store_id}/query"))
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<QueryResult>()
+                .await
+            {
+                Ok(rows) => {
+                    result.set(Some(rows));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    result.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let pending = run_query.pending();
+
+    view! {
+        <Form
+            title="Query Console"
+            subtitle="Run the lookup queries of an SQL store with a sample value and inspect the rows returned"
+        >
+            <Alerts/>
+            <FormSection>
+                <FormItem label="Store">
+                    {move || match stores.get() {
+                        Some(Ok(stores)) if !stores.is_empty() => {
+                            let ids = stores
+                                .into_iter()
+                                .filter_map(|mut store| store.remove("_id"))
+                                .collect::<Vec<_>>();
+                            if store_id.get_untracked().is_empty() {
+                                store_id.set(ids[0].clone());
+                            }
+                            view! {
+                                <select
+                                    class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                    on:change=move |ev| {
+                                        store_id.set(event_target_value(&ev));
+                                        result.set(None);
+                                    }
+                                >
+
+                                    {ids
+                                        .into_iter()
+                                        .map(|id| {
+                                            let selected = store_id.get_untracked() == id;
+                                            view! {
+                                                <option value=id.clone() selected=selected>
+                                                    {id}
+                                                </option>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(err)) => {
+                            view! {
+                                <p class="text-sm text-red-600 dark:text-red-500">
+                                    {Alert::from(err).message}
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        Some(Ok(_)) => {
+                            view! {
+                                <p class="text-sm text-gray-600 dark:text-gray-400">
+                                    "There are no PostgreSQL, MySQL or SQLite stores configured."
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        None => ().into_view(),
+                    }}
+
+                </FormItem>
+                <FormItem label="Query">
+                    <select
+                        class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            let id = event_target_value(&ev);
+                            if let Some((id, _, _)) = QUERIES
+                                .iter()
+                                .filter(|(query, _, _)| show_secrets || *query != SECRETS_QUERY)
+                                .find(|(query, _, _)| *query == id)
+                            {
+                                query_id.set(id);
+                                result.set(None);
+                            }
+                        }
+                    >
+
+                        {QUERIES
+                            .iter()
+                            .filter(|(id, _, _)| show_secrets || *id != SECRETS_QUERY)
+                            .map(|(id, label, _)| {
+                                view! {
+                                    <option value=*id selected=move || query_id.get() == *id>
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                    <pre class="mt-2 p-3 text-xs font-mono whitespace-pre-wrap break-all rounded-lg bg-gray-50 text-gray-800 dark:bg-white/10 dark:text-gray-200">
+                        {move || {
+                            query_text
+                                .get()
+                                .unwrap_or_else(|| {
+                                    "This query is not configured for the selected store.".to_string()
+                                })
+                        }}
+
+                    </pre>
+                </FormItem>
+                <FormItem label=Signal::derive(move || {
+                    QUERIES
+                        .iter()
+                        .find(|(id, _, _)| *id == query_id.get())
+                        .map(|(_, _, parameter)| parameter.to_string())
+                        .unwrap_or_default()
+                })>
+                    <InputText element=FormElement::new("parameter", data)/>
+                </FormItem>
+            </FormSection>
+            <FormButtonBar>
+                <Button
+                    text="Run query"
+                    color=Color::Blue
+                    disabled=Signal::derive(move || {
+                        pending.get() || store_id.with(|id| id.is_empty())
+                            || query_text.with(|query| query.is_none())
+                    })
+
+                    on_click=Callback::new(move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                run_query
+                                    .dispatch(QueryRequest {
+                                        query: query_id.get_untracked().to_string(),
+                                        parameter: data
+                                            .value::<String>("parameter")
+                                            .unwrap_or_default(),
+                                    });
+                            }
+                        });
+                    })
+                >
+
+                    <IconPlayCircle/>
+                </Button>
+            </FormButtonBar>
+            {move || {
+                result
+                    .get()
+                    .map(|result| {
+                        view! {
+                            <FormSection title=maybe_plural(result.rows.len(), "row", "rows")>
+                                <div class="overflow-x-auto">
+                                    <table class="min-w-full text-sm divide-y divide-gray-200 dark:divide-gray-700">
+                                        <thead>
+                                            <tr class="text-xs font-medium uppercase text-gray-500">
+                                                {result
+                                                    .columns
+                                                    .iter()
+                                                    .map(|column| {
+                                                        view! {
+                                                            <th class="py-2 pe-4 text-start">{column.clone()}</th>
+                                                        }
+                                                    })
+                                                    .collect_view()}
+                                            </tr>
+                                        </thead>
+                                        <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                            {result
+                                                .rows
+                                                .into_iter()
+                                                .map(|row| {
+                                                    view! {
+                                                        <tr class="align-top text-gray-800 dark:text-gray-200">
+                                                            {row
+                                                                .into_iter()
+                                                                .map(|value| {
+                                                                    let value = format_value(value);
+                                                                    view! {
+                                                                        <td class="py-2 pe-4 font-mono break-all">{value}</td>
+                                                                    }
+                                                                })
+                                                                .collect_view()}
+                                                        </tr>
+                                                    }
+                                                })
+                                                .collect_view()}
+                                        </tbody>
+                                    </table>
+                                </div>
+                            </FormSection>
+                        }
+                    })
+            }}
+
+        </Form>
+    }
+}
+
+fn format_value(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::String(value) => value,
+        value => value.to_string(),
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_store_query(self) -> Self {
+        self.new_schema("store-query")
+            .new_field("parameter")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .typ(Type::Input)
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,296 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+pub mod search;
+
+use ahash::AHashSet;
+
+use crate::{
+    components::Color,
+    core::{
+        http::{self, HttpRequest},
+        schema::{Builder, Schemas, SelectType, Source, Transformer, Type, Validator},
+        AccessToken,
+    },
+    pages::{
+        enterprise::tracing::event::{Event, Key},
+        List,
+    },
+};
+
+const RECEIVED_SPAN: &str = "smtp.connection-start";
+const DELIVERY_SPAN: &str = "delivery.attempt-start";
+const MAX_SPANS: u32 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraceField {
+    MessageId,
+    Sender,
+    Recipient,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TraceStage {
+    Received,
+    Queued,
+    Attempted,
+    Deferred,
+    Delivered,
+    Bounced,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub stage: TraceStage,
+    pub span_id: u64,
+    pub event: Event,
+}
+
+// All the events that could be correlated to a single queued message,
+// in chronological order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTrace {
+    pub queue_id: Option<u64>,
+    pub message_id: Option<String>,
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub events: Vec<TraceEvent>,
+}
+
+// Searches the received message and delivery attempt history for spans
+// matching the query, then follows the queue ids found in the received
+// spans into their delivery attempts.
+pub async fn trace_message(
+    auth: &AccessToken,
+    field: TraceField,
+    query: &str,
+) -> http::Result<Vec<MessageTrace>> {
+    let mut traces = Vec::new();
+    let mut seen = AHashSet::new();
+
+    for span_type in [RECEIVED_SPAN, DELIVERY_SPAN] {
+        for span in fetch_spans(auth, span_type, Some(query), None).await? {
+            if field.matches(&span, query) {
+                correlate_span(auth, &mut traces, &mut seen, span).await?;
+            }
+        }
+    }
+
+    let queue_ids = traces
+        .iter()
+        .filter_map(|trace: &MessageTrace| trace.queue_id)
+        .collect::<Vec<_>>();
+    for queue_id in queue_ids {
+        for span in fetch_spans(auth, DELIVERY_SPAN, None, Some(queue_id)).await? {
+            correlate_span(auth, &mut traces, &mut seen, span).await?;
+        }
+    }
+
+    for trace in &mut traces {
+        trace.events.sort_by_key(|event| event.event.created_at);
+    }
+    traces.sort_by_key(|trace| {
+        std::cmp::Reverse(trace.events.first().map(|event| event.event.created_at))
+    });
+
+    Ok(traces)
+}
+
+async fn fetch_spans(
+    auth: &AccessToken,
+    span_type: &str,
+    filter: Option<&str>,
+    queue_id: Option<u64>,
+) -> http::Result<Vec<Event>> {
+    HttpRequest::get("/api/telemetry/traces")
+        .with_authorization(auth)
+        .with_parameter("type", span_type)
+        .with_parameter("page", "1")
+        .with_parameter("limit", MAX_SPANS.to_string())
+        .with_parameter("values", "1")
+        .with_optional_parameter("filter", filter)
+        .with_optional_parameter("queue_id", queue_id.map(|id| id.to_string()))
+        .send::<List<Event>>()
+        .await
+        .map(|list| list.items)
+}
+
+async fn correlate_span(
+    auth: &AccessToken,
+    traces: &mut Vec<MessageTrace>,
+    seen: &mut AHashSet<u64>,
+    span: Event,
+) -> http::Result<()> {
+    let Some(span_id) = span.get_as_int(Key::SpanId) else {
+        return Ok(());
+    };
+    if !seen.insert(span_id) {
+        return Ok(());
+    }
+
+    let events = match HttpRequest::get(("/api/telemetry/trace", span_id.to_string()))
+        .with_authorization(auth)
+        .send::<Vec<Event>>()
+        .await
+    {
+        Ok(events) => events,
+        Err(http::Error::NotFound) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    // A connection can carry several messages, events without a queue id
+    // are attached to every message queued during the span.
+    let mut span_queue_ids = Vec::new();
+    for queue_id in std::iter::once(&span)
+        .chain(events.iter())
+        .filter_map(|event| event.get_as_int(Key::QueueId))
+    {
+        if !span_queue_ids.contains(&Some(queue_id)) {
+            span_queue_ids.push(Some(queue_id));
+        }
+    }
+    if span_queue_ids.is_empty() {
+        span_queue_ids.push(None);
+    }
+
+    for event in events {
+        let Some(stage) = TraceStage::from_event_type(&event.typ) else {
+            continue;
+        };
+        let queue_ids = match event.get_as_int(Key::QueueId) {
+            Some(queue_id) => vec![Some(queue_id)],
+            None => span_queue_ids.clone(),
+        };
+
+        for queue_id in queue_ids {
+            let trace = match traces.iter().position(|trace| trace.queue_id == queue_id) {
+                Some(pos) => &mut traces[pos],
+                None => {
+                    traces.push(MessageTrace {
+                        queue_id,
+                        message_id: None,
+                        from: None,
+                        to: Vec::new(),
+                        events: Vec::new(),
+                    });
+                    traces.last_mut().unwrap()
+                }
+            };
+            trace.merge_details(&span);
+            trace.merge_details(&event);
+            trace.events.push(TraceEvent {
+                stage,
+                span_id,
+                event: event.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+impl MessageTrace {
+    fn merge_details(&mut self, event: &Event) {
+        if self.message_id.is_none() {
+            self.message_id = event.get_as_str(Key::MessageId).map(|id| id.to_string());
+        }
+        if self.from.is_none() {
+            self.from = event.get_as_str(Key::From).map(|from| from.to_string());
+        }
+        for rcpt in event.get_as_str_list(Key::To) {
+            if !self.to.iter().any(|to| to == rcpt) {
+                self.to.push(rcpt.to_string());
+            }
+        }
+    }
+
+    pub fn status(&self) -> Option<TraceStage> {
+        self.events.last().map(|event| event.stage)
+    }
+}
+
+impl TraceField {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "sender" => TraceField::Sender,
+            "recipient" => TraceField::Recipient,
+            _ => TraceField::MessageId,
+        }
+    }
+
+    // The server filter is a full-text match, spans are narrowed down to
+    // the requested field when the span carries it.
+    fn matches(&self, span: &Event, query: &str) -> bool {
+        let key = match self {
+            TraceField::MessageId => Key::MessageId,
+            TraceField::Sender => Key::From,
+            TraceField::Recipient => Key::To,
+        };
+        let query = query.trim_matches(|c| c == '<' || c == '>').to_lowercase();
+        let mut values = span.get_as_str_list(key).peekable();
+
+        values.peek().is_none() || values.any(|value| value.to_lowercase().contains(&query))
+    }
+}
+
+impl TraceStage {
+    pub fn from_event_type(typ: &str) -> Option<Self> {
+        match typ {
+            RECEIVED_SPAN => Some(TraceStage::Received),
+            DELIVERY_SPAN => Some(TraceStage::Attempted),
+            "delivery.delivered" | "delivery.dsn-success" => Some(TraceStage::Delivered),
+            "delivery.failed" | "queue.rescheduled" => Some(TraceStage::Deferred),
+            _ if typ.starts_with("queue.queue-") => Some(TraceStage::Queued),
+            _ if typ.starts_with("message-ingest.") => Some(TraceStage::Delivered),
+            _ if typ.contains("temp-fail") => Some(TraceStage::Deferred),
+            _ if typ.contains("perm-fail") => Some(TraceStage::Bounced),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TraceStage::Received => "Received",
+            TraceStage::Queued => "Queued",
+            TraceStage::Attempted => "Delivery attempt",
+            TraceStage::Deferred => "Deferred",
+            TraceStage::Delivered => "Delivered",
+            TraceStage::Bounced => "Bounced",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            TraceStage::Received | TraceStage::Queued => Color::Blue,
+            TraceStage::Attempted => Color::Gray,
+            TraceStage::Deferred => Color::Yellow,
+            TraceStage::Delivered => Color::Green,
+            TraceStage::Bounced => Color::Red,
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_message_trace(self) -> Self {
+        self.new_schema("message-trace")
+            .new_field("field")
+            .default("message-id")
+            .typ(Type::Select {
+                typ: SelectType::Single,
+                source: Source::Static(&[
+                    ("message-id", "Message-ID"),
+                    ("sender", "Sender"),
+                    ("recipient", "Recipient"),
+                ]),
+            })
+            .build()
+            .new_field("query")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono_humanize::{Accuracy, HumanTime, Tense};
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button, input::InputText, select::Select, Form, FormButtonBar, FormElement,
+            FormItem, FormSection,
+        },
+        list::ZeroResults,
+        messages::alert::{use_alerts, Alert},
+        report::ReportView,
+        skeleton::Skeleton,
+        timeline::{Timeline, TimelineItem},
+        Color,
+    },
+    core::{http, oauth::use_authorization, schema::Schemas},
+    pages::{
+        enterprise::tracing::event::{Event, Key, Value},
+        maybe_plural, FormatDateTime,
+    },
+};
+
+use super::{trace_message, MessageTrace, TraceField};
+
+#[component]
+pub fn MessageTraceSearch() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("message-trace")
+        .into_signal();
+    let search = RwSignal::new(None::<(TraceField, String)>);
+
+    let traces = create_local_resource(
+        move || search.get(),
+        move |search| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match search {
+                    Some((field, query)) => trace_message(&auth, field, &query).await.map(Some),
+                    None => Ok(None),
+                }
+            }
+        },
+    );
+
+    view! {
+        <Show
+            when=move || search.get().is_some()
+            fallback=move || {
+                view! {
+                    <Form
+                        title="Message Trace"
+                        subtitle="Follow a message from reception to delivery across the tracing history"
+                    >
+
+                        <FormSection>
+                            <FormItem label="Search by">
+                                <Select element=FormElement::new("field", data)/>
+                            </FormItem>
+                            <FormItem
+                                label="Value"
+                                tooltip="Message-ID header, sender or recipient address of the message"
+                            >
+                                <InputText
+                                    placeholder="<id@example.org> or john@example.org"
+                                    element=FormElement::new("query", data)
+                                />
+                            </FormItem>
+
+                        </FormSection>
+
+                        <FormButtonBar>
+
+                            <Button
+                                text="Search"
+                                color=Color::Blue
+                                on_click=Callback::new(move |_| {
+                                    if data.try_update(|data| data.validate_form()).unwrap_or_default() {
+                                        let data = data.get_untracked();
+                                        search
+                                            .set(
+                                                Some((
+                                                    TraceField::parse(
+                                                        &data.value::<String>("field").unwrap_or_default(),
+                                                    ),
+                                                    data.value::<String>("query").unwrap_or_default(),
+                                                )),
+                                            );
+                                    }
+                                })
+                            />
+
+                        </FormButtonBar>
+
+                    </Form>
+                }
+            }
+        >
+
+            <Transition fallback=Skeleton>
+                {move || match traces.get() {
+                    None | Some(Ok(None)) => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        search.set(None);
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Ok(Some(traces))) if traces.is_empty() => {
+                        Some(
+                            view! {
+                                <ZeroResults
+                                    title="No messages found"
+                                    subtitle="No received messages or delivery attempts matched your search."
+                                    button_text="New search"
+                                    button_action=Callback::new(move |_| {
+                                        search.set(None);
+                                    })
+                                />
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(Some(traces))) => {
+                        Some(
+                            view! {
+                                <ReportView>
+
+                                    {traces
+                                        .into_iter()
+                                        .map(|trace| view! { <TraceView trace=trace/> })
+                                        .collect_view()}
+
+                                    <div class="flex justify-end">
+
+                                        <Button
+                                            text="Close"
+                                            color=Color::Blue
+                                            on_click=move |_| {
+                                                search.set(None);
+                                            }
+                                        />
+
+                                    </div>
+                                </ReportView>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </Show>
+    }
+}
+
+#[component]
+fn TraceView(trace: MessageTrace) -> impl IntoView {
+    let title = trace
+        .message_id
+        .clone()
+        .or_else(|| trace.queue_id.map(|id| format!("Queue id {id:x}")))
+        .unwrap_or_else(|| "Unknown message".to_string());
+    let subtitle = format!(
+        "{} to {} ({})",
+        trace.from.as_deref().unwrap_or("<>"),
+        trace.to.join(", "),
+        maybe_plural(trace.events.len(), "event", "events")
+    );
+    let status = trace.status();
+    let start = trace.events.first().map(|event| event.event.created_at);
+    let items = trace
+        .events
+        .into_iter()
+        .map(|event| {
+            let time = match start {
+                Some(start) if start != event.event.created_at => format!(
+                    "{} ({} later)",
+                    event.event.created_at.format_date_time(),
+                    HumanTime::from(event.event.created_at - start)
+                        .to_text_en(Accuracy::Rough, Tense::Present),
+                ),
+                _ => event.event.created_at.format_date_time(),
+            };
+            let title = match &event.event.text {
+                Some(text) => format!("{}: {text}", event.stage.label()),
+                None => event.stage.label().to_string(),
+            };
+
+            view! {
+                <TimelineItem
+                    color=event.stage.color()
+                    title=title
+                    time=time
+                    subtitle=event.event.details.clone()
+                    href=format!("/manage/tracing/span/{}", event.span_id)
+                    items=event_items(&event.event)
+                />
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+            <div class="pb-8 flex justify-between items-start gap-x-4">
+                <div>
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200 break-all">
+                        {title}
+                    </h2>
+                    <p class="mt-1 text-sm text-gray-600 dark:text-neutral-400 break-all">
+                        {subtitle}
+                    </p>
+                </div>
+                {status
+                    .map(|status| {
+                        view! {
+                            <Badge color=status.color() large=true>
+                                {status.label()}
+                            </Badge>
+                        }
+                    })}
+
+            </div>
+
+            <Timeline>{items}</Timeline>
+        </div>
+    }
+}
+
+fn event_items(event: &Event) -> Vec<String> {
+    [
+        (Key::RemoteIp, "Remote IP"),
+        (Key::Hostname, "Host"),
+        (Key::To, "Recipients"),
+        (Key::Code, "Code"),
+        (Key::Reason, "Reason"),
+        (Key::NextRetry, "Next retry"),
+    ]
+    .into_iter()
+    .filter_map(|(key, label)| {
+        let value = match event.get(key)? {
+            Value::String(value) => value.clone(),
+            Value::Int(value) => value.to_string(),
+            Value::Array(_) => event.get_as_str_list(key).collect::<Vec<_>>().join(", "),
+            _ => return None,
+        };
+        Some(format!("{label}: {value}"))
+    })
+    .collect()
+}
@@ -1299,5 +1299,11 @@ impl Builder<Schemas, ()> {
             .typ(Type::Input)
             .build()
             .build()
+            .new_schema("troubleshoot-dkim")
+            .new_field("message")
+            .input_check([], [Validator::Required])
+            .typ(Type::Input)
+            .build()
+            .build()
     }
 }
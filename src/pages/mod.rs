@@ -4,9 +4,11 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::core::format;
+
 pub mod account;
 pub mod authorize;
 pub mod config;
@@ -32,26 +34,48 @@ pub fn maybe_plural(items: usize, singular: &str, plural: &str) -> String {
     }
 }
 
+pub fn format_countdown(seconds: u64) -> String {
+    let (days, hours, minutes, seconds) = (
+        seconds / 86400,
+        (seconds % 86400) / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours:02}h {minutes:02}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else {
+        format!("{minutes}m {seconds:02}s")
+    }
+}
+
 pub trait FormatDateTime {
     fn format_date_time(&self) -> String;
     fn format_date(&self) -> String;
     fn format_time(&self) -> String;
+    fn format_relative(&self) -> String;
+    fn format_iso(&self) -> String;
 }
 
 impl FormatDateTime for DateTime<Utc> {
     fn format_date_time(&self) -> String {
-        self.with_timezone(&Local)
-            .format("%a, %d %b %Y %H:%M:%S")
-            .to_string()
+        format::format_date_time(self)
     }
 
     fn format_date(&self) -> String {
-        self.with_timezone(&Local)
-            .format("%a, %d %b %Y")
-            .to_string()
+        format::format_date(self)
     }
 
     fn format_time(&self) -> String {
-        self.with_timezone(&Local).format("%H:%M:%S").to_string()
+        format::format_time(self)
+    }
+
+    fn format_relative(&self) -> String {
+        format::format_relative(self)
+    }
+
+    fn format_iso(&self) -> String {
+        format::format_iso(self)
     }
 }
@@ -7,7 +7,7 @@
 use leptos::*;
 use leptos_router::*;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     components::{
@@ -28,22 +28,25 @@ use crate::{
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
+        command::{register_commands, Command},
+        events::{use_change_events, ChangeTopic},
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
     },
     pages::{
         maybe_plural,
-        queue::messages::{Message, Status},
+        queue::messages::{queues::fetch_virtual_queues, Message, Status},
     },
 };
 
-use chrono_humanize::HumanTime;
-
 const PAGE_SIZE: u32 = 10;
+// Used when the server does not push change events
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 #[derive(Clone, Serialize, Deserialize, Default)]
 pub struct List<T> {
@@ -74,15 +77,38 @@ pub fn QueueList() -> impl IntoView {
         })
     });
 
+    let queue =
+        create_memo(move |_| query.with(|q| q.get("queue").filter(|s| !s.is_empty()).cloned()));
+
     let auth = use_authorization();
     let alert = use_alerts();
     let modal = use_modals();
     let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
     provide_context(selected);
 
+    let virtual_queues = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                fetch_virtual_queues(&auth)
+                    .await
+                    .map(|queues| {
+                        queues
+                            .into_iter()
+                            .map(|queue| queue.name)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            }
+        },
+    );
+
+    let events = use_change_events(ChangeTopic::Queue, REFRESH_INTERVAL);
     let messages = create_resource(
-        move || (page.get(), filter.get()),
-        move |(page, filter)| {
+        move || (page.get(), filter.get(), queue.get(), events.tick()),
+        move |(page, filter, queue, _)| {
             let auth = auth.get_untracked();
 
             async move {
@@ -93,12 +119,19 @@ pub fn QueueList() -> impl IntoView {
                     .with_parameter("values", "1")
                     .with_parameter("max-total", "100")
                     .with_optional_parameter("text", filter)
+                    .with_optional_parameter("queue", queue)
                     .send::<List<Message>>()
                     .await
             }
         },
     );
 
+    register_commands(vec![Command::run(
+        "Refresh queue",
+        "Reload the list of queued messages",
+        move |_| messages.refetch(),
+    )]);
+
     let total_results = create_rw_signal(None::<u32>);
     let is_active = create_rw_signal(true);
 
@@ -106,6 +139,7 @@ pub fn QueueList() -> impl IntoView {
         let items = items.clone();
         let auth = auth.get();
         let filter = filter.get();
+        let queue = queue.get();
 
         async move {
             let mut total_deleted = 0;
@@ -115,6 +149,7 @@ pub fn QueueList() -> impl IntoView {
                     match HttpRequest::delete("/api/queue/messages")
                         .with_authorization(&auth)
                         .with_optional_parameter("text", filter)
+                        .with_optional_parameter("queue", queue)
                         .send::<serde_json::Value>()
                         .await
                     {
@@ -161,6 +196,7 @@ pub fn QueueList() -> impl IntoView {
         let items = items.clone();
         let auth = auth.get();
         let filter = filter.get();
+        let queue = queue.get();
 
         async move {
             let mut total_rescheduled = 0;
@@ -170,6 +206,7 @@ pub fn QueueList() -> impl IntoView {
                     match HttpRequest::patch("/api/queue/messages")
                         .with_authorization(&auth)
                         .with_optional_parameter("filter", filter)
+                        .with_optional_parameter("queue", queue)
                         .send::<bool>()
                         .await
                     {
@@ -251,12 +288,60 @@ pub fn QueueList() -> impl IntoView {
                             use_navigate()(
                                 &UrlBuilder::new("/manage/queue/messages")
                                     .with_parameter("filter", value)
+                                    .with_optional_parameter("queue", queue.get())
                                     .finish(),
                                 Default::default(),
                             );
                         }
                     />
 
+                    {move || {
+                        let queues = virtual_queues.get().unwrap_or_default();
+                        (!queues.is_empty())
+                            .then(|| {
+                                view! {
+                                    <select
+                                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                        on:change=move |ev| {
+                                            let value = event_target_value(&ev);
+                                            use_navigate()(
+                                                &UrlBuilder::new("/manage/queue/messages")
+                                                    .with_optional_parameter("filter", filter.get())
+                                                    .with_optional_parameter(
+                                                        "queue",
+                                                        (!value.is_empty()).then_some(value),
+                                                    )
+                                                    .finish(),
+                                                Default::default(),
+                                            );
+                                        }
+                                    >
+
+                                        <option value="" selected=move || queue.get().is_none()>
+                                            All queues
+                                        </option>
+                                        {queues
+                                            .into_iter()
+                                            .map(|name| {
+                                                let name_ = name.clone();
+                                                view! {
+                                                    <option
+                                                        value=name.clone()
+                                                        selected=move || {
+                                                            queue.get().is_some_and(|queue| queue == name_)
+                                                        }
+                                                    >
+
+                                                        {name}
+                                                    </option>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </select>
+                                }
+                            })
+                    }}
+
                     <ToolbarButton
                         text="Refresh"
 
@@ -434,6 +519,7 @@ pub fn QueueList() -> impl IntoView {
                                 &UrlBuilder::new("/manage/queue/messages")
                                     .with_parameter("page", page.to_string())
                                     .with_optional_parameter("filter", filter.get())
+                                    .with_optional_parameter("queue", queue.get())
                                     .finish(),
                                 Default::default(),
                             );
@@ -477,8 +563,10 @@ fn QueueItem(message: Message) -> impl IntoView {
 
     let next_retry = message
         .next_retry()
-        .map(|dt| HumanTime::from(dt).to_string());
-    let next_dsn = message.next_dsn().map(|dt| HumanTime::from(dt).to_string());
+        .map(|date| view! { <Timestamp date relative=true/> });
+    let next_dsn = message
+        .next_dsn()
+        .map(|date| view! { <Timestamp date relative=true/> });
     let return_path = message.return_path().to_string();
     let recipients = if total_recipients > 0 {
         format!("{first_recipient} and {total_recipients} more",)
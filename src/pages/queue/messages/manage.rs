@@ -7,8 +7,6 @@
 use std::vec;
 
 use chrono::Utc;
-use chrono_humanize::HumanTime;
-use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::{use_navigate, use_params_map};
 
@@ -29,9 +27,11 @@ use crate::{
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
+        format::format_bytes,
         http::{self, HttpRequest},
         oauth::use_authorization,
     },
@@ -247,14 +247,14 @@ pub fn QueueManage() -> impl IntoView {
                                 </CardItem>
                                 <CardItem
                                     title="Sent"
-                                    contents=HumanTime::from(message.created).to_string()
+                                    contents=message.created.format_relative()
                                     subcontents=message.created.format_date_time()
                                 >
 
                                     <IconClock attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
 
                                 </CardItem>
-                                <CardItem title="Size" contents=format_size(message.size, DECIMAL)>
+                                <CardItem title="Size" contents=format_bytes(message.size as u64)>
 
                                     <IconScale attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
 
@@ -279,7 +279,7 @@ pub fn QueueManage() -> impl IntoView {
                                 <CardItem
                                     title="Next Retry"
                                     contents=next_retry
-                                        .map(|dt| HumanTime::from(dt).to_string())
+                                        .map(|dt| dt.format_relative())
                                         .unwrap_or("N/A".to_string())
                                     subcontents=next_retry
                                         .map(|dt| { dt.format_date_time() })
@@ -292,7 +292,7 @@ pub fn QueueManage() -> impl IntoView {
                                 <CardItem
                                     title="Next notification"
                                     contents=next_dsn
-                                        .map(|dt| HumanTime::from(dt).to_string())
+                                        .map(|dt| dt.format_relative())
                                         .unwrap_or("N/A".to_string())
                                     subcontents=next_dsn
                                         .map(|dt| { dt.format_date_time() })
@@ -305,7 +305,7 @@ pub fn QueueManage() -> impl IntoView {
                                 <CardItem
                                     title="Last attempt"
                                     contents=expires
-                                        .map(|dt| HumanTime::from(dt).to_string())
+                                        .map(|dt| dt.format_relative())
                                         .unwrap_or("N/A".to_string())
                                     subcontents=expires
                                         .map(|dt| { dt.format_date_time() })
@@ -475,7 +475,7 @@ pub fn QueueManage() -> impl IntoView {
                                                         {
                                                             format!(
                                                                 "{} ({})",
-                                                                HumanTime::from(dt),
+                                                                dt.format_relative(),
                                                                 dt.format_date_time(),
                                                             )
                                                         } else {
@@ -589,7 +589,7 @@ pub fn QueueManage() -> impl IntoView {
                                                 view! {
                                                     <tr>
                                                         <ListTextItem>
-                                                            {span.created_at.format_date_time()}
+                                                            <Timestamp date=span.created_at/>
                                                         </ListTextItem>
                                                         <ListTextItem>{title}</ListTextItem>
                                                         <ListTextItem>
@@ -6,10 +6,26 @@
 
 pub mod list;
 pub mod manage;
+pub mod queues;
 
 use chrono::{DateTime, Utc};
+use leptos::{spawn_local, SignalGetUntracked, SignalSet};
+use leptos_router::use_navigate;
 use serde::{Deserialize, Deserializer, Serialize};
 
+use crate::{
+    components::messages::{
+        alert::{use_alerts, Alert},
+        modal::{use_modals, Modal},
+    },
+    core::{
+        command::Command,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        Permission, Permissions,
+    },
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Message {
     pub id: u64,
@@ -169,3 +185,55 @@ where
         ))
     }
 }
+
+pub fn commands(permissions: &Permissions) -> Vec<Command> {
+    let mut commands = Vec::new();
+    if permissions.has_access(Permission::MessageQueueUpdate) {
+        let auth = use_authorization();
+        let alert = use_alerts();
+        let modal = use_modals();
+        commands.push(Command::run(
+            "Flush queue",
+            "Retry delivery of all queued messages now",
+            move |_| {
+                modal.set(
+                    Modal::with_title("Flush queue")
+                        .with_message(
+                            "Delivery of all queued messages will be attempted immediately. Do you want to continue?",
+                        )
+                        .with_button("Flush queue")
+                        .with_callback(move || {
+                            let auth = auth.get_untracked();
+                            spawn_local(async move {
+                                match HttpRequest::patch("/api/queue/messages")
+                                    .with_authorization(&auth)
+                                    .send::<bool>()
+                                    .await
+                                {
+                                    Ok(_) => {
+                                        alert.set(Alert::success(
+                                            "All queued messages were scheduled for immediate delivery.",
+                                        ));
+                                    }
+                                    Err(http::Error::Unauthorized) => {
+                                        use_navigate()("/login", Default::default());
+                                    }
+                                    Err(err) => {
+                                        alert.set(Alert::from(err));
+                                    }
+                                }
+                            });
+                        }),
+                );
+            },
+        ));
+    }
+    if permissions.has_access(Permission::MessageQueueList) {
+        commands.push(Command::navigate(
+            "View queue",
+            "Messages waiting for delivery",
+            "/manage/queue/messages",
+        ));
+    }
+    commands
+}
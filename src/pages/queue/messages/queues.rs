@@ -0,0 +1,269 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconPauseCircle, IconPlayCircle, IconRefresh},
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        time::Timestamp,
+        Color,
+    },
+    core::{
+        format::{format_count, format_decimal},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+        AccessToken,
+    },
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VirtualQueue {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    // Messages delivered per minute
+    #[serde(default)]
+    pub throughput: f64,
+    #[serde(default)]
+    pub oldest: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub strategy: Option<String>,
+}
+
+// Servers without named queues answer 404, which callers treat as a single
+// default queue.
+pub async fn fetch_virtual_queues(auth: &AccessToken) -> http::Result<Vec<VirtualQueue>> {
+    HttpRequest::get("/api/queue/virtual")
+        .with_authorization(auth)
+        .send::<Vec<VirtualQueue>>()
+        .await
+}
+
+#[component]
+pub fn VirtualQueueList() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let queues = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_virtual_queues(&auth).await }
+        },
+    );
+
+    let set_status = create_action(move |(name, status): &(String, bool)| {
+        let auth = auth.get();
+        let name = name.clone();
+        let status = *status;
+
+        async move {
+            match HttpRequest::patch(
+                UrlBuilder::new("/api/queue/virtual")
+                    .with_subpath(&name)
+                    .with_subpath(if status { "start" } else { "stop" })
+                    .finish(),
+            )
+            .with_authorization(&auth)
+            .send::<serde_json::Value>()
+            .await
+            {
+                Ok(_) => {
+                    queues.refetch();
+                    alert.set(Alert::success(if status {
+                        format!("Processing of queue {name:?} has been resumed.")
+                    } else {
+                        format!("Processing of queue {name:?} has been paused.")
+                    }));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Virtual Queues"
+                subtitle="Size, throughput and status of each named delivery queue"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            queues.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match queues.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Virtual queues are not available"
+                                        subtitle="This server delivers all messages from a single queue."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(queues)) if !queues.is_empty() => {
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Queue".to_string(),
+                                        "Status".to_string(),
+                                        "Messages".to_string(),
+                                        "Throughput".to_string(),
+                                        "Oldest".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || queues.clone()
+                                            key=|queue| queue.name.clone()
+                                            let:queue
+                                        >
+                                            <VirtualQueueItem queue set_status/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No queues"
+                                        subtitle="No virtual queues are configured on this server."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn VirtualQueueItem(queue: VirtualQueue, set_status: Action<(String, bool), ()>) -> impl IntoView {
+    let name = queue.name.clone();
+    let paused = queue.paused;
+    let oldest = queue
+        .oldest
+        .map(|date| view! { <Timestamp date relative=true/> }.into_view())
+        .unwrap_or_else(|| "-".into_view());
+    let messages_url = UrlBuilder::new("/manage/queue/messages")
+        .with_parameter("queue", queue.name.clone())
+        .finish();
+
+    view! {
+        <tr>
+            <ListItem subclass="ps-6 pe-6 py-3">
+                <div class="grow">
+                    <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {queue.name.clone()}
+                    </span>
+                    <span class="block text-sm text-gray-500">
+                        {queue.strategy.clone().unwrap_or_default()}
+                    </span>
+                </div>
+            </ListItem>
+            <ListItem>
+                {if paused {
+                    view! { <Badge color=Color::Yellow>Paused</Badge> }
+                } else {
+                    view! { <Badge color=Color::Green>Active</Badge> }
+                }}
+
+            </ListItem>
+            <ListTextItem>{format_count(queue.size)}</ListTextItem>
+            <ListTextItem>{format!("{}/min", format_decimal(queue.throughput, 1))}</ListTextItem>
+            <ListTextItem>{oldest}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="inline-flex items-center gap-x-3">
+                    <a
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        href=messages_url
+                    >
+                        Messages
+                    </a>
+                    <a
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        href="/settings/smtp-out-queue/edit"
+                    >
+                        Strategy
+                    </a>
+                    <button
+                        class="inline-flex items-center gap-x-1 text-sm text-gray-600 decoration-2 hover:underline font-medium dark:text-gray-400"
+                        on:click=move |_| {
+                            set_status.dispatch((name.clone(), paused));
+                        }
+                    >
+
+                        {if paused {
+                            view! {
+                                <IconPlayCircle attr:class="flex-shrink-0 size-4"/>
+                                "Resume"
+                            }
+                                .into_view()
+                        } else {
+                            view! {
+                                <IconPauseCircle attr:class="flex-shrink-0 size-4"/>
+                                "Pause"
+                            }
+                                .into_view()
+                        }}
+
+                    </button>
+                </div>
+            </ListItem>
+        </tr>
+    }
+}
@@ -32,6 +32,7 @@ use crate::{
         report::{ReportItem, ReportSection, ReportTextValue, ReportView},
         Color,
     },
+    core::format::format_count,
     pages::{
         queue::reports::{display::PAGE_SIZE, ActionDisposition, Report},
         FormatDateTime,
@@ -377,7 +378,7 @@ pub fn DmarcReportDisplay(
                                         .unwrap_or_default()/>
                                 </ReportItem>
                                 <ReportItem label="Count" hide=record.row.count == 0>
-                                    <ReportTextValue value=record.row.count.to_string()/>
+                                    <ReportTextValue value=format_count(record.row.count as u64)/>
                                 </ReportItem>
                                 <ReportItem
                                     label="Override Reasons"
@@ -6,7 +6,7 @@
 
 use leptos::*;
 use leptos_router::*;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     components::{
@@ -25,9 +25,11 @@ use crate::{
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
+        time::Timestamp,
         Color,
     },
     core::{
+        events::{use_change_events, ChangeTopic},
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
@@ -35,13 +37,15 @@ use crate::{
     pages::{
         maybe_plural,
         queue::reports::{AggregateReportId, AggregateReportType},
-        FormatDateTime, List,
+        List,
     },
 };
 
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 
 const PAGE_SIZE: u32 = 10;
+// Used when the server does not push change events
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 #[component]
 pub fn ReportList() -> impl IntoView {
@@ -71,9 +75,10 @@ pub fn ReportList() -> impl IntoView {
     let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
     provide_context(selected);
 
+    let events = use_change_events(ChangeTopic::QueueReports, REFRESH_INTERVAL);
     let reports = create_resource(
-        move || (page.get(), filter.get()),
-        move |(page, filter)| {
+        move || (page.get(), filter.get(), events.tick()),
+        move |(page, filter, _)| {
             let auth = auth.get_untracked();
 
             async move {
@@ -364,8 +369,7 @@ fn ReportItem(report: AggregateReportId) -> impl IntoView {
             </td>
 
             <ListTextItem>
-                {format!("{} ({})", HumanTime::from(report.due), report.due.format_date_time())}
-
+                <Timestamp date=report.due relative=true/>
             </ListTextItem>
 
             <ListTextItem>
@@ -25,6 +25,7 @@ use crate::{
         report::{ReportItem, ReportSection, ReportTextValue, ReportView},
         Color,
     },
+    core::format::format_count,
     pages::{
         queue::reports::{display::PAGE_SIZE, Policy},
         FormatDateTime,
@@ -124,12 +125,12 @@ pub fn TlsReportDisplay(
                 <IconClock attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
 
             </CardItem>
-            <CardItem title="Successes" contents=total_success.to_string()>
+            <CardItem title="Successes" contents=format_count(total_success as u64)>
 
                 <IconCheckCircle attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
 
             </CardItem>
-            <CardItem title="Failures" contents=total_fail.to_string()>
+            <CardItem title="Failures" contents=format_count(total_fail as u64)>
 
                 <IconCancel attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
 
@@ -186,8 +187,8 @@ pub fn TlsReportDisplay(
                                                 <tr>
                                                     <ListTextItem>{domain}</ListTextItem>
                                                     <ListTextItem>{policy.policy.policy_type}</ListTextItem>
-                                                    <ListTextItem>{policy.summary.total_success}</ListTextItem>
-                                                    <ListTextItem>{policy.summary.total_failure}</ListTextItem>
+                                                    <ListTextItem>{format_count(policy.summary.total_success as u64)}</ListTextItem>
+                                                    <ListTextItem>{format_count(policy.summary.total_failure as u64)}</ListTextItem>
                                                     <ListItem subclass="px-6 py-1.5">
                                                         <button
                                                             class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
@@ -260,16 +261,14 @@ pub fn TlsReportDisplay(
                                     <ReportTextValue value=policy.policy.mx_host.join(", ")/>
                                 </ReportItem>
                                 <ReportItem label="Total successes">
-                                    <ReportTextValue value=policy
-                                        .summary
-                                        .total_success
-                                        .to_string()/>
+                                    <ReportTextValue value=format_count(
+                                        policy.summary.total_success as u64,
+                                    )/>
                                 </ReportItem>
                                 <ReportItem label="Total failures">
-                                    <ReportTextValue value=policy
-                                        .summary
-                                        .total_failure
-                                        .to_string()/>
+                                    <ReportTextValue value=format_count(
+                                        policy.summary.total_failure as u64,
+                                    )/>
                                 </ReportItem>
                             </ReportSection>
                             {if !policy.failure_details.is_empty() {
@@ -6,7 +6,7 @@
 
 use leptos::*;
 use leptos_router::*;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use super::ReportType;
 use crate::{
@@ -28,6 +28,7 @@ use crate::{
         Color,
     },
     core::{
+        events::{use_change_events, ChangeTopic},
         http::{self, HttpRequest},
         oauth::use_authorization,
         url::UrlBuilder,
@@ -42,6 +43,8 @@ use crate::{
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 
 const PAGE_SIZE: u32 = 10;
+// Used when the server does not push change events
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 #[component]
 pub fn IncomingReportList() -> impl IntoView {
@@ -85,9 +88,10 @@ pub fn IncomingReportList() -> impl IntoView {
     let selected = create_rw_signal::<ItemSelection>(ItemSelection::None);
     provide_context(selected);
 
+    let events = use_change_events(ChangeTopic::Reports, REFRESH_INTERVAL);
     let reports = create_resource(
-        move || (page.get(), filter.get()),
-        move |(page, filter)| {
+        move || (page.get(), filter.get(), events.tick()),
+        move |(page, filter, _)| {
             let auth = auth.get_untracked();
             let report_type = report_type.get();
 
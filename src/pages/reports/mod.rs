@@ -11,6 +11,7 @@ use super::queue::reports::{ActionDisposition, Feedback, FeedbackType, Report, T
 
 pub mod display;
 pub mod list;
+pub mod tls;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReportType {
@@ -0,0 +1,679 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{cmp::Reverse, collections::BTreeMap, sync::Arc};
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{DateTime, Duration, Local, Utc};
+use futures::future::join_all;
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        card::{Card, CardItem},
+        chart::{TimeSeriesChart, TimeSeriesPoint},
+        icon::{IconCancel, IconCheckCircle, IconDocumentChartBar, IconRefresh, IconShieldCheck},
+        list::{
+            header::ColumnList,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        format::{format_count, format_decimal},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+    },
+    pages::{
+        maybe_plural,
+        queue::reports::{ResultType, TlsReport},
+        reports::IncomingReport,
+        FormatDateTime, List,
+    },
+};
+
+// Reports received in the selected time range are aggregated in the browser,
+// fetching a few of them at a time
+const MAX_REPORTS: u32 = 1000;
+const CONCURRENT_FETCHES: usize = 10;
+const DEFAULT_RANGE: i64 = 30;
+const DAY: i64 = 86400;
+
+static RANGES: &[(i64, &str)] = &[
+    (7, "Last 7 days"),
+    (30, "Last 30 days"),
+    (90, "Last 90 days"),
+    (365, "Last year"),
+];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct TlsOverview {
+    total_reports: usize,
+    failed_reports: usize,
+    is_truncated: bool,
+    total_success: u64,
+    total_failure: u64,
+    domains: Vec<DomainSummary>,
+    failures: Vec<FailureSummary>,
+    sessions_per_day: Vec<TimeSeriesPoint>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct DomainSummary {
+    domain: String,
+    total_success: u64,
+    total_failure: u64,
+    failures: Vec<(ResultType, u64)>,
+    reports: Vec<DomainReport>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DomainReport {
+    id: String,
+    from: String,
+    range_from: DateTime<Utc>,
+    range_to: DateTime<Utc>,
+    total_success: u64,
+    total_failure: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct FailureSummary {
+    result_type: ResultType,
+    sessions: u64,
+    domains: usize,
+}
+
+#[component]
+pub fn TlsReportOverview() -> impl IntoView {
+    let query = use_query_map();
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter")
+                .map(|filter| filter.trim().to_string())
+                .filter(|filter| !filter.is_empty())
+        })
+    });
+    let selected = create_memo(move |_| query.with(|q| q.get("domain").cloned()));
+    let range = create_memo(move |_| {
+        query
+            .with(|q| q.get("days").and_then(|days| days.parse::<i64>().ok()))
+            .filter(|&days| days > 0)
+            .unwrap_or(DEFAULT_RANGE)
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let overview = create_local_resource(
+        move || (filter.get(), range.get()),
+        move |(filter, range)| {
+            let auth = auth.get_untracked();
+            let since = Utc::now() - Duration::days(range);
+
+            async move {
+                let ids = HttpRequest::get("/api/reports/tls")
+                    .with_authorization(&auth)
+                    .with_parameter("page", "1")
+                    .with_parameter("limit", MAX_REPORTS.to_string())
+                    .with_parameter("since", since.to_rfc3339())
+                    .with_optional_parameter("filter", filter)
+                    .send::<List<String>>()
+                    .await?;
+                let is_truncated = ids.total > ids.items.len() as u64;
+
+                // A report that cannot be fetched is left out of the overview
+                let mut reports = Vec::with_capacity(ids.items.len());
+                let mut failed_reports = 0;
+                for ids in ids.items.chunks(CONCURRENT_FETCHES) {
+                    let results = join_all(ids.iter().map(|id| {
+                        HttpRequest::get(("/api/reports/tls", id.as_str()))
+                            .with_authorization(&auth)
+                            .try_send::<IncomingReport<TlsReport>>()
+                    }))
+                    .await;
+                    for (id, result) in ids.iter().zip(results) {
+                        match result {
+                            Ok(Some(report)) if report.report.date_range.end_datetime >= since => {
+                                reports.push((id.clone(), report));
+                            }
+                            Ok(_) => (),
+                            Err(http::Error::Unauthorized) => {
+                                return Err(http::Error::Unauthorized)
+                            }
+                            Err(_) => failed_reports += 1,
+                        }
+                    }
+                }
+
+                let mut overview = TlsOverview::new(reports);
+                overview.failed_reports = failed_reports;
+                overview.is_truncated = is_truncated;
+                Ok(Arc::new(overview))
+            }
+        },
+    );
+    let sessions_per_day = Signal::derive(move || {
+        overview
+            .get()
+            .and_then(|overview| overview.ok())
+            .map(|overview| overview.sessions_per_day.clone())
+            .unwrap_or_default()
+    });
+
+    let domain_url = move |domain: Option<&str>| {
+        UrlBuilder::new("/manage/reports/tls/overview")
+            .with_optional_parameter("filter", filter.get())
+            .with_parameter("days", range.get().to_string())
+            .with_optional_parameter("domain", domain)
+            .finish()
+    };
+
+    view! {
+        <Transition fallback=Skeleton>
+            {move || match overview.get() {
+                None => None,
+                Some(Err(http::Error::Unauthorized)) => {
+                    use_navigate()("/login", Default::default());
+                    Some(view! { <div></div> }.into_view())
+                }
+                Some(Err(err)) => {
+                    alert.set(Alert::from(err));
+                    Some(view! { <Skeleton/> }.into_view())
+                }
+                Some(Ok(overview)) => {
+                    let total_sessions = overview.total_success + overview.total_failure;
+                    let reports_note = if overview.failed_reports > 0 {
+                        format!(
+                            "{} could not be loaded",
+                            maybe_plural(overview.failed_reports, "report", "reports"),
+                        )
+                    } else if overview.is_truncated {
+                        format!("From the latest {MAX_REPORTS} received")
+                    } else {
+                        format!("Received in the last {} days", range.get())
+                    };
+                    Some(
+                        view! {
+                            <Card>
+                                <CardItem
+                                    title="Reports"
+                                    contents=format_count(overview.total_reports as u64)
+                                    subcontents=reports_note
+                                >
+
+                                    <IconDocumentChartBar attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                </CardItem>
+                                <CardItem
+                                    title="Policy Domains"
+                                    contents=format_count(overview.domains.len() as u64)
+                                >
+
+                                    <IconShieldCheck attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                </CardItem>
+                                <CardItem
+                                    title="Successful Sessions"
+                                    contents=format_count(overview.total_success)
+                                >
+
+                                    <IconCheckCircle attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                </CardItem>
+                                <CardItem
+                                    title="Failed Sessions"
+                                    contents=format_count(overview.total_failure)
+                                    subcontents="Failure rate "
+                                    subcontents_bold=failure_rate(
+                                        overview.total_failure,
+                                        total_sessions,
+                                    )
+                                >
+
+                                    <IconCancel attr:class="flex-shrink-0 size-5 text-gray-400 dark:text-gray-600"/>
+
+                                </CardItem>
+                            </Card>
+                        }
+                            .into_view(),
+                    )
+                }
+            }}
+
+        </Transition>
+
+        <TimeSeriesChart
+            title="Sessions per day"
+            labels=&["successful", "failed"]
+            data=sessions_per_day
+        />
+
+        <ListSection>
+            <ListTable
+                title="Policy domains"
+                subtitle="TLS sessions reported by remote servers delivering to each policy domain"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/reports/tls/overview")
+                                    .with_parameter("filter", value)
+                                    .with_parameter("days", range.get().to_string())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/reports/tls/overview")
+                                    .with_optional_parameter("filter", filter.get())
+                                    .with_parameter("days", event_target_value(&ev))
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    >
+
+                        {RANGES
+                            .iter()
+                            .map(|(days, label)| {
+                                view! {
+                                    <option value=days.to_string() selected=move || range.get() == *days>
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+
+                    <ToolbarButton
+                        text="Reload"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            overview.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match overview.get() {
+                        Some(Ok(overview)) if !overview.domains.is_empty() => {
+                            let domains = overview.domains.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Domain".to_string(),
+                                        "Successful".to_string(),
+                                        "Failed".to_string(),
+                                        "Failure Rate".to_string(),
+                                        "Failure Types".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        {domains
+                                            .into_iter()
+                                            .map(|summary| {
+                                                let href = domain_url(Some(&summary.domain));
+                                                view! { <DomainItem summary href/> }
+                                            })
+                                            .collect_view()}
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No reports"
+                                        subtitle="No TLS reports were found with the selected criteria."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        _ => None,
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+
+        {move || {
+            let domain = selected.get()?;
+            let summary = overview
+                .get()?
+                .ok()?
+                .domains
+                .iter()
+                .find(|summary| summary.domain == domain)?
+                .clone();
+            Some(view! { <DomainReports summary close_url=domain_url(None)/> })
+        }}
+
+        <ListSection>
+            <ListTable
+                title="Failure types"
+                subtitle="Reasons remote servers gave for failing to establish a TLS session"
+            >
+                <Toolbar slot>
+                    <div></div>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match overview.get() {
+                        Some(Ok(overview)) if !overview.failures.is_empty() => {
+                            let failures = overview.failures.clone();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Type".to_string(),
+                                        "Failed Sessions".to_string(),
+                                        "Domains".to_string(),
+                                    ]>
+
+                                        {failures
+                                            .into_iter()
+                                            .map(|failure| {
+                                                view! {
+                                                    <tr>
+                                                        <ListItem subclass="px-6 py-3">
+                                                            <Badge color=Color::Red>
+                                                                {failure.result_type.to_string()}
+                                                            </Badge>
+                                                        </ListItem>
+                                                        <ListTextItem>{format_count(failure.sessions)}</ListTextItem>
+                                                        <ListTextItem>{failure.domains}</ListTextItem>
+                                                    </tr>
+                                                }
+                                            })
+                                            .collect_view()}
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No failures"
+                                        subtitle="No TLS failures were reported for the selected domains."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        _ => None,
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn DomainItem(summary: DomainSummary, href: String) -> impl IntoView {
+    let total = summary.total_success + summary.total_failure;
+
+    view! {
+        <tr>
+            <ListItem subclass="px-6 py-3">
+                <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {summary.domain}
+                </span>
+                <span class="block text-sm text-gray-500">
+                    {format!(
+                        "{} {}",
+                        summary.reports.len(),
+                        if summary.reports.len() == 1 { "report" } else { "reports" },
+                    )}
+                </span>
+            </ListItem>
+            <ListTextItem>{format_count(summary.total_success)}</ListTextItem>
+            <ListTextItem>{format_count(summary.total_failure)}</ListTextItem>
+            <ListTextItem>{failure_rate(summary.total_failure, total)}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex flex-wrap gap-1">
+                    {summary
+                        .failures
+                        .into_iter()
+                        .map(|(result_type, sessions)| {
+                            view! {
+                                <Badge color=Color::Red>
+                                    {format!("{result_type} ({})", format_count(sessions))}
+                                </Badge>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <a
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    href=href
+                >
+                    Reports
+                </a>
+            </ListItem>
+        </tr>
+    }
+}
+
+#[component]
+fn DomainReports(summary: DomainSummary, close_url: String) -> impl IntoView {
+    view! {
+        <ListSection>
+            <ListTable
+                title=format!("Reports for {}", summary.domain)
+                subtitle="Individual reports covering this policy domain"
+            >
+                <Toolbar slot>
+                    <a
+                        class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                        href=close_url
+                    >
+                        Close
+                    </a>
+                </Toolbar>
+
+                <ColumnList headers=vec![
+                    "From".to_string(),
+                    "Report Range".to_string(),
+                    "Successful".to_string(),
+                    "Failed".to_string(),
+                    "".to_string(),
+                ]>
+
+                    {summary
+                        .reports
+                        .into_iter()
+                        .map(|report| {
+                            view! {
+                                <tr>
+                                    <ListTextItem>{report.from}</ListTextItem>
+                                    <ListItem subclass="px-6 py-3">
+                                        <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
+                                            {report.range_from.format_date_time()}
+                                        </span>
+                                        <span class="block text-sm text-gray-500">
+                                            {format!("To {}", report.range_to.format_date_time())}
+                                        </span>
+                                    </ListItem>
+                                    <ListTextItem>{format_count(report.total_success)}</ListTextItem>
+                                    <ListTextItem>{format_count(report.total_failure)}</ListTextItem>
+                                    <ListItem subclass="px-6 py-1.5">
+                                        <a
+                                            class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                            href=format!("/manage/reports/tls/{}", report.id)
+                                        >
+                                            Show
+                                        </a>
+                                    </ListItem>
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+
+                </ColumnList>
+
+                <Footer slot>
+                    <div></div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+impl TlsOverview {
+    fn new(reports: Vec<(String, IncomingReport<TlsReport>)>) -> Self {
+        let mut overview = TlsOverview {
+            total_reports: reports.len(),
+            ..Default::default()
+        };
+        let mut domains: BTreeMap<String, DomainSummary> = BTreeMap::new();
+        let mut domain_failures: AHashMap<String, AHashMap<ResultType, u64>> = AHashMap::new();
+        let mut failures: AHashMap<ResultType, (u64, AHashSet<String>)> = AHashMap::new();
+        let mut days: BTreeMap<i64, (u64, u64)> = BTreeMap::new();
+
+        for (id, report) in reports {
+            let range_from = report.report.date_range.start_datetime;
+            let range_to = report.report.date_range.end_datetime;
+            let day = days
+                .entry(range_from.timestamp() - range_from.timestamp().rem_euclid(DAY))
+                .or_default();
+
+            for policy in report.report.policies {
+                let domain = policy.policy.policy_domain.to_lowercase();
+                let total_success = policy.summary.total_success as u64;
+                let total_failure = policy.summary.total_failure as u64;
+                overview.total_success += total_success;
+                overview.total_failure += total_failure;
+                day.0 += total_success;
+                day.1 += total_failure;
+
+                for details in policy.failure_details {
+                    let sessions = details.failed_session_count as u64;
+                    *domain_failures
+                        .entry(domain.clone())
+                        .or_default()
+                        .entry(details.result_type)
+                        .or_default() += sessions;
+                    let failure = failures.entry(details.result_type).or_default();
+                    failure.0 += sessions;
+                    failure.1.insert(domain.clone());
+                }
+
+                let summary = domains
+                    .entry(domain.clone())
+                    .or_insert_with(|| DomainSummary {
+                        domain,
+                        ..Default::default()
+                    });
+                summary.total_success += total_success;
+                summary.total_failure += total_failure;
+                // A report can list several policies for the same domain
+                match summary.reports.iter_mut().find(|report| report.id == id) {
+                    Some(report) => {
+                        report.total_success += total_success;
+                        report.total_failure += total_failure;
+                    }
+                    None => summary.reports.push(DomainReport {
+                        id: id.clone(),
+                        from: report.from.clone(),
+                        range_from,
+                        range_to,
+                        total_success,
+                        total_failure,
+                    }),
+                }
+            }
+        }
+
+        overview.domains = domains
+            .into_values()
+            .map(|mut summary| {
+                let mut failures = domain_failures
+                    .remove(&summary.domain)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                failures.sort_by_key(|(_, sessions)| Reverse(*sessions));
+                summary.failures = failures;
+                summary
+                    .reports
+                    .sort_by_key(|report| Reverse(report.range_from));
+                summary
+            })
+            .collect();
+        // Domains with the most failed sessions first
+        overview
+            .domains
+            .sort_by_key(|summary| Reverse(summary.total_failure));
+        overview.failures = failures
+            .into_iter()
+            .map(|(result_type, (sessions, domains))| FailureSummary {
+                result_type,
+                sessions,
+                domains: domains.len(),
+            })
+            .collect();
+        overview
+            .failures
+            .sort_by_key(|failure| Reverse(failure.sessions));
+        overview.sessions_per_day = days
+            .into_iter()
+            .map(|(day, (success, failure))| TimeSeriesPoint {
+                x: DateTime::from_timestamp(day, 0)
+                    .unwrap_or_default()
+                    .with_timezone(&Local),
+                y: vec![success as f64, failure as f64],
+            })
+            .collect();
+
+        overview
+    }
+}
+
+fn failure_rate(failed: u64, total: u64) -> String {
+    if total > 0 {
+        format!(
+            "{}%",
+            format_decimal(failed as f64 * 100.0 / total as f64, 1)
+        )
+    } else {
+        "-".to_string()
+    }
+}